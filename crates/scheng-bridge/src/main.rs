@@ -9,7 +9,7 @@ use glow::HasContext;
 use raw_window_handle::HasRawWindowHandle;
 use scheng_graph::{Graph, NodeKind};
 use scheng_runtime_glow::{
-    execute_plan_to_sink, EngineError, ExecOutput, FrameCtx, NodeProps, OutputSink,
+    execute_plan_to_sink, EngineError, ExecOutput, FrameCtx, NodeProps, OutputSink, TrackedSink,
     RuntimeState, ShaderSource, FULLSCREEN_VERT,
 };
 use std::num::NonZeroU32;
@@ -167,11 +167,11 @@ fn run() -> Result<(), EngineError> {
             Event::RedrawRequested(_) => {
                 let (w, h) = { let s = window.inner_size(); (s.width.max(1) as i32, s.height.max(1) as i32) };
                 frame_num += 1;
-                let frame = FrameCtx { time: start.elapsed().as_secs_f32(), width: w, height: h, frame: frame_num };
+                let frame = FrameCtx::new(w, h, start.elapsed().as_secs_f32(), frame_num);
 
                 let b = bundle.lock().unwrap();
                 if let Some(ref b) = *b {
-                    let mut sink = PresentSink { w, h };
+                    let mut sink = TrackedSink::new(PresentSink { w, h });
                     if let Err(e) = unsafe {
                         execute_plan_to_sink(&gl, &b.graph, &b.plan, &mut rt_state, &b.props, frame, &mut sink)
                     } {
@@ -158,7 +158,7 @@ impl BridgeState {
 
     pub fn set_mix(&mut self, node_id: &str, mix: f32) -> Result<(), String> {
         if !self.nodes.contains_key(node_id) { return Err(format!("node '{}' not found", node_id)); }
-        self.shaders.mix.insert(node_id.to_string(), scheng_runtime::MixerParams { mix });
+        self.shaders.mix.insert(node_id.to_string(), scheng_runtime::MixerParams::new(mix));
         Ok(())
     }
 
@@ -166,7 +166,7 @@ impl BridgeState {
 
     pub fn set_weights(&mut self, node_id: &str, weights: [f32; 4]) -> Result<(), String> {
         if !self.nodes.contains_key(node_id) { return Err(format!("node '{}' not found", node_id)); }
-        self.shaders.matrix.insert(node_id.to_string(), scheng_runtime::MatrixMixParams { weights });
+        self.shaders.matrix.insert(node_id.to_string(), scheng_runtime::MatrixMixParams::new(weights));
         Ok(())
     }
 
@@ -130,7 +130,7 @@ fn dispatch(ws_state: &Arc<Mutex<BridgeState>>, bundle: &SharedBundle, raw: &str
                     if let Ok(mut b) = bundle.lock() {
                         if let Some(ref mut rb) = *b {
                             if let Some(&eid) = rb.id_map.get(&node_id) {
-                                rb.props.mixer_params.insert(eid, scheng_runtime::MixerParams { mix: v });
+                                rb.props.mixer_params.insert(eid, scheng_runtime::MixerParams::new(v));
                             }
                         }
                     }
@@ -146,7 +146,7 @@ fn dispatch(ws_state: &Arc<Mutex<BridgeState>>, bundle: &SharedBundle, raw: &str
                     if let Ok(mut b) = bundle.lock() {
                         if let Some(ref mut rb) = *b {
                             if let Some(&eid) = rb.id_map.get(&node_id) {
-                                rb.props.matrix_params.insert(eid, scheng_runtime::MatrixMixParams { weights });
+                                rb.props.matrix_params.insert(eid, scheng_runtime::MatrixMixParams::new(weights));
                             }
                         }
                     }
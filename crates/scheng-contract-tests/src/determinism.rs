@@ -26,4 +26,39 @@ mod tests {
         assert_eq!(p1.nodes, p2.nodes, "plan nodes order must be stable");
         assert_eq!(p1.edges.len(), p2.edges.len(), "edge count must be stable");
     }
+
+    /// Fan-out contract:
+    /// an output feeding several inputs is scheduled once, so it renders once per frame.
+    #[test]
+    fn fan_out_upstream_is_scheduled_once() {
+        let mut g = Graph::new();
+
+        let src = g.add_node(NodeKind::ShaderSource);
+        let shared = g.add_node(NodeKind::ShaderPass);
+        let left = g.add_node(NodeKind::ShaderPass);
+        let right = g.add_node(NodeKind::ShaderPass);
+        let mix = g.add_node(NodeKind::Crossfade);
+        let out = g.add_node(NodeKind::PixelsOut);
+
+        g.connect_named(src, "out", shared, "in")
+            .expect("src -> shared");
+        g.connect_named(shared, "out", left, "in")
+            .expect("shared -> left");
+        g.connect_named(shared, "out", right, "in")
+            .expect("shared -> right");
+        g.connect_named(left, "out", mix, "a")
+            .expect("left -> mix.a");
+        g.connect_named(right, "out", mix, "b")
+            .expect("right -> mix.b");
+        g.connect_named(mix, "out", out, "in").expect("mix -> out");
+
+        let plan = g.compile().expect("compile");
+        plan.validate()
+            .expect("compiled plans satisfy the schedule invariants");
+
+        let listed = plan.nodes.iter().filter(|n| **n == shared).count();
+        assert_eq!(listed, 1, "fan-out source must be scheduled exactly once");
+        assert_eq!(plan.fan_out(shared), 2);
+        assert_eq!(plan.nodes.len(), 6);
+    }
 }
@@ -0,0 +1,227 @@
+#![forbid(unsafe_code)]
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use proptest::prelude::*;
+    use scheng_graph::{
+        Endpoint, Graph, Multiplicity, NodeClass, NodeId, NodeKind, Port, PortDir, PortId,
+    };
+
+    const KINDS: [NodeKind; 37] = [
+        NodeKind::ShaderSource,
+        NodeKind::NoiseSource,
+        NodeKind::PreviousFrame,
+        NodeKind::TextureInputPass,
+        NodeKind::VideoDecodeSource,
+        NodeKind::Particles,
+        NodeKind::AudioWaveform,
+        NodeKind::AudioSpectrum,
+        NodeKind::ShaderPass,
+        NodeKind::ColorCorrect,
+        NodeKind::Blur,
+        NodeKind::Keyer,
+        NodeKind::Feedback,
+        NodeKind::MotionDetect,
+        NodeKind::OpticalFlow,
+        NodeKind::GeometryPass,
+        NodeKind::Bloom,
+        NodeKind::Pixelate,
+        NodeKind::Posterize,
+        NodeKind::Dither,
+        NodeKind::ShaderMix2,
+        NodeKind::ShaderMix3,
+        NodeKind::ShaderMix4,
+        NodeKind::Crossfade,
+        NodeKind::Add,
+        NodeKind::Multiply,
+        NodeKind::KeyMix,
+        NodeKind::MatrixMix4,
+        NodeKind::FlowDisplace,
+        NodeKind::Window,
+        NodeKind::TextureOut,
+        NodeKind::PixelsOut,
+        NodeKind::Syphon,
+        NodeKind::Spout,
+        NodeKind::Recorder,
+        NodeKind::Ndi,
+        NodeKind::Rtsp,
+    ];
+
+    /// One editor action, with raw ids so it can name nodes and ports that do not exist.
+    #[derive(Debug, Clone)]
+    enum Op {
+        Add(usize),
+        AddWithOutputs(usize, usize),
+        Connect {
+            from: (u32, u32, bool),
+            to: (u32, u32, bool),
+            delayed: bool,
+        },
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        let endpoint = || (0u32..24, 0u32..64, any::<bool>());
+        prop_oneof![
+            1 => (0..KINDS.len()).prop_map(Op::Add),
+            1 => (0..KINDS.len(), 0usize..6).prop_map(|(k, n)| Op::AddWithOutputs(k, n)),
+            3 => (endpoint(), endpoint(), any::<bool>())
+                .prop_map(|(from, to, delayed)| Op::Connect { from, to, delayed }),
+        ]
+    }
+
+    fn endpoint((node, port, out): (u32, u32, bool)) -> Endpoint {
+        Endpoint {
+            node: NodeId(node),
+            port: PortId(port),
+            dir: if out { PortDir::Out } else { PortDir::In },
+        }
+    }
+
+    fn apply(g: &mut Graph, op: &Op) {
+        match op {
+            Op::Add(k) => {
+                g.add_node(KINDS[*k].clone());
+            }
+            Op::AddWithOutputs(k, n) => {
+                let res = g.add_node_with_outputs(KINDS[*k].clone(), *n);
+                if let Err(e) = res {
+                    assert!(e.to_string().contains("add_node_with_outputs"), "got: {e}");
+                }
+            }
+            Op::Connect { from, to, delayed } => {
+                let (from, to) = (endpoint(*from), endpoint(*to));
+                let res = if *delayed {
+                    g.connect_delayed(from, to)
+                } else {
+                    g.connect(from, to)
+                };
+                if let Err(e) = res {
+                    assert!(e.to_string().contains("connect:"), "got: {e}");
+                }
+            }
+        }
+    }
+
+    fn port_dir(g: &Graph, e: Endpoint) -> Option<PortDir> {
+        let node = g.node(e.node)?;
+        node.ports.iter().find(|p| p.id == e.port).map(|p| p.dir)
+    }
+
+    fn outputs(g: &Graph, id: NodeId) -> Vec<Endpoint> {
+        let node = g.node(id).unwrap();
+        node.ports
+            .iter()
+            .filter(|p| p.dir == PortDir::Out)
+            .map(|p| Endpoint {
+                node: id,
+                port: p.id,
+                dir: PortDir::Out,
+            })
+            .collect()
+    }
+
+    /// Every invariant a compiled plan promises about the graph it came from.
+    fn check_plan(g: &Graph) -> Result<(), TestCaseError> {
+        let plan = match g.compile() {
+            Ok(plan) => plan,
+            Err(e) => {
+                let msg = e.to_string();
+                prop_assert!(msg.starts_with("compile:"), "unstructured error: {msg}");
+                return Ok(());
+            }
+        };
+        prop_assert!(plan.validate().is_ok());
+
+        let listed: HashSet<NodeId> = plan.nodes.iter().copied().collect();
+        let all: HashSet<NodeId> = g.nodes().map(|n| n.id).collect();
+        prop_assert_eq!(&listed, &all);
+
+        let position: HashMap<NodeId, usize> = plan
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (*n, i))
+            .collect();
+        let mut driven = HashSet::new();
+        for e in &plan.edges {
+            prop_assert_eq!(port_dir(g, e.from), Some(PortDir::Out));
+            prop_assert_eq!(port_dir(g, e.to), Some(PortDir::In));
+            prop_assert!(driven.insert(e.to), "input driven twice: {:?}", e.to);
+            if !e.delayed {
+                prop_assert!(position[&e.from.node] < position[&e.to.node]);
+            }
+        }
+        for n in g.nodes() {
+            let inputs = n.ports.iter().filter(|p| p.dir == PortDir::In);
+            let fed = |p: &&Port| {
+                driven.contains(&Endpoint {
+                    node: n.id,
+                    port: p.id,
+                    dir: PortDir::In,
+                })
+            };
+            for p in inputs.clone() {
+                if p.multiplicity == Multiplicity::Required {
+                    prop_assert!(fed(&p), "required input {:?} of {:?} not fed", p.name, n.id);
+                }
+            }
+            let mut variadic = inputs
+                .filter(|p| p.multiplicity == Multiplicity::Variadic)
+                .peekable();
+            if variadic.peek().is_some() {
+                prop_assert!(variadic.any(|p| fed(&p)), "{:?} has no input fed", n.id);
+            }
+        }
+        Ok(())
+    }
+
+    proptest! {
+        /// Arbitrary edits never panic; compile either fails with a `compile:` error or yields
+        /// a plan whose edges connect existing ports in schedule order.
+        #[test]
+        fn arbitrary_edits_compile_or_fail_cleanly(ops in prop::collection::vec(op(), 0..48)) {
+            let mut g = Graph::new();
+            for op in &ops {
+                apply(&mut g, op);
+            }
+            check_plan(&g)?;
+        }
+
+        /// Forward plain edges plus delayed edges anywhere, with every non-optional input fed:
+        /// always compiles.
+        #[test]
+        fn acyclic_graphs_with_fed_outputs_compile(
+            kinds in prop::collection::vec(0..KINDS.len(), 1..16),
+            picks in prop::collection::vec((any::<prop::sample::Index>(), any::<bool>(), any::<bool>()), 64),
+        ) {
+            let mut g = Graph::new();
+            let mut outs: Vec<Endpoint> = Vec::new();
+            let mut picks = picks.into_iter().cycle();
+            let first = g.add_node(NodeKind::NoiseSource);
+            outs.extend(outputs(&g, first));
+            for k in kinds {
+                let id = g.add_node(KINDS[k].clone());
+                let node = g.node(id).unwrap().clone();
+                let is_output = node.kind.class() == NodeClass::Output;
+                for port in node.ports.iter().filter(|p| p.dir == PortDir::In) {
+                    let (pick, connect, delayed) = picks.next().unwrap();
+                    if !connect && port.multiplicity == Multiplicity::Optional {
+                        continue;
+                    }
+                    let to = Endpoint { node: id, port: port.id, dir: PortDir::In };
+                    let from = *pick.get(&outs);
+                    if delayed && !is_output {
+                        g.connect_delayed(from, to).unwrap();
+                    } else {
+                        g.connect(from, to).unwrap();
+                    }
+                }
+                outs.extend(outputs(&g, id));
+            }
+            prop_assert!(g.compile().is_ok());
+            check_plan(&g)?;
+        }
+    }
+}
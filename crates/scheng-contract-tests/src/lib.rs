@@ -15,6 +15,9 @@ mod tests {
     const BANKS_BAD_PRESET_JSON: &str = include_str!("../fixtures/banks_bad_preset.json");
     const BANKS_EMPTY_JSON: &str = include_str!("../fixtures/banks_empty.json");
     const BANKS_MISSING_KEY_JSON: &str = include_str!("../fixtures/banks_missing_key.json");
+    const BANKS_WEIGHTS_JSON: &str = include_str!("../fixtures/banks_weights.json");
+    const BANKS_LOOKS_JSON: &str = include_str!("../fixtures/banks_looks.json");
+    const RENDER_LEGACY_JSON: &str = include_str!("../fixtures/render_legacy.json");
 
     fn write_temp_fixture(name: &str, contents: &str) -> PathBuf {
         let mut p = std::env::temp_dir();
@@ -89,6 +92,101 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn golden_banks_weights_scenes_load() {
+        use scheng_runtime::{MatrixPreset, SceneDef};
+
+        let path = write_temp_fixture("banks_weights", BANKS_WEIGHTS_JSON);
+        let banks = BankSet::from_json_path(&path).expect("banks_weights.json should parse");
+        let scenes = &banks.banks[0].scenes;
+
+        // Weights alone are labelled with the nearest preset.
+        assert_eq!(scenes[0], SceneDef::custom("ab", [0.5, 0.5, 0.0, 0.0]));
+        assert_eq!(scenes[0].preset, MatrixPreset::Sum01);
+        // A preset string that is not a name falls back to weight parsing.
+        assert_eq!(scenes[1].params().weights, [0.25; 4]);
+        assert_eq!(scenes[1].preset, MatrixPreset::Quad);
+        // Explicit weights win; the preset is kept as the label.
+        assert_eq!(scenes[2].preset, MatrixPreset::Solo0);
+        assert_eq!(scenes[2].params().weights, [0.7, 0.3, 0.0, 0.0]);
+        assert_eq!(scenes[3], SceneDef::new("named", MatrixPreset::Solo3));
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn banks_scene_without_preset_or_weights_is_rejected() {
+        let path = write_temp_fixture(
+            "banks_no_preset",
+            r#"{ "banks": [{ "name": "B", "scenes": [{ "name": "empty" }] }] }"#,
+        );
+        let err = BankSet::from_json_path(&path).expect_err("scene needs a preset or weights");
+        assert!(err.contains("preset or weights"), "got: {err}");
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn golden_banks_scene_overrides_load() {
+        use scheng_runtime::{MatrixPreset, SceneDef};
+
+        let path = write_temp_fixture("banks_looks", BANKS_LOOKS_JSON);
+        let banks = BankSet::from_json_path(&path).expect("banks_looks.json should parse");
+        let scenes = &banks.banks[0].scenes;
+
+        let expected = SceneDef::new("warm", MatrixPreset::Quad)
+            .with_shader(NodeId(0), "shaders/warm.frag")
+            .with_uniform(NodeId(0), "u_gain", 1.5)
+            .with_uniform(NodeId(2), "u_hue", 0.1)
+            .with_uniform(NodeId(2), "u_sat", 0.8);
+        assert_eq!(scenes[0], expected);
+        assert!(scenes[0].override_for(NodeId(1)).is_none());
+        assert!(scenes[1].overrides.is_empty());
+
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn banks_overrides_address_nodes_by_name() {
+        use scheng_graph::{Graph, NodeKind};
+        use scheng_runtime::NodeRef;
+
+        let path = write_temp_fixture(
+            "banks_named_override",
+            r#"{ "banks": [{ "name": "B", "scenes": [{ "name": "s", "preset": "quad",
+                "overrides": [{ "node": "cam1", "uniforms": { "u_gain": 2.0 } },
+                              { "node": 0, "shader": "a.frag" }] }] }] }"#,
+        );
+        let banks = BankSet::from_json_path(&path).expect("named overrides load");
+        let scene = &banks.banks[0].scenes[0];
+        assert_eq!(scene.overrides[0].node, NodeRef::Name("cam1".into()));
+        assert_eq!(scene.overrides[1].node, NodeRef::Id(0));
+
+        // Names survive a rebuild that renumbers the nodes.
+        let mut g = Graph::new();
+        let first = g.add_node(NodeKind::ShaderPass);
+        let cam = g.add_node_named(NodeKind::ShaderPass, "cam1").unwrap();
+        assert_eq!(scene.overrides[0].node_id(&g), Some(cam));
+        assert_eq!(scene.overrides[1].node_id(&g), Some(first));
+        assert!(scene.override_for("cam1").is_some());
+        assert!(scene.override_for(cam).is_none());
+
+        banks.to_json_path(&path).expect("save banks");
+        assert_eq!(BankSet::from_json_path(&path).unwrap(), banks);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn banks_duplicate_node_override_is_rejected() {
+        let path = write_temp_fixture(
+            "banks_dup_override",
+            r#"{ "banks": [{ "name": "B", "scenes": [{ "name": "s", "preset": "quad",
+                "overrides": [{ "node": 1, "shader": "a.frag" }, { "node": 1 }] }] }] }"#,
+        );
+        let err = BankSet::from_json_path(&path).expect_err("one override per node");
+        assert!(err.contains("duplicate override for node 1"), "got: {err}");
+        let _ = fs::remove_file(path);
+    }
+
     // ---- Step 5.1 output naming contracts (backend-agnostic) ----
 
     #[test]
@@ -147,7 +245,316 @@ mod tests {
         assert_eq!(plan.named.get("program").copied(), Some(NodeId(11)));
         assert_eq!(plan.named.get("preview").copied(), Some(NodeId(12)));
     }
+
+    // ---- Error contract ----
+
+    #[test]
+    fn gl_error_display_names_code_op_and_node() {
+        let e = scheng_core::EngineError::Gl {
+            node: Some(3),
+            op: "ShaderPass draw".to_string(),
+            code: 0x0502,
+        };
+        assert_eq!(
+            e.to_string(),
+            "GL error GL_INVALID_OPERATION (0x0502) after ShaderPass draw in node 3"
+        );
+    }
+
+    // ---- Project bundle contract ----
+
+    #[test]
+    fn bundle_roundtrip_keeps_relative_asset_paths() {
+        use scheng_core::bundle::{self, Bundle, BundleBuilder, BundleFormat};
+        use scheng_core::{load_engine_config_from_bundle, AssetsRoot, ConfigMode};
+
+        let base = write_temp_fixture("bundle", "").with_extension("d");
+        let assets = base.join("assets");
+        fs::create_dir_all(assets.join("shaders")).unwrap();
+        for (rel, body) in [
+            ("render.json", r#"{ "frag": "shaders/a.frag" }"#),
+            ("params.json", "{}"),
+            ("output.json", "{}"),
+            ("recording.json", "{}"),
+            ("shaders/a.frag", "void main() {}"),
+            (".DS_Store", ""),
+        ] {
+            fs::write(assets.join(rel), body).unwrap();
+        }
+
+        let packed = base.join("show.schengproj");
+        let manifest = bundle::create(
+            &AssetsRoot::from_path(&assets),
+            &packed,
+            BundleFormat::Directory,
+        )
+        .expect("create bundle");
+        assert_eq!(manifest.name, "show");
+        let paths: Vec<_> = manifest.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(
+            paths.join(" "),
+            "output.json params.json recording.json render.json shaders/a.frag"
+        );
+
+        let copy = bundle::extract(&packed, &base.join("copy.schengproj")).expect("extract");
+        assert_eq!(copy.manifest(), &manifest);
+        let config = load_engine_config_from_bundle(copy.root(), ConfigMode::Strict)
+            .expect("config loads from the bundle");
+        assert_eq!(
+            config.render.frag_path,
+            copy.root().join("assets").join("shaders/a.frag")
+        );
+
+        fs::write(copy.root().join("assets/shaders/a.frag"), "changed").unwrap();
+        assert!(Bundle::open(copy.root()).unwrap().verify().is_err());
+        assert!(BundleBuilder::new("x")
+            .add_file(assets.join("params.json"), "../params.json")
+            .is_err());
+
+        let _ = fs::remove_dir_all(base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn bundle_skips_symlinked_directories_instead_of_looping() {
+        use scheng_core::bundle::BundleBuilder;
+        use scheng_core::AssetsRoot;
+
+        let assets = write_temp_fixture("bundle-links", "").with_extension("d");
+        fs::create_dir_all(assets.join("shaders")).unwrap();
+        fs::write(assets.join("shaders/a.frag"), "void main() {}").unwrap();
+        std::os::unix::fs::symlink(&assets, assets.join("shaders/loop")).unwrap();
+        std::os::unix::fs::symlink(assets.join("shaders/a.frag"), assets.join("b.frag")).unwrap();
+
+        let builder = BundleBuilder::from_assets("links", &AssetsRoot::from_path(&assets))
+            .expect("a looping link does not recurse");
+        assert_eq!(
+            builder.paths().collect::<Vec<_>>(),
+            ["b.frag", "shaders/a.frag"]
+        );
+
+        let _ = fs::remove_dir_all(assets);
+    }
+
+    // ---- Schema version contract ----
+
+    #[test]
+    fn unversioned_fixtures_upgrade_to_the_current_schema() {
+        use scheng_core::config::{load_render_selection_strict, RENDER_JSON_SCHEMA};
+        use scheng_core::{AssetsRoot, JsonSchema, Migration, SCHEMA_VERSION_KEY};
+        use scheng_runtime::BANKS_SCHEMA;
+
+        // Banks written before versioning are version 0 and load unchanged.
+        let mut value: serde_json::Value = serde_json::from_str(BANKS_BUILTIN_JSON).unwrap();
+        assert_eq!(BANKS_SCHEMA.migrate(&mut value), Ok(0));
+        assert_eq!(value[SCHEMA_VERSION_KEY], 1);
+        let saved = BankSet::builtin_matrix_banks().to_json();
+        let reloaded: serde_json::Value = serde_json::from_str(&saved).unwrap();
+        assert_eq!(reloaded[SCHEMA_VERSION_KEY], BANKS_SCHEMA.current);
+        let future = write_temp_fixture(
+            "banks_future",
+            &saved.replace(r#""schema_version": 1"#, r#""schema_version": 99"#),
+        );
+        let err = BankSet::from_json_path(&future).expect_err("newer banks are rejected");
+        assert!(err.contains("newer"), "{err}");
+        let _ = fs::remove_file(future);
+
+        // The old render.json `version` field becomes `schema_version`, even in strict mode.
+        let mut value: serde_json::Value = serde_json::from_str(RENDER_LEGACY_JSON).unwrap();
+        assert_eq!(RENDER_JSON_SCHEMA.migrate(&mut value), Ok(0));
+        assert!(value.get("version").is_none());
+        let assets = write_temp_fixture("render_legacy", "").with_extension("d");
+        fs::create_dir_all(&assets).unwrap();
+        fs::write(assets.join("render.json"), RENDER_LEGACY_JSON).unwrap();
+        let sel = load_render_selection_strict(&AssetsRoot::from_path(&assets))
+            .expect("legacy render.json loads strictly");
+        assert_eq!(sel.frag_idx, 1);
+        fs::write(assets.join("render.json"), r#"{ "version": 2 }"#).unwrap();
+        assert!(load_render_selection_strict(&AssetsRoot::from_path(&assets)).is_err());
+        let _ = fs::remove_dir_all(assets);
+
+        // Steps run in version order, whatever order they are listed in.
+        fn rename_gain(v: &mut serde_json::Value) -> Result<(), String> {
+            let gain = v.as_object_mut().unwrap().remove("gain").ok_or("no gain")?;
+            v["level"] = gain;
+            Ok(())
+        }
+        fn double_level(v: &mut serde_json::Value) -> Result<(), String> {
+            v["level"] = (v["level"].as_f64().ok_or("no level")? * 2.0).into();
+            Ok(())
+        }
+        const STEPS: &[Migration] = &[
+            Migration {
+                from: 2,
+                apply: double_level,
+            },
+            Migration::unchanged(0),
+            Migration {
+                from: 1,
+                apply: rename_gain,
+            },
+        ];
+        let schema = JsonSchema::new("test", 3, STEPS);
+        let mut v0 = serde_json::json!({ "gain": 0.25 });
+        assert_eq!(schema.migrate(&mut v0), Ok(0));
+        assert_eq!(v0, serde_json::json!({ "level": 0.5, "schema_version": 3 }));
+        let mut v2 = serde_json::json!({ "schema_version": 2, "level": 1.0 });
+        assert_eq!(schema.migrate(&mut v2), Ok(2));
+        assert_eq!(v2["level"], 2.0);
+        let mut bad = serde_json::json!({ "schema_version": 1 });
+        assert!(schema.migrate(&mut bad).unwrap_err().contains("no gain"));
+    }
+
+    // ---- Parameter registry contract ----
+
+    #[test]
+    fn params_node_keys_and_clamping_are_stable() {
+        use scheng_runtime::{node_param_key, ParamMeta, ParamRegistry};
+
+        assert_eq!(node_param_key(NodeId(7), "energy"), "node/7/energy");
+
+        let mut reg = ParamRegistry::new();
+        reg.declare("mix", ParamMeta::new(0.0, 1.0, 0.25));
+        assert_eq!(reg.get("mix"), Some(0.25));
+        assert_eq!(reg.set("mix", 3.0), 1.0);
+        reg.set(&node_param_key(NodeId(7), "cell/1"), 0.5);
+        reg.set(&node_param_key(NodeId(7), "cell/0"), 0.1);
+
+        let cells: Vec<_> = reg.iter_prefix("node/7/cell/").collect();
+        assert_eq!(cells, vec![("node/7/cell/0", 0.1), ("node/7/cell/1", 0.5)]);
+
+        // Named nodes are addressed by name; unnamed ones keep the id form.
+        use scheng_graph::{Graph, NodeKind};
+        use scheng_runtime::node_param_key_in;
+        let mut g = Graph::new();
+        let plain = g.add_node(NodeKind::MotionDetect);
+        let cam = g.add_node_named(NodeKind::MotionDetect, "cam1").unwrap();
+        assert_eq!(node_param_key_in(&g, cam, "energy"), "node/cam1/energy");
+        assert_eq!(
+            node_param_key_in(&g, plain, "energy"),
+            node_param_key(plain, "energy")
+        );
+    }
+
+    #[test]
+    fn matrix_mix_weight_options() {
+        use scheng_runtime::MatrixMixParams;
+
+        let p = MatrixMixParams::new([1.0, 1.0, -0.5, 0.0]);
+        assert_eq!(
+            p.effective_weights(),
+            [1.0, 1.0, -0.5, 0.0],
+            "additive by default"
+        );
+        assert_eq!(
+            p.with_allow_negative(false).effective_weights(),
+            [1.0, 1.0, 0.0, 0.0]
+        );
+        assert_eq!(
+            p.with_allow_negative(false)
+                .with_normalize(true)
+                .effective_weights(),
+            [0.5, 0.5, 0.0, 0.0]
+        );
+        // A zero sum leaves the weights alone instead of dividing by zero.
+        let silent = MatrixMixParams::new([0.0; 4]).with_normalize(true);
+        assert_eq!(silent.effective_weights(), [0.0; 4]);
+    }
+
+    // ---- Standard uniform contract ----
+
+    #[test]
+    fn standard_uniform_names_and_types_are_stable() {
+        use scheng_runtime::runtime_contract::{
+            standard_uniform, uniform_name_is_known, StandardUniform as S, UniformType as T,
+            STANDARD_UNIFORMS,
+        };
+
+        let got: Vec<_> = STANDARD_UNIFORMS
+            .iter()
+            .map(|d| (d.name, d.value, d.ty))
+            .collect();
+        assert_eq!(
+            got,
+            vec![
+                ("uTime", S::Time, T::Float),
+                ("u_time", S::Time, T::Float),
+                ("iTime", S::Time, T::Float),
+                ("uResolution", S::Resolution, T::Vec2),
+                ("u_resolution", S::Resolution, T::Vec2),
+                ("iResolution", S::Resolution, T::Vec3),
+                ("uDeltaTime", S::DeltaTime, T::Float),
+                ("iTimeDelta", S::DeltaTime, T::Float),
+                ("uAspect", S::Aspect, T::Float),
+                ("uSeed", S::Seed, T::Float),
+                ("uNodeSeed", S::NodeSeed, T::Float),
+                ("uReloadCount", S::ReloadCount, T::Float),
+            ]
+        );
+
+        assert_eq!(standard_uniform("iTime").map(|d| d.value), Some(S::Time));
+        assert!(standard_uniform("u_gain").is_none());
+        assert!(uniform_name_is_known("uMouse") && uniform_name_is_known("u_resolution"));
+        assert!(!uniform_name_is_known("u_gain"));
+    }
+
+    #[test]
+    fn node_seeds_are_pinned_to_handle_and_run_seed() {
+        use scheng_runtime::runtime_contract::node_seed;
+
+        // Pinned: changing the hash reseeds every saved patch.
+        assert_eq!(node_seed("noise", 0), 2_718_010_323);
+        assert_eq!(node_seed("3", 42), 445_550_205);
+        assert_ne!(node_seed("noise", 0), node_seed("noise", 1));
+        assert_ne!(node_seed("noise", 0), node_seed("noise2", 0));
+    }
+
+    #[test]
+    fn bank_edits_survive_save_and_load() {
+        use scheng_runtime::{MatrixPreset, SceneDef};
+
+        let mut banks = BankSet::builtin_matrix_banks();
+        let live = banks.add_bank("Live");
+        banks
+            .add_scene(
+                live,
+                SceneDef {
+                    name: "wash".to_string(),
+                    preset: MatrixPreset::Quad,
+                    weights: None,
+                    overrides: Vec::new(),
+                },
+            )
+            .unwrap();
+        banks.add_scene(live, banks.banks[0].scenes[1].clone()).unwrap();
+        banks.rename_scene(live, 1, "b_only").unwrap();
+        banks.move_scene(live, 1, 0).unwrap();
+        banks.move_bank(live, 0).unwrap();
+        banks.remove_scene(1, 6).unwrap();
+        assert!(banks.move_scene(0, 0, 9).is_err());
+
+        let path = write_temp_fixture("banks_saved", "");
+        banks.to_json_path(&path).expect("save banks");
+        let loaded = BankSet::from_json_path(&path).expect("saved banks reload");
+        assert_eq!(loaded, banks);
+        assert_eq!(loaded.banks[0].name, "Live");
+        assert_eq!(loaded.banks[0].scenes[0].name, "b_only");
+        assert_eq!(loaded.banks[0].scenes[0].preset, MatrixPreset::Solo1);
+
+        let mut single = BankSet {
+            banks: vec![loaded.banks[0].clone()],
+        };
+        assert!(single.remove_bank(0).is_err());
+
+        let _ = fs::remove_file(path);
+    }
 }
 
 #[cfg(test)]
 mod determinism;
+
+#[cfg(test)]
+mod graph_props;
+
+#[cfg(test)]
+mod serde_roundtrip;
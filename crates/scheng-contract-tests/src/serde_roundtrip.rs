@@ -0,0 +1,136 @@
+#![forbid(unsafe_code)]
+
+#[cfg(test)]
+mod tests {
+    use scheng_graph::NodeId;
+    use scheng_runtime::{
+        BankDef, BankSet, BlendParams, DisplaceParams, FlowParams, InputAdjust, KeyMixParams,
+        KeyMode, MatrixMixParams, MatrixPreset, MixerParams, MotionParams, ParticleParams,
+        SceneDef,
+    };
+
+    fn roundtrip<T>(value: &T) -> T
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+    {
+        let json = serde_json::to_string(value).expect("serialize");
+        serde_json::from_str(&json).expect("deserialize")
+    }
+
+    /// Serde contract:
+    /// parameter blocks and bank data survive a JSON round-trip unchanged.
+    #[test]
+    fn parameter_blocks_roundtrip() {
+        let mixer = MixerParams::new(0.25).with_input(1, InputAdjust::gain(0.5).with_offset(0.1));
+        assert_eq!(roundtrip(&mixer), mixer);
+
+        let blend = BlendParams {
+            gain_a: 0.5,
+            gain_b: 2.0,
+        };
+        assert_eq!(roundtrip(&blend), blend);
+        let b: BlendParams = serde_json::from_str("{}").unwrap();
+        assert_eq!(b, BlendParams::default());
+
+        let key = KeyMixParams::luma(0.2)
+            .with_softness(0.05)
+            .with_invert_matte(true);
+        assert_eq!(roundtrip(&key), key);
+        let k: KeyMixParams = serde_json::from_str(r#"{"mode":"chroma","spill":1.0}"#).unwrap();
+        assert_eq!(k, KeyMixParams::default().with_spill(1.0));
+        assert_eq!(k.mode, KeyMode::Chroma);
+
+        let matrix = MatrixMixParams::new([0.1, 0.2, 0.3, 0.4])
+            .with_normalize(true)
+            .with_allow_negative(false);
+        assert_eq!(roundtrip(&matrix), matrix);
+
+        let motion = MotionParams {
+            grid_cols: 4,
+            grid_rows: 3,
+            ..MotionParams::default()
+        };
+        assert_eq!(roundtrip(&motion), motion);
+        assert_eq!(roundtrip(&FlowParams::default()), FlowParams::default());
+        let particles = ParticleParams {
+            count: 1000,
+            gravity: [0.1, -1.0],
+            fade: false,
+            ..ParticleParams::default()
+        };
+        assert_eq!(roundtrip(&particles), particles);
+        assert_eq!(particles.state_side(), 32);
+        assert_eq!(
+            roundtrip(&DisplaceParams { amount: -1.0 }),
+            DisplaceParams { amount: -1.0 }
+        );
+
+        for preset in MatrixPreset::ALL {
+            assert_eq!(roundtrip(&preset), preset);
+        }
+    }
+
+    #[test]
+    fn banks_roundtrip() {
+        let builtin = BankSet::builtin_matrix_banks();
+        assert_eq!(roundtrip(&builtin), builtin);
+
+        let bank = BankDef {
+            name: "Live".to_string(),
+            scenes: vec![
+                SceneDef {
+                    name: "all".to_string(),
+                    preset: MatrixPreset::Quad,
+                    weights: None,
+                    overrides: Vec::new(),
+                },
+                SceneDef::custom("tilt", [0.4, 0.3, 0.2, 0.1])
+                    .with_shader(NodeId(3), "looks/tilt.frag")
+                    .with_uniform(NodeId(3), "u_amount", 0.25),
+            ],
+        };
+        assert_eq!(roundtrip(&bank), bank);
+
+        // Scenes without explicit weights keep the compact `{name, preset}` shape.
+        assert_eq!(
+            serde_json::to_string(&bank.scenes[0]).unwrap(),
+            r#"{"name":"all","preset":"quad"}"#
+        );
+    }
+
+    /// Presets are written as their stable names and read with the same aliases as bank JSON;
+    /// missing fields in parameter blocks fall back to defaults.
+    #[test]
+    fn json_shape_is_stable() {
+        assert_eq!(
+            serde_json::to_string(&MatrixPreset::Sum01).unwrap(),
+            "\"sum01\""
+        );
+        let p: MatrixPreset = serde_json::from_str("\"solo_2\"").unwrap();
+        assert_eq!(p, MatrixPreset::Solo2);
+        let err = serde_json::from_str::<MatrixPreset>("\"nope\"").unwrap_err();
+        assert!(err.to_string().contains("unknown preset"), "{err}");
+
+        let m: MixerParams = serde_json::from_str("{}").unwrap();
+        assert_eq!(m, MixerParams::default());
+        assert_eq!(
+            serde_json::to_string(&MatrixMixParams::default()).unwrap(),
+            r#"{"weights":[1.0,0.0,0.0,0.0]}"#
+        );
+        let avg = MatrixMixParams::new([1.0, 1.0, 0.0, 0.0]).with_normalize(true);
+        assert_eq!(
+            serde_json::to_string(&avg).unwrap(),
+            r#"{"weights":[1.0,1.0,0.0,0.0],"normalize":true}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&MixerParams::default()).unwrap(),
+            r#"{"mix":0.5}"#
+        );
+        let m: MixerParams =
+            serde_json::from_str(r#"{"mix":0.5,"inputs":[{},{"gain":2.0}]}"#).unwrap();
+        assert_eq!(
+            m,
+            MixerParams::default().with_input(1, InputAdjust::gain(2.0))
+        );
+    }
+}
@@ -0,0 +1,1953 @@
+//! Execution-semantics contracts for `scheng-runtime-glow`, run on a headless GPU.
+//!
+//! These pin down what a backend must do with a graph, not how: which port feeds which
+//! `iChannelN`, what delayed edges and `iSelf` see, how source nodes and extra outputs resolve,
+//! and what the output helpers (`Multiview`, the latency probe, `TrimSink`, recorders, the
+//! triple buffer) produce.
+//! A backend refactor (or a new backend) should pass them unchanged.
+//!
+//! Each test renders a 4x4 frame in a surfaceless EGL context and reads back one pixel. Without
+//! an EGL device (e.g. a CI runner with no GPU or Mesa) the tests skip with a note, unless
+//! `SCHENG_REQUIRE_GPU=1` is set, in which case they fail.
+#![cfg(target_os = "linux")]
+
+use glow::HasContext;
+use glutin::api::egl::context::PossiblyCurrentContext;
+use glutin::api::egl::device::Device;
+use glutin::api::egl::display::Display;
+use glutin::config::{ConfigSurfaceTypes, ConfigTemplateBuilder};
+use glutin::context::{ContextApi, ContextAttributesBuilder, Version};
+use glutin::prelude::*;
+use scheng_core::events::EngineEvent;
+use scheng_core::EngineError;
+use scheng_graph::{BranchFormat, Graph, InputDefault, NodeId, NodeKind};
+use scheng_runtime::runtime_contract::FRAME_BLOCK_GLSL;
+use scheng_runtime::{
+    declare_group_params, group_param_key, AudioVisParams, BloomParams, ColorTrim, DisplaceParams,
+    DitherMode, DitherParams, ParamRegistry, PixelateParams, PosterizeParams, TemporalParams,
+};
+use scheng_runtime_glow::{
+    create_render_target, execute_plan, execute_plan_outputs, execute_plan_to_sink,
+    export_sequence, name_program_and_clean, node_seed_f32, route_program_and_clean,
+    shader_slot_key, taps, AlphaPolicy, BufferedFrame, ClearMode, ExecOutput, ExportConfig,
+    FrameCtx, GuardedSink, HistoryTapSink, LatencyClock, LatencyPattern, LatencyProbe,
+    LatencyStamp, Multiview, MultiviewConfig, MultiviewLayout, MultiviewTile, NodeProps,
+    OutputSink, PatchbaySink, RecordControl, RecorderConfig, RecorderSink, RuntimeState,
+    ShaderBank, ShaderSource, StillFormat, TargetGroup, TextureFormat, TrackedSink, TrimSink,
+    TripleBufferSink, TripleBufferStats, FULLSCREEN_VERT,
+};
+
+const SIZE: i32 = 4;
+
+struct Gpu {
+    gl: glow::Context,
+    _context: PossiblyCurrentContext,
+    _display: Display,
+}
+
+fn gpu() -> Option<Gpu> {
+    let gpu = (|| {
+        let device = Device::query_devices().ok()?.next()?;
+        let display = unsafe { Display::with_device(&device, None) }.ok()?;
+        let template = ConfigTemplateBuilder::new()
+            .with_surface_type(ConfigSurfaceTypes::empty())
+            .build();
+        let config = unsafe { display.find_configs(template) }.ok()?.next()?;
+        let attrs = ContextAttributesBuilder::new()
+            .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+            .build(None);
+        let context = unsafe { display.create_context(&config, &attrs) }
+            .ok()?
+            .make_current_surfaceless()
+            .ok()?;
+        let gl = unsafe {
+            glow::Context::from_loader_function_cstr(|s| display.get_proc_address(s).cast())
+        };
+        Some(Gpu {
+            gl,
+            _context: context,
+            _display: display,
+        })
+    })();
+    if gpu.is_none() {
+        assert!(
+            std::env::var_os("SCHENG_REQUIRE_GPU").is_none(),
+            "SCHENG_REQUIRE_GPU is set but no headless EGL context could be created"
+        );
+        eprintln!("skipping GPU contract test: no headless EGL context");
+    }
+    gpu
+}
+
+fn frag(body: &str) -> ShaderSource {
+    ShaderSource {
+        vert: FULLSCREEN_VERT.to_string(),
+        frag: format!(
+            "#version 330 core
+in vec2 v_uv;
+out vec4 o;
+uniform float uTime;
+uniform sampler2D iChannel0;
+uniform sampler2D iChannel1;
+uniform sampler2D iChannel2;
+uniform sampler2D iChannel3;
+uniform sampler2D iSelf;
+void main() {{ {body} }}"
+        ),
+        origin: None,
+    }
+}
+
+fn solid(r: f32, g: f32, b: f32) -> ShaderSource {
+    frag(&format!("o = vec4({r:.3}, {g:.3}, {b:.3}, 1.0);"))
+}
+
+fn frame(n: u64) -> FrameCtx {
+    FrameCtx::new(SIZE, SIZE, n as f32 * 0.25, n)
+}
+
+/// RGBA8 of the centre pixel.
+fn pixel(gl: &glow::Context, out: &ExecOutput) -> [u8; 4] {
+    pixel_at(gl, out, out.width / 2, out.height / 2)
+}
+
+/// RGBA8 at `(x, y)`, GL convention (origin bottom-left).
+fn pixel_at(gl: &glow::Context, out: &ExecOutput, x: i32, y: i32) -> [u8; 4] {
+    let mut px = [0u8; 4];
+    unsafe {
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(out.fbo));
+        gl.read_pixels(
+            x,
+            y,
+            1,
+            1,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            glow::PixelPackData::Slice(&mut px),
+        );
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+    }
+    px
+}
+
+/// RGBA8 of texel (0, 0) of `tex`.
+fn texel(gl: &glow::Context, tex: glow::NativeTexture) -> [u8; 4] {
+    let mut px = [0u8; 4];
+    unsafe {
+        let fbo = gl.create_framebuffer().unwrap();
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(fbo));
+        gl.framebuffer_texture_2d(
+            glow::READ_FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::TEXTURE_2D,
+            Some(tex),
+            0,
+        );
+        gl.read_pixels(
+            0,
+            0,
+            1,
+            1,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            glow::PixelPackData::Slice(&mut px),
+        );
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        gl.delete_framebuffer(fbo);
+    }
+    px
+}
+
+/// A `SIZE` x `SIZE` host texture filled with `rgba`.
+fn host_texture(gl: &glow::Context, rgba: [u8; 4]) -> glow::NativeTexture {
+    let texels: Vec<u8> = rgba.repeat((SIZE * SIZE) as usize);
+    unsafe {
+        let tex = gl.create_texture().unwrap();
+        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MIN_FILTER,
+            glow::NEAREST as i32,
+        );
+        gl.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MAG_FILTER,
+            glow::NEAREST as i32,
+        );
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGBA8 as i32,
+            SIZE,
+            SIZE,
+            0,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            Some(&texels),
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        tex
+    }
+}
+
+/// Run `frames` frames and return the primary output's centre pixel after each.
+fn run(gpu: &Gpu, g: &Graph, props: &NodeProps, frames: u64) -> Vec<[u8; 4]> {
+    let plan = g.compile().expect("compile");
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.expect("runtime state");
+    let px = (0..frames)
+        .map(|n| {
+            let out = unsafe { execute_plan(&gpu.gl, g, &plan, &mut state, props, frame(n)) }
+                .expect("execute_plan");
+            pixel(&gpu.gl, &out)
+        })
+        .collect();
+    unsafe { state.destroy(&gpu.gl) };
+    px
+}
+
+/// Red channels per frame, within one step of 8-bit rounding.
+fn assert_reds(pixels: &[[u8; 4]], expected: &[u8]) {
+    let reds: Vec<u8> = pixels.iter().map(|p| p[0]).collect();
+    let close =
+        reds.len() == expected.len() && reds.iter().zip(expected).all(|(a, b)| a.abs_diff(*b) <= 1);
+    assert!(close, "red channels {reds:?}, expected {expected:?}");
+}
+
+fn pass(g: &mut Graph, props: &mut NodeProps, shader: ShaderSource) -> NodeId {
+    let n = g.add_node(NodeKind::ShaderPass);
+    props.shader_sources.insert(n, shader);
+    n
+}
+
+#[test]
+fn mixer_ports_bind_channels_in_port_order() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let red = pass(&mut g, &mut props, solid(1.0, 0.0, 0.0));
+    let green = pass(&mut g, &mut props, solid(0.0, 1.0, 0.0));
+    let mix = g.add_node(NodeKind::ShaderMix2);
+    props.shader_sources.insert(
+        mix,
+        frag("o = vec4(texture(iChannel0, v_uv).r, texture(iChannel1, v_uv).g, 0.0, 1.0);"),
+    );
+    let out = g.add_node(NodeKind::PixelsOut);
+    // Wire `b` first: channels follow port names, not connection order.
+    g.connect_named(green, "out", mix, "b").unwrap();
+    g.connect_named(red, "out", mix, "a").unwrap();
+    g.connect_named(mix, "out", out, "in").unwrap();
+    assert_eq!(run(&gpu, &g, &props, 1), vec![[255, 255, 0, 255]]);
+
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let mut ins = Vec::new();
+    for v in [0.0, 0.25, 0.5, 1.0] {
+        ins.push(pass(&mut g, &mut props, solid(v, 0.0, 0.0)));
+    }
+    let matrix = g.add_node(NodeKind::MatrixMix4);
+    props.shader_sources.insert(
+        matrix,
+        frag(
+            "o = vec4(texture(iChannel1, v_uv).r, texture(iChannel2, v_uv).r,
+                      texture(iChannel3, v_uv).r, 1.0 - texture(iChannel0, v_uv).r);",
+        ),
+    );
+    let out = g.add_node(NodeKind::PixelsOut);
+    for (i, n) in ins.iter().enumerate().rev() {
+        g.connect_named(*n, "out", matrix, &format!("in{i}"))
+            .unwrap();
+    }
+    g.connect_named(matrix, "out", out, "in").unwrap();
+    assert_eq!(run(&gpu, &g, &props, 1), vec![[64, 128, 255, 255]]);
+}
+
+#[test]
+fn unconnected_inputs_sample_their_default() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let half = pass(&mut g, &mut props, solid(0.5, 0.0, 0.0));
+    let matrix = g.add_node(NodeKind::MatrixMix4);
+    props.shader_sources.insert(
+        matrix,
+        frag(
+            "o = vec4(texture(iChannel1, v_uv).r, texture(iChannel2, v_uv).g,
+                      texture(iChannel3, v_uv).a, texture(iChannel0, v_uv).a);",
+        ),
+    );
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(half, "out", matrix, "in1").unwrap();
+    g.connect_named(matrix, "out", out, "in").unwrap();
+    g.set_input_default(matrix, "in2", InputDefault::Color([0.0, 0.25, 0.0, 1.0]))
+        .unwrap();
+    g.set_input_default(matrix, "in3", InputDefault::Transparent)
+        .unwrap();
+    // in0 has no default set: opaque black.
+    assert_eq!(run(&gpu, &g, &props, 1), vec![[128, 64, 0, 255]]);
+}
+
+#[test]
+fn self_history_and_delayed_edges_see_the_previous_frame() {
+    let Some(gpu) = gpu() else { return };
+
+    // `iSelf` starts black and accumulates.
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let acc = pass(
+        &mut g,
+        &mut props,
+        frag("o = vec4(texture(iSelf, v_uv).rgb + vec3(0.25), 1.0);"),
+    );
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(acc, "out", out, "in").unwrap();
+    assert_reds(&run(&gpu, &g, &props, 3), &[64, 128, 192]);
+
+    // A delayed edge reads the producer's previous frame (black on the first frame).
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let clock = pass(&mut g, &mut props, frag("o = vec4(uTime, 0.0, 0.0, 1.0);"));
+    let late = pass(&mut g, &mut props, frag("o = texture(iChannel0, v_uv);"));
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_delayed_named(clock, "out", late, "in").unwrap();
+    g.connect_named(late, "out", out, "in").unwrap();
+    assert_reds(&run(&gpu, &g, &props, 3), &[0, 0, 64]);
+}
+
+#[test]
+fn texture_input_passes_host_texture_through() {
+    let Some(gpu) = gpu() else { return };
+    let gl = &gpu.gl;
+    let tex = host_texture(gl, [10, 20, 30, 255]);
+
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let input = g.add_node(NodeKind::TextureInputPass);
+    let copy = pass(&mut g, &mut props, frag("o = texture(iChannel0, v_uv);"));
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(input, "out", copy, "in").unwrap();
+    g.connect_named(copy, "out", out, "in").unwrap();
+    props.texture_inputs.insert(input, tex);
+    assert_eq!(run(&gpu, &g, &props, 1), vec![[10, 20, 30, 255]]);
+    unsafe { gl.delete_texture(tex) };
+}
+
+#[test]
+fn extra_outputs_resolve_downstream_and_as_named_outputs() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let mrt = g.add_node_with_outputs(NodeKind::ShaderPass, 2).unwrap();
+    props.shader_sources.insert(
+        mrt,
+        ShaderSource {
+            vert: FULLSCREEN_VERT.to_string(),
+            frag: "#version 330 core
+layout(location = 0) out vec4 o0;
+layout(location = 1) out vec4 o1;
+void main() { o0 = vec4(1.0, 0.0, 0.0, 1.0); o1 = vec4(0.0, 0.0, 1.0, 1.0); }"
+                .to_string(),
+            origin: None,
+        },
+    );
+    let copy = pass(&mut g, &mut props, frag("o = texture(iChannel0, v_uv);"));
+    let main = g.add_node(NodeKind::PixelsOut);
+    let aux = g.add_node(NodeKind::PixelsOut);
+    let copied = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(mrt, "out", main, "in").unwrap();
+    g.connect_named(mrt, "out1", aux, "in").unwrap();
+    g.connect_named(mrt, "out1", copy, "in").unwrap();
+    g.connect_named(copy, "out", copied, "in").unwrap();
+    props.output_names.insert(aux, "aux".into());
+    props.output_names.insert(copied, "copied".into());
+
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    let outs =
+        unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, &mut state, &props, frame(0)) }.unwrap();
+    assert_eq!(pixel(&gpu.gl, outs.primary()), [255, 0, 0, 255]);
+    assert_eq!(pixel(&gpu.gl, outs.get("aux").unwrap()), [0, 0, 255, 255]);
+    assert_eq!(
+        pixel(&gpu.gl, outs.get("copied").unwrap()),
+        [0, 0, 255, 255]
+    );
+
+    // `main` first, then the named outputs in plan order, each tagged with its producer.
+    let plan_order: Vec<&str> = plan
+        .nodes
+        .iter()
+        .filter_map(|n| props.output_names.get(n).map(String::as_str))
+        .collect();
+    let names: Vec<&str> = outs.names().collect();
+    assert_eq!(names[0], "main");
+    assert_eq!(names[1..], plan_order);
+    let producers: Vec<(&str, NodeId)> = outs
+        .named
+        .iter()
+        .map(|e| (e.name.as_str(), e.node))
+        .collect();
+    for (name, node) in [("main", mrt), ("aux", mrt), ("copied", copy)] {
+        assert!(producers.contains(&(name, node)), "{name}: {producers:?}");
+    }
+    assert!(outs
+        .named
+        .iter()
+        .all(|e| (e.declared_width, e.declared_height) == (SIZE, SIZE)));
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn multiview_places_named_outputs_in_reading_order() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let red = pass(&mut g, &mut props, solid(1.0, 0.0, 0.0));
+    let green = pass(&mut g, &mut props, solid(0.0, 1.0, 0.0));
+    for (node, name) in [(red, "red"), (green, "green")] {
+        let out = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(node, "out", out, "in").unwrap();
+        props.output_names.insert(out, name.into());
+    }
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    let outs =
+        unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, &mut state, &props, frame(0)) }.unwrap();
+
+    let mut mv = Multiview::new(MultiviewConfig {
+        layout: MultiviewLayout::Grid2x2,
+        width: 40,
+        height: 40,
+        gap: 0,
+        background: [0.0, 0.0, 1.0, 1.0],
+        label_scale: 1,
+        tiles: vec![
+            MultiviewTile::new("red").with_label("I"),
+            MultiviewTile::new("green"),
+            MultiviewTile::new("missing"),
+        ],
+    });
+    let grid = unsafe { mv.render(&gpu.gl, &outs) }.unwrap();
+    let at = |x, y| pixel_at(&gpu.gl, &grid, x, y);
+    assert_eq!(at(10, 30), [255, 0, 0, 255], "tile 0 top-left");
+    assert_eq!(at(30, 30), [0, 255, 0, 255], "tile 1 top-right");
+    assert_eq!(
+        at(10, 10),
+        [0, 0, 255, 255],
+        "missing output shows the background"
+    );
+    // Label "I" in tile 0's bottom-left corner: stem at (3, 4) of the label box.
+    assert_eq!(at(3, 24), [255, 255, 255, 255]);
+    assert_eq!(at(2, 24), [0, 0, 0, 255]);
+    unsafe {
+        mv.destroy(&gpu.gl);
+        state.destroy(&gpu.gl);
+    }
+}
+
+#[test]
+fn latency_probe_counts_frames_through_a_delayed_edge() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let stamp = g.add_node(NodeKind::ShaderPass);
+    let copy = pass(&mut g, &mut props, frag("o = texture(iChannel0, v_uv);"));
+    let direct = g.add_node(NodeKind::PixelsOut);
+    let delayed = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(stamp, "out", direct, "in").unwrap();
+    g.connect_delayed_named(stamp, "out", copy, "in").unwrap();
+    g.connect_named(copy, "out", delayed, "in").unwrap();
+    props.output_names.insert(delayed, "delayed".into());
+
+    let clock = LatencyClock::new();
+    let pattern = LatencyPattern::default();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    state.set_node_executor(&gpu.gl, stamp, LatencyStamp::new(clock.clone(), pattern));
+    let mut now = LatencyProbe::new(clock.clone(), pattern);
+    let mut later = LatencyProbe::new(clock, pattern);
+    let plan = g.compile().unwrap();
+    for n in 0..3 {
+        let frame = FrameCtx::new(64, 32, n as f32 / 60.0, n);
+        let outs =
+            unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, &mut state, &props, frame) }.unwrap();
+        now.consume(&gpu.gl, outs.primary());
+        later.consume(&gpu.gl, outs.get("delayed").unwrap());
+    }
+    assert_eq!(now.last().map(|r| (r.code, r.frames)), Some((2, 0)));
+    assert_eq!(later.last().map(|r| (r.code, r.frames)), Some((1, 1)));
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+/// Records the centre pixel of every frame it consumes.
+struct Capture(Vec<[u8; 4]>);
+
+impl OutputSink for Capture {
+    fn consume(&mut self, gl: &glow::Context, out: &ExecOutput) {
+        self.0.push(pixel(gl, out));
+    }
+}
+
+#[test]
+fn trim_sink_applies_registry_trim_before_the_sink() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let grey = pass(&mut g, &mut props, solid(0.5, 0.5, 0.5));
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(grey, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    let frame_out =
+        unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(0)) }.unwrap();
+
+    let mut reg = ParamRegistry::new();
+    ColorTrim::declare(&mut reg, "left");
+    let mut sink =
+        TrimSink::new(Capture(Vec::new()), ColorTrim::default()).with_registry_name("left");
+    sink.sync(&reg);
+    sink.consume(&gpu.gl, &frame_out);
+    reg.set("sink/left/trim/brightness_r", 0.25);
+    reg.set("sink/left/trim/contrast_g", 0.0);
+    reg.set("sink/left/trim/gamma_b", 2.0);
+    sink.sync(&reg);
+    sink.consume(&gpu.gl, &frame_out);
+
+    let [untrimmed, trimmed] = sink.inner().0[..] else {
+        panic!("expected two frames")
+    };
+    assert_eq!(untrimmed, [128, 128, 128, 255]);
+    // r: 0.502 + 0.25; g: flattened to mid-grey; b: sqrt(0.502).
+    let expected = [192, 128, 181, 255];
+    assert!(
+        trimmed
+            .iter()
+            .zip(expected)
+            .all(|(a, b)| a.abs_diff(b) <= 1),
+        "trimmed {trimmed:?}, expected {expected:?}"
+    );
+    sink.destroy(&gpu.gl);
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn recorders_on_program_and_clean_share_take_boundaries() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let base = pass(&mut g, &mut props, solid(1.0, 0.0, 0.0));
+    let overlay = pass(
+        &mut g,
+        &mut props,
+        frag("o = texture(iChannel0, v_uv) + vec4(0.0, 0.0, 1.0, 0.0);"),
+    );
+    let program = g.add_node(NodeKind::PixelsOut);
+    let clean = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(base, "out", overlay, "in").unwrap();
+    g.connect_named(overlay, "out", program, "in").unwrap();
+    g.connect_named(base, "out", clean, "in").unwrap();
+    name_program_and_clean(&mut props, program, clean);
+
+    let dir = std::env::temp_dir().join(format!("scheng-recorder-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let control = RecordControl::new();
+    let recorder = |name: &str| {
+        let path = dir.join(format!("{name}_{{take}}.rgba"));
+        RecorderSink::new(control.clone(), RecorderConfig::raw(path))
+    };
+    let mut patchbay = PatchbaySink::new();
+    route_program_and_clean(&mut patchbay, recorder("program"), recorder("clean"));
+
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    for n in 0..5 {
+        match n {
+            1 => control.start(),
+            3 => control.stop(),
+            _ => {}
+        }
+        patchbay.begin_frame(&gpu.gl, &frame(n));
+        let outs =
+            unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, &mut state, &props, frame(n)) }
+                .unwrap();
+        patchbay.consume_named(&gpu.gl, &outs).unwrap();
+        patchbay.end_frame(&gpu.gl);
+    }
+    patchbay.destroy(&gpu.gl);
+    unsafe { state.destroy(&gpu.gl) };
+
+    // Frames 1 and 2 in both files, with and without the overlay.
+    let frame_bytes = (SIZE * SIZE * 4) as usize;
+    for (name, rgba) in [("program", [255, 0, 255, 255]), ("clean", [255, 0, 0, 255])] {
+        let bytes = std::fs::read(dir.join(format!("{name}_001.rgba"))).unwrap();
+        assert_eq!(bytes.len(), 2 * frame_bytes, "{name}");
+        assert!(bytes.chunks_exact(4).all(|px| px == rgba), "{name}");
+    }
+    assert_eq!(control.take(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Counts its frames in a shared cell and panics on frame `panic_on`.
+struct Flaky {
+    seen: std::rc::Rc<std::cell::Cell<u32>>,
+    panic_on: Option<u32>,
+}
+
+impl OutputSink for Flaky {
+    fn consume(&mut self, _gl: &glow::Context, _out: &ExecOutput) {
+        let n = self.seen.get();
+        self.seen.set(n + 1);
+        if self.panic_on == Some(n) {
+            panic!("sink exploded on frame {n}");
+        }
+    }
+}
+
+#[test]
+fn panicking_sinks_are_disabled_without_stopping_the_show() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let src = pass(&mut g, &mut props, solid(1.0, 0.0, 0.0));
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(src, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+
+    let flaky = |panic_on| Flaky {
+        seen: Default::default(),
+        panic_on,
+    };
+    let (bad, good) = (flaky(Some(1)), flaky(None));
+    let (bad_seen, good_seen) = (bad.seen.clone(), good.seen.clone());
+    let mut patchbay = PatchbaySink::new();
+    patchbay.add_route("main", bad);
+    patchbay.add_route("main", good);
+    let mut guarded = GuardedSink::new("preview", flaky(Some(0)));
+    for n in 0..3 {
+        patchbay.begin_frame(&gpu.gl, &frame(n));
+        let outs =
+            unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, &mut state, &props, frame(n)) }
+                .unwrap();
+        patchbay.consume_named(&gpu.gl, &outs).unwrap();
+        patchbay.end_frame(&gpu.gl);
+        guarded.consume(&gpu.gl, outs.primary());
+        // The show goes on: every frame still renders.
+        assert_eq!(pixel(&gpu.gl, outs.primary()), [255, 0, 0, 255]);
+    }
+
+    assert_eq!((bad_seen.get(), good_seen.get()), (2, 3));
+    let enabled: Vec<bool> = patchbay.routes().map(|r| r.enabled).collect();
+    assert_eq!(enabled, [false, true]);
+    let events = format!("{:?}", patchbay.take_events());
+    assert!(
+        events.contains("route 'main' #0") && events.contains("exploded on frame 1"),
+        "{events}"
+    );
+    assert!(patchbay.take_events().is_empty());
+
+    assert_eq!(guarded.failure(), Some("sink exploded on frame 0"));
+    assert_eq!(guarded.inner().seen.get(), 1, "skipped after the panic");
+    assert_eq!(guarded.take_events().len(), 1);
+    guarded.reset();
+    guarded.inner_mut().panic_on = None;
+    let outs =
+        unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, &mut state, &props, frame(3)) }.unwrap();
+    guarded.consume(&gpu.gl, outs.primary());
+    assert_eq!(guarded.inner().seen.get(), 2);
+
+    patchbay.destroy(&gpu.gl);
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+/// Logs every hook call as `"<tag> <hook>"` into a shared journal.
+struct Hooks {
+    tag: &'static str,
+    log: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+}
+
+impl Hooks {
+    fn note(&self, hook: String) {
+        self.log.borrow_mut().push(format!("{} {hook}", self.tag));
+    }
+}
+
+impl OutputSink for Hooks {
+    fn consume(&mut self, _gl: &glow::Context, out: &ExecOutput) {
+        self.note(format!("consume {}x{}", out.width, out.height));
+    }
+
+    fn on_frame_begin(&mut self, _gl: &glow::Context, frame: &FrameCtx) {
+        self.note(format!("begin {}", frame.frame));
+    }
+
+    fn on_frame_end(&mut self, _gl: &glow::Context) {
+        self.note("end".into());
+    }
+
+    fn on_resize(&mut self, _gl: &glow::Context, width: i32, height: i32) {
+        self.note(format!("resize {width}x{height}"));
+    }
+
+    fn destroy(&mut self, _gl: &glow::Context) {
+        self.note("destroy".into());
+    }
+}
+
+#[test]
+fn sink_hooks_run_begin_resize_consume_end_in_order() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let src = pass(&mut g, &mut props, solid(1.0, 0.0, 0.0));
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(src, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    let log = std::rc::Rc::<std::cell::RefCell<Vec<String>>>::default();
+    let hooks = |tag| Hooks {
+        tag,
+        log: log.clone(),
+    };
+    let take = || std::mem::take(&mut *log.borrow_mut());
+    // Two frames at 4x4, then two at 8x4: resize only on the first frame of each size.
+    let sized = |n: u64| {
+        let w = if n < 2 { SIZE } else { 2 * SIZE };
+        FrameCtx::new(w, SIZE, n as f32 * 0.25, n)
+    };
+
+    let mut sink = TrackedSink::new(hooks("s"));
+    for n in 0..4 {
+        unsafe {
+            execute_plan_to_sink(&gpu.gl, &g, &plan, &mut state, &props, sized(n), &mut sink)
+        }
+        .unwrap();
+    }
+    #[rustfmt::skip]
+    let expected = [
+        "s begin 0", "s resize 4x4", "s consume 4x4", "s end",
+        "s begin 1", "s consume 4x4", "s end",
+        "s begin 2", "s resize 8x4", "s consume 8x4", "s end",
+        "s begin 3", "s consume 8x4", "s end",
+    ];
+    assert_eq!(take(), expected);
+
+    // Resize tracking is per sink: a fresh sink on the same state still gets its first resize.
+    let mut fresh = TrackedSink::new(hooks("f"));
+    unsafe { execute_plan_to_sink(&gpu.gl, &g, &plan, &mut state, &props, sized(4), &mut fresh) }
+        .unwrap();
+    assert_eq!(
+        take(),
+        ["f begin 4", "f resize 8x4", "f consume 8x4", "f end"]
+    );
+
+    // A failing frame still ends the frame it began.
+    let huge = FrameCtx::new(1 << 20, SIZE, 0.0, 5);
+    unsafe { execute_plan_to_sink(&gpu.gl, &g, &plan, &mut state, &props, huge, &mut fresh) }
+        .unwrap_err();
+    assert_eq!(take(), ["f begin 5", "f end"]);
+
+    // The patchbay keeps the same order per route, and destroys every route once.
+    let mut patchbay = PatchbaySink::new();
+    patchbay.add_route("main", hooks("a"));
+    patchbay.add_route("main", hooks("b"));
+    patchbay.set_route_enabled("main", 1, false);
+    for n in 1..3 {
+        patchbay.begin_frame(&gpu.gl, &sized(n));
+        let outs =
+            unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, &mut state, &props, sized(n)) }
+                .unwrap();
+        patchbay.consume_named(&gpu.gl, &outs).unwrap();
+        patchbay.end_frame(&gpu.gl);
+    }
+    patchbay.destroy(&gpu.gl);
+    #[rustfmt::skip]
+    let expected = [
+        "a begin 1", "a resize 4x4", "a consume 4x4", "a end",
+        "a begin 2", "a resize 8x4", "a consume 8x4", "a end",
+        "a destroy", "b destroy",
+    ];
+    assert_eq!(take(), expected);
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn history_tap_keeps_the_newest_frames_across_ring_resizes() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    // A different red every frame.
+    let src = pass(
+        &mut g,
+        &mut props,
+        frag("o = vec4(uTime / 4.0, 0.0, 0.0, 1.0);"),
+    );
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(src, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+
+    // Reds pushed so far, and reds held by the tap, newest first.
+    let mut reds = Vec::new();
+    let mut push = |tap: &mut HistoryTapSink, n: u64| {
+        let out =
+            unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(n)) }.unwrap();
+        reds.insert(0, pixel(&gpu.gl, &out)[0]);
+        tap.consume(&gpu.gl, &out);
+        reds.clone()
+    };
+    let history = |tap: &HistoryTapSink, ages: usize| -> Vec<u8> {
+        (0..ages)
+            .map(|age| texel(&gpu.gl, tap.tex_at(age).unwrap())[0])
+            .collect()
+    };
+
+    let mut tap = HistoryTapSink::new(4);
+    assert!(tap.tex_latest().is_none());
+    for n in 1..=3 {
+        push(&mut tap, n);
+    }
+    assert_eq!(tap.valid_frames(), 3);
+    assert_eq!(history(&tap, 4)[3], 0, "unwritten slots are black");
+    push(&mut tap, 4);
+    let pushed = push(&mut tap, 5);
+    assert_eq!(tap.valid_frames(), 4);
+    assert_eq!(history(&tap, 4), pushed[..4]);
+
+    // Shrinking keeps the newest frames that still fit.
+    tap.set_len(2);
+    assert_eq!(tap.valid_frames(), 2);
+    let pushed = push(&mut tap, 6);
+    assert_eq!(tap.valid_frames(), 2);
+    assert_eq!(history(&tap, 2), pushed[..2]);
+
+    // Growing keeps all of them, and the new slots start black.
+    tap.set_len(5);
+    let pushed = push(&mut tap, 7);
+    assert_eq!(tap.valid_frames(), 3);
+    assert_eq!(history(&tap, 5), [&pushed[..3], &[0, 0]].concat());
+    assert!(
+        pushed.windows(2).all(|w| w[0] > w[1]),
+        "distinct frames {pushed:?}"
+    );
+
+    unsafe { tap.destroy(&gpu.gl) };
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+/// A `w` x `h` output cleared to red level `red`. Free it with `free_output`.
+fn cleared_output(gl: &glow::Context, w: i32, h: i32, red: u8) -> ExecOutput {
+    unsafe {
+        let target = create_render_target(gl, w, h).unwrap();
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(target.fbo));
+        gl.viewport(0, 0, w, h);
+        gl.clear_color(red as f32 / 255.0, 0.0, 0.0, 1.0);
+        gl.clear(glow::COLOR_BUFFER_BIT);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        ExecOutput {
+            tex: target.tex,
+            fbo: target.fbo,
+            width: w,
+            height: h,
+        }
+    }
+}
+
+fn free_output(gl: &glow::Context, out: ExecOutput) {
+    unsafe {
+        gl.delete_framebuffer(out.fbo);
+        gl.delete_texture(out.tex);
+    }
+}
+
+/// `(seq, width, height, red)` of a frame a triple buffer's consumer received.
+type Received = (u64, i32, i32, u8);
+
+/// A triple buffer whose consumer reports each frame, then waits for a message on the returned
+/// gate (or for the gate to be dropped) before taking the next.
+fn gated_triple_buffer() -> (
+    TripleBufferSink,
+    std::sync::mpsc::Sender<()>,
+    std::sync::mpsc::Receiver<Received>,
+) {
+    let (open, gate) = std::sync::mpsc::channel::<()>();
+    let (seen_tx, seen) = std::sync::mpsc::channel();
+    let sink = TripleBufferSink::new(move |f: &BufferedFrame| {
+        assert_eq!(f.rgba.len(), (f.width * f.height * 4) as usize);
+        seen_tx.send((f.seq, f.width, f.height, f.rgba[0])).unwrap();
+        let _ = gate.recv();
+    });
+    (sink, open, seen)
+}
+
+#[test]
+fn triple_buffer_hands_a_busy_worker_only_the_newest_completed_frame() {
+    let Some(gpu) = gpu() else { return };
+    let gl = &gpu.gl;
+    let outs: Vec<ExecOutput> = [10, 20, 30, 40]
+        .into_iter()
+        .map(|red| cleared_output(gl, SIZE, SIZE, red))
+        .collect();
+    let (mut sink, open, seen) = gated_triple_buffer();
+    let mut feed = |i: usize| {
+        sink.consume(gl, &outs[i]);
+        unsafe { gl.finish() };
+    };
+
+    // A frame is handed over on the next call, once its readback has finished.
+    feed(0);
+    assert!(seen.try_recv().is_err());
+    feed(1);
+    assert_eq!(seen.recv().unwrap(), (1, SIZE, SIZE, 10));
+
+    // While the worker is busy, frames 3 and 4 rotate through the ring (4 reuses frame 1's slot)
+    // and only the newest reaches the worker once it is free.
+    feed(2);
+    feed(3);
+    open.send(()).unwrap();
+    sink.flush(gl);
+    drop(open);
+    sink.destroy(gl);
+    assert_eq!(seen.try_iter().collect::<Vec<_>>(), [(4, SIZE, SIZE, 40)]);
+    assert_eq!(
+        sink.stats(),
+        TripleBufferStats {
+            frames_in: 4,
+            delivered: 2,
+            dropped: 2,
+        }
+    );
+    for out in outs {
+        free_output(gl, out);
+    }
+}
+
+#[test]
+fn triple_buffer_restarts_seq_and_drops_the_ring_on_resize() {
+    let Some(gpu) = gpu() else { return };
+    let gl = &gpu.gl;
+    let big = [10, 20].map(|red| cleared_output(gl, SIZE, SIZE, red));
+    let small = cleared_output(gl, 2, 2, 30);
+    let (mut sink, open, seen) = gated_triple_buffer();
+
+    sink.consume(gl, &big[0]);
+    unsafe { gl.finish() };
+    sink.consume(gl, &big[1]);
+    unsafe { gl.finish() };
+    assert_eq!(seen.recv().unwrap(), (1, SIZE, SIZE, 10));
+
+    // The worker is still busy, so the second 4x4 frame is lost with the old ring.
+    sink.consume(gl, &small);
+    open.send(()).unwrap();
+    sink.flush(gl);
+    drop(open);
+    sink.destroy(gl);
+    assert_eq!(seen.try_iter().collect::<Vec<_>>(), [(1, 2, 2, 30)]);
+    assert_eq!(
+        sink.stats(),
+        TripleBufferStats {
+            frames_in: 3,
+            delivered: 2,
+            dropped: 1,
+        }
+    );
+    for out in big.into_iter().chain([small]) {
+        free_output(gl, out);
+    }
+}
+
+#[test]
+fn export_writes_fixed_step_frames_top_down() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    // Red ramps with time; the top half is green.
+    let ramp = pass(
+        &mut g,
+        &mut props,
+        frag("o = vec4(uTime, step(0.5, v_uv.y), 0.0, 1.0);"),
+    );
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(ramp, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+
+    let dir = std::env::temp_dir().join(format!("scheng-export-{}", std::process::id()));
+    let mut config = ExportConfig::new(&dir, SIZE, SIZE, 3);
+    config.fps = 4.0;
+    let mut seen = Vec::new();
+    let summary = unsafe {
+        export_sequence(&gpu.gl, &g, &plan, &mut state, &props, &config, |p| {
+            seen.push((p.done, p.total, p.frame));
+            true
+        })
+    }
+    .unwrap();
+    assert_eq!((summary.frames_written, summary.cancelled), (3, false));
+    assert_eq!(seen, [(1, 3, 0), (2, 3, 1), (3, 3, 2)]);
+
+    for (n, red) in [0u8, 64, 128].into_iter().enumerate() {
+        let decoder = png::Decoder::new(std::fs::File::open(config.frame_path(n as u64)).unwrap());
+        let mut reader = decoder.read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut buf).unwrap();
+        let at = |x: usize, y: usize| &buf[(y * SIZE as usize + x) * 4..][..4];
+        assert!(at(0, 0)[0].abs_diff(red) <= 1, "frame {n}: {:?}", at(0, 0));
+        assert_eq!(at(0, 0)[1], 255, "frame {n}: first row is the top");
+        assert_eq!(
+            at(0, SIZE as usize - 1)[1],
+            0,
+            "frame {n}: last row is the bottom"
+        );
+    }
+
+    // Stop after the first frame.
+    config.format = StillFormat::Exr;
+    let summary =
+        unsafe { export_sequence(&gpu.gl, &g, &plan, &mut state, &props, &config, |_| false) }
+            .unwrap();
+    assert_eq!((summary.frames_written, summary.cancelled), (1, true));
+    assert!(config.frame_path(0).exists() && !config.frame_path(1).exists());
+    unsafe { state.destroy(&gpu.gl) };
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn caps_reject_targets_beyond_the_texture_limit() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let big = pass(&mut g, &mut props, solid(1.0, 0.0, 0.0));
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(big, "out", out, "in").unwrap();
+
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    let caps = state.caps().clone();
+    assert!(caps.supports_version(3, 3), "{caps}");
+    assert!(
+        caps.max_texture_size >= 1024 && caps.max_pass_outputs() >= 4,
+        "{caps}"
+    );
+    assert!(caps.float32_targets, "{caps}");
+
+    let plan = g.compile().unwrap();
+    assert!(caps.check_plan(&g, &plan, SIZE, SIZE).is_ok());
+
+    let side = caps.max_texture_size as u32 + 1;
+    g.set_branch_format(big, BranchFormat::sized(side, 4))
+        .unwrap();
+    let plan = g.compile().unwrap();
+    let err = caps
+        .check_plan(&g, &plan, SIZE, SIZE)
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains(&format!("node #{}", big.0)) && err.contains(&format!("{side}x4")),
+        "{err}"
+    );
+    let err = unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(0)) }
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("maximum texture size"), "{err}");
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn bloom_adds_glow_above_the_threshold_only() {
+    let Some(gpu) = gpu() else { return };
+    let bloom_over = |shader: ShaderSource, params: BloomParams| {
+        let mut g = Graph::new();
+        let mut props = NodeProps::default();
+        let src = pass(&mut g, &mut props, shader);
+        let bloom = g.add_node(NodeKind::Bloom);
+        let out = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(src, "out", bloom, "in").unwrap();
+        g.connect_named(bloom, "out", out, "in").unwrap();
+        props.bloom_params.insert(bloom, params);
+        let plan = g.compile().unwrap();
+        let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+        let out =
+            unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(0)) }.unwrap();
+        let px = [
+            pixel_at(&gpu.gl, &out, 0, 1),
+            pixel_at(&gpu.gl, &out, SIZE - 1, 1),
+        ];
+        unsafe { state.destroy(&gpu.gl) };
+        px
+    };
+    let everything = BloomParams {
+        threshold: 0.0,
+        knee: 0.0,
+        intensity: 1.0,
+        ..BloomParams::default()
+    };
+
+    // A flat image blooms to itself, so the glow doubles it.
+    let [px, _] = bloom_over(solid(0.25, 0.0, 0.0), everything);
+    assert!(px[0].abs_diff(128) <= 2, "{px:?}");
+    // Below the threshold nothing is added.
+    let [px, _] = bloom_over(solid(0.25, 0.0, 0.0), BloomParams::default());
+    assert!(px[0].abs_diff(64) <= 1, "{px:?}");
+
+    // White on the left spills into the black right edge.
+    let half = frag("o = v_uv.x < 0.5 ? vec4(1.0) : vec4(0.0, 0.0, 0.0, 1.0);");
+    let bright = BloomParams {
+        threshold: 0.5,
+        ..BloomParams::default()
+    };
+    let [left, right] = bloom_over(half, bright);
+    assert_eq!(left[0], 255);
+    assert!(right[0] > 0 && right[0] < 128, "{right:?}");
+}
+
+#[test]
+fn audio_visualizers_draw_host_samples() {
+    let Some(gpu) = gpu() else { return };
+    let draw = |kind: NodeKind, samples: Vec<f32>| {
+        let mut g = Graph::new();
+        let vis = g.add_node(kind);
+        let out = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(vis, "out", out, "in").unwrap();
+        let mut props = NodeProps::default();
+        props.audio_vis_params.insert(
+            vis,
+            AudioVisParams {
+                color: [1.0, 0.0, 0.0, 1.0],
+                bins: 2,
+                bar_gap: 0.0,
+                ..AudioVisParams::default()
+            },
+        );
+        props.audio_samples = samples;
+        let plan = g.compile().unwrap();
+        let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+        let out =
+            unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(0)) }.unwrap();
+        // Red of each row of the left and right columns, bottom first.
+        let reds = [0, SIZE - 1].map(|x| {
+            (0..SIZE)
+                .map(|y| pixel_at(&gpu.gl, &out, x, y)[0])
+                .collect::<Vec<_>>()
+        });
+        unsafe { state.destroy(&gpu.gl) };
+        reds
+    };
+
+    // Waveform bars grow from the centre line towards the sample value.
+    let [left, right] = draw(NodeKind::AudioWaveform, vec![1.0; 256]);
+    assert_eq!(
+        (left.as_slice(), right.as_slice()),
+        (&[0, 0, 255, 255][..], &[0, 0, 255, 255][..])
+    );
+    let [left, _] = draw(NodeKind::AudioWaveform, vec![-1.0; 256]);
+    assert_eq!(left, [255, 255, 0, 0]);
+
+    // A 1024-sample sine at FFT bin 64 fills the upper band only; silence draws nothing.
+    let sine = (0..1024)
+        .map(|i| (std::f32::consts::TAU * 64.0 * i as f32 / 1024.0).sin())
+        .collect();
+    let [low, high] = draw(NodeKind::AudioSpectrum, sine);
+    assert_eq!(high, [255; 4]);
+    assert_eq!(low[SIZE as usize - 1], 0, "{low:?}");
+    let [low, high] = draw(NodeKind::AudioSpectrum, vec![0.0; 1024]);
+    assert_eq!((low, high), (vec![0; 4], vec![0; 4]));
+}
+
+/// Centre pixel of an `OpticalFlow` node fed by a 32x32 luma pattern drifting `(dx, dy)`
+/// pixels per frame (GL axes), on the third frame.
+fn flow_of_motion(gpu: &Gpu, dx: f32, dy: f32) -> [u8; 4] {
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let src = pass(
+        &mut g,
+        &mut props,
+        frag(&format!(
+            "vec2 p = v_uv - vec2({dx:.1}, {dy:.1}) * uTime * 4.0 / 32.0;
+             float l = 0.5 + 0.2 * sin(12.566 * p.x) + 0.2 * sin(12.566 * p.y);
+             o = vec4(vec3(l), 1.0);"
+        )),
+    );
+    let flow = g.add_node(NodeKind::OpticalFlow);
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(src, "out", flow, "in").unwrap();
+    g.connect_named(flow, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    let mut px = [0; 4];
+    for n in 0..3 {
+        let ctx = FrameCtx::new(32, 32, n as f32 * 0.25, n);
+        let out = unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, ctx) }.unwrap();
+        px = pixel(&gpu.gl, &out);
+    }
+    unsafe { state.destroy(&gpu.gl) };
+    px
+}
+
+#[test]
+fn optical_flow_encodes_motion_around_neutral_grey() {
+    let Some(gpu) = gpu() else { return };
+    let neutral = |c: u8| c.abs_diff(128) <= 1;
+
+    let still = flow_of_motion(&gpu, 0.0, 0.0);
+    assert!(
+        neutral(still[0]) && neutral(still[1]),
+        "static input: {still:?}"
+    );
+    assert_eq!(still[2], 0, "no magnitude without motion");
+
+    let right_down = flow_of_motion(&gpu, 1.0, -1.0);
+    assert!(right_down[0] > 130 && right_down[1] < 126, "{right_down:?}");
+    let left_up = flow_of_motion(&gpu, -1.0, 1.0);
+    assert!(left_up[0] < 126 && left_up[1] > 130, "{left_up:?}");
+    assert!(left_up[2] > 0);
+}
+
+#[test]
+fn flow_displace_samples_against_the_encoded_flow() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    // Red and green ramps, so the sampled position reads straight off the pixel.
+    let ramp = pass(&mut g, &mut props, frag("o = vec4(v_uv, 0.0, 1.0);"));
+    // Flow of +1/16 of the frame in x, none in y (0.5 + flow * FLOW_ENCODING_SCALE).
+    let flow = pass(&mut g, &mut props, solid(0.75, 0.5, 0.0));
+    let displace = g.add_node(NodeKind::FlowDisplace);
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(ramp, "out", displace, "a").unwrap();
+    g.connect_named(flow, "out", displace, "b").unwrap();
+    g.connect_named(displace, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+
+    let mut centre = |props: &NodeProps| {
+        let ctx = FrameCtx::new(32, 32, 0.0, 0);
+        let out = unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, props, ctx) }.unwrap();
+        pixel(&gpu.gl, &out)
+    };
+    // Centre texel (16, 16) sits at uv 16.5 / 32.
+    let ramp_at = |uv: f32| uv * 255.0;
+    let close = |px: u8, uv: f32| (px as f32 - ramp_at(uv)).abs() <= 2.0;
+    let shift = (191.0 / 255.0 - 0.5) / scheng_runtime::FLOW_ENCODING_SCALE;
+
+    let moved = centre(&props);
+    assert!(close(moved[0], 16.5 / 32.0 - shift), "{moved:?}");
+    assert!(close(moved[1], 16.5 / 32.0), "{moved:?}");
+    props
+        .displace_params
+        .insert(displace, DisplaceParams { amount: -2.0 });
+    let reversed = centre(&props);
+    assert!(
+        close(reversed[0], 16.5 / 32.0 + 2.0 * shift),
+        "{reversed:?}"
+    );
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn temporal_blend_smooths_only_its_output_and_resets_on_jumps() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    // Black on frame 0, then 0.4 red.
+    let src = pass(
+        &mut g,
+        &mut props,
+        frag("o = vec4(uTime > 0.0 ? 0.4 : 0.0, 0.0, 0.0, 1.0);"),
+    );
+    let main = g.add_node(NodeKind::PixelsOut);
+    let smooth = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(src, "out", main, "in").unwrap();
+    g.connect_named(src, "out", smooth, "in").unwrap();
+    props.output_names.insert(smooth, "smooth".into());
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+
+    let smoothed = |state: &mut RuntimeState, props: &NodeProps, frames: &[u64]| {
+        frames
+            .iter()
+            .map(|n| {
+                let outs =
+                    unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, state, props, frame(*n)) }
+                        .unwrap();
+                let raw = pixel(&gpu.gl, outs.primary())[0];
+                assert!(
+                    raw == 0 || raw.abs_diff(102) <= 1,
+                    "main is not smoothed: {raw}"
+                );
+                pixel(&gpu.gl, outs.get("smooth").unwrap())
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut blend = TemporalParams {
+        blend: 0.5,
+        clamp_history: false,
+        reset_threshold: 0.0,
+    };
+    props.temporal_params.insert(smooth, blend);
+    // Converges towards 102; the jump to frame 9 drops the history.
+    assert_reds(
+        &smoothed(&mut state, &props, &[0, 1, 2, 9]),
+        &[0, 51, 77, 102],
+    );
+
+    // Neighbourhood clamping and the reset threshold both reject the stale black history.
+    blend.clamp_history = true;
+    props.temporal_params.insert(smooth, blend);
+    state.reset_temporal(smooth);
+    assert_reds(&smoothed(&mut state, &props, &[0, 1]), &[0, 102]);
+    blend.clamp_history = false;
+    blend.reset_threshold = 0.3;
+    props.temporal_params.insert(smooth, blend);
+    state.reset_temporal(smooth);
+    assert_reds(&smoothed(&mut state, &props, &[0, 1]), &[0, 102]);
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn stock_filters_pixelate_posterize_and_dither() {
+    let Some(gpu) = gpu() else { return };
+    let render = |kind: NodeKind, shader: ShaderSource, set: &dyn Fn(NodeId, &mut NodeProps)| {
+        let mut g = Graph::new();
+        let mut props = NodeProps::default();
+        let src = pass(&mut g, &mut props, shader);
+        let filter = g.add_node(kind);
+        let out = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(src, "out", filter, "in").unwrap();
+        g.connect_named(filter, "out", out, "in").unwrap();
+        set(filter, &mut props);
+        let plan = g.compile().unwrap();
+        let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+        let out =
+            unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(0)) }.unwrap();
+        let reds: Vec<u8> = (0..SIZE * SIZE)
+            .map(|i| pixel_at(&gpu.gl, &out, i % SIZE, i / SIZE)[0])
+            .collect();
+        unsafe { state.destroy(&gpu.gl) };
+        reds
+    };
+
+    // 2px blocks sample their centre: columns pair up.
+    let ramp = || frag("o = vec4(v_uv.x, 0.0, 0.0, 1.0);");
+    let reds = render(NodeKind::Pixelate, ramp(), &|n, p| {
+        p.pixelate_params
+            .insert(n, PixelateParams { block_size: 2.0 });
+    });
+    assert_eq!(reds[0], reds[1]);
+    assert_eq!(reds[2], reds[3]);
+    assert!(reds[0] < reds[2], "{reds:?}");
+
+    let posterize = |r: f32, levels: u32| {
+        render(NodeKind::Posterize, solid(r, 0.0, 0.0), &|n, p| {
+            p.posterize_params.insert(n, PosterizeParams { levels });
+        })[0]
+    };
+    assert_eq!(posterize(0.3, 2), 0);
+    assert_eq!(posterize(0.6, 2), 255);
+    assert!(posterize(0.3, 3).abs_diff(128) <= 1);
+
+    // Mid-grey dithered to two levels: half the pixels light up, or all without dithering.
+    let lit = |mode: DitherMode, amount: f32| {
+        let reds = render(NodeKind::Dither, solid(0.5, 0.0, 0.0), &|n, p| {
+            let params = DitherParams {
+                mode,
+                levels: 2,
+                amount,
+            };
+            p.dither_params.insert(n, params);
+        });
+        assert!(reds.iter().all(|r| *r == 0 || *r == 255), "{reds:?}");
+        reds.iter().filter(|r| **r == 255).count()
+    };
+    assert_eq!(lit(DitherMode::Ordered, 1.0), 8);
+    assert_eq!(lit(DitherMode::Ordered, 0.0), 16);
+    assert!((4..=12).contains(&lit(DitherMode::BlueNoise, 1.0)));
+}
+
+#[test]
+fn shader_params_become_registry_controls_that_follow_reloads() {
+    let Some(gpu) = gpu() else { return };
+    let with_params = |annotations: &str| {
+        let mut shader = frag("o = vec4(u_level, 0.0, 0.0, 1.0);");
+        shader.frag = shader.frag.replace(
+            "uniform float uTime;",
+            &format!("{annotations}\nuniform float u_level;"),
+        );
+        shader
+    };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let level = pass(
+        &mut g,
+        &mut props,
+        with_params("// @param u_level range(0, 1) default(0.5) \"Red level\""),
+    );
+    g.set_node_name(level, "level").unwrap();
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(level, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    let red = |state: &mut RuntimeState, props: &NodeProps| {
+        let out = unsafe { execute_plan(&gpu.gl, &g, &plan, state, props, frame(0)) }.unwrap();
+        pixel(&gpu.gl, &out)[0]
+    };
+    let key = "node/level/u_level";
+
+    assert_eq!(red(&mut state, &props), 128, "the default applies");
+    assert_eq!(state.params().meta(key).unwrap().description, "Red level");
+    state.params_mut().set(key, 1.0);
+    assert_eq!(red(&mut state, &props), 255);
+    props
+        .custom_uniforms
+        .entry(level)
+        .or_default()
+        .insert("u_level".into(), 0.25);
+    assert_eq!(red(&mut state, &props), 64, "custom uniforms win");
+    props.custom_uniforms.clear();
+
+    // Hot reload: the value survives, clamped to the new range.
+    props.shader_sources.insert(
+        level,
+        with_params("#pragma param u_level range(0, 0.5) default(0.2)"),
+    );
+    assert_eq!(red(&mut state, &props), 128);
+    assert_eq!(state.params().get(key), Some(0.5));
+
+    props
+        .shader_sources
+        .insert(level, with_params("// @param u_level range(0)"));
+    red(&mut state, &props);
+    assert_eq!(
+        state.params().get(key),
+        None,
+        "undeclared controls are removed"
+    );
+    let events = state.take_events();
+    assert!(
+        matches!(&events[..], [EngineEvent::Log { msg, .. }] if msg.contains("line 4")),
+        "{events:?}"
+    );
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn edge_taps_expose_intermediate_textures_as_debug_outputs() {
+    let Some(gpu) = gpu() else { return };
+    let gl = &gpu.gl;
+    let tex = host_texture(gl, [100, 20, 30, 255]);
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let input = g.add_node(NodeKind::TextureInputPass);
+    let grade = pass(
+        &mut g,
+        &mut props,
+        frag("o = texture(iChannel0, v_uv) * 0.5;"),
+    );
+    let fx = pass(
+        &mut g,
+        &mut props,
+        frag("o = vec4(1.0) - texture(iChannel0, v_uv);"),
+    );
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(input, "out", grade, "in").unwrap();
+    g.connect_named(grade, "out", fx, "in").unwrap();
+    g.connect_named(fx, "out", out, "in").unwrap();
+    g.set_node_name(fx, "fx").unwrap();
+    props.texture_inputs.insert(input, tex);
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(gl) }.unwrap();
+
+    state.set_edge_tap(fx, "in", true);
+    state.set_edge_tap(grade, "in", true);
+    state.set_edge_tap(grade, "missing", true);
+    let outs =
+        unsafe { execute_plan_outputs(gl, &g, &plan, &mut state, &props, frame(0)) }.unwrap();
+    let grade_in = taps::tap_output_name(&g, grade, "in");
+    assert_eq!(
+        outs.names().collect::<Vec<_>>(),
+        ["main", "debug:fx:in", grade_in.as_str()]
+    );
+    assert_eq!(taps::parse_tap_name(&g, "debug:fx:in"), Some((fx, "in")));
+    assert_eq!(
+        pixel(gl, outs.get("debug:fx:in").unwrap()),
+        [50, 10, 15, 128]
+    );
+    assert_eq!(outs.entry("debug:fx:in").unwrap().node, grade);
+    assert_eq!(pixel(gl, outs.get(&grade_in).unwrap()), [100, 20, 30, 255]);
+    assert_eq!(pixel(gl, outs.primary()), [205, 245, 240, 127]);
+
+    state.set_edge_tap(grade, "in", false);
+    let outs =
+        unsafe { execute_plan_outputs(gl, &g, &plan, &mut state, &props, frame(1)) }.unwrap();
+    assert_eq!(outs.names().collect::<Vec<_>>(), ["main", "debug:fx:in"]);
+    unsafe {
+        state.destroy(gl);
+        gl.delete_texture(tex);
+    }
+}
+
+#[test]
+fn stats_count_programs_targets_and_textures() {
+    let Some(gpu) = gpu() else { return };
+    let gl = &gpu.gl;
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let a = pass(&mut g, &mut props, solid(1.0, 0.0, 0.0));
+    let b = pass(&mut g, &mut props, frag("o = texture(iChannel0, v_uv);"));
+    let dither = g.add_node(NodeKind::Dither);
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(a, "out", b, "in").unwrap();
+    g.connect_named(b, "out", dither, "in").unwrap();
+    g.connect_named(dither, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(gl) }.unwrap();
+    assert_eq!(state.stats().target_count(), 0);
+
+    unsafe { execute_plan(gl, &g, &plan, &mut state, &props, frame(0)) }.unwrap();
+    let stats = state.stats();
+    assert_eq!(stats.programs, 3);
+    assert_eq!(
+        stats.targets,
+        [TargetGroup {
+            width: SIZE,
+            height: SIZE,
+            format: TextureFormat::Rgba8,
+            count: 6,
+        }],
+        "a ping-pong pair per render node"
+    );
+    assert_eq!(stats.target_bytes(), 6 * 4 * (SIZE * SIZE) as usize);
+    assert!(stats.host_textures >= 1, "the dither map: {stats:?}");
+    assert_eq!(stats.video_decoders, 0);
+
+    unsafe { execute_plan(gl, &g, &plan, &mut state, &props, frame(1)) }.unwrap();
+    assert_eq!(state.stats(), stats, "steady state allocates nothing");
+    unsafe { state.destroy(gl) };
+    assert_eq!(state.stats().gpu_bytes(), 0);
+}
+
+#[test]
+fn node_seed_survives_reloads_and_reload_count_steps() {
+    let Some(gpu) = gpu() else { return };
+    let source = |comment: &str| {
+        let mut shader = frag("o = vec4(uNodeSeed, uReloadCount * 0.25, 0.0, 1.0);");
+        shader.frag = shader.frag.replace(
+            "uniform float uTime;",
+            &format!("// {comment}\nuniform float uNodeSeed;\nuniform float uReloadCount;"),
+        );
+        shader
+    };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let noise = pass(&mut g, &mut props, source("v1"));
+    g.set_node_name(noise, "noise").unwrap();
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(noise, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    let render = |state: &mut RuntimeState, props: &NodeProps, seed: u32| {
+        let f = frame(0).with_seed(seed);
+        let out = unsafe { execute_plan(&gpu.gl, &g, &plan, state, props, f) }.unwrap();
+        pixel(&gpu.gl, &out)
+    };
+    let expected = |seed: u32| (node_seed_f32("noise", seed) * 255.0).round() as u8;
+
+    let px = render(&mut state, &props, 7);
+    assert_eq!((px[0], px[1]), (expected(7), 0));
+    props.shader_sources.insert(noise, source("v2"));
+    let px = render(&mut state, &props, 7);
+    assert_eq!((px[0], px[1]), (expected(7), 64), "same seed, one reload");
+    assert_eq!(state.reload_count(noise), 1);
+    render(&mut state, &props, 7);
+    assert_eq!(
+        state.reload_count(noise),
+        1,
+        "an unchanged source is no reload"
+    );
+
+    state.invalidate();
+    let px = render(&mut state, &props, 8);
+    assert_eq!((px[0], px[1]), (expected(8), 64), "another run reseeds");
+    assert_ne!(expected(7), expected(8));
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn output_alpha_policies_convert_each_named_output() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let src = pass(&mut g, &mut props, frag("o = vec4(0.8, 0.4, 0.2, 0.5);"));
+    let main = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(src, "out", main, "in").unwrap();
+    let mut named = Vec::new();
+    for (name, policy) in [
+        ("opaque", AlphaPolicy::Discard),
+        ("premul", AlphaPolicy::Premultiplied),
+    ] {
+        let out = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(src, "out", out, "in").unwrap();
+        props.output_names.insert(out, name.into());
+        props.output_alpha.insert(out, policy);
+        named.push(out);
+    }
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+
+    let outs =
+        unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, &mut state, &props, frame(0)) }.unwrap();
+    let px = |name: &str| {
+        pixel(
+            &gpu.gl,
+            &outs.named.iter().find(|o| o.name == name).unwrap().out,
+        )
+    };
+    assert_eq!(px("main"), [204, 102, 51, 128], "straight by default");
+    assert_eq!(px("opaque"), [204, 102, 51, 255]);
+    assert_eq!(px("premul"), [102, 51, 26, 128]);
+    let policies: Vec<_> = outs.named.iter().map(|o| o.alpha).collect();
+    assert_eq!(
+        policies,
+        [
+            AlphaPolicy::Straight,
+            AlphaPolicy::Discard,
+            AlphaPolicy::Premultiplied
+        ]
+    );
+
+    // The primary output follows `main`'s policy; dropping a policy drops its pass.
+    props.output_alpha.insert(main, AlphaPolicy::Discard);
+    props.output_alpha.remove(&named[1]);
+    let out = unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(1)) }.unwrap();
+    assert_eq!(pixel(&gpu.gl, &out), [204, 102, 51, 255]);
+    assert_eq!(
+        state.stats().target_count(),
+        4,
+        "the source's pair plus two alpha passes"
+    );
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn clear_modes_set_the_background_or_keep_the_previous_frame() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    // Frame n paints column n red and leaves the rest to the clear.
+    let paint = pass(
+        &mut g,
+        &mut props,
+        frag(
+            "if (int(gl_FragCoord.x) != int(uTime * 4.0 + 0.5)) discard;
+            o = vec4(1.0, 0.0, 0.0, 1.0);",
+        ),
+    );
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(paint, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    let row = |state: &mut RuntimeState, props: &NodeProps, n: u64| {
+        let out = unsafe { execute_plan(&gpu.gl, &g, &plan, state, props, frame(n)) }.unwrap();
+        (0..SIZE)
+            .map(|x| pixel_at(&gpu.gl, &out, x, 1))
+            .collect::<Vec<_>>()
+    };
+    const RED: [u8; 4] = [255, 0, 0, 255];
+
+    assert_eq!(
+        row(&mut state, &props, 0)[1],
+        [0, 0, 0, 255],
+        "opaque black"
+    );
+    props.clear_modes.insert(paint, ClearMode::TRANSPARENT);
+    assert_eq!(row(&mut state, &props, 0)[1], [0, 0, 0, 0]);
+    props
+        .clear_modes
+        .insert(paint, ClearMode::Color([0.0, 0.0, 1.0, 1.0]));
+    assert_eq!(row(&mut state, &props, 0)[..2], [RED, [0, 0, 255, 255]]);
+
+    props.clear_modes.insert(paint, ClearMode::Keep);
+    for n in 1..SIZE as u64 {
+        row(&mut state, &props, n);
+    }
+    assert_eq!(
+        row(&mut state, &props, 3),
+        [RED; 4],
+        "each frame's column stays"
+    );
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn render_hooks_wrap_the_node_draw_on_its_bound_target() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    // Red on the left half over a green clear.
+    let node = pass(
+        &mut g,
+        &mut props,
+        frag("if (v_uv.x > 0.5) discard; o = vec4(1.0, 0.0, 0.0, 1.0);"),
+    );
+    props
+        .clear_modes
+        .insert(node, ClearMode::Color([0.0, 1.0, 0.0, 1.0]));
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(node, "out", out, "in").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+
+    // The pre hook sees its target bound and cleared, even where the last draw into this
+    // (ping-pong) target left red.
+    let seen = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let log = seen.clone();
+    state.set_pre_render_hook(node, move |gl, ctx| {
+        let bound = unsafe { gl.get_parameter_i32(glow::FRAMEBUFFER_BINDING) } as u32;
+        let mut px = [0u8; 4];
+        unsafe {
+            gl.read_pixels(
+                0,
+                0,
+                1,
+                1,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut px),
+            )
+        };
+        log.borrow_mut().push((bound == ctx.fbo.0.get(), px));
+        Ok(())
+    });
+    // The post hook paints the top half blue over the node's draw.
+    state.set_post_render_hook(node, |gl, ctx| {
+        unsafe {
+            gl.enable(glow::SCISSOR_TEST);
+            gl.scissor(0, ctx.height / 2, ctx.width, ctx.height / 2);
+            gl.clear_color(0.0, 0.0, 1.0, 1.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+            gl.disable(glow::SCISSOR_TEST);
+        }
+        Ok(())
+    });
+    let mut last = None;
+    for n in 0..3 {
+        last = Some(
+            unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(n)) }.unwrap(),
+        );
+    }
+    let last = last.unwrap();
+    assert_eq!(*seen.borrow(), [(true, [0, 255, 0, 255]); 3]);
+    assert_eq!(pixel_at(&gpu.gl, &last, 0, 0), [255, 0, 0, 255]);
+    assert_eq!(pixel_at(&gpu.gl, &last, SIZE - 1, 0), [0, 255, 0, 255]);
+    assert_eq!(pixel_at(&gpu.gl, &last, 0, SIZE - 1), [0, 0, 255, 255]);
+
+    // A failing hook fails the frame.
+    state.set_post_render_hook(node, |_, _| Err(EngineError::other("overlay lost")));
+    let err = unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(3)) }
+        .expect_err("hook error propagates");
+    assert!(err.to_string().contains("overlay lost"), "{err}");
+    state.set_pre_render_hook(node, |_, _| Err(EngineError::other("pre failed")));
+    let err = unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(4)) }
+        .expect_err("hook error propagates");
+    assert!(err.to_string().contains("pre failed"), "{err}");
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn global_uniforms_yield_to_node_and_standard_values() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let shader = ShaderSource {
+        vert: FULLSCREEN_VERT.to_string(),
+        frag: "#version 330 core
+out vec4 o;
+uniform float u_gain;
+uniform float uTime;
+void main() { o = vec4(u_gain, uTime, 0.0, 1.0); }"
+            .to_string(),
+        origin: None,
+    };
+    let trimmed = pass(&mut g, &mut props, shader.clone());
+    let plain = pass(&mut g, &mut props, shader);
+    let main = g.add_node(NodeKind::PixelsOut);
+    let side = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(trimmed, "out", main, "in").unwrap();
+    g.connect_named(plain, "out", side, "in").unwrap();
+    props.output_names.insert(side, "side".into());
+    let props = props
+        .with_uniform(trimmed, "u_gain", 1.0)
+        .with_global_uniform("u_gain", 0.25)
+        .with_global_uniform("uTime", 0.0);
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+
+    // frame(2) runs at uTime 0.5; the global `uTime` must not replace it.
+    let outs =
+        unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, &mut state, &props, frame(2)) }.unwrap();
+    let [r, t, ..] = pixel(&gpu.gl, outs.primary());
+    assert_eq!(r, 255, "the node's custom uniform wins");
+    assert!(t.abs_diff(128) <= 1, "uTime {t}");
+    let [r, t, ..] = pixel(&gpu.gl, outs.get("side").unwrap());
+    assert_eq!(r, 64, "other nodes get the global");
+    assert!(t.abs_diff(128) <= 1, "uTime {t}");
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn frame_block_carries_per_frame_values_per_branch_size() {
+    let Some(gpu) = gpu() else { return };
+    let block = |body: &str| {
+        let mut shader = frag(body);
+        shader.frag = shader.frag.replace(
+            "uniform float uTime;",
+            &format!("uniform float uTime;\n{FRAME_BLOCK_GLSL}"),
+        );
+        shader
+    };
+    let body = "o = vec4(scheng.resolution.x / 8.0, scheng.time, scheng.frame / 8.0, 1.0);";
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let full = pass(&mut g, &mut props, block(body));
+    let small = pass(&mut g, &mut props, block(body));
+    g.set_branch_format(small, BranchFormat::sized(2, 2))
+        .unwrap();
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(full, "out", out, "in").unwrap();
+    let small_out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(small, "out", small_out, "in").unwrap();
+    props.output_names.insert(small_out, "small".into());
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+
+    let outs =
+        unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, &mut state, &props, frame(2)) }.unwrap();
+    assert!(state.standard_uniforms(full).unwrap().uses_frame_block());
+    assert_eq!(pixel(&gpu.gl, &outs.primary), [128, 128, 64, 255]);
+    let small_px = pixel(&gpu.gl, outs.get("small").unwrap());
+    assert_eq!(small_px, [64, 128, 64, 255], "the branch size is uploaded");
+
+    let out = unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(3)) }.unwrap();
+    assert_eq!(pixel(&gpu.gl, &out), [128, 191, 96, 255], "the next frame");
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn group_controls_bypass_and_fade_a_section() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    let red = pass(&mut g, &mut props, solid(1.0, 0.0, 0.0));
+    let fx = pass(&mut g, &mut props, frag("o = texture(iChannel0, v_uv);"));
+    let green = pass(&mut g, &mut props, solid(0.0, 1.0, 0.0));
+    let mix = g.add_node(NodeKind::Add);
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(red, "out", fx, "in").unwrap();
+    g.connect_named(fx, "out", mix, "a").unwrap();
+    g.connect_named(green, "out", mix, "b").unwrap();
+    g.connect_named(mix, "out", out, "in").unwrap();
+    g.add_to_group(red, "drums").unwrap();
+    g.add_to_group(fx, "drums").unwrap();
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    declare_group_params(state.params_mut(), &g);
+    let render = |state: &mut RuntimeState| {
+        let out = unsafe { execute_plan(&gpu.gl, &g, &plan, state, &props, frame(0)) }.unwrap();
+        pixel(&gpu.gl, &out)
+    };
+
+    assert_eq!(render(&mut state), [255, 255, 0, 255]);
+    // The edge inside the group is not scaled, the one leaving it into the mixer is.
+    state
+        .params_mut()
+        .set(&group_param_key("drums", "opacity"), 0.5);
+    assert_eq!(render(&mut state), [128, 255, 0, 255]);
+    state
+        .params_mut()
+        .set(&group_param_key("drums", "bypass"), 1.0);
+    assert_eq!(render(&mut state), [0, 255, 0, 255], "the section is muted");
+    assert!(
+        !state.is_node_bypassed(red),
+        "the node's own flag is untouched"
+    );
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn shader_bank_slots_switch_from_the_registry_without_compiling() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let look = g.add_node_named(NodeKind::ShaderPass, "look").unwrap();
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(look, "out", out, "in").unwrap();
+    let bank = ShaderBank::new(vec![solid(1.0, 0.0, 0.0), solid(0.0, 1.0, 0.0)]).with_active(1);
+    let props = NodeProps::new()
+        .with_frag(look, "o = vec4(1.0);", "ignored")
+        .with_shader_bank(look, bank);
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+    let report = unsafe { state.precompile(&gpu.gl, &g, &props) };
+    assert!(report.failures.is_empty(), "{:?}", report.failures);
+    let programs = state.stats().programs;
+    let render = |state: &mut RuntimeState| {
+        let out = unsafe { execute_plan(&gpu.gl, &g, &plan, state, &props, frame(0)) }.unwrap();
+        pixel(&gpu.gl, &out)
+    };
+
+    let key = shader_slot_key(&g, look);
+    assert_eq!(key, "node/look/slot");
+    assert_eq!(state.params().meta(&key).map(|m| m.max), Some(1.0));
+    assert_eq!(
+        render(&mut state),
+        [0, 255, 0, 255],
+        "the bank's active slot"
+    );
+    state.params_mut().set(&key, 0.0);
+    assert_eq!(render(&mut state), [255, 0, 0, 255]);
+    assert_eq!(
+        state.stats().programs,
+        programs,
+        "both slots were precompiled"
+    );
+    unsafe { state.destroy(&gpu.gl) };
+}
+
+#[test]
+fn probe_pixel_reads_named_outputs_top_down() {
+    let Some(gpu) = gpu() else { return };
+    let mut g = Graph::new();
+    let mut props = NodeProps::default();
+    // Red on the top half at time 0, green afterwards.
+    let src = pass(
+        &mut g,
+        &mut props,
+        frag("o = vec4(v_uv.y > 0.5 && uTime == 0.0 ? 1.0 : 0.0, uTime > 0.0 ? 1.0 : 0.0, 0.0, 1.0);"),
+    );
+    let main = g.add_node(NodeKind::PixelsOut);
+    let side = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(src, "out", main, "in").unwrap();
+    g.connect_named(src, "out", side, "in").unwrap();
+    props.output_names.insert(side, "side".into());
+    let plan = g.compile().unwrap();
+    let mut state = unsafe { RuntimeState::new(&gpu.gl) }.unwrap();
+
+    assert_eq!(unsafe { state.probe_pixel(&gpu.gl, "main", 0, 0) }, None);
+    unsafe { execute_plan_outputs(&gpu.gl, &g, &plan, &mut state, &props, frame(0)) }.unwrap();
+    let probe = |state: &mut RuntimeState, name: &str, x: i32, y: i32| unsafe {
+        state.probe_pixel(&gpu.gl, name, x, y)
+    };
+    assert_eq!(probe(&mut state, "side", 0, 0), Some([1.0, 0.0, 0.0, 1.0]));
+    assert_eq!(
+        probe(&mut state, "side", 0, SIZE - 1),
+        Some([0.0, 0.0, 0.0, 1.0])
+    );
+    assert_eq!(probe(&mut state, "main", 2, 1), Some([1.0, 0.0, 0.0, 1.0]));
+    assert_eq!(probe(&mut state, "side", SIZE, 0), None);
+    assert_eq!(probe(&mut state, "missing", 0, 0), None);
+
+    unsafe { execute_plan(&gpu.gl, &g, &plan, &mut state, &props, frame(1)) }.unwrap();
+    assert_eq!(probe(&mut state, "main", 0, 0), Some([0.0, 1.0, 0.0, 1.0]));
+    assert_eq!(
+        probe(&mut state, "side", 0, 0),
+        None,
+        "execute_plan only exposes main"
+    );
+    unsafe { state.destroy(&gpu.gl) };
+}
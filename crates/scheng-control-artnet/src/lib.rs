@@ -0,0 +1,220 @@
+//! scheng-control-artnet
+//!
+//! DMX over Art-Net or sACN (E1.31), in both directions:
+//! - **Input**: a [`DmxMap`] maps lighting-desk channels (8-bit, or 16-bit coarse/fine pairs)
+//!   to registry parameters, so video parameters can be run from the console.
+//! - **Output**: [`DmxOutputSink`] samples a routed engine output at the fixture positions of a
+//!   [`PixelMap`] and transmits the universes, for LED strips and pixel-mapped fixtures.
+//!
+//! Input has the same shape as `scheng-control-osc`: a non-blocking socket drained once per frame. Only
+//! channels whose value changed since the last poll are reported, since desks resend every
+//! universe continuously.
+//!
+//! ```no_run
+//! use scheng_control_artnet::{DmxMap, DmxMapping, DmxMode, DmxReceiver};
+//! use scheng_runtime::ParamRegistry;
+//!
+//! let map = DmxMap::default()
+//!     .with(DmxMapping::new(0, 1, DmxMode::Bit8, "mix"))
+//!     .with(DmxMapping::new(0, 2, DmxMode::Bit16, "node/3/gain").with_range(0.0, 2.0));
+//! let mut dmx = DmxReceiver::bind_artnet("0.0.0.0:6454", map)?;
+//! let mut params = ParamRegistry::new();
+//!
+//! // In render loop:
+//! dmx.apply(&mut params);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub mod mapping;
+pub mod packet;
+pub mod pixelmap;
+pub mod sender;
+pub mod sink;
+
+use std::io;
+use std::net::{Ipv4Addr, UdpSocket};
+
+use scheng_runtime::ParamRegistry;
+
+pub use mapping::{DmxMap, DmxMapping, DmxMode, DMX_MAP_SCHEMA};
+pub use packet::{
+    build_artnet, build_sacn, parse_artnet, parse_sacn, DmxFrame, SacnSource, ARTNET_PORT,
+    SACN_PORT,
+};
+pub use pixelmap::{ChannelOrder, Fixture, FixtureShape, PixelMap, PIXEL_MAP_SCHEMA};
+pub use sender::DmxSender;
+pub use sink::{DmxOutputSink, DmxSinkConfig};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmxProtocol {
+    ArtNet,
+    Sacn,
+}
+
+/// Non-blocking DMX receiver.
+#[derive(Debug)]
+pub struct DmxReceiver {
+    sock: UdpSocket,
+    protocol: DmxProtocol,
+    map: DmxMap,
+    /// Last normalized value per mapping (same index as `map.mappings`).
+    last: Vec<Option<f32>>,
+    buf: Vec<u8>,
+}
+
+impl DmxReceiver {
+    /// Listen for Art-Net on an address like "0.0.0.0:6454".
+    pub fn bind_artnet(addr: &str, map: DmxMap) -> io::Result<Self> {
+        Self::bind(addr, DmxProtocol::ArtNet, map)
+    }
+
+    /// Listen for sACN on an address like "0.0.0.0:5568". Multicast senders additionally need
+    /// [`join_sacn_universe`](Self::join_sacn_universe); unicast works as is.
+    pub fn bind_sacn(addr: &str, map: DmxMap) -> io::Result<Self> {
+        Self::bind(addr, DmxProtocol::Sacn, map)
+    }
+
+    fn bind(addr: &str, protocol: DmxProtocol, map: DmxMap) -> io::Result<Self> {
+        let sock = UdpSocket::bind(addr)?;
+        sock.set_nonblocking(true)?;
+        let last = vec![None; map.mappings.len()];
+        Ok(Self {
+            sock,
+            protocol,
+            map,
+            last,
+            buf: vec![0u8; 1024],
+        })
+    }
+
+    /// Join the multicast group of an sACN universe on `interface` (`UNSPECIFIED` = default).
+    pub fn join_sacn_universe(&self, universe: u16, interface: Ipv4Addr) -> io::Result<()> {
+        self.sock
+            .join_multicast_v4(&packet::sacn_multicast_addr(universe), &interface)
+    }
+
+    pub fn protocol(&self) -> DmxProtocol {
+        self.protocol
+    }
+
+    pub fn map(&self) -> &DmxMap {
+        &self.map
+    }
+
+    /// Replace the patch. Every mapping reports again on the next packet.
+    pub fn set_map(&mut self, map: DmxMap) {
+        self.last = vec![None; map.mappings.len()];
+        self.map = map;
+    }
+
+    /// Drain the socket and return changed `(param, value)` pairs, scaled by each mapping's
+    /// `range` (0..1 when unset).
+    pub fn poll(&mut self) -> Vec<(String, f32)> {
+        self.poll_changed()
+            .into_iter()
+            .map(|(i, norm)| {
+                let m = &self.map.mappings[i];
+                let (lo, hi) = m.range.unwrap_or((0.0, 1.0));
+                (m.param.clone(), lo + (hi - lo) * norm)
+            })
+            .collect()
+    }
+
+    /// Drain the socket and write changed values into `params`. Mappings without an explicit
+    /// range use the parameter's declared range. Returns the number of parameters updated.
+    pub fn apply(&mut self, params: &mut ParamRegistry) -> usize {
+        let changed = self.poll_changed();
+        for &(i, norm) in &changed {
+            let m = &self.map.mappings[i];
+            let (lo, hi) = m
+                .range
+                .or_else(|| params.meta(&m.param).map(|meta| (meta.min, meta.max)))
+                .unwrap_or((0.0, 1.0));
+            params.set(&m.param, lo + (hi - lo) * norm);
+        }
+        changed.len()
+    }
+
+    /// `(mapping index, normalized value)` for every mapping whose value changed.
+    fn poll_changed(&mut self) -> Vec<(usize, f32)> {
+        let mut out = Vec::new();
+        loop {
+            match self.sock.recv_from(&mut self.buf) {
+                Ok((n, _from)) => {
+                    let frame = match self.protocol {
+                        DmxProtocol::ArtNet => parse_artnet(&self.buf[..n]),
+                        DmxProtocol::Sacn => parse_sacn(&self.buf[..n]),
+                    };
+                    if let Some(frame) = frame {
+                        diff_frame(&self.map, &mut self.last, frame, &mut out);
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(_e) => break,
+            }
+        }
+        // A later packet in the same poll supersedes an earlier one.
+        out.reverse();
+        let mut seen = vec![false; self.last.len()];
+        out.retain(|&(i, _)| !std::mem::replace(&mut seen[i], true));
+        out.reverse();
+        out
+    }
+}
+
+fn diff_frame(
+    map: &DmxMap,
+    last: &mut [Option<f32>],
+    frame: DmxFrame<'_>,
+    out: &mut Vec<(usize, f32)>,
+) {
+    for (i, m) in map.mappings.iter().enumerate() {
+        if m.universe != frame.universe {
+            continue;
+        }
+        let Some(v) = m.read(frame.data) else {
+            continue;
+        };
+        if last[i] != Some(v) {
+            last[i] = Some(v);
+            out.push((i, v));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_changes_are_reported() {
+        let map = DmxMap::default()
+            .with(DmxMapping::new(1, 1, DmxMode::Bit8, "a"))
+            .with(DmxMapping::new(2, 1, DmxMode::Bit8, "other_universe"));
+        let mut last = vec![None; 2];
+        let mut out = Vec::new();
+
+        let p = build_artnet(1, 0, &[51]);
+        diff_frame(&map, &mut last, parse_artnet(&p).unwrap(), &mut out);
+        diff_frame(&map, &mut last, parse_artnet(&p).unwrap(), &mut out);
+        assert_eq!(out, vec![(0, 0.2)]);
+    }
+
+    #[test]
+    fn loopback_apply_uses_declared_range() {
+        let map = DmxMap::default().with(DmxMapping::new(0, 1, DmxMode::Bit16, "gain"));
+        let mut rx = DmxReceiver::bind_artnet("127.0.0.1:0", map).unwrap();
+        let addr = rx.sock.local_addr().unwrap();
+        let mut params = ParamRegistry::new();
+        params.declare("gain", scheng_runtime::ParamMeta::new(0.0, 4.0, 1.0));
+
+        let tx = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tx.send_to(&build_artnet(0, 0, &[0xff, 0xff]), addr)
+            .unwrap();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while rx.apply(&mut params) == 0 && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(params.get("gain"), Some(4.0));
+    }
+}
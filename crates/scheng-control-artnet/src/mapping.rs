@@ -0,0 +1,125 @@
+//! DMX channel → parameter mappings.
+
+use scheng_core::{JsonSchema, Migration};
+use serde::{Deserialize, Serialize};
+
+/// Channel resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DmxMode {
+    /// One channel, 0..=255.
+    #[default]
+    Bit8,
+    /// Coarse channel + fine channel (`channel + 1`), 0..=65535.
+    Bit16,
+}
+
+/// Maps one DMX channel (or coarse/fine pair) to a registry parameter.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DmxMapping {
+    pub universe: u16,
+    /// 1-based, as shown on the lighting desk.
+    pub channel: u16,
+    #[serde(default)]
+    pub mode: DmxMode,
+    pub param: String,
+    /// Output range. `None` uses the parameter's declared range, or 0..1 if undeclared.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<(f32, f32)>,
+}
+
+impl DmxMapping {
+    pub fn new(universe: u16, channel: u16, mode: DmxMode, param: impl Into<String>) -> Self {
+        Self {
+            universe,
+            channel,
+            mode,
+            param: param.into(),
+            range: None,
+        }
+    }
+
+    pub fn with_range(mut self, min: f32, max: f32) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Normalized value 0..1 from a universe's slots, or `None` if the channel is out of range.
+    pub fn read(&self, data: &[u8]) -> Option<f32> {
+        let i = self.channel.checked_sub(1)? as usize;
+        match self.mode {
+            DmxMode::Bit8 => Some(*data.get(i)? as f32 / 255.0),
+            DmxMode::Bit16 => {
+                let v = u16::from_be_bytes([*data.get(i)?, *data.get(i + 1)?]);
+                Some(v as f32 / 65535.0)
+            }
+        }
+    }
+}
+
+/// Schema of DMX map files.
+pub const DMX_MAP_SCHEMA: JsonSchema = JsonSchema::new("dmx map", 1, &[Migration::unchanged(0)]);
+
+/// A full patch: every mapped channel. Load/save as JSON.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DmxMap {
+    pub mappings: Vec<DmxMapping>,
+}
+
+impl DmxMap {
+    pub fn from_json_path(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("read {}: {e}", path.display()))?;
+        DMX_MAP_SCHEMA.from_str(&text)
+    }
+
+    pub fn to_json(&self) -> String {
+        DMX_MAP_SCHEMA
+            .to_string_pretty(self)
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+
+    pub fn with(mut self, mapping: DmxMapping) -> Self {
+        self.mappings.push(mapping);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eight_and_sixteen_bit_reads() {
+        let data = [255, 0x80, 0x00, 7];
+        let m8 = DmxMapping::new(0, 1, DmxMode::Bit8, "a");
+        assert_eq!(m8.read(&data), Some(1.0));
+        let m16 = DmxMapping::new(0, 2, DmxMode::Bit16, "b");
+        assert_eq!(m16.read(&data), Some(0x8000 as f32 / 65535.0));
+        // Fine channel past the end of the universe.
+        assert_eq!(DmxMapping::new(0, 4, DmxMode::Bit16, "c").read(&data), None);
+        assert_eq!(DmxMapping::new(0, 0, DmxMode::Bit8, "d").read(&data), None);
+    }
+
+    #[test]
+    fn map_json_defaults() {
+        let map: DmxMap = serde_json::from_str(
+            r#"{"mappings":[{"universe":1,"channel":10,"param":"mix"},
+                            {"universe":1,"channel":11,"mode":"bit16","param":"gain","range":[0,2]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(map.mappings[0].mode, DmxMode::Bit8);
+        assert_eq!(map.mappings[1].range, Some((0.0, 2.0)));
+    }
+
+    #[test]
+    fn saved_maps_carry_the_schema_version() {
+        let map = DmxMap::default().with(DmxMapping::new(1, 10, DmxMode::Bit8, "mix"));
+        let json = map.to_json();
+        assert!(json.contains(r#""schema_version": 1"#), "{json}");
+        assert_eq!(DMX_MAP_SCHEMA.from_str::<DmxMap>(&json).unwrap(), map);
+        let future = json.replace(r#""schema_version": 1"#, r#""schema_version": 2"#);
+        assert!(DMX_MAP_SCHEMA.from_str::<DmxMap>(&future).is_err());
+    }
+}
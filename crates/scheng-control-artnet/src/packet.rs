@@ -0,0 +1,176 @@
+//! Art-Net (ArtDmx) and sACN (E1.31) packet parsing and building.
+
+/// One universe worth of DMX slots (without the start code), borrowed from the packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DmxFrame<'a> {
+    /// Art-Net 15-bit port address, or sACN universe number.
+    pub universe: u16,
+    pub data: &'a [u8],
+}
+
+pub const ARTNET_PORT: u16 = 6454;
+pub const SACN_PORT: u16 = 5568;
+
+const ARTNET_ID: &[u8; 8] = b"Art-Net\0";
+const OP_DMX: u16 = 0x5000;
+
+const ACN_ID: &[u8; 12] = b"ASC-E1.17\0\0\0";
+const VECTOR_ROOT_E131_DATA: u32 = 0x0000_0004;
+const VECTOR_E131_DATA_PACKET: u32 = 0x0000_0002;
+const SACN_DATA_OFFSET: usize = 126;
+
+/// Parse an ArtDmx packet. Other Art-Net opcodes (poll, sync, ...) return `None`.
+pub fn parse_artnet(buf: &[u8]) -> Option<DmxFrame<'_>> {
+    if buf.len() < 18 || &buf[..8] != ARTNET_ID {
+        return None;
+    }
+    if u16::from_le_bytes([buf[8], buf[9]]) != OP_DMX {
+        return None;
+    }
+    let sub_uni = buf[14] as u16;
+    let net = (buf[15] & 0x7f) as u16;
+    let len = u16::from_be_bytes([buf[16], buf[17]]) as usize;
+    let data = buf.get(18..18 + len.min(512))?;
+    Some(DmxFrame {
+        universe: (net << 8) | sub_uni,
+        data,
+    })
+}
+
+/// Parse an E1.31 data packet carrying DMX (start code 0).
+pub fn parse_sacn(buf: &[u8]) -> Option<DmxFrame<'_>> {
+    if buf.len() < SACN_DATA_OFFSET || &buf[4..16] != ACN_ID {
+        return None;
+    }
+    let be32 = |i: usize| u32::from_be_bytes([buf[i], buf[i + 1], buf[i + 2], buf[i + 3]]);
+    if be32(18) != VECTOR_ROOT_E131_DATA || be32(40) != VECTOR_E131_DATA_PACKET {
+        return None;
+    }
+    // Preview data is meant for visualisers, not output.
+    const OPT_PREVIEW: u8 = 0x80;
+    if buf[112] & OPT_PREVIEW != 0 || buf[125] != 0 {
+        return None;
+    }
+    let universe = u16::from_be_bytes([buf[113], buf[114]]);
+    let count = u16::from_be_bytes([buf[123], buf[124]]) as usize;
+    let slots = count.saturating_sub(1).min(512);
+    let data = buf.get(SACN_DATA_OFFSET..SACN_DATA_OFFSET + slots)?;
+    Some(DmxFrame { universe, data })
+}
+
+/// IPv4 multicast group for an sACN universe (239.255.hi.lo).
+pub fn sacn_multicast_addr(universe: u16) -> std::net::Ipv4Addr {
+    let [hi, lo] = universe.to_be_bytes();
+    std::net::Ipv4Addr::new(239, 255, hi, lo)
+}
+
+/// Build an ArtDmx packet. `data` is padded to an even length (2..=512 slots) as required.
+pub fn build_artnet(universe: u16, sequence: u8, data: &[u8]) -> Vec<u8> {
+    let data = &data[..data.len().min(512)];
+    let len = (data.len().max(2) + 1) & !1;
+    let mut p = Vec::with_capacity(18 + len);
+    p.extend_from_slice(ARTNET_ID);
+    p.extend_from_slice(&OP_DMX.to_le_bytes());
+    p.extend_from_slice(&14u16.to_be_bytes());
+    p.extend_from_slice(&[
+        sequence,
+        0,
+        (universe & 0xff) as u8,
+        ((universe >> 8) & 0x7f) as u8,
+    ]);
+    p.extend_from_slice(&(len as u16).to_be_bytes());
+    p.extend_from_slice(data);
+    p.resize(18 + len, 0);
+    p
+}
+
+/// Source identity for outgoing sACN packets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SacnSource {
+    /// Component identifier (a UUID; keep it stable across runs).
+    pub cid: [u8; 16],
+    /// Shown on receivers; truncated to 63 bytes.
+    pub name: String,
+    /// 0..=200, default 100.
+    pub priority: u8,
+}
+
+impl Default for SacnSource {
+    fn default() -> Self {
+        Self {
+            cid: *b"scheng-pixelmap\0",
+            name: "scheng".to_string(),
+            priority: 100,
+        }
+    }
+}
+
+/// Build an E1.31 data packet.
+pub fn build_sacn(universe: u16, sequence: u8, data: &[u8], source: &SacnSource) -> Vec<u8> {
+    let data = &data[..data.len().min(512)];
+    let total = SACN_DATA_OFFSET + data.len();
+    // Flags (0x7) in the top nibble, PDU length in the low 12 bits.
+    let flags_len = |from: usize| (0x7000 | (total - from) as u16).to_be_bytes();
+
+    let mut p = vec![0u8; total];
+    p[0..2].copy_from_slice(&0x0010u16.to_be_bytes());
+    p[4..16].copy_from_slice(ACN_ID);
+    p[16..18].copy_from_slice(&flags_len(16));
+    p[18..22].copy_from_slice(&VECTOR_ROOT_E131_DATA.to_be_bytes());
+    p[22..38].copy_from_slice(&source.cid);
+    p[38..40].copy_from_slice(&flags_len(38));
+    p[40..44].copy_from_slice(&VECTOR_E131_DATA_PACKET.to_be_bytes());
+    let name = source.name.as_bytes();
+    let n = name.len().min(63);
+    p[44..44 + n].copy_from_slice(&name[..n]);
+    p[108] = source.priority.min(200);
+    p[111] = sequence;
+    p[113..115].copy_from_slice(&universe.to_be_bytes());
+    p[115..117].copy_from_slice(&flags_len(115));
+    p[117] = 0x02;
+    p[118] = 0xa1;
+    p[121..123].copy_from_slice(&1u16.to_be_bytes());
+    p[123..125].copy_from_slice(&(data.len() as u16 + 1).to_be_bytes());
+    p[SACN_DATA_OFFSET..].copy_from_slice(data);
+    p
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn artnet_dmx_roundtrip() {
+        let p = build_artnet(0x0102, 0, &[1, 2, 3, 4]);
+        let f = parse_artnet(&p).unwrap();
+        assert_eq!(f.universe, 0x0102);
+        assert_eq!(f.data, &[1, 2, 3, 4]);
+
+        let mut poll = p.clone();
+        poll[8..10].copy_from_slice(&0x2000u16.to_le_bytes());
+        assert!(parse_artnet(&poll).is_none());
+        assert!(parse_artnet(&p[..p.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn artnet_pads_to_even_length() {
+        let p = build_artnet(3, 9, &[1, 2, 3]);
+        assert_eq!(u16::from_be_bytes([p[16], p[17]]), 4);
+        assert_eq!(parse_artnet(&p).unwrap().data, &[1, 2, 3, 0]);
+    }
+
+    #[test]
+    fn sacn_roundtrip_and_preview_filter() {
+        let mut p = build_sacn(7, 1, &[10, 20, 30], &SacnSource::default());
+        assert_eq!(
+            u16::from_be_bytes([p[16], p[17]]) & 0x0fff,
+            (p.len() - 16) as u16
+        );
+        let f = parse_sacn(&p).unwrap();
+        assert_eq!((f.universe, f.data), (7, &[10u8, 20, 30][..]));
+
+        p[112] = 0x80;
+        assert!(parse_sacn(&p).is_none());
+        assert_eq!(sacn_multicast_addr(0x0107).octets(), [239, 255, 1, 7]);
+    }
+}
@@ -0,0 +1,282 @@
+//! Pixel-to-fixture mapping: sample image positions and pack them into DMX universes.
+//!
+//! Positions are normalized (`0..1`, origin top-left) so a map survives output resolution
+//! changes. Each fixture occupies consecutive channels starting at `start_channel`; a pixel that
+//! would straddle the 512-slot boundary moves to channel 1 of the next universe, which is how
+//! LED pixel controllers expect strips longer than one universe (170 RGB pixels) to be patched.
+
+use std::collections::BTreeMap;
+
+use scheng_core::{JsonSchema, Migration};
+use serde::{Deserialize, Serialize};
+
+/// Channel layout of one fixture pixel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChannelOrder {
+    #[default]
+    Rgb,
+    Grb,
+    Brg,
+    /// RGB + white (white = min(r, g, b), subtracted from the colour channels).
+    Rgbw,
+    /// Single dimmer channel from Rec.709 luma.
+    Luma,
+}
+
+impl ChannelOrder {
+    pub fn channels(self) -> usize {
+        match self {
+            ChannelOrder::Rgb | ChannelOrder::Grb | ChannelOrder::Brg => 3,
+            ChannelOrder::Rgbw => 4,
+            ChannelOrder::Luma => 1,
+        }
+    }
+
+    fn encode(self, [r, g, b]: [u8; 3], out: &mut Vec<u8>) {
+        match self {
+            ChannelOrder::Rgb => out.extend([r, g, b]),
+            ChannelOrder::Grb => out.extend([g, r, b]),
+            ChannelOrder::Brg => out.extend([b, r, g]),
+            ChannelOrder::Rgbw => {
+                let w = r.min(g).min(b);
+                out.extend([r - w, g - w, b - w, w]);
+            }
+            ChannelOrder::Luma => {
+                let y = 0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32;
+                out.push(y.round().min(255.0) as u8);
+            }
+        }
+    }
+}
+
+/// Where a fixture samples the image.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FixtureShape {
+    /// Single pixel (par can, single-cell fixture).
+    Point { x: f32, y: f32 },
+    /// `pixels` evenly spaced samples from `from` to `to` inclusive (LED strip / bar).
+    Line {
+        from: (f32, f32),
+        to: (f32, f32),
+        pixels: u32,
+    },
+    /// `cols x rows` samples over a rectangle, row by row; `serpentine` reverses odd rows
+    /// (zig-zag wired matrices).
+    Grid {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+        cols: u32,
+        rows: u32,
+        #[serde(default)]
+        serpentine: bool,
+    },
+}
+
+impl FixtureShape {
+    /// Sample positions in patch order.
+    pub fn positions(&self) -> Vec<(f32, f32)> {
+        match *self {
+            FixtureShape::Point { x, y } => vec![(x, y)],
+            FixtureShape::Line { from, to, pixels } => {
+                let n = pixels.max(1);
+                (0..n)
+                    .map(|i| {
+                        let t = if n == 1 {
+                            0.5
+                        } else {
+                            i as f32 / (n - 1) as f32
+                        };
+                        (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+                    })
+                    .collect()
+            }
+            FixtureShape::Grid {
+                x,
+                y,
+                w,
+                h,
+                cols,
+                rows,
+                serpentine,
+            } => {
+                let (cols, rows) = (cols.max(1), rows.max(1));
+                let mut out = Vec::with_capacity((cols * rows) as usize);
+                for r in 0..rows {
+                    for c in 0..cols {
+                        let c = if serpentine && r % 2 == 1 {
+                            cols - 1 - c
+                        } else {
+                            c
+                        };
+                        // Cell centres.
+                        out.push((
+                            x + w * (c as f32 + 0.5) / cols as f32,
+                            y + h * (r as f32 + 0.5) / rows as f32,
+                        ));
+                    }
+                }
+                out
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fixture {
+    #[serde(default)]
+    pub name: String,
+    pub universe: u16,
+    /// 1-based start address.
+    pub start_channel: u16,
+    #[serde(default)]
+    pub order: ChannelOrder,
+    pub shape: FixtureShape,
+}
+
+/// Schema of pixel-map files.
+pub const PIXEL_MAP_SCHEMA: JsonSchema =
+    JsonSchema::new("pixel map", 1, &[Migration::unchanged(0)]);
+
+/// A full pixel-mapping patch. Load/save as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PixelMap {
+    pub fixtures: Vec<Fixture>,
+    /// Master dimmer applied to every channel.
+    #[serde(default = "one")]
+    pub brightness: f32,
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+impl Default for PixelMap {
+    fn default() -> Self {
+        Self {
+            fixtures: Vec::new(),
+            brightness: 1.0,
+        }
+    }
+}
+
+impl PixelMap {
+    pub fn from_json_path(path: impl AsRef<std::path::Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("read {}: {e}", path.display()))?;
+        PIXEL_MAP_SCHEMA.from_str(&text)
+    }
+
+    pub fn to_json(&self) -> String {
+        PIXEL_MAP_SCHEMA
+            .to_string_pretty(self)
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+
+    pub fn with(mut self, fixture: Fixture) -> Self {
+        self.fixtures.push(fixture);
+        self
+    }
+
+    /// Sample a tightly packed RGBA8 image and return 512-slot DMX data per universe.
+    ///
+    /// `bottom_up` is true for GL readbacks (row 0 = bottom).
+    pub fn render(&self, rgba: &[u8], w: i32, h: i32, bottom_up: bool) -> BTreeMap<u16, Vec<u8>> {
+        let mut universes: BTreeMap<u16, Vec<u8>> = BTreeMap::new();
+        let (w, h) = (w.max(0) as usize, h.max(0) as usize);
+        if w == 0 || h == 0 || rgba.len() < w * h * 4 {
+            return universes;
+        }
+        let dim = self.brightness.clamp(0.0, 1.0);
+        let mut px = Vec::with_capacity(4);
+
+        for f in &self.fixtures {
+            let per_px = f.order.channels();
+            let mut universe = f.universe;
+            let mut slot = f.start_channel.max(1) as usize - 1;
+
+            for (x, y) in f.shape.positions() {
+                let sx = ((x.clamp(0.0, 1.0) * w as f32) as usize).min(w - 1);
+                let sy = ((y.clamp(0.0, 1.0) * h as f32) as usize).min(h - 1);
+                let row = if bottom_up { h - 1 - sy } else { sy };
+                let i = (row * w + sx) * 4;
+                let rgb = [rgba[i], rgba[i + 1], rgba[i + 2]].map(|c| (c as f32 * dim) as u8);
+
+                if slot + per_px > 512 {
+                    universe = universe.wrapping_add(1);
+                    slot = 0;
+                }
+                px.clear();
+                f.order.encode(rgb, &mut px);
+                let data = universes.entry(universe).or_insert_with(|| vec![0; 512]);
+                data[slot..slot + per_px].copy_from_slice(&px);
+                slot += per_px;
+            }
+        }
+        universes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_spills_into_next_universe_without_splitting_pixels() {
+        let map = PixelMap::default().with(Fixture {
+            name: "strip".into(),
+            universe: 1,
+            start_channel: 508,
+            order: ChannelOrder::Rgb,
+            shape: FixtureShape::Line {
+                from: (0.0, 0.5),
+                to: (1.0, 0.5),
+                pixels: 3,
+            },
+        });
+        let img: Vec<u8> = [10, 20, 30, 255].repeat(4);
+        let u = map.render(&img, 2, 2, true);
+        // 508..510 holds pixel 0; pixel 1 needs 511..513 -> universe 2 from slot 0.
+        assert_eq!(&u[&1][507..511], &[10, 20, 30, 0]);
+        assert_eq!(&u[&2][0..6], &[10, 20, 30, 10, 20, 30]);
+    }
+
+    #[test]
+    fn orders_and_flip() {
+        // 1x2 image, GL order: bottom red, top blue.
+        let img = [255, 0, 0, 255, 0, 0, 255, 255];
+        let top = |order| {
+            let map = PixelMap::default().with(Fixture {
+                name: String::new(),
+                universe: 0,
+                start_channel: 1,
+                order,
+                shape: FixtureShape::Point { x: 0.5, y: 0.0 },
+            });
+            map.render(&img, 1, 2, true)[&0][..4].to_vec()
+        };
+        assert_eq!(top(ChannelOrder::Rgb)[..3], [0, 0, 255]);
+        assert_eq!(top(ChannelOrder::Brg)[..3], [255, 0, 0]);
+        assert_eq!(top(ChannelOrder::Luma)[0], 18);
+    }
+
+    #[test]
+    fn serpentine_grid_reverses_odd_rows() {
+        let g = FixtureShape::Grid {
+            x: 0.0,
+            y: 0.0,
+            w: 1.0,
+            h: 1.0,
+            cols: 2,
+            rows: 2,
+            serpentine: true,
+        };
+        let p = g.positions();
+        assert_eq!(p[1], (0.75, 0.25));
+        assert_eq!(p[2], (0.75, 0.75));
+    }
+}
@@ -0,0 +1,93 @@
+//! Outgoing Art-Net / sACN universes.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, SocketAddrV4, ToSocketAddrs, UdpSocket};
+
+use crate::packet::{build_artnet, build_sacn, sacn_multicast_addr, SacnSource, SACN_PORT};
+use crate::DmxProtocol;
+
+#[derive(Debug, Clone)]
+enum Destination {
+    Unicast(SocketAddr),
+    /// sACN: one multicast group per universe.
+    Multicast,
+}
+
+/// Sends DMX universes over UDP, keeping a per-universe sequence number.
+#[derive(Debug)]
+pub struct DmxSender {
+    sock: UdpSocket,
+    protocol: DmxProtocol,
+    dest: Destination,
+    source: SacnSource,
+    sequence: HashMap<u16, u8>,
+}
+
+impl DmxSender {
+    /// Art-Net to a node or broadcast address, e.g. "2.255.255.255:6454" or "10.0.0.20:6454".
+    pub fn artnet(target: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::new(DmxProtocol::ArtNet, Destination::Unicast(resolve(target)?))
+    }
+
+    /// sACN unicast to one receiver, e.g. "10.0.0.20:5568".
+    pub fn sacn_unicast(target: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::new(DmxProtocol::Sacn, Destination::Unicast(resolve(target)?))
+    }
+
+    /// sACN multicast (239.255.hi.lo:5568 per universe).
+    pub fn sacn_multicast() -> io::Result<Self> {
+        Self::new(DmxProtocol::Sacn, Destination::Multicast)
+    }
+
+    fn new(protocol: DmxProtocol, dest: Destination) -> io::Result<Self> {
+        let sock = UdpSocket::bind("0.0.0.0:0")?;
+        sock.set_broadcast(true)?;
+        sock.set_nonblocking(true)?;
+        Ok(Self {
+            sock,
+            protocol,
+            dest,
+            source: SacnSource::default(),
+            sequence: HashMap::new(),
+        })
+    }
+
+    /// sACN source name / CID / priority (ignored for Art-Net).
+    pub fn with_source(mut self, source: SacnSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn protocol(&self) -> DmxProtocol {
+        self.protocol
+    }
+
+    /// Send one universe. A full socket buffer drops the packet (DMX is resent every frame).
+    pub fn send(&mut self, universe: u16, data: &[u8]) -> io::Result<()> {
+        let seq = self.sequence.entry(universe).or_insert(0);
+        *seq = seq.wrapping_add(1).max(1);
+        let packet = match self.protocol {
+            DmxProtocol::ArtNet => build_artnet(universe, *seq, data),
+            DmxProtocol::Sacn => build_sacn(universe, *seq, data, &self.source),
+        };
+        let to = match &self.dest {
+            Destination::Unicast(addr) => *addr,
+            Destination::Multicast => {
+                SocketAddr::V4(SocketAddrV4::new(sacn_multicast_addr(universe), SACN_PORT))
+            }
+        };
+        match self.sock.send_to(&packet, to) {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn resolve(target: impl ToSocketAddrs) -> io::Result<SocketAddr> {
+    target
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no address"))
+}
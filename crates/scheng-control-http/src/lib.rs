@@ -0,0 +1,61 @@
+//! scheng-control-http
+//!
+//! HTTP + WebSocket remote control for browser panels and companion apps, as an alternative to
+//! OSC tooling. Exposes the parameter registry, banks/scenes and transport as JSON.
+//!
+//! Like `scheng-control-osc` it never owns engine state and never spawns threads: the host
+//! publishes a [`ControlSnapshot`] and applies the returned [`ControlCommand`]s itself. `poll` never
+//! blocks; slow requests are buffered per connection across calls.
+//!
+//! ```no_run
+//! use scheng_control_http::{ControlCommand, ControlSnapshot, HttpControlServer};
+//! use scheng_runtime::{BankSet, ParamRegistry};
+//!
+//! let mut params = ParamRegistry::new();
+//! let banks = BankSet::builtin_matrix_banks();
+//! let mut server = HttpControlServer::bind("127.0.0.1:8080")?;
+//!
+//! // In render loop:
+//! let snap = ControlSnapshot::new().with_params(&params).with_banks(&banks);
+//! for cmd in server.poll(&snap) {
+//!     match cmd {
+//!         ControlCommand::SetParam { name, value } => {
+//!             params.set(&name, value);
+//!         }
+//!         ControlCommand::RecallScene { bank, scene } => { /* apply banks.banks[bank].scenes[scene] */ }
+//!         ControlCommand::Transport { .. } => { /* play / pause / seek */ }
+//!     }
+//! }
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! REST routes (JSON; browser pages from other sites need `set_allowed_origins`):
+//! - `GET  /state`                 full snapshot
+//! - `GET  /params`, `GET /params/<name>`
+//! - `PUT|POST /params/<name>`     body `0.5` or `{"value":0.5}`
+//! - `GET  /scenes`                banks + active scene
+//! - `POST /scenes/<bank>/<scene>` recall by index
+//! - `GET  /transport`, `POST /transport` with a partial `{"playing","speed","position"}`
+//! - `POST /command`               any [`ControlCommand`]
+//!
+//! WebSocket clients connect to `/ws`, receive `{"type":"state",...}` on connect and on every
+//! change plus periodic `{"type":"stats",...}`, and send commands as JSON text.
+//!
+//! A read-only monitor page for phones lives at `/monitor`; see [`monitor`]. To reach it from
+//! other devices without exposing the control routes, serve it on its own listener with
+//! [`HttpControlServer::bind_monitor`].
+//!
+//! `GET /metrics` serves the engine telemetry last passed to
+//! [`HttpControlServer::publish_metrics`] in the Prometheus text format, for scraping by
+//! installation monitoring (see `scheng_runtime::telemetry`).
+
+pub mod monitor;
+pub mod protocol;
+mod routes;
+pub mod server;
+
+pub use monitor::{MonitorConfig, MonitorStats};
+pub use protocol::{
+    ActiveScene, BankInfo, ControlCommand, ControlSnapshot, ParamInfo, TransportInfo,
+};
+pub use server::HttpControlServer;
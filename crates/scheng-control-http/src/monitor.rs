@@ -0,0 +1,173 @@
+//! Read-only patch monitor: periodic stats and JPEG thumbnails for FOH / phone clients.
+//!
+//! Monitor clients connect to `/monitor/ws` (or open the built-in page at `/monitor`). They
+//! receive the same `state` messages as control clients, a `stats` message every
+//! `stats_interval`, and the preview thumbnail as a binary JPEG message whenever the host
+//! publishes one. Anything they send is rejected.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Built-in monitor page served at `GET /monitor`.
+pub(crate) const MONITOR_HTML: &str = include_str!("monitor.html");
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorConfig {
+    pub stats_interval: Duration,
+    /// Minimum spacing between thumbnails (see [`HttpControlServer::wants_thumbnail`](crate::HttpControlServer::wants_thumbnail)).
+    pub thumbnail_interval: Duration,
+    /// Thumbnails are downscaled to at most this width (aspect preserved).
+    pub thumbnail_width: u32,
+    /// JPEG quality 1..=100.
+    pub jpeg_quality: u8,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            stats_interval: Duration::from_millis(250),
+            thumbnail_interval: Duration::from_secs(1),
+            thumbnail_width: 320,
+            jpeg_quality: 60,
+        }
+    }
+}
+
+/// Periodic performance message: `{"type":"stats",...}`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MonitorStats {
+    /// Frames per second, measured from the `poll` cadence.
+    pub fps: f32,
+    /// Smoothed frame time in milliseconds.
+    pub frame_time_ms: f32,
+    /// Number of `poll` calls so far.
+    pub frame: u64,
+}
+
+/// Exponentially smoothed frame-rate estimate.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FrameMeter {
+    last: Option<Instant>,
+    avg_secs: f32,
+    frames: u64,
+}
+
+impl FrameMeter {
+    const SMOOTHING: f32 = 0.1;
+
+    pub fn tick(&mut self, now: Instant) {
+        if let Some(last) = self.last {
+            let dt = now.duration_since(last).as_secs_f32();
+            self.avg_secs = if self.avg_secs == 0.0 {
+                dt
+            } else {
+                self.avg_secs + (dt - self.avg_secs) * Self::SMOOTHING
+            };
+        }
+        self.last = Some(now);
+        self.frames += 1;
+    }
+
+    pub fn stats(&self) -> MonitorStats {
+        MonitorStats {
+            fps: if self.avg_secs > 0.0 {
+                1.0 / self.avg_secs
+            } else {
+                0.0
+            },
+            frame_time_ms: self.avg_secs * 1000.0,
+            frame: self.frames,
+        }
+    }
+}
+
+/// Box-filter an RGBA image down to at most `max_w` wide, returning top-down RGB.
+///
+/// `bottom_up` flips rows (GL readbacks start at the bottom).
+pub(crate) fn downscale_rgb(
+    rgba: &[u8],
+    w: u32,
+    h: u32,
+    bottom_up: bool,
+    max_w: u32,
+) -> Option<(Vec<u8>, u32, u32)> {
+    if w == 0 || h == 0 || rgba.len() < (w * h * 4) as usize {
+        return None;
+    }
+    let tw = w.min(max_w.max(1));
+    let th = ((h as u64 * tw as u64) / w as u64).max(1) as u32;
+
+    let mut out = Vec::with_capacity((tw * th * 3) as usize);
+    for ty in 0..th {
+        let y0 = ty * h / th;
+        let y1 = ((ty + 1) * h / th).max(y0 + 1);
+        for tx in 0..tw {
+            let x0 = tx * w / tw;
+            let x1 = ((tx + 1) * w / tw).max(x0 + 1);
+            let mut acc = [0u32; 3];
+            for y in y0..y1 {
+                let sy = if bottom_up { h - 1 - y } else { y };
+                for x in x0..x1 {
+                    let i = ((sy * w + x) * 4) as usize;
+                    acc[0] += rgba[i] as u32;
+                    acc[1] += rgba[i + 1] as u32;
+                    acc[2] += rgba[i + 2] as u32;
+                }
+            }
+            let n = (y1 - y0) * (x1 - x0);
+            out.extend(acc.iter().map(|c| (c / n) as u8));
+        }
+    }
+    Some((out, tw, th))
+}
+
+pub(crate) fn encode_jpeg(rgb: &[u8], w: u32, h: u32, quality: u8) -> Option<Vec<u8>> {
+    let (w, h) = (u16::try_from(w).ok()?, u16::try_from(h).ok()?);
+    let mut out = Vec::new();
+    jpeg_encoder::Encoder::new(&mut out, quality.clamp(1, 100))
+        .encode(rgb, w, h, jpeg_encoder::ColorType::Rgb)
+        .ok()?;
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn downscale_averages_and_flips() {
+        // 2x2: bottom row red, top row blue (GL order: row 0 = bottom).
+        let px = [
+            255, 0, 0, 255, 255, 0, 0, 255, //
+            0, 0, 255, 255, 0, 0, 255, 255,
+        ];
+        let (rgb, w, h) = downscale_rgb(&px, 2, 2, true, 2).unwrap();
+        assert_eq!((w, h), (2, 2));
+        assert_eq!(&rgb[..3], &[0, 0, 255]);
+
+        let (rgb, w, h) = downscale_rgb(&px, 2, 2, false, 1).unwrap();
+        assert_eq!((w, h), (1, 1));
+        assert_eq!(rgb, vec![127, 0, 127]);
+
+        assert!(downscale_rgb(&px, 4, 4, false, 2).is_none());
+    }
+
+    #[test]
+    fn jpeg_has_soi_marker() {
+        let jpg = encode_jpeg(&[128u8; 8 * 8 * 3], 8, 8, 50).unwrap();
+        assert_eq!(&jpg[..2], &[0xFF, 0xD8]);
+    }
+
+    #[test]
+    fn frame_meter_smooths_intervals() {
+        let mut m = FrameMeter::default();
+        let t0 = Instant::now();
+        for i in 0..10 {
+            m.tick(t0 + Duration::from_millis(20 * i));
+        }
+        let s = m.stats();
+        assert_eq!(s.frame, 10);
+        assert!((s.fps - 50.0).abs() < 0.5);
+    }
+}
@@ -0,0 +1,200 @@
+//! JSON message types shared by the HTTP routes and the WebSocket channel.
+
+use serde::{Deserialize, Serialize};
+
+use scheng_runtime::{BankSet, ParamRegistry};
+
+use crate::monitor::MonitorStats;
+
+/// A request from a remote client. The host applies these on its own thread/frame.
+///
+/// Wire format (WebSocket message or `POST /command` body):
+/// `{"type":"set_param","name":"node/3/gain","value":0.5}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ControlCommand {
+    SetParam {
+        name: String,
+        value: f32,
+    },
+    /// Recall scene `scene` of bank `bank` (indices into [`ControlSnapshot::banks`]).
+    RecallScene {
+        bank: usize,
+        scene: usize,
+    },
+    /// Partial transport update; absent fields are left unchanged.
+    Transport {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        playing: Option<bool>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        speed: Option<f32>,
+        /// Normalized position 0..1.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        position: Option<f32>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParamInfo {
+    pub name: String,
+    pub value: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<f32>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BankInfo {
+    pub name: String,
+    pub scenes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ActiveScene {
+    pub bank: usize,
+    pub scene: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TransportInfo {
+    pub playing: bool,
+    pub speed: f32,
+    pub position: f32,
+}
+
+impl Default for TransportInfo {
+    fn default() -> Self {
+        Self {
+            playing: true,
+            speed: 1.0,
+            position: 0.0,
+        }
+    }
+}
+
+/// What remote clients can see. The host rebuilds it (cheaply) before each `poll`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ControlSnapshot {
+    pub params: Vec<ParamInfo>,
+    pub banks: Vec<BankInfo>,
+    pub active_scene: Option<ActiveScene>,
+    pub transport: TransportInfo,
+}
+
+impl ControlSnapshot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy all parameters (and their metadata) from a registry.
+    pub fn with_params(mut self, params: &ParamRegistry) -> Self {
+        self.params = params
+            .iter()
+            .map(|(name, value)| {
+                let meta = params.meta(name);
+                ParamInfo {
+                    name: name.to_string(),
+                    value,
+                    min: meta.map(|m| m.min),
+                    max: meta.map(|m| m.max),
+                    default: meta.map(|m| m.default),
+                    description: meta.map(|m| m.description.clone()).unwrap_or_default(),
+                }
+            })
+            .collect();
+        self
+    }
+
+    pub fn with_banks(mut self, banks: &BankSet) -> Self {
+        self.banks = banks
+            .banks
+            .iter()
+            .map(|b| BankInfo {
+                name: b.name.clone(),
+                scenes: b.scenes.iter().map(|s| s.name.clone()).collect(),
+            })
+            .collect();
+        self
+    }
+
+    pub fn with_active_scene(mut self, bank: usize, scene: usize) -> Self {
+        self.active_scene = Some(ActiveScene { bank, scene });
+        self
+    }
+
+    pub fn with_transport(mut self, transport: TransportInfo) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    pub fn param(&self, name: &str) -> Option<&ParamInfo> {
+        self.params.iter().find(|p| p.name == name)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+/// Messages pushed to WebSocket clients.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ServerMessage<'a> {
+    /// Full state, sent on connect and whenever it changes.
+    State(&'a ControlSnapshot),
+    Stats(MonitorStats),
+    Error {
+        message: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_wire_format() {
+        let c: ControlCommand =
+            serde_json::from_str(r#"{"type":"set_param","name":"gain","value":0.5}"#).unwrap();
+        assert_eq!(
+            c,
+            ControlCommand::SetParam {
+                name: "gain".into(),
+                value: 0.5
+            }
+        );
+        let t: ControlCommand = serde_json::from_str(r#"{"type":"transport","speed":2}"#).unwrap();
+        assert_eq!(
+            t,
+            ControlCommand::Transport {
+                playing: None,
+                speed: Some(2.0),
+                position: None
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&ControlCommand::RecallScene { bank: 1, scene: 2 }).unwrap(),
+            r#"{"type":"recall_scene","bank":1,"scene":2}"#
+        );
+    }
+
+    #[test]
+    fn snapshot_copies_registry_meta() {
+        let mut reg = ParamRegistry::new();
+        reg.declare("mix", scheng_runtime::ParamMeta::new(0.0, 1.0, 0.25));
+        reg.set("free", 3.0);
+        let snap = ControlSnapshot::new()
+            .with_params(&reg)
+            .with_banks(&BankSet::builtin_matrix_banks());
+        assert_eq!(snap.param("mix").unwrap().max, Some(1.0));
+        assert_eq!(snap.param("free").unwrap().min, None);
+        assert_eq!(snap.banks[1].name, "DJ Cuts");
+        let json = serde_json::to_string(&ServerMessage::State(&snap)).unwrap();
+        assert!(json.starts_with(r#"{"type":"state","params":"#));
+    }
+}
@@ -0,0 +1,354 @@
+//! HTTP request parsing and the REST routes listed in the crate docs.
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::protocol::{ControlCommand, ControlSnapshot};
+
+/// Upper bound for request head + body.
+pub(crate) const MAX_REQUEST_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Request {
+    pub method: String,
+    pub path: String,
+    /// Header names lowercased.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn is_websocket_upgrade(&self) -> bool {
+        self.header("upgrade")
+            .is_some_and(|v| v.eq_ignore_ascii_case("websocket"))
+    }
+}
+
+/// Outcome of [`parse_request`] on a possibly partial buffer.
+#[derive(Debug)]
+pub(crate) enum Parsed {
+    /// Need more bytes.
+    Partial,
+    /// Complete request and the number of bytes it used.
+    Done(Request, usize),
+    Invalid,
+}
+
+pub(crate) fn parse_request(buf: &[u8]) -> Parsed {
+    let mut headers = [httparse::EMPTY_HEADER; 32];
+    let mut req = httparse::Request::new(&mut headers);
+    let head_len = match req.parse(buf) {
+        Ok(httparse::Status::Complete(n)) => n,
+        Ok(httparse::Status::Partial) => return Parsed::Partial,
+        Err(_) => return Parsed::Invalid,
+    };
+
+    let headers: Vec<(String, String)> = req
+        .headers
+        .iter()
+        .map(|h| {
+            (
+                h.name.to_ascii_lowercase(),
+                String::from_utf8_lossy(h.value).trim().to_string(),
+            )
+        })
+        .collect();
+    let content_len = headers
+        .iter()
+        .find(|(k, _)| k == "content-length")
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    if head_len + content_len > MAX_REQUEST_BYTES {
+        return Parsed::Invalid;
+    }
+    if buf.len() < head_len + content_len {
+        return Parsed::Partial;
+    }
+
+    let path = req.path.unwrap_or("/");
+    let path = path.split('?').next().unwrap_or(path);
+    Parsed::Done(
+        Request {
+            method: req.method.unwrap_or("GET").to_string(),
+            path: percent_decode(path),
+            headers,
+            body: buf[head_len..head_len + content_len].to_vec(),
+        },
+        head_len + content_len,
+    )
+}
+
+fn percent_decode(s: &str) -> String {
+    let b = s.as_bytes();
+    let mut out = Vec::with_capacity(b.len());
+    let mut i = 0;
+    while i < b.len() {
+        if b[i] == b'%' && i + 2 < b.len() {
+            let hex = std::str::from_utf8(&b[i + 1..i + 3]).ok();
+            if let Some(v) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(v);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(b[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: Vec<u8>,
+    /// Cross-site origin granted access through CORS headers.
+    pub allow_origin: Option<String>,
+}
+
+impl Response {
+    fn json(status: u16, body: serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: body.to_string().into_bytes(),
+            allow_origin: None,
+        }
+    }
+
+    pub fn bytes(content_type: &'static str, body: Vec<u8>) -> Self {
+        Self {
+            status: 200,
+            content_type,
+            body,
+            allow_origin: None,
+        }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Self::json(status, json!({ "error": message }))
+    }
+
+    fn without_body(mut self) -> Self {
+        self.body.clear();
+        self
+    }
+
+    fn accepted() -> Self {
+        Self::json(202, json!({ "ok": true }))
+    }
+
+    pub fn with_allow_origin(mut self, origin: Option<String>) -> Self {
+        self.allow_origin = origin;
+        self
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let reason = match self.status {
+            200 => "OK",
+            202 => "Accepted",
+            204 => "No Content",
+            400 => "Bad Request",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Error",
+        };
+        let cors = match &self.allow_origin {
+            Some(origin) => format!(
+                "Access-Control-Allow-Origin: {origin}\r\n\
+                 Access-Control-Allow-Methods: GET, POST, PUT, OPTIONS\r\n\
+                 Access-Control-Allow-Headers: Content-Type\r\n\
+                 Vary: Origin\r\n"
+            ),
+            None => String::new(),
+        };
+        let mut out = format!(
+            "HTTP/1.1 {} {reason}\r\n\
+             Content-Type: {}\r\n\
+             {cors}\
+             Content-Length: {}\r\n\
+             Connection: close\r\n\r\n",
+            self.status,
+            self.content_type,
+            self.body.len(),
+        )
+        .into_bytes();
+        out.extend_from_slice(&self.body);
+        out
+    }
+}
+
+/// Route one plain HTTP request. Mutating routes return a command for the host instead of
+/// touching any state themselves.
+pub(crate) fn route(req: &Request, snap: &ControlSnapshot) -> (Response, Option<ControlCommand>) {
+    let method = req.method.as_str();
+    if method == "OPTIONS" {
+        return (Response::json(204, json!(null)).without_body(), None);
+    }
+    let segments: Vec<&str> = req.path.trim_matches('/').splitn(2, '/').collect();
+    let get = method == "GET";
+    let set = method == "POST" || method == "PUT";
+
+    match (segments.as_slice(), get, set) {
+        (["state"], true, _) => (ok(snap), None),
+        (["params"], true, _) => (ok(&snap.params), None),
+        (["params", name], true, _) => match snap.param(name) {
+            Some(p) => (ok(p), None),
+            None => (Response::error(404, "unknown parameter"), None),
+        },
+        (["params", name], _, true) => match parse_value(&req.body) {
+            Some(value) => (
+                Response::accepted(),
+                Some(ControlCommand::SetParam {
+                    name: (*name).to_string(),
+                    value,
+                }),
+            ),
+            None => (
+                Response::error(400, "expected a number or {\"value\": number}"),
+                None,
+            ),
+        },
+        (["scenes"], true, _) => (
+            ok(&json!({ "banks": snap.banks, "active": snap.active_scene })),
+            None,
+        ),
+        (["scenes", rest], _, true) => match parse_scene_path(rest, snap) {
+            Ok(cmd) => (Response::accepted(), Some(cmd)),
+            Err(r) => (r, None),
+        },
+        (["transport"], true, _) => (ok(&snap.transport), None),
+        (["transport"], _, true) => {
+            #[derive(Deserialize)]
+            struct Body {
+                playing: Option<bool>,
+                speed: Option<f32>,
+                position: Option<f32>,
+            }
+            match serde_json::from_slice::<Body>(&req.body) {
+                Ok(b) => (
+                    Response::accepted(),
+                    Some(ControlCommand::Transport {
+                        playing: b.playing,
+                        speed: b.speed,
+                        position: b.position,
+                    }),
+                ),
+                Err(e) => (Response::error(400, &e.to_string()), None),
+            }
+        }
+        (["command"], _, true) => match serde_json::from_slice::<ControlCommand>(&req.body) {
+            Ok(cmd) => (Response::accepted(), Some(cmd)),
+            Err(e) => (Response::error(400, &e.to_string()), None),
+        },
+        (["state" | "params" | "scenes" | "transport" | "command"], _, _)
+        | (["params" | "scenes", _], _, _) => (Response::error(405, "method not allowed"), None),
+        _ => (Response::error(404, "not found"), None),
+    }
+}
+
+fn ok<T: serde::Serialize + ?Sized>(v: &T) -> Response {
+    let body = serde_json::to_vec(v).unwrap_or_else(|_| b"null".to_vec());
+    Response::bytes("application/json", body)
+}
+
+fn parse_value(body: &[u8]) -> Option<f32> {
+    match serde_json::from_slice::<serde_json::Value>(body).ok()? {
+        serde_json::Value::Number(n) => n.as_f64().map(|v| v as f32),
+        serde_json::Value::Object(o) => o.get("value")?.as_f64().map(|v| v as f32),
+        _ => None,
+    }
+}
+
+fn parse_scene_path(rest: &str, snap: &ControlSnapshot) -> Result<ControlCommand, Response> {
+    let mut it = rest.split('/');
+    let (Some(b), Some(s), None) = (it.next(), it.next(), it.next()) else {
+        return Err(Response::error(404, "expected /scenes/<bank>/<scene>"));
+    };
+    let (Ok(bank), Ok(scene)) = (b.parse::<usize>(), s.parse::<usize>()) else {
+        return Err(Response::error(400, "bank and scene must be indices"));
+    };
+    let exists = snap.banks.get(bank).is_some_and(|b| scene < b.scenes.len());
+    if !exists {
+        return Err(Response::error(404, "unknown scene"));
+    }
+    Ok(ControlCommand::RecallScene { bank, scene })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::BankInfo;
+
+    fn req(method: &str, path: &str, body: &str) -> Request {
+        let raw = format!(
+            "{method} {path} HTTP/1.1\r\nHost: x\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        match parse_request(raw.as_bytes()) {
+            Parsed::Done(r, n) => {
+                assert_eq!(n, raw.len());
+                r
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    fn snap() -> ControlSnapshot {
+        let mut reg = scheng_runtime::ParamRegistry::new();
+        reg.set("node/3/gain", 0.5);
+        let mut s = ControlSnapshot::new().with_params(&reg);
+        s.banks.push(BankInfo {
+            name: "A".into(),
+            scenes: vec!["one".into(), "two".into()],
+        });
+        s
+    }
+
+    #[test]
+    fn partial_body_waits_for_content_length() {
+        let raw = b"POST /params/x HTTP/1.1\r\nContent-Length: 4\r\n\r\n0.";
+        assert!(matches!(parse_request(raw), Parsed::Partial));
+        assert!(matches!(parse_request(b"GET /sta"), Parsed::Partial));
+    }
+
+    #[test]
+    fn param_routes() {
+        let s = snap();
+        let (r, c) = route(&req("GET", "/params/node/3/gain", ""), &s);
+        assert_eq!((r.status, c), (200, None));
+        assert!(String::from_utf8_lossy(&r.body).contains("\"value\":0.5"));
+
+        let (r, c) = route(&req("PUT", "/params/node%2F3%2Fgain", "{\"value\": 2}"), &s);
+        assert_eq!(r.status, 202);
+        assert_eq!(
+            c,
+            Some(ControlCommand::SetParam {
+                name: "node/3/gain".into(),
+                value: 2.0
+            })
+        );
+
+        assert_eq!(route(&req("POST", "/params/x", "\"a\""), &s).0.status, 400);
+        assert_eq!(route(&req("GET", "/params/missing", ""), &s).0.status, 404);
+        assert_eq!(route(&req("DELETE", "/params", ""), &s).0.status, 405);
+    }
+
+    #[test]
+    fn scene_recall_is_bounds_checked() {
+        let s = snap();
+        let (r, c) = route(&req("POST", "/scenes/0/1", ""), &s);
+        assert_eq!(r.status, 202);
+        assert_eq!(c, Some(ControlCommand::RecallScene { bank: 0, scene: 1 }));
+        assert_eq!(route(&req("POST", "/scenes/0/2", ""), &s).0.status, 404);
+        assert_eq!(route(&req("POST", "/scenes/a/b", ""), &s).0.status, 400);
+    }
+}
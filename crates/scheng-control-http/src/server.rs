@@ -0,0 +1,734 @@
+//! Non-blocking HTTP + WebSocket server, driven by `poll` from the host loop.
+
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+use scheng_runtime::Telemetry;
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::Role;
+use tungstenite::{Message, WebSocket};
+
+use crate::monitor::{
+    downscale_rgb, encode_jpeg, FrameMeter, MonitorConfig, MonitorStats, MONITOR_HTML,
+};
+use crate::protocol::{ControlCommand, ControlSnapshot, ServerMessage};
+use crate::routes::{parse_request, route, Parsed, Request, Response, MAX_REQUEST_BYTES};
+
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// How long an accepted connection may take to send its request before it is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Connections still sending a request or receiving a response; the oldest is dropped beyond
+/// this, so idle sockets cannot pile up.
+const MAX_PENDING: usize = 64;
+
+/// An accepted connection whose request is still arriving or whose response is still being
+/// written. Sockets stay non-blocking; `poll` advances each one as far as it can without waiting.
+#[derive(Debug)]
+struct Pending {
+    stream: TcpStream,
+    /// Request bytes received so far.
+    buf: Vec<u8>,
+    /// Response bytes not written yet (`Some` once the request was answered).
+    out: Option<Vec<u8>>,
+    accepted: Instant,
+    /// Accepted on the monitor listener, so only `/monitor` routes are served.
+    monitor_only: bool,
+}
+
+#[derive(Debug)]
+struct Client {
+    ws: WebSocket<TcpStream>,
+    /// Read-only monitor connection (`/monitor/ws`).
+    monitor: bool,
+}
+
+/// HTTP + WebSocket remote control endpoint.
+///
+/// Nothing runs in the background: call [`poll`](Self::poll) once per frame with the current
+/// [`ControlSnapshot`]. It serves pending HTTP requests, reads WebSocket messages, pushes the
+/// snapshot to WebSocket clients when it changed, and returns the commands clients sent.
+///
+/// Browsers let any web page send requests to a local server, so requests carrying an `Origin`
+/// from another site are refused with 403 (including WebSocket upgrades) unless that origin is
+/// in [`set_allowed_origins`](Self::set_allowed_origins). Only allowed origins get CORS headers.
+/// Clients that send no `Origin` (curl, native apps) and pages served by this server are not
+/// affected.
+#[derive(Debug)]
+pub struct HttpControlServer {
+    listener: TcpListener,
+    /// Listener from `bind_monitor`, serving only the read-only monitor.
+    monitor_listener: Option<TcpListener>,
+    pending: Vec<Pending>,
+    clients: Vec<Client>,
+    /// Last state message pushed to WebSocket clients.
+    last_state: String,
+    monitor: MonitorConfig,
+    meter: FrameMeter,
+    last_stats: Option<Instant>,
+    last_thumbnail: Option<Instant>,
+    thumbnail: Option<Vec<u8>>,
+    /// Prometheus text from the last `publish_metrics`.
+    metrics: Option<String>,
+    /// Cross-site origins allowed to send requests.
+    allowed_origins: Vec<String>,
+}
+
+impl HttpControlServer {
+    /// Bind to an address like "127.0.0.1:8080" (non-blocking).
+    pub fn bind(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            monitor_listener: None,
+            pending: Vec::new(),
+            clients: Vec::new(),
+            last_state: String::new(),
+            monitor: MonitorConfig::default(),
+            meter: FrameMeter::default(),
+            last_stats: None,
+            last_thumbnail: None,
+            thumbnail: None,
+            metrics: None,
+            allowed_origins: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Also listen on `addr` for the monitor alone. Connections there get `/monitor`,
+    /// `/monitor/ws`, `/monitor/stats` and `/monitor/thumbnail.jpg`; every control route,
+    /// including `/ws`, is refused with 403. Use it to expose the monitor to the venue network
+    /// while the control API stays on loopback.
+    pub fn bind_monitor(&mut self, addr: &str) -> io::Result<()> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        self.monitor_listener = Some(listener);
+        Ok(())
+    }
+
+    /// Address of the [`bind_monitor`](Self::bind_monitor) listener, if any.
+    pub fn monitor_addr(&self) -> Option<SocketAddr> {
+        self.monitor_listener.as_ref()?.local_addr().ok()
+    }
+
+    /// Connected WebSocket clients (control and monitor).
+    pub fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn monitor_config(&self) -> &MonitorConfig {
+        &self.monitor
+    }
+
+    pub fn set_monitor_config(&mut self, cfg: MonitorConfig) {
+        self.monitor = cfg;
+    }
+
+    pub fn allowed_origins(&self) -> &[String] {
+        &self.allowed_origins
+    }
+
+    /// Origins (like `"http://localhost:3000"`) of other sites allowed to use the API from a
+    /// browser; `"*"` allows every site. Empty by default.
+    pub fn set_allowed_origins(&mut self, origins: Vec<String>) {
+        self.allowed_origins = origins;
+    }
+
+    /// Check a request's `Origin`: `Ok(None)` for same-site or non-browser requests,
+    /// `Ok(Some(origin))` for an allowed cross-site origin, `Err` otherwise.
+    ///
+    /// Same-site means the origin names `local`, the address the connection arrived on. The
+    /// `Host` header proves nothing: a DNS-rebinding page sends its own name in both headers.
+    fn check_origin(
+        &self,
+        req: &Request,
+        local: Option<SocketAddr>,
+    ) -> Result<Option<String>, Response> {
+        let Some(origin) = req.header("origin") else {
+            return Ok(None);
+        };
+        if local.is_some_and(|local| origin_is_local(origin, local)) {
+            return Ok(None);
+        }
+        if self
+            .allowed_origins
+            .iter()
+            .any(|o| o == "*" || o.eq_ignore_ascii_case(origin))
+        {
+            return Ok(Some(origin.to_string()));
+        }
+        Err(Response::error(403, "origin not allowed"))
+    }
+
+    /// Latest frame-rate stats (measured from `poll` calls).
+    pub fn stats(&self) -> MonitorStats {
+        self.meter.stats()
+    }
+
+    /// True when a monitor client is connected and the thumbnail interval has elapsed.
+    ///
+    /// Lets the host skip the GPU readback when nobody is watching.
+    pub fn wants_thumbnail(&self) -> bool {
+        self.clients.iter().any(|c| c.monitor)
+            && self
+                .last_thumbnail
+                .is_none_or(|t| t.elapsed() >= self.monitor.thumbnail_interval)
+    }
+
+    /// Downscale + JPEG-encode a preview frame and send it to monitor clients.
+    ///
+    /// `rgba` is `width * height * 4` bytes; pass `bottom_up = true` for GL readbacks (e.g.
+    /// `ReadbackSink::last`). The latest thumbnail is also served at `/monitor/thumbnail.jpg`.
+    pub fn publish_thumbnail(&mut self, width: u32, height: u32, rgba: &[u8], bottom_up: bool) {
+        self.last_thumbnail = Some(Instant::now());
+        let Some((rgb, w, h)) =
+            downscale_rgb(rgba, width, height, bottom_up, self.monitor.thumbnail_width)
+        else {
+            return;
+        };
+        let Some(jpg) = encode_jpeg(&rgb, w, h, self.monitor.jpeg_quality) else {
+            return;
+        };
+        self.clients
+            .retain_mut(|c| !c.monitor || send(&mut c.ws, Message::Binary(jpg.clone())));
+        self.thumbnail = Some(jpg);
+    }
+
+    /// Publish engine metrics for `GET /metrics` (Prometheus text format).
+    ///
+    /// Scrapes are rare, so once a second or so is plenty; requests before the first publish
+    /// get a 404.
+    pub fn publish_metrics(&mut self, telemetry: &Telemetry) {
+        self.metrics = Some(telemetry.to_prometheus());
+    }
+
+    /// Serve everything pending right now and return the received commands in arrival order.
+    ///
+    /// Never waits: sockets are non-blocking, and a request or response that does not fit in
+    /// one call is carried over to the next poll. Connections that take longer than five seconds
+    /// are dropped.
+    pub fn poll(&mut self, snapshot: &ControlSnapshot) -> Vec<ControlCommand> {
+        let now = Instant::now();
+        self.meter.tick(now);
+        let mut out = Vec::new();
+
+        let listeners = std::iter::once((&self.listener, false))
+            .chain(self.monitor_listener.iter().map(|l| (l, true)));
+        for (listener, monitor_only) in listeners {
+            while let Ok((stream, _)) = listener.accept() {
+                if stream.set_nonblocking(true).is_err() {
+                    continue;
+                }
+                if self.pending.len() >= MAX_PENDING {
+                    self.pending.remove(0);
+                }
+                self.pending.push(Pending {
+                    stream,
+                    buf: Vec::new(),
+                    out: None,
+                    accepted: now,
+                    monitor_only,
+                });
+            }
+        }
+        for conn in std::mem::take(&mut self.pending) {
+            if let Some(conn) = self.advance(conn, snapshot, &mut out) {
+                self.pending.push(conn);
+            }
+        }
+
+        self.clients.retain_mut(|c| read_client(c, &mut out));
+
+        let state = state_message(snapshot);
+        if state != self.last_state {
+            self.clients
+                .retain_mut(|c| send(&mut c.ws, Message::Text(state.clone())));
+            self.last_state = state;
+        }
+
+        let stats_due = self
+            .last_stats
+            .is_none_or(|t| now.duration_since(t) >= self.monitor.stats_interval);
+        if stats_due && !self.clients.is_empty() {
+            let msg = serde_json::to_string(&ServerMessage::Stats(self.meter.stats()))
+                .unwrap_or_default();
+            self.clients
+                .retain_mut(|c| send(&mut c.ws, Message::Text(msg.clone())));
+            self.last_stats = Some(now);
+        }
+
+        // Flush queued frames (e.g. pongs, partial writes).
+        self.clients.retain_mut(|c| match c.ws.flush() {
+            Ok(()) => true,
+            Err(tungstenite::Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => true,
+            Err(_) => false,
+        });
+
+        out
+    }
+
+    /// Read what has arrived on `conn` and answer or upgrade it once the request is complete.
+    /// Returns the connection while it still waits for bytes or has a response to finish.
+    fn advance(
+        &mut self,
+        mut conn: Pending,
+        snapshot: &ControlSnapshot,
+        out: &mut Vec<ControlCommand>,
+    ) -> Option<Pending> {
+        if conn.out.is_some() {
+            return flush_response(conn);
+        }
+        let mut chunk = [0u8; 4096];
+        loop {
+            match conn.stream.read(&mut chunk) {
+                Ok(0) => return None,
+                Ok(n) => conn.buf.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => return None,
+            }
+            if conn.buf.len() > MAX_REQUEST_BYTES {
+                break;
+            }
+        }
+        let resp = match parse_request(&conn.buf) {
+            Parsed::Partial if conn.buf.len() < MAX_REQUEST_BYTES => {
+                return (conn.accepted.elapsed() < REQUEST_TIMEOUT).then_some(conn);
+            }
+            Parsed::Done(req, used) => {
+                let leftover = conn.buf.split_off(used);
+                let monitor_only = conn.monitor_only;
+                match self.handle_request(conn.stream, req, leftover, monitor_only, snapshot, out) {
+                    Handled::Upgraded(client) => {
+                        self.clients.push(*client);
+                        return None;
+                    }
+                    Handled::Closed => return None,
+                    Handled::Respond(stream, resp) => {
+                        conn.stream = stream;
+                        resp
+                    }
+                }
+            }
+            _ => Response::error(400, "bad request"),
+        };
+        conn.out = Some(resp.to_bytes());
+        flush_response(conn)
+    }
+
+    /// Answer a complete request, or upgrade it to a WebSocket client.
+    fn handle_request(
+        &self,
+        mut stream: TcpStream,
+        req: Request,
+        leftover: Vec<u8>,
+        monitor_only: bool,
+        snapshot: &ControlSnapshot,
+        out: &mut Vec<ControlCommand>,
+    ) -> Handled {
+        let cors = match self.check_origin(&req, stream.local_addr().ok()) {
+            Ok(cors) => cors,
+            Err(resp) => return Handled::Respond(stream, resp),
+        };
+        let path = req.path.trim_end_matches('/');
+        if monitor_only && path != "/monitor" && !path.starts_with("/monitor/") {
+            let resp = Response::error(403, "monitor listener is read-only");
+            return Handled::Respond(stream, resp);
+        }
+
+        if req.is_websocket_upgrade() && (path == "/ws" || path == "/monitor/ws") {
+            let Some(key) = req.header("sec-websocket-key") else {
+                return Handled::Respond(stream, Response::error(400, "missing websocket key"));
+            };
+            let head = format!(
+                "HTTP/1.1 101 Switching Protocols\r\n\
+                 Upgrade: websocket\r\n\
+                 Connection: Upgrade\r\n\
+                 Sec-WebSocket-Accept: {}\r\n\r\n",
+                derive_accept_key(key.as_bytes())
+            );
+            // A fresh socket's send buffer always takes the short handshake reply.
+            if stream.write_all(head.as_bytes()).is_err() {
+                return Handled::Closed;
+            }
+            let mut ws = WebSocket::from_partially_read(stream, leftover, Role::Server, None);
+            if !send(&mut ws, Message::Text(state_message(snapshot))) {
+                return Handled::Closed;
+            }
+            return Handled::Upgraded(Box::new(Client {
+                ws,
+                monitor: path == "/monitor/ws",
+            }));
+        }
+
+        let resp = match (req.method.as_str(), path) {
+            ("GET", "/monitor") => Response::bytes("text/html; charset=utf-8", MONITOR_HTML.into()),
+            ("GET", "/monitor/thumbnail.jpg") => match &self.thumbnail {
+                Some(jpg) => Response::bytes("image/jpeg", jpg.clone()),
+                None => Response::error(404, "no thumbnail yet"),
+            },
+            ("GET", "/monitor/stats") => Response::bytes(
+                "application/json",
+                serde_json::to_vec(&self.meter.stats()).unwrap_or_default(),
+            ),
+            ("GET", "/metrics") => match &self.metrics {
+                Some(text) => Response::bytes(PROMETHEUS_CONTENT_TYPE, text.clone().into_bytes()),
+                None => Response::error(404, "no metrics published"),
+            },
+            _ => {
+                let (resp, cmd) = route(&req, snapshot);
+                out.extend(cmd);
+                resp
+            }
+        };
+        Handled::Respond(stream, resp.with_allow_origin(cors))
+    }
+}
+
+/// What became of a complete request.
+enum Handled {
+    Respond(TcpStream, Response),
+    Upgraded(Box<Client>),
+    Closed,
+}
+
+/// Write as much of `conn`'s response as the socket takes. Returns the connection while bytes
+/// remain; dropping it closes the socket.
+/// Whether `origin` (`scheme://host[:port]`) is `local` itself: its IP address, or `localhost`
+/// when `local` is a loopback address.
+fn origin_is_local(origin: &str, local: SocketAddr) -> bool {
+    let (authority, default_port) = if let Some(a) = origin.strip_prefix("http://") {
+        (a, 80)
+    } else if let Some(a) = origin.strip_prefix("https://") {
+        (a, 443)
+    } else {
+        return false;
+    };
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.ends_with(']') => match port.parse() {
+            Ok(port) => (host, port),
+            Err(_) => return false,
+        },
+        _ => (authority, default_port),
+    };
+    if port != local.port() {
+        return false;
+    }
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match host.parse::<IpAddr>() {
+        Ok(ip) => ip.to_canonical() == local.ip().to_canonical(),
+        Err(_) => host.eq_ignore_ascii_case("localhost") && local.ip().is_loopback(),
+    }
+}
+
+fn flush_response(mut conn: Pending) -> Option<Pending> {
+    let out = conn.out.as_mut()?;
+    while !out.is_empty() {
+        match conn.stream.write(out) {
+            Ok(0) => return None,
+            Ok(n) => {
+                out.drain(..n);
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                return (conn.accepted.elapsed() < REQUEST_TIMEOUT).then_some(conn);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+fn state_message(snapshot: &ControlSnapshot) -> String {
+    serde_json::to_string(&ServerMessage::State(snapshot)).unwrap_or_default()
+}
+
+/// Drain a client's messages. Returns false when the client is gone.
+fn read_client(client: &mut Client, out: &mut Vec<ControlCommand>) -> bool {
+    loop {
+        let reply = match client.ws.read() {
+            Ok(Message::Text(_)) if client.monitor => {
+                "monitor connections are read-only".to_string()
+            }
+            Ok(Message::Text(text)) => match serde_json::from_str::<ControlCommand>(&text) {
+                Ok(cmd) => {
+                    out.push(cmd);
+                    continue;
+                }
+                Err(e) => e.to_string(),
+            },
+            Ok(Message::Close(_)) => return false,
+            Ok(_) => continue,
+            Err(tungstenite::Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => return true,
+            Err(_) => return false,
+        };
+        let msg =
+            serde_json::to_string(&ServerMessage::Error { message: reply }).unwrap_or_default();
+        if !send(&mut client.ws, Message::Text(msg)) {
+            return false;
+        }
+    }
+}
+
+/// Queue + flush a message. WouldBlock leaves it queued for the next poll.
+fn send(ws: &mut WebSocket<TcpStream>, msg: Message) -> bool {
+    match ws.send(msg) {
+        Ok(()) => true,
+        Err(tungstenite::Error::Io(e)) if e.kind() == io::ErrorKind::WouldBlock => true,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loopback_put_param_becomes_command() {
+        let mut server = HttpControlServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut s = TcpStream::connect(addr).unwrap();
+            s.write_all(b"PUT /params/gain HTTP/1.1\r\nContent-Length: 4\r\n\r\n0.75")
+                .unwrap();
+            let mut resp = String::new();
+            s.read_to_string(&mut resp).unwrap();
+            resp
+        });
+
+        let snap = ControlSnapshot::new();
+        let mut cmds = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while cmds.is_empty() && Instant::now() < deadline {
+            cmds = server.poll(&snap);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(
+            cmds,
+            vec![ControlCommand::SetParam {
+                name: "gain".into(),
+                value: 0.75
+            }]
+        );
+        assert!(client.join().unwrap().starts_with("HTTP/1.1 202"));
+    }
+
+    #[test]
+    fn slow_requests_are_buffered_across_polls() {
+        let mut server = HttpControlServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let snap = ControlSnapshot::new();
+        let _idle = TcpStream::connect(addr).unwrap();
+        let mut slow = TcpStream::connect(addr).unwrap();
+        slow.write_all(b"PUT /params/gain HTTP/1.1\r\nContent-Le")
+            .unwrap();
+
+        // Neither the silent nor the half-sent connection may stall the frame.
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while server.pending.len() < 2 && Instant::now() < deadline {
+            let started = Instant::now();
+            assert!(server.poll(&snap).is_empty());
+            assert!(started.elapsed() < Duration::from_millis(50));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(server.pending.len(), 2);
+
+        slow.write_all(b"ngth: 3\r\n\r\n0.5").unwrap();
+        let mut cmds = Vec::new();
+        while cmds.is_empty() && Instant::now() < deadline {
+            cmds = server.poll(&snap);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(
+            cmds,
+            vec![ControlCommand::SetParam {
+                name: "gain".into(),
+                value: 0.5
+            }]
+        );
+        let mut resp = String::new();
+        slow.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 202"));
+        assert_eq!(server.pending.len(), 1);
+    }
+
+    /// Send raw `request` bytes to the control listener and return the response and commands.
+    fn exchange(server: &mut HttpControlServer, request: String) -> (String, Vec<ControlCommand>) {
+        let addr = server.local_addr().unwrap();
+        exchange_with(server, addr, request)
+    }
+
+    /// [`exchange`] with the listener at `addr`, polling `server` meanwhile.
+    fn exchange_with(
+        server: &mut HttpControlServer,
+        addr: SocketAddr,
+        request: String,
+    ) -> (String, Vec<ControlCommand>) {
+        let client = std::thread::spawn(move || {
+            let mut s = TcpStream::connect(addr).unwrap();
+            s.write_all(request.as_bytes()).unwrap();
+            let mut resp = String::new();
+            s.read_to_string(&mut resp).unwrap();
+            resp
+        });
+        let snap = ControlSnapshot::new();
+        let mut cmds = Vec::new();
+        while !client.is_finished() {
+            cmds.extend(server.poll(&snap));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        (client.join().unwrap(), cmds)
+    }
+
+    #[test]
+    fn cross_site_requests_need_an_allowed_origin() {
+        let mut server = HttpControlServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let put = |origin: &str| {
+            format!(
+                "PUT /params/gain HTTP/1.1\r\nHost: {addr}\r\nOrigin: {origin}\r\n\
+                 Content-Length: 3\r\n\r\n0.5"
+            )
+        };
+        let upgrade = format!(
+            "GET /ws HTTP/1.1\r\nHost: {addr}\r\nOrigin: http://evil.example\r\n\
+             Connection: Upgrade\r\nUpgrade: websocket\r\nSec-WebSocket-Version: 13\r\n\
+             Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n"
+        );
+
+        let (resp, cmds) = exchange(&mut server, put("http://evil.example"));
+        assert!(resp.starts_with("HTTP/1.1 403"));
+        assert!(cmds.is_empty());
+        let (resp, _) = exchange(&mut server, upgrade);
+        assert!(resp.starts_with("HTTP/1.1 403"));
+        assert_eq!(server.client_count(), 0);
+
+        // Pages served from this server are same-site and get no CORS headers.
+        let port = addr.port();
+        for origin in [format!("http://{addr}"), format!("http://localhost:{port}")] {
+            let (resp, cmds) = exchange(&mut server, put(&origin));
+            assert!(resp.starts_with("HTTP/1.1 202"), "{origin}: {resp}");
+            assert!(!resp.contains("Access-Control-Allow-Origin"));
+            assert_eq!(cmds.len(), 1);
+        }
+
+        // A DNS-rebinding page names itself in both headers; only the bound address counts.
+        let rebound = format!(
+            "PUT /params/gain HTTP/1.1\r\nHost: evil.test:{port}\r\n\
+             Origin: http://evil.test:{port}\r\nContent-Length: 3\r\n\r\n0.5"
+        );
+        let (resp, cmds) = exchange(&mut server, rebound);
+        assert!(resp.starts_with("HTTP/1.1 403"));
+        assert!(cmds.is_empty());
+
+        server.set_allowed_origins(vec!["http://panel.example".into()]);
+        let (resp, cmds) = exchange(&mut server, put("http://panel.example"));
+        assert!(resp.starts_with("HTTP/1.1 202"));
+        assert!(resp.contains("Access-Control-Allow-Origin: http://panel.example\r\n"));
+        assert_eq!(cmds.len(), 1);
+        let (resp, _) = exchange(&mut server, put("http://evil.example"));
+        assert!(resp.starts_with("HTTP/1.1 403"));
+    }
+
+    #[test]
+    fn origin_is_local_matches_the_address_not_a_name() {
+        let v4: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+        let v6: SocketAddr = "[::1]:80".parse().unwrap();
+        let lan: SocketAddr = "192.168.1.5:8080".parse().unwrap();
+        assert!(origin_is_local("http://127.0.0.1:8080", v4));
+        assert!(origin_is_local("http://LOCALHOST:8080", v4));
+        assert!(origin_is_local("http://[::1]", v6));
+        assert!(origin_is_local("http://localhost", v6));
+        assert!(origin_is_local("http://192.168.1.5:8080", lan));
+        assert!(!origin_is_local("http://localhost:8080", lan));
+        assert!(!origin_is_local("http://127.0.0.1:8081", v4));
+        assert!(!origin_is_local("https://127.0.0.1", v4));
+        assert!(!origin_is_local("http://evil.test:8080", v4));
+        assert!(!origin_is_local("null", v4));
+    }
+
+    #[test]
+    fn monitor_listener_refuses_control_routes() {
+        let mut server = HttpControlServer::bind("127.0.0.1:0").unwrap();
+        server.bind_monitor("127.0.0.1:0").unwrap();
+        let monitor = server.monitor_addr().unwrap();
+        let upgrade = "GET /ws HTTP/1.1\r\nConnection: Upgrade\r\nUpgrade: websocket\r\n\
+                       Sec-WebSocket-Version: 13\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\r\n";
+        for refused in [
+            "PUT /params/gain HTTP/1.1\r\nContent-Length: 3\r\n\r\n0.5",
+            "GET /state HTTP/1.1\r\n\r\n",
+            upgrade,
+        ] {
+            let (resp, cmds) = exchange_with(&mut server, monitor, refused.into());
+            assert!(resp.starts_with("HTTP/1.1 403"), "{refused}");
+            assert!(cmds.is_empty());
+        }
+        assert_eq!(server.client_count(), 0);
+        let (resp, _) = exchange_with(&mut server, monitor, "GET /monitor HTTP/1.1\r\n\r\n".into());
+        assert!(resp.starts_with("HTTP/1.1 200"));
+        // The control listener is unaffected.
+        let (resp, _) = exchange(&mut server, "GET /state HTTP/1.1\r\n\r\n".into());
+        assert!(resp.starts_with("HTTP/1.1 200"));
+    }
+
+    #[test]
+    fn metrics_endpoint_serves_published_telemetry() {
+        let mut server = HttpControlServer::bind("127.0.0.1:0").unwrap();
+        let mut telemetry = Telemetry::new();
+        telemetry.record_frame(Duration::from_millis(16));
+        server.publish_metrics(&telemetry);
+        let addr = server.local_addr().unwrap();
+        let client = std::thread::spawn(move || {
+            let mut s = TcpStream::connect(addr).unwrap();
+            s.write_all(b"GET /metrics HTTP/1.1\r\n\r\n").unwrap();
+            let mut resp = String::new();
+            s.read_to_string(&mut resp).unwrap();
+            resp
+        });
+
+        let snap = ControlSnapshot::new();
+        while !client.is_finished() {
+            server.poll(&snap);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let resp = client.join().unwrap();
+        assert!(resp.starts_with("HTTP/1.1 200"));
+        assert!(resp.contains("text/plain; version=0.0.4"));
+        assert!(resp.contains("scheng_frames_total 1\n"));
+    }
+
+    #[test]
+    fn monitor_socket_gets_state_and_thumbnail() {
+        let mut server = HttpControlServer::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}/monitor/ws", server.local_addr().unwrap());
+        let client = std::thread::spawn(move || {
+            let (mut ws, _) = tungstenite::connect(url).unwrap();
+            let mut kinds = Vec::new();
+            while kinds.len() < 2 {
+                match ws.read().unwrap() {
+                    Message::Text(t) if t.starts_with(r#"{"type":"state""#) => kinds.push("state"),
+                    Message::Binary(b) if b.starts_with(&[0xFF, 0xD8]) => kinds.push("jpeg"),
+                    _ => {}
+                }
+            }
+            kinds
+        });
+
+        let snap = ControlSnapshot::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !server.wants_thumbnail() && Instant::now() < deadline {
+            server.poll(&snap);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        server.publish_thumbnail(4, 4, &[200u8; 64], true);
+        server.poll(&snap);
+        assert!(!server.wants_thumbnail());
+        assert_eq!(client.join().unwrap(), vec!["state", "jpeg"]);
+    }
+}
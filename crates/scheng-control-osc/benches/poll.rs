@@ -0,0 +1,55 @@
+//! Decoding cost of `OscParamReceiver::poll` for typical controller traffic.
+//!
+//! Each iteration sends the packets over loopback and drains them, so the numbers include two
+//! syscalls per packet. `cargo bench -p scheng-control-osc`
+
+use std::net::UdpSocket;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rosc::{encoder, OscBundle, OscMessage, OscPacket, OscTime, OscType};
+use scheng_control_osc::OscParamReceiver;
+
+fn message(i: usize) -> OscPacket {
+    OscPacket::Message(OscMessage {
+        addr: format!("/param/fader{i}"),
+        args: vec![OscType::Float(i as f32 / 100.0)],
+    })
+}
+
+fn poll(c: &mut Criterion) {
+    let mut rx = OscParamReceiver::bind("127.0.0.1:0").unwrap();
+    let tx = UdpSocket::bind("127.0.0.1:0").unwrap();
+    tx.connect(rx.local_addr().unwrap()).unwrap();
+
+    let mut group = c.benchmark_group("osc_poll");
+    for n in [1usize, 16, 64] {
+        let singles: Vec<Vec<u8>> = (0..n)
+            .map(|i| encoder::encode(&message(i)).unwrap())
+            .collect();
+        let bundle = encoder::encode(&OscPacket::Bundle(OscBundle {
+            timetag: OscTime::from((0, 1)),
+            content: (0..n).map(message).collect(),
+        }))
+        .unwrap();
+
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::new("messages", n), &singles, |b, packets| {
+            b.iter(|| {
+                for p in packets {
+                    tx.send(p).unwrap();
+                }
+                black_box(rx.poll())
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("bundle", n), &bundle, |b, bundle| {
+            b.iter(|| {
+                tx.send(bundle).unwrap();
+                black_box(rx.poll())
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, poll);
+criterion_main!(benches);
@@ -0,0 +1,286 @@
+//! Controller layout generation from a [`ParamRegistry`].
+//!
+//! Only *declared* parameters (those with [`ParamMeta`](scheng_runtime::ParamMeta)) are
+//! exported: they are the performance controls with a known range. Values published by analysis
+//! nodes are undeclared and skipped.
+//!
+//! Two outputs:
+//! - [`OscMap`]: generic JSON (`address`, `min`, `max`, `default` per parameter) for custom
+//!   apps or controller scripts.
+//! - [`touchosc_xml`] / [`write_touchosc`]: a TouchOSC (mk2) `.tosc` layout with one labelled
+//!   fader per parameter. Faders send `/param/<name>` with the value already scaled to the
+//!   parameter range, which is exactly what [`OscParamReceiver`](crate::OscParamReceiver) reads.
+
+use std::fmt::Write as _;
+use std::io::{self, Write as _};
+use std::path::Path;
+
+use scheng_core::{JsonSchema, Migration};
+use serde::{Deserialize, Serialize};
+
+use scheng_runtime::ParamRegistry;
+
+/// One exported control.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OscMapEntry {
+    pub name: String,
+    pub address: String,
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+}
+
+/// Schema of OSC map files.
+pub const OSC_MAP_SCHEMA: JsonSchema = JsonSchema::new("osc map", 1, &[Migration::unchanged(0)]);
+
+/// Generic OSC map: every declared parameter with its address and range.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OscMap {
+    pub params: Vec<OscMapEntry>,
+}
+
+impl OscMap {
+    /// Build from all declared parameters, addressed as `<prefix><name>` (e.g. `/param/`).
+    pub fn from_registry(reg: &ParamRegistry, prefix: &str) -> Self {
+        let params = reg
+            .iter()
+            .filter_map(|(name, _)| {
+                let meta = reg.meta(name)?;
+                Some(OscMapEntry {
+                    name: name.to_string(),
+                    address: format!("{prefix}{name}"),
+                    min: meta.min,
+                    max: meta.max,
+                    default: meta.default,
+                    description: meta.description.clone(),
+                })
+            })
+            .collect();
+        Self { params }
+    }
+
+    /// Pretty JSON stamped with the current [`OSC_MAP_SCHEMA`] version.
+    pub fn to_json(&self) -> String {
+        OSC_MAP_SCHEMA
+            .to_string_pretty(self)
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Read a map written by [`to_json`](Self::to_json) (older versions are migrated).
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        OSC_MAP_SCHEMA.from_str(text)
+    }
+}
+
+/// Page geometry for the generated TouchOSC layout.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutConfig {
+    pub width: u32,
+    pub height: u32,
+    pub columns: u32,
+    /// Preferred row height; rows shrink to fit the page.
+    pub row_height: u32,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            width: 1024,
+            height: 768,
+            columns: 2,
+            row_height: 64,
+        }
+    }
+}
+
+/// TouchOSC mk2 layout XML (uncompressed) for `map`.
+pub fn touchosc_xml(map: &OscMap, cfg: &LayoutConfig) -> String {
+    let n = map.params.len() as u32;
+    let cols = cfg.columns.max(1);
+    let rows = n.div_ceil(cols).max(1);
+    let row_h = cfg.row_height.min(cfg.height / rows).max(16);
+    let col_w = cfg.width / cols;
+    let pad = 4;
+
+    let mut children = String::new();
+    for (i, p) in map.params.iter().enumerate() {
+        let (col, row) = (i as u32 % cols, i as u32 / cols);
+        let (x, y) = (col * col_w, row * row_h);
+        let label_w = col_w * 3 / 10;
+
+        let label = Frame {
+            x: x + pad,
+            y: y + pad,
+            w: label_w.saturating_sub(pad),
+            h: row_h - 2 * pad,
+        };
+        let fader = Frame {
+            x: x + label_w + pad,
+            y: y + pad,
+            w: (col_w - label_w).saturating_sub(2 * pad),
+            h: row_h - 2 * pad,
+        };
+        let norm_default = if p.max != p.min {
+            ((p.default - p.min) / (p.max - p.min)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        write_label(&mut children, 2 * i, &p.name, label);
+        write_fader(&mut children, 2 * i + 1, p, fader, norm_default);
+    }
+
+    let mut xml = String::new();
+    let _ = write!(
+        xml,
+        "<?xml version='1.0' encoding='UTF-8'?>\
+         <lexml version='3'><node ID='{}' type='GROUP'><properties>{}{}</properties>\
+         <values></values><messages></messages><children>{children}</children></node></lexml>",
+        node_id(usize::MAX),
+        prop_frame(Frame {
+            x: 0,
+            y: 0,
+            w: cfg.width,
+            h: cfg.height,
+        }),
+        prop_s("name", "scheng"),
+    );
+    xml
+}
+
+/// Write a compressed `.tosc` file (zlib-deflated XML) for `map`.
+pub fn write_touchosc(path: impl AsRef<Path>, map: &OscMap, cfg: &LayoutConfig) -> io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    let mut enc = flate2::write::ZlibEncoder::new(file, flate2::Compression::default());
+    enc.write_all(touchosc_xml(map, cfg).as_bytes())?;
+    enc.finish()?.flush()
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    x: u32,
+    y: u32,
+    w: u32,
+    h: u32,
+}
+
+/// Stable, UUID-shaped node id.
+fn node_id(i: usize) -> String {
+    format!(
+        "5c4e0000-0000-4000-8000-{:012x}",
+        i as u64 & 0xffff_ffff_ffff
+    )
+}
+
+fn cdata(s: &str) -> String {
+    format!("<![CDATA[{}]]>", s.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+fn prop_s(key: &str, value: &str) -> String {
+    format!(
+        "<property type='s'><key>{}</key><value>{}</value></property>",
+        cdata(key),
+        cdata(value)
+    )
+}
+
+fn prop_b(key: &str, value: bool) -> String {
+    format!(
+        "<property type='b'><key>{}</key><value>{}</value></property>",
+        cdata(key),
+        u8::from(value)
+    )
+}
+
+fn prop_frame(f: Frame) -> String {
+    format!(
+        "<property type='r'><key>{}</key><value><x>{}</x><y>{}</y><w>{}</w><h>{}</h></value></property>",
+        cdata("frame"),
+        f.x,
+        f.y,
+        f.w,
+        f.h
+    )
+}
+
+fn write_label(out: &mut String, id: usize, text: &str, frame: Frame) {
+    let _ = write!(
+        out,
+        "<node ID='{}' type='LABEL'><properties>{}{}{}</properties>\
+         <values><value><key>{}</key><locked>1</locked><lockedDefaultCurrent>1</lockedDefaultCurrent>\
+         <default>{}</default><defaultPull>0</defaultPull></value></values></node>",
+        node_id(id),
+        prop_frame(frame),
+        prop_s("name", &format!("label_{id}")),
+        prop_b("interactive", false),
+        cdata("text"),
+        cdata(text),
+    );
+}
+
+fn write_fader(out: &mut String, id: usize, p: &OscMapEntry, frame: Frame, norm_default: f32) {
+    let _ = write!(
+        out,
+        "<node ID='{}' type='FADER'><properties>{}{}{}</properties>\
+         <values><value><key>{}</key><locked>0</locked><lockedDefaultCurrent>0</lockedDefaultCurrent>\
+         <default>{norm_default}</default><defaultPull>0</defaultPull></value></values>\
+         <messages><osc><enabled>1</enabled><send>1</send><receive>1</receive><feedback>0</feedback>\
+         <connections>00001</connections>\
+         <triggers><trigger><var>{}</var><condition>ANY</condition></trigger></triggers>\
+         <path><partial><type>CONSTANT</type><conversion>STRING</conversion><value>{}</value>\
+         <scaleMin>0</scaleMin><scaleMax>1</scaleMax></partial></path>\
+         <arguments><partial><type>VALUE</type><conversion>FLOAT</conversion><value>{}</value>\
+         <scaleMin>{}</scaleMin><scaleMax>{}</scaleMax></partial></arguments></osc></messages></node>",
+        node_id(id),
+        prop_frame(frame),
+        prop_s("name", &p.name),
+        prop_b("horizontal", true),
+        cdata("x"),
+        cdata("x"),
+        cdata(&p.address),
+        cdata("x"),
+        p.min,
+        p.max,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scheng_runtime::ParamMeta;
+
+    fn registry() -> ParamRegistry {
+        let mut reg = ParamRegistry::new();
+        reg.declare("node/2/gain", ParamMeta::new(0.0, 2.0, 1.0));
+        reg.declare(
+            "mix",
+            ParamMeta::new(-1.0, 1.0, 0.0).with_description("A/B"),
+        );
+        reg.set("node/5/energy", 0.3); // analysis output: no meta
+        reg
+    }
+
+    #[test]
+    fn map_exports_only_declared_params() {
+        let map = OscMap::from_registry(&registry(), "/param/");
+        let names: Vec<_> = map.params.iter().map(|p| p.address.as_str()).collect();
+        assert_eq!(names, ["/param/mix", "/param/node/2/gain"]);
+        assert_eq!(map.params[1].max, 2.0);
+        let back = OscMap::from_json(&map.to_json()).unwrap();
+        assert_eq!(back, map);
+    }
+
+    #[test]
+    fn touchosc_faders_scale_to_range() {
+        let map = OscMap::from_registry(&registry(), "/param/");
+        let xml = touchosc_xml(&map, &LayoutConfig::default());
+        assert_eq!(xml.matches("type='FADER'").count(), 2);
+        assert!(xml.contains("<![CDATA[/param/node/2/gain]]>"));
+        assert!(xml.contains("<scaleMin>0</scaleMin><scaleMax>2</scaleMax>"));
+        // mix default 0 in -1..1 -> fader at the middle.
+        assert!(xml.contains("<default>0.5</default>"));
+        assert_eq!(cdata("a]]>b"), "<![CDATA[a]]]]><![CDATA[>b]]>");
+    }
+}
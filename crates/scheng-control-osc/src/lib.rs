@@ -3,26 +3,60 @@
 //! Minimal OSC control-plane helper used by scheng examples.
 //!
 //! This crate intentionally stays tiny: it only knows how to receive OSC packets
-//! over UDP and extract simple (path, f32) parameter updates.
+//! over UDP (or TCP / unix sockets, see [`stream`]) and extract simple (path, f32) parameter
+//! updates. The [`layout`] module goes the other way and generates controller layouts for the
+//! same addresses.
 //!
 //! rosc 0.10.x API note:
 //! - `rosc::decoder::decode_udp` returns `Result<(&[u8], OscPacket), _>` (nom-style),
 //!   where the first tuple element is the *unconsumed remainder* of the buffer.
 
+pub mod layout;
+pub mod stream;
+
 use std::io;
-use std::net::UdpSocket;
+use std::net::{SocketAddr, UdpSocket};
 
 use rosc::{OscPacket, OscType};
 
+pub use layout::{touchosc_xml, write_touchosc, LayoutConfig, OscMap, OscMapEntry, OSC_MAP_SCHEMA};
+pub use stream::{Framing, OscStreamReceiver};
+
+/// Largest possible UDP payload; a receive buffer this size never truncates.
+pub const MAX_DATAGRAM: usize = 65536;
+
+/// Counters kept by [`OscParamReceiver`] since bind (or the last [`reset_stats`]).
+///
+/// [`reset_stats`]: OscParamReceiver::reset_stats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OscStats {
+    /// Datagrams (or stream frames) received.
+    pub packets: u64,
+    /// Parameter updates extracted.
+    pub params: u64,
+    /// Packets dropped for size: UDP datagrams that filled the buffer (possibly truncated), or
+    /// stream packets over the size limit.
+    pub truncated: u64,
+    /// Packets that were not valid OSC.
+    pub decode_errors: u64,
+    /// Messages that did not match the parameter convention.
+    pub ignored: u64,
+}
+
 /// Non-blocking UDP OSC receiver that extracts parameter messages.
 ///
 /// Convention:
 /// - Address: "/param/<name>" or "/<name>"
 /// - Value: first argument, coercible to f32 (Float, Double, Int, Long)
+///
+/// The receive buffer defaults to [`MAX_DATAGRAM`], so large bundles from control surfaces
+/// arrive whole. A smaller buffer (see [`with_buffer_size`](Self::with_buffer_size)) doubles
+/// whenever a datagram fills it; that datagram is lost and counted in [`OscStats::truncated`].
 #[derive(Debug)]
 pub struct OscParamReceiver {
     sock: UdpSocket,
-    buf: [u8; 2048],
+    buf: Vec<u8>,
+    stats: OscStats,
 }
 
 impl OscParamReceiver {
@@ -32,23 +66,57 @@ impl OscParamReceiver {
         sock.set_nonblocking(true)?;
         Ok(Self {
             sock,
-            buf: [0u8; 2048],
+            buf: vec![0u8; MAX_DATAGRAM],
+            stats: OscStats::default(),
         })
     }
 
-    /// Poll the socket and return all parameter updates available right now.
+    /// Initial receive buffer size in bytes (clamped to `64..=MAX_DATAGRAM`).
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.buf = vec![0u8; size.clamp(64, MAX_DATAGRAM)];
+        self
+    }
+
+    pub fn buffer_size(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.sock.local_addr()
+    }
+
+    pub fn stats(&self) -> OscStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = OscStats::default();
+    }
+
+    /// Poll the socket and return all numeric parameter updates available right now (see
+    /// [`OscValue::as_f32`]; other values are dropped).
     ///
     /// This never blocks; it drains the UDP socket until `WouldBlock`.
     pub fn poll(&mut self) -> Vec<(String, f32)> {
-        let mut out: Vec<(String, f32)> = Vec::new();
+        scalars(self.poll_values())
+    }
+
+    /// Like [`poll`](Self::poll), but returns every update with its typed value.
+    pub fn poll_values(&mut self) -> Vec<(String, OscValue)> {
+        let mut out: Vec<(String, OscValue)> = Vec::new();
 
         loop {
             match self.sock.recv_from(&mut self.buf) {
                 Ok((n, _from)) => {
-                    // decode_udp is nom-style: Ok((rest, packet))
-                    if let Ok((_rest, pkt)) = rosc::decoder::decode_udp(&self.buf[..n]) {
-                        extract_from_packet(pkt, &mut out);
+                    self.stats.packets += 1;
+                    // UDP drops whatever does not fit; a full buffer means we cannot tell.
+                    if n == self.buf.len() && n < MAX_DATAGRAM {
+                        self.stats.truncated += 1;
+                        let grown = (self.buf.len() * 2).min(MAX_DATAGRAM);
+                        self.buf.resize(grown, 0);
+                        continue;
                     }
+                    decode_into(&self.buf[..n], &mut out, &mut self.stats);
                 }
                 Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
                 Err(_e) => break, // ignore transient socket errors for now
@@ -59,32 +127,276 @@ impl OscParamReceiver {
     }
 }
 
-/// Walk a packet/bundle tree and push parsed param messages into `out`.
-fn extract_from_packet(pkt: OscPacket, out: &mut Vec<(String, f32)>) {
+/// A parameter value as sent by the controller.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscValue {
+    /// A single Float or Double.
+    F32(f32),
+    /// Several numeric arguments (vec2/vec3, XY pads) or an OSC color (RGBA in `0..=1`).
+    F32s(Vec<f32>),
+    /// A single Int or Long.
+    Int(i64),
+    /// A String or Symbol (file paths, scene names).
+    Str(String),
+    /// True/False.
+    Bool(bool),
+}
+
+impl OscValue {
+    /// Scalar view used by [`OscParamReceiver::poll`]: numbers as-is, bools as 0/1, multi-value
+    /// messages by their first component; strings have none.
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            OscValue::F32(x) => Some(*x),
+            OscValue::F32s(v) => v.first().copied(),
+            OscValue::Int(x) => Some(*x as f32),
+            OscValue::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            OscValue::Str(_) => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            OscValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// All numeric components (a scalar gives one).
+    pub fn to_f32s(&self) -> Option<Vec<f32>> {
+        match self {
+            OscValue::F32s(v) => Some(v.clone()),
+            other => other.as_f32().map(|x| vec![x]),
+        }
+    }
+}
+
+pub(crate) fn scalars(values: Vec<(String, OscValue)>) -> Vec<(String, f32)> {
+    values
+        .into_iter()
+        .filter_map(|(name, v)| Some((name, v.as_f32()?)))
+        .collect()
+}
+
+/// Decode one OSC packet and push its param messages into `out`, updating `stats` (except
+/// `packets`, which the transport counts).
+pub(crate) fn decode_into(bytes: &[u8], out: &mut Vec<(String, OscValue)>, stats: &mut OscStats) {
+    // decode_udp is nom-style: Ok((rest, packet))
+    match rosc::decoder::decode_udp(bytes) {
+        Ok((_rest, pkt)) => {
+            let before = out.len();
+            stats.ignored += extract_from_packet(pkt, out);
+            stats.params += (out.len() - before) as u64;
+        }
+        Err(_) => stats.decode_errors += 1,
+    }
+}
+
+/// Walk a packet/bundle tree and push parsed param messages into `out`. Returns the number of
+/// messages that did not parse as params.
+fn extract_from_packet(pkt: OscPacket, out: &mut Vec<(String, OscValue)>) -> u64 {
     match pkt {
-        OscPacket::Message(m) => {
-            if let Some(kv) = parse_param_message(&m.addr, &m.args) {
+        OscPacket::Message(m) => match parse_param_message(&m.addr, &m.args) {
+            Some(kv) => {
                 out.push(kv);
+                0
             }
-        }
-        OscPacket::Bundle(b) => {
-            for p in b.content {
-                extract_from_packet(p, out);
-            }
-        }
+            None => 1,
+        },
+        OscPacket::Bundle(b) => b
+            .content
+            .into_iter()
+            .map(|p| extract_from_packet(p, out))
+            .sum(),
     }
 }
 
 /// Parse a message into a `(name, value)` pair if it matches our convention.
-fn parse_param_message(addr: &str, args: &[OscType]) -> Option<(String, f32)> {
-    let name = addr.strip_prefix("/param/").or_else(|| addr.strip_prefix('/'))?;
-    let v0 = args.first()?;
-    let v = match *v0 {
-        OscType::Float(x) => x,
-        OscType::Double(x) => x as f32,
-        OscType::Int(x) => x as f32,
-        OscType::Long(x) => x as f32,
+///
+/// Several arguments that are all numeric become [`OscValue::F32s`]; otherwise the first
+/// argument is the value (so `"/scene", "intro", 2` yields `Str("intro")`).
+fn parse_param_message(addr: &str, args: &[OscType]) -> Option<(String, OscValue)> {
+    let name = addr
+        .strip_prefix("/param/")
+        .or_else(|| addr.strip_prefix('/'))?;
+    let numeric = |a: &OscType| match *a {
+        OscType::Float(x) => Some(x),
+        OscType::Double(x) => Some(x as f32),
+        OscType::Int(x) => Some(x as f32),
+        OscType::Long(x) => Some(x as f32),
+        _ => None,
+    };
+    if args.len() > 1 {
+        if let Some(v) = args.iter().map(numeric).collect::<Option<Vec<f32>>>() {
+            return Some((name.to_string(), OscValue::F32s(v)));
+        }
+    }
+    let v = match args.first()? {
+        OscType::Float(x) => OscValue::F32(*x),
+        OscType::Double(x) => OscValue::F32(*x as f32),
+        OscType::Int(x) => OscValue::Int(*x as i64),
+        OscType::Long(x) => OscValue::Int(*x),
+        OscType::String(s) => OscValue::Str(s.clone()),
+        OscType::Bool(b) => OscValue::Bool(*b),
+        OscType::Color(c) => OscValue::F32s(
+            [c.red, c.green, c.blue, c.alpha]
+                .iter()
+                .map(|&x| x as f32 / 255.0)
+                .collect(),
+        ),
         _ => return None,
     };
     Some((name.to_string(), v))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosc::{OscBundle, OscMessage, OscTime};
+    use std::time::{Duration, Instant};
+
+    fn bundle(n: usize) -> Vec<u8> {
+        let content = (0..n)
+            .map(|i| {
+                OscPacket::Message(OscMessage {
+                    addr: format!("/param/node/{i}/gain"),
+                    args: vec![OscType::Float(i as f32)],
+                })
+            })
+            .chain(std::iter::once(OscPacket::Message(OscMessage {
+                addr: "/transport/play".to_string(),
+                args: vec![],
+            })))
+            .collect();
+        let pkt = OscPacket::Bundle(OscBundle {
+            timetag: OscTime::from((0, 1)),
+            content,
+        });
+        rosc::encoder::encode(&pkt).unwrap()
+    }
+
+    fn send_and_poll(rx: &mut OscParamReceiver, bytes: &[u8]) -> Vec<(String, f32)> {
+        scalars(send_and_poll_values(rx, bytes))
+    }
+
+    fn send_and_poll_values(rx: &mut OscParamReceiver, bytes: &[u8]) -> Vec<(String, OscValue)> {
+        let tx = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tx.send_to(bytes, rx.local_addr().unwrap()).unwrap();
+        let before = rx.stats().packets;
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut out = Vec::new();
+        while rx.stats().packets == before && Instant::now() < deadline {
+            out.extend(rx.poll_values());
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        out
+    }
+
+    fn parse(args: Vec<OscType>) -> Option<OscValue> {
+        parse_param_message("/param/x", &args).map(|(_, v)| v)
+    }
+
+    #[test]
+    fn typed_values() {
+        assert_eq!(parse(vec![OscType::Double(0.5)]), Some(OscValue::F32(0.5)));
+        assert_eq!(parse(vec![OscType::Long(7)]), Some(OscValue::Int(7)));
+        assert_eq!(parse(vec![OscType::Bool(true)]), Some(OscValue::Bool(true)));
+        assert_eq!(
+            parse(vec![OscType::String("clips/a.mov".into())]),
+            Some(OscValue::Str("clips/a.mov".into()))
+        );
+        assert_eq!(
+            parse(vec![
+                OscType::Float(0.25),
+                OscType::Int(1),
+                OscType::Double(2.0)
+            ]),
+            Some(OscValue::F32s(vec![0.25, 1.0, 2.0]))
+        );
+        // Mixed arguments: the first one wins.
+        assert_eq!(
+            parse(vec![OscType::String("intro".into()), OscType::Int(2)]),
+            Some(OscValue::Str("intro".into()))
+        );
+        let color = OscType::Color(rosc::OscColor {
+            red: 255,
+            green: 0,
+            blue: 51,
+            alpha: 255,
+        });
+        assert_eq!(
+            parse(vec![color]),
+            Some(OscValue::F32s(vec![1.0, 0.0, 0.2, 1.0]))
+        );
+        assert_eq!(parse(vec![]), None);
+        assert_eq!(parse(vec![OscType::Nil]), None);
+
+        assert_eq!(OscValue::Bool(true).as_f32(), Some(1.0));
+        assert_eq!(OscValue::Str("a".into()).as_f32(), None);
+        assert_eq!(OscValue::Int(3).to_f32s(), Some(vec![3.0]));
+    }
+
+    #[test]
+    fn poll_keeps_scalars_and_poll_values_keeps_everything() {
+        let pkt = OscPacket::Bundle(OscBundle {
+            timetag: OscTime::from((0, 1)),
+            content: vec![
+                OscPacket::Message(OscMessage {
+                    addr: "/param/scene".to_string(),
+                    args: vec![OscType::String("outro".into())],
+                }),
+                OscPacket::Message(OscMessage {
+                    addr: "/param/xy".to_string(),
+                    args: vec![OscType::Float(0.1), OscType::Float(0.9)],
+                }),
+            ],
+        });
+        let bytes = rosc::encoder::encode(&pkt).unwrap();
+
+        let mut rx = OscParamReceiver::bind("127.0.0.1:0").unwrap();
+        let values = send_and_poll_values(&mut rx, &bytes);
+        assert_eq!(
+            values,
+            [
+                ("scene".to_string(), OscValue::Str("outro".into())),
+                ("xy".to_string(), OscValue::F32s(vec![0.1, 0.9])),
+            ]
+        );
+        assert_eq!(send_and_poll(&mut rx, &bytes), [("xy".to_string(), 0.1)]);
+    }
+
+    #[test]
+    fn large_bundle_arrives_whole() {
+        let bytes = bundle(400);
+        assert!(bytes.len() > 4 * 2048, "well past the old 2048-byte buffer");
+
+        let mut rx = OscParamReceiver::bind("127.0.0.1:0").unwrap();
+        let got = send_and_poll(&mut rx, &bytes);
+        assert_eq!(got.len(), 400);
+        assert_eq!(got[399], ("node/399/gain".to_string(), 399.0));
+        let stats = rx.stats();
+        assert_eq!((stats.packets, stats.params, stats.ignored), (1, 400, 1));
+        assert_eq!(stats.truncated, 0);
+    }
+
+    #[test]
+    fn small_buffer_counts_truncation_and_grows() {
+        let bytes = bundle(100);
+        let mut rx = OscParamReceiver::bind("127.0.0.1:0")
+            .unwrap()
+            .with_buffer_size(1024);
+
+        assert!(send_and_poll(&mut rx, &bytes).is_empty());
+        assert_eq!(rx.stats().truncated, 1);
+        assert_eq!(rx.buffer_size(), 2048);
+
+        while rx.buffer_size() <= bytes.len() {
+            send_and_poll(&mut rx, &bytes);
+        }
+        assert_eq!(send_and_poll(&mut rx, &bytes).len(), 100);
+
+        rx.reset_stats();
+        send_and_poll(&mut rx, b"not osc");
+        assert_eq!(rx.stats().decode_errors, 1);
+    }
+}
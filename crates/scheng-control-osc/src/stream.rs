@@ -0,0 +1,433 @@
+//! OSC over stream transports (TCP, unix-domain sockets).
+//!
+//! Streams have no datagram boundaries, so packets are framed. OSC 1.0 prefixes each packet with
+//! its length as a big-endian int32; OSC 1.1 uses SLIP (RFC 1055) with `END` bytes on both
+//! sides. [`OscStreamReceiver`] accepts any number of connections and exposes the same
+//! non-blocking `poll()` / `poll_values()` as the UDP receiver.
+
+use std::io::{self, Read};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+
+use crate::{decode_into, scalars, OscStats, OscValue};
+
+const SLIP_END: u8 = 0xC0;
+const SLIP_ESC: u8 = 0xDB;
+const SLIP_ESC_END: u8 = 0xDC;
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+/// How packets are delimited on the stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Framing {
+    /// OSC 1.1: SLIP-encoded, `END` (0xC0) before and after each packet.
+    #[default]
+    Slip,
+    /// OSC 1.0: big-endian int32 byte count, then the packet.
+    LengthPrefixed,
+}
+
+impl Framing {
+    /// Frame one encoded OSC packet for sending.
+    pub fn encode(self, packet: &[u8]) -> Vec<u8> {
+        match self {
+            Framing::LengthPrefixed => {
+                let mut out = Vec::with_capacity(packet.len() + 4);
+                out.extend_from_slice(&(packet.len() as u32).to_be_bytes());
+                out.extend_from_slice(packet);
+                out
+            }
+            Framing::Slip => {
+                let mut out = Vec::with_capacity(packet.len() + 2);
+                out.push(SLIP_END);
+                for &b in packet {
+                    match b {
+                        SLIP_END => out.extend_from_slice(&[SLIP_ESC, SLIP_ESC_END]),
+                        SLIP_ESC => out.extend_from_slice(&[SLIP_ESC, SLIP_ESC_ESC]),
+                        _ => out.push(b),
+                    }
+                }
+                out.push(SLIP_END);
+                out
+            }
+        }
+    }
+}
+
+/// Incremental deframer for one connection.
+#[derive(Debug, Default)]
+struct Deframer {
+    buf: Vec<u8>,
+    escaped: bool,
+    /// Skipping the rest of an oversized SLIP frame.
+    discarding: bool,
+}
+
+/// Why a connection must be dropped.
+#[derive(Debug, PartialEq, Eq)]
+struct Oversized;
+
+impl Deframer {
+    /// Feed received bytes; calls `emit` once per complete packet.
+    fn feed(
+        &mut self,
+        framing: Framing,
+        bytes: &[u8],
+        max_packet: usize,
+        stats: &mut OscStats,
+        mut emit: impl FnMut(&[u8], &mut OscStats),
+    ) -> Result<(), Oversized> {
+        match framing {
+            Framing::LengthPrefixed => {
+                self.buf.extend_from_slice(bytes);
+                let mut at = 0;
+                while self.buf.len() - at >= 4 {
+                    let len = u32::from_be_bytes(self.buf[at..at + 4].try_into().unwrap()) as usize;
+                    // The stream cannot resync after a bogus length.
+                    if len > max_packet {
+                        stats.truncated += 1;
+                        return Err(Oversized);
+                    }
+                    if self.buf.len() - at - 4 < len {
+                        break;
+                    }
+                    emit(&self.buf[at + 4..at + 4 + len], stats);
+                    at += 4 + len;
+                }
+                self.buf.drain(..at);
+            }
+            Framing::Slip => {
+                for &b in bytes {
+                    match b {
+                        SLIP_END => {
+                            if self.discarding {
+                                self.discarding = false;
+                            } else if !self.buf.is_empty() {
+                                emit(&self.buf, stats);
+                            }
+                            self.buf.clear();
+                            self.escaped = false;
+                        }
+                        _ if self.discarding => {}
+                        SLIP_ESC => self.escaped = true,
+                        _ => {
+                            let b = match (self.escaped, b) {
+                                (true, SLIP_ESC_END) => SLIP_END,
+                                (true, SLIP_ESC_ESC) => SLIP_ESC,
+                                _ => b,
+                            };
+                            self.escaped = false;
+                            if self.buf.len() == max_packet {
+                                // SLIP resyncs on the next END; drop just this frame.
+                                stats.truncated += 1;
+                                self.buf.clear();
+                                self.discarding = true;
+                            } else {
+                                self.buf.push(b);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener, PathBuf),
+}
+
+#[derive(Debug)]
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(s) => s.read(buf),
+            #[cfg(unix)]
+            Stream::Unix(s) => s.read(buf),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Conn {
+    stream: Stream,
+    deframer: Deframer,
+}
+
+/// Non-blocking OSC receiver over TCP or a unix-domain socket.
+///
+/// Same address/value convention and `poll()` shape as
+/// [`OscParamReceiver`](crate::OscParamReceiver). Connections are accepted and drained on each
+/// poll; a peer that closes, errors, or sends a length prefix over the size limit is dropped.
+#[derive(Debug)]
+pub struct OscStreamReceiver {
+    listener: Listener,
+    framing: Framing,
+    conns: Vec<Conn>,
+    max_packet: usize,
+    stats: OscStats,
+}
+
+impl OscStreamReceiver {
+    /// Listen for TCP connections on an address like "127.0.0.1:9000".
+    pub fn bind_tcp(addr: &str, framing: Framing) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self::new(Listener::Tcp(listener), framing))
+    }
+
+    /// Listen on a unix-domain socket. A stale socket file at `path` is replaced; the file is
+    /// removed again on drop.
+    #[cfg(unix)]
+    pub fn bind_unix(path: impl AsRef<Path>, framing: Framing) -> io::Result<Self> {
+        use std::os::unix::fs::FileTypeExt;
+
+        let path = path.as_ref();
+        if let Ok(meta) = std::fs::symlink_metadata(path) {
+            if meta.file_type().is_socket() {
+                std::fs::remove_file(path)?;
+            }
+        }
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self::new(
+            Listener::Unix(listener, path.to_path_buf()),
+            framing,
+        ))
+    }
+
+    fn new(listener: Listener, framing: Framing) -> Self {
+        Self {
+            listener,
+            framing,
+            conns: Vec::new(),
+            max_packet: 1 << 20,
+            stats: OscStats::default(),
+        }
+    }
+
+    /// Largest accepted packet in bytes (default 1 MiB).
+    pub fn with_max_packet(mut self, max_packet: usize) -> Self {
+        self.max_packet = max_packet.max(16);
+        self
+    }
+
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// The bound TCP address (`None` for unix sockets).
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        match &self.listener {
+            Listener::Tcp(l) => l.local_addr().ok(),
+            #[cfg(unix)]
+            Listener::Unix(..) => None,
+        }
+    }
+
+    /// Open connections.
+    pub fn connections(&self) -> usize {
+        self.conns.len()
+    }
+
+    pub fn stats(&self) -> OscStats {
+        self.stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = OscStats::default();
+    }
+
+    /// Accept pending connections and return all numeric parameter updates available right now
+    /// (see [`OscValue::as_f32`]).
+    ///
+    /// This never blocks; each connection is drained until `WouldBlock`.
+    pub fn poll(&mut self) -> Vec<(String, f32)> {
+        scalars(self.poll_values())
+    }
+
+    /// Like [`poll`](Self::poll), but returns every update with its typed value.
+    pub fn poll_values(&mut self) -> Vec<(String, OscValue)> {
+        self.accept();
+
+        let mut out: Vec<(String, OscValue)> = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let (framing, max_packet) = (self.framing, self.max_packet);
+        let stats = &mut self.stats;
+        self.conns.retain_mut(|conn| loop {
+            match conn.stream.read(&mut chunk) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    let fed = conn.deframer.feed(
+                        framing,
+                        &chunk[..n],
+                        max_packet,
+                        stats,
+                        |packet, stats| {
+                            stats.packets += 1;
+                            decode_into(packet, &mut out, stats);
+                        },
+                    );
+                    if fed.is_err() {
+                        return false;
+                    }
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return true,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(_e) => return false,
+            }
+        });
+
+        out
+    }
+
+    fn accept(&mut self) {
+        loop {
+            let stream = match &self.listener {
+                Listener::Tcp(l) => l.accept().and_then(|(s, _)| {
+                    s.set_nonblocking(true)?;
+                    Ok(Stream::Tcp(s))
+                }),
+                #[cfg(unix)]
+                Listener::Unix(l, _) => l.accept().and_then(|(s, _)| {
+                    s.set_nonblocking(true)?;
+                    Ok(Stream::Unix(s))
+                }),
+            };
+            match stream {
+                Ok(stream) => self.conns.push(Conn {
+                    stream,
+                    deframer: Deframer::default(),
+                }),
+                // WouldBlock, or a connection that died before we got to it.
+                Err(_e) => break,
+            }
+        }
+    }
+}
+
+impl Drop for OscStreamReceiver {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Listener::Unix(_, path) = &self.listener {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rosc::{OscMessage, OscPacket, OscType};
+    use std::io::Write;
+    use std::time::{Duration, Instant};
+
+    fn message(addr: &str, v: f32) -> Vec<u8> {
+        rosc::encoder::encode(&OscPacket::Message(OscMessage {
+            addr: addr.to_string(),
+            args: vec![OscType::Float(v)],
+        }))
+        .unwrap()
+    }
+
+    fn poll_until(rx: &mut OscStreamReceiver, want: usize) -> Vec<(String, f32)> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut out = Vec::new();
+        while out.len() < want && Instant::now() < deadline {
+            out.extend(rx.poll());
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        out
+    }
+
+    #[test]
+    fn slip_escapes_and_resyncs() {
+        let packet = [1, SLIP_END, 2, SLIP_ESC, 3];
+        let framed = Framing::Slip.encode(&packet);
+        assert_eq!(
+            framed,
+            [
+                SLIP_END,
+                1,
+                SLIP_ESC,
+                SLIP_ESC_END,
+                2,
+                SLIP_ESC,
+                SLIP_ESC_ESC,
+                3,
+                SLIP_END
+            ]
+        );
+
+        let mut d = Deframer::default();
+        let mut stats = OscStats::default();
+        let mut got = Vec::new();
+        // Split mid-escape, plus an oversized frame that is skipped up to its END.
+        let mut bytes = framed.clone();
+        bytes.extend_from_slice(&[SLIP_END, 9, 9, 9, 9, 9, 9, SLIP_END]);
+        bytes.extend_from_slice(&framed);
+        for part in bytes.chunks(3) {
+            d.feed(Framing::Slip, part, 5, &mut stats, |p, _| {
+                got.push(p.to_vec())
+            })
+            .unwrap();
+        }
+        assert_eq!(got, [packet.to_vec(), packet.to_vec()]);
+        assert_eq!(stats.truncated, 1);
+    }
+
+    #[test]
+    fn tcp_length_prefixed_across_writes() {
+        let mut rx = OscStreamReceiver::bind_tcp("127.0.0.1:0", Framing::LengthPrefixed).unwrap();
+        let mut tx = TcpStream::connect(rx.local_addr().unwrap()).unwrap();
+
+        let mut bytes = Framing::LengthPrefixed.encode(&message("/param/gain", 0.5));
+        bytes.extend(Framing::LengthPrefixed.encode(&message("/mix", -1.0)));
+        let (a, b) = bytes.split_at(7);
+        tx.write_all(a).unwrap();
+        tx.flush().unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(rx.poll().is_empty(), "half a frame is held back");
+        tx.write_all(b).unwrap();
+
+        let got = poll_until(&mut rx, 2);
+        assert_eq!(got, [("gain".to_string(), 0.5), ("mix".to_string(), -1.0)]);
+        assert_eq!(rx.connections(), 1);
+
+        // A bogus length drops the connection.
+        tx.write_all(&u32::MAX.to_be_bytes()).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while rx.connections() > 0 && Instant::now() < deadline {
+            rx.poll();
+        }
+        assert_eq!(rx.connections(), 0);
+        assert_eq!(rx.stats().truncated, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_slip() {
+        let path = std::env::temp_dir().join(format!("scheng_osc_{}.sock", std::process::id()));
+        let mut rx = OscStreamReceiver::bind_unix(&path, Framing::Slip).unwrap();
+        let mut tx = UnixStream::connect(&path).unwrap();
+        tx.write_all(&Framing::Slip.encode(&message("/param/node/3/hue", 0.25)))
+            .unwrap();
+
+        assert_eq!(poll_until(&mut rx, 1), [("node/3/hue".to_string(), 0.25)]);
+        drop(tx);
+        drop(rx);
+        assert!(!path.exists());
+    }
+}
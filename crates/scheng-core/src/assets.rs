@@ -1,5 +1,6 @@
 use std::path::{Path, PathBuf};
 
+use crate::bundle::Bundle;
 use crate::error::EngineError;
 
 /// A validated root directory containing scheng runtime assets (JSON + shaders).
@@ -15,11 +16,14 @@ impl AssetsRoot {
     /// Locate the `assets/` directory.
     ///
     /// Resolution order:
-    /// 1) `scheng_ASSETS` env var (if set)
+    /// 1) `scheng_ASSETS` env var (if set); a project bundle directory resolves to its `assets/`
     /// 2) Search upward from `start_dir` for a folder named `assets`
     pub fn discover(start_dir: &Path) -> Result<Self, EngineError> {
         if let Ok(p) = std::env::var("scheng_ASSETS") {
             let pb = PathBuf::from(p);
+            if Bundle::is_bundle(&pb) {
+                return Ok(Bundle::open(&pb)?.assets());
+            }
             if pb.exists() {
                 return Ok(Self { path: pb });
             }
@@ -41,6 +45,11 @@ impl AssetsRoot {
         })
     }
 
+    /// Use `path` as the assets directory without searching (e.g. a bundle's `assets/`).
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
     pub fn path(&self) -> &Path {
         &self.path
     }
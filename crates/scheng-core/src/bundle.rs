@@ -0,0 +1,383 @@
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+
+use crate::assets::{read_to_string_result, AssetsRoot};
+use crate::config::{JsonSchema, Migration};
+use crate::error::EngineError;
+
+/// Conventional extension for project bundles (`show.schengproj`).
+pub const BUNDLE_EXTENSION: &str = "schengproj";
+
+/// Manifest file at the bundle root.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Directory inside the bundle that becomes the `AssetsRoot`.
+pub const BUNDLE_ASSETS_DIR: &str = "assets";
+
+/// Schema of `manifest.json`.
+pub const BUNDLE_SCHEMA: JsonSchema =
+    JsonSchema::new("manifest.json", 1, &[Migration::unchanged(0)]);
+
+/// Container for a bundle on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFormat {
+    /// A plain directory (`show.schengproj/manifest.json`, `show.schengproj/assets/...`).
+    Directory,
+    /// The same layout in a zip archive (requires the `zip` feature).
+    Zip,
+}
+
+/// `manifest.json`: what the bundle holds.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BundleManifest {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// Every bundled file, relative to the bundle's `assets/` directory, sorted.
+    pub files: Vec<BundleFile>,
+}
+
+/// One bundled file. `path` always uses `/` separators.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BundleFile {
+    pub path: String,
+    pub size: u64,
+}
+
+/// Collects files for a bundle. Paths inside the bundle are relative to its assets root, so
+/// configs that use relative paths (the normal case, see `resolve_assets_path`) load unchanged.
+///
+/// Files outside the assets root (e.g. a video on another drive) are added with
+/// [`add_file`](Self::add_file) under a relative path of your choice; point the config at that
+/// relative path before bundling.
+#[derive(Debug, Clone)]
+pub struct BundleBuilder {
+    name: String,
+    description: String,
+    /// Bundle-relative path -> source file.
+    files: BTreeMap<String, PathBuf>,
+}
+
+impl BundleBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            description: String::new(),
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Every file under `assets` (hidden files, hidden directories and symlinked directories
+    /// skipped).
+    pub fn from_assets(name: impl Into<String>, assets: &AssetsRoot) -> Result<Self, EngineError> {
+        let mut builder = Self::new(name);
+        builder.add_dir(assets.path(), "")?;
+        Ok(builder)
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Bundle `src` as `rel` (replacing an earlier file at the same path).
+    pub fn add_file(
+        &mut self,
+        src: impl Into<PathBuf>,
+        rel: &str,
+    ) -> Result<&mut Self, EngineError> {
+        let src = src.into();
+        let rel = normalize_rel(rel).ok_or_else(|| EngineError::InvalidConfig {
+            path: src.clone(),
+            msg: format!("bundle path '{rel}' must be relative and stay inside the bundle"),
+        })?;
+        if !src.is_file() {
+            return Err(EngineError::Io {
+                path: src,
+                source: std::io::ErrorKind::NotFound.into(),
+            });
+        }
+        self.files.insert(rel, src);
+        Ok(self)
+    }
+
+    /// Bundle every file under `dir` below `rel_prefix` (`""` for the bundle root).
+    ///
+    /// Symlinked files are bundled by content; symlinked directories are skipped, since following
+    /// them can loop.
+    pub fn add_dir(&mut self, dir: &Path, rel_prefix: &str) -> Result<&mut Self, EngineError> {
+        let io = |source| EngineError::Io {
+            path: dir.to_path_buf(),
+            source,
+        };
+        let mut entries = std::fs::read_dir(dir)
+            .map_err(io)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io)?;
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') {
+                continue;
+            }
+            let rel = if rel_prefix.is_empty() {
+                name
+            } else {
+                format!("{}/{name}", rel_prefix.trim_end_matches('/'))
+            };
+            let path = entry.path();
+            let kind = entry.file_type().map_err(io)?;
+            if kind.is_dir() {
+                self.add_dir(&path, &rel)?;
+            } else if kind.is_symlink() && path.is_dir() {
+                continue;
+            } else {
+                self.add_file(path, &rel)?;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Bundled paths, sorted.
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    fn manifest(&self) -> Result<BundleManifest, EngineError> {
+        let files = self
+            .files
+            .iter()
+            .map(|(rel, src)| {
+                let meta = std::fs::metadata(src).map_err(|source| EngineError::Io {
+                    path: src.clone(),
+                    source,
+                })?;
+                Ok(BundleFile {
+                    path: rel.clone(),
+                    size: meta.len(),
+                })
+            })
+            .collect::<Result<_, EngineError>>()?;
+        Ok(BundleManifest {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            files,
+        })
+    }
+
+    /// Write the bundle to `dest` (a directory, or a zip file).
+    pub fn write(&self, dest: &Path, format: BundleFormat) -> Result<BundleManifest, EngineError> {
+        let manifest = self.manifest()?;
+        let json = BUNDLE_SCHEMA
+            .to_string_pretty(&manifest)
+            .map_err(EngineError::other)?;
+        match format {
+            BundleFormat::Directory => self.write_dir(dest, &json)?,
+            BundleFormat::Zip => self.write_zip(dest, &json)?,
+        }
+        Ok(manifest)
+    }
+
+    fn write_dir(&self, dest: &Path, manifest: &str) -> Result<(), EngineError> {
+        let io = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| EngineError::Io { path, source }
+        };
+        let assets = dest.join(BUNDLE_ASSETS_DIR);
+        std::fs::create_dir_all(&assets).map_err(io(&assets))?;
+        for (rel, src) in &self.files {
+            let to = assets.join(rel);
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent).map_err(io(parent))?;
+            }
+            std::fs::copy(src, &to).map_err(io(&to))?;
+        }
+        let path = dest.join(MANIFEST_FILE);
+        std::fs::write(&path, manifest).map_err(io(&path))
+    }
+
+    #[cfg(feature = "zip")]
+    fn write_zip(&self, dest: &Path, manifest: &str) -> Result<(), EngineError> {
+        use std::io::Write;
+
+        let io = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| EngineError::Io { path, source }
+        };
+        let zip_err = |e: zip::result::ZipError| {
+            EngineError::other(format!("bundle {}: {e}", dest.display()))
+        };
+        let file = std::fs::File::create(dest).map_err(io(dest))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file(MANIFEST_FILE, options).map_err(zip_err)?;
+        zip.write_all(manifest.as_bytes()).map_err(io(dest))?;
+        for (rel, src) in &self.files {
+            zip.start_file(format!("{BUNDLE_ASSETS_DIR}/{rel}"), options)
+                .map_err(zip_err)?;
+            let mut f = std::fs::File::open(src).map_err(io(src))?;
+            std::io::copy(&mut f, &mut zip).map_err(io(src))?;
+        }
+        zip.finish().map_err(zip_err)?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "zip"))]
+    fn write_zip(&self, dest: &Path, _manifest: &str) -> Result<(), EngineError> {
+        Err(zip_disabled(dest))
+    }
+}
+
+/// An opened (directory) bundle.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    root: PathBuf,
+    manifest: BundleManifest,
+}
+
+impl Bundle {
+    /// Open a directory bundle and read its manifest. Zip bundles must be [`extract`]ed first.
+    pub fn open(root: &Path) -> Result<Self, EngineError> {
+        if root.is_file() {
+            return Err(EngineError::InvalidConfig {
+                path: root.to_path_buf(),
+                msg: "zip bundles must be extracted before opening".to_string(),
+            });
+        }
+        let manifest_path = root.join(MANIFEST_FILE);
+        let manifest: BundleManifest = BUNDLE_SCHEMA
+            .from_str(&read_to_string_result(&manifest_path)?)
+            .map_err(|msg| EngineError::InvalidConfig {
+                path: manifest_path.clone(),
+                msg,
+            })?;
+        if let Some(bad) = manifest
+            .files
+            .iter()
+            .find(|f| normalize_rel(&f.path).is_none())
+        {
+            return Err(EngineError::InvalidConfig {
+                path: manifest_path,
+                msg: format!("bundle file '{}' escapes the bundle", bad.path),
+            });
+        }
+        Ok(Self {
+            root: root.to_path_buf(),
+            manifest,
+        })
+    }
+
+    /// Whether `path` looks like a directory bundle (has a manifest).
+    pub fn is_bundle(path: &Path) -> bool {
+        path.join(MANIFEST_FILE).is_file()
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn manifest(&self) -> &BundleManifest {
+        &self.manifest
+    }
+
+    /// The bundle's assets directory, for the usual config loaders.
+    pub fn assets(&self) -> AssetsRoot {
+        AssetsRoot::from_path(self.root.join(BUNDLE_ASSETS_DIR))
+    }
+
+    /// Check that every manifest file is present with its recorded size.
+    pub fn verify(&self) -> Result<(), EngineError> {
+        let assets = self.root.join(BUNDLE_ASSETS_DIR);
+        for file in &self.manifest.files {
+            let path = assets.join(&file.path);
+            let meta = std::fs::metadata(&path).map_err(|source| EngineError::Io {
+                path: path.clone(),
+                source,
+            })?;
+            if meta.len() != file.size {
+                return Err(EngineError::InvalidConfig {
+                    path,
+                    msg: format!(
+                        "size {} does not match the manifest ({})",
+                        meta.len(),
+                        file.size
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bundle everything under `assets` into `dest`, named after `dest`'s file stem.
+pub fn create(
+    assets: &AssetsRoot,
+    dest: &Path,
+    format: BundleFormat,
+) -> Result<BundleManifest, EngineError> {
+    let name = dest
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    BundleBuilder::from_assets(name, assets)?.write(dest, format)
+}
+
+/// Unpack a bundle into the directory `dest` and open it. `src` may be a zip bundle or a
+/// directory bundle (copied).
+pub fn extract(src: &Path, dest: &Path) -> Result<Bundle, EngineError> {
+    if src.is_dir() {
+        let bundle = Bundle::open(src)?;
+        let mut builder = BundleBuilder::new(bundle.manifest.name.clone())
+            .with_description(bundle.manifest.description.clone());
+        let assets = src.join(BUNDLE_ASSETS_DIR);
+        for file in &bundle.manifest.files {
+            builder.add_file(assets.join(&file.path), &file.path)?;
+        }
+        builder.write(dest, BundleFormat::Directory)?;
+    } else {
+        extract_zip(src, dest)?;
+    }
+    let bundle = Bundle::open(dest)?;
+    bundle.verify()?;
+    Ok(bundle)
+}
+
+#[cfg(feature = "zip")]
+fn extract_zip(src: &Path, dest: &Path) -> Result<(), EngineError> {
+    let file = std::fs::File::open(src).map_err(|source| EngineError::Io {
+        path: src.to_path_buf(),
+        source,
+    })?;
+    // `extract` refuses entries that would land outside `dest`.
+    zip::ZipArchive::new(file)
+        .and_then(|mut zip| zip.extract(dest))
+        .map_err(|e| EngineError::other(format!("bundle {}: {e}", src.display())))
+}
+
+#[cfg(not(feature = "zip"))]
+fn extract_zip(src: &Path, _dest: &Path) -> Result<(), EngineError> {
+    Err(zip_disabled(src))
+}
+
+#[cfg(not(feature = "zip"))]
+fn zip_disabled(path: &Path) -> EngineError {
+    EngineError::InvalidConfig {
+        path: path.to_path_buf(),
+        msg: "zip bundles need scheng-core's `zip` feature".to_string(),
+    }
+}
+
+/// `rel` as a `/`-separated path of normal components, or `None` if it is absolute or climbs
+/// out with `..`.
+fn normalize_rel(rel: &str) -> Option<String> {
+    let mut parts = Vec::new();
+    for c in Path::new(rel).components() {
+        match c {
+            Component::Normal(s) => parts.push(s.to_str()?.to_string()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
@@ -6,6 +6,7 @@ use serde_json::Value;
 use crate::assets::{
     load_json_result, pick_platform_json, read_to_string_result, resolve_assets_path, AssetsRoot,
 };
+use crate::bundle::Bundle;
 use crate::error::EngineError;
 
 /// How strictly to interpret/validate config files.
@@ -35,7 +36,11 @@ pub struct ConfigPaths {
 /// This is intentionally *path-only* so the CLI and scratchpad can decide how to
 /// interpret/validate configs. For typed helpers, see `load_render_selection` below.
 pub fn resolve_config_paths_from(start_dir: &std::path::Path) -> Result<ConfigPaths, EngineError> {
-    let assets = AssetsRoot::discover(start_dir)?;
+    Ok(resolve_config_paths(&AssetsRoot::discover(start_dir)?))
+}
+
+/// Resolve the JSON config file paths inside an already located assets root.
+pub fn resolve_config_paths(assets: &AssetsRoot) -> ConfigPaths {
     let assets_dir = assets.path().to_path_buf();
 
     let render_json = assets_dir.join("render.json");
@@ -43,23 +48,42 @@ pub fn resolve_config_paths_from(start_dir: &std::path::Path) -> Result<ConfigPa
     let output_json = pick_platform_json(&assets_dir, "output");
     let recording_json = pick_platform_json(&assets_dir, "recording");
 
-    Ok(ConfigPaths {
+    ConfigPaths {
         assets_dir,
         render_json,
         params_json,
         output_json,
         recording_json,
-    })
+    }
+}
+
+/// Schema of `render.json`. Version 1 replaced the old `version` field with `schema_version`.
+pub const RENDER_JSON_SCHEMA: JsonSchema = JsonSchema::new(
+    "render.json",
+    1,
+    &[Migration {
+        from: 0,
+        apply: render_json_from_v0,
+    }],
+);
+
+/// Unversioned files may carry `"version": 1`, the only value that ever existed.
+fn render_json_from_v0(value: &mut Value) -> Result<(), String> {
+    match value.as_object_mut().and_then(|o| o.remove("version")) {
+        None => Ok(()),
+        Some(v) if v == 1 => Ok(()),
+        Some(v) => Err(format!("unsupported version {v} (expected 1)")),
+    }
 }
 
 /// Typed view of `assets/render.json`.
 ///
-/// Versioning: `version` defaults to 1 when omitted.
+/// Versioning: see [`RENDER_JSON_SCHEMA`]; files are migrated before they are read.
 /// Unknown fields are ignored by default (serde default behavior), keeping configs forward-compatible.
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct RenderJson {
     #[serde(default = "default_version")]
-    pub version: u32,
+    pub schema_version: u32,
 
     #[serde(default)]
     pub frag: Option<String>,
@@ -90,7 +114,7 @@ pub struct RenderJson {
 #[serde(deny_unknown_fields)]
 struct RenderJsonStrict {
     #[serde(default = "default_version")]
-    pub version: u32,
+    pub schema_version: u32,
 
     #[serde(default)]
     pub frag: Option<String>,
@@ -167,48 +191,51 @@ fn load_render_selection_with_mode(
     }
 
     let data = read_to_string_result(&render_cfg)?;
-
-    // Parse in the requested mode.
-    let (version, frag, frag_variants_s, active_frag, present_frag, frag_profile_map_s) = match mode
-    {
-        ConfigMode::Lenient => {
-            let rj: RenderJson = serde_json::from_str(&data).map_err(|e| EngineError::Json {
-                path: render_cfg.clone(),
-                source: e,
-            })?;
-            (
-                rj.version,
-                rj.frag,
-                rj.frag_variants,
-                rj.active_frag,
-                rj.present_frag,
-                rj.frag_profile_map,
-            )
-        }
-        ConfigMode::Strict => {
-            let rj: RenderJsonStrict =
-                serde_json::from_str(&data).map_err(|e| EngineError::Json {
-                    path: render_cfg.clone(),
-                    source: e,
-                })?;
-            (
-                rj.version,
-                rj.frag,
-                rj.frag_variants,
-                rj.active_frag,
-                rj.present_frag,
-                rj.frag_profile_map,
-            )
-        }
-    };
-
-    // Minimal semantic validation in strict mode.
-    if mode == ConfigMode::Strict && version != 1 {
-        return Err(EngineError::InvalidConfig {
+    let mut value: Value = serde_json::from_str(&data).map_err(|e| EngineError::Json {
+        path: render_cfg.clone(),
+        source: e,
+    })?;
+    RENDER_JSON_SCHEMA
+        .migrate(&mut value)
+        .map_err(|msg| EngineError::InvalidConfig {
             path: render_cfg.clone(),
-            msg: format!("unsupported render.json version {version} (expected 1)"),
-        });
-    }
+            msg,
+        })?;
+
+    // Parse in the requested mode. (`migrate` already checked the version.)
+    let (_version, frag, frag_variants_s, active_frag, present_frag, frag_profile_map_s) =
+        match mode {
+            ConfigMode::Lenient => {
+                let rj: RenderJson =
+                    serde_json::from_value(value).map_err(|e| EngineError::JsonValue {
+                        path: render_cfg.clone(),
+                        source: e,
+                    })?;
+                (
+                    rj.schema_version,
+                    rj.frag,
+                    rj.frag_variants,
+                    rj.active_frag,
+                    rj.present_frag,
+                    rj.frag_profile_map,
+                )
+            }
+            ConfigMode::Strict => {
+                let rj: RenderJsonStrict =
+                    serde_json::from_value(value).map_err(|e| EngineError::JsonValue {
+                        path: render_cfg.clone(),
+                        source: e,
+                    })?;
+                (
+                    rj.schema_version,
+                    rj.frag,
+                    rj.frag_variants,
+                    rj.active_frag,
+                    rj.present_frag,
+                    rj.frag_profile_map,
+                )
+            }
+        };
 
     // Resolve variants (if present), else fall back to single frag.
     let mut frag_variants: Vec<PathBuf> = Vec::new();
@@ -366,12 +393,30 @@ pub fn load_engine_config_from_strict(start_dir: &Path) -> Result<EngineConfig,
     load_engine_config_from_mode(start_dir, ConfigMode::Strict)
 }
 
+/// Load the standard config files from a project bundle directory (see `bundle`).
+///
+/// The bundle's `assets/` directory is used as the assets root, so relative paths in its
+/// configs resolve inside the bundle. Zip bundles must be `bundle::extract`ed first.
+pub fn load_engine_config_from_bundle(
+    bundle_dir: &Path,
+    mode: ConfigMode,
+) -> Result<EngineConfig, EngineError> {
+    load_engine_config_from_assets(Bundle::open(bundle_dir)?.assets(), mode)
+}
+
 fn load_engine_config_from_mode(
     start_dir: &Path,
     mode: ConfigMode,
 ) -> Result<EngineConfig, EngineError> {
-    let assets = AssetsRoot::discover(start_dir)?;
-    let paths = resolve_config_paths_from(start_dir)?;
+    load_engine_config_from_assets(AssetsRoot::discover(start_dir)?, mode)
+}
+
+/// Load all standard config files from `assets`.
+pub fn load_engine_config_from_assets(
+    assets: AssetsRoot,
+    mode: ConfigMode,
+) -> Result<EngineConfig, EngineError> {
+    let paths = resolve_config_paths(&assets);
     let render = if mode == ConfigMode::Strict {
         load_render_selection_strict(&assets)?
     } else {
@@ -397,3 +442,108 @@ pub fn load_typed_json<T: serde::de::DeserializeOwned>(
 ) -> Result<T, EngineError> {
     load_json_result(path)
 }
+
+// ---- Schema versioning ----
+
+/// Top-level key holding the version of a scheng JSON format.
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// One upgrade step: rewrites a version-`from` document into version `from + 1`, in place.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub from: u32,
+    pub apply: fn(&mut Value) -> Result<(), String>,
+}
+
+impl Migration {
+    /// A step whose shape did not change (only the version number moves).
+    pub const fn unchanged(from: u32) -> Self {
+        fn keep(_: &mut Value) -> Result<(), String> {
+            Ok(())
+        }
+        Self { from, apply: keep }
+    }
+}
+
+/// A versioned JSON format: its current version and the steps that upgrade older documents.
+///
+/// Documents without `schema_version` predate versioning and count as version 0. Loaders call
+/// [`migrate`](Self::migrate) (or [`from_str`](Self::from_str)) before deserializing; writers
+/// go through [`to_string_pretty`](Self::to_string_pretty) so saved files carry the current
+/// version. Documents newer than `current` are rejected rather than half-read.
+#[derive(Debug, Clone, Copy)]
+pub struct JsonSchema {
+    /// Format name for error messages (e.g. `"banks"`).
+    pub name: &'static str,
+    pub current: u32,
+    /// One step per version below `current`, in any order.
+    pub migrations: &'static [Migration],
+}
+
+impl JsonSchema {
+    pub const fn new(name: &'static str, current: u32, migrations: &'static [Migration]) -> Self {
+        Self {
+            name,
+            current,
+            migrations,
+        }
+    }
+
+    /// The document's `schema_version` (0 when absent).
+    pub fn version_of(&self, value: &Value) -> Result<u32, String> {
+        match value.get(SCHEMA_VERSION_KEY) {
+            None => Ok(0),
+            Some(v) => v
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| format!("{}: {SCHEMA_VERSION_KEY} must be an integer", self.name)),
+        }
+    }
+
+    /// Upgrade `value` to the current version and stamp it. Returns the version it had.
+    pub fn migrate(&self, value: &mut Value) -> Result<u32, String> {
+        if !value.is_object() {
+            return Err(format!("{}: expected a JSON object", self.name));
+        }
+        let found = self.version_of(value)?;
+        if found > self.current {
+            return Err(format!(
+                "{}: schema_version {found} is newer than this build supports ({})",
+                self.name, self.current
+            ));
+        }
+        for from in found..self.current {
+            let step = self
+                .migrations
+                .iter()
+                .find(|m| m.from == from)
+                .ok_or_else(|| format!("{}: no migration from schema_version {from}", self.name))?;
+            (step.apply)(value)
+                .map_err(|e| format!("{}: migrating from schema_version {from}: {e}", self.name))?;
+        }
+        self.stamp(value);
+        Ok(found)
+    }
+
+    /// Set `schema_version` to the current version.
+    pub fn stamp(&self, value: &mut Value) {
+        if let Some(obj) = value.as_object_mut() {
+            obj.insert(SCHEMA_VERSION_KEY.to_string(), Value::from(self.current));
+        }
+    }
+
+    /// Parse, migrate and deserialize a document.
+    pub fn from_str<T: serde::de::DeserializeOwned>(&self, text: &str) -> Result<T, String> {
+        let mut value: Value =
+            serde_json::from_str(text).map_err(|e| format!("{}: parse json: {e}", self.name))?;
+        self.migrate(&mut value)?;
+        serde_json::from_value(value).map_err(|e| format!("{}: {e}", self.name))
+    }
+
+    /// Pretty JSON for `doc`, stamped with the current version.
+    pub fn to_string_pretty<T: serde::Serialize>(&self, doc: &T) -> Result<String, String> {
+        let mut value = serde_json::to_value(doc).map_err(|e| format!("{}: {e}", self.name))?;
+        self.stamp(&mut value);
+        serde_json::to_string_pretty(&value).map_err(|e| format!("{}: {e}", self.name))
+    }
+}
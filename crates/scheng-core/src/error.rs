@@ -36,6 +36,15 @@ pub enum EngineError {
     FragmentCompile(String),
     Link(String),
     GlCreate(String),
+    /// A GL error observed by the runtime's error-checking mode.
+    ///
+    /// `node` is the graph node id (`NodeId.0`) being executed, if any; `op` names the
+    /// operation after which `glGetError` reported `code`.
+    Gl {
+        node: Option<u32>,
+        op: String,
+        code: u32,
+    },
 
     // ---- Fallback ----
     Other(String),
@@ -70,12 +79,33 @@ impl fmt::Display for EngineError {
             EngineError::FragmentCompile(msg) => write!(f, "fragment shader compile error: {msg}"),
             EngineError::Link(msg) => write!(f, "program link error: {msg}"),
             EngineError::GlCreate(msg) => write!(f, "backend object creation failed: {msg}"),
+            EngineError::Gl { node, op, code } => {
+                write!(f, "GL error {} (0x{code:04X}) after {op}", gl_error_name(*code))?;
+                if let Some(node) = node {
+                    write!(f, " in node {node}")?;
+                }
+                Ok(())
+            }
 
             EngineError::Other(msg) => write!(f, "{msg}"),
         }
     }
 }
 
+/// Symbolic name for a `glGetError` code (values are fixed by the GL spec).
+fn gl_error_name(code: u32) -> &'static str {
+    match code {
+        0x0500 => "GL_INVALID_ENUM",
+        0x0501 => "GL_INVALID_VALUE",
+        0x0502 => "GL_INVALID_OPERATION",
+        0x0503 => "GL_STACK_OVERFLOW",
+        0x0504 => "GL_STACK_UNDERFLOW",
+        0x0505 => "GL_OUT_OF_MEMORY",
+        0x0506 => "GL_INVALID_FRAMEBUFFER_OPERATION",
+        _ => "unknown",
+    }
+}
+
 impl std::error::Error for EngineError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
@@ -44,4 +44,28 @@ pub enum EngineEvent {
 
     /// Optional runtime stats (fps, frame time, etc.) for UI clients.
     Stats { fps: f32 },
+
+    /// Frame time stayed above the watchdog threshold; the fallback policy moved to `level`.
+    WatchdogTriggered {
+        frame_ms: f32,
+        threshold_ms: f32,
+        level: u32,
+    },
+
+    /// Frame time recovered; the fallback policy stepped back to `level` (0 = full quality).
+    WatchdogRecovered { level: u32 },
+
+    /// A sink or input panicked. It has been disabled and the engine kept running.
+    ComponentPanicked { component: String, message: String },
+}
+
+/// The message of a caught panic (`panic!` payloads are `&str` or `String`).
+pub fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
 }
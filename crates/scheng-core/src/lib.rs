@@ -2,6 +2,7 @@
 #![deny(missing_debug_implementations)]
 
 pub mod assets;
+pub mod bundle;
 pub mod config;
 pub mod error;
 pub mod events;
@@ -11,10 +12,12 @@ pub use error::EngineError;
 
 // These types are referenced elsewhere in your repo; keep them accessible.
 pub use assets::AssetsRoot;
+pub use bundle::{Bundle, BundleBuilder, BundleFormat, BundleManifest};
 
 // Config / JSON utilities: re-export the *module* rather than guessing function names.
 // This preserves stability and avoids accidental API promises.
 pub use config::{
-    load_engine_config_from, load_typed_json, parse_loaded_json, ConfigMode, EngineConfig,
-    LoadedJson, RenderSelection,
+    load_engine_config_from, load_engine_config_from_bundle, load_typed_json, parse_loaded_json,
+    ConfigMode, EngineConfig, JsonSchema, LoadedJson, Migration, RenderSelection,
+    SCHEMA_VERSION_KEY,
 };
@@ -0,0 +1,66 @@
+//! `Graph::compile` on large patches.
+//!
+//! `cargo bench -p scheng-graph`
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use scheng_graph::{BranchFormat, Graph, NodeKind};
+
+/// `n` passes in a line, one delayed feedback edge, and an output.
+fn chain(n: usize) -> Graph {
+    let mut g = Graph::new();
+    let src = g.add_node(NodeKind::ShaderSource);
+    let mut prev = g.add_node(NodeKind::ShaderPass);
+    g.connect_named(src, "out", prev, "in").unwrap();
+    g.set_branch_format(prev, BranchFormat::sized(960, 540)).unwrap();
+    for _ in 1..n {
+        let pass = g.add_node(NodeKind::ShaderPass);
+        g.connect_named(prev, "out", pass, "in").unwrap();
+        prev = pass;
+    }
+    let mix = g.add_node(NodeKind::Crossfade);
+    g.connect_named(prev, "out", mix, "a").unwrap();
+    g.connect_delayed_named(mix, "out", mix, "b").unwrap();
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(mix, "out", out, "in").unwrap();
+    g
+}
+
+/// Layers of `width` passes, each fed by a mixer of two nodes from the layer before.
+fn layered(layers: usize, width: usize) -> Graph {
+    let mut g = Graph::new();
+    let mut prev: Vec<_> = (0..width).map(|_| g.add_node(NodeKind::NoiseSource)).collect();
+    for _ in 0..layers {
+        let mut next = Vec::with_capacity(width);
+        for i in 0..width {
+            let mix = g.add_node(NodeKind::Add);
+            g.connect_named(prev[i], "out", mix, "a").unwrap();
+            g.connect_named(prev[(i + 1) % width], "out", mix, "b").unwrap();
+            let pass = g.add_node(NodeKind::ShaderPass);
+            g.connect_named(mix, "out", pass, "in").unwrap();
+            next.push(pass);
+        }
+        prev = next;
+    }
+    let out = g.add_node(NodeKind::PixelsOut);
+    g.connect_named(prev[0], "out", out, "in").unwrap();
+    g
+}
+
+fn compile(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compile");
+    for n in [16, 256, 4096] {
+        let g = chain(n);
+        group.bench_with_input(BenchmarkId::new("chain", n), &g, |b, g| {
+            b.iter(|| black_box(g.compile().unwrap()))
+        });
+    }
+    for (layers, width) in [(8, 8), (32, 32)] {
+        let g = layered(layers, width);
+        let id = BenchmarkId::new("layered", format!("{layers}x{width}"));
+        group.bench_with_input(id, &g, |b, g| b.iter(|| black_box(g.compile().unwrap())));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compile);
+criterion_main!(benches);
@@ -0,0 +1,238 @@
+//! Fluent builder for common node chains.
+//!
+//! `Chain` describes a signal path as an expression (sources → passes → mixers) and replays it
+//! into a [`Graph`] using the same `add_node` / `connect_named` calls a host would write by hand.
+//!
+//! Shader payloads are opaque (`S`): the graph never stores code, so the built result hands them
+//! back keyed by `NodeId` for the runtime to attach (e.g. into `NodeProps::shader_sources`).
+//!
+//! ```
+//! use scheng_graph::Chain;
+//!
+//! let built = Chain::source("gradient.frag")
+//!     .pass("warp.frag")
+//!     .output("main")
+//!     .unwrap();
+//! assert_eq!(built.shaders.len(), 2);
+//! ```
+
+use crate::{Graph, NodeId, NodeKind, PortDir};
+use scheng_core::EngineError;
+
+/// Reserved name for the primary (unnamed) `PixelsOut`.
+const PRIMARY_OUTPUT: &str = "main";
+
+#[derive(Debug, Clone)]
+enum Step<S> {
+    /// `ShaderSource -> ShaderPass`, shader keyed by the `ShaderSource` node.
+    Source(S),
+    /// Single-input `ShaderPass`, shader keyed by the pass node.
+    Pass { input: Box<Chain<S>>, shader: S },
+    /// Built-in mixer; inputs are wired to the kind's input ports in declaration order.
+    Mix {
+        kind: NodeKind,
+        inputs: Vec<Chain<S>>,
+    },
+}
+
+/// A declarative chain of nodes that can be built into a [`Graph`].
+#[derive(Debug, Clone)]
+pub struct Chain<S> {
+    step: Step<S>,
+}
+
+/// Result of building a [`Chain`].
+#[derive(Debug)]
+pub struct BuiltChain<S> {
+    pub graph: Graph,
+    /// The last render node of the chain (the node feeding `output`).
+    pub head: NodeId,
+    /// The `PixelsOut` node created by [`Chain::output`].
+    pub output: NodeId,
+    /// Shader payloads keyed by the node that should own them, in build order.
+    pub shaders: Vec<(NodeId, S)>,
+    /// Explicit output names (empty when the output is the primary `"main"`).
+    pub output_names: Vec<(NodeId, String)>,
+}
+
+impl<S> Chain<S> {
+    /// Start a chain from a generator shader (`ShaderSource -> ShaderPass`).
+    pub fn source(shader: S) -> Self {
+        Self {
+            step: Step::Source(shader),
+        }
+    }
+
+    /// Append a single-input `ShaderPass` fed by this chain.
+    pub fn pass(self, shader: S) -> Self {
+        Self {
+            step: Step::Pass {
+                input: Box::new(self),
+                shader,
+            },
+        }
+    }
+
+    /// Mix this chain (`"a"`) with `other` (`"b"`) using a 2-input built-in mixer kind.
+    pub fn mix(self, kind: NodeKind, other: Chain<S>) -> Self {
+        Self {
+            step: Step::Mix {
+                kind,
+                inputs: vec![self, other],
+            },
+        }
+    }
+
+    /// Shorthand for `mix(NodeKind::Crossfade, other)`.
+    pub fn crossfade(self, other: Chain<S>) -> Self {
+        self.mix(NodeKind::Crossfade, other)
+    }
+
+    /// Feed four chains into a `MatrixMix4` (`in0..in3` in array order).
+    pub fn mix4(inputs: [Chain<S>; 4]) -> Self {
+        Self {
+            step: Step::Mix {
+                kind: NodeKind::MatrixMix4,
+                inputs: inputs.into(),
+            },
+        }
+    }
+
+    /// Terminate the chain in a `PixelsOut` and build the graph.
+    ///
+    /// `"main"` yields the primary (unnamed) output; any other name is returned in
+    /// `BuiltChain::output_names` for explicit multi-output routing.
+    pub fn output(self, name: &str) -> Result<BuiltChain<S>, EngineError> {
+        let mut graph = Graph::new();
+        let mut shaders = Vec::new();
+        let head = self.build_into(&mut graph, &mut shaders)?;
+
+        let output = graph.add_node(NodeKind::PixelsOut);
+        graph.connect_named(head, "out", output, "in")?;
+
+        let output_names = if name == PRIMARY_OUTPUT {
+            Vec::new()
+        } else {
+            vec![(output, name.to_string())]
+        };
+
+        Ok(BuiltChain {
+            graph,
+            head,
+            output,
+            shaders,
+            output_names,
+        })
+    }
+
+    /// Depth-first replay: every node is added after its upstream nodes, so creation order is
+    /// already a valid topological plan order.
+    fn build_into(
+        self,
+        g: &mut Graph,
+        shaders: &mut Vec<(NodeId, S)>,
+    ) -> Result<NodeId, EngineError> {
+        match self.step {
+            Step::Source(shader) => {
+                let src = g.add_node(NodeKind::ShaderSource);
+                let pass = g.add_node(NodeKind::ShaderPass);
+                g.connect_named(src, "out", pass, "in")?;
+                shaders.push((src, shader));
+                Ok(pass)
+            }
+            Step::Pass { input, shader } => {
+                let from = input.build_into(g, shaders)?;
+                let pass = g.add_node(NodeKind::ShaderPass);
+                g.connect_named(from, "out", pass, "in")?;
+                shaders.push((pass, shader));
+                Ok(pass)
+            }
+            Step::Mix { kind, inputs } => {
+                let mut froms = Vec::with_capacity(inputs.len());
+                for c in inputs {
+                    froms.push(c.build_into(g, shaders)?);
+                }
+                let mix = g.add_node(kind.clone());
+                let in_ports: Vec<&'static str> = g
+                    .node(mix)
+                    .map(|n| {
+                        n.ports
+                            .iter()
+                            .filter(|p| p.dir == PortDir::In)
+                            .map(|p| p.name)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if in_ports.len() != froms.len() {
+                    return Err(EngineError::other(format!(
+                        "chain: {kind:?} has {} inputs, chain provided {}",
+                        in_ports.len(),
+                        froms.len()
+                    )));
+                }
+                for (from, port) in froms.into_iter().zip(in_ports) {
+                    g.connect_named(from, "out", mix, port)?;
+                }
+                Ok(mix)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_pass_output_matches_manual_graph() {
+        let built = Chain::source("a").pass("b").output("main").unwrap();
+        let plan = built.graph.compile().unwrap();
+        // ShaderSource, ShaderPass, ShaderPass, PixelsOut
+        assert_eq!(plan.nodes.len(), 4);
+        assert_eq!(plan.edges.len(), 3);
+        assert!(built.output_names.is_empty());
+        assert_eq!(
+            built.shaders.iter().map(|(_, s)| *s).collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+        assert_eq!(
+            built.graph.node(built.head).unwrap().kind,
+            NodeKind::ShaderPass
+        );
+    }
+
+    #[test]
+    fn mix4_wires_inputs_in_order_and_names_output() {
+        let built = Chain::mix4([
+            Chain::source(0),
+            Chain::source(1),
+            Chain::source(2),
+            Chain::source(3),
+        ])
+        .output("preview")
+        .unwrap();
+
+        let mix = built.head;
+        assert_eq!(built.graph.node(mix).unwrap().kind, NodeKind::MatrixMix4);
+        assert_eq!(
+            built.output_names,
+            vec![(built.output, "preview".to_string())]
+        );
+
+        // Upstream nodes always precede the mixer in plan order.
+        let plan = built.graph.compile().unwrap();
+        let pos = plan.nodes.iter().position(|n| *n == mix).unwrap();
+        for e in plan.edges.iter().filter(|e| e.to.node == mix) {
+            assert!(plan.nodes.iter().position(|n| *n == e.from.node).unwrap() < pos);
+        }
+    }
+
+    #[test]
+    fn mix_rejects_kind_with_wrong_arity() {
+        let err = Chain::source(0)
+            .mix(NodeKind::MatrixMix4, Chain::source(1))
+            .output("main")
+            .unwrap_err();
+        assert!(err.to_string().contains("inputs"));
+    }
+}
@@ -0,0 +1,231 @@
+//! Dry-run resource estimation for a compiled [`Plan`].
+//!
+//! [`Plan::estimate`] counts what the GL runtime allocates and runs for a patch at a given
+//! output resolution, without a GPU, so a show can be budget-checked before it starts. The
+//! model follows `scheng-runtime-glow`:
+//!
+//! - every rendering node owns a ping-pong pair of targets per output port, at its branch
+//!   format size (see [`Graph::set_branch_format`]) or the frame size;
+//! - `MotionDetect` / `OpticalFlow` keep one extra copy of their input;
+//! - `VideoDecodeSource` owns one upload texture (counted at the frame size, since the clip
+//!   size lives in its runtime config);
+//! - `Particles` runs an extra update pass and a second program. Its state textures depend on
+//!   the particle count and are not included.
+//! - `AudioWaveform` / `AudioSpectrum` upload their columns to one small data texture (its
+//!   few bytes are not counted).
+//! - `Bloom` keeps a mip chain of `BLOOM_LEVELS` half-size `RGBA16F` levels and runs a
+//!   down and an up pass per level with two extra programs (counted at the default depth; the
+//!   real depth comes from its runtime params).
+//!
+//! Program counts are upper bounds: passes with identical sources share one program.
+
+use std::fmt;
+
+use crate::{Graph, NodeKind, Plan, PortDir};
+
+/// Mip levels assumed for `Bloom` nodes (`scheng_runtime::BloomParams::default().levels`).
+const BLOOM_LEVELS: u32 = 6;
+
+/// Storage format of render targets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TexelFormat {
+    /// 8-bit RGBA (what the GL runtime allocates today).
+    #[default]
+    Rgba8,
+    Rgba16F,
+    Rgba32F,
+}
+
+impl TexelFormat {
+    pub fn bytes_per_pixel(self) -> u64 {
+        match self {
+            TexelFormat::Rgba8 => 4,
+            TexelFormat::Rgba16F => 8,
+            TexelFormat::Rgba32F => 16,
+        }
+    }
+}
+
+/// The output the estimate is for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceProfile {
+    pub width: u32,
+    pub height: u32,
+    pub format: TexelFormat,
+    /// Internal resolution scale (`RuntimeState::set_render_scale`), `0 < scale <= 1`.
+    pub render_scale: f32,
+}
+
+impl ResourceProfile {
+    /// `width`x`height`, RGBA8, full internal resolution.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            format: TexelFormat::Rgba8,
+            render_scale: 1.0,
+        }
+    }
+
+    pub fn with_format(mut self, format: TexelFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_render_scale(mut self, scale: f32) -> Self {
+        self.render_scale = scale;
+        self
+    }
+
+    fn scaled(&self, (w, h): (u32, u32)) -> (u64, u64) {
+        let scale = if self.render_scale > 0.0 {
+            self.render_scale.min(1.0)
+        } else {
+            1.0
+        };
+        let s = |v: u32| ((v as f32 * scale).round() as u64).max(1);
+        (s(w), s(h))
+    }
+}
+
+/// What a plan costs per frame and in texture memory (see the module docs).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PlanEstimate {
+    /// Nodes that render (shader passes, mixers, analysis, geometry, particles).
+    pub render_nodes: usize,
+    /// Draw passes per frame.
+    pub passes_per_frame: usize,
+    /// Linked programs, at most.
+    pub programs: usize,
+    /// Render targets and other runtime-owned textures.
+    pub textures: usize,
+    pub texture_bytes: u64,
+}
+
+impl PlanEstimate {
+    pub fn texture_mib(&self) -> f64 {
+        self.texture_bytes as f64 / (1024.0 * 1024.0)
+    }
+}
+
+impl fmt::Display for PlanEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} render nodes, {} passes/frame, <= {} programs, {} textures ({:.1} MiB)",
+            self.render_nodes,
+            self.passes_per_frame,
+            self.programs,
+            self.textures,
+            self.texture_mib()
+        )
+    }
+}
+
+impl Plan {
+    /// Estimate this plan's per-frame work and texture memory at `resources`. `graph` is the
+    /// graph the plan was compiled from; nodes it does not contain are skipped.
+    pub fn estimate(&self, graph: &Graph, resources: &ResourceProfile) -> PlanEstimate {
+        let mut est = PlanEstimate::default();
+        let frame_size = (resources.width, resources.height);
+        let bpp = resources.format.bytes_per_pixel();
+        for id in &self.nodes {
+            let Some(node) = graph.node(*id) else {
+                continue;
+            };
+            let size = self
+                .format(node.id)
+                .and_then(|f| f.size)
+                .unwrap_or(frame_size);
+            let (w, h) = resources.scaled(size);
+            let target_bytes = w * h * bpp;
+
+            if node.kind == NodeKind::VideoDecodeSource {
+                let (w, h) = frame_size;
+                est.textures += 1;
+                est.texture_bytes += w as u64 * h as u64 * 4;
+                continue;
+            }
+            if !node.kind.renders() {
+                continue;
+            }
+            let outputs = node
+                .ports
+                .iter()
+                .filter(|p| p.dir == PortDir::Out)
+                .count()
+                .max(1);
+            est.render_nodes += 1;
+            est.passes_per_frame += 1;
+            est.programs += 1;
+            est.textures += 2 * outputs;
+            est.texture_bytes += 2 * outputs as u64 * target_bytes;
+            match node.kind {
+                NodeKind::MotionDetect | NodeKind::OpticalFlow => {
+                    est.textures += 1;
+                    est.texture_bytes += target_bytes;
+                }
+                NodeKind::Particles => {
+                    est.passes_per_frame += 1;
+                    est.programs += 1;
+                }
+                NodeKind::AudioWaveform | NodeKind::AudioSpectrum => {
+                    est.textures += 1;
+                }
+                NodeKind::Bloom => {
+                    let level_bpp = TexelFormat::Rgba16F.bytes_per_pixel();
+                    for level in 1..=BLOOM_LEVELS {
+                        let (lw, lh) = ((w >> level).max(1), (h >> level).max(1));
+                        est.textures += 1;
+                        est.texture_bytes += lw * lh * level_bpp;
+                    }
+                    est.passes_per_frame += 2 * BLOOM_LEVELS as usize - 1;
+                    est.programs += 2;
+                }
+                _ => {}
+            }
+        }
+        est
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BranchFormat;
+
+    #[test]
+    fn counts_targets_and_memory() {
+        let mut g = Graph::new();
+        let src = g.add_node(NodeKind::ShaderSource);
+        let pass = g.add_node(NodeKind::ShaderPass);
+        let small = g.add_node(NodeKind::ShaderPass);
+        let mix = g.add_node(NodeKind::Crossfade);
+        let out = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(src, "out", pass, "in").unwrap();
+        g.connect_named(src, "out", small, "in").unwrap();
+        g.connect_named(pass, "out", mix, "a").unwrap();
+        g.connect_named(small, "out", mix, "b").unwrap();
+        g.connect_named(mix, "out", out, "in").unwrap();
+        g.set_branch_format(small, BranchFormat::sized(960, 540))
+            .unwrap();
+        // Conform back to the frame size where the branches meet.
+        g.set_branch_format(mix, BranchFormat::default()).unwrap();
+        let plan = g.compile().unwrap();
+
+        let est = plan.estimate(&g, &ResourceProfile::new(1920, 1080));
+        assert_eq!(est.render_nodes, 3);
+        assert_eq!(est.passes_per_frame, 3);
+        assert_eq!(est.textures, 6);
+        let full = 1920 * 1080 * 4;
+        assert_eq!(est.texture_bytes, 2 * full + 2 * full / 4 + 2 * full);
+
+        let half = plan.estimate(
+            &g,
+            &ResourceProfile::new(1920, 1080)
+                .with_format(TexelFormat::Rgba16F)
+                .with_render_scale(0.5),
+        );
+        assert_eq!(half.texture_bytes, est.texture_bytes / 2);
+    }
+}
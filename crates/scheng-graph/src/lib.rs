@@ -8,7 +8,12 @@
 #![deny(missing_debug_implementations)]
 
 use scheng_core::EngineError;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+pub mod chain;
+pub use chain::{BuiltChain, Chain};
+pub mod estimate;
+pub use estimate::{PlanEstimate, ResourceProfile, TexelFormat};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct NodeId(pub u32);
@@ -30,6 +35,9 @@ pub struct Endpoint {
 pub struct Edge {
     pub from: Endpoint,
     pub to: Endpoint,
+    /// Feedback edge (see `Graph::connect_delayed`): the consumer samples the producer's
+    /// previous-frame output, so the edge does not order the plan and may close a cycle.
+    pub delayed: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -43,6 +51,13 @@ pub enum NodeKind {
     PreviousFrame,
     TextureInputPass,
     VideoDecodeSource,
+    /// GPU particle system rendered as additive point sprites
+    /// (see `scheng_runtime::ParticleParams`).
+    Particles,
+    /// Oscilloscope of the host's audio block (see `scheng_runtime::AudioVisParams`).
+    AudioWaveform,
+    /// Log-frequency spectrum of the host's audio block (see `scheng_runtime::AudioVisParams`).
+    AudioSpectrum,
 
     // Processors (single input "in")
     ShaderPass,
@@ -50,6 +65,24 @@ pub enum NodeKind {
     Blur,
     Keyer,
     Feedback,
+    /// Frame-difference analysis: outputs the per-pixel difference mask and publishes motion
+    /// energy scalars (see `scheng_runtime::MotionParams`).
+    MotionDetect,
+    /// Two-frame optical-flow estimate; outputs an encoded flow texture
+    /// (see `scheng_runtime::FLOW_ENCODING_SCALE`).
+    OpticalFlow,
+    /// Draws host-provided or built-in geometry (quad, grid, lines) with its own vertex shader
+    /// instead of a fullscreen triangle (see `scheng_runtime_glow::Geometry`).
+    GeometryPass,
+    /// Glow around bright areas: threshold, mip-chain blur and additive composite, run as one
+    /// node by the runtime (see `scheng_runtime::BloomParams`).
+    Bloom,
+    /// Mosaic of square blocks (see `scheng_runtime::PixelateParams`).
+    Pixelate,
+    /// Quantizes each channel to a few levels (see `scheng_runtime::PosterizeParams`).
+    Posterize,
+    /// Ordered or blue-noise dithering to a few levels (see `scheng_runtime::DitherParams`).
+    Dither,
 
     // --- NEW: Multi-input shader passes ---
     // These are Mixers (so the graph gives them multi-input ports)
@@ -67,8 +100,12 @@ pub enum NodeKind {
     Crossfade,
     Add,
     Multiply,
+    /// Keys `b` over `a`; `matte` (optional) is a garbage matte
+    /// (see `scheng_runtime::KeyMixParams`).
     KeyMix,
     MatrixMix4,
+    /// Offsets input `a` by the flow texture on `b` (typically an `OpticalFlow` output).
+    FlowDisplace,
 
     // Outputs
     Window,
@@ -86,24 +123,103 @@ impl NodeKind {
         use NodeKind::*;
         match self {
             ShaderSource | NoiseSource | PreviousFrame | TextureInputPass | VideoDecodeSource
+            | Particles | AudioWaveform | AudioSpectrum
                 => NodeClass::Source,
-            ShaderPass | ColorCorrect | Blur | Keyer | Feedback
+            ShaderPass | ColorCorrect | Blur | Keyer | Feedback | MotionDetect | OpticalFlow
+            | GeometryPass | Bloom | Pixelate | Posterize | Dither
                 => NodeClass::Processor,
             // ShaderMixN are Mixers — this gives them multi-input ports
             ShaderMix2 | ShaderMix3 | ShaderMix4
-            | Crossfade | Add | Multiply | KeyMix | MatrixMix4
+            | Crossfade | Add | Multiply | KeyMix | MatrixMix4 | FlowDisplace
                 => NodeClass::Mixer,
             Window | TextureOut | PixelsOut | Syphon | Spout | Recorder | Ndi | Rtsp
                 => NodeClass::Output,
         }
     }
+
+    /// Whether the runtime draws this node into its own render targets (shader passes,
+    /// mixers, analysis, geometry, particles, audio visualizers, bloom and the stock filters).
+    pub fn renders(&self) -> bool {
+        use NodeKind::*;
+        matches!(
+            self,
+            ShaderPass
+                | GeometryPass
+                | Particles
+                | AudioWaveform
+                | AudioSpectrum
+                | MotionDetect
+                | OpticalFlow
+                | Bloom
+                | Pixelate
+                | Posterize
+                | Dither
+        ) || self.class() == NodeClass::Mixer
+    }
+
+    /// How input `port` of this kind must be connected for `Graph::compile` to accept it.
+    pub fn input_multiplicity(&self, port: &str) -> Multiplicity {
+        use NodeKind::*;
+        match (self, port) {
+            // A pass without input renders as a generator; geometry draws without one.
+            (ShaderPass | GeometryPass, "in") | (KeyMix, "matte") => Multiplicity::Optional,
+            // Unused matrix channels read black; the mix needs at least one.
+            (MatrixMix4, _) => Multiplicity::Variadic,
+            _ => Multiplicity::Required,
+        }
+    }
 }
 
+/// Connection requirement of a port, checked by [`Graph::compile`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Multiplicity {
+    /// Must be driven (a delayed edge counts).
+    Required,
+    /// May be left open; the runtime then samples its [`InputDefault`].
+    Optional,
+    /// One of a group of interchangeable inputs: each may be open, but the node needs at
+    /// least one of its `Variadic` inputs driven.
+    Variadic,
+}
+
+/// What an unconnected `Optional` or `Variadic` input samples (see
+/// [`Graph::set_input_default`]). Unset inputs read `Black`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum InputDefault {
+    /// Opaque black.
+    #[default]
+    Black,
+    /// Opaque white.
+    White,
+    /// Black with zero alpha.
+    Transparent,
+    /// Any RGBA colour, components in 0..=1.
+    Color([f32; 4]),
+}
+
+impl InputDefault {
+    pub fn rgba(&self) -> [f32; 4] {
+        match *self {
+            InputDefault::Black       => [0.0, 0.0, 0.0, 1.0],
+            InputDefault::White       => [1.0, 1.0, 1.0, 1.0],
+            InputDefault::Transparent => [0.0, 0.0, 0.0, 0.0],
+            InputDefault::Color(c)    => c,
+        }
+    }
+}
+
+/// Most colour outputs a node can have (see [`Graph::add_node_with_outputs`]).
+pub const MAX_NODE_OUTPUTS: usize = 4;
+
+const OUTPUT_PORT_NAMES: [&str; MAX_NODE_OUTPUTS] = ["out", "out1", "out2", "out3"];
+
 #[derive(Debug, Clone)]
 pub struct Port {
     pub id: PortId,
     pub name: &'static str,
     pub dir: PortDir,
+    /// Always `Optional` for outputs.
+    pub multiplicity: Multiplicity,
 }
 
 #[derive(Debug, Clone)]
@@ -113,12 +229,76 @@ pub struct Node {
     pub ports: Vec<Port>,
 }
 
+/// Processing format for a branch of the graph (see [`Graph::set_branch_format`]).
+///
+/// Size and pixel aspect flow downstream along plain edges until a node with its own format,
+/// so a branch can run at its source's native size and be conformed once, where it meets the
+/// rest of the graph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BranchFormat {
+    /// Render size in pixels; `None` renders at the frame size.
+    pub size: Option<(u32, u32)>,
+    /// Pixel width over pixel height (1 = square pixels).
+    pub pixel_aspect: f32,
+    /// Region of its inputs the node samples, as UV `[x, y, w, h]`. Applies to the node it is
+    /// set on only; downstream nodes inherit size and pixel aspect but not the crop.
+    pub crop: [f32; 4],
+}
+
+impl Default for BranchFormat {
+    fn default() -> Self {
+        Self { size: None, pixel_aspect: 1.0, crop: BranchFormat::FULL_FRAME }
+    }
+}
+
+impl BranchFormat {
+    /// The uncropped input region.
+    pub const FULL_FRAME: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+    /// Render at `width` x `height` with square pixels.
+    pub fn sized(width: u32, height: u32) -> Self {
+        Self { size: Some((width.max(1), height.max(1))), ..Self::default() }
+    }
+
+    pub fn with_pixel_aspect(mut self, pixel_aspect: f32) -> Self {
+        self.pixel_aspect = pixel_aspect;
+        self
+    }
+
+    pub fn with_crop(mut self, crop: [f32; 4]) -> Self {
+        self.crop = crop;
+        self
+    }
+
+    pub fn is_cropped(&self) -> bool {
+        self.crop != Self::FULL_FRAME
+    }
+
+    /// Displayed width / height of a `width` x `height` target in this format.
+    pub fn display_aspect(&self, width: i32, height: i32) -> f32 {
+        width.max(1) as f32 * self.pixel_aspect / height.max(1) as f32
+    }
+}
+
 #[derive(Debug, Default)]
 pub struct Graph {
     next_node: u32,
     next_port: u32,
     nodes: HashMap<NodeId, Node>,
     edges: Vec<Edge>,
+    /// Index into `edges` of the edge driving each input endpoint (inputs take one edge).
+    drivers: HashMap<Endpoint, usize>,
+    /// Indices into `edges` leaving each output endpoint, in connection order.
+    consumers: HashMap<Endpoint, Vec<usize>>,
+    /// Branch format overrides set with `set_branch_format`.
+    formats: HashMap<NodeId, BranchFormat>,
+    /// Stable string handles (see `add_node_named`), both ways.
+    names: HashMap<String, NodeId>,
+    node_names: HashMap<NodeId, String>,
+    /// Per-input fallbacks set with `set_input_default`.
+    input_defaults: HashMap<Endpoint, InputDefault>,
+    /// Named groups (see `add_to_group`) and their members, in the order they joined.
+    groups: BTreeMap<String, Vec<NodeId>>,
 }
 
 impl Graph {
@@ -128,11 +308,42 @@ impl Graph {
     pub fn edges(&self) -> &[Edge] { &self.edges }
     pub fn node(&self, id: NodeId) -> Option<&Node> { self.nodes.get(&id) }
 
+    /// Edges into `node`, in the node's port order.
+    pub fn incoming(&self, node: NodeId) -> impl Iterator<Item = &Edge> + '_ {
+        self.port_endpoints(node, PortDir::In)
+            .filter_map(move |ep| self.drivers.get(&ep).map(|&i| &self.edges[i]))
+    }
+
+    /// Edges out of `node`, in port order, then connection order.
+    pub fn outgoing(&self, node: NodeId) -> impl Iterator<Item = &Edge> + '_ {
+        self.port_endpoints(node, PortDir::Out)
+            .filter_map(move |ep| self.consumers.get(&ep))
+            .flat_map(move |ids| ids.iter().map(move |&i| &self.edges[i]))
+    }
+
+    /// The output endpoint feeding an input endpoint, if connected.
+    pub fn driver_of(&self, input: Endpoint) -> Option<Endpoint> {
+        self.drivers.get(&input).map(|&i| self.edges[i].from)
+    }
+
+    /// Input endpoints fed by an output endpoint.
+    pub fn consumers_of(&self, output: Endpoint) -> impl Iterator<Item = Endpoint> + '_ {
+        self.consumers.get(&output).into_iter()
+            .flat_map(move |ids| ids.iter().map(move |&i| self.edges[i].to))
+    }
+
+    fn port_endpoints(&self, node: NodeId, dir: PortDir) -> impl Iterator<Item = Endpoint> + '_ {
+        self.nodes.get(&node).into_iter()
+            .flat_map(|n| n.ports.iter())
+            .filter(move |p| p.dir == dir)
+            .map(move |p| Endpoint { node, port: p.id, dir })
+    }
+
     pub fn add_node(&mut self, kind: NodeKind) -> NodeId {
         let id = NodeId(self.next_node);
         self.next_node += 1;
 
-        let ports = match kind {
+        let mut ports = match kind {
             // ShaderMix2: 2 custom-shader inputs "a" and "b"
             NodeKind::ShaderMix2 => vec![
                 self.new_port("a", PortDir::In),
@@ -154,6 +365,13 @@ impl Graph {
                 self.new_port("d", PortDir::In),
                 self.new_port("out", PortDir::Out),
             ],
+            // KeyMix: background "a", foreground "b", optional garbage matte "matte"
+            NodeKind::KeyMix => vec![
+                self.new_port("a", PortDir::In),
+                self.new_port("b", PortDir::In),
+                self.new_port("matte", PortDir::In),
+                self.new_port("out", PortDir::Out),
+            ],
             NodeKind::MatrixMix4 => vec![
                 self.new_port("in0", PortDir::In),
                 self.new_port("in1", PortDir::In),
@@ -176,14 +394,112 @@ impl Graph {
             },
         };
 
+        for p in ports.iter_mut().filter(|p| p.dir == PortDir::In) {
+            p.multiplicity = kind.input_multiplicity(p.name);
+        }
         self.nodes.insert(id, Node { id, kind, ports });
         id
     }
 
+    /// Add a node with a stable string handle.
+    ///
+    /// `NodeId`s are assigned in creation order, so they change when a patch is rebuilt; names
+    /// do not. External tooling (OSC mappings, scene files, the parameter registry) should refer
+    /// to named nodes by name. See [`set_node_name`](Self::set_node_name) for what a valid
+    /// name is.
+    pub fn add_node_named(&mut self, kind: NodeKind, name: &str) -> Result<NodeId, EngineError> {
+        self.check_node_name(name, None)?;
+        let id = self.add_node(kind);
+        self.names.insert(name.to_string(), id);
+        self.node_names.insert(id, name.to_string());
+        Ok(id)
+    }
+
+    /// Name (or rename) an existing node. Names are unique within the graph, non-empty, free of
+    /// `/` and whitespace (they appear in OSC and registry paths) and not all digits (so they
+    /// never read as a `NodeId`).
+    pub fn set_node_name(&mut self, node: NodeId, name: &str) -> Result<(), EngineError> {
+        if !self.nodes.contains_key(&node) {
+            return Err(EngineError::other("set_node_name: node not found"));
+        }
+        self.check_node_name(name, Some(node))?;
+        self.clear_node_name(node);
+        self.names.insert(name.to_string(), node);
+        self.node_names.insert(node, name.to_string());
+        Ok(())
+    }
+
+    pub fn clear_node_name(&mut self, node: NodeId) {
+        if let Some(name) = self.node_names.remove(&node) {
+            self.names.remove(&name);
+        }
+    }
+
+    pub fn node_by_name(&self, name: &str) -> Option<NodeId> {
+        self.names.get(name).copied()
+    }
+
+    pub fn node_name(&self, node: NodeId) -> Option<&str> {
+        self.node_names.get(&node).map(String::as_str)
+    }
+
+    /// How external tooling should refer to `node`: its name, or else its id in decimal.
+    pub fn handle(&self, node: NodeId) -> String {
+        self.node_name(node).map_or_else(|| node.0.to_string(), str::to_string)
+    }
+
+    /// Resolve a [`handle`](Self::handle): a node name, or the decimal id of an existing node.
+    pub fn resolve_handle(&self, handle: &str) -> Option<NodeId> {
+        self.node_by_name(handle).or_else(|| {
+            let id = NodeId(handle.parse().ok()?);
+            self.nodes.contains_key(&id).then_some(id)
+        })
+    }
+
+    fn check_node_name(&self, name: &str, node: Option<NodeId>) -> Result<(), EngineError> {
+        if name.is_empty()
+            || name.contains('/')
+            || name.chars().any(char::is_whitespace)
+            || name.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(EngineError::Other(format!("invalid node name {name:?}")));
+        }
+        match self.names.get(name) {
+            Some(owner) if Some(*owner) != node => {
+                Err(EngineError::Other(format!("node name {name:?} already used by node {}", owner.0)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Add a `ShaderPass` with `outputs` colour outputs (1..=[`MAX_NODE_OUTPUTS`]), for multiple
+    /// render targets. Ports are `out`, `out1`, `out2`, `out3`; fragment output
+    /// `layout(location = n)` feeds port n.
+    pub fn add_node_with_outputs(
+        &mut self,
+        kind: NodeKind,
+        outputs: usize,
+    ) -> Result<NodeId, EngineError> {
+        if kind != NodeKind::ShaderPass {
+            return Err(EngineError::other("add_node_with_outputs: only ShaderPass supports MRT"));
+        }
+        if !(1..=MAX_NODE_OUTPUTS).contains(&outputs) {
+            return Err(EngineError::Other(format!(
+                "add_node_with_outputs: {outputs} outputs (expected 1..={MAX_NODE_OUTPUTS})"
+            )));
+        }
+        let id = self.add_node(kind);
+        for name in &OUTPUT_PORT_NAMES[1..outputs] {
+            let port = self.new_port(name, PortDir::Out);
+            self.nodes.get_mut(&id).expect("node just added").ports.push(port);
+        }
+        Ok(id)
+    }
+
     fn new_port(&mut self, name: &'static str, dir: PortDir) -> Port {
         let id = PortId(self.next_port);
         self.next_port += 1;
-        Port { id, name, dir }
+        Port { id, name, dir, multiplicity: Multiplicity::Optional }
     }
 
     pub fn find_port(&self, node: NodeId, name: &str, dir: PortDir) -> Option<PortId> {
@@ -193,6 +509,17 @@ impl Graph {
     }
 
     pub fn connect(&mut self, from: Endpoint, to: Endpoint) -> Result<(), EngineError> {
+        self.add_edge(from, to, false)
+    }
+
+    /// Connect with a guaranteed one-frame delay: `to` reads what `from` rendered on the previous
+    /// frame. This is the only way to close a feedback loop (A → B → A); `compile` rejects cycles
+    /// made of plain edges.
+    pub fn connect_delayed(&mut self, from: Endpoint, to: Endpoint) -> Result<(), EngineError> {
+        self.add_edge(from, to, true)
+    }
+
+    fn add_edge(&mut self, from: Endpoint, to: Endpoint, delayed: bool) -> Result<(), EngineError> {
         if from.dir != PortDir::Out {
             return Err(EngineError::other("connect: from endpoint must be Out"));
         }
@@ -202,16 +529,20 @@ impl Graph {
         if !self.nodes.contains_key(&from.node) || !self.nodes.contains_key(&to.node) {
             return Err(EngineError::other("connect: node not found"));
         }
+        // The endpoint's `dir` must match the port's own direction, or an input could drive.
         let from_ok = self.nodes.get(&from.node)
-            .and_then(|n| n.ports.iter().find(|p| p.id == from.port)).is_some();
+            .and_then(|n| n.ports.iter().find(|p| p.id == from.port && p.dir == PortDir::Out)).is_some();
         if !from_ok { return Err(EngineError::other("connect: from port not found on node")); }
         let to_ok = self.nodes.get(&to.node)
-            .and_then(|n| n.ports.iter().find(|p| p.id == to.port)).is_some();
+            .and_then(|n| n.ports.iter().find(|p| p.id == to.port && p.dir == PortDir::In)).is_some();
         if !to_ok { return Err(EngineError::other("connect: to port not found on node")); }
-        if self.edges.iter().any(|e| e.to == to) {
+        if self.drivers.contains_key(&to) {
             return Err(EngineError::other("connect: input already connected"));
         }
-        self.edges.push(Edge { from, to });
+        let idx = self.edges.len();
+        self.edges.push(Edge { from, to, delayed });
+        self.drivers.insert(to, idx);
+        self.consumers.entry(from).or_default().push(idx);
         Ok(())
     }
 
@@ -220,38 +551,261 @@ impl Graph {
         from_node: NodeId, from_port: &str,
         to_node: NodeId,   to_port: &str,
     ) -> Result<(), EngineError> {
+        let (from, to) = self.named_endpoints(from_node, from_port, to_node, to_port)?;
+        self.connect(from, to)
+    }
+
+    /// Name-based [`connect_delayed`](Self::connect_delayed).
+    pub fn connect_delayed_named(
+        &mut self,
+        from_node: NodeId, from_port: &str,
+        to_node: NodeId,   to_port: &str,
+    ) -> Result<(), EngineError> {
+        let (from, to) = self.named_endpoints(from_node, from_port, to_node, to_port)?;
+        self.connect_delayed(from, to)
+    }
+
+    fn named_endpoints(
+        &self,
+        from_node: NodeId, from_port: &str,
+        to_node: NodeId,   to_port: &str,
+    ) -> Result<(Endpoint, Endpoint), EngineError> {
         let from_pid = self.find_port(from_node, from_port, PortDir::Out)
             .ok_or_else(|| EngineError::other("connect_named: from port not found"))?;
         let to_pid = self.find_port(to_node, to_port, PortDir::In)
             .ok_or_else(|| EngineError::other("connect_named: to port not found"))?;
-        self.connect(
+        Ok((
             Endpoint { node: from_node, port: from_pid, dir: PortDir::Out },
             Endpoint { node: to_node,   port: to_pid,   dir: PortDir::In  },
-        )
+        ))
+    }
+
+    /// Choose what input `port` of `node` samples while nothing drives it. Only `Optional` and
+    /// `Variadic` inputs take a default; an unconnected `Required` input fails `compile`.
+    pub fn set_input_default(
+        &mut self,
+        node: NodeId,
+        port: &str,
+        default: InputDefault,
+    ) -> Result<(), EngineError> {
+        let n = self.nodes.get(&node)
+            .ok_or_else(|| EngineError::other("set_input_default: node not found"))?;
+        let p = n.ports.iter().find(|p| p.dir == PortDir::In && p.name == port)
+            .ok_or_else(|| EngineError::Other(format!("set_input_default: no input '{port}'")))?;
+        if p.multiplicity == Multiplicity::Required {
+            return Err(EngineError::Other(format!(
+                "set_input_default: input '{port}' of {:?} is required", n.kind
+            )));
+        }
+        let at = Endpoint { node, port: p.id, dir: PortDir::In };
+        self.input_defaults.insert(at, default);
+        Ok(())
+    }
+
+    pub fn clear_input_default(&mut self, node: NodeId, port: &str) {
+        if let Some(pid) = self.find_port(node, port, PortDir::In) {
+            self.input_defaults.remove(&Endpoint { node, port: pid, dir: PortDir::In });
+        }
+    }
+
+    /// The default set on `input`, if any (unset inputs read [`InputDefault::Black`]).
+    pub fn input_default(&self, input: Endpoint) -> Option<InputDefault> {
+        self.input_defaults.get(&input).copied()
+    }
+
+    /// Tag `node` into the group `group`, creating it. A node may be in several groups; group
+    /// names follow the node name rules, but live in their own namespace.
+    ///
+    /// Groups carry no meaning for `compile`: runtimes resolve collective controls (bypass,
+    /// opacity) over [`group_members`](Self::group_members).
+    pub fn add_to_group(&mut self, node: NodeId, group: &str) -> Result<(), EngineError> {
+        if !self.nodes.contains_key(&node) {
+            return Err(EngineError::other("add_to_group: node not found"));
+        }
+        if group.is_empty()
+            || group.contains('/')
+            || group.chars().any(char::is_whitespace)
+            || group.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(EngineError::Other(format!("invalid group name {group:?}")));
+        }
+        let members = self.groups.entry(group.to_string()).or_default();
+        if !members.contains(&node) {
+            members.push(node);
+        }
+        Ok(())
+    }
+
+    /// Take `node` out of `group`; the group goes away with its last member. Returns whether
+    /// the node was a member.
+    pub fn remove_from_group(&mut self, node: NodeId, group: &str) -> bool {
+        let Some(members) = self.groups.get_mut(group) else { return false };
+        let before = members.len();
+        members.retain(|&m| m != node);
+        let removed = members.len() != before;
+        if members.is_empty() {
+            self.groups.remove(group);
+        }
+        removed
+    }
+
+    /// Group names, sorted.
+    pub fn groups(&self) -> impl Iterator<Item = &str> {
+        self.groups.keys().map(String::as_str)
+    }
+
+    /// Members of `group` (empty if there is no such group).
+    pub fn group_members(&self, group: &str) -> &[NodeId] {
+        self.groups.get(group).map_or(&[], Vec::as_slice)
+    }
+
+    /// The groups `node` is in, sorted.
+    pub fn node_groups(&self, node: NodeId) -> impl Iterator<Item = &str> + '_ {
+        self.groups.iter().filter(move |(_, m)| m.contains(&node)).map(|(g, _)| g.as_str())
+    }
+
+    /// Process `node` and, unless they set their own, everything downstream of it in `format`.
+    ///
+    /// Setting `BranchFormat::default()` conforms a branch back to the frame size, e.g. on the
+    /// mixer where a 4:3 camera pipeline meets the main program.
+    pub fn set_branch_format(&mut self, node: NodeId, format: BranchFormat) -> Result<(), EngineError> {
+        if !self.nodes.contains_key(&node) {
+            return Err(EngineError::other("set_branch_format: node not found"));
+        }
+        self.formats.insert(node, format);
+        Ok(())
+    }
+
+    pub fn clear_branch_format(&mut self, node: NodeId) {
+        self.formats.remove(&node);
+    }
+
+    /// The format set on `node` itself (not the one it inherits; see [`Plan::format`]).
+    pub fn branch_format(&self, node: NodeId) -> Option<&BranchFormat> {
+        self.formats.get(&node)
+    }
+
+    /// Resolve inherited formats in schedule order: a node without its own format takes the
+    /// one of the first input (port order) fed over a plain edge by a formatted producer.
+    fn resolve_formats(&self, order: &[NodeId]) -> HashMap<NodeId, BranchFormat> {
+        let mut resolved: HashMap<NodeId, BranchFormat> = HashMap::new();
+        for &id in order {
+            let format = self.formats.get(&id).copied().or_else(|| {
+                self.incoming(id)
+                    .filter(|e| !e.delayed)
+                    .find_map(|e| resolved.get(&e.from.node))
+                    .map(|f| BranchFormat { crop: BranchFormat::FULL_FRAME, ..*f })
+            });
+            if let Some(f) = format {
+                resolved.insert(id, f);
+            }
+        }
+        resolved
     }
 
     pub fn compile(&self) -> Result<Plan, EngineError> {
-        for n in self.nodes.values() {
-            if n.kind.class() == NodeClass::Output {
-                let in_port = n.ports.iter().find(|p| p.dir == PortDir::In).map(|p| p.id);
-                if let Some(pid) = in_port {
-                    let to = Endpoint { node: n.id, port: pid, dir: PortDir::In };
-                    if !self.edges.iter().any(|e| e.to == to) {
-                        return Err(EngineError::other("compile: output input not connected"));
-                    }
+        self.check_inputs()?;
+        let nodes = self.schedule()?;
+        let formats = self.resolve_formats(&nodes);
+        Ok(Plan { nodes, edges: self.edges.clone(), formats })
+    }
+
+    /// Every `Required` input driven and every node with `Variadic` inputs fed on at least one.
+    /// Nodes are checked in id order so the first error reported is deterministic.
+    fn check_inputs(&self) -> Result<(), EngineError> {
+        let mut ids: Vec<NodeId> = self.nodes.keys().copied().collect();
+        ids.sort_by_key(|id| id.0);
+        for id in ids {
+            let n = &self.nodes[&id];
+            let driven = |p: &Port| {
+                self.drivers.contains_key(&Endpoint { node: id, port: p.id, dir: PortDir::In })
+            };
+            let inputs = || n.ports.iter().filter(|p| p.dir == PortDir::In);
+            if let Some(p) = inputs().find(|p| p.multiplicity == Multiplicity::Required && !driven(p)) {
+                return Err(EngineError::Other(format!(
+                    "compile: node {} ({:?}) input '{}' is not connected",
+                    self.handle(id), n.kind, p.name
+                )));
+            }
+            let mut variadic = inputs().filter(|p| p.multiplicity == Multiplicity::Variadic).peekable();
+            if variadic.peek().is_some() && !variadic.any(driven) {
+                return Err(EngineError::Other(format!(
+                    "compile: node {} ({:?}) needs at least one connected input",
+                    self.handle(id), n.kind
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Topological order over plain (non-delayed) edges, lowest `NodeId` first among ready
+    /// nodes so the order is deterministic. Fails if plain edges form a cycle.
+    fn schedule(&self) -> Result<Vec<NodeId>, EngineError> {
+        let mut pending: HashMap<NodeId, usize> = self.nodes.keys().map(|&id| (id, 0)).collect();
+        for e in self.edges.iter().filter(|e| !e.delayed) {
+            *pending.entry(e.to.node).or_default() += 1;
+        }
+        let mut ready: BTreeSet<u32> = pending.iter()
+            .filter(|(_, &n)| n == 0).map(|(id, _)| id.0).collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(id) = ready.pop_first() {
+            let id = NodeId(id);
+            order.push(id);
+            for e in self.outgoing(id).filter(|e| !e.delayed) {
+                let n = pending.get_mut(&e.to.node).expect("edge endpoints are graph nodes");
+                *n -= 1;
+                if *n == 0 {
+                    ready.insert(e.to.node.0);
                 }
             }
         }
-        let mut nodes: Vec<NodeId> = self.nodes.keys().copied().collect();
-        nodes.sort_by_key(|id| id.0);
-        Ok(Plan { nodes, edges: self.edges.clone() })
+        if order.len() < self.nodes.len() {
+            let mut stuck: Vec<u32> = pending.iter().filter(|(_, &n)| n > 0).map(|(id, _)| id.0).collect();
+            stuck.sort_unstable();
+            return Err(EngineError::Other(format!(
+                "compile: cycle through nodes {stuck:?} (break feedback loops with connect_delayed)"
+            )));
+        }
+        Ok(order)
     }
 }
 
+/// Compiled schedule: node order plus the edges between them.
+///
+/// `nodes` is topologically sorted over plain edges, so every producer precedes its consumers;
+/// delayed edges are exempt and read the producer's previous frame.
+///
+/// Fan-out contract: every node is listed in `nodes` exactly once, so a runtime evaluates each
+/// node at most once per frame. An output port feeding several inputs renders once and hands
+/// the same result to every consumer.
 #[derive(Debug, Clone)]
 pub struct Plan {
     pub nodes: Vec<NodeId>,
     pub edges: Vec<Edge>,
+    /// Resolved branch formats; nodes missing here render at the frame size.
+    pub formats: HashMap<NodeId, BranchFormat>,
+}
+
+impl Plan {
+    /// The format `node` renders in, set on it or inherited from upstream.
+    pub fn format(&self, node: NodeId) -> Option<&BranchFormat> {
+        self.formats.get(&node)
+    }
+
+    /// Number of inputs fed by `node`'s outputs.
+    pub fn fan_out(&self, node: NodeId) -> usize {
+        self.edges.iter().filter(|e| e.from.node == node).count()
+    }
+
+    /// Check the schedule invariants. `Graph::compile` always upholds them; this is for plans
+    /// built or edited by hand.
+    pub fn validate(&self) -> Result<(), EngineError> {
+        let mut seen = std::collections::HashSet::new();
+        if let Some(dup) = self.nodes.iter().find(|n| !seen.insert(**n)) {
+            return Err(EngineError::Other(format!("plan: node {} listed more than once", dup.0)));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -284,4 +838,245 @@ mod tests {
         let plan = g.compile().unwrap();
         assert_eq!(plan.edges.len(), 3);
     }
+
+    #[test]
+    fn connect_checks_port_direction() {
+        let mut g = Graph::new();
+        let a    = g.add_node(NodeKind::ShaderPass);
+        let b    = g.add_node(NodeKind::ShaderPass);
+        let a_in = g.find_port(a, "in", PortDir::In).unwrap();
+        let b_in = g.find_port(b, "in", PortDir::In).unwrap();
+        // An input port passed off as an output must not drive anything.
+        let from = Endpoint { node: a, port: a_in, dir: PortDir::Out };
+        let to   = Endpoint { node: b, port: b_in, dir: PortDir::In };
+        assert!(g.connect(from, to).is_err());
+        assert!(g.edges().is_empty());
+    }
+
+    #[test]
+    fn named_nodes_resolve_by_handle() {
+        let mut g = Graph::new();
+        let src = g.add_node(NodeKind::ShaderSource);
+        let cam = g.add_node_named(NodeKind::TextureInputPass, "cam1").unwrap();
+        assert_eq!(g.node_by_name("cam1"), Some(cam));
+        assert_eq!(g.handle(cam), "cam1");
+        assert_eq!(g.handle(src), src.0.to_string());
+        assert_eq!(g.resolve_handle(&src.0.to_string()), Some(src));
+        assert_eq!(g.resolve_handle("99"), None);
+
+        for bad in ["", "a/b", "two words", "42", "cam1"] {
+            assert!(g.add_node_named(NodeKind::ShaderPass, bad).is_err(), "{bad:?}");
+        }
+        g.set_node_name(cam, "cam_left").unwrap();
+        assert_eq!(g.node_by_name("cam1"), None);
+        g.set_node_name(src, "cam1").unwrap();
+        assert_eq!(g.resolve_handle("cam1"), Some(src));
+        assert!(g.set_node_name(src, "cam_left").is_err());
+    }
+
+    #[test]
+    fn mrt_pass_gets_extra_output_ports() {
+        let mut g = Graph::new();
+        let pass = g.add_node_with_outputs(NodeKind::ShaderPass, 3).unwrap();
+        for name in ["out", "out1", "out2"] {
+            assert!(g.find_port(pass, name, PortDir::Out).is_some(), "{name}");
+        }
+        assert!(g.find_port(pass, "out3", PortDir::Out).is_none());
+        assert!(g.add_node_with_outputs(NodeKind::ShaderPass, 5).is_err());
+        assert!(g.add_node_with_outputs(NodeKind::Crossfade, 2).is_err());
+    }
+
+    #[test]
+    fn key_mix_matte_is_optional() {
+        let mut g = Graph::new();
+        let bg  = g.add_node(NodeKind::ShaderSource);
+        let fg  = g.add_node(NodeKind::ShaderSource);
+        let key = g.add_node(NodeKind::KeyMix);
+        let out = g.add_node(NodeKind::PixelsOut);
+        assert!(g.find_port(key, "matte", PortDir::In).is_some());
+        g.connect_named(bg,  "out", key, "a").unwrap();
+        g.connect_named(fg,  "out", key, "b").unwrap();
+        g.connect_named(key, "out", out, "in").unwrap();
+        assert!(g.compile().is_ok());
+    }
+
+    #[test]
+    fn compile_checks_input_multiplicity() {
+        let mut g = Graph::new();
+        let a   = g.add_node(NodeKind::ShaderSource);
+        let mix = g.add_node_named(NodeKind::Crossfade, "xf").unwrap();
+        let out = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(a,   "out", mix, "a").unwrap();
+        g.connect_named(mix, "out", out, "in").unwrap();
+        let err = g.compile().unwrap_err().to_string();
+        assert!(err.contains("node xf (Crossfade) input 'b' is not connected"), "{err}");
+        g.connect_delayed_named(mix, "out", mix, "b").unwrap();
+        assert!(g.compile().is_ok());
+
+        let mut g = Graph::new();
+        let a   = g.add_node(NodeKind::ShaderSource);
+        let mat = g.add_node(NodeKind::MatrixMix4);
+        let out = g.add_node(NodeKind::PixelsOut);
+        let in2 = g.node(mat).unwrap().ports.iter().find(|p| p.name == "in2").unwrap();
+        assert_eq!(in2.multiplicity, Multiplicity::Variadic);
+        g.connect_named(mat, "out", out, "in").unwrap();
+        assert!(g.compile().unwrap_err().to_string().contains("at least one connected input"));
+        g.connect_named(a, "out", mat, "in2").unwrap();
+        assert!(g.compile().is_ok());
+    }
+
+    #[test]
+    fn input_defaults_only_on_optional_inputs() {
+        let mut g = Graph::new();
+        let key = g.add_node(NodeKind::KeyMix);
+        assert!(g.set_input_default(key, "a", InputDefault::White).is_err());
+        assert!(g.set_input_default(key, "nope", InputDefault::White).is_err());
+        g.set_input_default(key, "matte", InputDefault::White).unwrap();
+        let matte = Endpoint {
+            node: key,
+            port: g.find_port(key, "matte", PortDir::In).unwrap(),
+            dir:  PortDir::In,
+        };
+        assert_eq!(g.input_default(matte), Some(InputDefault::White));
+        g.clear_input_default(key, "matte");
+        assert_eq!(g.input_default(matte), None);
+        assert_eq!(InputDefault::default().rgba(), [0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn adjacency_queries() {
+        let mut g = Graph::new();
+        let a   = g.add_node(NodeKind::ShaderSource);
+        let b   = g.add_node(NodeKind::ShaderSource);
+        let mix = g.add_node(NodeKind::Crossfade);
+        let out = g.add_node(NodeKind::PixelsOut);
+        // Connect "b" first: incoming() still follows port order.
+        g.connect_named(b,   "out", mix, "b").unwrap();
+        g.connect_named(a,   "out", mix, "a").unwrap();
+        g.connect_named(mix, "out", out, "in").unwrap();
+
+        let from: Vec<NodeId> = g.incoming(mix).map(|e| e.from.node).collect();
+        assert_eq!(from, vec![a, b]);
+        assert_eq!(g.outgoing(mix).map(|e| e.to.node).collect::<Vec<_>>(), vec![out]);
+        assert_eq!(g.incoming(a).count(), 0);
+
+        let b_in = Endpoint { node: mix, port: g.find_port(mix, "b", PortDir::In).unwrap(), dir: PortDir::In };
+        let b_out = Endpoint { node: b, port: g.find_port(b, "out", PortDir::Out).unwrap(), dir: PortDir::Out };
+        assert_eq!(g.driver_of(b_in), Some(b_out));
+        assert_eq!(g.consumers_of(b_out).collect::<Vec<_>>(), vec![b_in]);
+        assert!(g.connect_named(a, "out", mix, "b").is_err());
+    }
+
+    #[test]
+    fn fan_out_schedules_upstream_once() {
+        let mut g = Graph::new();
+        let src  = g.add_node(NodeKind::ShaderSource);
+        let pass = g.add_node(NodeKind::ShaderPass);
+        let mix  = g.add_node(NodeKind::Crossfade);
+        let out  = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(src,  "out", pass, "in").unwrap();
+        g.connect_named(pass, "out", mix,  "a").unwrap();
+        g.connect_named(pass, "out", mix,  "b").unwrap();
+        g.connect_named(mix,  "out", out,  "in").unwrap();
+
+        let mut plan = g.compile().unwrap();
+        assert_eq!(plan.nodes.iter().filter(|n| **n == pass).count(), 1);
+        assert_eq!(plan.fan_out(pass), 2);
+        assert!(plan.validate().is_ok());
+
+        plan.nodes.push(pass);
+        assert!(plan.validate().is_err());
+    }
+
+    #[test]
+    fn cycles_need_a_delayed_edge() {
+        let mut g = Graph::new();
+        let src = g.add_node(NodeKind::ShaderSource);
+        let mix = g.add_node(NodeKind::Crossfade);
+        let fx  = g.add_node(NodeKind::ShaderPass);
+        let out = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(src, "out", mix, "a").unwrap();
+        g.connect_named(mix, "out", fx,  "in").unwrap();
+        g.connect_named(fx,  "out", out, "in").unwrap();
+
+        let mut plain = Graph::new();
+        for kind in [NodeKind::ShaderSource, NodeKind::Crossfade, NodeKind::ShaderPass, NodeKind::PixelsOut] {
+            plain.add_node(kind);
+        }
+        plain.connect_named(src, "out", mix, "a").unwrap();
+        plain.connect_named(mix, "out", fx,  "in").unwrap();
+        plain.connect_named(fx,  "out", out, "in").unwrap();
+        plain.connect_named(fx,  "out", mix, "b").unwrap();
+        let err = plain.compile().unwrap_err().to_string();
+        assert!(err.contains("cycle") && err.contains("connect_delayed"), "{err}");
+
+        // fx -> mix.b closes the loop A -> B -> A through a delayed edge.
+        g.connect_delayed_named(fx, "out", mix, "b").unwrap();
+        let plan = g.compile().unwrap();
+        assert_eq!(plan.nodes, vec![src, mix, fx, out]);
+        assert!(plan.edges.iter().any(|e| e.delayed && e.to.node == mix));
+    }
+
+    #[test]
+    fn nodes_join_and_leave_named_groups() {
+        let mut g = Graph::new();
+        let a = g.add_node(NodeKind::ShaderSource);
+        let b = g.add_node(NodeKind::ShaderPass);
+        g.add_to_group(a, "drums").unwrap();
+        g.add_to_group(b, "drums").unwrap();
+        g.add_to_group(a, "drums").unwrap();
+        g.add_to_group(b, "fx").unwrap();
+        assert_eq!(g.group_members("drums"), &[a, b]);
+        assert_eq!(g.node_groups(b).collect::<Vec<_>>(), ["drums", "fx"]);
+        assert!(g.add_to_group(a, "two words").is_err());
+        assert!(g.add_to_group(NodeId(99), "fx").is_err());
+
+        assert!(g.remove_from_group(b, "fx"));
+        assert!(!g.remove_from_group(b, "fx"));
+        assert_eq!(g.groups().collect::<Vec<_>>(), ["drums"]);
+        assert!(g.group_members("fx").is_empty());
+    }
+
+    #[test]
+    fn plan_order_is_topological_not_by_id() {
+        let mut g = Graph::new();
+        let out  = g.add_node(NodeKind::PixelsOut);
+        let pass = g.add_node(NodeKind::ShaderPass);
+        let src  = g.add_node(NodeKind::ShaderSource);
+        g.connect_named(src,  "out", pass, "in").unwrap();
+        g.connect_named(pass, "out", out,  "in").unwrap();
+        assert_eq!(g.compile().unwrap().nodes, vec![src, pass, out]);
+    }
+
+    #[test]
+    fn branch_format_flows_downstream_until_conformed() {
+        let mut g = Graph::new();
+        let cam   = g.add_node(NodeKind::ShaderSource);
+        let grade = g.add_node(NodeKind::ShaderPass);
+        let main  = g.add_node(NodeKind::ShaderSource);
+        let mix   = g.add_node(NodeKind::Crossfade);
+        let out   = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(cam,   "out", grade, "in").unwrap();
+        g.connect_named(grade, "out", mix,   "b").unwrap();
+        g.connect_named(main,  "out", mix,   "a").unwrap();
+        g.connect_named(mix,   "out", out,   "in").unwrap();
+
+        let sd = BranchFormat::sized(640, 480).with_pixel_aspect(0.9).with_crop([0.1, 0.0, 0.8, 1.0]);
+        g.set_branch_format(cam, sd).unwrap();
+        let plan = g.compile().unwrap();
+        assert_eq!(plan.format(cam), Some(&sd));
+        // Inherited downstream without the crop.
+        let grade_fmt = plan.format(grade).unwrap();
+        assert_eq!(grade_fmt.size, Some((640, 480)));
+        assert!(!grade_fmt.is_cropped());
+        // The mixer follows its first formatted input until it is conformed explicitly.
+        assert_eq!(plan.format(mix).unwrap().size, Some((640, 480)));
+        assert_eq!(plan.format(main), None);
+
+        g.set_branch_format(mix, BranchFormat::default()).unwrap();
+        let plan = g.compile().unwrap();
+        assert_eq!(plan.format(mix).unwrap().size, None);
+        assert_eq!(plan.format(out).unwrap().size, None);
+        assert!(g.set_branch_format(NodeId(99), sd).is_err());
+    }
 }
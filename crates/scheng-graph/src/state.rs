@@ -159,7 +159,7 @@ impl BridgeState {
         if !self.nodes.contains_key(node_id) {
             return Err(format!("node '{}' not found", node_id));
         }
-        self.shaders.mix.insert(node_id.to_string(), scheng_runtime::MixerParams { mix });
+        self.shaders.mix.insert(node_id.to_string(), scheng_runtime::MixerParams::new(mix));
         Ok(())
     }
 
@@ -169,7 +169,7 @@ impl BridgeState {
         }
         self.shaders.matrix.insert(
             node_id.to_string(),
-            scheng_runtime::MatrixMixParams { weights },
+            scheng_runtime::MatrixMixParams::new(weights),
         );
         Ok(())
     }
@@ -0,0 +1,182 @@
+//! A ready-made event loop for simple hosts.
+//!
+//! [`run_app`] owns the window, the surface and `ControlFlow`; the host only implements [`App`].
+//! Hosts that need their own event handling (multiple windows, custom redraw policy) keep using
+//! [`WindowGl`] directly.
+
+use std::time::Instant;
+
+use glow::HasContext;
+use scheng_runtime_glow::{run_seed, EngineError, FrameCtx};
+use winit::dpi::{LogicalSize, Size};
+use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
+use winit::event_loop::{ControlFlow, EventLoop};
+
+use crate::WindowGl;
+
+/// Window options for [`run_app`].
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub title: String,
+    pub size: Size,
+    pub gl_version: Option<(u8, u8)>,
+    pub vsync: bool,
+    /// Close the window on Escape (after [`App::key`] has seen it).
+    pub exit_on_escape: bool,
+}
+
+impl AppConfig {
+    /// 960x540 logical, driver-default GL version, vsync on, Escape quits.
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            size: LogicalSize::new(960.0, 540.0).into(),
+            gl_version: None,
+            vsync: true,
+            exit_on_escape: true,
+        }
+    }
+
+    pub fn with_size(mut self, size: impl Into<Size>) -> Self {
+        self.size = size.into();
+        self
+    }
+
+    pub fn with_gl_version(mut self, major: u8, minor: u8) -> Self {
+        self.gl_version = Some((major, minor));
+        self
+    }
+
+    pub fn with_vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+
+    pub fn with_exit_on_escape(mut self, exit: bool) -> Self {
+        self.exit_on_escape = exit;
+        self
+    }
+}
+
+/// Host callbacks driven by [`run_app`]. Only `render` is required.
+///
+/// Per frame the order is `update` then `render`; the default framebuffer is bound and the
+/// buffers are swapped after `render` returns.
+pub trait App {
+    /// Advance state for this frame (before any GL work).
+    fn update(&mut self, _frame: &FrameCtx) {}
+
+    /// Draw the frame into the default framebuffer. An error is printed and closes the app.
+    fn render(&mut self, gl: &glow::Context, frame: &FrameCtx) -> Result<(), EngineError>;
+
+    /// A key was pressed or released.
+    fn key(&mut self, _key: VirtualKeyCode, _state: ElementState) {}
+
+    /// The window was resized; the surface has already been resized. Sizes are never zero.
+    fn resize(&mut self, _gl: &glow::Context, _width: u32, _height: u32) {}
+
+    /// Checked once per frame; return true to close the window.
+    fn should_exit(&self) -> bool {
+        false
+    }
+
+    /// Release GL resources; called once, with the context still current, before exit.
+    fn exit(&mut self, _gl: &glow::Context) {}
+}
+
+/// Open a window, build the app with `init`, and run the event loop.
+///
+/// Only returns on setup errors (window/context creation or `init`); once the loop is running
+/// the process exits when the window closes, with code 1 if `render` failed.
+pub fn run_app<A, F>(config: AppConfig, init: F) -> Result<(), EngineError>
+where
+    A: App + 'static,
+    F: FnOnce(&glow::Context, &WindowGl) -> Result<A, EngineError>,
+{
+    let event_loop = EventLoop::new();
+    let (win, gl) = WindowGl::new(&event_loop, &config.title, config.size, config.gl_version)?;
+    win.set_vsync(config.vsync);
+
+    let mut app = init(&gl, &win)?;
+    let start = Instant::now();
+    let mut prev: Option<FrameCtx> = None;
+    let seed = run_seed();
+    let mut exited = false;
+
+    event_loop.run(move |event, _, control_flow| {
+        *control_flow = ControlFlow::Poll;
+
+        match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::CloseRequested => {
+                    shutdown(&mut app, &gl, &mut exited, control_flow, 0)
+                }
+                WindowEvent::Resized(size) => {
+                    win.resize(size);
+                    let (w, h) = win.size();
+                    app.resize(&gl, w, h);
+                }
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            virtual_keycode: Some(key),
+                            state,
+                            ..
+                        },
+                    ..
+                } => {
+                    app.key(key, state);
+                    if config.exit_on_escape
+                        && key == VirtualKeyCode::Escape
+                        && state == ElementState::Pressed
+                    {
+                        shutdown(&mut app, &gl, &mut exited, control_flow, 0);
+                    }
+                }
+                _ => {}
+            },
+            Event::MainEventsCleared => {
+                if app.should_exit() {
+                    shutdown(&mut app, &gl, &mut exited, control_flow, 0);
+                } else {
+                    win.request_redraw();
+                }
+            }
+            Event::RedrawRequested(_) if !exited => {
+                let (w, h) = win.size();
+                let (w, h, time) = (w as i32, h as i32, start.elapsed().as_secs_f32());
+                let frame = match prev {
+                    Some(p) => p.next(w, h, time),
+                    None => FrameCtx::new(w, h, time, 0).with_seed(seed),
+                };
+                prev = Some(frame);
+
+                app.update(&frame);
+                unsafe {
+                    gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+                    gl.viewport(0, 0, frame.width, frame.height);
+                }
+                let res = app.render(&gl, &frame).and_then(|()| win.swap_buffers());
+                if let Err(e) = res {
+                    eprintln!("[scheng-host-winit] render error: {e}");
+                    shutdown(&mut app, &gl, &mut exited, control_flow, 1);
+                }
+            }
+            _ => {}
+        }
+    })
+}
+
+fn shutdown<A: App>(
+    app: &mut A,
+    gl: &glow::Context,
+    exited: &mut bool,
+    control_flow: &mut ControlFlow,
+    code: i32,
+) {
+    if !*exited {
+        *exited = true;
+        app.exit(gl);
+        *control_flow = ControlFlow::ExitWithCode(code);
+    }
+}
@@ -1,18 +1,159 @@
 //! Host glue (policy layer).
 //!
-//! This crate will eventually provide winit + GL context creation helpers.
-//! It stays separate so the runtime can remain embed-friendly.
+//! winit + glutin window and GL context creation, shared by the examples and by hosts that do
+//! not need anything special from their window. It stays separate so the runtime can remain
+//! embed-friendly: `scheng-runtime-glow` only ever sees a `glow::Context`.
 
-pub struct Host;
+mod app;
 
-impl Host {
-    pub fn new() -> Self {
-        Self
-    }
+pub use app::{run_app, App, AppConfig};
+
+use std::ffi::CString;
+use std::num::NonZeroU32;
+
+use glutin::config::ConfigTemplateBuilder;
+use glutin::context::{
+    ContextApi, ContextAttributesBuilder, GlProfile, PossiblyCurrentContext, Version,
+};
+use glutin::display::GetGlDisplay;
+use glutin::prelude::*;
+use glutin::surface::{Surface, SurfaceAttributesBuilder, SwapInterval, WindowSurface};
+use glutin_winit::DisplayBuilder;
+use raw_window_handle::HasRawWindowHandle;
+use scheng_runtime_glow::EngineError;
+use winit::dpi::{PhysicalSize, Size};
+use winit::event_loop::EventLoopWindowTarget;
+use winit::window::{Window, WindowBuilder};
+
+/// A window with a current GL context and its surface.
+///
+/// The `glow::Context` is returned separately by [`WindowGl::new`] so render code can own it
+/// (and borrow it into the runtime) independently of the window.
+#[derive(Debug)]
+pub struct WindowGl {
+    pub window: Window,
+    pub surface: Surface<WindowSurface>,
+    pub context: PossiblyCurrentContext,
 }
 
-impl Default for Host {
-    fn default() -> Self {
-        Self::new()
+impl WindowGl {
+    /// Open a window titled `title` with a core-profile context and make it current.
+    ///
+    /// `size` may be logical or physical. `gl_version` requests a specific `(major, minor)`
+    /// version; `None` takes the driver's default (3.3+ core on every platform scheng targets).
+    /// The config with the most MSAA samples is picked, matching what the examples always did.
+    pub fn new<T>(
+        event_loop: &EventLoopWindowTarget<T>,
+        title: &str,
+        size: impl Into<Size>,
+        gl_version: Option<(u8, u8)>,
+    ) -> Result<(Self, glow::Context), EngineError> {
+        let window_builder = WindowBuilder::new().with_title(title).with_inner_size(size);
+        let template = ConfigTemplateBuilder::new()
+            .with_alpha_size(8)
+            .with_depth_size(0)
+            .with_stencil_size(0);
+
+        let (window, gl_config) = DisplayBuilder::new()
+            .with_window_builder(Some(window_builder))
+            .build(event_loop, template, |configs| {
+                configs
+                    .reduce(|a, b| {
+                        if b.num_samples() > a.num_samples() {
+                            b
+                        } else {
+                            a
+                        }
+                    })
+                    .unwrap()
+            })
+            .map_err(|e| EngineError::GlCreate(format!("DisplayBuilder.build: {e}")))?;
+        let window = window.ok_or_else(|| {
+            EngineError::GlCreate("DisplayBuilder did not create a window".into())
+        })?;
+
+        let gl_display = gl_config.display();
+        let raw_window_handle = window.raw_window_handle();
+        let api = ContextApi::OpenGl(gl_version.map(|(major, minor)| Version::new(major, minor)));
+        let context_attributes = ContextAttributesBuilder::new()
+            .with_profile(GlProfile::Core)
+            .with_context_api(api)
+            .build(Some(raw_window_handle));
+        let fallback_attributes = ContextAttributesBuilder::new()
+            .with_profile(GlProfile::Core)
+            .with_context_api(api)
+            .build(None);
+        let not_current = unsafe {
+            gl_display
+                .create_context(&gl_config, &context_attributes)
+                .or_else(|_| gl_display.create_context(&gl_config, &fallback_attributes))
+                .map_err(|e| EngineError::GlCreate(format!("create_context: {e}")))?
+        };
+
+        let (w, h) = nonzero_size(window.inner_size());
+        let attrs = SurfaceAttributesBuilder::<WindowSurface>::new().build(raw_window_handle, w, h);
+        let surface = unsafe {
+            gl_display
+                .create_window_surface(&gl_config, &attrs)
+                .map_err(|e| EngineError::GlCreate(format!("create_window_surface: {e}")))?
+        };
+        let context = not_current
+            .make_current(&surface)
+            .map_err(|e| EngineError::GlCreate(format!("make_current: {e}")))?;
+
+        let gl = unsafe {
+            glow::Context::from_loader_function(|s| {
+                let s = CString::new(s).unwrap();
+                gl_display.get_proc_address(s.as_c_str()) as *const _
+            })
+        };
+
+        Ok((
+            Self {
+                window,
+                surface,
+                context,
+            },
+            gl,
+        ))
+    }
+
+    /// The window's drawable size, never zero.
+    pub fn size(&self) -> (u32, u32) {
+        let (w, h) = nonzero_size(self.window.inner_size());
+        (w.get(), h.get())
     }
+
+    /// Resize the surface; call on `WindowEvent::Resized`. Zero sizes (minimized) are clamped.
+    pub fn resize(&self, size: PhysicalSize<u32>) {
+        let (w, h) = nonzero_size(size);
+        self.surface.resize(&self.context, w, h);
+    }
+
+    pub fn swap_buffers(&self) -> Result<(), EngineError> {
+        self.surface
+            .swap_buffers(&self.context)
+            .map_err(|e| EngineError::GlCreate(format!("swap_buffers: {e}")))
+    }
+
+    /// Sync swaps to the display refresh (best effort; some drivers ignore it).
+    pub fn set_vsync(&self, vsync: bool) {
+        let interval = if vsync {
+            SwapInterval::Wait(NonZeroU32::new(1).unwrap())
+        } else {
+            SwapInterval::DontWait
+        };
+        let _ = self.surface.set_swap_interval(&self.context, interval);
+    }
+
+    pub fn request_redraw(&self) {
+        self.window.request_redraw();
+    }
+}
+
+fn nonzero_size(size: PhysicalSize<u32>) -> (NonZeroU32, NonZeroU32) {
+    (
+        NonZeroU32::new(size.width.max(1)).unwrap(),
+        NonZeroU32::new(size.height.max(1)).unwrap(),
+    )
 }
@@ -2,16 +2,22 @@ use serde::{Deserialize, Serialize};
 use std::{
     ffi::OsStr,
     io::{self, Read},
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
-    process::{Child, Command, Stdio},
+    process::{Child, ChildStdout, Command, Stdio},
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc, Mutex,
+        Arc, Mutex, MutexGuard, PoisonError,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+pub mod pool;
+pub mod thumbnail;
+pub use pool::{MediaPool, MediaState};
+pub use thumbnail::{thumbnail, thumbnails, ThumbnailRequest};
+
 #[derive(Debug, Clone)]
 pub struct VideoFrame {
     pub width: u32,
@@ -19,6 +25,10 @@ pub struct VideoFrame {
     pub bytes: Vec<u8>, // RGBA, row-major, tightly packed
 }
 
+/// Schema of video config JSON ([`VideoDecoder::from_json_path`], [`VideoConfig::to_json`]).
+pub const VIDEO_CONFIG_SCHEMA: scheng_core::JsonSchema =
+    scheng_core::JsonSchema::new("video config", 1, &[scheng_core::Migration::unchanged(0)]);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VideoConfig {
     /// Output width (pixels).
@@ -29,8 +39,8 @@ pub struct VideoConfig {
     #[serde(default = "default_height")]
     pub height: u32,
 
-    /// Target output fps (currently not enforced when trusting source fps,
-    /// but kept for schema compatibility and future use if needed).
+    /// Target output fps. Looping clips are resampled to it and paced by the decoder thread;
+    /// one-shot playback trusts the source fps.
     #[serde(default = "default_fps")]
     pub fps: u32,
 
@@ -44,6 +54,114 @@ pub struct VideoConfig {
     /// Optional explicit ffmpeg binary path.
     #[serde(default)]
     pub ffmpeg_path: Option<String>,
+
+    /// Where playback begins (seconds into the file). Positions before `in_point` start at
+    /// `in_point`.
+    #[serde(default)]
+    pub start_seconds: f64,
+
+    /// Start of the playable range (seconds). Loops restart here.
+    #[serde(default)]
+    pub in_point: Option<f64>,
+
+    /// End of the playable range (seconds). Playback stops or loops here.
+    #[serde(default)]
+    pub out_point: Option<f64>,
+
+    /// How the runtime shows the timeline between two decoded frames.
+    #[serde(default)]
+    pub interpolation: FrameInterpolation,
+}
+
+/// Playback between decoded frames ([`VideoConfig::interpolation`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FrameInterpolation {
+    /// Hold each frame until the next one (steps when played slower than `fps`).
+    #[default]
+    Off,
+    /// Crossfade the previous and current frame by the fractional frame position, so slow
+    /// motion and scrubbing look smooth. Adds one frame of latency and one blend pass.
+    Blend,
+}
+
+impl VideoConfig {
+    /// Pretty JSON stamped with the current [`VIDEO_CONFIG_SCHEMA`] version.
+    pub fn to_json(&self) -> String {
+        VIDEO_CONFIG_SCHEMA
+            .to_string_pretty(self)
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Check dimensions, fps and trim points.
+    pub fn validate(&self) -> Result<(), VideoError> {
+        if self.file.trim().is_empty() {
+            return Err(VideoError::InvalidConfig("file is empty".into()));
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(VideoError::InvalidConfig("width/height must be > 0".into()));
+        }
+        if self.fps == 0 {
+            return Err(VideoError::InvalidConfig("fps must be > 0".into()));
+        }
+        let times = [
+            ("start_seconds", Some(self.start_seconds)),
+            ("in_point", self.in_point),
+            ("out_point", self.out_point),
+        ];
+        for (name, t) in times {
+            if let Some(t) = t.filter(|t| !t.is_finite() || *t < 0.0) {
+                return Err(VideoError::InvalidConfig(format!(
+                    "{name} must be a non-negative number of seconds (got {t})"
+                )));
+            }
+        }
+        if let Some(out) = self.out_point {
+            let start = self.first_seek();
+            if out <= self.loop_seek() {
+                return Err(VideoError::InvalidConfig(format!(
+                    "out_point ({out}s) must be after in_point ({}s)",
+                    self.loop_seek()
+                )));
+            }
+            if start >= out {
+                return Err(VideoError::InvalidConfig(format!(
+                    "start_seconds ({start}s) must be before out_point ({out}s)"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether start offset or trim points are set.
+    pub fn is_trimmed(&self) -> bool {
+        self.start_seconds > 0.0 || self.in_point.is_some() || self.out_point.is_some()
+    }
+
+    /// Bytes in one decoded RGBA frame.
+    fn frame_len(&self) -> usize {
+        (self.width as usize) * (self.height as usize) * 4
+    }
+
+    /// Seek position for the first pass through the clip.
+    fn first_seek(&self) -> f64 {
+        self.start_seconds.max(self.loop_seek())
+    }
+
+    /// Seek position for every later (looped) pass.
+    fn loop_seek(&self) -> f64 {
+        self.in_point.unwrap_or(0.0)
+    }
+
+    /// `(seek, duration)` for one pass of ffmpeg; `None` duration plays to the end of the file.
+    fn segment(&self, first: bool) -> (f64, Option<f64>) {
+        let seek = if first {
+            self.first_seek()
+        } else {
+            self.loop_seek()
+        };
+        (seek, self.out_point.map(|out| out - seek))
+    }
 }
 
 fn default_width() -> u32 {
@@ -77,44 +195,49 @@ pub enum VideoError {
 
     #[error("invalid config: {0}")]
     InvalidConfig(String),
+
+    /// The decode thread panicked; the decoder produces no more frames.
+    #[error("decoder thread panicked: {0}")]
+    DecoderPanicked(String),
 }
 
 pub struct VideoDecoder {
     cfg: VideoConfig,
     latest: Arc<Mutex<Option<VideoFrame>>>,
     stop: Arc<AtomicBool>,
+    /// Message of a panic caught on the decode thread.
+    panicked: Arc<Mutex<Option<String>>>,
     worker: Option<thread::JoinHandle<()>>,
 }
 
 impl VideoDecoder {
     pub fn from_config(cfg: VideoConfig) -> Result<Self, VideoError> {
-        if cfg.file.trim().is_empty() {
-            return Err(VideoError::InvalidConfig("file is empty".into()));
-        }
-        if cfg.width == 0 || cfg.height == 0 {
-            return Err(VideoError::InvalidConfig(
-                "width/height must be > 0".into(),
-            ));
-        }
-        if cfg.fps == 0 {
-            return Err(VideoError::InvalidConfig("fps must be > 0".into()));
-        }
+        cfg.validate()?;
 
         let latest = Arc::new(Mutex::new(None));
         let stop = Arc::new(AtomicBool::new(false));
+        let panicked = Arc::new(Mutex::new(None));
 
         let cfg_for_thread = cfg.clone();
         let latest_for_thread = Arc::clone(&latest);
         let stop_for_thread = Arc::clone(&stop);
+        let panicked_for_thread = Arc::clone(&panicked);
 
         let worker = thread::spawn(move || {
-            decode_loop(cfg_for_thread, latest_for_thread, stop_for_thread);
+            // A panic here must not poison the host: record it for `poll_rgba` to report.
+            let run = panic::catch_unwind(AssertUnwindSafe(|| {
+                decode_loop(cfg_for_thread, latest_for_thread, stop_for_thread)
+            }));
+            if let Err(payload) = run {
+                *lock(&panicked_for_thread) = Some(scheng_core::events::panic_message(&*payload));
+            }
         });
 
         Ok(Self {
             cfg,
             latest,
             stop,
+            panicked,
             worker: Some(worker),
         })
     }
@@ -122,8 +245,9 @@ impl VideoDecoder {
     pub fn from_json_path(path: impl AsRef<Path>) -> Result<Self, VideoError> {
         let text = std::fs::read_to_string(path.as_ref())
             .map_err(|e| VideoError::InvalidConfig(format!("read json: {e}")))?;
-        let cfg: VideoConfig = serde_json::from_str(&text)
-            .map_err(|e| VideoError::InvalidConfig(format!("parse json: {e}")))?;
+        let cfg: VideoConfig = VIDEO_CONFIG_SCHEMA
+            .from_str(&text)
+            .map_err(VideoError::InvalidConfig)?;
         Self::from_config(cfg)
     }
 
@@ -131,9 +255,21 @@ impl VideoDecoder {
         &self.cfg
     }
 
+    /// Bytes of decoded frames held in memory: the decode thread's read buffer plus the latest
+    /// published frame.
+    pub fn buffer_bytes(&self) -> usize {
+        let latest = lock(&self.latest).as_ref().map_or(0, |f| f.bytes.len());
+        self.cfg.frame_len() + latest
+    }
+
     /// Non-blocking: returns the latest available frame (if any), otherwise NoFrameYet.
+    ///
+    /// Once the decode thread has panicked this returns `DecoderPanicked` on every call.
     pub fn poll_rgba(&mut self) -> Result<VideoFrame, VideoError> {
-        let guard = self.latest.lock().unwrap();
+        if let Some(msg) = lock(&self.panicked).clone() {
+            return Err(VideoError::DecoderPanicked(msg));
+        }
+        let guard = lock(&self.latest);
         if let Some(f) = guard.as_ref() {
             Ok(f.clone())
         } else {
@@ -153,107 +289,210 @@ impl Drop for VideoDecoder {
 
 // ---------------- internal ----------------
 
+/// Lock, ignoring poisoning: the data is a plain value that a panicking writer cannot leave
+/// half-updated.
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 fn decode_loop(cfg: VideoConfig, latest: Arc<Mutex<Option<VideoFrame>>>, stop: Arc<AtomicBool>) {
-    let frame_len = (cfg.width as usize) * (cfg.height as usize) * 4;
-    let mut buf = vec![0u8; frame_len];
+    let ffmpeg =
+        resolve_ffmpeg_path(cfg.ffmpeg_path.as_deref()).unwrap_or_else(|| PathBuf::from("ffmpeg"));
+    if cfg.r#loop {
+        gapless_loop(&cfg, &ffmpeg, &latest, &stop);
+    } else {
+        play_once(&cfg, &ffmpeg, &latest, &stop);
+    }
+}
 
-    // Ensure we never silently swallow ffmpeg spawn failures.
+/// One pass through the clip, paced by ffmpeg (`-re`). The last frame stays in `latest`.
+fn play_once(
+    cfg: &VideoConfig,
+    ffmpeg: &Path,
+    latest: &Mutex<Option<VideoFrame>>,
+    stop: &AtomicBool,
+) {
+    let mut pass = match DecodePass::spawn(ffmpeg, cfg, cfg.segment(true), true) {
+        Ok(p) => p,
+        Err(e) => {
+            // Ensure we never silently swallow ffmpeg spawn failures.
+            eprintln!(
+                "scheng-input-video: failed to spawn ffmpeg at {:?}: {}",
+                ffmpeg, e
+            );
+            return;
+        }
+    };
+    let mut buf = vec![0u8; cfg.frame_len()];
+    while !stop.load(Ordering::SeqCst) && pass.read(&mut buf) {
+        publish(cfg, latest, &buf);
+    }
+    pass.kill();
+}
+
+/// Looping playback without a gap at the loop point.
+///
+/// A standby ffmpeg is spawned as soon as a pass starts, seeked to the loop start. It decodes
+/// its first frame and then blocks on the full pipe, so when the current pass hits the end the
+/// switch costs no process startup or seek. Neither process runs with `-re` (the standby would
+/// burst to catch up); instead ffmpeg resamples to `cfg.fps` and this thread paces frames.
+fn gapless_loop(
+    cfg: &VideoConfig,
+    ffmpeg: &Path,
+    latest: &Mutex<Option<VideoFrame>>,
+    stop: &AtomicBool,
+) {
     let mut logged_spawn_error = false;
+    let mut spawn = |first: bool| match DecodePass::spawn(ffmpeg, cfg, cfg.segment(first), false) {
+        Ok(p) => {
+            logged_spawn_error = false;
+            Some(p)
+        }
+        Err(e) => {
+            if !logged_spawn_error {
+                eprintln!(
+                    "scheng-input-video: failed to spawn ffmpeg at {:?}: {}",
+                    ffmpeg, e
+                );
+                logged_spawn_error = true;
+            }
+            None
+        }
+    };
+
+    let period = Duration::from_secs_f64(1.0 / cfg.fps.max(1) as f64);
+    let mut buf = vec![0u8; cfg.frame_len()];
+    let mut current = spawn(true);
+    let mut standby = spawn(false);
+    let mut next_due = Instant::now();
 
     while !stop.load(Ordering::SeqCst) {
-        let ffmpeg = resolve_ffmpeg_path(cfg.ffmpeg_path.as_deref())
-            .unwrap_or_else(|| PathBuf::from("ffmpeg"));
-
-        let mut child = match spawn_ffmpeg(&ffmpeg, &cfg) {
-            Ok(c) => {
-                // Once we successfully spawn, clear any previous error flag.
-                logged_spawn_error = false;
-                c
-            }
-            Err(e) => {
-                if !logged_spawn_error {
-                    eprintln!(
-                        "scheng-input-video: failed to spawn ffmpeg at {:?}: {}",
-                        ffmpeg, e
-                    );
-                    logged_spawn_error = true;
-                }
-
-                // If looping is disabled, fail fast instead of silently spinning.
-                if !cfg.r#loop {
-                    return;
-                }
-
-                // Backoff a bit before retrying, to avoid busy-looping.
-                thread::sleep(Duration::from_millis(500));
-                continue;
-            }
+        let Some(pass) = current.as_mut() else {
+            // Backoff a bit before retrying, to avoid busy-looping.
+            thread::sleep(Duration::from_millis(500));
+            current = standby.take().or_else(|| spawn(false));
+            standby = spawn(false);
+            next_due = Instant::now();
+            continue;
         };
 
-        let mut stdout = child.stdout.take().expect("ffmpeg stdout piped");
-
-        loop {
-            if stop.load(Ordering::SeqCst) {
-                let _ = child.kill();
-                let _ = child.wait();
-                return;
+        if pass.read(&mut buf) {
+            pass.frames += 1;
+            let now = Instant::now();
+            if next_due > now {
+                thread::sleep(next_due - now);
+                next_due += period;
+            } else {
+                // Fell behind (slow decode, stalled host): resync instead of bursting.
+                next_due = now + period;
             }
+            publish(cfg, latest, &buf);
+            continue;
+        }
 
-            match stdout.read_exact(&mut buf) {
-                Ok(()) => {
-                    let frame = VideoFrame {
-                        width: cfg.width,
-                        height: cfg.height,
-                        bytes: buf.clone(),
-                    };
-                    *latest.lock().unwrap() = Some(frame);
-                }
-                Err(e) => {
-                    // EOF or stream ended. Decide whether to loop.
-                    let _ = child.kill();
-                    let _ = child.wait();
-
-                    if cfg.r#loop {
-                        // For looped playback, break out to respawn ffmpeg.
-                        break;
-                    } else {
-                        let _ = e;
-                        // Leave the last frame in `latest` and exit the worker.
-                        return;
-                    }
-                }
-            }
+        // End of the pass: switch to the primed standby and prime a new one.
+        let ended = current.take().expect("current pass is set");
+        let empty = ended.frames == 0;
+        ended.kill();
+        if empty {
+            // Nothing decodable in range; don't spin on a broken clip.
+            thread::sleep(Duration::from_millis(500));
         }
+        current = standby.take();
+        standby = spawn(false);
+    }
+
+    for pass in [current, standby].into_iter().flatten() {
+        pass.kill();
+    }
+}
+
+fn publish(cfg: &VideoConfig, latest: &Mutex<Option<VideoFrame>>, bytes: &[u8]) {
+    *lock(latest) = Some(VideoFrame {
+        width: cfg.width,
+        height: cfg.height,
+        bytes: bytes.to_vec(),
+    });
+}
+
+/// One ffmpeg process decoding one pass through (a segment of) the clip.
+struct DecodePass {
+    child: Child,
+    stdout: ChildStdout,
+    /// Frames read so far.
+    frames: u64,
+}
+
+impl DecodePass {
+    fn spawn(
+        ffmpeg: &Path,
+        cfg: &VideoConfig,
+        segment: (f64, Option<f64>),
+        realtime: bool,
+    ) -> io::Result<Self> {
+        let mut child = spawn_ffmpeg(ffmpeg, cfg, segment, realtime)?;
+        let stdout = child.stdout.take().expect("ffmpeg stdout piped");
+        Ok(Self {
+            child,
+            stdout,
+            frames: 0,
+        })
+    }
+
+    /// Read the next frame into `buf`; false at the end of the pass (or on a pipe error).
+    fn read(&mut self, buf: &mut [u8]) -> bool {
+        self.stdout.read_exact(buf).is_ok()
+    }
+
+    fn kill(mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
     }
 }
 
 /// Spawn ffmpeg configured to:
-/// - read the input at (approx) real-time speed (`-re`), trusting source timestamps/fps
+/// - play `segment` (`(seek, duration)` seconds) of the input
+/// - with `realtime`, read the input at (approx) real-time speed (`-re`), trusting source
+///   timestamps/fps; otherwise decode as fast as the pipe allows, resampled to `cfg.fps`
 /// - scale to cfg.width x cfg.height
 /// - flip vertically, so the resulting RGBA is GL-friendly (bottom-left origin in UVs)
-fn spawn_ffmpeg(ffmpeg: &Path, cfg: &VideoConfig) -> io::Result<Child> {
+fn spawn_ffmpeg(
+    ffmpeg: &Path,
+    cfg: &VideoConfig,
+    segment: (f64, Option<f64>),
+    realtime: bool,
+) -> io::Result<Child> {
     // ffmpeg args:
-    // -re                 (throttle to real time using input timestamps)
+    // -re                 (optional, throttle to real time using input timestamps)
     // -loglevel error     (quiet)
-    // -stream_loop -1     (optional, for looping)
-    // -vf scale=WxH,vflip (trust source fps; no fps= filter)
+    // -ss S / -t D        (optional, trim points; input-side seek)
+    // -vf [fps=N,]scale=WxH,vflip
     // -pix_fmt rgba -f rawvideo pipe:1
     let mut cmd = Command::new(ffmpeg);
 
     cmd.arg("-hide_banner").arg("-loglevel").arg("error");
 
-    // Throttle decoding so frames come out at (approx) real-time rate based on input timestamps.
-    cmd.arg("-re");
+    let mut filters = format!("scale={}:{},vflip", cfg.width, cfg.height);
+    if realtime {
+        // Throttle decoding so frames come out at (approx) real-time rate based on input timestamps.
+        cmd.arg("-re");
+    } else {
+        // The reader paces frames at cfg.fps, so make the stream match it.
+        filters = format!("fps={},{filters}", cfg.fps);
+    }
 
-    if cfg.r#loop {
-        // For many demuxers, stream_loop works well for local files.
-        cmd.arg("-stream_loop").arg("-1");
+    let (seek, duration) = segment;
+    if seek > 0.0 {
+        cmd.arg("-ss").arg(format!("{seek:.3}"));
+    }
+    if let Some(d) = duration {
+        cmd.arg("-t").arg(format!("{d:.3}"));
     }
 
     cmd.arg("-i")
         .arg(&cfg.file)
         .arg("-vf")
-        .arg(format!("scale={}:{},vflip", cfg.width, cfg.height))
+        .arg(filters)
         .arg("-pix_fmt")
         .arg("rgba")
         .arg("-f")
@@ -315,3 +554,56 @@ fn ffmpeg_filename() -> &'static OsStr {
         OsStr::new("ffmpeg")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip() -> VideoConfig {
+        serde_json::from_str(r#"{ "file": "clip.mp4" }"#).unwrap()
+    }
+
+    #[test]
+    fn interpolation_defaults_off_and_round_trips() {
+        assert_eq!(clip().interpolation, FrameInterpolation::Off);
+        let cfg: VideoConfig =
+            serde_json::from_str(r#"{ "file": "clip.mp4", "interpolation": "blend" }"#).unwrap();
+        assert_eq!(cfg.interpolation, FrameInterpolation::Blend);
+        assert!(cfg.to_json().contains("\"interpolation\": \"blend\""));
+    }
+
+    #[test]
+    fn trim_points_pick_first_and_loop_segments() {
+        let mut cfg = clip();
+        assert!(!cfg.is_trimmed());
+        assert_eq!(cfg.segment(true), (0.0, None));
+
+        cfg.in_point = Some(2.0);
+        cfg.out_point = Some(10.0);
+        cfg.start_seconds = 4.0;
+        assert!(cfg.validate().is_ok());
+        assert_eq!(cfg.segment(true), (4.0, Some(6.0)));
+        assert_eq!(cfg.segment(false), (2.0, Some(8.0)));
+
+        // A start before the in point begins at the in point.
+        cfg.start_seconds = 1.0;
+        assert_eq!(cfg.segment(true), (2.0, Some(8.0)));
+    }
+
+    #[test]
+    fn invalid_trim_points_are_rejected() {
+        let mut cfg = clip();
+        cfg.in_point = Some(5.0);
+        cfg.out_point = Some(5.0);
+        assert!(cfg.validate().is_err());
+
+        let mut cfg = clip();
+        cfg.out_point = Some(3.0);
+        cfg.start_seconds = 3.5;
+        assert!(cfg.validate().is_err());
+
+        let mut cfg = clip();
+        cfg.in_point = Some(-1.0);
+        assert!(cfg.validate().is_err());
+    }
+}
@@ -0,0 +1,290 @@
+//! Media pool: every configured clip, preloaded and kept within a memory budget.
+//!
+//! Register clips with [`MediaPool::add`] and warm them with [`MediaPool::preload`] (or
+//! `preload_all`): each gets a running decoder, and its first decoded frame is cached so a
+//! deck can show it the instant the clip is launched. Call [`MediaPool::update`] once per host
+//! frame to pick up first frames and enforce the budget. When the estimated memory use exceeds
+//! it, the least recently used idle decoders are stopped first (their first frame is kept), then
+//! the oldest cached first frames. Clips in use (between [`acquire`](MediaPool::acquire) and
+//! [`release`](MediaPool::release)) are never evicted, so they may push the pool over budget.
+//!
+//! Decoders play from the moment they are preloaded, so a warm looping clip launches mid-loop.
+
+use std::collections::HashMap;
+
+use crate::{VideoConfig, VideoDecoder, VideoError, VideoFrame};
+
+/// Readiness of one clip in a [`MediaPool`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaState {
+    /// Registered; nothing decoded or running.
+    Idle,
+    /// Decoder running, first frame not decoded yet.
+    Loading,
+    /// Decoder running and first frame cached: launches are instant.
+    Ready,
+    /// Decoder stopped to stay within budget; the cached first frame is still available.
+    Cached,
+    /// The decoder could not be started.
+    Failed(String),
+}
+
+struct MediaEntry {
+    cfg: VideoConfig,
+    decoder: Option<VideoDecoder>,
+    first_frame: Option<VideoFrame>,
+    state: MediaState,
+    in_use: bool,
+    /// Pool clock value at the last preload / acquire / release.
+    last_used: u64,
+}
+
+impl MediaEntry {
+    fn decoder_bytes(&self) -> usize {
+        // The shared latest frame plus the worker's read buffer.
+        self.decoder
+            .as_ref()
+            .map_or(0, |_| 2 * self.cfg.frame_len())
+    }
+
+    fn frame_bytes(&self) -> usize {
+        self.first_frame.as_ref().map_or(0, |f| f.bytes.len())
+    }
+}
+
+/// All configured media, keyed by name (see the module docs).
+pub struct MediaPool {
+    entries: HashMap<String, MediaEntry>,
+    budget_bytes: usize,
+    clock: u64,
+}
+
+impl MediaPool {
+    /// An empty pool that keeps decoders and cached frames within `budget_bytes` (estimated
+    /// from frame sizes).
+    pub fn new(budget_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            budget_bytes,
+            clock: 0,
+        }
+    }
+
+    /// Register (or replace) the clip `name`. The config is validated; nothing is decoded yet.
+    pub fn add(&mut self, name: impl Into<String>, cfg: VideoConfig) -> Result<(), VideoError> {
+        cfg.validate()?;
+        self.entries.insert(
+            name.into(),
+            MediaEntry {
+                cfg,
+                decoder: None,
+                first_frame: None,
+                state: MediaState::Idle,
+                in_use: false,
+                last_used: 0,
+            },
+        );
+        Ok(())
+    }
+
+    /// Forget `name`, stopping its decoder. Returns whether it was registered.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.entries.remove(name).is_some()
+    }
+
+    /// Start `name`'s decoder if it is not running.
+    pub fn preload(&mut self, name: &str) -> Result<(), VideoError> {
+        self.clock += 1;
+        let clock = self.clock;
+        let entry = self.entry_mut(name)?;
+        entry.last_used = clock;
+        start_decoder(entry)
+    }
+
+    /// Preload every registered clip. Failures are recorded as [`MediaState::Failed`].
+    pub fn preload_all(&mut self) {
+        let mut names: Vec<String> = self.entries.keys().cloned().collect();
+        names.sort();
+        for name in names {
+            let _ = self.preload(&name);
+        }
+    }
+
+    /// Cache newly decoded first frames and evict idle media over budget. Call once per frame.
+    pub fn update(&mut self) {
+        for entry in self.entries.values_mut() {
+            if entry.state != MediaState::Loading {
+                continue;
+            }
+            if let Some(Ok(frame)) = entry.decoder.as_mut().map(|d| d.poll_rgba()) {
+                entry.first_frame.get_or_insert(frame);
+                entry.state = MediaState::Ready;
+            }
+        }
+        self.enforce_budget();
+    }
+
+    /// The running decoder for `name`, started if needed. The clip counts as in use (and is
+    /// never evicted) until [`release`](Self::release).
+    pub fn acquire(&mut self, name: &str) -> Result<&mut VideoDecoder, VideoError> {
+        self.clock += 1;
+        let clock = self.clock;
+        let entry = self.entry_mut(name)?;
+        entry.in_use = true;
+        entry.last_used = clock;
+        start_decoder(entry)?;
+        Ok(entry.decoder.as_mut().expect("decoder just started"))
+    }
+
+    /// Mark `name` idle again; its decoder stays warm until the budget needs the memory.
+    pub fn release(&mut self, name: &str) {
+        self.clock += 1;
+        let clock = self.clock;
+        if let Some(entry) = self.entries.get_mut(name) {
+            entry.in_use = false;
+            entry.last_used = clock;
+        }
+    }
+
+    pub fn state(&self, name: &str) -> Option<&MediaState> {
+        self.entries.get(name).map(|e| &e.state)
+    }
+
+    /// Whether `name` has a running decoder and a cached first frame.
+    pub fn is_ready(&self, name: &str) -> bool {
+        self.state(name) == Some(&MediaState::Ready)
+    }
+
+    /// The clip's first decoded frame, for thumbnails or an instant first frame on launch.
+    pub fn first_frame(&self, name: &str) -> Option<&VideoFrame> {
+        self.entries.get(name)?.first_frame.as_ref()
+    }
+
+    pub fn config(&self, name: &str) -> Option<&VideoConfig> {
+        self.entries.get(name).map(|e| &e.cfg)
+    }
+
+    /// Registered clip names, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.entries.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Estimated bytes held by running decoders and cached first frames.
+    pub fn memory_bytes(&self) -> usize {
+        self.entries
+            .values()
+            .map(|e| e.decoder_bytes() + e.frame_bytes())
+            .sum()
+    }
+
+    pub fn budget_bytes(&self) -> usize {
+        self.budget_bytes
+    }
+
+    pub fn set_budget_bytes(&mut self, budget_bytes: usize) {
+        self.budget_bytes = budget_bytes;
+        self.enforce_budget();
+    }
+
+    fn entry_mut(&mut self, name: &str) -> Result<&mut MediaEntry, VideoError> {
+        self.entries
+            .get_mut(name)
+            .ok_or_else(|| VideoError::InvalidConfig(format!("unknown media '{name}'")))
+    }
+
+    fn enforce_budget(&mut self) {
+        while self.memory_bytes() > self.budget_bytes {
+            // Idle decoders first, then cached frames; least recently used first.
+            let victim = self
+                .lru_idle(|e| e.decoder.is_some())
+                .or_else(|| self.lru_idle(|e| e.first_frame.is_some()));
+            let Some(name) = victim else {
+                break;
+            };
+            let entry = self.entries.get_mut(&name).expect("victim is registered");
+            if entry.decoder.take().is_some() {
+                entry.state = if entry.first_frame.is_some() {
+                    MediaState::Cached
+                } else {
+                    MediaState::Idle
+                };
+            } else {
+                entry.first_frame = None;
+                entry.state = MediaState::Idle;
+            }
+        }
+    }
+
+    fn lru_idle(&self, holds: impl Fn(&MediaEntry) -> bool) -> Option<String> {
+        self.entries
+            .iter()
+            .filter(|(_, e)| !e.in_use && holds(e))
+            .min_by_key(|(_, e)| e.last_used)
+            .map(|(name, _)| name.clone())
+    }
+}
+
+fn start_decoder(entry: &mut MediaEntry) -> Result<(), VideoError> {
+    if entry.decoder.is_some() {
+        return Ok(());
+    }
+    match VideoDecoder::from_config(entry.cfg.clone()) {
+        Ok(decoder) => {
+            entry.decoder = Some(decoder);
+            entry.state = if entry.first_frame.is_some() {
+                MediaState::Ready
+            } else {
+                MediaState::Loading
+            };
+            Ok(())
+        }
+        Err(e) => {
+            entry.state = MediaState::Failed(e.to_string());
+            Err(e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clip(name: &str) -> VideoConfig {
+        // No ffmpeg at this path: the worker logs once and exits, no frames arrive.
+        serde_json::from_value(serde_json::json!({
+            "file": name,
+            "width": 10,
+            "height": 10,
+            "loop": false,
+            "ffmpeg_path": "/nonexistent/ffmpeg",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn evicts_least_recently_used_idle_decoders() {
+        let decoder_bytes = 2 * 10 * 10 * 4;
+        let mut pool = MediaPool::new(2 * decoder_bytes);
+        for name in ["a", "b", "c"] {
+            pool.add(name, clip(name)).unwrap();
+        }
+        pool.preload_all();
+        assert_eq!(pool.memory_bytes(), 3 * decoder_bytes);
+        pool.update();
+        assert_eq!(pool.state("a"), Some(&MediaState::Idle));
+        assert_eq!(pool.state("b"), Some(&MediaState::Loading));
+        assert_eq!(pool.memory_bytes(), 2 * decoder_bytes);
+
+        // In-use clips are kept; the next oldest idle one goes instead.
+        pool.acquire("a").unwrap();
+        pool.update();
+        assert_eq!(pool.state("a"), Some(&MediaState::Loading));
+        assert_eq!(pool.state("b"), Some(&MediaState::Idle));
+
+        assert!(pool.acquire("missing").is_err());
+        assert!(pool.add("bad", clip("")).is_err());
+        assert_eq!(pool.names(), vec!["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,154 @@
+//! Single-frame extraction for clip browsers.
+//!
+//! [`thumbnail`] runs ffmpeg once to grab one frame at a given time, scaled to the requested
+//! size; [`thumbnails`] does a batch on a small pool of worker threads. Frames use the same
+//! layout as [`VideoDecoder`](crate::VideoDecoder) output (RGBA, rows bottom-up for GL upload).
+
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{resolve_ffmpeg_path, VideoError, VideoFrame};
+
+/// One entry of a [`thumbnails`] batch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThumbnailRequest {
+    pub path: PathBuf,
+    pub at_seconds: f64,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ThumbnailRequest {
+    pub fn new(path: impl Into<PathBuf>, at_seconds: f64, width: u32, height: u32) -> Self {
+        Self {
+            path: path.into(),
+            at_seconds,
+            width,
+            height,
+        }
+    }
+}
+
+/// Extract the frame at `at_seconds` from `path`, scaled to `size` (`(width, height)`).
+///
+/// Blocks until ffmpeg exits. Fails with [`VideoError::FfmpegExited`] if no frame came out
+/// (e.g. `at_seconds` is past the end of the clip).
+pub fn thumbnail(
+    path: impl AsRef<Path>,
+    at_seconds: f64,
+    size: (u32, u32),
+) -> Result<VideoFrame, VideoError> {
+    let (width, height) = size;
+    if width == 0 || height == 0 {
+        return Err(VideoError::InvalidConfig(
+            "thumbnail size must be > 0".into(),
+        ));
+    }
+    if !at_seconds.is_finite() || at_seconds < 0.0 {
+        return Err(VideoError::InvalidConfig(format!(
+            "thumbnail time must be a non-negative number of seconds (got {at_seconds})"
+        )));
+    }
+
+    let ffmpeg = resolve_ffmpeg_path(None).unwrap_or_else(|| PathBuf::from("ffmpeg"));
+    let mut child = Command::new(ffmpeg)
+        .arg("-hide_banner")
+        .arg("-loglevel")
+        .arg("error")
+        .arg("-ss")
+        .arg(format!("{at_seconds:.3}"))
+        .arg("-i")
+        .arg(path.as_ref())
+        .arg("-frames:v")
+        .arg("1")
+        .arg("-vf")
+        .arg(format!("scale={width}:{height},vflip"))
+        .arg("-pix_fmt")
+        .arg("rgba")
+        .arg("-f")
+        .arg("rawvideo")
+        .arg("pipe:1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let mut bytes = Vec::with_capacity(width as usize * height as usize * 4);
+    let read = child
+        .stdout
+        .take()
+        .expect("ffmpeg stdout piped")
+        .read_to_end(&mut bytes);
+    let _ = child.wait();
+    read?;
+    if bytes.len() != width as usize * height as usize * 4 {
+        return Err(VideoError::FfmpegExited);
+    }
+    Ok(VideoFrame {
+        width,
+        height,
+        bytes,
+    })
+}
+
+/// Extract a batch of thumbnails on up to `workers` threads (at least one). Results are in
+/// request order.
+pub fn thumbnails(
+    requests: &[ThumbnailRequest],
+    workers: usize,
+) -> Vec<Result<VideoFrame, VideoError>> {
+    let results: Vec<Mutex<Option<Result<VideoFrame, VideoError>>>> =
+        requests.iter().map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for _ in 0..workers.clamp(1, requests.len().max(1)) {
+            s.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(req) = requests.get(i) else {
+                    break;
+                };
+                let frame = thumbnail(&req.path, req.at_seconds, (req.width, req.height));
+                *results[i].lock().unwrap() = Some(frame);
+            });
+        }
+    });
+    results
+        .into_iter()
+        .map(|r| {
+            r.into_inner()
+                .unwrap()
+                .expect("every request was processed")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_keeps_request_order() {
+        // Invalid sizes and times fail before ffmpeg is involved.
+        let requests = [
+            ThumbnailRequest::new("a.mp4", 0.0, 0, 90),
+            ThumbnailRequest::new("b.mp4", -1.0, 160, 90),
+            ThumbnailRequest::new("c.mp4", 1.0, 160, 0),
+        ];
+        let results = thumbnails(&requests, 2);
+        assert_eq!(results.len(), 3);
+        let messages: Vec<String> = results
+            .into_iter()
+            .map(|r| r.unwrap_err().to_string())
+            .collect();
+        assert!(messages[0].contains("size"));
+        assert!(messages[1].contains("time"));
+        assert!(messages[2].contains("size"));
+    }
+}
@@ -0,0 +1,400 @@
+//! Capture through an ffmpeg child process (feature `ffmpeg`).
+//!
+//! An alternative to the nokhwa backend for devices it does not handle well, such as HDMI /
+//! SDI capture cards. ffmpeg reads the platform capture API (avfoundation on macOS, v4l2 on
+//! Linux, dshow on Windows) and pipes raw RGBA; a reader thread keeps only the newest frame so
+//! a slow host never falls behind the device. Rows arrive top-down and unmirrored, so present
+//! them with a vertical flip.
+//!
+//! Devices are named by [`DeviceInfo::id`] from [`list_ffmpeg_devices`]: the avfoundation
+//! index on macOS, the `/dev/video*` path on Linux and the device name on Windows.
+
+use std::{
+    io::{BufRead, BufReader, Read},
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    process::{Child, ChildStdout, Command, Stdio},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
+};
+
+use crate::{DeviceInfo, RgbaFrame, WebcamError};
+
+/// What to open and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FfmpegCaptureConfig {
+    /// Device id as listed by [`list_ffmpeg_devices`].
+    pub device: String,
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    /// ffmpeg binary; defaults to `$scheng_FFMPEG`, then `ffmpeg` on `PATH`.
+    pub ffmpeg_path: Option<PathBuf>,
+}
+
+impl FfmpegCaptureConfig {
+    /// `device` at `width`x`height`, 30 fps.
+    pub fn new(device: impl Into<String>, width: u32, height: u32) -> Self {
+        Self {
+            device: device.into(),
+            width,
+            height,
+            fps: 30,
+            ffmpeg_path: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Latest {
+    frame: Option<Vec<u8>>,
+    /// Bumped for every frame read; `poll_rgba` waits for it to move.
+    seq: u64,
+    /// Set when ffmpeg's output ends (device gone, unsupported mode, process killed).
+    ended: Option<String>,
+}
+
+/// A capture device read through ffmpeg, polled like [`Webcam`](crate::Webcam).
+pub struct FfmpegCapture {
+    cfg: FfmpegCaptureConfig,
+    child: Child,
+    shared: Arc<(Mutex<Latest>, Condvar)>,
+    seen: u64,
+    reader: Option<JoinHandle<()>>,
+}
+
+impl FfmpegCapture {
+    /// Open device `index` in the platform's ffmpeg device order (see [`list_ffmpeg_devices`]).
+    pub fn new(index: u32, width: u32, height: u32) -> Result<Self, WebcamError> {
+        let device = if cfg!(target_os = "linux") {
+            format!("/dev/video{index}")
+        } else if cfg!(windows) {
+            list_ffmpeg_devices()?
+                .into_iter()
+                .nth(index as usize)
+                .map(|d| d.id)
+                .ok_or_else(|| WebcamError::Backend(format!("no capture device {index}")))?
+        } else {
+            index.to_string()
+        };
+        Self::open(FfmpegCaptureConfig::new(device, width, height))
+    }
+
+    /// Start ffmpeg on `cfg.device`. The device must support the requested size and rate;
+    /// if it does not, ffmpeg exits and the first `poll_rgba` reports why.
+    pub fn open(cfg: FfmpegCaptureConfig) -> Result<Self, WebcamError> {
+        if cfg.width == 0 || cfg.height == 0 || cfg.fps == 0 {
+            return Err(WebcamError::Backend(
+                "ffmpeg capture size and fps must be > 0".into(),
+            ));
+        }
+
+        let mut child = Command::new(ffmpeg_path(&cfg))
+            .arg("-hide_banner")
+            .arg("-loglevel")
+            .arg("error")
+            .arg("-f")
+            .arg(input_format())
+            // Capture devices are real-time; no -re needed.
+            .arg("-framerate")
+            .arg(cfg.fps.to_string())
+            .arg("-video_size")
+            .arg(format!("{}x{}", cfg.width, cfg.height))
+            .arg("-i")
+            .arg(input_spec(&cfg.device))
+            .arg("-pix_fmt")
+            .arg("rgba")
+            .arg("-f")
+            .arg("rawvideo")
+            .arg("pipe:1")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| WebcamError::Backend(format!("spawn ffmpeg: {e}")))?;
+
+        let stdout = child.stdout.take().expect("ffmpeg stdout piped");
+        let stderr = child.stderr.take().expect("ffmpeg stderr piped");
+        let frame_len = cfg.width as usize * cfg.height as usize * 4;
+        let shared = Arc::new((Mutex::new(Latest::default()), Condvar::new()));
+        let reader_shared = Arc::clone(&shared);
+        let reader = thread::Builder::new()
+            .name("scheng-ffmpeg-capture".into())
+            .spawn(move || {
+                // A panicking reader must still end the stream, or `poll_rgba` waits forever.
+                let run = panic::catch_unwind(AssertUnwindSafe(|| {
+                    read_frames(stdout, stderr, frame_len, &reader_shared)
+                }));
+                if let Err(payload) = run {
+                    let msg = payload
+                        .downcast_ref::<&str>()
+                        .map(|s| s.to_string())
+                        .or_else(|| payload.downcast_ref::<String>().cloned())
+                        .unwrap_or_default();
+                    let (lock, cvar) = &*reader_shared;
+                    lock_latest(lock).ended = Some(format!("reader thread panicked: {msg}"));
+                    cvar.notify_all();
+                }
+            })
+            .map_err(|e| WebcamError::Backend(format!("spawn reader thread: {e}")))?;
+
+        Ok(Self {
+            cfg,
+            child,
+            shared,
+            seen: 0,
+            reader: Some(reader),
+        })
+    }
+
+    pub fn config(&self) -> &FfmpegCaptureConfig {
+        &self.cfg
+    }
+
+    /// Wait for the next frame and return it as RGBA8 bytes (older frames the host did not
+    /// poll in time are dropped). Fails with [`WebcamError::Disconnected`] once ffmpeg stops.
+    pub fn poll_rgba(&mut self) -> Result<RgbaFrame, WebcamError> {
+        let (lock, cvar) = &*self.shared;
+        let mut latest = lock_latest(lock);
+        while latest.seq == self.seen && latest.ended.is_none() {
+            latest = cvar.wait(latest).unwrap_or_else(PoisonError::into_inner);
+        }
+        if latest.seq == self.seen {
+            let reason = latest.ended.as_deref().unwrap_or_default();
+            return Err(if reason.is_empty() {
+                WebcamError::Disconnected
+            } else {
+                WebcamError::Backend(format!("ffmpeg capture ended: {reason}"))
+            });
+        }
+        self.seen = latest.seq;
+        Ok(RgbaFrame {
+            width: self.cfg.width,
+            height: self.cfg.height,
+            bytes: latest.frame.clone().expect("frame with seq > 0"),
+        })
+    }
+}
+
+impl Drop for FfmpegCapture {
+    fn drop(&mut self) {
+        // Kill ffmpeg so the device is released for the next open.
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(reader) = self.reader.take() {
+            let _ = reader.join();
+        }
+    }
+}
+
+fn read_frames(
+    mut stdout: ChildStdout,
+    stderr: impl Read + Send + 'static,
+    frame_len: usize,
+    shared: &(Mutex<Latest>, Condvar),
+) {
+    // Keep ffmpeg's last error line; drain stderr on its own thread so it never blocks.
+    let last_error = Arc::new(Mutex::new(String::new()));
+    let error_sink = Arc::clone(&last_error);
+    let stderr_thread = thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            if !line.trim().is_empty() {
+                *error_sink.lock().unwrap() = line;
+            }
+        }
+    });
+
+    let (lock, cvar) = shared;
+    let mut buf = vec![0u8; frame_len];
+    while stdout.read_exact(&mut buf).is_ok() {
+        let mut latest = lock_latest(lock);
+        match latest.frame.as_mut() {
+            Some(frame) => frame.copy_from_slice(&buf),
+            None => latest.frame = Some(buf.clone()),
+        }
+        latest.seq += 1;
+        cvar.notify_all();
+    }
+
+    let _ = stderr_thread.join();
+    let reason = last_error.lock().unwrap().clone();
+    lock_latest(lock).ended = Some(reason);
+    cvar.notify_all();
+}
+
+/// Lock the shared frame slot, ignoring poisoning (a panicked reader is reported through
+/// `ended` instead).
+fn lock_latest(lock: &Mutex<Latest>) -> MutexGuard<'_, Latest> {
+    lock.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn ffmpeg_path(cfg: &FfmpegCaptureConfig) -> PathBuf {
+    cfg.ffmpeg_path
+        .clone()
+        .or_else(|| std::env::var_os("scheng_FFMPEG").map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("ffmpeg"))
+}
+
+fn input_format() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "avfoundation"
+    } else if cfg!(windows) {
+        "dshow"
+    } else {
+        "v4l2"
+    }
+}
+
+fn input_spec(device: &str) -> String {
+    if cfg!(target_os = "macos") {
+        // Video only: binding an audio device makes avfoundation fall back unpredictably.
+        format!("{device}:none")
+    } else if cfg!(windows) {
+        format!("video={device}")
+    } else {
+        device.to_string()
+    }
+}
+
+/// Capture devices ffmpeg can open on this platform, in its own order.
+pub fn list_ffmpeg_devices() -> Result<Vec<DeviceInfo>, WebcamError> {
+    if cfg!(target_os = "linux") {
+        return Ok(list_v4l2_devices());
+    }
+
+    // ffmpeg prints the list on stderr and then fails to open the dummy input.
+    let dummy = if cfg!(windows) { "dummy" } else { "" };
+    let out = Command::new(ffmpeg_path(&FfmpegCaptureConfig::new("", 0, 0)))
+        .args(["-hide_banner", "-f", input_format()])
+        .args(["-list_devices", "true", "-i", dummy])
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| WebcamError::Backend(format!("spawn ffmpeg: {e}")))?;
+    let log = String::from_utf8_lossy(&out.stderr);
+    Ok(if cfg!(windows) {
+        parse_dshow_devices(&log)
+    } else {
+        parse_avfoundation_devices(&log)
+    })
+}
+
+fn list_v4l2_devices() -> Vec<DeviceInfo> {
+    let Ok(entries) = std::fs::read_dir("/dev") else {
+        return Vec::new();
+    };
+    let mut numbers: Vec<u32> = entries
+        .filter_map(|e| {
+            e.ok()?
+                .file_name()
+                .to_str()?
+                .strip_prefix("video")?
+                .parse()
+                .ok()
+        })
+        .collect();
+    numbers.sort_unstable();
+    numbers
+        .into_iter()
+        .map(|n| {
+            let name = std::fs::read_to_string(format!("/sys/class/video4linux/video{n}/name"))
+                .map(|s| s.trim().to_string())
+                .unwrap_or_else(|_| format!("video{n}"));
+            DeviceInfo {
+                id: format!("/dev/video{n}"),
+                name,
+            }
+        })
+        .collect()
+}
+
+/// Video devices from `ffmpeg -f avfoundation -list_devices true`, e.g.
+/// `[AVFoundation indev @ 0x7f8] [0] FaceTime HD Camera`.
+fn parse_avfoundation_devices(log: &str) -> Vec<DeviceInfo> {
+    let mut devices = Vec::new();
+    let mut in_video = false;
+    for line in log.lines() {
+        if line.contains("AVFoundation video devices") {
+            in_video = true;
+            continue;
+        }
+        if line.contains("AVFoundation audio devices") {
+            break;
+        }
+        if !in_video {
+            continue;
+        }
+        // Skip the "[AVFoundation indev @ ...]" prefix, then read "[N] name".
+        let Some(rest) = line.split_once("] ").map(|(_, rest)| rest) else {
+            continue;
+        };
+        let Some((index, name)) = rest.strip_prefix('[').and_then(|r| r.split_once("] ")) else {
+            continue;
+        };
+        if index.parse::<u32>().is_ok() {
+            devices.push(DeviceInfo {
+                id: index.to_string(),
+                name: name.trim().to_string(),
+            });
+        }
+    }
+    devices
+}
+
+/// Video devices from `ffmpeg -f dshow -list_devices true`, e.g.
+/// `[dshow @ 000001] "USB Capture HDMI" (video)`.
+fn parse_dshow_devices(log: &str) -> Vec<DeviceInfo> {
+    log.lines()
+        .filter(|line| line.trim_end().ends_with("(video)"))
+        .filter_map(|line| {
+            let start = line.find('"')? + 1;
+            let len = line[start..].find('"')?;
+            let name = &line[start..start + len];
+            Some(DeviceInfo {
+                id: name.to_string(),
+                name: name.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_avfoundation_video_devices_only() {
+        let log = "\
+[AVFoundation indev @ 0x7f8] AVFoundation video devices:
+[AVFoundation indev @ 0x7f8] [0] FaceTime HD Camera
+[AVFoundation indev @ 0x7f8] [1] Capture screen 0
+[AVFoundation indev @ 0x7f8] AVFoundation audio devices:
+[AVFoundation indev @ 0x7f8] [0] MacBook Pro Microphone
+";
+        let devices = parse_avfoundation_devices(log);
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].id, "0");
+        assert_eq!(devices[0].name, "FaceTime HD Camera");
+        assert_eq!(devices[1].index(), Some(1));
+    }
+
+    #[test]
+    fn parses_dshow_video_devices_only() {
+        let log = "\
+[dshow @ 000001] \"USB Capture HDMI\" (video)
+[dshow @ 000001]   Alternative name \"@device_pnp_\\\\?\\usb#vid\"
+[dshow @ 000001] \"Microphone (USB Capture HDMI)\" (audio)
+";
+        let devices = parse_dshow_devices(log);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].id, "USB Capture HDMI");
+    }
+
+    #[test]
+    fn missing_ffmpeg_fails_to_open() {
+        let mut cfg = FfmpegCaptureConfig::new("0", 64, 48);
+        cfg.ffmpeg_path = Some("/nonexistent/ffmpeg".into());
+        assert!(FfmpegCapture::open(cfg.clone()).is_err());
+        cfg.fps = 0;
+        assert!(FfmpegCapture::open(cfg).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+//! Capture formats: what a device offers and what a host asks for.
+//!
+//! [`Webcam::supported_formats`](crate::Webcam::supported_formats) lists a device's modes;
+//! [`Webcam::with_format`](crate::Webcam::with_format) opens exactly one of them and fails with
+//! [`WebcamError::UnsupportedFormat`](crate::WebcamError::UnsupportedFormat) otherwise, unlike
+//! `Webcam::new`, which takes whatever is closest.
+
+use std::fmt;
+
+/// Pixel encoding delivered by the device (frames are always converted to RGBA8).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PixelFormat {
+    Mjpeg,
+    Yuyv,
+    Nv12,
+    Gray,
+    Rgb,
+    Bgr,
+}
+
+impl PixelFormat {
+    /// FourCC code as V4L2 spells it.
+    pub fn fourcc(self) -> &'static str {
+        match self {
+            PixelFormat::Mjpeg => "MJPG",
+            PixelFormat::Yuyv => "YUYV",
+            PixelFormat::Nv12 => "NV12",
+            PixelFormat::Gray => "GREY",
+            PixelFormat::Rgb => "RGB3",
+            PixelFormat::Bgr => "BGR3",
+        }
+    }
+}
+
+/// One capture mode: resolution, frame rate and pixel encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WebcamFormat {
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+    pub pixel_format: PixelFormat,
+}
+
+impl WebcamFormat {
+    pub fn new(width: u32, height: u32, fps: u32, pixel_format: PixelFormat) -> Self {
+        Self {
+            width,
+            height,
+            fps,
+            pixel_format,
+        }
+    }
+}
+
+impl fmt::Display for WebcamFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{}@{} {}",
+            self.width,
+            self.height,
+            self.fps,
+            self.pixel_format.fourcc()
+        )
+    }
+}
+
+/// Sort largest first (then fastest) and drop duplicates the backend reports twice.
+#[cfg_attr(not(feature = "native"), allow(dead_code))]
+pub(crate) fn normalize(formats: &mut Vec<WebcamFormat>) {
+    formats.sort_by(|a, b| b.cmp(a));
+    formats.dedup();
+}
+
+#[cfg(feature = "native")]
+pub(crate) mod native {
+    use nokhwa::utils::{CameraFormat, FrameFormat};
+
+    use super::{PixelFormat, WebcamFormat};
+
+    pub(crate) fn from_camera_format(f: &CameraFormat) -> WebcamFormat {
+        let pixel_format = match f.format() {
+            FrameFormat::MJPEG => PixelFormat::Mjpeg,
+            FrameFormat::YUYV => PixelFormat::Yuyv,
+            FrameFormat::NV12 => PixelFormat::Nv12,
+            FrameFormat::GRAY => PixelFormat::Gray,
+            FrameFormat::RAWRGB => PixelFormat::Rgb,
+            FrameFormat::RAWBGR => PixelFormat::Bgr,
+        };
+        WebcamFormat::new(f.width(), f.height(), f.frame_rate(), pixel_format)
+    }
+
+    pub(crate) fn to_camera_format(f: &WebcamFormat) -> CameraFormat {
+        let format = match f.pixel_format {
+            PixelFormat::Mjpeg => FrameFormat::MJPEG,
+            PixelFormat::Yuyv => FrameFormat::YUYV,
+            PixelFormat::Nv12 => FrameFormat::NV12,
+            PixelFormat::Gray => FrameFormat::GRAY,
+            PixelFormat::Rgb => FrameFormat::RAWRGB,
+            PixelFormat::Bgr => FrameFormat::RAWBGR,
+        };
+        CameraFormat::new_from(f.width, f.height, format, f.fps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_sort_largest_first_without_duplicates() {
+        let hd = WebcamFormat::new(1280, 720, 30, PixelFormat::Mjpeg);
+        let sd = WebcamFormat::new(640, 480, 30, PixelFormat::Yuyv);
+        let hd60 = WebcamFormat::new(1280, 720, 60, PixelFormat::Mjpeg);
+        let mut formats = vec![sd, hd, hd60, sd];
+        normalize(&mut formats);
+        assert_eq!(formats, vec![hd60, hd, sd]);
+        assert_eq!(hd.to_string(), "1280x720@30 MJPG");
+    }
+}
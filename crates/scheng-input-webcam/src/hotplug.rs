@@ -0,0 +1,218 @@
+//! Device hot-plug: watching the device list and pacing reopen attempts.
+//!
+//! [`Webcam`](crate::Webcam) reopens its device on its own when frames stop (see
+//! [`WebcamStatus`](crate::WebcamStatus)), spacing attempts with a [`Backoff`]. A host that
+//! also runs a [`DeviceWatcher`] can react to `DeviceEvent::Added` by calling
+//! [`Webcam::retry_now`](crate::Webcam::retry_now), so a replugged camera comes back at once
+//! instead of at the next backoff step.
+
+use std::time::{Duration, Instant};
+
+use crate::WebcamError;
+
+/// Exponential backoff between reopen attempts.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    initial: Duration,
+    max: Duration,
+    delay: Duration,
+    next_at: Option<Instant>,
+    attempts: u32,
+}
+
+impl Backoff {
+    /// Wait `initial` after the first failure, doubling up to `max`.
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self {
+            initial,
+            max: max.max(initial),
+            delay: initial,
+            next_at: None,
+            attempts: 0,
+        }
+    }
+
+    /// Whether another attempt is allowed at `now`.
+    pub fn ready(&self, now: Instant) -> bool {
+        self.next_at.is_none_or(|t| now >= t)
+    }
+
+    /// Record a failed attempt at `now` and schedule the next one.
+    pub fn failed(&mut self, now: Instant) {
+        self.attempts += 1;
+        self.next_at = Some(now + self.delay);
+        self.delay = (self.delay * 2).min(self.max);
+    }
+
+    /// Back to the initial delay (after a successful attempt).
+    pub fn reset(&mut self) {
+        self.delay = self.initial;
+        self.next_at = None;
+        self.attempts = 0;
+    }
+
+    /// Allow the next attempt immediately, keeping the current delay.
+    pub fn retry_now(&mut self) {
+        self.next_at = None;
+    }
+
+    /// Failed attempts since the last reset.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+}
+
+impl Default for Backoff {
+    /// 250 ms doubling up to 5 s.
+    fn default() -> Self {
+        Self::new(Duration::from_millis(250), Duration::from_secs(5))
+    }
+}
+
+/// A capture device as reported by the platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// Backend identifier (the numeric index on most platforms).
+    pub id: String,
+    pub name: String,
+}
+
+impl DeviceInfo {
+    /// The index to pass to [`Webcam::new`](crate::Webcam::new), if `id` is numeric.
+    pub fn index(&self) -> Option<u32> {
+        self.id.parse().ok()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceEvent {
+    Added(DeviceInfo),
+    Removed(DeviceInfo),
+}
+
+/// Capture devices currently attached.
+pub fn list_devices() -> Result<Vec<DeviceInfo>, WebcamError> {
+    #[cfg(not(feature = "native"))]
+    {
+        Err(WebcamError::NotEnabled)
+    }
+
+    #[cfg(feature = "native")]
+    {
+        use nokhwa::utils::ApiBackend;
+
+        let infos =
+            nokhwa::query(ApiBackend::Auto).map_err(|e| WebcamError::Backend(e.to_string()))?;
+        Ok(infos
+            .iter()
+            .map(|info| DeviceInfo {
+                id: info.index().to_string(),
+                name: info.human_name(),
+            })
+            .collect())
+    }
+}
+
+/// Polls the device list and reports what changed.
+#[derive(Debug, Clone)]
+pub struct DeviceWatcher {
+    known: Vec<DeviceInfo>,
+    interval: Duration,
+    last_poll: Option<Instant>,
+}
+
+impl DeviceWatcher {
+    /// Query the platform at most once per `interval` (device queries can take milliseconds).
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            known: Vec::new(),
+            interval,
+            last_poll: None,
+        }
+    }
+
+    /// Devices seen at the last query.
+    pub fn devices(&self) -> &[DeviceInfo] {
+        &self.known
+    }
+
+    /// Re-query if `interval` has passed and return the changes since the previous query. The
+    /// first query reports every device as added.
+    pub fn poll(&mut self) -> Result<Vec<DeviceEvent>, WebcamError> {
+        let now = Instant::now();
+        if self
+            .last_poll
+            .is_some_and(|t| now.duration_since(t) < self.interval)
+        {
+            return Ok(Vec::new());
+        }
+        self.last_poll = Some(now);
+        Ok(self.update(list_devices()?))
+    }
+
+    fn update(&mut self, current: Vec<DeviceInfo>) -> Vec<DeviceEvent> {
+        let mut events: Vec<DeviceEvent> = self
+            .known
+            .iter()
+            .filter(|d| !current.contains(d))
+            .cloned()
+            .map(DeviceEvent::Removed)
+            .collect();
+        events.extend(
+            current
+                .iter()
+                .filter(|d| !self.known.contains(d))
+                .cloned()
+                .map(DeviceEvent::Added),
+        );
+        self.known = current;
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_max_and_resets() {
+        let t0 = Instant::now();
+        let mut b = Backoff::new(Duration::from_millis(100), Duration::from_millis(300));
+        assert!(b.ready(t0));
+        b.failed(t0);
+        assert!(!b.ready(t0 + Duration::from_millis(99)));
+        assert!(b.ready(t0 + Duration::from_millis(100)));
+        b.failed(t0);
+        b.failed(t0);
+        assert!(b.ready(t0 + Duration::from_millis(300)));
+        b.failed(t0);
+        assert!(!b.ready(t0 + Duration::from_millis(299)));
+        assert_eq!(b.attempts(), 4);
+        b.retry_now();
+        assert!(b.ready(t0));
+        b.reset();
+        assert_eq!(b.attempts(), 0);
+    }
+
+    #[test]
+    fn watcher_reports_added_and_removed_devices() {
+        let dev = |id: &str| DeviceInfo {
+            id: id.into(),
+            name: format!("cam {id}"),
+        };
+        let mut w = DeviceWatcher::new(Duration::ZERO);
+        assert_eq!(
+            w.update(vec![dev("0"), dev("1")]),
+            vec![DeviceEvent::Added(dev("0")), DeviceEvent::Added(dev("1"))]
+        );
+        assert_eq!(
+            w.update(vec![dev("1")]),
+            vec![DeviceEvent::Removed(dev("0"))]
+        );
+        assert_eq!(
+            w.update(vec![dev("0"), dev("1")]),
+            vec![DeviceEvent::Added(dev("0"))]
+        );
+        assert_eq!(w.devices()[0].index(), Some(0));
+    }
+}
@@ -1,10 +1,27 @@
+use std::time::{Duration, Instant};
+
 use thiserror::Error;
 
+#[cfg(feature = "ffmpeg")]
+pub mod ffmpeg;
+pub mod formats;
+pub mod hotplug;
+#[cfg(feature = "ffmpeg")]
+pub use ffmpeg::{list_ffmpeg_devices, FfmpegCapture, FfmpegCaptureConfig};
+pub use formats::{PixelFormat, WebcamFormat};
+pub use hotplug::{list_devices, Backoff, DeviceEvent, DeviceInfo, DeviceWatcher};
+
 #[derive(Debug, Error)]
 pub enum WebcamError {
     #[error("webcam support not enabled (build with feature: scheng-input-webcam/native)")]
     NotEnabled,
 
+    #[error("webcam disconnected (reconnecting)")]
+    Disconnected,
+
+    #[error("webcam format {0} not supported by the device")]
+    UnsupportedFormat(WebcamFormat),
+
     #[error("{0}")]
     Backend(String),
 }
@@ -16,9 +33,31 @@ pub struct RgbaFrame {
     pub bytes: Vec<u8>, // RGBA8
 }
 
+/// Connection state of a [`Webcam`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WebcamStatus {
+    /// The stream is open and delivering frames.
+    Streaming,
+    /// The device stopped delivering frames. `poll_rgba` reopens it with backoff and returns
+    /// `WebcamError::Disconnected` until it is back.
+    Reconnecting {
+        /// Failed reopen attempts so far.
+        attempts: u32,
+        /// Why the stream was lost or the last reopen failed.
+        last_error: String,
+    },
+}
+
 pub struct Webcam {
+    index: u32,
+    width: u32,
+    height: u32,
+    /// Set by `with_format`: reopen with exactly this mode instead of best effort.
+    format: Option<WebcamFormat>,
+    status: WebcamStatus,
+    backoff: Backoff,
     #[cfg(feature = "native")]
-    cam: nokhwa::Camera,
+    cam: Option<nokhwa::Camera>,
 }
 
 impl Webcam {
@@ -27,50 +66,162 @@ impl Webcam {
         #[cfg(not(feature = "native"))]
         {
             let _ = (index, width, height);
-            return Err(WebcamError::NotEnabled);
+            Err(WebcamError::NotEnabled)
+        }
+
+        #[cfg(feature = "native")]
+        {
+            let cam = open_camera(index, width, height, None)?;
+            Ok(Self {
+                index,
+                width,
+                height,
+                format: None,
+                status: WebcamStatus::Streaming,
+                backoff: Backoff::default(),
+                cam: Some(cam),
+            })
+        }
+    }
+
+    /// Open webcam `index` in exactly `format`, one of [`Webcam::supported_formats`].
+    ///
+    /// Fails with [`WebcamError::UnsupportedFormat`] if the device does not offer it; reconnects
+    /// reopen in the same format.
+    pub fn with_format(index: u32, format: WebcamFormat) -> Result<Self, WebcamError> {
+        #[cfg(not(feature = "native"))]
+        {
+            let _ = (index, format);
+            Err(WebcamError::NotEnabled)
+        }
+
+        #[cfg(feature = "native")]
+        {
+            if !Self::supported_formats(index)?.contains(&format) {
+                return Err(WebcamError::UnsupportedFormat(format));
+            }
+            let cam = open_camera(index, format.width, format.height, Some(format))?;
+            Ok(Self {
+                index,
+                width: format.width,
+                height: format.height,
+                format: Some(format),
+                status: WebcamStatus::Streaming,
+                backoff: Backoff::default(),
+                cam: Some(cam),
+            })
+        }
+    }
+
+    /// Capture modes webcam `index` offers, largest and fastest first.
+    pub fn supported_formats(index: u32) -> Result<Vec<WebcamFormat>, WebcamError> {
+        #[cfg(not(feature = "native"))]
+        {
+            let _ = index;
+            Err(WebcamError::NotEnabled)
         }
 
         #[cfg(feature = "native")]
         {
             use nokhwa::{
                 pixel_format::RgbFormat,
-                utils::{CameraIndex, RequestedFormat, RequestedFormatType, Resolution},
+                utils::{CameraIndex, RequestedFormat, RequestedFormatType},
                 Camera,
             };
 
-            let idx = CameraIndex::Index(index);
+            let requested = RequestedFormat::new::<RgbFormat>(RequestedFormatType::None);
+            let mut cam = Camera::new(CameraIndex::Index(index), requested)
+                .map_err(|e| WebcamError::Backend(e.to_string()))?;
+            let mut formats: Vec<WebcamFormat> = cam
+                .compatible_camera_formats()
+                .map_err(|e| WebcamError::Backend(e.to_string()))?
+                .iter()
+                .map(formats::native::from_camera_format)
+                .collect();
+            formats::normalize(&mut formats);
+            Ok(formats)
+        }
+    }
 
-            // RequestedFormat is not generic in nokhwa-core 0.1.8; pixel format is via the constructor.
-            let requested = RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestFrameRate);
+    /// Device index this webcam opens.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
 
-            let mut cam =
-                Camera::new(idx, requested).map_err(|e| WebcamError::Backend(e.to_string()))?;
+    /// Resolution requested at open (the device may deliver another).
+    pub fn requested_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
 
-            cam.open_stream()
-                .map_err(|e| WebcamError::Backend(e.to_string()))?;
+    /// The exact format requested with [`Webcam::with_format`], if any.
+    pub fn requested_format(&self) -> Option<WebcamFormat> {
+        self.format
+    }
 
-            // Best-effort set resolution; do not fail if rejected.
-            let _ = cam.set_resolution(Resolution::new(width, height));
+    /// The format the device is currently delivering (`None` while reconnecting).
+    pub fn active_format(&self) -> Option<WebcamFormat> {
+        #[cfg(not(feature = "native"))]
+        {
+            None
+        }
 
-            Ok(Self { cam })
+        #[cfg(feature = "native")]
+        {
+            self.cam
+                .as_ref()
+                .map(|cam| formats::native::from_camera_format(&cam.camera_format()))
         }
     }
 
+    pub fn status(&self) -> &WebcamStatus {
+        &self.status
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        self.status == WebcamStatus::Streaming
+    }
+
+    /// Delays between reopen attempts (default 250 ms doubling up to 5 s).
+    pub fn set_reconnect_backoff(&mut self, initial: Duration, max: Duration) {
+        self.backoff = Backoff::new(initial, max);
+    }
+
+    /// Try to reopen on the next `poll_rgba` instead of waiting out the backoff, e.g. when a
+    /// [`DeviceWatcher`] reports the device again.
+    pub fn retry_now(&mut self) {
+        self.backoff.retry_now();
+    }
+
     /// Poll the latest decoded frame as RGBA8 bytes.
+    ///
+    /// If the device went away this returns `WebcamError::Disconnected` (status
+    /// [`WebcamStatus::Reconnecting`]) and keeps trying to reopen it on later calls.
     pub fn poll_rgba(&mut self) -> Result<RgbaFrame, WebcamError> {
         #[cfg(not(feature = "native"))]
         {
-            return Err(WebcamError::NotEnabled);
+            Err(WebcamError::NotEnabled)
         }
 
         #[cfg(feature = "native")]
         {
             use nokhwa::pixel_format::RgbAFormat;
 
-            let buf = self
-                .cam
-                .frame()
-                .map_err(|e| WebcamError::Backend(e.to_string()))?;
+            if self.cam.is_none() {
+                self.reopen()?;
+            }
+            let cam = self.cam.as_mut().expect("camera open");
+
+            let buf = match cam.frame() {
+                Ok(buf) => buf,
+                Err(e) => {
+                    // The stream broke (unplugged, driver reset): drop it and start reconnecting.
+                    if let Some(mut cam) = self.cam.take() {
+                        let _ = cam.stop_stream();
+                    }
+                    self.lost(e.to_string());
+                    return Err(WebcamError::Disconnected);
+                }
+            };
 
             let res = buf.resolution();
             let w = res.width_x;   // already u32
@@ -89,4 +240,68 @@ impl Webcam {
             })
         }
     }
+
+    /// Record a lost stream or failed reopen and schedule the next attempt.
+    #[cfg_attr(not(feature = "native"), allow(dead_code))]
+    fn lost(&mut self, error: String) {
+        self.backoff.failed(Instant::now());
+        self.status = WebcamStatus::Reconnecting {
+            attempts: self.backoff.attempts().saturating_sub(1),
+            last_error: error,
+        };
+    }
+
+    #[cfg(feature = "native")]
+    fn reopen(&mut self) -> Result<(), WebcamError> {
+        if !self.backoff.ready(Instant::now()) {
+            return Err(WebcamError::Disconnected);
+        }
+        match open_camera(self.index, self.width, self.height, self.format) {
+            Ok(cam) => {
+                self.cam = Some(cam);
+                self.backoff.reset();
+                self.status = WebcamStatus::Streaming;
+                Ok(())
+            }
+            Err(e) => {
+                self.lost(e.to_string());
+                Err(WebcamError::Disconnected)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "native")]
+fn open_camera(
+    index: u32,
+    width: u32,
+    height: u32,
+    format: Option<WebcamFormat>,
+) -> Result<nokhwa::Camera, WebcamError> {
+    use nokhwa::{
+        pixel_format::RgbFormat,
+        utils::{CameraIndex, RequestedFormat, RequestedFormatType, Resolution},
+        Camera,
+    };
+
+    let idx = CameraIndex::Index(index);
+
+    // RequestedFormat is not generic in nokhwa-core 0.1.8; pixel format is via the constructor.
+    let requested = RequestedFormat::new::<RgbFormat>(match format {
+        Some(f) => RequestedFormatType::Exact(formats::native::to_camera_format(&f)),
+        None => RequestedFormatType::AbsoluteHighestFrameRate,
+    });
+
+    let mut cam =
+        Camera::new(idx, requested).map_err(|e| WebcamError::Backend(e.to_string()))?;
+
+    cam.open_stream()
+        .map_err(|e| WebcamError::Backend(e.to_string()))?;
+
+    if format.is_none() {
+        // Best-effort set resolution; do not fail if rejected.
+        let _ = cam.set_resolution(Resolution::new(width, height));
+    }
+
+    Ok(cam)
 }
@@ -0,0 +1,35 @@
+//! scheng-installation
+//!
+//! Unattended-installation helpers: an on/off [`Schedule`], a crash-restart [`Supervisor`] that
+//! launches the show binary (autostart, exponential backoff) and status reporting over OSC or a
+//! tiny HTTP endpoint.
+//!
+//! The supervisor runs as its own small process next to the show, so a GL driver crash or panic
+//! in the show never takes the supervisor down with it.
+//!
+//! ```no_run
+//! use scheng_installation::{Schedule, Supervisor, SupervisorConfig};
+//!
+//! let cfg = SupervisorConfig::new("./my-show")
+//!     .with_args(["--fullscreen"])
+//!     .with_schedule(Schedule::daily(10 * 60, 18 * 60));
+//! let mut sup = Supervisor::new(cfg);
+//! loop {
+//!     let status = sup.tick();
+//!     println!("{}", status.to_json());
+//!     std::thread::sleep(std::time::Duration::from_millis(500));
+//! }
+//! ```
+
+pub mod schedule;
+pub mod status;
+pub mod supervisor;
+
+pub use schedule::{LocalTime, Schedule, ScheduleWindow};
+pub use status::HttpStatusServer;
+#[cfg(feature = "osc")]
+pub use status::OscStatusReporter;
+pub use supervisor::{
+    ChildExit, CommandLauncher, Launch, RunState, Supervised, Supervisor, SupervisorConfig,
+    SupervisorStatus,
+};
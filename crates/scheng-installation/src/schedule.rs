@@ -0,0 +1,165 @@
+//! Weekly on/off schedule.
+
+use serde::{Deserialize, Serialize};
+
+/// Minutes in a day.
+const DAY: u16 = 24 * 60;
+
+/// Wall-clock time used for schedule checks: weekday (0 = Monday) and minute of day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    pub weekday: u8,
+    pub minute: u16,
+}
+
+impl LocalTime {
+    pub fn new(weekday: u8, hour: u8, minute: u8) -> Self {
+        Self {
+            weekday: weekday % 7,
+            minute: (hour as u16 * 60 + minute as u16) % DAY,
+        }
+    }
+
+    /// Current local time (UTC on platforms without `localtime_r`).
+    pub fn now() -> Self {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        Self::from_unix(secs)
+    }
+
+    #[cfg(unix)]
+    fn from_unix(secs: i64) -> Self {
+        let t = secs as libc::time_t;
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&t, &mut tm) }.is_null() {
+            return Self::from_unix_utc(secs);
+        }
+        // tm_wday: 0 = Sunday.
+        Self {
+            weekday: ((tm.tm_wday + 6) % 7) as u8,
+            minute: (tm.tm_hour * 60 + tm.tm_min) as u16,
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn from_unix(secs: i64) -> Self {
+        Self::from_unix_utc(secs)
+    }
+
+    fn from_unix_utc(secs: i64) -> Self {
+        let days = secs.div_euclid(86_400);
+        let minute = (secs.rem_euclid(86_400) / 60) as u16;
+        // 1970-01-01 was a Thursday (weekday 3 with Monday = 0).
+        Self {
+            weekday: ((days + 3).rem_euclid(7)) as u8,
+            minute,
+        }
+    }
+
+    fn prev_weekday(self) -> u8 {
+        (self.weekday + 6) % 7
+    }
+}
+
+/// One "on" window. `end < start` spans midnight (e.g. 22:00–02:00).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleWindow {
+    /// Bit `d` set = window starts on weekday `d` (0 = Monday).
+    pub days: u8,
+    /// Minutes after midnight.
+    pub start: u16,
+    pub end: u16,
+}
+
+impl ScheduleWindow {
+    pub const EVERY_DAY: u8 = 0b111_1111;
+
+    fn starts_on(&self, weekday: u8) -> bool {
+        self.days & (1 << weekday) != 0
+    }
+
+    fn contains(&self, t: LocalTime) -> bool {
+        if self.start == self.end {
+            // Full day.
+            return self.starts_on(t.weekday);
+        }
+        if self.start < self.end {
+            self.starts_on(t.weekday) && t.minute >= self.start && t.minute < self.end
+        } else {
+            (self.starts_on(t.weekday) && t.minute >= self.start)
+                || (self.starts_on(t.prev_weekday()) && t.minute < self.end)
+        }
+    }
+}
+
+/// Set of on-windows; an empty schedule means "always on".
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub windows: Vec<ScheduleWindow>,
+}
+
+impl Schedule {
+    pub fn always() -> Self {
+        Self::default()
+    }
+
+    /// Every day from `start` to `end` (minutes after midnight).
+    pub fn daily(start: u16, end: u16) -> Self {
+        Self {
+            windows: vec![ScheduleWindow {
+                days: ScheduleWindow::EVERY_DAY,
+                start: start % DAY,
+                end: end % DAY,
+            }],
+        }
+    }
+
+    pub fn with_window(mut self, window: ScheduleWindow) -> Self {
+        self.windows.push(window);
+        self
+    }
+
+    pub fn is_on(&self, t: LocalTime) -> bool {
+        self.windows.is_empty() || self.windows.iter().any(|w| w.contains(t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daily_window_bounds() {
+        let s = Schedule::daily(10 * 60, 18 * 60);
+        assert!(!s.is_on(LocalTime::new(0, 9, 59)));
+        assert!(s.is_on(LocalTime::new(0, 10, 0)));
+        assert!(s.is_on(LocalTime::new(6, 17, 59)));
+        assert!(!s.is_on(LocalTime::new(6, 18, 0)));
+        assert!(Schedule::always().is_on(LocalTime::new(3, 3, 3)));
+    }
+
+    #[test]
+    fn overnight_window_belongs_to_start_day() {
+        // Friday 22:00 -> Saturday 02:00 only.
+        let s = Schedule::default().with_window(ScheduleWindow {
+            days: 1 << 4,
+            start: 22 * 60,
+            end: 2 * 60,
+        });
+        assert!(s.is_on(LocalTime::new(4, 23, 0)));
+        assert!(s.is_on(LocalTime::new(5, 1, 30)));
+        assert!(!s.is_on(LocalTime::new(5, 23, 0)));
+        assert!(!s.is_on(LocalTime::new(4, 1, 30)));
+    }
+
+    #[test]
+    fn utc_epoch_is_thursday() {
+        assert_eq!(LocalTime::from_unix_utc(0), LocalTime::new(3, 0, 0));
+        assert_eq!(
+            LocalTime::from_unix_utc(4 * 86_400 + 3_660),
+            LocalTime::new(0, 1, 1)
+        );
+    }
+}
@@ -0,0 +1,264 @@
+//! Status reporting: OSC push and a minimal polled HTTP endpoint.
+
+use std::io::{self, Read, Write};
+#[cfg(feature = "osc")]
+use std::net::UdpSocket;
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "osc")]
+use rosc::{OscMessage, OscPacket, OscType};
+
+#[cfg(feature = "osc")]
+use crate::supervisor::RunState;
+use crate::supervisor::SupervisorStatus;
+
+/// Sends each status as OSC messages under a prefix (default `/scheng/supervisor`):
+/// `<prefix>/state` (string), `<prefix>/restarts` (int), `<prefix>/uptime` (float).
+#[cfg(feature = "osc")]
+#[derive(Debug)]
+pub struct OscStatusReporter {
+    sock: UdpSocket,
+    target: String,
+    prefix: String,
+}
+
+#[cfg(feature = "osc")]
+impl OscStatusReporter {
+    /// Send to `target` (e.g. "192.168.1.20:9001").
+    pub fn new(target: &str) -> io::Result<Self> {
+        let sock = UdpSocket::bind("0.0.0.0:0")?;
+        Ok(Self {
+            sock,
+            target: target.to_string(),
+            prefix: "/scheng/supervisor".to_string(),
+        })
+    }
+
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn report(&self, status: &SupervisorStatus) -> io::Result<()> {
+        let state = match status.state {
+            RunState::Off => "off",
+            RunState::Running => "running",
+            RunState::Backoff => "backoff",
+        };
+        let msgs = [
+            ("state", OscType::String(state.to_string())),
+            (
+                "restarts",
+                OscType::Int(status.restarts.min(i32::MAX as u64) as i32),
+            ),
+            ("uptime", OscType::Float(status.uptime_secs as f32)),
+        ];
+        for (name, arg) in msgs {
+            let pkt = OscPacket::Message(OscMessage {
+                addr: format!("{}/{name}", self.prefix),
+                args: vec![arg],
+            });
+            let bytes = rosc::encoder::encode(&pkt)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+            self.sock.send_to(&bytes, &self.target)?;
+        }
+        Ok(())
+    }
+}
+
+/// Connections that have not sent their request head (or taken the response) by then are closed.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Connections held open at once; further ones are closed as soon as they are accepted.
+const MAX_PENDING: usize = 16;
+
+/// Request heads longer than this are answered without waiting for the rest.
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+
+/// An accepted connection whose request head is still arriving or whose response is still being
+/// written. Sockets stay non-blocking; `poll` advances each one as far as it can without waiting.
+#[derive(Debug)]
+struct Pending {
+    stream: TcpStream,
+    head: Vec<u8>,
+    /// Response bytes not written yet (`Some` once the head has arrived).
+    out: Option<Vec<u8>>,
+    accepted: Instant,
+}
+
+/// Answers any HTTP request with the latest status as JSON. Call `poll` from the supervisor loop.
+#[derive(Debug)]
+pub struct HttpStatusServer {
+    listener: TcpListener,
+    pending: Vec<Pending>,
+}
+
+impl HttpStatusServer {
+    /// Bind to an address like "0.0.0.0:8088" (non-blocking).
+    pub fn bind(addr: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            pending: Vec::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<std::net::SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept new connections and advance every open one; never blocks. A slow or silent client
+    /// stays pending across calls (up to a few seconds) instead of stalling the supervisor.
+    pub fn poll(&mut self, status: &SupervisorStatus) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if self.pending.len() >= MAX_PENDING || stream.set_nonblocking(true).is_err() {
+                        continue;
+                    }
+                    self.pending.push(Pending {
+                        stream,
+                        head: Vec::new(),
+                        out: None,
+                        accepted: Instant::now(),
+                    });
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+        let body = status.to_json();
+        self.pending = std::mem::take(&mut self.pending)
+            .into_iter()
+            .filter_map(|conn| advance(conn, &body))
+            .collect();
+    }
+}
+
+/// Read what has arrived of the request head, answer once it is complete, and write as much of
+/// the answer as the socket takes. `None` once the connection is done with.
+fn advance(mut conn: Pending, body: &str) -> Option<Pending> {
+    if conn.out.is_none() {
+        let mut chunk = [0u8; 1024];
+        while !head_complete(&conn.head) {
+            match conn.stream.read(&mut chunk) {
+                Ok(0) => return None,
+                Ok(n) => conn.head.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    return (conn.accepted.elapsed() < REQUEST_TIMEOUT).then_some(conn);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(_) => return None,
+            }
+        }
+        // The request's content does not matter, only that it has arrived.
+        conn.out = Some(
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .into_bytes(),
+        );
+    }
+    let out = conn.out.as_mut()?;
+    while !out.is_empty() {
+        match conn.stream.write(out) {
+            Ok(0) => return None,
+            Ok(n) => {
+                out.drain(..n);
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                return (conn.accepted.elapsed() < REQUEST_TIMEOUT).then_some(conn);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => return None,
+        }
+    }
+    None
+}
+
+fn head_complete(head: &[u8]) -> bool {
+    head.len() >= MAX_HEAD_BYTES || head.windows(4).any(|w| w == b"\r\n\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::supervisor::RunState;
+
+    fn status() -> SupervisorStatus {
+        SupervisorStatus {
+            state: RunState::Running,
+            scheduled_on: true,
+            pid: Some(42),
+            uptime_secs: 1.0,
+            restarts: 0,
+            last_exit: None,
+            last_error: None,
+            backoff_secs: 0.0,
+        }
+    }
+
+    /// Poll until `client` has read the whole response (the server closes after it).
+    fn response(server: &mut HttpStatusServer, client: &mut TcpStream) -> String {
+        client.set_nonblocking(true).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut resp = Vec::new();
+        let mut chunk = [0u8; 1024];
+        while Instant::now() < deadline {
+            server.poll(&status());
+            match client.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => resp.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                Err(e) => panic!("read: {e}"),
+            }
+        }
+        String::from_utf8(resp).unwrap()
+    }
+
+    #[test]
+    fn silent_clients_wait_without_blocking_the_poll() {
+        let mut server = HttpStatusServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+
+        // A client that connects and says nothing stays pending; poll returns at once.
+        let _silent = TcpStream::connect(addr).unwrap();
+        let mut slow = TcpStream::connect(addr).unwrap();
+        slow.write_all(b"GET / HTTP/1.1\r\n").unwrap();
+        let t0 = Instant::now();
+        for _ in 0..10 {
+            server.poll(&status());
+        }
+        assert!(t0.elapsed() < Duration::from_millis(100));
+        assert_eq!(server.pending.len(), 2);
+
+        // The slow client's head completes on a later poll and is answered.
+        slow.write_all(b"Host: x\r\n\r\n").unwrap();
+        let resp = response(&mut server, &mut slow);
+        assert!(resp.starts_with("HTTP/1.1 200 OK"), "{resp}");
+        assert!(resp.ends_with(&status().to_json()));
+        assert_eq!(server.pending.len(), 1);
+    }
+
+    #[test]
+    fn pending_connections_are_capped() {
+        let mut server = HttpStatusServer::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let clients: Vec<_> = (0..MAX_PENDING + 4)
+            .map(|_| TcpStream::connect(addr).unwrap())
+            .collect();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while server.pending.len() < MAX_PENDING && Instant::now() < deadline {
+            server.poll(&status());
+        }
+        server.poll(&status());
+        assert_eq!(server.pending.len(), MAX_PENDING);
+        drop(clients);
+    }
+}
@@ -0,0 +1,419 @@
+//! Crash-restart supervisor for the show process.
+
+use std::io;
+use std::path::PathBuf;
+use std::process::{Child, Command};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::schedule::{LocalTime, Schedule};
+
+/// How a supervised process ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum ChildExit {
+    Code(i32),
+    /// Killed by a signal / no exit code available.
+    Signal,
+}
+
+impl ChildExit {
+    pub fn is_clean(self) -> bool {
+        self == ChildExit::Code(0)
+    }
+}
+
+/// A running child process.
+pub trait Supervised {
+    /// Non-blocking exit check.
+    fn try_exit(&mut self) -> io::Result<Option<ChildExit>>;
+    /// Terminate the process (used when the schedule switches off).
+    fn kill(&mut self);
+    fn id(&self) -> u32;
+}
+
+/// Starts the show process.
+pub trait Launch {
+    type Child: Supervised;
+    fn launch(&mut self) -> io::Result<Self::Child>;
+}
+
+impl Supervised for Child {
+    fn try_exit(&mut self) -> io::Result<Option<ChildExit>> {
+        Ok(self
+            .try_wait()?
+            .map(|s| s.code().map(ChildExit::Code).unwrap_or(ChildExit::Signal)))
+    }
+
+    fn kill(&mut self) {
+        let _ = Child::kill(self);
+        let _ = self.wait();
+    }
+
+    fn id(&self) -> u32 {
+        Child::id(self)
+    }
+}
+
+/// Launches `program args..` with `std::process::Command`.
+#[derive(Debug, Clone)]
+pub struct CommandLauncher {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub cwd: Option<PathBuf>,
+}
+
+impl Launch for CommandLauncher {
+    type Child = Child;
+
+    fn launch(&mut self) -> io::Result<Child> {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args);
+        if let Some(dir) = &self.cwd {
+            cmd.current_dir(dir);
+        }
+        cmd.spawn()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SupervisorConfig {
+    pub launcher: CommandLauncher,
+    pub schedule: Schedule,
+    /// First restart delay; doubles on every quick crash.
+    pub backoff_initial: Duration,
+    pub backoff_max: Duration,
+    /// A run at least this long resets the backoff.
+    pub stable_after: Duration,
+}
+
+impl SupervisorConfig {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            launcher: CommandLauncher {
+                program: program.into(),
+                args: Vec::new(),
+                cwd: None,
+            },
+            schedule: Schedule::always(),
+            backoff_initial: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(60),
+            stable_after: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.launcher.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_cwd(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.launcher.cwd = Some(dir.into());
+        self
+    }
+
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+}
+
+/// Coarse supervisor state for status reporting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    /// Outside the schedule; the show is stopped.
+    Off,
+    Running,
+    /// Waiting before the next restart attempt.
+    Backoff,
+}
+
+/// Snapshot returned by every `tick`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SupervisorStatus {
+    pub state: RunState,
+    pub scheduled_on: bool,
+    pub pid: Option<u32>,
+    pub uptime_secs: f64,
+    /// Restarts after an unexpected exit (not schedule starts).
+    pub restarts: u64,
+    pub last_exit: Option<ChildExit>,
+    pub last_error: Option<String>,
+    pub backoff_secs: f64,
+}
+
+impl SupervisorStatus {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_else(|_| "{}".to_string())
+    }
+}
+
+enum Phase<C> {
+    Off,
+    Running { child: C, since: Instant },
+    Backoff { until: Instant },
+}
+
+/// Keeps the show running while the schedule is on (see crate docs).
+pub struct Supervisor<L: Launch = CommandLauncher> {
+    launcher: L,
+    schedule: Schedule,
+    backoff_initial: Duration,
+    backoff_max: Duration,
+    stable_after: Duration,
+    phase: Phase<L::Child>,
+    backoff: Duration,
+    restarts: u64,
+    last_exit: Option<ChildExit>,
+    last_error: Option<String>,
+}
+
+impl<L: Launch> std::fmt::Debug for Supervisor<L> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Supervisor")
+            .field("restarts", &self.restarts)
+            .field("backoff", &self.backoff)
+            .finish()
+    }
+}
+
+impl Supervisor<CommandLauncher> {
+    pub fn new(cfg: SupervisorConfig) -> Self {
+        Self::with_launcher(cfg.launcher.clone(), cfg)
+    }
+}
+
+impl<L: Launch> Supervisor<L> {
+    /// Supervise with a custom launcher (`cfg.launcher` is ignored).
+    pub fn with_launcher(launcher: L, cfg: SupervisorConfig) -> Self {
+        Self {
+            launcher,
+            schedule: cfg.schedule,
+            backoff_initial: cfg.backoff_initial,
+            backoff_max: cfg.backoff_max,
+            stable_after: cfg.stable_after,
+            phase: Phase::Off,
+            backoff: cfg.backoff_initial,
+            restarts: 0,
+            last_exit: None,
+            last_error: None,
+        }
+    }
+
+    pub fn set_schedule(&mut self, schedule: Schedule) {
+        self.schedule = schedule;
+    }
+
+    /// Advance the state machine using the current wall clock.
+    pub fn tick(&mut self) -> SupervisorStatus {
+        self.tick_at(LocalTime::now(), Instant::now())
+    }
+
+    /// Advance the state machine at an explicit time (tests, simulated schedules).
+    pub fn tick_at(&mut self, local: LocalTime, now: Instant) -> SupervisorStatus {
+        let on = self.schedule.is_on(local);
+
+        let phase = std::mem::replace(&mut self.phase, Phase::Off);
+        self.phase = match (phase, on) {
+            (Phase::Running { mut child, .. }, false) => {
+                child.kill();
+                self.backoff = self.backoff_initial;
+                Phase::Off
+            }
+            (_, false) => {
+                self.backoff = self.backoff_initial;
+                Phase::Off
+            }
+            (Phase::Off, true) => self.start(now, false),
+            (Phase::Backoff { until }, true) if now >= until => self.start(now, true),
+            (p @ Phase::Backoff { .. }, true) => p,
+            (Phase::Running { mut child, since }, true) => match child.try_exit() {
+                Ok(None) => Phase::Running { child, since },
+                Ok(Some(exit)) => {
+                    self.last_exit = Some(exit);
+                    self.after_exit(now, since)
+                }
+                Err(e) => {
+                    self.last_error = Some(format!("wait failed: {e}"));
+                    child.kill();
+                    self.after_exit(now, since)
+                }
+            },
+        };
+
+        self.status(on, now)
+    }
+
+    fn start(&mut self, now: Instant, is_restart: bool) -> Phase<L::Child> {
+        match self.launcher.launch() {
+            Ok(child) => {
+                if is_restart {
+                    self.restarts += 1;
+                }
+                Phase::Running { child, since: now }
+            }
+            Err(e) => {
+                self.last_error = Some(format!("launch failed: {e}"));
+                self.schedule_backoff(now)
+            }
+        }
+    }
+
+    fn after_exit(&mut self, now: Instant, since: Instant) -> Phase<L::Child> {
+        if now.duration_since(since) >= self.stable_after {
+            self.backoff = self.backoff_initial;
+        }
+        self.schedule_backoff(now)
+    }
+
+    fn schedule_backoff(&mut self, now: Instant) -> Phase<L::Child> {
+        let until = now + self.backoff;
+        self.backoff = (self.backoff * 2).min(self.backoff_max);
+        Phase::Backoff { until }
+    }
+
+    fn status(&self, scheduled_on: bool, now: Instant) -> SupervisorStatus {
+        let (state, pid, uptime, wait) = match &self.phase {
+            Phase::Off => (RunState::Off, None, 0.0, 0.0),
+            Phase::Running { child, since } => (
+                RunState::Running,
+                Some(child.id()),
+                now.duration_since(*since).as_secs_f64(),
+                0.0,
+            ),
+            Phase::Backoff { until } => (
+                RunState::Backoff,
+                None,
+                0.0,
+                until.saturating_duration_since(now).as_secs_f64(),
+            ),
+        };
+        SupervisorStatus {
+            state,
+            scheduled_on,
+            pid,
+            uptime_secs: uptime,
+            restarts: self.restarts,
+            last_exit: self.last_exit,
+            last_error: self.last_error.clone(),
+            backoff_secs: wait,
+        }
+    }
+}
+
+impl<L: Launch> Drop for Supervisor<L> {
+    fn drop(&mut self) {
+        if let Phase::Running { child, .. } = &mut self.phase {
+            child.kill();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Scripted child: exits with the next queued result when `exit` is set.
+    struct FakeChild {
+        exit: Rc<RefCell<Option<ChildExit>>>,
+        killed: Rc<RefCell<u32>>,
+    }
+
+    impl Supervised for FakeChild {
+        fn try_exit(&mut self) -> io::Result<Option<ChildExit>> {
+            Ok(self.exit.borrow_mut().take())
+        }
+        fn kill(&mut self) {
+            *self.killed.borrow_mut() += 1;
+        }
+        fn id(&self) -> u32 {
+            42
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeLauncher {
+        launches: u32,
+        exit: Rc<RefCell<Option<ChildExit>>>,
+        killed: Rc<RefCell<u32>>,
+    }
+
+    impl Launch for FakeLauncher {
+        type Child = FakeChild;
+        fn launch(&mut self) -> io::Result<FakeChild> {
+            self.launches += 1;
+            Ok(FakeChild {
+                exit: self.exit.clone(),
+                killed: self.killed.clone(),
+            })
+        }
+    }
+
+    fn cfg() -> SupervisorConfig {
+        SupervisorConfig {
+            backoff_initial: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(4),
+            stable_after: Duration::from_secs(30),
+            ..SupervisorConfig::new("unused")
+        }
+    }
+
+    #[test]
+    fn crash_restarts_with_doubling_backoff() {
+        let launcher = FakeLauncher::default();
+        let exit = launcher.exit.clone();
+        let mut sup = Supervisor::with_launcher(launcher, cfg());
+        let t = LocalTime::new(0, 12, 0);
+        let t0 = Instant::now();
+
+        assert_eq!(sup.tick_at(t, t0).state, RunState::Running);
+
+        *exit.borrow_mut() = Some(ChildExit::Code(101));
+        let s = sup.tick_at(t, t0 + Duration::from_secs(1));
+        assert_eq!(s.state, RunState::Backoff);
+        assert_eq!(s.last_exit, Some(ChildExit::Code(101)));
+
+        // Still waiting at +1.5s, restarted at +2s.
+        assert_eq!(
+            sup.tick_at(t, t0 + Duration::from_millis(1500)).state,
+            RunState::Backoff
+        );
+        let s = sup.tick_at(t, t0 + Duration::from_secs(2));
+        assert_eq!((s.state, s.restarts), (RunState::Running, 1));
+
+        // Second quick crash waits twice as long.
+        *exit.borrow_mut() = Some(ChildExit::Signal);
+        let s = sup.tick_at(t, t0 + Duration::from_secs(3));
+        assert_eq!(s.backoff_secs, 2.0);
+    }
+
+    #[test]
+    fn schedule_off_kills_and_on_starts_without_counting_restart() {
+        let launcher = FakeLauncher::default();
+        let killed = launcher.killed.clone();
+        let mut sup =
+            Supervisor::with_launcher(launcher, cfg().with_schedule(Schedule::daily(600, 1080)));
+        let t0 = Instant::now();
+
+        assert_eq!(
+            sup.tick_at(LocalTime::new(0, 9, 0), t0).state,
+            RunState::Off
+        );
+        assert_eq!(
+            sup.tick_at(LocalTime::new(0, 10, 0), t0).state,
+            RunState::Running
+        );
+        let s = sup.tick_at(LocalTime::new(0, 18, 0), t0);
+        assert_eq!(s.state, RunState::Off);
+        assert_eq!(*killed.borrow(), 1);
+        assert_eq!(s.restarts, 0);
+        assert!(s.to_json().contains("\"state\":\"off\""));
+    }
+}
@@ -0,0 +1,144 @@
+//! Per-frame CPU overhead of `execute_plan` and standard-uniform application.
+//!
+//! Renders at 16x16 in a surfaceless EGL context (Linux; Mesa's llvmpipe is enough), so the
+//! numbers are dominated by the runtime's own bookkeeping and GL call overhead rather than
+//! fill rate. Each frame ends with `glFinish` to keep the driver queue bounded. Without an EGL
+//! device the benches are skipped. `cargo bench -p scheng-runtime-glow`
+
+#[cfg(target_os = "linux")]
+mod gpu {
+    use criterion::{black_box, BenchmarkId, Criterion};
+    use glow::HasContext;
+    use glutin::api::egl::context::PossiblyCurrentContext;
+    use glutin::api::egl::device::Device;
+    use glutin::api::egl::display::Display;
+    use glutin::config::{ConfigSurfaceTypes, ConfigTemplateBuilder};
+    use glutin::context::{ContextApi, ContextAttributesBuilder, Version};
+    use glutin::prelude::*;
+    use scheng_graph::{Graph, NodeKind};
+    use scheng_runtime_glow::{
+        apply_standard_uniforms, compile_program, execute_plan, FrameCtx, NodeProps,
+        RuntimeState, ShaderSource, StandardUniformLocations, FULLSCREEN_VERT,
+    };
+
+    const SIZE: i32 = 16;
+
+    const FRAG: &str = "#version 330 core
+in vec2 v_uv;
+out vec4 o;
+uniform sampler2D iChannel0;
+uniform float uTime;
+uniform vec2 uResolution;
+uniform float uAspect;
+void main() { o = texture(iChannel0, v_uv) * 0.5 + vec4(v_uv, fract(uTime), 1.0) * 0.5; }";
+
+    struct Gpu {
+        gl: glow::Context,
+        _context: PossiblyCurrentContext,
+        _display: Display,
+    }
+
+    fn gpu() -> Option<Gpu> {
+        let device = Device::query_devices().ok()?.next()?;
+        let display = unsafe { Display::with_device(&device, None) }.ok()?;
+        let template = ConfigTemplateBuilder::new()
+            .with_surface_type(ConfigSurfaceTypes::empty())
+            .build();
+        let config = unsafe { display.find_configs(template) }.ok()?.next()?;
+        let attrs = ContextAttributesBuilder::new()
+            .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+            .build(None);
+        let context = unsafe { display.create_context(&config, &attrs) }
+            .ok()?
+            .make_current_surfaceless()
+            .ok()?;
+        let gl = unsafe {
+            glow::Context::from_loader_function_cstr(|s| display.get_proc_address(s).cast())
+        };
+        Some(Gpu {
+            gl,
+            _context: context,
+            _display: display,
+        })
+    }
+
+    /// `n` chained passes into a `PixelsOut`.
+    fn chain(n: usize) -> (Graph, NodeProps) {
+        let mut g = Graph::new();
+        let mut props = NodeProps::default();
+        let shader = ShaderSource {
+            vert: FULLSCREEN_VERT.to_string(),
+            frag: FRAG.to_string(),
+            origin: None,
+        };
+        let mut prev = None;
+        for _ in 0..n {
+            let pass = g.add_node(NodeKind::ShaderPass);
+            props.shader_sources.insert(pass, shader.clone());
+            if let Some(prev) = prev {
+                g.connect_named(prev, "out", pass, "in").unwrap();
+            }
+            prev = Some(pass);
+        }
+        let out = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(prev.unwrap(), "out", out, "in").unwrap();
+        (g, props)
+    }
+
+    pub fn execute(c: &mut Criterion) {
+        let Some(gpu) = gpu() else {
+            eprintln!("skipping execute_plan bench: no headless EGL context");
+            return;
+        };
+        let gl = &gpu.gl;
+        let mut group = c.benchmark_group("execute_plan");
+        for n in [1usize, 8, 64] {
+            let (g, props) = chain(n);
+            let plan = g.compile().unwrap();
+            let mut state = unsafe { RuntimeState::new(gl) }.unwrap();
+            let mut frame = FrameCtx::new(SIZE, SIZE, 0.0, 0);
+            group.bench_function(BenchmarkId::new("passes", n), |b| {
+                b.iter(|| {
+                    frame = frame.next(SIZE, SIZE, frame.time + 1.0 / 60.0);
+                    let out = unsafe { execute_plan(gl, &g, &plan, &mut state, &props, frame) };
+                    unsafe { gl.finish() };
+                    black_box(out.unwrap())
+                })
+            });
+            unsafe { state.destroy(gl) };
+        }
+        group.finish();
+    }
+
+    pub fn uniforms(c: &mut Criterion) {
+        let Some(gpu) = gpu() else {
+            eprintln!("skipping uniform bench: no headless EGL context");
+            return;
+        };
+        let gl = &gpu.gl;
+        let program = unsafe { compile_program(gl, FULLSCREEN_VERT, FRAG) }.unwrap();
+        unsafe { gl.use_program(Some(program)) };
+        let frame = FrameCtx::new(SIZE, SIZE, 1.0, 60);
+        c.bench_function("apply_standard_uniforms/reflect", |b| {
+            b.iter(|| black_box(unsafe { StandardUniformLocations::reflect(gl, program) }))
+        });
+        let locs = unsafe { StandardUniformLocations::reflect(gl, program) };
+        c.bench_function("apply_standard_uniforms/apply", |b| {
+            b.iter(|| unsafe { apply_standard_uniforms(gl, &locs, black_box(&frame)) })
+        });
+        unsafe {
+            gl.use_program(None);
+            gl.delete_program(program);
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+criterion::criterion_group!(benches, gpu::execute, gpu::uniforms);
+#[cfg(target_os = "linux")]
+criterion::criterion_main!(benches);
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    eprintln!("scheng-runtime-glow benches need a headless EGL context (Linux)");
+}
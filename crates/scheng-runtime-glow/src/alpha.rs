@@ -0,0 +1,135 @@
+//! Alpha policy of named outputs (`NodeProps::output_alpha`).
+//!
+//! Render targets hold straight (unassociated) alpha: mixers and keyers blend colour and
+//! alpha separately, and a pass's `alpha` is whatever it wrote. By default an output hands that
+//! texture to its sinks unchanged. An [`AlphaPolicy`] on a `PixelsOut` makes `execute_plan` run
+//! one more pass after every node (and after temporal smoothing) into a target owned by the
+//! runtime. Every sink then sees the converted frame: presenters, recorders, Syphon and any
+//! host sink alike. Pick the encoding the receiving app expects when keying downstream.
+
+use glow::HasContext;
+
+use crate::stats::{RuntimeStats, TextureFormat};
+use crate::{create_render_target, EngineError, FullscreenTriangle, RenderTarget};
+
+/// How an output's alpha channel reaches its sinks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AlphaPolicy {
+    /// Colour and alpha as rendered (no extra pass).
+    #[default]
+    Straight,
+    /// Alpha forced to 1, for sinks and apps that treat the frame as opaque RGB.
+    Discard,
+    /// Colour multiplied by alpha, as compositors that expect premultiplied frames need.
+    Premultiplied,
+}
+
+impl AlphaPolicy {
+    /// The policy applied to one straight-alpha RGBA value.
+    pub fn apply(self, [r, g, b, a]: [f32; 4]) -> [f32; 4] {
+        match self {
+            AlphaPolicy::Straight => [r, g, b, a],
+            AlphaPolicy::Discard => [r, g, b, 1.0],
+            AlphaPolicy::Premultiplied => [r * a, g * a, b * a, a],
+        }
+    }
+
+    /// Whether the output needs a conversion pass.
+    pub fn converts(self) -> bool {
+        self != AlphaPolicy::Straight
+    }
+
+    /// `uMode` of [`ALPHA_FRAG`].
+    fn mode(self) -> i32 {
+        match self {
+            AlphaPolicy::Straight => 0,
+            AlphaPolicy::Discard => 1,
+            AlphaPolicy::Premultiplied => 2,
+        }
+    }
+}
+
+pub(crate) const ALPHA_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 o;
+uniform sampler2D uSrc;
+uniform int uMode;
+void main() {
+    vec4 c = texture(uSrc, v_uv);
+    if (uMode == 1) {
+        c.a = 1.0;
+    } else if (uMode == 2) {
+        c.rgb *= c.a;
+    }
+    o = c;
+}
+"#;
+
+/// The converted frame of one output.
+#[derive(Debug)]
+pub(crate) struct AlphaOutput {
+    target: RenderTarget,
+}
+
+impl AlphaOutput {
+    pub(crate) unsafe fn new(gl: &glow::Context, w: i32, h: i32) -> Result<Self, EngineError> {
+        Ok(Self {
+            target: create_render_target(gl, w, h)?,
+        })
+    }
+
+    pub(crate) fn output(&self) -> &RenderTarget {
+        &self.target
+    }
+
+    /// Convert `src` (`w` x `h`) with `policy` (`program` is [`ALPHA_FRAG`]).
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) unsafe fn convert(
+        &mut self,
+        gl: &glow::Context,
+        fs_tri: &FullscreenTriangle,
+        program: glow::NativeProgram,
+        policy: AlphaPolicy,
+        src: glow::NativeTexture,
+        w: i32,
+        h: i32,
+    ) {
+        if (self.target.w, self.target.h) != (w, h) {
+            self.target.resize(gl, w, h);
+        }
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.target.fbo));
+        gl.viewport(0, 0, w, h);
+        gl.disable(glow::BLEND);
+        gl.use_program(Some(program));
+        let loc = |name: &str| gl.get_uniform_location(program, name);
+        gl.uniform_1_i32(loc("uSrc").as_ref(), 0);
+        gl.uniform_1_i32(loc("uMode").as_ref(), policy.mode());
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, Some(src));
+        fs_tri.draw(gl);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+    }
+
+    pub(crate) fn account(&self, stats: &mut RuntimeStats) {
+        stats.add_target(self.target.w, self.target.h, TextureFormat::Rgba8);
+    }
+
+    pub(crate) unsafe fn destroy(self, gl: &glow::Context) {
+        gl.delete_framebuffer(self.target.fbo);
+        gl.delete_texture(self.target.tex);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies_convert_straight_alpha() {
+        let c = [0.8, 0.4, 0.2, 0.5];
+        assert_eq!(AlphaPolicy::default().apply(c), c);
+        assert_eq!(AlphaPolicy::Discard.apply(c), [0.8, 0.4, 0.2, 1.0]);
+        assert_eq!(AlphaPolicy::Premultiplied.apply(c), [0.4, 0.2, 0.1, 0.5]);
+        assert!(!AlphaPolicy::Straight.converts());
+    }
+}
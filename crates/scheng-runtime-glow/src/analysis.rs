@@ -0,0 +1,287 @@
+//! Two-frame analysis nodes (`MotionDetect`, `OpticalFlow`).
+//!
+//! Both keep a copy of their input from the previous frame ([`PrevInput`]) and bind it on
+//! `iChannel1` next to the live input on `iChannel0`.
+//!
+//! `MotionDetect` renders the thresholded luma difference between its input and the input's
+//! previous frame (its visible output), then reduces that mask to a small grid on the GPU and
+//! reads back only the grid cells. Results land in the runtime `ParamRegistry` as
+//! `node/<handle>/energy` and `node/<handle>/cell/<i>`, where the handle is the node's name or
+//! else its id (see `scheng_runtime::node_param_key_in`).
+
+use glow::HasContext;
+use scheng_graph::{Graph, NodeId};
+use scheng_runtime::{node_param_key_in, MotionParams, ParamRegistry};
+
+use crate::stats::{RuntimeStats, TextureFormat};
+use crate::{create_render_target, EngineError, FullscreenTriangle, RenderTarget};
+
+/// Per-pixel thresholded luma difference (current = iChannel0, previous input = iChannel1).
+pub const MOTION_DIFF_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 FragColor;
+
+uniform sampler2D iChannel0;
+uniform sampler2D iChannel1;
+uniform float uThreshold;
+uniform float uGain;
+
+void main() {
+    vec3 a = texture(iChannel0, v_uv).rgb;
+    vec3 b = texture(iChannel1, v_uv).rgb;
+    float d = abs(dot(a - b, vec3(0.2126, 0.7152, 0.0722))) * uGain;
+    d = d < uThreshold ? 0.0 : min(d, 1.0);
+    FragColor = vec4(vec3(d), 1.0);
+}
+"#;
+
+/// Average the difference mask over each grid cell (8x8 taps per output texel).
+pub const MOTION_REDUCE_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 FragColor;
+
+uniform sampler2D iChannel0;
+uniform vec2 uGrid;
+
+void main() {
+    vec2 base = floor(v_uv * uGrid) / uGrid;
+    float acc = 0.0;
+    for (int j = 0; j < 8; j++) {
+        for (int i = 0; i < 8; i++) {
+            vec2 uv = base + (vec2(float(i), float(j)) + 0.5) / (8.0 * uGrid);
+            acc += texture(iChannel0, uv).r;
+        }
+    }
+    FragColor = vec4(vec3(acc / 64.0), 1.0);
+}
+"#;
+
+/// Lucas–Kanade style flow estimate between the previous (iChannel1) and current (iChannel0)
+/// input. Output: `rg = 0.5 + flow_uv * FLOW_ENCODING_SCALE` (clamped), `b = |flow|` in pixels
+/// normalised by the window size, `a = 1`.
+pub const OPTICAL_FLOW_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 FragColor;
+
+uniform sampler2D iChannel0;
+uniform sampler2D iChannel1;
+uniform vec2 uResolution;
+uniform int uRadius;
+uniform float uLambda;
+uniform float uGain;
+uniform float uEncodeScale;
+
+float luma(sampler2D s, vec2 uv) {
+    return dot(texture(s, uv).rgb, vec3(0.2126, 0.7152, 0.0722));
+}
+
+void main() {
+    vec2 px = 1.0 / uResolution;
+    float sxx = 0.0, sxy = 0.0, syy = 0.0, sxt = 0.0, syt = 0.0;
+    for (int j = -uRadius; j <= uRadius; j++) {
+        for (int i = -uRadius; i <= uRadius; i++) {
+            vec2 uv = v_uv + vec2(float(i), float(j)) * px;
+            float ix = 0.25 * (luma(iChannel0, uv + vec2(px.x, 0.0)) - luma(iChannel0, uv - vec2(px.x, 0.0))
+                             + luma(iChannel1, uv + vec2(px.x, 0.0)) - luma(iChannel1, uv - vec2(px.x, 0.0)));
+            float iy = 0.25 * (luma(iChannel0, uv + vec2(0.0, px.y)) - luma(iChannel0, uv - vec2(0.0, px.y))
+                             + luma(iChannel1, uv + vec2(0.0, px.y)) - luma(iChannel1, uv - vec2(0.0, px.y)));
+            float it = luma(iChannel0, uv) - luma(iChannel1, uv);
+            sxx += ix * ix;
+            sxy += ix * iy;
+            syy += iy * iy;
+            sxt += ix * it;
+            syt += iy * it;
+        }
+    }
+    sxx += uLambda;
+    syy += uLambda;
+    float det = sxx * syy - sxy * sxy;
+    vec2 flow_px = det > 1e-8
+        ? vec2(-(syy * sxt - sxy * syt), -(sxx * syt - sxy * sxt)) / det
+        : vec2(0.0);
+    flow_px *= uGain;
+    vec2 enc = clamp(0.5 + flow_px * px * uEncodeScale, 0.0, 1.0);
+    float mag = clamp(length(flow_px) / float(2 * uRadius + 1), 0.0, 1.0);
+    FragColor = vec4(enc, mag, 1.0);
+}
+"#;
+
+/// Offset `a` (iChannel0) by the flow encoded in `b` (iChannel1).
+pub const FLOW_DISPLACE_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 FragColor;
+
+uniform sampler2D iChannel0;
+uniform sampler2D iChannel1;
+uniform float uAmount;
+uniform float uEncodeScale;
+
+void main() {
+    vec2 flow_uv = (texture(iChannel1, v_uv).rg - 0.5) / uEncodeScale;
+    FragColor = texture(iChannel0, v_uv - flow_uv * uAmount);
+}
+"#;
+
+/// Copy of a node's input from the previous frame.
+#[derive(Debug)]
+pub(crate) struct PrevInput {
+    target: RenderTarget,
+    /// False until one input frame has been captured (the first comparison is meaningless).
+    primed: bool,
+}
+
+impl PrevInput {
+    pub(crate) unsafe fn new(gl: &glow::Context, w: i32, h: i32) -> Result<Self, EngineError> {
+        Ok(Self {
+            target: create_render_target(gl, w, h)?,
+            primed: false,
+        })
+    }
+
+    /// Match the frame size; a resize discards the stored frame.
+    pub(crate) unsafe fn ensure(&mut self, gl: &glow::Context, w: i32, h: i32) {
+        if self.target.w != w || self.target.h != h {
+            self.target.resize(gl, w, h);
+            self.primed = false;
+        }
+    }
+
+    pub(crate) fn tex(&self) -> glow::NativeTexture {
+        self.target.tex
+    }
+
+    pub(crate) fn primed(&self) -> bool {
+        self.primed
+    }
+
+    /// Store `input_tex` for the next frame (`copy_prog` samples iChannel0).
+    pub(crate) unsafe fn capture(
+        &mut self,
+        gl: &glow::Context,
+        fs_tri: &FullscreenTriangle,
+        copy_prog: glow::NativeProgram,
+        input_tex: glow::NativeTexture,
+    ) {
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.target.fbo));
+        gl.viewport(0, 0, self.target.w, self.target.h);
+        gl.use_program(Some(copy_prog));
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, Some(input_tex));
+        if let Some(loc) = gl.get_uniform_location(copy_prog, "iChannel0") {
+            gl.uniform_1_i32(Some(&loc), 0);
+        }
+        fs_tri.draw(gl);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        gl.use_program(None);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        self.primed = true;
+    }
+
+    pub(crate) fn account(&self, stats: &mut RuntimeStats) {
+        stats.add_target(self.target.w, self.target.h, TextureFormat::Rgba8);
+    }
+
+    pub(crate) unsafe fn destroy(self, gl: &glow::Context) {
+        gl.delete_framebuffer(self.target.fbo);
+        gl.delete_texture(self.target.tex);
+    }
+}
+
+/// Reduction state owned by one `MotionDetect` node.
+#[derive(Debug)]
+pub(crate) struct MotionState {
+    /// `grid_cols x grid_rows` reduction target.
+    grid: RenderTarget,
+    buf: Vec<u8>,
+}
+
+impl MotionState {
+    pub(crate) unsafe fn new(gl: &glow::Context, params: &MotionParams) -> Result<Self, EngineError> {
+        let (gw, gh) = grid_size(params);
+        Ok(Self {
+            grid: create_render_target(gl, gw, gh)?,
+            buf: Vec::new(),
+        })
+    }
+
+    pub(crate) unsafe fn ensure(&mut self, gl: &glow::Context, params: &MotionParams) {
+        let (gw, gh) = grid_size(params);
+        if self.grid.w != gw || self.grid.h != gh {
+            self.grid.resize(gl, gw, gh);
+        }
+    }
+
+    /// Reduce the difference mask in `diff_tex` to the grid and publish the results.
+    ///
+    /// Publishes zeros while `primed` is false (no previous frame to compare against).
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) unsafe fn publish(
+        &mut self,
+        gl: &glow::Context,
+        fs_tri: &FullscreenTriangle,
+        reduce_prog: glow::NativeProgram,
+        graph: &Graph,
+        node: NodeId,
+        diff_tex: glow::NativeTexture,
+        primed: bool,
+        params: &mut ParamRegistry,
+    ) {
+        let cells = (self.grid.w * self.grid.h) as usize;
+
+        // 1) GPU reduction of the mask into the grid target.
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.grid.fbo));
+        gl.viewport(0, 0, self.grid.w, self.grid.h);
+        gl.use_program(Some(reduce_prog));
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, Some(diff_tex));
+        if let Some(loc) = gl.get_uniform_location(reduce_prog, "iChannel0") {
+            gl.uniform_1_i32(Some(&loc), 0);
+        }
+        if let Some(loc) = gl.get_uniform_location(reduce_prog, "uGrid") {
+            gl.uniform_2_f32(Some(&loc), self.grid.w as f32, self.grid.h as f32);
+        }
+        fs_tri.draw(gl);
+
+        // 2) Read back only the grid cells.
+        self.buf.resize(cells * 4, 0);
+        gl.read_pixels(
+            0,
+            0,
+            self.grid.w,
+            self.grid.h,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            glow::PixelPackData::Slice(&mut self.buf),
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        gl.use_program(None);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+        // GL rows are bottom-up; publish cells row-major from the top.
+        let mut total = 0.0;
+        for row in 0..self.grid.h as usize {
+            for col in 0..self.grid.w as usize {
+                let gl_row = self.grid.h as usize - 1 - row;
+                let px = (gl_row * self.grid.w as usize + col) * 4;
+                let v = if primed { self.buf[px] as f32 / 255.0 } else { 0.0 };
+                total += v;
+                let i = row * self.grid.w as usize + col;
+                params.set(&node_param_key_in(graph, node, &format!("cell/{i}")), v);
+            }
+        }
+        params.set(&node_param_key_in(graph, node, "energy"), total / cells.max(1) as f32);
+    }
+
+    pub(crate) fn account(&self, stats: &mut RuntimeStats) {
+        stats.add_target(self.grid.w, self.grid.h, TextureFormat::Rgba8);
+    }
+
+    pub(crate) unsafe fn destroy(self, gl: &glow::Context) {
+        gl.delete_framebuffer(self.grid.fbo);
+        gl.delete_texture(self.grid.tex);
+    }
+}
+
+fn grid_size(params: &MotionParams) -> (i32, i32) {
+    (params.grid_cols.max(1) as i32, params.grid_rows.max(1) as i32)
+}
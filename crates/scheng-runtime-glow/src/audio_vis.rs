@@ -0,0 +1,190 @@
+//! Built-in audio visualizers (`NodeKind::AudioWaveform`, `NodeKind::AudioSpectrum`).
+//!
+//! Each frame the node reduces `NodeProps::audio_samples` to [`AudioVisParams::bins`] columns
+//! on the CPU (see `scheng_runtime::audio`), uploads them as a 1-row `R32F` texture and draws
+//! them with one fragment shader in the chosen [`AudioVisStyle`]. Spectrum columns are smoothed
+//! across frames by `smoothing`; the smoothed bands live here.
+
+use glow::HasContext;
+use scheng_graph::NodeKind;
+use scheng_runtime::{spectrum_bands, waveform_columns, AudioVisParams, AudioVisStyle};
+
+use crate::stats::{RuntimeStats, TextureFormat};
+use crate::{compile_program, EngineError, FullscreenTriangle, FULLSCREEN_VERT};
+
+const AUDIO_VIS_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 o;
+uniform sampler2D uData;
+uniform int uCount;
+uniform int uStyle;
+uniform int uCentered;
+uniform vec4 uColor;
+uniform vec2 uResolution;
+uniform float uLineWidth;
+uniform float uGap;
+uniform float uInner;
+
+float value(int i) {
+    return texelFetch(uData, ivec2(clamp(i, 0, uCount - 1), 0), 0).r;
+}
+
+float curve(float x) {
+    float f = clamp(x, 0.0, 1.0) * float(uCount - 1);
+    int i = int(floor(f));
+    return mix(value(i), value(i + 1), f - float(i));
+}
+
+// Bar coverage at column position x and height y (both 0..1).
+float bar(float x, float y) {
+    float f = x * float(uCount);
+    if (abs(fract(f) - 0.5) > 0.5 * (1.0 - uGap)) {
+        return 0.0;
+    }
+    float v = value(int(floor(f)));
+    float base = uCentered != 0 ? 0.5 : 0.0;
+    return (y >= min(base, v) && y <= max(base, v)) ? 1.0 : 0.0;
+}
+
+void main() {
+    float cov;
+    if (uStyle == 1) {
+        // Distance to the curve, corrected for its slope so steep segments keep their width.
+        float px = 1.0 / uResolution.x;
+        float slope = (curve(v_uv.x + px) - curve(v_uv.x - px)) * 0.5 * uResolution.y;
+        float dist = abs(v_uv.y - curve(v_uv.x)) * uResolution.y / sqrt(1.0 + slope * slope);
+        cov = clamp(0.5 * uLineWidth - dist + 0.5, 0.0, 1.0);
+    } else if (uStyle == 2) {
+        // Columns run clockwise from 12 o'clock; height grows outwards from the inner ring.
+        float side = min(uResolution.x, uResolution.y);
+        vec2 p = (v_uv - 0.5) * uResolution / side;
+        float x = fract(atan(p.x, p.y) / 6.28318530718 + 1.0);
+        float y = (length(p) - uInner) / max(0.5 - uInner, 1e-3);
+        cov = (y < 0.0 || y > 1.0) ? 0.0 : bar(x, y);
+    } else {
+        cov = bar(v_uv.x, v_uv.y);
+    }
+    o = vec4(uColor.rgb * cov * uColor.a, 1.0);
+}
+"#;
+
+/// GPU state for one visualizer node: its program, data texture and spectrum history.
+#[derive(Debug)]
+pub(crate) struct AudioVisual {
+    program: glow::NativeProgram,
+    data: glow::NativeTexture,
+    /// Columns last uploaded to `data` (one row of `R32F`).
+    data_width: i32,
+    /// Smoothed spectrum bands of the previous frame (empty for waveforms or after a resize).
+    bands: Vec<f32>,
+}
+
+impl AudioVisual {
+    pub(crate) unsafe fn new(gl: &glow::Context) -> Result<Self, EngineError> {
+        let program = compile_program(gl, FULLSCREEN_VERT, AUDIO_VIS_FRAG)?;
+        let data = gl.create_texture().map_err(|e| {
+            gl.delete_program(program);
+            EngineError::GlCreate(format!("create_texture failed: {e:?}"))
+        })?;
+        gl.bind_texture(glow::TEXTURE_2D, Some(data));
+        for (pname, v) in [
+            (glow::TEXTURE_MIN_FILTER, glow::NEAREST),
+            (glow::TEXTURE_MAG_FILTER, glow::NEAREST),
+            (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+            (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+        ] {
+            gl.tex_parameter_i32(glow::TEXTURE_2D, pname, v as i32);
+        }
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        Ok(Self {
+            program,
+            data,
+            data_width: 0,
+            bands: Vec::new(),
+        })
+    }
+
+    /// Reduce `samples` for `kind` and draw them into `target` (`w` x `h`).
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) unsafe fn render(
+        &mut self,
+        gl: &glow::Context,
+        fs_tri: &FullscreenTriangle,
+        kind: &NodeKind,
+        params: &AudioVisParams,
+        samples: &[f32],
+        target: glow::NativeFramebuffer,
+        w: i32,
+        h: i32,
+    ) {
+        let count = params.bin_count();
+        let waveform = *kind == NodeKind::AudioWaveform;
+        let columns = if waveform {
+            self.bands.clear();
+            waveform_columns(samples, count, params.gain)
+        } else {
+            let bands = spectrum_bands(samples, count, params.gain, params.min_db);
+            let k = params.smoothing.clamp(0.0, 0.99);
+            if self.bands.len() == count {
+                for (old, new) in self.bands.iter_mut().zip(&bands) {
+                    *old = k * *old + (1.0 - k) * new;
+                }
+            } else {
+                self.bands = bands;
+            }
+            self.bands.clone()
+        };
+
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.data));
+        gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+        self.data_width = count as i32;
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::R32F as i32,
+            count as i32,
+            1,
+            0,
+            glow::RED,
+            glow::FLOAT,
+            Some(bytemuck::cast_slice(&columns)),
+        );
+
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(target));
+        gl.viewport(0, 0, w, h);
+        gl.disable(glow::BLEND);
+        gl.disable(glow::DEPTH_TEST);
+        let p = self.program;
+        gl.use_program(Some(p));
+        let loc = |name: &str| gl.get_uniform_location(p, name);
+        gl.uniform_1_i32(loc("uData").as_ref(), 0);
+        gl.uniform_1_i32(loc("uCount").as_ref(), count as i32);
+        let style = match params.style {
+            AudioVisStyle::Bars => 0,
+            AudioVisStyle::Line => 1,
+            AudioVisStyle::Radial => 2,
+        };
+        gl.uniform_1_i32(loc("uStyle").as_ref(), style);
+        gl.uniform_1_i32(loc("uCentered").as_ref(), waveform as i32);
+        let [r, g, b, a] = params.color;
+        gl.uniform_4_f32(loc("uColor").as_ref(), r, g, b, a);
+        gl.uniform_2_f32(loc("uResolution").as_ref(), w as f32, h as f32);
+        gl.uniform_1_f32(loc("uLineWidth").as_ref(), params.line_width.max(0.0));
+        gl.uniform_1_f32(loc("uGap").as_ref(), params.bar_gap.clamp(0.0, 0.95));
+        gl.uniform_1_f32(loc("uInner").as_ref(), params.inner_radius.clamp(0.0, 0.45));
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.data));
+        fs_tri.draw(gl);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+    }
+
+    pub(crate) fn account(&self, stats: &mut RuntimeStats) {
+        stats.programs += 1;
+        stats.add_texture(self.data_width, 1, TextureFormat::R32F);
+    }
+
+    pub(crate) unsafe fn destroy(self, gl: &glow::Context) {
+        gl.delete_program(self.program);
+        gl.delete_texture(self.data);
+    }
+}
@@ -0,0 +1,142 @@
+//! Shader banks: several shaders per render node, switched live (`NodeProps::shader_banks`).
+//!
+//! Replacing a node's `ShaderSource` from the host swaps its shader, but the new program
+//! compiles on the frame it is first shown. A [`ShaderBank`] lists every shader the node may
+//! run instead. The runtime compiles all slots up front (in `precompile`, or the first frame
+//! the bank is seen), so switching is a program change with no compile in between.
+//!
+//! The active slot is the registry parameter `node/<handle>/slot` ([`shader_slot_key`]),
+//! declared with the bank's range and `active` as its default, so OSC/MIDI mappings and
+//! generated UIs can switch it. Values round to the nearest slot and clamp to the bank. A bank
+//! takes precedence over the node's `shader_sources` entry; an empty bank is ignored. A slot
+//! switch changes the node's source, so it counts towards `uReloadCount` and re-declares the
+//! slot's `@param` controls.
+
+use scheng_core::events::{EngineEvent, LogLevel};
+use scheng_graph::{Graph, NodeId};
+use scheng_runtime::{node_param_key_in, ParamMeta, ParamRegistry};
+
+use crate::{hash_str, EngineError, ProgramKey, RuntimeState, ShaderSource};
+
+/// Shaders a render node can switch between.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderBank {
+    pub slots: Vec<ShaderSource>,
+    /// Slot shown until the registry parameter is set.
+    pub active: usize,
+}
+
+impl ShaderBank {
+    pub fn new(slots: Vec<ShaderSource>) -> Self {
+        Self { slots, active: 0 }
+    }
+
+    pub fn with_active(mut self, active: usize) -> Self {
+        self.active = active;
+        self
+    }
+
+    /// The slot selected by the registry value `value` (the bank's `active` slot when unset).
+    pub fn slot(&self, value: Option<f32>) -> usize {
+        let last = self.slots.len().saturating_sub(1);
+        match value {
+            Some(v) => (v.round().max(0.0) as usize).min(last),
+            None => self.active.min(last),
+        }
+    }
+
+    /// The shader of the slot selected in `params`.
+    pub fn shader(
+        &self,
+        graph: &Graph,
+        params: &ParamRegistry,
+        node: NodeId,
+    ) -> Option<&ShaderSource> {
+        let value = params.get(&shader_slot_key(graph, node));
+        self.slots.get(self.slot(value))
+    }
+
+    fn keys(&self) -> Vec<ProgramKey> {
+        self.slots
+            .iter()
+            .map(|s| ProgramKey {
+                vert_hash: hash_str(&s.vert),
+                frag_hash: hash_str(&s.frag),
+            })
+            .collect()
+    }
+}
+
+/// Registry key selecting `node`'s slot: `node/<handle>/slot`.
+pub fn shader_slot_key(graph: &Graph, node: NodeId) -> String {
+    node_param_key_in(graph, node, "slot")
+}
+
+impl RuntimeState {
+    /// Compile every slot of `node`'s bank and declare its slot parameter, unless this bank was
+    /// already warmed. Failed slots are returned (and not retried until the bank changes).
+    pub(crate) unsafe fn warm_shader_bank(
+        &mut self,
+        gl: &glow::Context,
+        graph: &Graph,
+        node: NodeId,
+        bank: &ShaderBank,
+    ) -> Vec<(usize, EngineError)> {
+        let keys = bank.keys();
+        if bank.slots.is_empty() || self.warmed_banks.get(&node) == Some(&keys) {
+            return Vec::new();
+        }
+        let last = (bank.slots.len() - 1) as f32;
+        self.params.declare(
+            shader_slot_key(graph, node),
+            ParamMeta::new(0.0, last, bank.slot(None) as f32)
+                .with_description(format!("shader slot of node {}", graph.handle(node))),
+        );
+        let mut failures = Vec::new();
+        for (slot, shader) in bank.slots.iter().enumerate() {
+            if let Err(e) = self.cached_program(gl, &shader.vert, &shader.frag) {
+                failures.push((slot, e));
+            }
+        }
+        self.warmed_banks.insert(node, keys);
+        failures
+    }
+
+    /// [`warm_shader_bank`](Self::warm_shader_bank) during a frame: failures become warnings,
+    /// and a failing active slot fails the frame when it is drawn.
+    pub(crate) unsafe fn warm_shader_bank_logged(
+        &mut self,
+        gl: &glow::Context,
+        graph: &Graph,
+        node: NodeId,
+        bank: &ShaderBank,
+    ) {
+        for (slot, e) in self.warm_shader_bank(gl, graph, node, bank) {
+            self.events.push(EngineEvent::Log {
+                level: LogLevel::Warn,
+                tag: "shader-bank",
+                msg: format!("node #{} slot {slot}: {e}", node.0),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slot_rounds_and_clamps_to_the_bank() {
+        let shader = |frag: &str| ShaderSource {
+            vert: String::new(),
+            frag: frag.to_string(),
+            origin: None,
+        };
+        let bank = ShaderBank::new(vec![shader("a"), shader("b"), shader("c")]).with_active(1);
+        assert_eq!(bank.slot(None), 1);
+        assert_eq!(bank.slot(Some(1.6)), 2);
+        assert_eq!(bank.slot(Some(7.0)), 2);
+        assert_eq!(bank.slot(Some(-1.0)), 0);
+        assert_eq!(ShaderBank::default().slot(Some(3.0)), 0);
+    }
+}
@@ -0,0 +1,352 @@
+//! Built-in bloom (`NodeKind::Bloom`).
+//!
+//! One node runs the whole pyramid: the input is thresholded while downsampling into the first
+//! half-size level, each further level halves again, then the levels are upsampled with a tent
+//! filter and added into the level above, and the accumulated glow is composited over the
+//! input. Levels are `RGBA16F` when the context can render to it (see
+//! [`RuntimeCaps`](crate::RuntimeCaps)) so the sum does not clip, `RGBA8` otherwise.
+//!
+//! Parameters come from `NodeProps::bloom_params` ([`BloomParams`]).
+
+use glow::HasContext;
+use scheng_runtime::BloomParams;
+
+use crate::stats::{RuntimeStats, TextureFormat};
+use crate::{compile_program, EngineError, FullscreenTriangle, FULLSCREEN_VERT};
+
+/// 3x3 tent over bilinear taps, `d` apart.
+const TENT: &str = r#"
+vec3 tent(sampler2D s, vec2 uv, vec2 d) {
+    vec3 c = texture(s, uv).rgb * 4.0;
+    c += (texture(s, uv + vec2(-d.x, 0.0)).rgb + texture(s, uv + vec2(d.x, 0.0)).rgb
+        + texture(s, uv + vec2(0.0, -d.y)).rgb + texture(s, uv + vec2(0.0, d.y)).rgb) * 2.0;
+    c += texture(s, uv - d).rgb + texture(s, uv + d).rgb
+        + texture(s, uv + vec2(-d.x, d.y)).rgb + texture(s, uv + vec2(d.x, -d.y)).rgb;
+    return c / 16.0;
+}
+"#;
+
+/// 4-tap box downsample; the first level also applies the soft threshold.
+const DOWN_FRAG: &str = r#"
+in vec2 v_uv;
+out vec4 o;
+uniform sampler2D uSrc;
+uniform vec2 uTexel;
+uniform int uPrefilter;
+uniform float uThreshold;
+uniform float uKnee;
+void main() {
+    vec2 d = uTexel;
+    vec3 c = (texture(uSrc, v_uv + vec2(-d.x, -d.y)).rgb + texture(uSrc, v_uv + vec2(d.x, -d.y)).rgb
+        + texture(uSrc, v_uv + vec2(-d.x, d.y)).rgb + texture(uSrc, v_uv + vec2(d.x, d.y)).rgb) * 0.25;
+    if (uPrefilter != 0) {
+        float b = max(c.r, max(c.g, c.b));
+        float knee = max(uKnee, 1e-5);
+        float soft = clamp(b - uThreshold + knee, 0.0, 2.0 * knee);
+        soft = soft * soft / (4.0 * knee);
+        c *= max(soft, b - uThreshold) / max(b, 1e-5);
+    }
+    o = vec4(c, 1.0);
+}
+"#;
+
+const UP_FRAG: &str = r#"
+in vec2 v_uv;
+out vec4 o;
+uniform sampler2D uSrc;
+uniform vec2 uTexel;
+uniform float uRadius;
+void main() {
+    o = vec4(tent(uSrc, v_uv, uTexel * uRadius), 1.0);
+}
+"#;
+
+const COMPOSITE_FRAG: &str = r#"
+in vec2 v_uv;
+out vec4 o;
+uniform sampler2D uSrc;
+uniform sampler2D uBloom;
+uniform vec2 uTexel;
+uniform float uRadius;
+uniform float uIntensity;
+void main() {
+    vec4 c = texture(uSrc, v_uv);
+    o = vec4(c.rgb + tent(uBloom, v_uv, uTexel * uRadius) * uIntensity, c.a);
+}
+"#;
+
+/// Sizes of the `levels` pyramid levels below a `width` x `height` input.
+fn level_sizes(width: i32, height: i32, levels: u32) -> Vec<(i32, i32)> {
+    let mut size = (width, height);
+    (0..levels)
+        .map(|_| {
+            size = ((size.0 / 2).max(1), (size.1 / 2).max(1));
+            size
+        })
+        .collect()
+}
+
+#[derive(Debug)]
+struct Level {
+    fbo: glow::NativeFramebuffer,
+    tex: glow::NativeTexture,
+    w: i32,
+    h: i32,
+}
+
+impl Level {
+    unsafe fn new(gl: &glow::Context, w: i32, h: i32, float: bool) -> Result<Self, EngineError> {
+        let tex = gl
+            .create_texture()
+            .map_err(|e| EngineError::GlCreate(format!("create_texture failed: {e:?}")))?;
+        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+        for (pname, v) in [
+            (glow::TEXTURE_MIN_FILTER, glow::LINEAR),
+            (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+            (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+            (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+        ] {
+            gl.tex_parameter_i32(glow::TEXTURE_2D, pname, v as i32);
+        }
+        let (internal, ty) = if float {
+            (glow::RGBA16F, glow::HALF_FLOAT)
+        } else {
+            (glow::RGBA8, glow::UNSIGNED_BYTE)
+        };
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            internal as i32,
+            w,
+            h,
+            0,
+            glow::RGBA,
+            ty,
+            None,
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+
+        let fbo = gl
+            .create_framebuffer()
+            .map_err(|e| EngineError::GlCreate(format!("create_framebuffer failed: {e:?}")))?;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+        gl.framebuffer_texture_2d(
+            glow::FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::TEXTURE_2D,
+            Some(tex),
+            0,
+        );
+        let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            gl.delete_framebuffer(fbo);
+            gl.delete_texture(tex);
+            return Err(EngineError::GlCreate(format!(
+                "bloom level framebuffer incomplete: 0x{status:x}"
+            )));
+        }
+        Ok(Self { fbo, tex, w, h })
+    }
+
+    unsafe fn destroy(self, gl: &glow::Context) {
+        gl.delete_framebuffer(self.fbo);
+        gl.delete_texture(self.tex);
+    }
+}
+
+/// GPU state for one `Bloom` node: its pyramid and programs.
+#[derive(Debug)]
+pub struct BloomChain {
+    levels: Vec<Level>,
+    float: bool,
+    down_prog: glow::NativeProgram,
+    up_prog: glow::NativeProgram,
+    composite_prog: glow::NativeProgram,
+}
+
+impl BloomChain {
+    /// Compile the programs. `float` selects `RGBA16F` levels (pass
+    /// `RuntimeCaps::float16_targets`).
+    pub unsafe fn new(gl: &glow::Context, float: bool) -> Result<Self, EngineError> {
+        let header = "#version 330 core\n";
+        let down_prog = compile_program(gl, FULLSCREEN_VERT, &format!("{header}{DOWN_FRAG}"))?;
+        let up_prog = compile_program(gl, FULLSCREEN_VERT, &format!("{header}{TENT}{UP_FRAG}"))
+            .inspect_err(|_| gl.delete_program(down_prog))?;
+        let composite_prog = compile_program(
+            gl,
+            FULLSCREEN_VERT,
+            &format!("{header}{TENT}{COMPOSITE_FRAG}"),
+        )
+        .inspect_err(|_| {
+            gl.delete_program(down_prog);
+            gl.delete_program(up_prog);
+        })?;
+        Ok(Self {
+            levels: Vec::new(),
+            float,
+            down_prog,
+            up_prog,
+            composite_prog,
+        })
+    }
+
+    /// (Re)allocate the pyramid for a `width` x `height` input if its shape changed.
+    unsafe fn ensure(
+        &mut self,
+        gl: &glow::Context,
+        width: i32,
+        height: i32,
+        levels: u32,
+    ) -> Result<(), EngineError> {
+        let sizes = level_sizes(width, height, levels);
+        let current: Vec<(i32, i32)> = self.levels.iter().map(|l| (l.w, l.h)).collect();
+        if current == sizes {
+            return Ok(());
+        }
+        for level in self.levels.drain(..) {
+            level.destroy(gl);
+        }
+        for (w, h) in sizes {
+            self.levels.push(Level::new(gl, w, h, self.float)?);
+        }
+        Ok(())
+    }
+
+    /// Bloom `src` (sampled over the whole target) into `target` (`w` x `h`), which is bound
+    /// again on return.
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn render(
+        &mut self,
+        gl: &glow::Context,
+        fs_tri: &FullscreenTriangle,
+        params: &BloomParams,
+        src: glow::NativeTexture,
+        target: glow::NativeFramebuffer,
+        w: i32,
+        h: i32,
+    ) -> Result<(), EngineError> {
+        let levels = params.levels_for(w.max(1) as u32, h.max(1) as u32);
+        self.ensure(gl, w, h, levels)?;
+        gl.disable(glow::BLEND);
+        gl.active_texture(glow::TEXTURE0);
+
+        // Down: input -> level 0 (thresholded) -> level 1 -> ...
+        gl.use_program(Some(self.down_prog));
+        let p = self.down_prog;
+        let loc = |name: &str| gl.get_uniform_location(p, name);
+        gl.uniform_1_i32(loc("uSrc").as_ref(), 0);
+        gl.uniform_1_f32(loc("uThreshold").as_ref(), params.threshold.max(0.0));
+        gl.uniform_1_f32(loc("uKnee").as_ref(), params.knee.max(0.0));
+        let (mut from_tex, mut from_w, mut from_h) = (src, w, h);
+        for (i, level) in self.levels.iter().enumerate() {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(level.fbo));
+            gl.viewport(0, 0, level.w, level.h);
+            gl.bind_texture(glow::TEXTURE_2D, Some(from_tex));
+            gl.uniform_2_f32(
+                loc("uTexel").as_ref(),
+                1.0 / from_w.max(1) as f32,
+                1.0 / from_h.max(1) as f32,
+            );
+            gl.uniform_1_i32(loc("uPrefilter").as_ref(), (i == 0) as i32);
+            fs_tri.draw(gl);
+            (from_tex, from_w, from_h) = (level.tex, level.w, level.h);
+        }
+
+        // Up: add each level into the one above it.
+        gl.use_program(Some(self.up_prog));
+        let p = self.up_prog;
+        let loc = |name: &str| gl.get_uniform_location(p, name);
+        gl.uniform_1_i32(loc("uSrc").as_ref(), 0);
+        gl.uniform_1_f32(loc("uRadius").as_ref(), params.radius.max(0.0));
+        gl.enable(glow::BLEND);
+        gl.blend_func(glow::ONE, glow::ONE);
+        for pair in self.levels.windows(2).rev() {
+            let (upper, lower) = (&pair[0], &pair[1]);
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(upper.fbo));
+            gl.viewport(0, 0, upper.w, upper.h);
+            gl.bind_texture(glow::TEXTURE_2D, Some(lower.tex));
+            gl.uniform_2_f32(
+                loc("uTexel").as_ref(),
+                1.0 / lower.w as f32,
+                1.0 / lower.h as f32,
+            );
+            fs_tri.draw(gl);
+        }
+        gl.disable(glow::BLEND);
+
+        // Composite: input + glow, averaged over the levels that were summed.
+        let top = &self.levels[0];
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(target));
+        gl.viewport(0, 0, w, h);
+        gl.use_program(Some(self.composite_prog));
+        let p = self.composite_prog;
+        let loc = |name: &str| gl.get_uniform_location(p, name);
+        gl.uniform_1_i32(loc("uSrc").as_ref(), 0);
+        gl.uniform_1_i32(loc("uBloom").as_ref(), 1);
+        gl.uniform_2_f32(
+            loc("uTexel").as_ref(),
+            1.0 / top.w as f32,
+            1.0 / top.h as f32,
+        );
+        gl.uniform_1_f32(loc("uRadius").as_ref(), params.radius.max(0.0));
+        gl.uniform_1_f32(
+            loc("uIntensity").as_ref(),
+            params.intensity / self.levels.len() as f32,
+        );
+        gl.bind_texture(glow::TEXTURE_2D, Some(src));
+        gl.active_texture(glow::TEXTURE1);
+        gl.bind_texture(glow::TEXTURE_2D, Some(top.tex));
+        fs_tri.draw(gl);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        Ok(())
+    }
+
+    /// Add the programs and pyramid levels to `stats`.
+    pub(crate) fn account(&self, stats: &mut RuntimeStats) {
+        stats.programs += 3;
+        let format = if self.float {
+            TextureFormat::Rgba16F
+        } else {
+            TextureFormat::Rgba8
+        };
+        for level in &self.levels {
+            stats.add_target(level.w, level.h, format);
+        }
+    }
+
+    pub unsafe fn destroy(self, gl: &glow::Context) {
+        for level in self.levels {
+            level.destroy(gl);
+        }
+        gl.delete_program(self.down_prog);
+        gl.delete_program(self.up_prog);
+        gl.delete_program(self.composite_prog);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pyramid_halves_until_one_pixel() {
+        let params = BloomParams::default();
+        assert_eq!(params.levels_for(1920, 1080), 6);
+        assert_eq!(params.levels_for(4, 4), 2);
+        assert_eq!(params.levels_for(1, 1), 1);
+        let params = BloomParams {
+            levels: 20,
+            ..params
+        };
+        assert_eq!(params.levels_for(4096, 4096), BloomParams::MAX_LEVELS);
+
+        assert_eq!(
+            level_sizes(1920, 1080, 3),
+            [(960, 540), (480, 270), (240, 135)]
+        );
+        assert_eq!(level_sizes(5, 3, 2), [(2, 1), (1, 1)]);
+    }
+}
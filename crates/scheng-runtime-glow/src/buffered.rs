@@ -0,0 +1,280 @@
+//! Triple-buffered output stage for slow consumers.
+//!
+//! `TripleBufferSink` reads each rendered output back into one of three rotating pixel buffers
+//! (PBOs) and fences it, so the render thread never waits for the transfer. On later frames the
+//! newest buffer whose fence has signalled is copied out and handed to a worker thread, which runs
+//! the consumer (an encoder, a file writer, a network sender) off the render thread. The worker
+//! holds at most one frame: while it is busy, newer frames overwrite older ones in the ring and
+//! it only ever receives the most recent completed frame. A slow consumer therefore drops its own
+//! frames instead of dragging down the render cadence.
+//!
+//! Frames are RGBA8 with rows bottom-up (GL order), like [`crate::ReadbackSink`].
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use glow::HasContext;
+
+use crate::{ExecOutput, OutputSink};
+
+const SLOTS: usize = 3;
+
+#[derive(Debug)]
+struct Slot {
+    pbo: glow::NativeBuffer,
+    fence: Option<glow::Fence>,
+    /// Sequence number of the frame read into this slot (0 = never written).
+    seq: u64,
+}
+
+/// A frame handed to the consumer of a [`TripleBufferSink`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BufferedFrame {
+    /// Sequence number of the frame since the output size last changed, starting at 1.
+    pub seq: u64,
+    pub width: i32,
+    pub height: i32,
+    /// RGBA8, rows bottom-up.
+    pub rgba: Vec<u8>,
+}
+
+/// Delivery counters for a [`TripleBufferSink`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TripleBufferStats {
+    /// Frames read back into the ring.
+    pub frames_in: u64,
+    /// Frames handed to the consumer.
+    pub delivered: u64,
+    /// Frames the consumer never saw: a newer frame was delivered first, or the output size
+    /// changed while they were still in the ring.
+    pub dropped: u64,
+}
+
+/// Decouples a slow consumer from render cadence (see module docs).
+pub struct TripleBufferSink {
+    slots: Vec<Slot>,
+    /// Size the slots were allocated for.
+    size: (i32, i32),
+    write: usize,
+    seq: u64,
+    last_delivered_seq: u64,
+    /// The frame buffer the worker is not holding; `None` while the worker is busy with it.
+    spare: Option<BufferedFrame>,
+    to_worker: Option<Sender<BufferedFrame>>,
+    from_worker: Receiver<BufferedFrame>,
+    worker: Option<JoinHandle<()>>,
+    stats: TripleBufferStats,
+}
+
+impl TripleBufferSink {
+    /// Spawn the worker thread that runs `consumer` on each delivered frame.
+    pub fn new<F>(mut consumer: F) -> Self
+    where
+        F: FnMut(&BufferedFrame) + Send + 'static,
+    {
+        let (to_worker, frames) = mpsc::channel::<BufferedFrame>();
+        let (back, from_worker) = mpsc::channel();
+        let worker = thread::spawn(move || {
+            for frame in frames {
+                consumer(&frame);
+                // Returning the buffer is what marks the worker free again.
+                if back.send(frame).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            slots: Vec::new(),
+            size: (0, 0),
+            write: 0,
+            seq: 0,
+            last_delivered_seq: 0,
+            spare: Some(BufferedFrame::default()),
+            to_worker: Some(to_worker),
+            from_worker,
+            worker: Some(worker),
+            stats: TripleBufferStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> TripleBufferStats {
+        self.stats
+    }
+
+    /// Block until every queued readback has finished and the worker is free, then hand it the
+    /// newest frame. For shutdown and tests; the render loop never needs it.
+    pub fn flush(&mut self, gl: &glow::Context) {
+        unsafe {
+            gl.finish();
+            self.deliver(gl, true);
+        }
+    }
+
+    /// (Re)allocate the ring for a `w` x `h` output. A size change discards the frames still in
+    /// the ring and restarts the sequence at 1.
+    unsafe fn ensure_slots(&mut self, gl: &glow::Context, w: i32, h: i32) -> bool {
+        if self.slots.len() == SLOTS && self.size == (w, h) {
+            return true;
+        }
+        self.stats.dropped += self.seq - self.last_delivered_seq;
+        self.seq = 0;
+        self.last_delivered_seq = 0;
+        self.write = 0;
+        self.delete_slots(gl);
+
+        let len = w.max(1) * h.max(1) * 4;
+        for _ in 0..SLOTS {
+            match gl.create_buffer() {
+                Ok(pbo) => {
+                    gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(pbo));
+                    gl.buffer_data_size(glow::PIXEL_PACK_BUFFER, len, glow::STREAM_READ);
+                    self.slots.push(Slot {
+                        pbo,
+                        fence: None,
+                        seq: 0,
+                    });
+                }
+                Err(e) => {
+                    eprintln!("[TripleBufferSink] buffer allocation error: {e}");
+                    gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+                    self.delete_slots(gl);
+                    return false;
+                }
+            }
+        }
+        gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+        self.size = (w, h);
+        true
+    }
+
+    unsafe fn delete_slots(&mut self, gl: &glow::Context) {
+        for s in self.slots.drain(..) {
+            if let Some(f) = s.fence {
+                gl.delete_sync(f);
+            }
+            gl.delete_buffer(s.pbo);
+        }
+    }
+
+    /// Newest undelivered slot whose readback has finished on the GPU.
+    unsafe fn newest_complete(&mut self, gl: &glow::Context) -> Option<usize> {
+        let mut best: Option<(usize, u64)> = None;
+        for (i, s) in self.slots.iter_mut().enumerate() {
+            if s.seq <= self.last_delivered_seq {
+                continue;
+            }
+            if let Some(f) = s.fence {
+                if gl.get_sync_status(f) != glow::SIGNALED {
+                    continue;
+                }
+                gl.delete_sync(f);
+                s.fence = None;
+            }
+            if best.is_none_or(|(_, seq)| seq < s.seq) {
+                best = Some((i, s.seq));
+            }
+        }
+        best.map(|(i, _)| i)
+    }
+
+    /// Copy the newest completed slot out and send it to the worker, if the worker is free.
+    /// With `wait`, block until it is.
+    unsafe fn deliver(&mut self, gl: &glow::Context, wait: bool) {
+        if self.spare.is_none() {
+            self.spare = if wait {
+                self.from_worker.recv().ok()
+            } else {
+                self.from_worker.try_recv().ok()
+            };
+        }
+        if self.spare.is_none() {
+            return;
+        }
+        let Some(i) = self.newest_complete(gl) else {
+            return;
+        };
+        let Some(mut frame) = self.spare.take() else {
+            return;
+        };
+
+        let (w, h) = self.size;
+        let len = (w * h * 4) as usize;
+        let seq = self.slots[i].seq;
+        gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(self.slots[i].pbo));
+        let ptr = gl.map_buffer_range(glow::PIXEL_PACK_BUFFER, 0, len as i32, glow::MAP_READ_BIT);
+        if ptr.is_null() {
+            eprintln!("[TripleBufferSink] could not map readback buffer");
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+            self.spare = Some(frame);
+            return;
+        }
+        frame.rgba.clear();
+        frame
+            .rgba
+            .extend_from_slice(std::slice::from_raw_parts(ptr, len));
+        gl.unmap_buffer(glow::PIXEL_PACK_BUFFER);
+        gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+
+        frame.seq = seq;
+        frame.width = w;
+        frame.height = h;
+        self.stats.dropped += seq - self.last_delivered_seq - 1;
+        self.last_delivered_seq = seq;
+        if self
+            .to_worker
+            .as_ref()
+            .is_some_and(|tx| tx.send(frame).is_ok())
+        {
+            self.stats.delivered += 1;
+        }
+    }
+}
+
+impl OutputSink for TripleBufferSink {
+    fn consume(&mut self, gl: &glow::Context, out: &ExecOutput) {
+        unsafe {
+            // 1) Frames from earlier calls first: this one's readback has not even started.
+            self.deliver(gl, false);
+
+            if !self.ensure_slots(gl, out.width, out.height) {
+                return;
+            }
+
+            // 2) Queue this frame's readback into the write slot and fence it.
+            let slot = &mut self.slots[self.write];
+            if let Some(f) = slot.fence.take() {
+                gl.delete_sync(f);
+            }
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(out.fbo));
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, Some(slot.pbo));
+            gl.read_pixels(
+                0,
+                0,
+                out.width,
+                out.height,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::BufferOffset(0),
+            );
+            gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+            slot.fence = gl.fence_sync(glow::SYNC_GPU_COMMANDS_COMPLETE, 0).ok();
+            self.seq += 1;
+            slot.seq = self.seq;
+            self.stats.frames_in += 1;
+            self.write = (self.write + 1) % SLOTS;
+        }
+    }
+
+    fn destroy(&mut self, gl: &glow::Context) {
+        unsafe {
+            self.delete_slots(gl);
+        }
+        // Closing the channel ends the worker once it has finished its current frame.
+        self.to_worker = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Self-referential buffer passes (Shadertoy "Buffer A/B/C/D").
+//!
+//! Every render pass keeps its previous output in its ping-pong target, and any pass can read
+//! it by declaring `uniform sampler2D iSelf;` ([`SELF_UNIFORM`]) — no `history` port or delayed
+//! self-edge needed. It is black on the first frame and after a resize.
+//!
+//! For multipass effects, a [`BufferSet`] in `NodeProps::buffer_sets` names up to four passes
+//! as buffers A–D. Each buffer and each listed reader (usually the effect's final image pass)
+//! can sample them as `iBufferA`..`iBufferD` ([`BUFFER_UNIFORMS`]):
+//!
+//! - a buffer reading its own slot gets its previous frame (same texture as `iSelf`);
+//! - a buffer that already rendered this frame reads as this frame's output;
+//! - one that has not rendered yet reads as its previous frame.
+//!
+//! Buffers render in plan order (lowest `NodeId` first among independent nodes), so creating
+//! them in A..D order gives Shadertoy's semantics. Buffer samplers sit on texture units after
+//! the `iChannel` inputs and do not use input ports.
+
+use scheng_graph::NodeId;
+
+/// Sampler uniform bound to the pass's own previous output.
+pub const SELF_UNIFORM: &str = "iSelf";
+
+/// Sampler uniforms for buffers A..D of the pass's [`BufferSet`].
+pub const BUFFER_UNIFORMS: [&str; 4] = ["iBufferA", "iBufferB", "iBufferC", "iBufferD"];
+
+/// Up to four named buffer passes that make up one logical effect.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BufferSet {
+    /// Nodes rendering buffers A..D.
+    pub buffers: [Option<NodeId>; 4],
+    /// Other passes that sample the buffers (e.g. the effect's image pass).
+    pub readers: Vec<NodeId>,
+}
+
+impl BufferSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `node` as buffer `slot` (0 = A .. 3 = D); out-of-range slots are ignored.
+    pub fn with_buffer(mut self, slot: usize, node: NodeId) -> Self {
+        if let Some(b) = self.buffers.get_mut(slot) {
+            *b = Some(node);
+        }
+        self
+    }
+
+    pub fn with_reader(mut self, node: NodeId) -> Self {
+        self.readers.push(node);
+        self
+    }
+
+    /// Whether `node` sees this set's `iBufferN` samplers.
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.buffers.contains(&Some(node)) || self.readers.contains(&node)
+    }
+
+    /// `(uniform name, buffer node)` for each assigned slot.
+    pub fn bindings(&self) -> impl Iterator<Item = (&'static str, NodeId)> + '_ {
+        BUFFER_UNIFORMS
+            .iter()
+            .zip(self.buffers)
+            .filter_map(|(name, node)| Some((*name, node?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn members_and_bindings() {
+        let set = BufferSet::new()
+            .with_buffer(0, NodeId(3))
+            .with_buffer(2, NodeId(5))
+            .with_buffer(7, NodeId(9))
+            .with_reader(NodeId(8));
+        assert!(set.contains(NodeId(3)) && set.contains(NodeId(8)));
+        assert!(!set.contains(NodeId(9)));
+        let bound: Vec<_> = set.bindings().collect();
+        assert_eq!(
+            bound,
+            vec![("iBufferA", NodeId(3)), ("iBufferC", NodeId(5))]
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! What the current GL context can do.
+//!
+//! [`RuntimeCaps::probe`] queries the version, profile and limits once so hosts can check a
+//! patch before building it (e.g. pick a smaller output on a 4096px GPU, or skip compute
+//! nodes on GL 3.3). `RuntimeState` probes on creation ([`RuntimeState::caps`]) and
+//! `execute_plan` checks each render node against the result, failing with an error that names
+//! the node and the limit instead of an opaque incomplete-framebuffer or `GL_INVALID_VALUE`.
+//!
+//! [`RuntimeState::caps`]: crate::RuntimeState::caps
+
+use std::fmt;
+
+use glow::HasContext;
+use scheng_graph::{Graph, NodeKind, Plan, PortDir};
+
+use crate::compute::compute_supported;
+use crate::EngineError;
+
+/// Which flavour of GL the context speaks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlProfile {
+    Core,
+    Compatibility,
+    Es,
+}
+
+impl fmt::Display for GlProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Core => "core",
+            Self::Compatibility => "compatibility",
+            Self::Es => "ES",
+        })
+    }
+}
+
+/// Version, profile and limits of a GL context (see [`RuntimeCaps::probe`]).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeCaps {
+    /// `(major, minor)` GL (or GLES) version.
+    pub version: (u32, u32),
+    pub profile: GlProfile,
+    /// `GL_VENDOR`, `GL_RENDERER`, `GL_VERSION` and `GL_SHADING_LANGUAGE_VERSION` as reported.
+    pub vendor: String,
+    pub renderer: String,
+    pub version_string: String,
+    pub glsl_version: String,
+    /// Largest texture (and therefore render target) side in pixels.
+    pub max_texture_size: i32,
+    /// Texture units a fragment shader can sample from.
+    pub max_texture_units: i32,
+    /// Texture units across all stages.
+    pub max_combined_texture_units: i32,
+    /// Colour attachments per framebuffer.
+    pub max_color_attachments: i32,
+    /// Outputs a fragment shader can write at once (bounds MRT passes).
+    pub max_draw_buffers: i32,
+    /// `RGBA16F` is colour-renderable.
+    pub float16_targets: bool,
+    /// `RGBA32F` is colour-renderable (needed by `Particles` nodes).
+    pub float32_targets: bool,
+    /// Compute shaders are available (see [`ComputeExecutor`](crate::ComputeExecutor)).
+    pub compute: bool,
+}
+
+impl RuntimeCaps {
+    /// Query the current context. Float target support is tested by building a 1x1 target of
+    /// each format; the probe leaves no GL error and no bindings behind.
+    pub unsafe fn probe(gl: &glow::Context) -> Self {
+        let v = gl.version();
+        let profile = if v.is_embedded {
+            GlProfile::Es
+        } else if (v.major, v.minor) >= (3, 2)
+            && gl.get_parameter_i32(glow::CONTEXT_PROFILE_MASK)
+                & glow::CONTEXT_CORE_PROFILE_BIT as i32
+                != 0
+        {
+            GlProfile::Core
+        } else {
+            GlProfile::Compatibility
+        };
+        let caps = Self {
+            version: (v.major, v.minor),
+            profile,
+            vendor: gl.get_parameter_string(glow::VENDOR),
+            renderer: gl.get_parameter_string(glow::RENDERER),
+            version_string: gl.get_parameter_string(glow::VERSION),
+            glsl_version: gl.get_parameter_string(glow::SHADING_LANGUAGE_VERSION),
+            max_texture_size: gl.get_parameter_i32(glow::MAX_TEXTURE_SIZE),
+            max_texture_units: gl.get_parameter_i32(glow::MAX_TEXTURE_IMAGE_UNITS),
+            max_combined_texture_units: gl
+                .get_parameter_i32(glow::MAX_COMBINED_TEXTURE_IMAGE_UNITS),
+            max_color_attachments: gl.get_parameter_i32(glow::MAX_COLOR_ATTACHMENTS),
+            max_draw_buffers: gl.get_parameter_i32(glow::MAX_DRAW_BUFFERS),
+            float16_targets: float_target_renderable(gl, glow::RGBA16F, glow::HALF_FLOAT),
+            float32_targets: float_target_renderable(gl, glow::RGBA32F, glow::FLOAT),
+            compute: compute_supported(gl),
+        };
+        // Don't leave errors from unsupported queries for the host (or `check_gl_errors`).
+        while gl.get_error() != glow::NO_ERROR {}
+        caps
+    }
+
+    /// True if the context is at least GL (or GLES) `major.minor`.
+    pub fn supports_version(&self, major: u32, minor: u32) -> bool {
+        self.version >= (major, minor)
+    }
+
+    /// Colour outputs one pass can write (the smaller of draw buffers and attachments).
+    pub fn max_pass_outputs(&self) -> usize {
+        self.max_draw_buffers.min(self.max_color_attachments).max(1) as usize
+    }
+
+    /// Check a `width`x`height` render target against `GL_MAX_TEXTURE_SIZE`.
+    pub fn check_target_size(&self, width: i32, height: i32) -> Result<(), String> {
+        if width > self.max_texture_size || height > self.max_texture_size {
+            return Err(format!(
+                "{width}x{height} render target exceeds the GPU's maximum texture size \
+                 ({max}x{max} on {renderer})",
+                max = self.max_texture_size,
+                renderer = self.renderer
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check one render node: its target size, output count and any format it needs.
+    pub fn check_node(
+        &self,
+        kind: &NodeKind,
+        outputs: usize,
+        width: i32,
+        height: i32,
+    ) -> Result<(), String> {
+        self.check_target_size(width, height)?;
+        if outputs > self.max_pass_outputs() {
+            return Err(format!(
+                "{outputs} outputs but the GPU can write at most {} per pass",
+                self.max_pass_outputs()
+            ));
+        }
+        if *kind == NodeKind::Particles && !self.float32_targets {
+            return Err(
+                "particle state needs RGBA32F render targets, which this context \
+                        cannot render to"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Check every render node of `plan` at a `width`x`height` output (branch formats with a
+    /// fixed size use theirs), plus the texture units `execute_plan` binds. Lists all problems.
+    pub fn check_plan(
+        &self,
+        graph: &Graph,
+        plan: &Plan,
+        width: i32,
+        height: i32,
+    ) -> Result<(), EngineError> {
+        let mut problems = Vec::new();
+        if self.max_texture_units < crate::BOUND_UNITS as i32 {
+            problems.push(format!(
+                "execute_plan binds {} texture units but the GPU has {}",
+                crate::BOUND_UNITS,
+                self.max_texture_units
+            ));
+        }
+        for node in plan.nodes.iter().filter_map(|id| graph.node(*id)) {
+            if !node.kind.renders() {
+                continue;
+            }
+            let (w, h) = plan
+                .format(node.id)
+                .and_then(|f| f.size)
+                .map_or((width, height), |(w, h)| (w as i32, h as i32));
+            let outputs = node.ports.iter().filter(|p| p.dir == PortDir::Out).count();
+            if let Err(e) = self.check_node(&node.kind, outputs, w, h) {
+                problems.push(format!("node #{} ({:?}): {e}", node.id.0, node.kind));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(EngineError::Other(format!(
+                "plan exceeds GPU capabilities: {}",
+                problems.join("; ")
+            )))
+        }
+    }
+}
+
+impl fmt::Display for RuntimeCaps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "GL {}.{} {} ({}), max texture {}, {} texture units, {} draw buffers, float targets: \
+             16f={} 32f={}, compute={}",
+            self.version.0,
+            self.version.1,
+            self.profile,
+            self.renderer,
+            self.max_texture_size,
+            self.max_texture_units,
+            self.max_draw_buffers,
+            self.float16_targets,
+            self.float32_targets,
+            self.compute
+        )
+    }
+}
+
+/// Build a 1x1 `internal_format` target and report whether the framebuffer is complete.
+unsafe fn float_target_renderable(gl: &glow::Context, internal_format: u32, ty: u32) -> bool {
+    let (Ok(tex), Ok(fbo)) = (gl.create_texture(), gl.create_framebuffer()) else {
+        return false;
+    };
+    gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+    gl.tex_image_2d(
+        glow::TEXTURE_2D,
+        0,
+        internal_format as i32,
+        1,
+        1,
+        0,
+        glow::RGBA,
+        ty,
+        None,
+    );
+    gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+    gl.framebuffer_texture_2d(
+        glow::FRAMEBUFFER,
+        glow::COLOR_ATTACHMENT0,
+        glow::TEXTURE_2D,
+        Some(tex),
+        0,
+    );
+    let complete = gl.check_framebuffer_status(glow::FRAMEBUFFER) == glow::FRAMEBUFFER_COMPLETE;
+    gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+    gl.bind_texture(glow::TEXTURE_2D, None);
+    gl.delete_framebuffer(fbo);
+    gl.delete_texture(tex);
+    complete
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn caps() -> RuntimeCaps {
+        RuntimeCaps {
+            version: (3, 3),
+            profile: GlProfile::Core,
+            vendor: "test".into(),
+            renderer: "test gpu".into(),
+            version_string: "3.3".into(),
+            glsl_version: "3.30".into(),
+            max_texture_size: 4096,
+            max_texture_units: 16,
+            max_combined_texture_units: 48,
+            max_color_attachments: 8,
+            max_draw_buffers: 2,
+            float16_targets: true,
+            float32_targets: false,
+            compute: false,
+        }
+    }
+
+    #[test]
+    fn node_checks_name_the_limit() {
+        let caps = caps();
+        assert!(caps.supports_version(3, 3) && !caps.supports_version(4, 3));
+        assert!(caps
+            .check_node(&NodeKind::ShaderPass, 2, 4096, 2160)
+            .is_ok());
+
+        let err = caps
+            .check_node(&NodeKind::ShaderPass, 1, 8192, 4320)
+            .unwrap_err();
+        assert!(
+            err.contains("8192x4320") && err.contains("4096x4096 on test gpu"),
+            "{err}"
+        );
+        let err = caps
+            .check_node(&NodeKind::ShaderPass, 3, 64, 64)
+            .unwrap_err();
+        assert!(err.contains("at most 2"), "{err}");
+        let err = caps
+            .check_node(&NodeKind::Particles, 1, 64, 64)
+            .unwrap_err();
+        assert!(err.contains("RGBA32F"), "{err}");
+    }
+}
@@ -0,0 +1,70 @@
+//! What a render node's target holds before it draws (`NodeProps::clear_modes`).
+//!
+//! Every target is cleared to opaque black by default. A fullscreen shader overwrites every
+//! pixel anyway, so the clear shows where a pass leaves pixels alone: geometry passes, particle
+//! sprites, executors, pre-render hooks and fragments that `discard`. [`ClearMode::Color`] picks
+//! another background, e.g. transparent for intermediates that are composited later.
+//! [`ClearMode::Keep`] starts from the node's previous frame instead, so draws accumulate
+//! (trails, paint-style passes) without a feedback edge.
+
+use glow::HasContext;
+
+use crate::PingPong;
+
+/// How a render node's target starts each frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClearMode {
+    /// Clear to this RGBA colour.
+    Color([f32; 4]),
+    /// Keep the previous frame's output and draw over it. The first frame after creation or a
+    /// resize starts transparent black.
+    Keep,
+}
+
+impl Default for ClearMode {
+    fn default() -> Self {
+        ClearMode::Color([0.0, 0.0, 0.0, 1.0])
+    }
+}
+
+impl ClearMode {
+    pub const TRANSPARENT: ClearMode = ClearMode::Color([0.0; 4]);
+}
+
+impl PingPong {
+    /// Prepare `curr` (bound as the draw framebuffer, after `swap`) for drawing with `mode`.
+    pub(crate) unsafe fn begin_draw(&mut self, gl: &glow::Context, mode: ClearMode) {
+        let color = match mode {
+            ClearMode::Color(color) => Some(color),
+            ClearMode::Keep if self.fresh => Some([0.0; 4]),
+            ClearMode::Keep => None,
+        };
+        self.fresh = false;
+        if let Some([r, g, b, a]) = color {
+            gl.clear_color(r, g, b, a);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+            return;
+        }
+        // Blitting into the main FBO writes every draw buffer, so the MRT attachments are
+        // copied after it through their own FBOs.
+        let pairs = std::iter::once((&self.prev, &self.curr))
+            .chain(self.prev_extra.iter().zip(&self.curr_extra));
+        for (src, dst) in pairs {
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(src.fbo));
+            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(dst.fbo));
+            gl.blit_framebuffer(
+                0,
+                0,
+                src.w,
+                src.h,
+                0,
+                0,
+                dst.w,
+                dst.h,
+                glow::COLOR_BUFFER_BIT,
+                glow::NEAREST,
+            );
+        }
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.curr.fbo));
+    }
+}
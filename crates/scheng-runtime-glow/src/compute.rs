@@ -0,0 +1,215 @@
+//! Compute-shader passes (GL 4.3 / GLES 3.1).
+//!
+//! [`ComputeExecutor`] is a [`NodeExecutor`] that runs a compute shader over the node's target:
+//! one invocation per output pixel, writing through `image2D` binding 0. Inputs are bound as
+//! samplers (`iChannel0..3`, same units as fragment passes), standard uniforms (`uTime`,
+//! `uResolution`, ...) are set as usual, and storage buffers added with
+//! [`ComputeExecutor::with_storage_buffer`] persist across frames (particle state, histograms).
+//!
+//! ```glsl
+//! #version 430
+//! layout(local_size_x = 8, local_size_y = 8) in;
+//! layout(rgba8, binding = 0) writeonly uniform image2D uOutput;
+//! uniform sampler2D iChannel0;
+//! uniform vec2 uResolution;
+//! void main() {
+//!     ivec2 p = ivec2(gl_GlobalInvocationID.xy);
+//!     vec2 uv = (vec2(p) + 0.5) / uResolution;
+//!     imageStore(uOutput, p, texture(iChannel0, uv));
+//! }
+//! ```
+//!
+//! # Fallback on GL 3.3
+//!
+//! macOS and many older drivers stop at GL 3.3, which has no compute. Check [`compute_supported`]
+//! and only register the executor when it returns true; otherwise leave the node's fragment
+//! shader in `NodeProps::shader_sources` as the fallback implementation (a node without an
+//! executor renders its shader as usual). [`ComputeExecutor::new`] fails with a clear error
+//! on such contexts rather than at dispatch time.
+
+use glow::HasContext;
+
+use crate::executor::{NodeExecCtx, NodeExecutor};
+use crate::uniforms::{apply_standard_uniforms, StandardUniformLocations};
+use crate::EngineError;
+
+/// Whether the current context can run [`ComputeExecutor`]s.
+pub fn compute_supported(gl: &glow::Context) -> bool {
+    let v = gl.version();
+    version_supports_compute(v.major, v.minor, v.is_embedded)
+        || gl.supported_extensions().contains("GL_ARB_compute_shader")
+}
+
+fn version_supports_compute(major: u32, minor: u32, embedded: bool) -> bool {
+    let min = if embedded { (3, 1) } else { (4, 3) };
+    (major, minor) >= min
+}
+
+/// A compute-shader node (see the module docs for the binding contract).
+#[derive(Debug)]
+pub struct ComputeExecutor {
+    program: glow::NativeProgram,
+    local_size: (u32, u32),
+    uniforms: StandardUniformLocations,
+    /// `(binding, buffer)` pairs bound to `GL_SHADER_STORAGE_BUFFER` each dispatch.
+    storage: Vec<(u32, glow::NativeBuffer)>,
+}
+
+impl ComputeExecutor {
+    /// Compile `src`. Fails on contexts without compute support (see [`compute_supported`]).
+    ///
+    /// The work-group size defaults to 8x8; call [`with_local_size`](Self::with_local_size) if
+    /// the shader declares something else.
+    pub unsafe fn new(gl: &glow::Context, src: &str) -> Result<Self, EngineError> {
+        if !compute_supported(gl) {
+            let v = gl.version();
+            return Err(EngineError::Other(format!(
+                "compute shaders need GL 4.3 or GLES 3.1 (context is {}.{}); \
+                 keep a fragment-shader fallback for this node",
+                v.major, v.minor
+            )));
+        }
+        let program = compile_compute(gl, src)?;
+        Ok(Self {
+            program,
+            local_size: (8, 8),
+            uniforms: StandardUniformLocations::reflect(gl, program),
+            storage: Vec::new(),
+        })
+    }
+
+    /// Work-group size declared by the shader (`local_size_x`, `local_size_y`).
+    pub fn with_local_size(mut self, x: u32, y: u32) -> Self {
+        self.local_size = (x.max(1), y.max(1));
+        self
+    }
+
+    /// Add a storage buffer at `binding`, initialised with `data`. It lives (and keeps whatever
+    /// the shader writes) until the executor is destroyed.
+    pub unsafe fn with_storage_buffer(
+        mut self,
+        gl: &glow::Context,
+        binding: u32,
+        data: &[u8],
+    ) -> Result<Self, EngineError> {
+        let buf = gl
+            .create_buffer()
+            .map_err(|e| EngineError::GlCreate(format!("create_buffer failed: {e:?}")))?;
+        gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, Some(buf));
+        gl.buffer_data_u8_slice(glow::SHADER_STORAGE_BUFFER, data, glow::DYNAMIC_COPY);
+        gl.bind_buffer(glow::SHADER_STORAGE_BUFFER, None);
+        self.storage.push((binding, buf));
+        Ok(self)
+    }
+
+    /// Storage buffer at `binding`, e.g. to read results back on the host.
+    pub fn storage_buffer(&self, binding: u32) -> Option<glow::NativeBuffer> {
+        self.storage
+            .iter()
+            .find(|(b, _)| *b == binding)
+            .map(|(_, buf)| *buf)
+    }
+}
+
+impl NodeExecutor for ComputeExecutor {
+    fn execute(&mut self, gl: &glow::Context, ctx: &NodeExecCtx) -> Result<(), EngineError> {
+        unsafe {
+            gl.use_program(Some(self.program));
+            apply_standard_uniforms(gl, &self.uniforms, &ctx.frame);
+
+            for (unit, tex) in ctx.inputs {
+                gl.active_texture(glow::TEXTURE0 + unit);
+                gl.bind_texture(glow::TEXTURE_2D, Some(*tex));
+                let name = format!("iChannel{unit}");
+                if let Some(loc) = gl.get_uniform_location(self.program, &name) {
+                    gl.uniform_1_i32(Some(&loc), *unit as i32);
+                }
+            }
+            gl.bind_image_texture(0, ctx.tex, 0, false, 0, glow::WRITE_ONLY, glow::RGBA8);
+            for (binding, buf) in &self.storage {
+                gl.bind_buffer_base(glow::SHADER_STORAGE_BUFFER, *binding, Some(*buf));
+            }
+
+            let (lx, ly) = self.local_size;
+            let gx = (ctx.width.max(1) as u32).div_ceil(lx);
+            let gy = (ctx.height.max(1) as u32).div_ceil(ly);
+            gl.dispatch_compute(gx, gy, 1);
+            // Downstream passes sample the image; the host may read storage buffers.
+            gl.memory_barrier(
+                glow::TEXTURE_FETCH_BARRIER_BIT
+                    | glow::FRAMEBUFFER_BARRIER_BIT
+                    | glow::SHADER_IMAGE_ACCESS_BARRIER_BIT
+                    | glow::SHADER_STORAGE_BARRIER_BIT
+                    | glow::BUFFER_UPDATE_BARRIER_BIT,
+            );
+
+            for (binding, _) in &self.storage {
+                gl.bind_buffer_base(glow::SHADER_STORAGE_BUFFER, *binding, None);
+            }
+            for (unit, _) in ctx.inputs {
+                gl.active_texture(glow::TEXTURE0 + unit);
+                gl.bind_texture(glow::TEXTURE_2D, None);
+            }
+            gl.active_texture(glow::TEXTURE0);
+            gl.use_program(None);
+        }
+        Ok(())
+    }
+
+    fn destroy(&mut self, gl: &glow::Context) {
+        unsafe {
+            gl.delete_program(self.program);
+            for (_, buf) in self.storage.drain(..) {
+                gl.delete_buffer(buf);
+            }
+        }
+    }
+}
+
+unsafe fn compile_compute(
+    gl: &glow::Context,
+    src: &str,
+) -> Result<glow::NativeProgram, EngineError> {
+    let cs = gl
+        .create_shader(glow::COMPUTE_SHADER)
+        .map_err(|e| EngineError::GlCreate(format!("create_shader(CS) failed: {e:?}")))?;
+    gl.shader_source(cs, src);
+    gl.compile_shader(cs);
+    if !gl.get_shader_compile_status(cs) {
+        let log = gl.get_shader_info_log(cs);
+        gl.delete_shader(cs);
+        return Err(EngineError::Other(format!(
+            "compute shader compile failed: {log}"
+        )));
+    }
+
+    let program = gl
+        .create_program()
+        .map_err(|e| EngineError::GlCreate(format!("create_program failed: {e:?}")))?;
+    gl.attach_shader(program, cs);
+    gl.link_program(program);
+    gl.detach_shader(program, cs);
+    gl.delete_shader(cs);
+
+    if !gl.get_program_link_status(program) {
+        let log = gl.get_program_info_log(program);
+        gl.delete_program(program);
+        return Err(EngineError::Link(log));
+    }
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compute_needs_gl43_or_gles31() {
+        assert!(!version_supports_compute(3, 3, false));
+        assert!(!version_supports_compute(4, 2, false));
+        assert!(version_supports_compute(4, 3, false));
+        assert!(version_supports_compute(4, 6, false));
+        assert!(!version_supports_compute(3, 0, true));
+        assert!(version_supports_compute(3, 1, true));
+    }
+}
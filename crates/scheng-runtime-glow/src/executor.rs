@@ -0,0 +1,137 @@
+//! Host-implemented node executors.
+//!
+//! Not every node is a shader. Register a [`NodeExecutor`] on a render node (usually a
+//! `ShaderPass`, or a mixer for multi-input ops) with
+//! [`RuntimeState::set_node_executor`](crate::RuntimeState::set_node_executor) and
+//! `execute_plan` calls it instead of compiling and drawing a shader. The node needs no entry in
+//! `NodeProps::shader_sources`; its output is whatever the executor leaves in the target and it
+//! feeds downstream nodes like any other pass.
+//!
+//! The GL state on entry matches the render hooks (see [`hooks`](crate::hooks)): the node's
+//! target is bound and cleared to opaque black, the viewport covers it, depth test and blending
+//! are off. Executors may change program, VAO and texture bindings but must restore the
+//! framebuffer binding, viewport and enable flags.
+
+use glow::HasContext;
+use scheng_graph::NodeId;
+
+use crate::{EngineError, FrameCtx};
+
+/// What an executor sees for one frame.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeExecCtx<'a> {
+    pub node: NodeId,
+    /// The node's target, already bound.
+    pub fbo: glow::NativeFramebuffer,
+    /// Colour attachment of `fbo` (RGBA); the node's output once the executor returns.
+    pub tex: glow::NativeTexture,
+    pub width: i32,
+    pub height: i32,
+    pub frame: FrameCtx,
+    /// Input textures by channel (the same channels a shader sees as `iChannelN`). Unconnected
+    /// inputs carry their `InputDefault` colour.
+    pub inputs: &'a [(u32, glow::NativeTexture)],
+}
+
+impl NodeExecCtx<'_> {
+    /// Input texture on `channel`, if the node has that input.
+    pub fn input(&self, channel: u32) -> Option<glow::NativeTexture> {
+        self.inputs
+            .iter()
+            .find(|(ch, _)| *ch == channel)
+            .map(|(_, t)| *t)
+    }
+}
+
+/// A node implemented in Rust rather than GLSL.
+pub trait NodeExecutor: std::fmt::Debug {
+    /// Produce this frame's output into `ctx.fbo` / `ctx.tex`. An error aborts the frame.
+    fn execute(&mut self, gl: &glow::Context, ctx: &NodeExecCtx) -> Result<(), EngineError>;
+
+    /// Release GL resources. Called from `RuntimeState::destroy` and when the executor is
+    /// replaced or removed.
+    fn destroy(&mut self, _gl: &glow::Context) {}
+}
+
+/// CPU image generator: fills an RGBA8 buffer each frame and uploads it to the node's target.
+///
+/// The closure gets the frame, the target size and a `width * height * 4` byte buffer (kept
+/// between frames, rows bottom-up as GL expects). Good for QR codes, text or data plots drawn
+/// with a CPU raster library.
+pub struct CpuImageExecutor<F> {
+    generate: F,
+    pixels: Vec<u8>,
+}
+
+impl<F> std::fmt::Debug for CpuImageExecutor<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CpuImageExecutor")
+            .field("bytes", &self.pixels.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<F> CpuImageExecutor<F>
+where
+    F: FnMut(&FrameCtx, i32, i32, &mut [u8]),
+{
+    pub fn new(generate: F) -> Self {
+        Self {
+            generate,
+            pixels: Vec::new(),
+        }
+    }
+}
+
+impl<F> NodeExecutor for CpuImageExecutor<F>
+where
+    F: FnMut(&FrameCtx, i32, i32, &mut [u8]),
+{
+    fn execute(&mut self, gl: &glow::Context, ctx: &NodeExecCtx) -> Result<(), EngineError> {
+        let len = ctx.width.max(0) as usize * ctx.height.max(0) as usize * 4;
+        self.pixels.resize(len, 0);
+        (self.generate)(&ctx.frame, ctx.width, ctx.height, &mut self.pixels);
+
+        unsafe {
+            gl.bind_texture(glow::TEXTURE_2D, Some(ctx.tex));
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            gl.tex_sub_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                0,
+                0,
+                ctx.width,
+                ctx.height,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelUnpackData::Slice(&self.pixels),
+            );
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU32;
+
+    #[test]
+    fn input_looks_up_by_channel() {
+        let tex = |n| glow::NativeTexture(NonZeroU32::new(n).unwrap());
+        let inputs = [(0, tex(7)), (2, tex(9))];
+        let ctx = NodeExecCtx {
+            node: NodeId(1),
+            fbo: glow::NativeFramebuffer(NonZeroU32::new(1).unwrap()),
+            tex: tex(1),
+            width: 4,
+            height: 4,
+            frame: FrameCtx::new(4, 4, 0.0, 0),
+            inputs: &inputs,
+        };
+        assert_eq!(ctx.input(2), Some(tex(9)));
+        assert_eq!(ctx.input(1), None);
+    }
+}
@@ -0,0 +1,352 @@
+//! Offline still-sequence export: render a patch frame by frame and write numbered images.
+//!
+//! [`export_sequence`] drives `execute_plan_outputs` itself at a fixed frame rate. Frame `n` gets
+//! `time = n / fps`, `dt = 1 / fps` and the configured seed, so a patch renders the same frames
+//! on every run no matter how long each takes. It needs a current GL context; a surfaceless
+//! EGL context works as well as a hidden window. Anything the patch pulls from outside (live
+//! cameras, audio, OSC) is the host's to freeze or script.
+//!
+//! Images are written top-down (flipped from GL), one file per frame: 8-bit RGBA PNG, or
+//! uncompressed 32-bit float RGBA OpenEXR. Render targets are RGBA8, so EXR frames carry 8-bit
+//! precision in a float container (for compositing tools that want EXR).
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use glow::HasContext;
+use scheng_graph::{Graph, Plan};
+
+use crate::{execute_plan_outputs, EngineError, ExecOutput, FrameCtx, NodeProps, RuntimeState};
+
+/// Image format for exported frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StillFormat {
+    #[default]
+    Png,
+    Exr,
+}
+
+impl StillFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Png => "png",
+            Self::Exr => "exr",
+        }
+    }
+}
+
+/// What to render and where to write it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportConfig {
+    /// Directory for the frames (created if missing).
+    pub dir: PathBuf,
+    /// File name stem: frame `n` is written as `<prefix>_<n:05>.<ext>`.
+    pub prefix: String,
+    pub format: StillFormat,
+    pub width: i32,
+    pub height: i32,
+    pub fps: f32,
+    /// First frame number; time starts at `start_frame / fps`.
+    pub start_frame: u64,
+    /// Number of frames to write.
+    pub frames: u64,
+    /// `FrameCtx::seed` for every frame.
+    pub seed: u32,
+    /// Named output to export; `None` exports the primary output.
+    pub output: Option<String>,
+}
+
+impl ExportConfig {
+    /// `frames` PNG frames of `width`x`height` at 30 fps into `dir`, named `frame_00000.png`...
+    pub fn new(dir: impl Into<PathBuf>, width: i32, height: i32, frames: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            prefix: "frame".to_string(),
+            format: StillFormat::Png,
+            width,
+            height,
+            fps: 30.0,
+            start_frame: 0,
+            frames,
+            seed: 0,
+            output: None,
+        }
+    }
+
+    /// Path of frame number `n`.
+    pub fn frame_path(&self, n: u64) -> PathBuf {
+        self.dir.join(format!(
+            "{}_{n:05}.{}",
+            self.prefix,
+            self.format.extension()
+        ))
+    }
+
+    /// The frame context used for frame number `n`.
+    pub fn frame_ctx(&self, n: u64) -> FrameCtx {
+        let fps = self.fps.max(f32::EPSILON) as f64;
+        FrameCtx::new(self.width, self.height, (n as f64 / fps) as f32, n)
+            .with_dt((1.0 / fps) as f32)
+            .with_seed(self.seed)
+    }
+}
+
+/// Passed to the progress callback after each frame is written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportProgress {
+    /// Frames written so far (1 after the first).
+    pub done: u64,
+    pub total: u64,
+    /// Frame number just written.
+    pub frame: u64,
+    pub path: PathBuf,
+}
+
+impl ExportProgress {
+    /// Completed fraction in `0..=1`.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+}
+
+/// Result of an export.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportSummary {
+    pub frames_written: u64,
+    /// True when the progress callback stopped the export early.
+    pub cancelled: bool,
+}
+
+/// Render `config.frames` frames of `plan` and write them as images (see the module docs).
+///
+/// `progress` runs after every written frame; return `false` to stop early. `state` is used as
+/// is, so start from a fresh `RuntimeState` (or one reset for the export) when delayed edges and
+/// feedback should begin empty.
+///
+/// # Safety
+/// Requires a current GL context that `state` was created on.
+pub unsafe fn export_sequence(
+    gl: &glow::Context,
+    graph: &Graph,
+    plan: &Plan,
+    state: &mut RuntimeState,
+    props: &NodeProps,
+    config: &ExportConfig,
+    mut progress: impl FnMut(&ExportProgress) -> bool,
+) -> Result<ExportSummary, EngineError> {
+    if config.width <= 0 || config.height <= 0 {
+        return Err(EngineError::other(format!(
+            "export_sequence: invalid size {}x{}",
+            config.width, config.height
+        )));
+    }
+    std::fs::create_dir_all(&config.dir).map_err(|e| {
+        EngineError::other(format!(
+            "export_sequence: cannot create {}: {e}",
+            config.dir.display()
+        ))
+    })?;
+
+    let mut summary = ExportSummary {
+        frames_written: 0,
+        cancelled: false,
+    };
+    for n in config.start_frame..config.start_frame + config.frames {
+        let outs = execute_plan_outputs(gl, graph, plan, state, props, config.frame_ctx(n))?;
+        let out = match &config.output {
+            None => outs.primary(),
+            Some(name) => outs.get(name).ok_or_else(|| {
+                EngineError::other(format!("export_sequence: missing named output '{name}'"))
+            })?,
+        };
+        let path = config.frame_path(n);
+        let written = match config.format {
+            StillFormat::Png => write_png(&path, out.width, out.height, &read_rgba8(gl, out)),
+            StillFormat::Exr => write_exr(&path, out.width, out.height, &read_rgba32f(gl, out)),
+        };
+        written.map_err(|e| {
+            EngineError::other(format!(
+                "export_sequence: writing {} failed: {e}",
+                path.display()
+            ))
+        })?;
+        summary.frames_written += 1;
+        let report = ExportProgress {
+            done: summary.frames_written,
+            total: config.frames,
+            frame: n,
+            path,
+        };
+        if !progress(&report) {
+            summary.cancelled = summary.frames_written < config.frames;
+            break;
+        }
+    }
+    Ok(summary)
+}
+
+unsafe fn read_pixels<T: bytemuck::Pod + Default>(
+    gl: &glow::Context,
+    out: &ExecOutput,
+    ty: u32,
+) -> Vec<T> {
+    let mut px = vec![T::default(); (out.width * out.height * 4) as usize];
+    gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(out.fbo));
+    gl.read_pixels(
+        0,
+        0,
+        out.width,
+        out.height,
+        glow::RGBA,
+        ty,
+        glow::PixelPackData::Slice(bytemuck::cast_slice_mut(&mut px)),
+    );
+    gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+    px
+}
+
+unsafe fn read_rgba8(gl: &glow::Context, out: &ExecOutput) -> Vec<u8> {
+    read_pixels(gl, out, glow::UNSIGNED_BYTE)
+}
+
+unsafe fn read_rgba32f(gl: &glow::Context, out: &ExecOutput) -> Vec<f32> {
+    read_pixels(gl, out, glow::FLOAT)
+}
+
+/// Write bottom-up RGBA8 as a top-down PNG.
+fn write_png(path: &Path, width: i32, height: i32, rgba: &[u8]) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut encoder = png::Encoder::new(BufWriter::new(file), width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    let top_down: Vec<u8> = rgba
+        .chunks_exact(width as usize * 4)
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
+    writer
+        .write_image_data(&top_down)
+        .map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())
+}
+
+/// Write bottom-up float RGBA as an uncompressed scanline OpenEXR (FLOAT channels A, B, G, R).
+fn write_exr(path: &Path, width: i32, height: i32, rgba: &[f32]) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut w = BufWriter::new(file);
+    w.write_all(&exr_bytes(width, height, rgba))
+        .and_then(|_| w.flush())
+        .map_err(|e| e.to_string())
+}
+
+fn exr_bytes(width: i32, height: i32, rgba: &[f32]) -> Vec<u8> {
+    fn attr(buf: &mut Vec<u8>, name: &str, ty: &str, value: &[u8]) {
+        for s in [name, ty] {
+            buf.extend_from_slice(s.as_bytes());
+            buf.push(0);
+        }
+        buf.extend_from_slice(&(value.len() as i32).to_le_bytes());
+        buf.extend_from_slice(value);
+    }
+    let ints = |v: &[i32]| v.iter().flat_map(|x| x.to_le_bytes()).collect::<Vec<u8>>();
+
+    // Channels are stored in alphabetical order; `src` is the RGBA component.
+    const CHANNELS: [(&str, usize); 4] = [("A", 3), ("B", 2), ("G", 1), ("R", 0)];
+    let mut chlist = Vec::new();
+    for (name, _) in CHANNELS {
+        chlist.extend_from_slice(name.as_bytes());
+        chlist.push(0);
+        // pixel type FLOAT, pLinear + reserved, x/y sampling.
+        chlist.extend_from_slice(&ints(&[2, 0, 1, 1]));
+    }
+    chlist.push(0);
+    let window = ints(&[0, 0, width - 1, height - 1]);
+
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&20000630i32.to_le_bytes());
+    buf.extend_from_slice(&2i32.to_le_bytes());
+    attr(&mut buf, "channels", "chlist", &chlist);
+    attr(&mut buf, "compression", "compression", &[0]);
+    attr(&mut buf, "dataWindow", "box2i", &window);
+    attr(&mut buf, "displayWindow", "box2i", &window);
+    attr(&mut buf, "lineOrder", "lineOrder", &[0]);
+    attr(&mut buf, "pixelAspectRatio", "float", &1f32.to_le_bytes());
+    attr(&mut buf, "screenWindowCenter", "v2f", &[0; 8]);
+    attr(&mut buf, "screenWindowWidth", "float", &1f32.to_le_bytes());
+    buf.push(0);
+
+    // One scanline per chunk, top-down.
+    let line_bytes = width as usize * 4 * 4;
+    let table_end = buf.len() + height as usize * 8;
+    for y in 0..height as usize {
+        let offset = table_end + y * (8 + line_bytes);
+        buf.extend_from_slice(&(offset as u64).to_le_bytes());
+    }
+    for y in 0..height {
+        buf.extend_from_slice(&ints(&[y, line_bytes as i32]));
+        let row = (height - 1 - y) as usize * width as usize * 4;
+        let px = &rgba[row..row + width as usize * 4];
+        for (_, src) in CHANNELS {
+            for x in 0..width as usize {
+                buf.extend_from_slice(&px[x * 4 + src].to_le_bytes());
+            }
+        }
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_get_fixed_step_time_and_numbered_paths() {
+        let mut cfg = ExportConfig::new("/tmp/loop", 64, 32, 10);
+        cfg.fps = 25.0;
+        cfg.seed = 7;
+        let f = cfg.frame_ctx(50);
+        assert_eq!((f.time, f.dt, f.frame, f.seed), (2.0, 0.04, 50, 7));
+        cfg.format = StillFormat::Exr;
+        assert_eq!(
+            cfg.frame_path(3),
+            PathBuf::from("/tmp/loop/frame_00003.exr")
+        );
+    }
+
+    #[test]
+    fn exr_layout_matches_the_offset_table() {
+        let (w, h) = (3, 2);
+        // Bottom row red, top row blue.
+        let rgba: Vec<f32> = (0..w * h)
+            .flat_map(|i| {
+                if i < w {
+                    [1.0, 0.0, 0.0, 1.0]
+                } else {
+                    [0.0, 0.0, 1.0, 1.0]
+                }
+            })
+            .collect();
+        let bytes = exr_bytes(w, h, &rgba);
+        assert_eq!(&bytes[..4], &[0x76, 0x2f, 0x31, 0x01]);
+
+        let line = 8 + w as usize * 16;
+        let first = bytes.len() - h as usize * line;
+        let table = first - h as usize * 8;
+        let offset = |y: usize| {
+            u64::from_le_bytes(bytes[table + y * 8..table + y * 8 + 8].try_into().unwrap()) as usize
+        };
+        assert_eq!((offset(0), offset(1)), (first, first + line));
+        // Line 0 is the top row: its B channel (second block) is 1.0, R (last block) is 0.0.
+        let f32_at = |p: usize| f32::from_le_bytes(bytes[p..p + 4].try_into().unwrap());
+        let data = first + 8;
+        assert_eq!(f32_at(data + w as usize * 4), 1.0);
+        assert_eq!(f32_at(data + 3 * w as usize * 4), 0.0);
+    }
+}
@@ -0,0 +1,227 @@
+//! Geometry for `GeometryPass` nodes.
+//!
+//! Every other pass draws one fullscreen triangle. A `GeometryPass` draws the [`Geometry`] set
+//! in `NodeProps::geometry` (a unit quad when absent) with the node's own vertex shader, which
+//! makes scan-line, mesh-warp and 3D-ish effects possible inside the graph.
+//!
+//! Vertex layout seen by the vertex shader:
+//!
+//! ```glsl
+//! layout (location = 0) in vec3 a_pos; // NDC for the built-ins (z = 0); `vec2` also works
+//! layout (location = 1) in vec2 a_uv;  // 0..1 across the quad / grid
+//! ```
+//!
+//! Targets have no depth buffer, so draw order decides overlap.
+
+use glow::HasContext;
+
+use crate::EngineError;
+
+/// How a mesh's indices are assembled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Primitive {
+    #[default]
+    Triangles,
+    Lines,
+    LineStrip,
+    Points,
+}
+
+impl Primitive {
+    fn gl_mode(self) -> u32 {
+        match self {
+            Primitive::Triangles => glow::TRIANGLES,
+            Primitive::Lines => glow::LINES,
+            Primitive::LineStrip => glow::LINE_STRIP,
+            Primitive::Points => glow::POINTS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[repr(C)]
+pub struct Vertex {
+    pub pos: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+/// Host-provided vertices and indices.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Mesh {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+    pub primitive: Primitive,
+}
+
+/// What a `GeometryPass` draws.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Geometry {
+    /// One quad covering the target.
+    #[default]
+    Quad,
+    /// `cols` x `rows` cells covering the target (for mesh warps); each cell is two triangles.
+    Grid {
+        cols: u32,
+        rows: u32,
+    },
+    /// `count` full-width horizontal lines, evenly spaced bottom to top (for scan-line effects).
+    Lines {
+        count: u32,
+    },
+    /// Host-provided vertices and indices.
+    Mesh(Mesh),
+}
+
+impl Geometry {
+    /// CPU-side mesh for this geometry.
+    pub fn to_mesh(&self) -> Mesh {
+        match self {
+            Geometry::Quad => grid(1, 1),
+            Geometry::Grid { cols, rows } => grid((*cols).max(1), (*rows).max(1)),
+            Geometry::Lines { count } => lines((*count).max(1)),
+            Geometry::Mesh(m) => m.clone(),
+        }
+    }
+}
+
+fn grid(cols: u32, rows: u32) -> Mesh {
+    let mut vertices = Vec::with_capacity(((cols + 1) * (rows + 1)) as usize);
+    for y in 0..=rows {
+        for x in 0..=cols {
+            let u = x as f32 / cols as f32;
+            let v = y as f32 / rows as f32;
+            vertices.push(Vertex {
+                pos: [u * 2.0 - 1.0, v * 2.0 - 1.0, 0.0],
+                uv: [u, v],
+            });
+        }
+    }
+    let stride = cols + 1;
+    let mut indices = Vec::with_capacity((cols * rows * 6) as usize);
+    for y in 0..rows {
+        for x in 0..cols {
+            let i = y * stride + x;
+            indices.extend_from_slice(&[i, i + 1, i + stride, i + 1, i + stride + 1, i + stride]);
+        }
+    }
+    Mesh {
+        vertices,
+        indices,
+        primitive: Primitive::Triangles,
+    }
+}
+
+fn lines(count: u32) -> Mesh {
+    let mut vertices = Vec::with_capacity(count as usize * 2);
+    for i in 0..count {
+        let v = (i as f32 + 0.5) / count as f32;
+        for u in [0.0, 1.0] {
+            vertices.push(Vertex {
+                pos: [u * 2.0 - 1.0, v * 2.0 - 1.0, 0.0],
+                uv: [u, v],
+            });
+        }
+    }
+    Mesh {
+        indices: (0..count * 2).collect(),
+        vertices,
+        primitive: Primitive::Lines,
+    }
+}
+
+/// A [`Geometry`] uploaded to GL.
+#[derive(Debug)]
+pub(crate) struct GpuMesh {
+    vao: glow::NativeVertexArray,
+    vbo: glow::NativeBuffer,
+    ebo: glow::NativeBuffer,
+    count: i32,
+    mode: u32,
+    /// What was uploaded, to notice when the host changes it.
+    pub(crate) source: Geometry,
+}
+
+impl GpuMesh {
+    pub(crate) unsafe fn new(gl: &glow::Context, geometry: &Geometry) -> Result<Self, EngineError> {
+        let mesh = geometry.to_mesh();
+        let vao = gl
+            .create_vertex_array()
+            .map_err(|e| EngineError::GlCreate(format!("create_vertex_array: {e}")))?;
+        let vbo = gl
+            .create_buffer()
+            .map_err(|e| EngineError::GlCreate(format!("create_buffer: {e}")))?;
+        let ebo = gl
+            .create_buffer()
+            .map_err(|e| EngineError::GlCreate(format!("create_buffer: {e}")))?;
+
+        gl.bind_vertex_array(Some(vao));
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+        let bytes = core::slice::from_raw_parts(
+            mesh.vertices.as_ptr() as *const u8,
+            std::mem::size_of_val(mesh.vertices.as_slice()),
+        );
+        gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, bytes, glow::STATIC_DRAW);
+        let stride = std::mem::size_of::<Vertex>() as i32;
+        gl.enable_vertex_attrib_array(0);
+        gl.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, stride, 0);
+        gl.enable_vertex_attrib_array(1);
+        gl.vertex_attrib_pointer_f32(1, 2, glow::FLOAT, false, stride, 3 * 4);
+
+        gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(ebo));
+        let bytes = core::slice::from_raw_parts(
+            mesh.indices.as_ptr() as *const u8,
+            std::mem::size_of_val(mesh.indices.as_slice()),
+        );
+        gl.buffer_data_u8_slice(glow::ELEMENT_ARRAY_BUFFER, bytes, glow::STATIC_DRAW);
+
+        gl.bind_vertex_array(None);
+        gl.bind_buffer(glow::ARRAY_BUFFER, None);
+        gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
+
+        Ok(Self {
+            vao,
+            vbo,
+            ebo,
+            count: mesh.indices.len() as i32,
+            mode: mesh.primitive.gl_mode(),
+            source: geometry.clone(),
+        })
+    }
+
+    pub(crate) unsafe fn draw(&self, gl: &glow::Context) {
+        gl.bind_vertex_array(Some(self.vao));
+        gl.draw_elements(self.mode, self.count, glow::UNSIGNED_INT, 0);
+        gl.bind_vertex_array(None);
+    }
+
+    pub(crate) unsafe fn destroy(self, gl: &glow::Context) {
+        gl.delete_vertex_array(self.vao);
+        gl.delete_buffer(self.vbo);
+        gl.delete_buffer(self.ebo);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_covers_ndc_with_two_triangles_per_cell() {
+        let m = Geometry::Grid { cols: 4, rows: 2 }.to_mesh();
+        assert_eq!(m.vertices.len(), 5 * 3);
+        assert_eq!(m.indices.len(), 4 * 2 * 6);
+        assert_eq!(m.vertices[0].pos, [-1.0, -1.0, 0.0]);
+        assert_eq!(m.vertices.last().unwrap().pos, [1.0, 1.0, 0.0]);
+        assert_eq!(m.vertices.last().unwrap().uv, [1.0, 1.0]);
+        assert!(m.indices.iter().all(|&i| (i as usize) < m.vertices.len()));
+    }
+
+    #[test]
+    fn lines_are_centred_in_their_rows() {
+        let m = Geometry::Lines { count: 2 }.to_mesh();
+        assert_eq!(m.primitive, Primitive::Lines);
+        assert_eq!(m.indices, vec![0, 1, 2, 3]);
+        assert_eq!(m.vertices[0].uv, [0.0, 0.25]);
+        assert_eq!(m.vertices[3].uv, [1.0, 0.75]);
+    }
+}
@@ -0,0 +1,136 @@
+//! Panic boundaries around sinks and inputs.
+//!
+//! A panicking sink or decoder thread should cost the show one output, not the process.
+//! [`GuardedSink`] runs every [`OutputSink`] call under `catch_unwind`; the first panic disables
+//! the sink and queues an [`EngineEvent::ComponentPanicked`]. `PatchbaySink` guards each route
+//! the same way (the route is switched off), and `execute_plan` freezes a `VideoDecodeSource`
+//! whose decoder panicked (events via `RuntimeState::take_events`).
+//!
+//! Only Rust panics are caught. Aborts in FFI code (a crashing GPU driver, SDK or codec) still
+//! take the process down. After a caught panic the GL bindings are reset to their defaults,
+//! since the sink may have stopped halfway through a draw.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use glow::HasContext;
+use scheng_core::events::{panic_message, EngineEvent};
+
+use crate::{ExecOutput, FrameCtx, OutputSink};
+
+/// Run `f`, returning the panic message instead of unwinding.
+pub(crate) fn guarded<R>(gl: &glow::Context, f: impl FnOnce() -> R) -> Result<R, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        unsafe { reset_bindings(gl) };
+        panic_message(&*payload)
+    })
+}
+
+/// The event for a panic in `component`.
+pub(crate) fn panicked(component: impl Into<String>, message: String) -> EngineEvent {
+    EngineEvent::ComponentPanicked {
+        component: component.into(),
+        message,
+    }
+}
+
+unsafe fn reset_bindings(gl: &glow::Context) {
+    gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+    gl.bind_vertex_array(None);
+    gl.bind_buffer(glow::ARRAY_BUFFER, None);
+    gl.bind_buffer(glow::PIXEL_PACK_BUFFER, None);
+    gl.use_program(None);
+    gl.active_texture(glow::TEXTURE0);
+    gl.bind_texture(glow::TEXTURE_2D, None);
+    gl.disable(glow::BLEND);
+}
+
+/// Wraps a sink so a panic in any of its calls disables it instead of unwinding into the host.
+///
+/// Once failed, the sink is skipped until [`reset`](Self::reset). `destroy` is still forwarded
+/// (guarded) so the sink can release what it holds.
+#[derive(Debug)]
+pub struct GuardedSink<S> {
+    inner: S,
+    name: String,
+    failure: Option<String>,
+    events: Vec<EngineEvent>,
+}
+
+impl<S: OutputSink> GuardedSink<S> {
+    /// `name` identifies the sink in events (e.g. `"ndi main"`).
+    pub fn new(name: impl Into<String>, inner: S) -> Self {
+        Self {
+            inner,
+            name: name.into(),
+            failure: None,
+            events: Vec::new(),
+        }
+    }
+
+    /// The panic message that disabled the sink, if any.
+    pub fn failure(&self) -> Option<&str> {
+        self.failure.as_deref()
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.failure.is_some()
+    }
+
+    /// Re-enable a failed sink (e.g. after the host reconfigured it).
+    pub fn reset(&mut self) {
+        self.failure = None;
+    }
+
+    /// Events queued since the last call.
+    pub fn take_events(&mut self) -> Vec<EngineEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn call(&mut self, gl: &glow::Context, f: impl FnOnce(&mut S)) {
+        if self.failure.is_some() {
+            return;
+        }
+        let inner = &mut self.inner;
+        if let Err(message) = guarded(gl, || f(inner)) {
+            self.failure = Some(message.clone());
+            self.events.push(panicked(&self.name, message));
+        }
+    }
+}
+
+impl<S: OutputSink> OutputSink for GuardedSink<S> {
+    fn consume(&mut self, gl: &glow::Context, out: &ExecOutput) {
+        self.call(gl, |s| s.consume(gl, out));
+    }
+
+    fn on_frame_begin(&mut self, gl: &glow::Context, frame: &FrameCtx) {
+        self.call(gl, |s| s.on_frame_begin(gl, frame));
+    }
+
+    fn on_frame_end(&mut self, gl: &glow::Context) {
+        self.call(gl, |s| s.on_frame_end(gl));
+    }
+
+    fn on_resize(&mut self, gl: &glow::Context, width: i32, height: i32) {
+        self.call(gl, |s| s.on_resize(gl, width, height));
+    }
+
+    fn destroy(&mut self, gl: &glow::Context) {
+        let inner = &mut self.inner;
+        if let Err(message) = guarded(gl, || inner.destroy(gl)) {
+            self.events.push(panicked(&self.name, message));
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Per-node execution hooks.
+//!
+//! A host registers a pre- and/or post-render callback for a node with
+//! [`RuntimeState::set_pre_render_hook`](crate::RuntimeState::set_pre_render_hook) /
+//! [`set_post_render_hook`](crate::RuntimeState::set_post_render_hook). `execute_plan` calls
+//! them with the node's render target bound, so a host can draw its own GL (UI overlays,
+//! debug geometry) into an intermediate target without a separate pass.
+//!
+//! # GL state
+//!
+//! On entry: the node's target framebuffer is bound to `GL_FRAMEBUFFER`, the viewport covers
+//! the whole target, depth test is disabled and blending is off. The pre hook runs after the
+//! target was cleared and before the node's program is bound; the post hook runs after the
+//! node's draw. Hooks may change the bound program, VAO, textures and texture units (the
+//! runtime rebinds those before it draws), but must leave the framebuffer binding, the viewport
+//! and every enable flag (blend, depth, scissor, `FRAMEBUFFER_SRGB`) as they found them.
+
+use scheng_graph::NodeId;
+
+use crate::{EngineError, FrameCtx};
+
+/// What a hook can see about the node being rendered.
+#[derive(Debug, Clone, Copy)]
+pub struct NodeHookCtx {
+    pub node: NodeId,
+    /// The node's target, already bound.
+    pub fbo: glow::NativeFramebuffer,
+    /// Colour attachment of `fbo`. In a post hook it holds the node's output for this frame.
+    pub tex: glow::NativeTexture,
+    pub width: i32,
+    pub height: i32,
+    pub frame: FrameCtx,
+}
+
+/// A host callback run inside `execute_plan`. An error aborts the frame like a failed draw.
+pub type NodeHook = Box<dyn FnMut(&glow::Context, &NodeHookCtx) -> Result<(), EngineError>>;
+
+/// The hooks registered for one node.
+#[derive(Default)]
+pub(crate) struct NodeHooks {
+    pub(crate) pre: Option<NodeHook>,
+    pub(crate) post: Option<NodeHook>,
+}
+
+impl std::fmt::Debug for NodeHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeHooks")
+            .field("pre", &self.pre.is_some())
+            .field("post", &self.post.is_some())
+            .finish()
+    }
+}
+
+impl NodeHooks {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pre.is_none() && self.post.is_none()
+    }
+}
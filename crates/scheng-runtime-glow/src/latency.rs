@@ -0,0 +1,321 @@
+//! End-to-end latency measurement: a stamp source and a probe sink.
+//!
+//! [`LatencyStamp`] is a [`NodeExecutor`] that renders a frame sequence number as a row of
+//! black/white blocks. [`LatencyProbe`] decodes the row wherever it shows up again and reports
+//! how many frames and milliseconds ago that number was stamped. Both share a [`LatencyClock`].
+//!
+//! In-process, put the stamp on a `ShaderPass` and route its output (through the patch under
+//! test) to the probe. For capture→projector latency, project the stamp, point the camera at the
+//! screen and probe the capture: feed the probe from the camera's `TextureInputPass` output, or
+//! call [`LatencyProbe::observe_rgba`] with CPU frames. Make the pattern region large enough to
+//! survive the camera (blocks of 20+ pixels in the captured image).
+//!
+//! The row has [`LATENCY_BLOCKS`] blocks: a white and a black reference at the ends (the
+//! threshold is their midpoint, so exposure does not matter) and [`LATENCY_BITS`] code bits,
+//! most significant first. The clock remembers the last 256 stamps, so longer latencies are not
+//! reported.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use glow::HasContext;
+
+use crate::executor::{NodeExecCtx, NodeExecutor};
+use crate::{EngineError, ExecOutput, OutputSink};
+
+/// Code bits per stamp.
+pub const LATENCY_BITS: u32 = 14;
+
+/// Blocks in the pattern row: white reference, code bits, black reference.
+pub const LATENCY_BLOCKS: usize = LATENCY_BITS as usize + 2;
+
+/// Stamps remembered for lookup.
+const CLOCK_HISTORY: usize = 256;
+
+/// Where the pattern row sits, in normalized frame coordinates (origin bottom-left).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencyPattern {
+    /// `[x, y, w, h]`; the row of blocks fills it.
+    pub region: [f32; 4],
+}
+
+impl Default for LatencyPattern {
+    /// A band across the middle of the frame, so it survives overscan and keystone.
+    fn default() -> Self {
+        Self {
+            region: [0.1, 0.4, 0.8, 0.2],
+        }
+    }
+}
+
+impl LatencyPattern {
+    /// Pixel rect `(x, y, w, h)` of block `i` in a `width x height` frame.
+    pub fn block_rect(&self, i: usize, width: i32, height: i32) -> (i32, i32, i32, i32) {
+        let [rx, ry, rw, rh] = self.region;
+        let x0 = (rx * width as f32).round() as i32;
+        let x1 = ((rx + rw) * width as f32).round() as i32;
+        let y0 = (ry * height as f32).round() as i32;
+        let y1 = ((ry + rh) * height as f32).round() as i32;
+        let edge = |k: usize| x0 + (x1 - x0) * k as i32 / LATENCY_BLOCKS as i32;
+        (
+            edge(i),
+            y0,
+            (edge(i + 1) - edge(i)).max(1),
+            (y1 - y0).max(1),
+        )
+    }
+
+    /// Block values (true = white) for `code`.
+    pub fn blocks(code: u16) -> [bool; LATENCY_BLOCKS] {
+        let mut blocks = [false; LATENCY_BLOCKS];
+        blocks[0] = true;
+        for bit in 0..LATENCY_BITS as usize {
+            blocks[1 + bit] = code >> (LATENCY_BITS as usize - 1 - bit) & 1 == 1;
+        }
+        blocks
+    }
+
+    /// Decode the row from a tightly packed RGBA8 frame (rows bottom-up, as read from GL) by
+    /// sampling the centre of each block. `None` when the references are too close to tell
+    /// white from black (no pattern in view).
+    pub fn decode_rgba(&self, rgba: &[u8], width: i32, height: i32) -> Option<u16> {
+        let luma = |i: usize| -> Option<f32> {
+            let (x, y, w, h) = self.block_rect(i, width, height);
+            let (cx, cy) = (x + w / 2, y + h / 2);
+            if cx < 0 || cy < 0 || cx >= width || cy >= height {
+                return None;
+            }
+            let p = ((cy * width + cx) * 4) as usize;
+            let px = rgba.get(p..p + 3)?;
+            Some(0.2126 * px[0] as f32 + 0.7152 * px[1] as f32 + 0.0722 * px[2] as f32)
+        };
+        let white = luma(0)?;
+        let black = luma(LATENCY_BLOCKS - 1)?;
+        if white - black < 64.0 {
+            return None;
+        }
+        let threshold = (white + black) * 0.5;
+        let mut code = 0u16;
+        for bit in 0..LATENCY_BITS as usize {
+            code = code << 1 | (luma(1 + bit)? > threshold) as u16;
+        }
+        Some(code)
+    }
+}
+
+/// One measurement.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencyReport {
+    pub code: u16,
+    /// Stamps emitted since this one (0 = the probe saw this frame's stamp).
+    pub frames: u64,
+    pub elapsed: Duration,
+}
+
+impl LatencyReport {
+    pub fn ms(&self) -> f64 {
+        self.elapsed.as_secs_f64() * 1000.0
+    }
+}
+
+#[derive(Debug, Default)]
+struct ClockInner {
+    /// Sequence number of the next stamp.
+    next: u64,
+    /// `(sequence, time)` of recent stamps, oldest first.
+    stamps: VecDeque<(u64, Instant)>,
+}
+
+/// Stamp history shared by a [`LatencyStamp`] and its [`LatencyProbe`]s. Clones share state.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyClock {
+    inner: Arc<Mutex<ClockInner>>,
+}
+
+impl LatencyClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a stamp emitted now; returns its code.
+    pub fn stamp(&self) -> u16 {
+        let mut c = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let seq = c.next;
+        c.next += 1;
+        c.stamps.push_back((seq, Instant::now()));
+        if c.stamps.len() > CLOCK_HISTORY {
+            c.stamps.pop_front();
+        }
+        Self::code(seq)
+    }
+
+    fn code(seq: u64) -> u16 {
+        (seq % (1 << LATENCY_BITS)) as u16
+    }
+
+    /// Match `code` against the most recent stamp that carried it.
+    pub fn resolve(&self, code: u16) -> Option<LatencyReport> {
+        let c = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let &(seq, at) = c
+            .stamps
+            .iter()
+            .rev()
+            .find(|(seq, _)| Self::code(*seq) == code)?;
+        Some(LatencyReport {
+            code,
+            frames: c.next - 1 - seq,
+            elapsed: at.elapsed(),
+        })
+    }
+}
+
+/// Renders the current stamp (see the module docs) on a black frame.
+#[derive(Debug)]
+pub struct LatencyStamp {
+    pub pattern: LatencyPattern,
+    clock: LatencyClock,
+}
+
+impl LatencyStamp {
+    pub fn new(clock: LatencyClock, pattern: LatencyPattern) -> Self {
+        Self { pattern, clock }
+    }
+}
+
+impl NodeExecutor for LatencyStamp {
+    fn execute(&mut self, gl: &glow::Context, ctx: &NodeExecCtx) -> Result<(), EngineError> {
+        let blocks = LatencyPattern::blocks(self.clock.stamp());
+        unsafe {
+            // Target is already cleared to black; paint the white blocks.
+            gl.enable(glow::SCISSOR_TEST);
+            gl.clear_color(1.0, 1.0, 1.0, 1.0);
+            for (i, _) in blocks.iter().enumerate().filter(|(_, white)| **white) {
+                let (x, y, w, h) = self.pattern.block_rect(i, ctx.width, ctx.height);
+                gl.scissor(x, y, w, h);
+                gl.clear(glow::COLOR_BUFFER_BIT);
+            }
+            gl.disable(glow::SCISSOR_TEST);
+        }
+        Ok(())
+    }
+}
+
+/// Decodes stamps from the routed output and keeps recent measurements.
+#[derive(Debug)]
+pub struct LatencyProbe {
+    pub pattern: LatencyPattern,
+    clock: LatencyClock,
+    buf: Vec<u8>,
+    last: Option<LatencyReport>,
+    recent: VecDeque<LatencyReport>,
+}
+
+impl LatencyProbe {
+    pub fn new(clock: LatencyClock, pattern: LatencyPattern) -> Self {
+        Self {
+            pattern,
+            clock,
+            buf: Vec::new(),
+            last: None,
+            recent: VecDeque::new(),
+        }
+    }
+
+    /// Decode a CPU frame (RGBA8, rows bottom-up) and record the measurement, if any.
+    pub fn observe_rgba(&mut self, rgba: &[u8], width: i32, height: i32) -> Option<LatencyReport> {
+        let code = self.pattern.decode_rgba(rgba, width, height)?;
+        let report = self.clock.resolve(code)?;
+        self.last = Some(report);
+        self.recent.push_back(report);
+        if self.recent.len() > 120 {
+            self.recent.pop_front();
+        }
+        Some(report)
+    }
+
+    /// The latest measurement.
+    pub fn last(&self) -> Option<LatencyReport> {
+        self.last
+    }
+
+    /// Mean frames and milliseconds over the last (up to) 120 measurements.
+    pub fn average(&self) -> Option<(f64, f64)> {
+        let n = self.recent.len();
+        if n == 0 {
+            return None;
+        }
+        let frames = self.recent.iter().map(|r| r.frames as f64).sum::<f64>() / n as f64;
+        let ms = self.recent.iter().map(|r| r.ms()).sum::<f64>() / n as f64;
+        Some((frames, ms))
+    }
+}
+
+impl OutputSink for LatencyProbe {
+    fn consume(&mut self, gl: &glow::Context, out: &ExecOutput) {
+        let len = (out.width.max(0) * out.height.max(0) * 4) as usize;
+        let mut buf = std::mem::take(&mut self.buf);
+        buf.resize(len, 0);
+        unsafe {
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(out.fbo));
+            gl.read_pixels(
+                0,
+                0,
+                out.width,
+                out.height,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut buf),
+            );
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        }
+        self.observe_rgba(&buf, out.width, out.height);
+        self.buf = buf;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paint(pattern: &LatencyPattern, code: u16, w: i32, h: i32) -> Vec<u8> {
+        let mut rgba = vec![0u8; (w * h * 4) as usize];
+        for (i, white) in LatencyPattern::blocks(code).iter().enumerate() {
+            let (bx, by, bw, bh) = pattern.block_rect(i, w, h);
+            for y in by..by + bh {
+                for x in bx..bx + bw {
+                    // A dim, low-contrast capture still decodes.
+                    let v = if *white { 180 } else { 40 };
+                    let p = ((y * w + x) * 4) as usize;
+                    rgba[p..p + 4].copy_from_slice(&[v, v, v, 255]);
+                }
+            }
+        }
+        rgba
+    }
+
+    #[test]
+    fn codes_roundtrip_through_the_pattern() {
+        let pattern = LatencyPattern::default();
+        for code in [0, 1, 0x2AAA, (1 << LATENCY_BITS) - 1] {
+            let rgba = paint(&pattern, code, 160, 90);
+            assert_eq!(pattern.decode_rgba(&rgba, 160, 90), Some(code));
+        }
+        assert_eq!(pattern.decode_rgba(&vec![0; 160 * 90 * 4], 160, 90), None);
+    }
+
+    #[test]
+    fn probe_reports_frames_since_the_stamp() {
+        let clock = LatencyClock::new();
+        let pattern = LatencyPattern::default();
+        let mut probe = LatencyProbe::new(clock.clone(), pattern);
+        let seen = clock.stamp();
+        clock.stamp();
+        clock.stamp();
+        let report = probe
+            .observe_rgba(&paint(&pattern, seen, 64, 64), 64, 64)
+            .unwrap();
+        assert_eq!((report.code, report.frames), (seen, 2));
+        assert_eq!(probe.average().unwrap().0, 2.0);
+    }
+}
@@ -9,14 +9,95 @@
 #![allow(clippy::missing_safety_doc)]
 
 use glow::HasContext;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 
-use scheng_graph::{Edge, Graph, NodeClass, NodeId, NodeKind, Plan, PortDir, PortId};
+use scheng_graph::{
+    BuiltChain, Edge, Endpoint, Graph, Node, NodeId, NodeKind, Plan, PortDir, PortId,
+};
+use scheng_core::events::{EngineEvent, LogLevel};
+#[cfg(feature = "video")]
 use scheng_input_video as input_video;
-use scheng_runtime::{standard_op_for, MixerOp, StandardOp};
+use geometry::GpuMesh;
+use hooks::NodeHooks;
+use scheng_runtime::{
+    declare_shader_params, scan_shader_params, standard_op_for, DitherMode, FilterOp,
+    GroupControls, InputAdjust, MixerOp, ParamRegistry, StandardOp,
+};
+use scheng_runtime::runtime_contract::{standard_uniform, FRAME_BLOCK_BINDING};
 
 pub use scheng_core::EngineError;
+
+pub mod alpha;
+pub mod analysis;
+mod audio_vis;
+pub mod banks;
+pub mod bloom;
+pub mod buffered;
+pub mod buffers;
+pub mod caps;
+pub mod clear;
+pub mod compute;
+pub mod executor;
+pub mod export;
+pub mod geometry;
+pub mod guard;
+pub mod hooks;
+pub mod latency;
+pub mod multiview;
+pub mod particles;
+pub mod present;
+pub mod probe;
+pub mod program_cache;
+pub mod props;
+pub mod recorder;
+pub mod scopes;
+pub mod shared_surface;
+pub mod stats;
+pub mod taps;
+pub mod temporal;
+pub mod trim;
+pub mod uniforms;
+pub mod validate;
+#[cfg(feature = "video")]
+mod video_blend;
+pub mod watchdog;
+pub use alpha::AlphaPolicy;
+pub use banks::{shader_slot_key, ShaderBank};
+pub use bloom::BloomChain;
+pub use buffered::{BufferedFrame, TripleBufferSink, TripleBufferStats};
+pub use buffers::BufferSet;
+pub use caps::{GlProfile, RuntimeCaps};
+pub use clear::ClearMode;
+pub use compute::{compute_supported, ComputeExecutor};
+pub use executor::{CpuImageExecutor, NodeExecCtx, NodeExecutor};
+pub use export::{export_sequence, ExportConfig, ExportProgress, ExportSummary, StillFormat};
+pub use geometry::{Geometry, Mesh, Primitive, Vertex};
+pub use guard::GuardedSink;
+pub use hooks::{NodeHook, NodeHookCtx};
+pub use latency::{LatencyClock, LatencyPattern, LatencyProbe, LatencyReport, LatencyStamp};
+pub use multiview::{Multiview, MultiviewConfig, MultiviewLayout, MultiviewTile};
+pub use particles::ParticleSystem;
+pub use present::{
+    present, PresentConfig, PresentFilter, PresentSink, Presenter, PresenterConfig, Rotation, ScaleMode,
+    SrgbMode,
+};
+pub use program_cache::{ProgramBinaryApi, ProgramDiskCache, ProgramDiskCacheStats};
+pub use props::NodeParams;
+pub use recorder::{
+    name_program_and_clean, route_program_and_clean, RecordControl, RecordFormat, RecorderConfig,
+    RecorderSink, TakeSummary, OUTPUT_CLEAN, OUTPUT_PROGRAM,
+};
+pub use stats::{RuntimeStats, TargetGroup, TextureFormat};
+pub use scopes::{ScopeConfig, ScopeData, ScopeSink};
+pub use shared_surface::{DmaBuf, DmaBufPlane, SharedSurface, SharedSurfaceImporter};
+pub use trim::TrimSink;
+pub use uniforms::{
+    apply_node_uniforms, apply_standard_uniforms, frame_block_bytes, node_seed_f32,
+    NodeUniformValues, StandardUniformLocations,
+};
+pub use validate::{validate_props, Diagnostic, DiagnosticKind};
+pub use watchdog::{Watchdog, WatchdogConfig};
 #[derive(Debug, Clone)]
 pub struct ShaderSource {
     pub vert: String,
@@ -34,6 +115,7 @@ pub struct RenderTarget {
     pub h: i32,
 }
 
+#[cfg(feature = "video")]
 struct VideoNodeState {
     dec: input_video::VideoDecoder,
     tex: glow::NativeTexture,
@@ -43,8 +125,23 @@ struct VideoNodeState {
     fps: f32,
     /// Last timeline frame index we uploaded into the texture, derived from FrameCtx::time.
     last_frame_index: i64,
+    /// The decoder panicked; the node keeps showing its last frame.
+    failed: bool,
+    /// Previous frame and blend target with `FrameInterpolation::Blend`.
+    blend: Option<video_blend::FrameBlend>,
+}
+
+#[cfg(feature = "video")]
+impl VideoNodeState {
+    /// The texture downstream nodes sample: the blend target, or the latest upload.
+    fn output(&self) -> (glow::NativeTexture, i32, i32) {
+        self.blend
+            .as_ref()
+            .map_or((self.tex, self.w, self.h), |b| b.output())
+    }
 }
 
+#[cfg(feature = "video")]
 impl std::fmt::Debug for VideoNodeState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // `VideoDecoder` owns OS/process resources and does not implement Debug.
@@ -55,6 +152,7 @@ impl std::fmt::Debug for VideoNodeState {
             .field("h", &self.h)
             .field("fps", &self.fps)
             .field("last_frame_index", &self.last_frame_index)
+            .field("blend", &self.blend)
             .field("dec", &"<video decoder>")
             .finish()
     }
@@ -65,22 +163,133 @@ impl std::fmt::Debug for VideoNodeState {
 struct PingPong {
     curr: RenderTarget,
     prev: RenderTarget,
+    /// MRT attachments 1.. (output ports `out1`..), swapped together with `curr` / `prev`.
+    /// Each texture is also attached to the main FBO at `COLOR_ATTACHMENT<n>`; its own FBO is
+    /// only for reading it back as an `ExecOutput`.
+    curr_extra: Vec<RenderTarget>,
+    prev_extra: Vec<RenderTarget>,
+    /// `prev` has not been drawn since creation or the last reallocation (see [`clear`]).
+    fresh: bool,
 }
 
 impl PingPong {
+    unsafe fn new(gl: &glow::Context, w: i32, h: i32) -> Result<Self, EngineError> {
+        Ok(Self {
+            curr: create_render_target(gl, w, h)?,
+            prev: create_render_target(gl, w, h)?,
+            curr_extra: Vec::new(),
+            prev_extra: Vec::new(),
+            fresh: true,
+        })
+    }
+
     unsafe fn ensure_size(&mut self, gl: &glow::Context, w: i32, h: i32) {
-        if self.curr.w != w || self.curr.h != h {
-            self.curr.resize(gl, w, h);
+        let all = [&mut self.curr, &mut self.prev]
+            .into_iter()
+            .chain(self.curr_extra.iter_mut())
+            .chain(self.prev_extra.iter_mut());
+        for t in all {
+            if t.w != w || t.h != h {
+                t.resize(gl, w, h);
+                self.fresh = true;
+            }
+        }
+    }
+
+    /// Make sure both FBOs carry `extra` MRT attachments beyond attachment 0.
+    unsafe fn ensure_attachments(
+        &mut self,
+        gl: &glow::Context,
+        extra: usize,
+    ) -> Result<(), EngineError> {
+        if self.curr_extra.len() == extra {
+            return Ok(());
+        }
+        let (w, h) = (self.curr.w, self.curr.h);
+        self.fresh = true;
+        for side in [&mut self.curr_extra, &mut self.prev_extra] {
+            for t in side.drain(..) {
+                gl.delete_framebuffer(t.fbo);
+                gl.delete_texture(t.tex);
+            }
+            for _ in 0..extra {
+                side.push(create_render_target(gl, w, h)?);
+            }
         }
-        if self.prev.w != w || self.prev.h != h {
-            self.prev.resize(gl, w, h);
+        for (main, extras) in [(&self.curr, &self.curr_extra), (&self.prev, &self.prev_extra)] {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(main.fbo));
+            let mut buffers = vec![glow::COLOR_ATTACHMENT0];
+            for i in 1..MAX_PASS_OUTPUTS as u32 {
+                let tex = extras.get(i as usize - 1).map(|t| t.tex);
+                let attachment = glow::COLOR_ATTACHMENT0 + i;
+                gl.framebuffer_texture_2d(glow::FRAMEBUFFER, attachment, glow::TEXTURE_2D, tex, 0);
+                if tex.is_some() {
+                    buffers.push(attachment);
+                }
+            }
+            gl.draw_buffers(&buffers);
         }
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        Ok(())
     }
 
     /// Swap current/previous targets at the start of a new frame render for this node.
     fn swap(&mut self) {
         core::mem::swap(&mut self.curr, &mut self.prev);
+        core::mem::swap(&mut self.curr_extra, &mut self.prev_extra);
+    }
+
+    /// Target for output `index` (0 = `out`, 1.. = MRT attachments).
+    fn output(&self, index: usize, prev: bool) -> Option<&RenderTarget> {
+        let (main, extra) = if prev {
+            (&self.prev, &self.prev_extra)
+        } else {
+            (&self.curr, &self.curr_extra)
+        };
+        match index {
+            0 => Some(main),
+            n => extra.get(n - 1),
+        }
+    }
+
+    fn account(&self, stats: &mut stats::RuntimeStats) {
+        let all = [&self.curr, &self.prev]
+            .into_iter()
+            .chain(&self.curr_extra)
+            .chain(&self.prev_extra);
+        for t in all {
+            stats.add_target(t.w, t.h, stats::TextureFormat::Rgba8);
+        }
     }
+
+    unsafe fn destroy(self, gl: &glow::Context) {
+        let all = [self.curr, self.prev]
+            .into_iter()
+            .chain(self.curr_extra)
+            .chain(self.prev_extra);
+        for t in all {
+            gl.delete_framebuffer(t.fbo);
+            gl.delete_texture(t.tex);
+        }
+    }
+}
+
+/// Most colour outputs a pass may declare (`out`, `out1`..`out3`); see
+/// `scheng_graph::Graph::add_node_with_outputs`.
+const MAX_PASS_OUTPUTS: usize = scheng_graph::MAX_NODE_OUTPUTS;
+
+/// Position of `output` among its node's output ports (0 for `out`).
+fn output_index(graph: &Graph, output: scheng_graph::Endpoint) -> usize {
+    graph
+        .node(output.node)
+        .map(|n| {
+            n.ports
+                .iter()
+                .filter(|p| p.dir == PortDir::Out)
+                .position(|p| p.id == output.port)
+                .unwrap_or(0)
+        })
+        .unwrap_or(0)
 }
 
 impl RenderTarget {
@@ -208,11 +417,21 @@ unsafe fn create_host_texture(gl: &glow::Context, w: i32, h: i32) -> glow::Nativ
     tex
 }
 
-
 pub unsafe fn compile_program(
     gl: &glow::Context,
     vert_src: &str,
     frag_src: &str,
+) -> Result<glow::NativeProgram, EngineError> {
+    compile_program_with(gl, vert_src, frag_src, |_| {})
+}
+
+/// `compile_program` with a hook that runs on the program object just before linking
+/// (e.g. to set `GL_PROGRAM_BINARY_RETRIEVABLE_HINT`).
+pub(crate) unsafe fn compile_program_with(
+    gl: &glow::Context,
+    vert_src: &str,
+    frag_src: &str,
+    before_link: impl FnOnce(glow::NativeProgram),
 ) -> Result<glow::NativeProgram, EngineError> {
     let vs = gl
         .create_shader(glow::VERTEX_SHADER)
@@ -242,6 +461,7 @@ pub unsafe fn compile_program(
         .map_err(|e| EngineError::GlCreate(format!("create_program failed: {e:?}")))?;
     gl.attach_shader(program, vs);
     gl.attach_shader(program, fs);
+    before_link(program);
     gl.link_program(program);
 
     gl.detach_shader(program, vs);
@@ -295,12 +515,70 @@ impl ShaderProgram {
 // -------------------------------------------------------------------------------------------------
 
 /// Per-frame context supplied by the host (pull-based runtime).
+///
+/// Build with [`FrameCtx::new`] so `aspect` is filled in; `dt` and `seed` default to 0.
+/// Shaders see them as `uDeltaTime` (also `iTimeDelta`), `uAspect` and `uSeed`.
 #[derive(Clone, Copy, Debug)]
 pub struct FrameCtx {
     pub width: i32,
     pub height: i32,
     pub time: f32,
     pub frame: u64,
+    /// Seconds since the previous frame (0 on the first frame).
+    pub dt: f32,
+    /// `width / height` of the host's output (not of a scaled internal resolution).
+    pub aspect: f32,
+    /// Per-run seed, constant for the life of the host. `uSeed` is this mapped to `[0, 1)`.
+    pub seed: u32,
+}
+
+impl FrameCtx {
+    pub fn new(width: i32, height: i32, time: f32, frame: u64) -> Self {
+        Self {
+            width,
+            height,
+            time,
+            frame,
+            dt: 0.0,
+            aspect: width.max(1) as f32 / height.max(1) as f32,
+            seed: 0,
+        }
+    }
+
+    pub fn with_dt(mut self, dt: f32) -> Self {
+        self.dt = dt;
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The next frame at `time`: `dt` is the (non-negative) step from this frame, `frame` + 1.
+    pub fn next(&self, width: i32, height: i32, time: f32) -> Self {
+        Self::new(width, height, time, self.frame + 1)
+            .with_dt((time - self.time).max(0.0))
+            .with_seed(self.seed)
+    }
+
+    /// `uSeed`: the seed mapped to `[0, 1)`.
+    pub fn seed_f32(&self) -> f32 {
+        (self.seed as f64 / 4_294_967_296.0) as f32
+    }
+}
+
+/// A seed for [`FrameCtx::seed`] that differs between runs (clock and process id).
+pub fn run_seed() -> u32 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    let mut x = nanos ^ ((std::process::id() as u64) << 32);
+    // splitmix64 finalizer so nearby clocks give unrelated seeds.
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (x ^ (x >> 31)) as u32
 }
 
 /// Runtime-only properties keyed by graph NodeId.
@@ -311,6 +589,26 @@ pub struct NodeProps {
     pub shader_sources: HashMap<NodeId, ShaderSource>,
     /// Parameters for 2-input mixers (e.g., Crossfade).
     pub mixer_params: HashMap<NodeId, scheng_runtime::MixerParams>,
+    /// Parameters for the Add and Multiply mixers (unit gains when absent).
+    pub blend_params: HashMap<NodeId, scheng_runtime::BlendParams>,
+    /// Parameters for `NodeKind::Particles` nodes (defaults apply when absent). Changing
+    /// `count` rebuilds the simulation.
+    pub particle_params: HashMap<NodeId, scheng_runtime::ParticleParams>,
+    /// Parameters for `NodeKind::AudioWaveform` / `NodeKind::AudioSpectrum` nodes (defaults
+    /// apply when absent).
+    pub audio_vis_params: HashMap<NodeId, scheng_runtime::AudioVisParams>,
+    /// The host's latest block of mono audio samples (-1..1, oldest first), drawn by every
+    /// audio visualizer node. Replace it each frame from the audio callback; 1024–4096
+    /// samples give a useful spectrum.
+    pub audio_samples: Vec<f32>,
+    /// What each `GeometryPass` node draws (a unit quad when absent). Changing an entry
+    /// re-uploads the geometry on the next frame.
+    pub geometry: HashMap<NodeId, Geometry>,
+    /// Multipass effects whose passes sample each other as `iBufferA`..`iBufferD` (see the
+    /// [`buffers`] module). A node should belong to at most one set; the first match wins.
+    pub buffer_sets: Vec<BufferSet>,
+    /// Parameters for KeyMix nodes (default chroma key on green when absent).
+    pub key_params: HashMap<NodeId, scheng_runtime::KeyMixParams>,
     /// Parameters for matrix mixers (e.g., MatrixMix4).
     pub matrix_params: HashMap<NodeId, scheng_runtime::MatrixMixParams>,
     /// Optional explicit names for `NodeKind::PixelsOut` nodes (Step 5).
@@ -319,10 +617,15 @@ pub struct NodeProps {
     /// `ExecOutputs.named`. Unnamed PixelsOut nodes are ignored (explicit-only policy).
     pub output_names: HashMap<NodeId, String>,
     pub texture_inputs: HashMap<NodeId, glow::NativeTexture>,
+    /// IOSurfaces / DMA-BUFs bound zero-copy as `TextureInputPass` outputs (see
+    /// [`shared_surface`]). An entry in `texture_inputs` for the same node wins.
+    pub shared_surfaces: HashMap<NodeId, SharedSurface>,
     /// Per-node video decode source configuration loaded from a JSON file (see `scheng-input-video`).
+    #[cfg(feature = "video")]
     pub video_decode_json: std::collections::HashMap<scheng_graph::NodeId, std::path::PathBuf>,
 
     /// Per-node video decode source configuration provided directly.
+    #[cfg(feature = "video")]
     pub video_decode_cfg: std::collections::HashMap<scheng_graph::NodeId, input_video::VideoConfig>,
 
     /// Per-node arbitrary f32 uniforms injected each frame.
@@ -331,6 +634,70 @@ pub struct NodeProps {
     /// Uniforms not present in the shader are silently ignored.
     /// Hotpatchable without recompile — same as mixer_params.
     pub custom_uniforms: HashMap<NodeId, HashMap<String, f32>>,
+    /// Graph-wide f32 uniforms (master brightness, global speed, ...) offered to every pass.
+    ///
+    /// Globals never replace a value set for the node: names in its `custom_uniforms` or its
+    /// `@param` controls win, so per-node trims still work, and standard uniform names (`uTime`,
+    /// `u_resolution`, ..., see `runtime_contract::STANDARD_UNIFORMS`) are skipped. Other names
+    /// the runtime sets for a node kind (a mixer's `uMix`) are not checked and would be
+    /// overridden. Like `custom_uniforms`, names a shader does not declare are ignored.
+    pub global_uniforms: HashMap<String, f32>,
+    /// Parameters for `NodeKind::MotionDetect` nodes (defaults apply when absent).
+    pub motion_params: HashMap<NodeId, scheng_runtime::MotionParams>,
+    /// Parameters for `NodeKind::OpticalFlow` nodes.
+    pub flow_params: HashMap<NodeId, scheng_runtime::FlowParams>,
+    /// Parameters for `NodeKind::FlowDisplace` nodes.
+    pub displace_params: HashMap<NodeId, scheng_runtime::DisplaceParams>,
+    /// Parameters for `NodeKind::Bloom` nodes (defaults apply when absent).
+    pub bloom_params: HashMap<NodeId, scheng_runtime::BloomParams>,
+    /// Parameters for `NodeKind::Pixelate` nodes.
+    pub pixelate_params: HashMap<NodeId, scheng_runtime::PixelateParams>,
+    /// Parameters for `NodeKind::Posterize` nodes.
+    pub posterize_params: HashMap<NodeId, scheng_runtime::PosterizeParams>,
+    /// Parameters for `NodeKind::Dither` nodes.
+    pub dither_params: HashMap<NodeId, scheng_runtime::DitherParams>,
+    /// Temporal accumulation for `PixelsOut` nodes (see [`temporal`]); outputs without an
+    /// entry are not smoothed.
+    pub temporal_params: HashMap<NodeId, scheng_runtime::TemporalParams>,
+    /// Alpha encoding of `PixelsOut` nodes (see [`alpha`]); outputs without an entry pass the
+    /// rendered alpha through.
+    pub output_alpha: HashMap<NodeId, AlphaPolicy>,
+    /// How render nodes' targets start each frame (see [`clear`]); opaque black when absent.
+    pub clear_modes: HashMap<NodeId, ClearMode>,
+    /// Shaders render nodes switch between live (see [`banks`]); a bank takes precedence over
+    /// `shader_sources`.
+    pub shader_banks: HashMap<NodeId, ShaderBank>,
+}
+
+impl NodeProps {
+    /// Props pre-populated from a built `scheng_graph::Chain` (shader sources + output names).
+    pub fn from_chain(built: &BuiltChain<ShaderSource>) -> Self {
+        let mut props = Self::default();
+        for (id, shader) in &built.shaders {
+            props.shader_sources.insert(*id, shader.clone());
+        }
+        for (id, name) in &built.output_names {
+            props.output_names.insert(*id, name.clone());
+        }
+        props
+    }
+
+    /// Apply a scene's shader swaps and uniform overrides in place.
+    ///
+    /// Merges [`scene_overlay`](Self::scene_overlay) into these props, so what the scene changes
+    /// stays changed after the next scene is recalled. Prefer keeping the base props and
+    /// rendering `base.merged(&overlay)` (see the [`props`] module). Every node is resolved and
+    /// every file read before anything changes, so on `Err` the props are untouched.
+    pub fn apply_scene_overrides(
+        &mut self,
+        graph: &Graph,
+        scene: &scheng_runtime::SceneDef,
+        shader_dir: &std::path::Path,
+    ) -> Result<(), String> {
+        let overlay = self.scene_overlay(graph, scene, shader_dir)?;
+        self.merge(&overlay);
+        Ok(())
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -345,6 +712,41 @@ struct ProgramEntry {
     key: ProgramKey,
 }
 
+/// Execution options stored on `RuntimeState` (see `RuntimeState::set_options`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExecOptions {
+    /// Wrap the frame and each executed node in `KHR_debug` groups (labelled with node kind, id
+    /// and shader origin) so RenderDoc / apitrace captures are readable. No-op when unsupported.
+    pub debug_groups: bool,
+    /// After the plan executes, leave GL in a known state: framebuffer 0, no program / VAO,
+    /// input texture units unbound and `TEXTURE0` active.
+    pub restore_gl_state: bool,
+    /// Check `glGetError` after each node's GL work and fail the frame with `EngineError::Gl`
+    /// naming the node and operation. Costs a pipeline sync per check; debugging only.
+    pub check_gl_errors: bool,
+    /// Run [`validate_props`] before each frame and fail with `EngineError::Other` listing the
+    /// diagnostics instead of rendering. Missing shaders on nodes with an executor are allowed.
+    pub strict_props: bool,
+}
+
+/// Result of `RuntimeState::precompile`.
+#[derive(Debug, Default)]
+pub struct PrecompileReport {
+    /// Render nodes whose programs are compiled and bound, in `NodeId` order.
+    pub compiled: Vec<NodeId>,
+    /// Render nodes whose shader could not be resolved or compiled.
+    pub failures: Vec<(NodeId, EngineError)>,
+    /// Failures compiling built-in helper passes (indicates a driver problem).
+    pub helper_failures: Vec<EngineError>,
+}
+
+impl PrecompileReport {
+    /// True if every program compiled.
+    pub fn is_ok(&self) -> bool {
+        self.failures.is_empty() && self.helper_failures.is_empty()
+    }
+}
+
 /// Mutable runtime state that can persist across frames.
 ///
 /// The host owns the GL context lifecycle. We keep GL object ids here and expose explicit
@@ -354,8 +756,73 @@ pub struct RuntimeState {
     pub fs_tri: FullscreenTriangle,
     programs: HashMap<NodeId, ProgramEntry>,
     program_cache: HashMap<ProgramKey, glow::NativeProgram>,
+    /// Standard uniforms each cached program declares (reflected on first use).
+    uniform_reflection: HashMap<ProgramKey, StandardUniformLocations>,
+    /// Uniform buffer behind the standard block (see [`uniforms`]).
+    frame_block: uniforms::FrameBlock,
     targets: HashMap<NodeId, PingPong>,
+    #[cfg(feature = "video")]
     video_nodes: HashMap<NodeId, VideoNodeState>,
+    /// Previous-frame input copies for two-frame analysis nodes (MotionDetect, OpticalFlow).
+    prev_inputs: HashMap<NodeId, analysis::PrevInput>,
+    motion_nodes: HashMap<NodeId, analysis::MotionState>,
+    /// Values published by analysis nodes (and anything else the host stores here).
+    params: ParamRegistry,
+    options: ExecOptions,
+    /// Set by `invalidate`; the next `execute_plan` recreates context-bound objects first.
+    invalidated: bool,
+    /// Optional on-disk program binary cache consulted on in-memory cache misses.
+    disk_cache: Option<ProgramDiskCache>,
+    /// Fraction of the host frame size used for internal targets (`0 < scale <= 1`).
+    render_scale: f32,
+    /// Nodes rendered as a passthrough of their first input.
+    bypassed: HashSet<NodeId>,
+    /// Program keys of the shader banks whose slots were compiled (see [`banks`]).
+    warmed_banks: HashMap<NodeId, Vec<ProgramKey>>,
+    /// Host pre/post-render callbacks (see [`hooks`]).
+    hooks: HashMap<NodeId, NodeHooks>,
+    /// Nodes implemented in Rust instead of a shader (see [`executor`]).
+    executors: HashMap<NodeId, Box<dyn NodeExecutor>>,
+    /// Uploaded geometry for `GeometryPass` nodes.
+    meshes: HashMap<NodeId, GpuMesh>,
+    /// Simulation state for `Particles` nodes.
+    particles: HashMap<NodeId, ParticleSystem>,
+    /// Mip chains for `Bloom` nodes.
+    blooms: HashMap<NodeId, BloomChain>,
+    /// Programs, data textures and spectrum history for the audio visualizer nodes.
+    audio_visuals: HashMap<NodeId, audio_vis::AudioVisual>,
+    /// Accumulated history for `PixelsOut` nodes with `temporal_params`.
+    temporal: HashMap<NodeId, temporal::TemporalState>,
+    /// Converted frames of `PixelsOut` nodes with an `output_alpha` policy.
+    alpha_outputs: HashMap<NodeId, alpha::AlphaOutput>,
+    /// Fullscreen triangles with remapped UVs for cropped branches, with their crop rect.
+    crop_tris: HashMap<NodeId, ([f32; 4], FullscreenTriangle)>,
+    /// Imported shared surfaces for `TextureInputPass` nodes.
+    shared: shared_surface::SharedInputs,
+    /// 1x1 textures bound to unconnected inputs, keyed by RGBA8 colour.
+    solid_textures: HashMap<[u8; 4], glow::NativeTexture>,
+    /// Threshold maps for `Dither` nodes.
+    dither_textures: HashMap<DitherMode, glow::NativeTexture>,
+    /// Limits of the context, probed on creation and after `invalidate`.
+    caps: RuntimeCaps,
+    /// Panics caught while polling inputs (see [`guard`]) and skipped `@param` annotations,
+    /// until `take_events`.
+    events: Vec<EngineEvent>,
+    /// `@param` controls of each node's current fragment shader.
+    shader_params: HashMap<NodeId, ShaderParams>,
+    /// Edges exposed as `debug:` outputs (see [`taps`]).
+    taps: taps::EdgeTaps,
+    /// Outputs of the last frame for `probe_pixel` (see [`probe`]).
+    probes: probe::OutputProbes,
+    /// Current shader source of each node and how often it changed (`uReloadCount`).
+    reloads: HashMap<NodeId, (ProgramKey, u32)>,
+}
+
+/// `(uniform, registry key)` pairs scanned from the source behind `key`.
+#[derive(Debug)]
+struct ShaderParams {
+    key: ProgramKey,
+    params: Vec<(String, String)>,
 }
 
 impl RuntimeState {
@@ -365,11 +832,226 @@ impl RuntimeState {
             fs_tri: FullscreenTriangle::new(gl)?,
             programs: HashMap::new(),
             program_cache: HashMap::new(),
+            uniform_reflection: HashMap::new(),
+            frame_block: uniforms::FrameBlock::default(),
             targets: HashMap::new(),
+            #[cfg(feature = "video")]
             video_nodes: HashMap::new(),
+            prev_inputs: HashMap::new(),
+            motion_nodes: HashMap::new(),
+            params: ParamRegistry::new(),
+            options: ExecOptions::default(),
+            invalidated: false,
+            disk_cache: None,
+            render_scale: 1.0,
+            bypassed: HashSet::new(),
+            warmed_banks: HashMap::new(),
+            hooks: HashMap::new(),
+            executors: HashMap::new(),
+            meshes: HashMap::new(),
+            particles: HashMap::new(),
+            blooms: HashMap::new(),
+            audio_visuals: HashMap::new(),
+            temporal: HashMap::new(),
+            alpha_outputs: HashMap::new(),
+            crop_tris: HashMap::new(),
+            shared: shared_surface::SharedInputs::default(),
+            solid_textures: HashMap::new(),
+            dither_textures: HashMap::new(),
+            caps: RuntimeCaps::probe(gl),
+            events: Vec::new(),
+            shader_params: HashMap::new(),
+            taps: taps::EdgeTaps::default(),
+            probes: probe::OutputProbes::default(),
+            reloads: HashMap::new(),
+        })
+    }
+
+    /// Forget every GL object after the context was lost or recreated.
+    ///
+    /// No GL calls are made (the old handles are meaningless in the new context). Programs,
+    /// render targets and analysis state are rebuilt lazily by the next `execute_plan`; the
+    /// fullscreen triangle and video textures are recreated there too, with video decoders kept
+    /// running and the next decoded frame uploaded. Parameters and options survive.
+    ///
+    /// Sinks and node executors own their GL resources and must be reset by the host.
+    pub fn invalidate(&mut self) {
+        self.program_cache.clear();
+        self.uniform_reflection.clear();
+        self.frame_block.invalidate();
+        self.programs.clear();
+        self.warmed_banks.clear();
+        self.targets.clear();
+        self.meshes.clear();
+        self.particles.clear();
+        self.blooms.clear();
+        self.audio_visuals.clear();
+        self.temporal.clear();
+        self.alpha_outputs.clear();
+        self.crop_tris.clear();
+        self.solid_textures.clear();
+        self.dither_textures.clear();
+        self.shared.invalidate();
+        self.taps.invalidate();
+        self.probes.invalidate();
+        self.prev_inputs.clear();
+        self.motion_nodes.clear();
+        self.invalidated = true;
+    }
+
+    /// A cached 1x1 texture of `rgba` (components quantized to 8 bits).
+    unsafe fn solid_texture(&mut self, gl: &glow::Context, rgba: [f32; 4]) -> glow::NativeTexture {
+        let texel = rgba.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        *self.solid_textures.entry(texel).or_insert_with(|| {
+            let tex = create_host_texture(gl, 1, 1);
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+            gl.tex_sub_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                0,
+                0,
+                1,
+                1,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelUnpackData::Slice(&texel),
+            );
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            tex
+        })
+    }
+
+    /// `ComponentPanicked` events for inputs disabled since the last call (see [`guard`]), and
+    /// warnings for malformed `@param` annotations.
+    pub fn take_events(&mut self) -> Vec<EngineEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// What the context supports (see [`caps`]). Check a patch up front with
+    /// `state.caps().check_plan(..)`.
+    pub fn caps(&self) -> &RuntimeCaps {
+        &self.caps
+    }
+
+    /// Count the GL objects this state owns and estimate their memory (see [`stats`]).
+    /// Cheap enough to call every frame.
+    pub fn stats(&self) -> RuntimeStats {
+        use stats::TextureFormat;
+
+        let mut stats = RuntimeStats {
+            programs: self.program_cache.len(),
+            ..RuntimeStats::default()
+        };
+        for pp in self.targets.values() {
+            pp.account(&mut stats);
+        }
+        #[cfg(feature = "video")]
+        for vn in self.video_nodes.values() {
+            stats.add_texture(vn.w, vn.h, TextureFormat::Rgba8);
+            if let Some(blend) = &vn.blend {
+                blend.account(&mut stats);
+            }
+            stats.video_decoders += 1;
+            stats.video_buffer_bytes += vn.dec.buffer_bytes();
+        }
+        for pi in self.prev_inputs.values() {
+            pi.account(&mut stats);
+        }
+        for ms in self.motion_nodes.values() {
+            ms.account(&mut stats);
+        }
+        for ps in self.particles.values() {
+            ps.account(&mut stats);
+        }
+        for chain in self.blooms.values() {
+            chain.account(&mut stats);
+        }
+        for vis in self.audio_visuals.values() {
+            vis.account(&mut stats);
+        }
+        for ts in self.temporal.values() {
+            ts.account(&mut stats);
+        }
+        for ao in self.alpha_outputs.values() {
+            ao.account(&mut stats);
+        }
+        self.shared.account(&mut stats);
+        self.taps.account(&mut stats);
+        for _ in self.solid_textures.values() {
+            stats.add_texture(1, 1, TextureFormat::Rgba8);
+        }
+        for mode in self.dither_textures.keys() {
+            let n = mode.map_size() as i32;
+            stats.add_texture(n, n, TextureFormat::R8);
+        }
+        stats.finish()
+    }
+
+    /// The tiled threshold map for `mode` (R8, nearest, repeat), created on first use.
+    unsafe fn dither_texture(&mut self, gl: &glow::Context, mode: DitherMode) -> glow::NativeTexture {
+        *self.dither_textures.entry(mode).or_insert_with(|| {
+            let n = mode.map_size() as i32;
+            let texels: Vec<u8> = mode
+                .threshold_map()
+                .into_iter()
+                .map(|t| (t * 255.0).round() as u8)
+                .collect();
+            let tex = gl.create_texture().expect("create_texture");
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+            for (pname, v) in [
+                (glow::TEXTURE_MIN_FILTER, glow::NEAREST),
+                (glow::TEXTURE_MAG_FILTER, glow::NEAREST),
+                (glow::TEXTURE_WRAP_S, glow::REPEAT),
+                (glow::TEXTURE_WRAP_T, glow::REPEAT),
+            ] {
+                gl.tex_parameter_i32(glow::TEXTURE_2D, pname, v as i32);
+            }
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+            gl.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::R8 as i32,
+                n,
+                n,
+                0,
+                glow::RED,
+                glow::UNSIGNED_BYTE,
+                Some(&texels),
+            );
+            gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 4);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+            tex
         })
     }
 
+    /// True between `invalidate` and the next `execute_plan`.
+    pub fn is_invalidated(&self) -> bool {
+        self.invalidated
+    }
+
+    /// The standard uniforms `node`'s current program declares, once it has rendered.
+    pub fn standard_uniforms(&self, node: NodeId) -> Option<&StandardUniformLocations> {
+        let key = self.programs.get(&node)?.key;
+        self.uniform_reflection.get(&key)
+    }
+
+    /// Recreate the objects `invalidate` cannot drop lazily (called from `execute_plan`).
+    unsafe fn recreate_after_invalidate(&mut self, gl: &glow::Context) -> Result<(), EngineError> {
+        self.fs_tri = FullscreenTriangle::new(gl)?;
+        self.caps = RuntimeCaps::probe(gl);
+        #[cfg(feature = "video")]
+        for vn in self.video_nodes.values_mut() {
+            vn.tex = create_host_texture(gl, vn.w, vn.h);
+            if vn.blend.is_some() {
+                vn.blend = Some(video_blend::FrameBlend::new(gl, vn.w, vn.h)?);
+            }
+            // Force the next decoded frame into the new texture.
+            vn.last_frame_index = -1;
+        }
+        self.invalidated = false;
+        Ok(())
+    }
+
     /// Explicitly destroys GL objects owned by this state.
     ///
     /// Note: `RenderTarget` cleanup is intentionally conservative: we delete the FBO/texture
@@ -379,23 +1061,350 @@ impl RuntimeState {
         for (_, prog) in self.program_cache.drain() {
             gl.delete_program(prog);
         }
+        self.uniform_reflection.clear();
+        self.frame_block.destroy(gl);
         self.programs.clear();
+        self.shader_params.clear();
         // Targets
         for (_, pp) in self.targets.drain() {
-            gl.delete_framebuffer(pp.curr.fbo);
-            gl.delete_texture(pp.curr.tex);
-            gl.delete_framebuffer(pp.prev.fbo);
-            gl.delete_texture(pp.prev.tex);
+            pp.destroy(gl);
         }
 
         // Video decode nodes (textures + decoder processes)
+        #[cfg(feature = "video")]
         for (_, vn) in self.video_nodes.drain() {
             gl.delete_texture(vn.tex);
+            if let Some(blend) = vn.blend {
+                blend.destroy(gl);
+            }
             // `vn.dec` drops here, terminating ffmpeg reader thread.
         }
 
+        for (_, pi) in self.prev_inputs.drain() {
+            pi.destroy(gl);
+        }
+        for (_, ms) in self.motion_nodes.drain() {
+            ms.destroy(gl);
+        }
+        for (_, mut exec) in self.executors.drain() {
+            exec.destroy(gl);
+        }
+        for (_, mesh) in self.meshes.drain() {
+            mesh.destroy(gl);
+        }
+        for (_, ps) in self.particles.drain() {
+            ps.destroy(gl);
+        }
+        for (_, chain) in self.blooms.drain() {
+            chain.destroy(gl);
+        }
+        for (_, vis) in self.audio_visuals.drain() {
+            vis.destroy(gl);
+        }
+        for (_, ts) in self.temporal.drain() {
+            ts.destroy(gl);
+        }
+        for (_, ao) in self.alpha_outputs.drain() {
+            ao.destroy(gl);
+        }
+        for (_, (_, mut tri)) in self.crop_tris.drain() {
+            tri.destroy(gl);
+        }
+        for (_, tex) in self.solid_textures.drain() {
+            gl.delete_texture(tex);
+        }
+        for (_, tex) in self.dither_textures.drain() {
+            gl.delete_texture(tex);
+        }
+        self.shared.destroy(gl);
+        self.taps.destroy(gl);
+
         self.fs_tri.destroy(gl);
     }
+
+    pub fn options(&self) -> ExecOptions {
+        self.options
+    }
+
+    /// Enable `NodeProps::shared_surfaces` (see [`shared_surface`]).
+    pub fn set_shared_surface_importer(&mut self, importer: SharedSurfaceImporter) {
+        self.shared.importer = Some(importer);
+    }
+
+    pub fn set_options(&mut self, options: ExecOptions) {
+        self.options = options;
+    }
+
+    /// Enable (or disable with `None`) on-disk program binary caching.
+    pub fn set_program_disk_cache(&mut self, cache: Option<ProgramDiskCache>) {
+        self.disk_cache = cache;
+    }
+
+    pub fn program_disk_cache(&self) -> Option<&ProgramDiskCache> {
+        self.disk_cache.as_ref()
+    }
+
+    /// Render internal targets at `scale` x the host frame size (clamped to `0.1..=1.0`).
+    ///
+    /// Outputs shrink accordingly; presentation sinks that scale (e.g. `PresentSink`) hide it.
+    pub fn set_render_scale(&mut self, scale: f32) {
+        self.render_scale = if scale.is_finite() { scale.clamp(0.1, 1.0) } else { 1.0 };
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Bypass a render node: it copies its first input (black if it has none) instead of
+    /// running its shader. Used for quality fallback and for A/B-ing effects live.
+    pub fn set_node_bypassed(&mut self, node: NodeId, bypassed: bool) {
+        if bypassed {
+            self.bypassed.insert(node);
+        } else {
+            self.bypassed.remove(&node);
+        }
+    }
+
+    pub fn is_node_bypassed(&self, node: NodeId) -> bool {
+        self.bypassed.contains(&node)
+    }
+
+    /// Expose the texture on the edge into `node`'s input `port` as the extra output
+    /// [`taps::tap_output_name`] from `execute_plan_outputs`. Takes effect on the next frame.
+    pub fn set_edge_tap(&mut self, node: NodeId, port: &str, enabled: bool) {
+        let tapped = self.is_edge_tapped(node, port);
+        if enabled && !tapped {
+            self.taps.enabled.push((node, port.to_string()));
+        } else if !enabled && tapped {
+            self.taps.enabled.retain(|(n, p)| (*n, p.as_str()) != (node, port));
+        }
+    }
+
+    pub fn is_edge_tapped(&self, node: NodeId, port: &str) -> bool {
+        self.taps.enabled.iter().any(|(n, p)| *n == node && p == port)
+    }
+
+    /// Enabled taps as `(node, input port)`, in the order they were enabled.
+    pub fn edge_taps(&self) -> impl Iterator<Item = (NodeId, &str)> {
+        self.taps.enabled.iter().map(|(node, port)| (*node, port.as_str()))
+    }
+
+    /// Run `hook` inside `execute_plan` just before `node` draws, with its target bound and
+    /// cleared. Replaces any previous pre hook. See [`hooks`] for the GL state contract.
+    pub fn set_pre_render_hook(
+        &mut self,
+        node: NodeId,
+        hook: impl FnMut(&glow::Context, &NodeHookCtx) -> Result<(), EngineError> + 'static,
+    ) {
+        self.hooks.entry(node).or_default().pre = Some(Box::new(hook));
+    }
+
+    /// Run `hook` inside `execute_plan` right after `node` draws, with its target still bound
+    /// (e.g. to draw UI over the node's output). Replaces any previous post hook.
+    pub fn set_post_render_hook(
+        &mut self,
+        node: NodeId,
+        hook: impl FnMut(&glow::Context, &NodeHookCtx) -> Result<(), EngineError> + 'static,
+    ) {
+        self.hooks.entry(node).or_default().post = Some(Box::new(hook));
+    }
+
+    /// Declare the `@param` annotations of `node`'s fragment shader in the registry when its
+    /// program changed (see `scheng_runtime::shader_params`). Controls the new source no longer
+    /// declares are removed.
+    fn register_shader_params(&mut self, graph: &Graph, node: NodeId, key: ProgramKey, frag: &str) {
+        if self.shader_params.get(&node).is_some_and(|p| p.key == key) {
+            return;
+        }
+        let scan = scan_shader_params(frag);
+        for e in scan.errors {
+            self.events.push(EngineEvent::Log {
+                level: LogLevel::Warn,
+                tag: "shader-params",
+                msg: format!("node #{}: {e}", node.0),
+            });
+        }
+        let params = declare_shader_params(&mut self.params, graph, node, &scan.params);
+        if let Some(old) = self.shader_params.remove(&node) {
+            for (_, stale) in old.params {
+                if !params.iter().any(|(_, k)| *k == stale) {
+                    self.params.remove(&stale);
+                }
+            }
+        }
+        self.shader_params.insert(node, ShaderParams { key, params });
+    }
+
+    /// How often `node`'s shader source has changed since it was first built (`uReloadCount`).
+    /// Survives `invalidate`, since the source did not change.
+    pub fn reload_count(&self, node: NodeId) -> u32 {
+        self.reloads.get(&node).map_or(0, |r| r.1)
+    }
+
+    /// Record the source behind `node`'s program, counting a reload when it changed.
+    fn note_shader_source(&mut self, node: NodeId, key: ProgramKey) {
+        let entry = self.reloads.entry(node).or_insert((key, 0));
+        if entry.0 != key {
+            *entry = (key, entry.1 + 1);
+        }
+    }
+
+    /// Remove `node`'s pre and post hooks.
+    pub fn clear_node_hooks(&mut self, node: NodeId) {
+        self.hooks.remove(&node);
+    }
+
+    pub fn has_node_hooks(&self, node: NodeId) -> bool {
+        self.hooks.get(&node).is_some_and(|h| !h.is_empty())
+    }
+
+    /// Run `node` with a Rust executor instead of its shader (see [`executor`]). A previous
+    /// executor for the node is destroyed first.
+    pub fn set_node_executor(
+        &mut self,
+        gl: &glow::Context,
+        node: NodeId,
+        executor: impl NodeExecutor + 'static,
+    ) {
+        if let Some(mut old) = self.executors.insert(node, Box::new(executor)) {
+            old.destroy(gl);
+        }
+    }
+
+    /// Destroy and remove `node`'s executor; the node renders its shader again.
+    pub fn remove_node_executor(&mut self, gl: &glow::Context, node: NodeId) {
+        if let Some(mut old) = self.executors.remove(&node) {
+            old.destroy(gl);
+        }
+    }
+
+    pub fn has_node_executor(&self, node: NodeId) -> bool {
+        self.executors.contains_key(&node)
+    }
+
+    /// Respawn every particle of a `Particles` node on its next frame.
+    pub fn reset_particles(&mut self, node: NodeId) {
+        if let Some(ps) = self.particles.get_mut(&node) {
+            ps.reset();
+        }
+    }
+
+    /// Drop the temporal history of output `pixels_out` (e.g. on a hard cut); its next frame
+    /// passes through unblended.
+    pub fn reset_temporal(&mut self, pixels_out: NodeId) {
+        if let Some(ts) = self.temporal.get_mut(&pixels_out) {
+            ts.reset();
+        }
+    }
+
+    /// Parameter registry (analysis results such as `node/<handle>/energy` are published here;
+    /// see `scheng_runtime::node_param_key_in`).
+    pub fn params(&self) -> &ParamRegistry {
+        &self.params
+    }
+
+    pub fn params_mut(&mut self) -> &mut ParamRegistry {
+        &mut self.params
+    }
+
+    /// Compile and cache every program the graph can resolve, so the first frame a node is
+    /// shown does not hitch on shader compilation.
+    ///
+    /// Covers custom shaders, built-in mixers/analysis nodes and their helper passes. Failures
+    /// are collected rather than returned early, so a show can check everything up front.
+    /// (Drivers may still defer some work to the first draw.)
+    pub unsafe fn precompile(
+        &mut self,
+        gl: &glow::Context,
+        graph: &Graph,
+        props: &NodeProps,
+    ) -> PrecompileReport {
+        let mut report = PrecompileReport::default();
+
+        // Executor nodes have no shader to compile.
+        let mut nodes: Vec<&Node> = graph
+            .nodes()
+            .filter(|n| is_render_node(&n.kind))
+            .filter(|n| {
+                !matches!(
+                    n.kind,
+                    NodeKind::Particles
+                        | NodeKind::AudioWaveform
+                        | NodeKind::AudioSpectrum
+                        | NodeKind::Bloom
+                )
+            })
+            .filter(|n| !self.executors.contains_key(&n.id))
+            .collect();
+        nodes.sort_by_key(|n| n.id.0);
+
+        let mut helpers: Vec<&str> = Vec::new();
+        for node in nodes {
+            if let Some(bank) = props.shader_banks.get(&node.id) {
+                for (slot, e) in self.warm_shader_bank(gl, graph, node.id, bank) {
+                    let e = EngineError::Other(format!("slot {slot}: {e}"));
+                    report.failures.push((node.id, e));
+                }
+            }
+            let shader = resolve_pass_shader(graph, props, &self.params, node.id);
+            let compiled = shader.and_then(|shader| {
+                let (key, program) = self.cached_program(gl, &shader.vert, &shader.frag)?;
+                self.register_shader_params(graph, node.id, key, &shader.frag);
+                self.note_shader_source(node.id, key);
+                Ok((key, program))
+            });
+            match compiled {
+                Ok((key, program)) => {
+                    self.programs.insert(node.id, ProgramEntry { program, key });
+                    report.compiled.push(node.id);
+                }
+                Err(e) => report.failures.push((node.id, e)),
+            }
+            if matches!(node.kind, NodeKind::MotionDetect | NodeKind::OpticalFlow) {
+                helpers.push(TEX_INPUT_FRAG);
+            }
+            if node.kind == NodeKind::MotionDetect {
+                helpers.push(analysis::MOTION_REDUCE_FRAG);
+            }
+        }
+        if !props.temporal_params.is_empty() {
+            helpers.push(temporal::TEMPORAL_FRAG);
+        }
+        if props.output_alpha.values().any(|p| p.converts()) {
+            helpers.push(alpha::ALPHA_FRAG);
+        }
+        helpers.sort_unstable();
+        helpers.dedup();
+        for frag in helpers {
+            if let Err(e) = self.cached_program(gl, FULLSCREEN_VERT, frag) {
+                report.helper_failures.push(e);
+            }
+        }
+
+        report
+    }
+
+    /// Compile (or fetch from the shared cache) a program for `vert`/`frag`.
+    unsafe fn cached_program(
+        &mut self,
+        gl: &glow::Context,
+        vert: &str,
+        frag: &str,
+    ) -> Result<(ProgramKey, glow::NativeProgram), EngineError> {
+        let key = ProgramKey {
+            vert_hash: hash_str(vert),
+            frag_hash: hash_str(frag),
+        };
+        if let Some(p) = self.program_cache.get(&key) {
+            return Ok((key, *p));
+        }
+        let p = match self.disk_cache.as_mut() {
+            Some(disk) => disk.get_or_compile(gl, vert, frag)?,
+            None => compile_program(gl, vert, frag)?,
+        };
+        self.program_cache.insert(key, p);
+        Ok((key, p))
+    }
 }
 
 /// Output of executing a plan for one frame.
@@ -409,19 +1418,41 @@ pub struct ExecOutput {
     pub height: i32,
 }
 
+/// A named output entry with the metadata sinks need for routing decisions.
+#[derive(Debug, Clone)]
+pub struct NamedOutput {
+    pub name: String,
+    /// The render node (ShaderPass or Mixer) that produced `out`.
+    pub node: NodeId,
+    /// Size requested for this frame (`FrameCtx`), which may differ from the texture size.
+    pub declared_width: i32,
+    pub declared_height: i32,
+    /// How `out` encodes alpha (`NodeProps::output_alpha`); debug taps are always straight.
+    pub alpha: AlphaPolicy,
+    pub out: ExecOutput,
+}
+
 #[derive(Debug, Clone)]
 pub struct ExecOutputs {
     /// The traditional final output (backwards-compatible)
     pub primary: ExecOutput,
 
-    /// Named outputs for routing to multiple sinks
-    pub named: HashMap<String, ExecOutput>,
+    /// Named outputs for routing to multiple sinks.
+    ///
+    /// Ordered deterministically: `main` first, then explicit names in plan order, then
+    /// enabled debug taps (`debug:<node>:<port>`, see [`taps`]).
+    pub named: Vec<NamedOutput>,
 }
 
 impl ExecOutputs {
     /// Get a named output (e.g. `"main"`).
     pub fn get(&self, name: &str) -> Option<&ExecOutput> {
-        self.named.get(name)
+        self.entry(name).map(|e| &e.out)
+    }
+
+    /// Get a named output together with its metadata.
+    pub fn entry(&self, name: &str) -> Option<&NamedOutput> {
+        self.named.iter().find(|e| e.name == name)
     }
 
     /// The primary output (also available as `OUTPUT_MAIN`).
@@ -429,9 +1460,14 @@ impl ExecOutputs {
         &self.primary
     }
 
-    /// Iterate named outputs in insertion order (currently just `main`).
+    /// Iterate named outputs in insertion order (`main` first, then plan order).
     pub fn iter(&self) -> impl Iterator<Item = (&str, &ExecOutput)> {
-        self.named.iter().map(|(k, v)| (k.as_str(), v))
+        self.named.iter().map(|e| (e.name.as_str(), &e.out))
+    }
+
+    /// Output names in iteration order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.named.iter().map(|e| e.name.as_str())
     }
 }
 
@@ -457,46 +1493,75 @@ pub unsafe fn execute_plan_outputs(
     frame: FrameCtx,
 ) -> Result<ExecOutputs, EngineError> {
     let primary = execute_plan(gl, graph, plan, state, props, frame)?;
-    let main = primary; // `ExecOutput` is Copy (GL handles).
-
-    let mut named = std::collections::HashMap::new();
-    named.insert(OUTPUT_MAIN.to_string(), main);
 
     // Step 5 (explicit-only): expose additional named outputs backed by `PixelsOut` nodes.
     //
     // We do not re-execute the plan. We resolve each PixelsOut's upstream render-pass target from
     // `state.targets` (populated by `execute_plan` for this frame).
-    let resolve_pixels_out = |pixels_out: NodeId| -> Result<ExecOutput, EngineError> {
-        let out_edge = graph
-            .edges()
-            .iter()
-            .find(|e| e.to.node == pixels_out && e.to.dir == PortDir::In)
-            .ok_or_else(|| EngineError::other("execute_plan_outputs: PixelsOut has no input edge"))?;
+    let resolve_pixels_out = |pixels_out: NodeId| -> Result<(NodeId, ExecOutput), EngineError> {
+        let out_edge = graph.incoming(pixels_out).next().ok_or_else(|| {
+            EngineError::other("execute_plan_outputs: PixelsOut has no input edge")
+        })?;
 
         let from_node = graph.node(out_edge.from.node).ok_or_else(|| {
             EngineError::other("execute_plan_outputs: output edge references missing node")
         })?;
 
-        let from_is_render_pass = from_node.kind == NodeKind::ShaderPass
-            || from_node.kind.class() == NodeClass::Mixer;
+        let from_is_render_pass = is_render_node(&from_node.kind);
         if !from_is_render_pass {
             return Err(EngineError::other(
                 "execute_plan_outputs: PixelsOut input must come from a render pass (ShaderPass or Mixer)",
             ));
         }
 
-        let pp = state.targets.get(&from_node.id).ok_or_else(|| {
-            EngineError::other("execute_plan_outputs: missing render target for output pass")
-        })?;
+        // Converted and smoothed outputs read the runtime's final pass (see
+        // `NodeProps::output_alpha` and `NodeProps::temporal_params`).
+        let t = state
+            .alpha_outputs
+            .get(&pixels_out)
+            .map(|ao| ao.output())
+            .or_else(|| state.temporal.get(&pixels_out).map(|ts| ts.output()))
+            .or_else(|| {
+                state
+                    .targets
+                    .get(&from_node.id)
+                    .and_then(|pp| pp.output(output_index(graph, out_edge.from), false))
+            })
+            .ok_or_else(|| {
+                EngineError::other("execute_plan_outputs: missing render target for output pass")
+            })?;
 
-        Ok(ExecOutput {
-            tex: pp.curr.tex,
-            fbo: pp.curr.fbo,
-            width: pp.curr.w,
-            height: pp.curr.h,
-        })
+        Ok((
+            from_node.id,
+            ExecOutput {
+                tex: t.tex,
+                fbo: t.fbo,
+                width: t.w,
+                height: t.h,
+            },
+        ))
     };
 
+    let entry = |name: &str, pixels_out: NodeId, node: NodeId, out: ExecOutput| NamedOutput {
+        name: name.to_string(),
+        node,
+        declared_width: frame.width,
+        declared_height: frame.height,
+        alpha: props.output_alpha.get(&pixels_out).copied().unwrap_or_default(),
+        out,
+    };
+
+    // `main` mirrors `execute_plan`: the first PixelsOut in plan order.
+    let main_out = plan
+        .nodes
+        .iter()
+        .copied()
+        .find(|nid| graph.node(*nid).map(|n| n.kind == NodeKind::PixelsOut).unwrap_or(false))
+        .ok_or_else(|| EngineError::other("execute_plan_outputs: missing PixelsOut node in plan"))?;
+    let (main_node, _) = resolve_pixels_out(main_out)?;
+
+    let mut named = vec![entry(OUTPUT_MAIN, main_out, main_node, primary)];
+
     for nid in &plan.nodes {
         let Some(node) = graph.node(*nid) else { continue; };
         if node.kind != NodeKind::PixelsOut {
@@ -512,16 +1577,23 @@ pub unsafe fn execute_plan_outputs(
             ));
         }
 
-        if named.contains_key(name) {
+        if named.iter().any(|e| e.name == *name) {
             return Err(EngineError::other(format!(
                 "execute_plan_outputs: duplicate output name '{name}'"
             )));
         }
 
-        let out = resolve_pixels_out(node.id)?;
-        named.insert(name.clone(), out);
+        let (from, out) = resolve_pixels_out(node.id)?;
+        named.push(entry(name, node.id, from, out));
     }
 
+    // Debug taps come last (see `RuntimeState::set_edge_tap`).
+    let tapped = taps::tap_outputs(gl, graph, state, props, frame, &named)?;
+    named.extend(tapped);
+    state
+        .probes
+        .set_outputs(named.iter().map(|e| (e.name.as_str(), e.out)));
+
     Ok(ExecOutputs { primary, named })
 }
 
@@ -530,11 +1602,29 @@ pub unsafe fn execute_plan_outputs(
 /// This is intentionally defined in the glow backend first (most surgical).
 /// Later (S4) we can lift a backend-agnostic sink interface into `scheng-runtime`
 /// once output routing and portability contracts are finalized.
+///
+/// Lifecycle: `on_frame_begin` → (`on_resize` when the output size changes) → `consume` →
+/// `on_frame_end`. `destroy` releases GPU resources owned by the sink; the hooks all default to
+/// no-ops so simple sinks only implement `consume`.
 pub trait OutputSink {
     /// Consume the final output produced by `execute_plan` for this frame.
     ///
     /// Sinks should not delete GL resources they did not create.
     fn consume(&mut self, gl: &glow::Context, out: &ExecOutput);
+
+    /// Called before the plan executes for a frame.
+    fn on_frame_begin(&mut self, _gl: &glow::Context, _frame: &FrameCtx) {}
+
+    /// Called after `consume` for a frame, and after `on_frame_begin` when the frame failed.
+    fn on_frame_end(&mut self, _gl: &glow::Context) {}
+
+    /// Called before `consume` when the routed output size differs from the one this sink saw
+    /// last (including its first frame).
+    fn on_resize(&mut self, _gl: &glow::Context, _width: i32, _height: i32) {}
+
+    /// Release GPU resources owned by this sink. The sink may be reused afterwards and should
+    /// lazily reallocate.
+    fn destroy(&mut self, _gl: &glow::Context) {}
 }
 
 /// A sink that does nothing (useful as a default during integration).
@@ -558,6 +1648,75 @@ impl<A: OutputSink, B: OutputSink> OutputSink for FanoutSink<A, B> {
         self.a.consume(gl, out);
         self.b.consume(gl, out);
     }
+
+    fn on_frame_begin(&mut self, gl: &glow::Context, frame: &FrameCtx) {
+        self.a.on_frame_begin(gl, frame);
+        self.b.on_frame_begin(gl, frame);
+    }
+
+    fn on_frame_end(&mut self, gl: &glow::Context) {
+        self.a.on_frame_end(gl);
+        self.b.on_frame_end(gl);
+    }
+
+    fn on_resize(&mut self, gl: &glow::Context, width: i32, height: i32) {
+        self.a.on_resize(gl, width, height);
+        self.b.on_resize(gl, width, height);
+    }
+
+    fn destroy(&mut self, gl: &glow::Context) {
+        self.a.destroy(gl);
+        self.b.destroy(gl);
+    }
+}
+
+/// What `PatchbaySink::consume_named` does when a routed output is missing for a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissingOutputPolicy {
+    /// Return an error (strict; the default).
+    #[default]
+    Error,
+    /// Log once per name (until the output reappears) and skip the route.
+    Warn,
+    /// Silently skip the route.
+    Ignore,
+}
+
+/// Introspection record for a single patchbay route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteInfo<'a> {
+    pub name: &'a str,
+    /// Position of the sink within its name's route list.
+    pub index: usize,
+    pub enabled: bool,
+    /// The name's rate divisor (see `PatchbaySink::set_rate_divisor`).
+    pub rate_divisor: u32,
+}
+
+struct RouteSlot {
+    sink: Box<dyn OutputSink>,
+    enabled: bool,
+}
+
+impl RouteSlot {
+    /// Run `f` on an enabled sink behind a panic boundary; a panic disables the route.
+    fn call(
+        &mut self,
+        gl: &glow::Context,
+        name: &str,
+        index: usize,
+        events: &mut Vec<EngineEvent>,
+        f: impl FnOnce(&mut dyn OutputSink),
+    ) {
+        if !self.enabled {
+            return;
+        }
+        let sink = &mut *self.sink;
+        if let Err(message) = guard::guarded(gl, || f(sink)) {
+            self.enabled = false;
+            events.push(guard::panicked(format!("route '{name}' #{index}"), message));
+        }
+    }
 }
 
 /// S6: Patchbay sink for named output routing.
@@ -565,10 +1724,32 @@ impl<A: OutputSink, B: OutputSink> OutputSink for FanoutSink<A, B> {
 /// This is intentionally minimal: it maps `OutputName` -> `Vec<Box<dyn OutputSink>>` and
 /// calls each sink with the resolved output for that name.
 ///
+/// Routes can be added, replaced, removed and toggled between frames for live re-patching.
+/// Sinks are addressed by `(name, index)` where `index` is the position within that name.
+///
+/// Every sink call runs behind a panic boundary (see [`guard`]): a sink that panics has its
+/// route disabled and a `ComponentPanicked` event queued for [`take_events`](Self::take_events).
+///
+/// A name can be given a rate divisor so its sinks (previews, recording proxies) only see every
+/// Nth frame while other names stay at full rate; see [`set_rate_divisor`](Self::set_rate_divisor).
+///
 /// This lives here (runtime-glow) for surgical iteration. Once the contract stabilizes,
 /// we can lift the trait to `scheng-runtime` and keep glow/wgpu backends implementing it.
 pub struct PatchbaySink {
-    routes: HashMap<String, Vec<Box<dyn OutputSink>>>,
+    /// Routes in insertion order (deterministic sink invocation order).
+    routes: Vec<(String, Vec<RouteSlot>)>,
+    missing: MissingOutputPolicy,
+    warned_missing: Vec<String>,
+    /// Last consumed size per output name (drives `OutputSink::on_resize`).
+    last_sizes: HashMap<String, (i32, i32)>,
+    /// Output names fed only every Nth frame (absent = every frame).
+    divisors: HashMap<String, u32>,
+    /// Frames consumed so far; a divided name is due when this is a multiple of its divisor.
+    frame: u64,
+    /// Names fed by the last `consume_named` (their sinks get `on_frame_end`).
+    fed: Vec<String>,
+    /// Panics caught since the last `take_events`.
+    events: Vec<EngineEvent>,
 }
 
 impl Default for PatchbaySink {
@@ -580,13 +1761,190 @@ impl Default for PatchbaySink {
 impl PatchbaySink {
     pub fn new() -> Self {
         Self {
-            routes: HashMap::new(),
+            routes: Vec::new(),
+            missing: MissingOutputPolicy::default(),
+            warned_missing: Vec::new(),
+            last_sizes: HashMap::new(),
+            divisors: HashMap::new(),
+            frame: 0,
+            fed: Vec::new(),
+            events: Vec::new(),
         }
     }
 
-    pub fn add_route<S: OutputSink + 'static>(&mut self, name: impl Into<String>, sink: S) {
+    /// `ComponentPanicked` events for routes disabled since the last call.
+    pub fn take_events(&mut self) -> Vec<EngineEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Feed `name`'s sinks only every `divisor`th frame (`0` or `1` = every frame).
+    ///
+    /// Skipped frames are skipped entirely: those sinks get no `on_frame_begin`, `consume` or
+    /// `on_frame_end`. Frames are counted by `consume_named` calls, and divided names all
+    /// fire on frame 0, so names with the same divisor stay in step.
+    pub fn set_rate_divisor(&mut self, name: impl Into<String>, divisor: u32) {
+        let name = name.into();
+        if divisor > 1 {
+            self.divisors.insert(name, divisor);
+        } else {
+            self.divisors.remove(&name);
+        }
+    }
+
+    pub fn rate_divisor(&self, name: &str) -> u32 {
+        self.divisors.get(name).copied().unwrap_or(1)
+    }
+
+    /// Whether `name`'s sinks are fed on the current frame.
+    fn is_due(&self, name: &str) -> bool {
+        self.frame.is_multiple_of(self.rate_divisor(name) as u64)
+    }
+
+    pub fn set_missing_output_policy(&mut self, policy: MissingOutputPolicy) {
+        self.missing = policy;
+    }
+
+    pub fn missing_output_policy(&self) -> MissingOutputPolicy {
+        self.missing
+    }
+
+    /// Append a sink to `name`'s route list. Returns its index within that name.
+    pub fn add_route<S: OutputSink + 'static>(&mut self, name: impl Into<String>, sink: S) -> usize {
         let name = name.into();
-        self.routes.entry(name).or_default().push(Box::new(sink));
+        let slot = RouteSlot {
+            sink: Box::new(sink),
+            enabled: true,
+        };
+        match self.routes.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, slots)) => {
+                slots.push(slot);
+                slots.len() - 1
+            }
+            None => {
+                self.routes.push((name, vec![slot]));
+                0
+            }
+        }
+    }
+
+    /// Remove the sink at `(name, index)`. Later sinks for `name` shift down by one.
+    pub fn remove_route(&mut self, name: &str, index: usize) -> Option<Box<dyn OutputSink>> {
+        let pos = self.routes.iter().position(|(n, _)| n == name)?;
+        let slots = &mut self.routes[pos].1;
+        if index >= slots.len() {
+            return None;
+        }
+        let slot = slots.remove(index);
+        if slots.is_empty() {
+            self.routes.remove(pos);
+        }
+        Some(slot.sink)
+    }
+
+    /// Swap the sink at `(name, index)` for `sink`, keeping its enable flag. Returns the old sink.
+    pub fn replace_route<S: OutputSink + 'static>(
+        &mut self,
+        name: &str,
+        index: usize,
+        sink: S,
+    ) -> Option<Box<dyn OutputSink>> {
+        let slot = self.slot_mut(name, index)?;
+        Some(std::mem::replace(&mut slot.sink, Box::new(sink)))
+    }
+
+    /// Remove every sink routed from `name`. Returns how many were removed.
+    pub fn clear_route(&mut self, name: &str) -> usize {
+        let Some(pos) = self.routes.iter().position(|(n, _)| n == name) else {
+            return 0;
+        };
+        self.routes.remove(pos).1.len()
+    }
+
+    /// Remove all routes.
+    pub fn clear(&mut self) {
+        self.routes.clear();
+        self.warned_missing.clear();
+    }
+
+    /// Forward `OutputSink::on_frame_begin` to every enabled route due this frame. Call before
+    /// executing the plan.
+    ///
+    /// Each `begin_frame` must be followed by exactly one `consume_named`: only `consume_named`
+    /// advances the frame count, so calling `begin_frame` twice forwards the begin twice, and a
+    /// `consume_named` without a `begin_frame` feeds sinks that never saw the frame begin.
+    pub fn begin_frame(&mut self, gl: &glow::Context, frame: &FrameCtx) {
+        let due: Vec<bool> = self.routes.iter().map(|(name, _)| self.is_due(name)).collect();
+        for ((name, slots), _) in self.routes.iter_mut().zip(due).filter(|(_, due)| *due) {
+            for (index, slot) in slots.iter_mut().enumerate() {
+                slot.call(gl, name, index, &mut self.events, |s| s.on_frame_begin(gl, frame));
+            }
+        }
+    }
+
+    /// Forward `OutputSink::on_frame_end` to every enabled route fed by the last
+    /// `consume_named`. Call after `consume_named`.
+    pub fn end_frame(&mut self, gl: &glow::Context) {
+        for (name, slots) in self.routes.iter_mut() {
+            if !self.fed.contains(name) {
+                continue;
+            }
+            for (index, slot) in slots.iter_mut().enumerate() {
+                slot.call(gl, name, index, &mut self.events, |s| s.on_frame_end(gl));
+            }
+        }
+    }
+
+    /// Call `OutputSink::destroy` on every routed sink (routes are kept).
+    pub fn destroy(&mut self, gl: &glow::Context) {
+        for (name, slots) in self.routes.iter_mut() {
+            for (index, slot) in slots.iter_mut().enumerate() {
+                if let Err(message) = guard::guarded(gl, || slot.sink.destroy(gl)) {
+                    let component = format!("route '{name}' #{index}");
+                    self.events.push(guard::panicked(component, message));
+                }
+            }
+        }
+        self.last_sizes.clear();
+    }
+
+    /// Enable/disable a single route without removing it. Returns false if it does not exist.
+    pub fn set_route_enabled(&mut self, name: &str, index: usize, enabled: bool) -> bool {
+        match self.slot_mut(name, index) {
+            Some(slot) => {
+                slot.enabled = enabled;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// All routes in invocation order.
+    pub fn routes(&self) -> impl Iterator<Item = RouteInfo<'_>> {
+        self.routes.iter().flat_map(|(name, slots)| {
+            let rate_divisor = self.rate_divisor(name);
+            slots.iter().enumerate().map(move |(index, slot)| RouteInfo {
+                name: name.as_str(),
+                index,
+                enabled: slot.enabled,
+                rate_divisor,
+            })
+        })
+    }
+
+    /// Number of sinks routed from `name`.
+    pub fn route_count(&self, name: &str) -> usize {
+        self.routes
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, slots)| slots.len())
+            .unwrap_or(0)
+    }
+
+    fn slot_mut(&mut self, name: &str, index: usize) -> Option<&mut RouteSlot> {
+        self.routes
+            .iter_mut()
+            .find(|(n, _)| n == name)
+            .and_then(|(_, slots)| slots.get_mut(index))
     }
 
     pub fn consume_named(
@@ -594,12 +1952,42 @@ impl PatchbaySink {
         gl: &glow::Context,
         outs: &ExecOutputs,
     ) -> Result<(), EngineError> {
-        for (name, sinks) in self.routes.iter_mut() {
-            let out = outs.get(name).ok_or_else(|| {
-                EngineError::other(format!("PatchbaySink: missing named output '{name}'"))
-            })?;
-            for s in sinks.iter_mut() {
-                s.consume(gl, out);
+        self.fed.clear();
+        let frame = self.frame;
+        self.frame += 1;
+        for (name, slots) in self.routes.iter_mut() {
+            let divisor = self.divisors.get(name).copied().unwrap_or(1);
+            if !frame.is_multiple_of(divisor as u64) {
+                continue;
+            }
+            let Some(out) = outs.get(name) else {
+                match self.missing {
+                    MissingOutputPolicy::Error => {
+                        return Err(EngineError::other(format!(
+                            "PatchbaySink: missing named output '{name}'"
+                        )));
+                    }
+                    MissingOutputPolicy::Warn => {
+                        if !self.warned_missing.contains(name) {
+                            eprintln!("[PatchbaySink] missing named output '{name}' (skipping)");
+                            self.warned_missing.push(name.clone());
+                        }
+                    }
+                    MissingOutputPolicy::Ignore => {}
+                }
+                continue;
+            };
+            self.warned_missing.retain(|n| n != name);
+            self.fed.push(name.clone());
+            let size = (out.width, out.height);
+            let resized = self.last_sizes.insert(name.clone(), size) != Some(size);
+            for (index, slot) in slots.iter_mut().enumerate() {
+                slot.call(gl, name, index, &mut self.events, |s| {
+                    if resized {
+                        s.on_resize(gl, out.width, out.height);
+                    }
+                    s.consume(gl, out);
+                });
             }
         }
         Ok(())
@@ -612,18 +2000,49 @@ impl PatchbaySink {
 pub struct HistoryTapSink {
     frames: Vec<RenderTarget>,
     write_idx: usize,
+    /// Requested ring length (frames are allocated lazily to this length).
+    len: usize,
+    /// Number of slots holding captured frames (saturates at `frames.len()`).
+    filled: usize,
 }
 
 impl HistoryTapSink {
     /// Create a history tap with `len` frames. The actual GPU targets are lazily allocated
     /// on first use (or reallocated on resize).
     pub fn new(len: usize) -> Self {
+        let len = len.max(1);
         Self {
-            frames: Vec::with_capacity(len.max(1)),
+            frames: Vec::with_capacity(len),
             write_idx: 0,
+            len,
+            filled: 0,
         }
     }
 
+    /// Requested number of history frames.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Always false: a history tap holds at least one frame slot.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Number of frames captured so far (ages `0..valid_frames()` hold real content).
+    #[inline]
+    pub fn valid_frames(&self) -> usize {
+        self.filled.min(self.len)
+    }
+
+    /// Change the ring length. Takes effect on the next `consume`; the newest
+    /// `min(valid_frames, len)` frames are preserved when the output size is unchanged.
+    pub fn set_len(&mut self, len: usize) {
+        self.len = len.max(1);
+    }
+
     /// Latest captured texture (age 0).
     #[inline]
     pub fn tex_latest(&self) -> Option<glow::NativeTexture> {
@@ -631,6 +2050,9 @@ impl HistoryTapSink {
     }
 
     /// Texture at a given age: 0 = newest, 1 = previous, ...
+    ///
+    /// Ages wrap modulo the allocated length; ages at or beyond `valid_frames()` refer to slots
+    /// that have not been written yet (cleared to black).
     pub fn tex_at(&self, age: usize) -> Option<glow::NativeTexture> {
         if self.frames.is_empty() {
             return None;
@@ -650,55 +2072,64 @@ impl HistoryTapSink {
             gl.delete_texture(rt.tex);
         }
         self.write_idx = 0;
+        self.filled = 0;
     }
 
-    unsafe fn ensure_allocated(
-        &mut self,
-        gl: &glow::Context,
-        want_len: usize,
-        w: i32,
-        h: i32,
-    ) -> Result<(), EngineError> {
-        let want_len = want_len.max(1);
+    unsafe fn alloc_cleared(gl: &glow::Context, w: i32, h: i32) -> Result<RenderTarget, EngineError> {
+        let rt = create_render_target(gl, w, h)?;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(rt.fbo));
+        gl.viewport(0, 0, rt.w, rt.h);
+        gl.clear_color(0.0, 0.0, 0.0, 1.0);
+        gl.clear(glow::COLOR_BUFFER_BIT);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        Ok(rt)
+    }
 
-        // Allocate if empty or wrong length.
-        if self.frames.len() != want_len {
-            for rt in self.frames.drain(..) {
-                gl.delete_framebuffer(rt.fbo);
-                gl.delete_texture(rt.tex);
-            }
-            self.frames = Vec::with_capacity(want_len);
-            for _ in 0..want_len {
-                self.frames.push(create_render_target(gl, w, h)?);
+    unsafe fn ensure_allocated(&mut self, gl: &glow::Context, w: i32, h: i32) -> Result<(), EngineError> {
+        // Recreate on resize mismatch (history cannot be preserved across sizes).
+        if let Some(rt0) = self.frames.first() {
+            if rt0.w != w || rt0.h != h {
+                self.destroy(gl);
             }
-            self.write_idx = 0;
+        }
+
+        if self.frames.len() == self.len {
             return Ok(());
         }
 
-        // Recreate on resize mismatch.
-        if let Some(rt0) = self.frames.first() {
-            if rt0.w != w || rt0.h != h {
-                for rt in self.frames.drain(..) {
-                    gl.delete_framebuffer(rt.fbo);
-                    gl.delete_texture(rt.tex);
-                }
-                self.frames = Vec::with_capacity(want_len);
-                for _ in 0..want_len {
-                    self.frames.push(create_render_target(gl, w, h)?);
-                }
-                self.write_idx = 0;
+        // Re-lay the ring oldest..newest, keeping the newest frames that still fit.
+        let n = self.frames.len();
+        let keep = self.valid_frames().min(n).min(self.len);
+        let mut ordered: Vec<Option<RenderTarget>> = self.frames.drain(..).map(Some).collect();
+        let mut kept = Vec::with_capacity(self.len);
+        for age in (0..keep).rev() {
+            let idx = (self.write_idx + n - 1 - age) % n;
+            if let Some(rt) = ordered[idx].take() {
+                kept.push(rt);
             }
         }
-
+        for rt in ordered.into_iter().flatten() {
+            gl.delete_framebuffer(rt.fbo);
+            gl.delete_texture(rt.tex);
+        }
+        self.filled = kept.len();
+        self.write_idx = kept.len() % self.len;
+        while kept.len() < self.len {
+            kept.push(Self::alloc_cleared(gl, w, h)?);
+        }
+        self.frames = kept;
         Ok(())
     }
 }
 
 impl OutputSink for HistoryTapSink {
+    fn destroy(&mut self, gl: &glow::Context) {
+        unsafe { HistoryTapSink::destroy(self, gl) }
+    }
+
     fn consume(&mut self, gl: &glow::Context, out: &ExecOutput) {
         unsafe {
-            let want_len = self.frames.capacity().max(1);
-            if let Err(e) = self.ensure_allocated(gl, want_len, out.width, out.height) {
+            if let Err(e) = self.ensure_allocated(gl, out.width, out.height) {
                 eprintln!("[HistoryTapSink] ensure_allocated error: {e:?}");
                 return;
             }
@@ -725,6 +2156,7 @@ impl OutputSink for HistoryTapSink {
             gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
 
             self.write_idx = (self.write_idx + 1) % self.frames.len();
+            self.filled = (self.filled + 1).min(self.frames.len());
         }
     }
 }
@@ -794,12 +2226,17 @@ impl Default for ReadbackSink {
 }
 
 impl OutputSink for ReadbackSink {
+    fn on_resize(&mut self, _gl: &glow::Context, _width: i32, _height: i32) {
+        // Drop the stale capture so `last()` never reports a mismatched size.
+        self.clear();
+    }
+
     fn consume(&mut self, gl: &glow::Context, out: &ExecOutput) {
         if !self.enabled {
             return;
         }
         self.frame_counter += 1;
-        if (self.frame_counter % self.stride) != 0 {
+        if !self.frame_counter.is_multiple_of(self.stride) {
             return;
         }
 
@@ -833,6 +2270,10 @@ pub struct BlitToScreenSink;
 impl OutputSink for BlitToScreenSink {
     fn consume(&mut self, gl: &glow::Context, out: &ExecOutput) {
         unsafe {
+            // Targets hold display-encoded values; never let GL re-encode them on the way out.
+            let was_srgb = gl.is_enabled(glow::FRAMEBUFFER_SRGB);
+            gl.disable(glow::FRAMEBUFFER_SRGB);
+
             gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(out.fbo));
             gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
 
@@ -851,6 +2292,10 @@ impl OutputSink for BlitToScreenSink {
 
             gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
             gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
+
+            if was_srgb {
+                gl.enable(glow::FRAMEBUFFER_SRGB);
+            }
         }
     }
 }
@@ -946,6 +2391,11 @@ impl OutputSink for SyphonSink {
     fn consume(&mut self, _gl: &glow::Context, _out: &ExecOutput) {}
 }
 
+/// Node kinds that render into a runtime-owned target (and can therefore feed textures downstream).
+fn is_render_node(kind: &NodeKind) -> bool {
+    kind.renders()
+}
+
 fn hash_str(s: &str) -> u64 {
     let mut h = std::collections::hash_map::DefaultHasher::new();
     s.hash(&mut h);
@@ -978,6 +2428,222 @@ pub unsafe fn execute_plan(
     state: &mut RuntimeState,
     props: &NodeProps,
     frame: FrameCtx,
+) -> Result<ExecOutput, EngineError> {
+    if state.invalidated {
+        state.recreate_after_invalidate(gl)?;
+    }
+    state.frame_block.begin_frame();
+
+    // Internal resolution (see `RuntimeState::set_render_scale`).
+    let frame = if state.render_scale < 1.0 {
+        FrameCtx {
+            width: ((frame.width as f32 * state.render_scale).round() as i32).max(1),
+            height: ((frame.height as f32 * state.render_scale).round() as i32).max(1),
+            ..frame
+        }
+    } else {
+        frame
+    };
+
+    let options = state.options;
+    if options.strict_props {
+        let diagnostics: Vec<String> = validate_props(graph, props)
+            .into_iter()
+            .filter(|d| {
+                !(d.kind == DiagnosticKind::MissingShader && state.executors.contains_key(&d.node))
+            })
+            .map(|d| d.to_string())
+            .collect();
+        if !diagnostics.is_empty() {
+            return Err(EngineError::Other(format!(
+                "execute_plan (strict): {}",
+                diagnostics.join("; ")
+            )));
+        }
+    }
+    let mut groups = DebugGroups::new(gl, options.debug_groups);
+    groups.push(gl, "scheng::execute_plan");
+
+    let checks = GlChecks {
+        enabled: options.check_gl_errors,
+    };
+    // Errors left by the host would otherwise be blamed on the first node.
+    let result = checks
+        .check(gl, None, "host GL calls before execute_plan")
+        .and_then(|()| execute_plan_nodes(gl, graph, plan, state, props, frame, &mut groups, checks));
+
+    // Balanced on success; an early error may leave node groups open.
+    groups.pop_all(gl);
+    if options.restore_gl_state {
+        restore_gl_state(gl);
+    }
+    if let Ok(out) = result {
+        state.probes.set_outputs([(OUTPUT_MAIN, out)]);
+    }
+    result
+}
+
+/// Texture units `execute_plan` may bind for inputs (`iChannel0..3`).
+const INPUT_UNITS: u32 = 4;
+
+/// Unit for `iSelf`; `iBufferA..D` follow it.
+const SELF_UNIT: u32 = INPUT_UNITS;
+
+/// Every texture unit `execute_plan` may bind.
+const BOUND_UNITS: u32 = SELF_UNIT + 1 + buffers::BUFFER_UNIFORMS.len() as u32;
+
+/// Reset the bindings `execute_plan` touches (see `ExecOptions::restore_gl_state`).
+unsafe fn restore_gl_state(gl: &glow::Context) {
+    for unit in 0..BOUND_UNITS {
+        gl.active_texture(glow::TEXTURE0 + unit);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+    }
+    gl.active_texture(glow::TEXTURE0);
+    gl.use_program(None);
+    gl.bind_vertex_array(None);
+    gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+    gl.bind_buffer_base(glow::UNIFORM_BUFFER, FRAME_BLOCK_BINDING, None);
+}
+
+/// `glGetError` polling for `ExecOptions::check_gl_errors`.
+#[derive(Clone, Copy)]
+struct GlChecks {
+    enabled: bool,
+}
+
+impl GlChecks {
+    /// Drain the GL error queue; report the first error attributed to `node` / `op`.
+    unsafe fn check(self, gl: &glow::Context, node: Option<NodeId>, op: &str) -> Result<(), EngineError> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let first = gl.get_error();
+        if first == glow::NO_ERROR {
+            return Ok(());
+        }
+        // GL may queue one flag per error type; clear them so the next check starts clean.
+        for _ in 0..8 {
+            if gl.get_error() == glow::NO_ERROR {
+                break;
+            }
+        }
+        Err(EngineError::Gl {
+            node: node.map(|n| n.0),
+            op: op.to_string(),
+            code: first,
+        })
+    }
+}
+
+/// `KHR_debug` group stack for one `execute_plan` call.
+struct DebugGroups {
+    enabled: bool,
+    depth: u32,
+}
+
+impl DebugGroups {
+    fn new(gl: &glow::Context, requested: bool) -> Self {
+        Self {
+            enabled: requested && gl.supports_debug(),
+            depth: 0,
+        }
+    }
+
+    unsafe fn push(&mut self, gl: &glow::Context, label: &str) {
+        if self.enabled {
+            gl.push_debug_group(glow::DEBUG_SOURCE_APPLICATION, self.depth, label);
+            self.depth += 1;
+        }
+    }
+
+    unsafe fn pop(&mut self, gl: &glow::Context) {
+        if self.enabled && self.depth > 0 {
+            gl.pop_debug_group();
+            self.depth -= 1;
+        }
+    }
+
+    unsafe fn pop_all(&mut self, gl: &glow::Context) {
+        while self.depth > 0 {
+            self.pop(gl);
+        }
+    }
+}
+
+/// Resolve the shader for a render node (see the resolution order in `execute_plan`).
+fn resolve_pass_shader(
+    graph: &Graph,
+    props: &NodeProps,
+    params: &ParamRegistry,
+    pass_node: NodeId,
+) -> Result<ShaderSource, EngineError> {
+    // 1) Direct override: the selected bank slot, else the node's shader.
+    let banked = props
+        .shader_banks
+        .get(&pass_node)
+        .and_then(|b| b.shader(graph, params, pass_node));
+    if let Some(s) = banked.or_else(|| props.shader_sources.get(&pass_node)) {
+        return Ok(s.clone());
+    }
+
+    // 2) Built-in mixers (semantic node kinds)
+    let pass = graph
+        .node(pass_node)
+        .ok_or_else(|| EngineError::other("execute_plan: resolve_shader missing node"))?;
+
+    if let Some(stdop) = standard_op_for(pass.kind.clone()) {
+        let (frag, origin) = match stdop {
+            StandardOp::Mixer(op) => (builtin_mixer_frag(op), format!("builtin:{op:?}")),
+            StandardOp::Filter(op) => (builtin_filter_frag(op), format!("builtin:{op:?}")),
+        };
+        return Ok(ShaderSource {
+            vert: FULLSCREEN_VERT.to_string(),
+            frag: frag.to_string(),
+            origin: Some(origin),
+        });
+    }
+    // Built-in analysis nodes.
+    let analysis_frag = match pass.kind {
+        NodeKind::MotionDetect => Some(analysis::MOTION_DIFF_FRAG),
+        NodeKind::OpticalFlow => Some(analysis::OPTICAL_FLOW_FRAG),
+        NodeKind::FlowDisplace => Some(analysis::FLOW_DISPLACE_FRAG),
+        _ => None,
+    };
+    if let Some(frag) = analysis_frag {
+        return Ok(ShaderSource {
+            vert: FULLSCREEN_VERT.to_string(),
+            frag: frag.to_string(),
+            origin: Some(format!("builtin:{:?}", pass.kind)),
+        });
+    }
+
+    // 3) Back-compat: if there's an incoming edge from a ShaderSource node, use that.
+    if let Some(e) = graph.incoming(pass_node).next() {
+        let from = graph
+            .node(e.from.node)
+            .ok_or_else(|| EngineError::other("execute_plan: edge references missing node"))?;
+        if from.kind == NodeKind::ShaderSource {
+            return props.shader_sources.get(&from.id).cloned().ok_or_else(|| {
+                EngineError::other("execute_plan: missing ShaderSource in NodeProps")
+            });
+        }
+    }
+
+    Err(EngineError::other(
+        "execute_plan: missing shader source (provide NodeProps for pass node, or connect ShaderSource -> ShaderPass)",
+    ))
+}
+
+#[allow(clippy::too_many_arguments)]
+unsafe fn execute_plan_nodes(
+    gl: &glow::Context,
+    graph: &Graph,
+    plan: &Plan,
+    state: &mut RuntimeState,
+    props: &NodeProps,
+    frame: FrameCtx,
+    groups: &mut DebugGroups,
+    checks: GlChecks,
 ) -> Result<ExecOutput, EngineError> {
     // Pull-based execution v1:
     // - Execute all ShaderPass nodes in plan order.
@@ -999,13 +2665,7 @@ pub unsafe fn execute_plan(
         .ok_or_else(|| EngineError::other("execute_plan: missing PixelsOut node in plan"))?;
 
     // Helper: find all incoming edges to a node.
-    let incoming_edges = |nid: NodeId| -> Vec<&Edge> {
-        graph
-            .edges()
-            .iter()
-            .filter(|e| e.to.node == nid && e.to.dir == PortDir::In)
-            .collect()
-    };
+    let incoming_edges = |nid: NodeId| -> Vec<&Edge> { graph.incoming(nid).collect() };
 
     // Helper: map a node-local input port id to a stable channel index.
     // v0 contract (Option A): the port name defines semantic ordering.
@@ -1027,62 +2687,39 @@ pub unsafe fn execute_plan(
     // 1) NodeProps override for the pass node (always wins)
     // 2) Built-in standard ops (mixers) via scheng-runtime mapping table
     // 3) Back-compat: incoming edge from a ShaderSource node (props keyed by that node)
-    let resolve_shader = |pass_node: NodeId| -> Result<ShaderSource, EngineError> {
-        // 1) Direct override
-        if let Some(s) = props.shader_sources.get(&pass_node) {
-            return Ok(s.clone());
-        }
 
-        // 2) Built-in mixers (semantic node kinds)
-        let pass = graph
-            .node(pass_node)
-            .ok_or_else(|| EngineError::other("execute_plan: resolve_shader missing node"))?;
+    // We'll store outputs for pass nodes here.
+    // (We use the RenderTarget cache in RuntimeState; this map is just for quick lookup.)
+    use std::collections::HashMap;
+    let mut outputs: HashMap<NodeId, (glow::NativeTexture, glow::NativeFramebuffer, i32, i32)> =
+        HashMap::new();
 
-        if let Some(stdop) = standard_op_for(pass.kind.clone()) {
-            let StandardOp::Mixer(op) = stdop;
-            {
-                return Ok(ShaderSource {
-                    vert: FULLSCREEN_VERT.to_string(),
-                    frag: builtin_mixer_frag(op).to_string(),
-                    origin: Some(format!("builtin:{op:?}")),
-                });
-            }
-        }
+    // Source outputs (Step 11.1): textures produced by Source-class nodes (e.g., TextureInputPass).
+    // These do not allocate render targets and do not run shaders.
+    let mut source_outputs: HashMap<NodeId, (glow::NativeTexture, i32, i32)> = HashMap::new();
 
-        // 3) Back-compat: if there's an incoming edge from a ShaderSource node, use that.
-        if let Some(e) = incoming_edges(pass_node).into_iter().next() {
-            let from = graph
-                .node(e.from.node)
-                .ok_or_else(|| EngineError::other("execute_plan: edge references missing node"))?;
-            if from.kind == NodeKind::ShaderSource {
-                return props.shader_sources.get(&from.id).cloned().ok_or_else(|| {
-                    EngineError::other("execute_plan: missing ShaderSource in NodeProps")
-                });
-            }
-        }
-
-        Err(EngineError::other(
-            "execute_plan: missing shader source (provide NodeProps for pass node, or connect ShaderSource -> ShaderPass)",
-        ))
-    };
-
-    // We'll store outputs for pass nodes here.
-    // (We use the RenderTarget cache in RuntimeState; this map is just for quick lookup.)
-    use std::collections::HashMap;
-    let mut outputs: HashMap<NodeId, (glow::NativeTexture, glow::NativeFramebuffer, i32, i32)> =
-        HashMap::new();
-
-    // Source outputs (Step 11.1): textures produced by Source-class nodes (e.g., TextureInputPass).
-    // These do not allocate render targets and do not run shaders.
-    let mut source_outputs: HashMap<NodeId, (glow::NativeTexture, i32, i32)> = HashMap::new();
+    // Release imports of shared surfaces the host stopped providing.
+    state.shared.retain(gl, |n| props.shared_surfaces.contains_key(&n));
 
     // Execute passes in plan order.
     for nid in &plan.nodes {
         let node = graph
             .node(*nid)
             .ok_or_else(|| EngineError::other("execute_plan: plan references missing node"))?;
+        // Fan-out contract (see `Plan`): each node renders once per frame and every consumer
+        // reads that result, even if a hand-built plan lists the node twice.
+        if outputs.contains_key(&node.id) || source_outputs.contains_key(&node.id) {
+            continue;
+        }
         // Step 11.1: Source nodes are resolved without rendering.
         if node.kind == NodeKind::TextureInputPass {
+            if let (None, Some(surface)) =
+                (props.texture_inputs.get(&node.id), props.shared_surfaces.get(&node.id))
+            {
+                let imported = state.shared.texture(gl, node.id, surface)?;
+                source_outputs.insert(node.id, imported);
+                continue;
+            }
             let tex = *props.texture_inputs.get(&node.id).ok_or_else(|| {
                 EngineError::Other(format!("TextureInputPass missing host texture for node {node:?}"))
             })?;
@@ -1092,7 +2729,17 @@ pub unsafe fn execute_plan(
         }
 
 
+        #[cfg(not(feature = "video"))]
         if node.kind == NodeKind::VideoDecodeSource {
+            return Err(EngineError::Other(format!(
+                "VideoDecodeSource #{} needs scheng-runtime-glow's `video` feature",
+                node.id.0
+            )));
+        }
+
+        #[cfg(feature = "video")]
+        if node.kind == NodeKind::VideoDecodeSource {
+            groups.push(gl, &format!("VideoDecodeSource #{}", node.id.0));
             // Engine-integrated video decode: ffmpeg -> RGBA -> host texture.
             let vn = if let Some(vn) = state.video_nodes.get_mut(&node.id) {
                 vn
@@ -1117,6 +2764,12 @@ pub unsafe fn execute_plan(
                 let tex = create_host_texture(gl, w, h);
                 // Clamp to at least 1.0 to avoid division by zero if someone passes 0.
                 let fps = cfg.fps.max(1) as f32;
+                let blend = match cfg.interpolation {
+                    input_video::FrameInterpolation::Off => None,
+                    input_video::FrameInterpolation::Blend => {
+                        Some(video_blend::FrameBlend::new(gl, w, h)?)
+                    }
+                };
 
                 state.video_nodes.insert(
                     node.id,
@@ -1128,6 +2781,8 @@ pub unsafe fn execute_plan(
                         fps,
                         // No frame uploaded yet.
                         last_frame_index: -1,
+                        failed: false,
+                        blend,
                     },
                 );
                 state.video_nodes.get_mut(&node.id).unwrap()
@@ -1146,18 +2801,43 @@ let timeline_index = if vn.fps > 0.0 {
         // Only sample a new decoded frame when the timeline advances past
         // the last index we uploaded. If time is paused (no change in
         // FrameCtx::time), this keeps the texture frozen (visual pause).
-        if timeline_index < 0 || timeline_index > vn.last_frame_index {
-            if let Ok(vf) = vn.dec.poll_rgba() {
-                if vf.width as i32 != vn.w || vf.height as i32 != vn.h {
+        if !vn.failed && (timeline_index < 0 || timeline_index > vn.last_frame_index) {
+            let polled = match guard::guarded(gl, || vn.dec.poll_rgba()) {
+                Err(message) | Ok(Err(input_video::VideoError::DecoderPanicked(message))) => {
+                    vn.failed = true;
+                    let component = format!("VideoDecodeSource #{}", node.id.0);
+                    state.events.push(guard::panicked(component, message));
+                    None
+                }
+                Ok(polled) => polled.ok(),
+            };
+            if let Some(vf) = polled {
+                let first = vn.last_frame_index < 0;
+                let resized = vf.width as i32 != vn.w || vf.height as i32 != vn.h;
+                if resized {
                     // Resolution changed (rare). Reallocate texture.
                     gl.delete_texture(vn.tex);
                     vn.w = vf.width as i32;
                     vn.h = vf.height as i32;
                     vn.tex = create_host_texture(gl, vn.w, vn.h);
+                    if let Some(blend) = &mut vn.blend {
+                        blend.resize(gl, vn.w, vn.h);
+                    }
                 }
 
-                unsafe {
-                    gl.bind_texture(glow::TEXTURE_2D, Some(vn.tex));
+                // Blending: the outgoing frame becomes the previous one. The first frame (and
+                // the first after a resize) fills both, so the blend starts from it.
+                let mut uploads = Vec::with_capacity(2);
+                if let Some(blend) = &mut vn.blend {
+                    if first || resized {
+                        uploads.push(blend.prev);
+                    } else {
+                        std::mem::swap(&mut blend.prev, &mut vn.tex);
+                    }
+                }
+                uploads.push(vn.tex);
+                for tex in uploads {
+                    gl.bind_texture(glow::TEXTURE_2D, Some(tex));
                     gl.tex_sub_image_2d(
                         glow::TEXTURE_2D,
                         0,
@@ -1177,30 +2857,68 @@ let timeline_index = if vn.fps > 0.0 {
         }
 
 
-            source_outputs.insert(node.id, (vn.tex, vn.w, vn.h));
+            checks.check(gl, Some(node.id), "video frame upload")?;
+            let weight = video_blend::blend_weight(frame.time, vn.fps, vn.last_frame_index);
+            let (blending, curr) = (vn.blend.is_some(), vn.tex);
+            if blending {
+                let (_, program) =
+                    state.cached_program(gl, FULLSCREEN_VERT, video_blend::VIDEO_BLEND_FRAG)?;
+                if let Some(blend) = state.video_nodes.get(&node.id).and_then(|v| v.blend.as_ref()) {
+                    blend.render(gl, &state.fs_tri, program, curr, weight);
+                }
+                checks.check(gl, Some(node.id), "video frame blend")?;
+            }
+            source_outputs.insert(node.id, state.video_nodes[&node.id].output());
+            groups.pop(gl);
             continue;
         }
 
-        let is_render_pass =
-            node.kind == NodeKind::ShaderPass || node.kind.class() == NodeClass::Mixer;
+        let is_render_pass = is_render_node(&node.kind);
         if !is_render_pass {
             continue;
         }
+        // Branch format (see `Graph::set_branch_format`): this pass's size and aspect.
+        let format = plan.format(node.id).copied();
+        let frame = match format {
+            Some(f) => {
+                let (w, h) = f.size.map_or((frame.width, frame.height), |(w, h)| {
+                    let scale = state.render_scale;
+                    (
+                        ((w as f32 * scale).round() as i32).max(1),
+                        ((h as f32 * scale).round() as i32).max(1),
+                    )
+                });
+                FrameCtx {
+                    width: w,
+                    height: h,
+                    aspect: f.display_aspect(w, h),
+                    ..frame
+                }
+            }
+            None => frame,
+        };
+
 
+        let outs = node.ports.iter().filter(|p| p.dir == PortDir::Out).count();
+        state
+            .caps
+            .check_node(&node.kind, outs, frame.width, frame.height)
+            .map_err(|e| {
+                EngineError::Other(format!("node #{} ({:?}): {e}", node.id.0, node.kind))
+            })?;
 
         // Ensure ping-pong targets exist for this node and match frame size.
         // We use `pp.curr` as the "previous frame" texture for the `history` input.
         let history_tex: glow::NativeTexture = {
             if let std::collections::hash_map::Entry::Vacant(e) = state.targets.entry(node.id) {
-                let curr = create_render_target(gl, frame.width, frame.height)?;
-                let prev = create_render_target(gl, frame.width, frame.height)?;
-                e.insert(PingPong { curr, prev });
+                e.insert(PingPong::new(gl, frame.width, frame.height)?);
             }
             let pp = state
                 .targets
                 .get_mut(&node.id)
                 .expect("just inserted ping-pong targets");
             pp.ensure_size(gl, frame.width, frame.height);
+            pp.ensure_attachments(gl, outs.clamp(1, MAX_PASS_OUTPUTS) - 1)?;
             pp.curr.tex
         };
 
@@ -1229,16 +2947,21 @@ let timeline_index = if vn.fps > 0.0 {
             let from_node = graph
                 .node(e.from.node)
                 .ok_or_else(|| EngineError::other("execute_plan: edge references missing node"))?;
-            let from_is_render_pass =
-                from_node.kind == NodeKind::ShaderPass || from_node.kind.class() == NodeClass::Mixer;
+            let from_is_render_pass = is_render_node(&from_node.kind);
 
             if from_is_render_pass {
-                let tex = if let Some((t, _f, _w, _h)) = outputs.get(&from_node.id) {
-                    *t
-                } else if let Some(pp) = state.targets.get(&from_node.id) {
-                    pp.curr.tex
-                } else {
-                    continue;
+                let out_index = output_index(graph, e.from);
+                let rendered = outputs.contains_key(&from_node.id);
+                // Delayed edge: the producer's previous frame. If it already rendered this
+                // frame that is `prev` (after its swap), otherwise still `curr`.
+                let target = state
+                    .targets
+                    .get(&from_node.id)
+                    .and_then(|pp| pp.output(out_index, e.delayed && rendered));
+                let tex = match (outputs.get(&from_node.id), target) {
+                    (Some((t, ..)), _) if !e.delayed && out_index == 0 => *t,
+                    (_, Some(t)) => t.tex,
+                    _ => continue,
                 };
                 inputs.push((ch, tex));
                 continue;
@@ -1267,22 +2990,226 @@ let timeline_index = if vn.fps > 0.0 {
             // ShaderSource edges are allowed only for shader resolution; they don't produce textures.
             continue;
         }
+        // Unconnected inputs sample their `InputDefault` (black unless the graph sets one), so
+        // a partial patch never reads whatever the unit last held. `unset_inputs` are the
+        // implicit ones, which KeyMix treats as "no matte".
+        let mut unset_inputs: Vec<u32> = Vec::new();
+        for p in node.ports.iter().filter(|p| p.dir == PortDir::In) {
+            let at = Endpoint { node: node.id, port: p.id, dir: PortDir::In };
+            let Some(ch) = port_channel_index(node.id, p.id) else {
+                continue;
+            };
+            if graph.driver_of(at).is_some() {
+                continue;
+            }
+            let default = graph.input_default(at);
+            if default.is_none() {
+                unset_inputs.push(ch);
+            }
+            let rgba = default.unwrap_or_default().rgba();
+            inputs.push((ch, state.solid_texture(gl, rgba)));
+        }
+        // Two-frame analysis: channel 1 is the node's copy of its input from the previous frame.
+        let motion_params = props.motion_params.get(&node.id).copied().unwrap_or_default();
+        // Bypassed nodes (see `RuntimeState::set_node_bypassed`, or a bypassed group) copy their
+        // first input instead.
+        let bypassed = state.bypassed.contains(&node.id)
+            || GroupControls::node_bypassed(&state.params, graph, node.id);
+        let uses_prev_input =
+            !bypassed && matches!(node.kind, NodeKind::MotionDetect | NodeKind::OpticalFlow);
+        if uses_prev_input {
+            if let std::collections::hash_map::Entry::Vacant(e) = state.prev_inputs.entry(node.id) {
+                e.insert(analysis::PrevInput::new(gl, frame.width, frame.height)?);
+            }
+            let pi = state
+                .prev_inputs
+                .get_mut(&node.id)
+                .expect("just inserted previous-input target");
+            pi.ensure(gl, frame.width, frame.height);
+            inputs.retain(|(ch, _)| *ch == 0);
+            inputs.push((1, pi.tex()));
+        }
+        // Dither: channel 1 is the threshold map for the node's mode.
+        let builtin = !props.shader_sources.contains_key(&node.id)
+            && props.shader_banks.get(&node.id).is_none_or(|b| b.slots.is_empty());
+        if !bypassed && node.kind == NodeKind::Dither && builtin {
+            let mode = props.dither_params.get(&node.id).map(|p| p.mode).unwrap_or_default();
+            inputs.retain(|(ch, _)| *ch == 0);
+            inputs.push((1, state.dither_texture(gl, mode)));
+        }
+        if !bypassed && node.kind == NodeKind::MotionDetect {
+            if let std::collections::hash_map::Entry::Vacant(e) = state.motion_nodes.entry(node.id) {
+                e.insert(analysis::MotionState::new(gl, &motion_params)?);
+            }
+            if let Some(ms) = state.motion_nodes.get_mut(&node.id) {
+                ms.ensure(gl, &motion_params);
+            }
+        }
+
         // Ensure deterministic binding order.
         inputs.sort_by_key(|(ch, _)| *ch);
-        // Ensure program cached and up-to-date (shared across nodes).
-        let shader = resolve_shader(node.id)?;
-        let key = ProgramKey {
-            vert_hash: hash_str(&shader.vert),
-            frag_hash: hash_str(&shader.frag),
-        };
 
-        let cached_prog = if let Some(p) = state.program_cache.get(&key) {
-            *p
+        // Own previous output (`iSelf`) and buffer-set samplers, on units after the inputs.
+        // Other buffers read `curr`: this frame's output if they already rendered, else the last.
+        let mut state_samplers: Vec<(&str, glow::NativeTexture)> =
+            vec![(buffers::SELF_UNIFORM, history_tex)];
+        if let Some(set) = props.buffer_sets.iter().find(|s| s.contains(node.id)) {
+            for (name, id) in set.bindings() {
+                let tex = if id == node.id {
+                    Some(history_tex)
+                } else {
+                    state.targets.get(&id).map(|pp| pp.curr.tex)
+                };
+                if let Some(tex) = tex {
+                    state_samplers.push((name, tex));
+                }
+            }
+        }
+
+        let clear_mode = props.clear_modes.get(&node.id).copied().unwrap_or_default();
+
+        // Host executors replace the shader entirely (bypass still wins).
+        if let Some(exec) = state.executors.get_mut(&node.id).filter(|_| !bypassed) {
+            groups.push(gl, &format!("{:?} #{} (executor)", node.kind, node.id.0));
+            let pp = state
+                .targets
+                .get_mut(&node.id)
+                .expect("ping-pong targets exist");
+            pp.swap();
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(pp.curr.fbo));
+            gl.viewport(0, 0, pp.curr.w, pp.curr.h);
+            gl.disable(glow::DEPTH_TEST);
+            pp.begin_draw(gl, clear_mode);
+            let tgt = &pp.curr;
+            // Executors may read the standard block; their programs are not reflected here.
+            state.frame_block.update(gl, &frame)?;
+            let ctx = NodeExecCtx {
+                node: node.id,
+                fbo: tgt.fbo,
+                tex: tgt.tex,
+                width: tgt.w,
+                height: tgt.h,
+                frame,
+                inputs: &inputs,
+            };
+            exec.execute(gl, &ctx)?;
+            checks.check(gl, Some(node.id), &format!("{:?} executor", node.kind))?;
+            outputs.insert(node.id, (tgt.tex, tgt.fbo, tgt.w, tgt.h));
+            groups.pop(gl);
+            continue;
+        }
+        // Built-in particle system: its own update and point-sprite passes.
+        if node.kind == NodeKind::Particles && !bypassed {
+            let params = props.particle_params.get(&node.id).copied().unwrap_or_default();
+            if state.particles.get(&node.id).is_some_and(|ps| !ps.matches(&params)) {
+                if let Some(old) = state.particles.remove(&node.id) {
+                    old.destroy(gl);
+                }
+            }
+            if let std::collections::hash_map::Entry::Vacant(e) = state.particles.entry(node.id) {
+                e.insert(ParticleSystem::new(gl, &params)?);
+            }
+            groups.push(gl, &format!("{:?} #{} (builtin)", node.kind, node.id.0));
+            let pp = state
+                .targets
+                .get_mut(&node.id)
+                .expect("ping-pong targets exist");
+            pp.swap();
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(pp.curr.fbo));
+            gl.viewport(0, 0, pp.curr.w, pp.curr.h);
+            gl.disable(glow::DEPTH_TEST);
+            pp.begin_draw(gl, clear_mode);
+            let tgt = &pp.curr;
+            let ps = state.particles.get_mut(&node.id).expect("particle system exists");
+            ps.step(gl, &state.fs_tri, &params, frame.time, frame.dt, tgt.fbo, tgt.w, tgt.h);
+            checks.check(gl, Some(node.id), "particles update / draw")?;
+            outputs.insert(node.id, (tgt.tex, tgt.fbo, tgt.w, tgt.h));
+            groups.pop(gl);
+            continue;
+        }
+        // Built-in audio visualizers: host samples reduced on the CPU, drawn by one program.
+        if matches!(node.kind, NodeKind::AudioWaveform | NodeKind::AudioSpectrum) && !bypassed {
+            let params = props.audio_vis_params.get(&node.id).copied().unwrap_or_default();
+            if let std::collections::hash_map::Entry::Vacant(e) = state.audio_visuals.entry(node.id)
+            {
+                e.insert(audio_vis::AudioVisual::new(gl)?);
+            }
+            groups.push(gl, &format!("{:?} #{} (builtin)", node.kind, node.id.0));
+            let pp = state
+                .targets
+                .get_mut(&node.id)
+                .expect("ping-pong targets exist");
+            pp.swap();
+            let tgt = &pp.curr;
+            let vis = state.audio_visuals.get_mut(&node.id).expect("audio visual exists");
+            vis.render(
+                gl,
+                &state.fs_tri,
+                &node.kind,
+                &params,
+                &props.audio_samples,
+                tgt.fbo,
+                tgt.w,
+                tgt.h,
+            );
+            checks.check(gl, Some(node.id), "audio visualizer")?;
+            outputs.insert(node.id, (tgt.tex, tgt.fbo, tgt.w, tgt.h));
+            groups.pop(gl);
+            continue;
+        }
+        // Built-in bloom: its own mip chain, composited over the input.
+        if node.kind == NodeKind::Bloom && !bypassed {
+            let params = props.bloom_params.get(&node.id).copied().unwrap_or_default();
+            let src = inputs
+                .iter()
+                .find(|(ch, _)| *ch == 0)
+                .map(|(_, tex)| *tex)
+                .ok_or_else(|| EngineError::other("Bloom: missing input texture"))?;
+            if let std::collections::hash_map::Entry::Vacant(e) = state.blooms.entry(node.id) {
+                e.insert(BloomChain::new(gl, state.caps.float16_targets)?);
+            }
+            groups.push(gl, &format!("{:?} #{} (builtin)", node.kind, node.id.0));
+            let pp = state
+                .targets
+                .get_mut(&node.id)
+                .expect("ping-pong targets exist");
+            pp.swap();
+            let tgt = &pp.curr;
+            let chain = state.blooms.get_mut(&node.id).expect("bloom chain exists");
+            chain.render(gl, &state.fs_tri, &params, src, tgt.fbo, tgt.w, tgt.h)?;
+            checks.check(gl, Some(node.id), "bloom chain")?;
+            outputs.insert(node.id, (tgt.tex, tgt.fbo, tgt.w, tgt.h));
+            groups.pop(gl);
+            continue;
+        }
+
+        // Ensure program cached and up-to-date (shared across nodes).
+        let shader = if bypassed {
+            ShaderSource {
+                vert: FULLSCREEN_VERT.to_string(),
+                frag: TEX_INPUT_FRAG.to_string(),
+                origin: Some("bypass".to_string()),
+            }
         } else {
-            let p = compile_program(gl, &shader.vert, &shader.frag)?;
-            state.program_cache.insert(key, p);
-            p
+            if let Some(bank) = props.shader_banks.get(&node.id) {
+                state.warm_shader_bank_logged(gl, graph, node.id, bank);
+            }
+            resolve_pass_shader(graph, props, &state.params, node.id)?
         };
+        groups.push(
+            gl,
+            &format!(
+                "{:?} #{} ({})",
+                node.kind,
+                node.id.0,
+                shader.origin.as_deref().unwrap_or("inline")
+            ),
+        );
+        let (key, cached_prog) = state.cached_program(gl, &shader.vert, &shader.frag)?;
+        if !bypassed {
+            state.register_shader_params(graph, node.id, key, &shader.frag);
+            state.note_shader_source(node.id, key);
+        }
 
         let needs_rebind = match state.programs.get(&node.id) {
             Some(stored) => stored.key != key,
@@ -1311,14 +3238,27 @@ let timeline_index = if vn.fps > 0.0 {
             .get_mut(&node.id)
             .expect("ping-pong targets exist");
         pp.swap();
-        let tgt = &pp.curr;
 
         // Render.
-        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(tgt.fbo));
-        gl.viewport(0, 0, tgt.w, tgt.h);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(pp.curr.fbo));
+        gl.viewport(0, 0, pp.curr.w, pp.curr.h);
         gl.disable(glow::DEPTH_TEST);
-        gl.clear_color(0.0, 0.0, 0.0, 1.0);
-        gl.clear(glow::COLOR_BUFFER_BIT);
+        pp.begin_draw(gl, clear_mode);
+        let tgt = &pp.curr;
+
+        let hook_ctx = NodeHookCtx {
+            node: node.id,
+            fbo: tgt.fbo,
+            tex: tgt.tex,
+            width: tgt.w,
+            height: tgt.h,
+            frame,
+        };
+        let hooks = state.hooks.get_mut(&node.id);
+        if let Some(pre) = hooks.and_then(|h| h.pre.as_mut()) {
+            pre(gl, &hook_ctx)?;
+            checks.check(gl, Some(node.id), &format!("{:?} pre-render hook", node.kind))?;
+        }
 
         gl.use_program(Some(prog));
 
@@ -1330,6 +3270,47 @@ let timeline_index = if vn.fps > 0.0 {
                     if let Some(loc) = gl.get_uniform_location(prog, "uMix") {
                         gl.uniform_1_f32(Some(&loc), p.mix);
                     }
+                    let inputs = group_inputs(graph, &state.params, node.id, p.inputs);
+                    set_input_adjust(gl, prog, &inputs);
+                }
+                MixerOp::Add | MixerOp::Multiply => {
+                    let inputs = props.mixer_params.get(&node.id).copied().unwrap_or_default();
+                    let inputs = group_inputs(graph, &state.params, node.id, inputs.inputs);
+                    set_input_adjust(gl, prog, &inputs);
+                    let p = props.blend_params.get(&node.id).copied().unwrap_or_default();
+                    if let Some(loc) = gl.get_uniform_location(prog, "uGainA") {
+                        gl.uniform_1_f32(Some(&loc), p.gain_a);
+                    }
+                    if let Some(loc) = gl.get_uniform_location(prog, "uGainB") {
+                        gl.uniform_1_f32(Some(&loc), p.gain_b);
+                    }
+                }
+                MixerOp::KeyMix => {
+                    let p = props.key_params.get(&node.id).copied().unwrap_or_default();
+                    let [r, g, b] = p.key_color;
+                    if let Some(loc) = gl.get_uniform_location(prog, "uKeyColor") {
+                        gl.uniform_3_f32(Some(&loc), r, g, b);
+                    }
+                    let floats = [
+                        ("uThreshold", p.threshold),
+                        ("uSoftness", p.softness.max(0.0)),
+                        ("uSpill", p.spill.clamp(0.0, 1.0)),
+                    ];
+                    for (name, v) in floats {
+                        if let Some(loc) = gl.get_uniform_location(prog, name) {
+                            gl.uniform_1_f32(Some(&loc), v);
+                        }
+                    }
+                    let ints = [
+                        ("uLumaMode", (p.mode == scheng_runtime::KeyMode::Luma) as i32),
+                        ("uHasMatte", !unset_inputs.contains(&2) as i32),
+                        ("uInvertMatte", p.invert_matte as i32),
+                    ];
+                    for (name, v) in ints {
+                        if let Some(loc) = gl.get_uniform_location(prog, name) {
+                            gl.uniform_1_i32(Some(&loc), v);
+                        }
+                    }
                 }
                 MixerOp::MatrixMix4 => {
                     let p = props.matrix_params.get(&node.id).copied().unwrap_or_default();
@@ -1342,30 +3323,86 @@ let timeline_index = if vn.fps > 0.0 {
                             p.weights[3],
                         );
                     }
+                    let flags = [
+                        ("uNormalize", p.normalize),
+                        ("uClampOutput", p.clamp_output),
+                        ("uAllowNegative", p.allow_negative),
+                    ];
+                    for (name, on) in flags {
+                        if let Some(loc) = gl.get_uniform_location(prog, name) {
+                            gl.uniform_1_i32(Some(&loc), on as i32);
+                        }
+                    }
+                    let inputs = group_inputs(graph, &state.params, node.id, p.inputs);
+                    set_input_adjust(gl, prog, &inputs);
                 }
-                _ => {}
             }
         }
-
-        // Common uniforms (set if present).
-        if let Some(loc) = gl.get_uniform_location(prog, "uTime") {
-            gl.uniform_1_f32(Some(&loc), frame.time);
+        if let Some(StandardOp::Filter(op)) = standard_op_for(node.kind.clone()) {
+            let (name, value) = match op {
+                FilterOp::Pixelate => {
+                    let p = props.pixelate_params.get(&node.id).copied().unwrap_or_default();
+                    ("uBlockSize", p.block_size.max(1.0))
+                }
+                FilterOp::Posterize => {
+                    let p = props.posterize_params.get(&node.id).copied().unwrap_or_default();
+                    ("uLevels", p.levels.max(2) as f32)
+                }
+                FilterOp::Dither => {
+                    let p = props.dither_params.get(&node.id).copied().unwrap_or_default();
+                    if let Some(loc) = gl.get_uniform_location(prog, "uAmount") {
+                        gl.uniform_1_f32(Some(&loc), p.amount.max(0.0));
+                    }
+                    ("uLevels", p.levels.max(2) as f32)
+                }
+            };
+            if let Some(loc) = gl.get_uniform_location(prog, name) {
+                gl.uniform_1_f32(Some(&loc), value);
+            }
         }
-        if let Some(loc) = gl.get_uniform_location(prog, "uResolution") {
-            gl.uniform_2_f32(Some(&loc), frame.width as f32, frame.height as f32);
+        if node.kind == NodeKind::MotionDetect {
+            if let Some(loc) = gl.get_uniform_location(prog, "uThreshold") {
+                gl.uniform_1_f32(Some(&loc), motion_params.threshold);
+            }
+            if let Some(loc) = gl.get_uniform_location(prog, "uGain") {
+                gl.uniform_1_f32(Some(&loc), motion_params.gain);
+            }
         }
-        if let Some(loc) = gl.get_uniform_location(prog, "u_time") {
-            gl.uniform_1_f32(Some(&loc), frame.time);
+        if node.kind == NodeKind::OpticalFlow {
+            let p = props.flow_params.get(&node.id).copied().unwrap_or_default();
+            if let Some(loc) = gl.get_uniform_location(prog, "uRadius") {
+                gl.uniform_1_i32(Some(&loc), p.window_radius.clamp(1, 4) as i32);
+            }
+            if let Some(loc) = gl.get_uniform_location(prog, "uLambda") {
+                gl.uniform_1_f32(Some(&loc), p.lambda);
+            }
+            if let Some(loc) = gl.get_uniform_location(prog, "uGain") {
+                gl.uniform_1_f32(Some(&loc), p.gain);
+            }
         }
-        if let Some(loc) = gl.get_uniform_location(prog, "u_resolution") {
-            gl.uniform_2_f32(Some(&loc), frame.width as f32, frame.height as f32);
+        if node.kind == NodeKind::FlowDisplace {
+            let p = props.displace_params.get(&node.id).copied().unwrap_or_default();
+            if let Some(loc) = gl.get_uniform_location(prog, "uAmount") {
+                gl.uniform_1_f32(Some(&loc), p.amount);
+            }
         }
-        // Shadertoy-ish names (optional).
-        if let Some(loc) = gl.get_uniform_location(prog, "iTime") {
-            gl.uniform_1_f32(Some(&loc), frame.time);
+        if let Some(loc) = gl.get_uniform_location(prog, "uEncodeScale") {
+            gl.uniform_1_f32(Some(&loc), scheng_runtime::FLOW_ENCODING_SCALE);
         }
-        if let Some(loc) = gl.get_uniform_location(prog, "iResolution") {
-            gl.uniform_3_f32(Some(&loc), frame.width as f32, frame.height as f32, 1.0);
+
+        // Standard uniforms (runtime_contract::STANDARD_UNIFORMS), reflected once per program.
+        let node_values = NodeUniformValues {
+            seed: node_seed_f32(&graph.handle(node.id), frame.seed),
+            reload_count: state.reloads.get(&node.id).map_or(0, |r| r.1),
+        };
+        let std_uniforms = state
+            .uniform_reflection
+            .entry(key)
+            .or_insert_with(|| StandardUniformLocations::reflect(gl, prog));
+        apply_standard_uniforms(gl, std_uniforms, &frame);
+        apply_node_uniforms(gl, std_uniforms, &node_values);
+        if std_uniforms.uses_frame_block() {
+            state.frame_block.update(gl, &frame)?;
         }
 
         // Bind input textures by semantic port order (Option A).
@@ -1387,48 +3424,150 @@ let timeline_index = if vn.fps > 0.0 {
             }
         }
 
+        for (i, (name, tex)) in state_samplers.iter().enumerate() {
+            if let Some(loc) = gl.get_uniform_location(prog, name) {
+                let unit = SELF_UNIT + i as u32;
+                gl.active_texture(glow::TEXTURE0 + unit);
+                gl.bind_texture(glow::TEXTURE_2D, Some(*tex));
+                gl.uniform_1_i32(Some(&loc), unit as i32);
+            }
+        }
+        gl.active_texture(glow::TEXTURE0);
+
         // Custom per-node f32 uniforms (bridge-controlled, hotpatchable without recompile).
-        if let Some(uniforms) = props.custom_uniforms.get(&node.id) {
+        let node_uniforms = props.custom_uniforms.get(&node.id);
+        if let Some(uniforms) = node_uniforms {
             for (name, &value) in uniforms {
                 if let Some(loc) = gl.get_uniform_location(prog, name) {
                     gl.uniform_1_f32(Some(&loc), value);
                 }
             }
         }
+        // `@param` controls from the registry, unless the node's custom uniforms set the name.
+        let shader_params = state.shader_params.get(&node.id).map(|p| p.params.as_slice());
+        for (name, param) in shader_params.unwrap_or_default() {
+            if node_uniforms.is_some_and(|u| u.contains_key(name)) {
+                continue;
+            }
+            if let (Some(value), Some(loc)) =
+                (state.params.get(param), gl.get_uniform_location(prog, name))
+            {
+                gl.uniform_1_f32(Some(&loc), value);
+            }
+        }
+        // Global uniforms, unless the engine, the node or one of its `@param`s sets the name.
+        for (name, &value) in &props.global_uniforms {
+            if standard_uniform(name).is_some()
+                || node_uniforms.is_some_and(|u| u.contains_key(name))
+                || shader_params.is_some_and(|p| p.iter().any(|(u, _)| u == name))
+            {
+                continue;
+            }
+            if let Some(loc) = gl.get_uniform_location(prog, name) {
+                gl.uniform_1_f32(Some(&loc), value);
+            }
+        }
         
-        state.fs_tri.draw(gl);
+        if node.kind == NodeKind::GeometryPass && !bypassed {
+            let geometry = props.geometry.get(&node.id).cloned().unwrap_or_default();
+            if state.meshes.get(&node.id).is_none_or(|m| m.source != geometry) {
+                if let Some(old) = state.meshes.remove(&node.id) {
+                    old.destroy(gl);
+                }
+                state.meshes.insert(node.id, GpuMesh::new(gl, &geometry)?);
+            }
+            state.meshes[&node.id].draw(gl);
+        } else if let Some(crop) = format.filter(|f| f.is_cropped()).map(|f| f.crop) {
+            if state.crop_tris.get(&node.id).is_none_or(|(r, _)| *r != crop) {
+                if let Some((_, mut old)) = state.crop_tris.remove(&node.id) {
+                    old.destroy(gl);
+                }
+                let tri = FullscreenTriangle::with_uv_rect(gl, crop)?;
+                state.crop_tris.insert(node.id, (crop, tri));
+            }
+            state.crop_tris[&node.id].1.draw(gl);
+        } else {
+            state.fs_tri.draw(gl);
+        }
+        checks.check(gl, Some(node.id), &format!("{:?} draw", node.kind))?;
+        if let Some(post) = state.hooks.get_mut(&node.id).and_then(|h| h.post.as_mut()) {
+            post(gl, &hook_ctx)?;
+            checks.check(gl, Some(node.id), &format!("{:?} post-render hook", node.kind))?;
+        }
 
         // Record output.
+        let out_tex = tgt.tex;
         outputs.insert(node.id, (tgt.tex, tgt.fbo, tgt.w, tgt.h));
+
+        if !bypassed && node.kind == NodeKind::MotionDetect {
+            let (_, reduce_prog) =
+                state.cached_program(gl, FULLSCREEN_VERT, analysis::MOTION_REDUCE_FRAG)?;
+            let primed = state.prev_inputs.get(&node.id).is_some_and(|pi| pi.primed());
+            if let Some(ms) = state.motion_nodes.get_mut(&node.id) {
+                ms.publish(
+                    gl,
+                    &state.fs_tri,
+                    reduce_prog,
+                    graph,
+                    node.id,
+                    out_tex,
+                    primed,
+                    &mut state.params,
+                );
+            }
+            checks.check(gl, Some(node.id), "motion reduction / readback")?;
+        }
+        if uses_prev_input {
+            let (_, copy_prog) = state.cached_program(gl, FULLSCREEN_VERT, TEX_INPUT_FRAG)?;
+            let input_tex = inputs.iter().find(|(ch, _)| *ch == 0).map(|(_, t)| *t);
+            if let (Some(pi), Some(tex)) = (state.prev_inputs.get_mut(&node.id), input_tex) {
+                pi.capture(gl, &state.fs_tri, copy_prog, tex);
+            }
+            checks.check(gl, Some(node.id), "previous-input capture")?;
+        }
+        groups.pop(gl);
+    }
+
+    accumulate_temporal(gl, graph, plan, state, props, frame, checks)?;
+    convert_output_alpha(gl, graph, plan, state, props, checks)?;
+    let last_pass = state.alpha_outputs.get(&out_node).map(|ao| ao.output());
+    if let Some(t) = last_pass.or_else(|| state.temporal.get(&out_node).map(|ts| ts.output())) {
+        return Ok(ExecOutput {
+            tex: t.tex,
+            fbo: t.fbo,
+            width: t.w,
+            height: t.h,
+        });
     }
 
     // Resolve final output texture from PixelsOut's incoming edge.
     let out_edge = graph
-        .edges()
-        .iter()
-        .find(|e| e.to.node == out_node && e.to.dir == PortDir::In)
+        .incoming(out_node)
+        .next()
         .ok_or_else(|| EngineError::other("execute_plan: PixelsOut has no input edge"))?;
 
     let from_node = graph
         .node(out_edge.from.node)
         .ok_or_else(|| EngineError::other("execute_plan: output edge references missing node"))?;
 
-    let from_is_render_pass =
-        from_node.kind == NodeKind::ShaderPass || from_node.kind.class() == NodeClass::Mixer;
+    let from_is_render_pass = is_render_node(&from_node.kind);
     if !from_is_render_pass {
         return Err(EngineError::other(
             "execute_plan: PixelsOut input must come from a render pass (ShaderPass or Mixer) in v1",
         ));
     }
 
+    let out_index = output_index(graph, out_edge.from);
     let (tex, fbo, w, h) = outputs
         .get(&from_node.id)
         .copied()
+        .filter(|_| out_index == 0)
         .or_else(|| {
             state
                 .targets
                 .get(&from_node.id)
-                .map(|pp| (pp.curr.tex, pp.curr.fbo, pp.curr.w, pp.curr.h))
+                .and_then(|pp| pp.output(out_index, false))
+                .map(|t| (t.tex, t.fbo, t.w, t.h))
         })
         .ok_or_else(|| EngineError::other("execute_plan: missing output texture for final pass"))?;
 
@@ -1440,7 +3579,155 @@ let timeline_index = if vn.fps > 0.0 {
     })
 }
 
+/// Run the temporal pass for every `PixelsOut` with `temporal_params` (after all nodes have
+/// rendered, so `curr` targets hold this frame) and drop history for outputs that lost theirs.
+unsafe fn accumulate_temporal(
+    gl: &glow::Context,
+    graph: &Graph,
+    plan: &Plan,
+    state: &mut RuntimeState,
+    props: &NodeProps,
+    frame: FrameCtx,
+    checks: GlChecks,
+) -> Result<(), EngineError> {
+    let stale: Vec<NodeId> = state
+        .temporal
+        .keys()
+        .filter(|id| !props.temporal_params.contains_key(id))
+        .copied()
+        .collect();
+    for id in stale {
+        if let Some(ts) = state.temporal.remove(&id) {
+            ts.destroy(gl);
+        }
+    }
+    if props.temporal_params.is_empty() {
+        return Ok(());
+    }
+
+    let (_, program) = state.cached_program(gl, FULLSCREEN_VERT, temporal::TEMPORAL_FRAG)?;
+    for id in &plan.nodes {
+        let Some(params) = props.temporal_params.get(id) else {
+            continue;
+        };
+        if graph.node(*id).map(|n| &n.kind) != Some(&NodeKind::PixelsOut) {
+            continue;
+        }
+        let Some(edge) = graph.incoming(*id).next() else {
+            continue;
+        };
+        let Some(src) = state
+            .targets
+            .get(&edge.from.node)
+            .and_then(|pp| pp.output(output_index(graph, edge.from), false))
+            .map(|t| (t.tex, t.w, t.h))
+        else {
+            continue;
+        };
+        let (tex, w, h) = src;
+        if let std::collections::hash_map::Entry::Vacant(e) = state.temporal.entry(*id) {
+            e.insert(temporal::TemporalState::new(gl, w, h)?);
+        }
+        let ts = state.temporal.get_mut(id).expect("temporal state exists");
+        ts.accumulate(gl, &state.fs_tri, program, params, tex, frame.frame, w, h);
+        checks.check(gl, Some(*id), "temporal accumulation")?;
+    }
+    Ok(())
+}
+
+/// Convert every `PixelsOut` with a converting `output_alpha` policy (after temporal smoothing)
+/// and drop the targets of outputs that no longer convert.
+unsafe fn convert_output_alpha(
+    gl: &glow::Context,
+    graph: &Graph,
+    plan: &Plan,
+    state: &mut RuntimeState,
+    props: &NodeProps,
+    checks: GlChecks,
+) -> Result<(), EngineError> {
+    let policy = |id: &NodeId| props.output_alpha.get(id).copied().unwrap_or_default();
+    let stale: Vec<NodeId> = state
+        .alpha_outputs
+        .keys()
+        .filter(|id| !policy(id).converts())
+        .copied()
+        .collect();
+    for id in stale {
+        if let Some(ao) = state.alpha_outputs.remove(&id) {
+            ao.destroy(gl);
+        }
+    }
+    if !props.output_alpha.values().any(|p| p.converts()) {
+        return Ok(());
+    }
+
+    let (_, program) = state.cached_program(gl, FULLSCREEN_VERT, alpha::ALPHA_FRAG)?;
+    for id in &plan.nodes {
+        if !policy(id).converts() {
+            continue;
+        }
+        if graph.node(*id).map(|n| &n.kind) != Some(&NodeKind::PixelsOut) {
+            continue;
+        }
+        let Some(edge) = graph.incoming(*id).next() else {
+            continue;
+        };
+        let Some((tex, w, h)) = state
+            .temporal
+            .get(id)
+            .map(|ts| ts.output())
+            .or_else(|| {
+                state
+                    .targets
+                    .get(&edge.from.node)
+                    .and_then(|pp| pp.output(output_index(graph, edge.from), false))
+            })
+            .map(|t| (t.tex, t.w, t.h))
+        else {
+            continue;
+        };
+        if let std::collections::hash_map::Entry::Vacant(e) = state.alpha_outputs.entry(*id) {
+            e.insert(alpha::AlphaOutput::new(gl, w, h)?);
+        }
+        let ao = state.alpha_outputs.get_mut(id).expect("alpha output exists");
+        ao.convert(gl, &state.fs_tri, program, policy(id), tex, w, h);
+        checks.check(gl, Some(*id), "output alpha conversion")?;
+    }
+    Ok(())
+}
+
+/// A sink plus the output size it last saw, so [`execute_plan_to_sink`] calls `on_resize` when
+/// the size changes for *this* sink (and on its first frame), whatever else shares the state.
+pub struct TrackedSink<S> {
+    sink: S,
+    last_size: Option<(i32, i32)>,
+}
+
+impl<S: OutputSink> TrackedSink<S> {
+    pub fn new(sink: S) -> Self {
+        Self {
+            sink,
+            last_size: None,
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.sink
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    pub fn into_inner(self) -> S {
+        self.sink
+    }
+}
+
 /// S2: Execute a frame and immediately route the final output into a sink.
+///
+/// Drives the sink lifecycle hooks (`on_frame_begin`, `on_resize`, `consume`, `on_frame_end`).
+/// `on_frame_end` runs even when the plan fails, so every `on_frame_begin` is paired.
 pub unsafe fn execute_plan_to_sink<S: OutputSink>(
     gl: &glow::Context,
     graph: &Graph,
@@ -1448,10 +3735,23 @@ pub unsafe fn execute_plan_to_sink<S: OutputSink>(
     state: &mut RuntimeState,
     props: &NodeProps,
     frame: FrameCtx,
-    sink: &mut S,
+    sink: &mut TrackedSink<S>,
 ) -> Result<ExecOutput, EngineError> {
-    let out = execute_plan(gl, graph, plan, state, props, frame)?;
-    sink.consume(gl, &out);
+    sink.sink.on_frame_begin(gl, &frame);
+    let out = match execute_plan(gl, graph, plan, state, props, frame) {
+        Ok(out) => out,
+        Err(e) => {
+            sink.sink.on_frame_end(gl);
+            return Err(e);
+        }
+    };
+    let size = (out.width, out.height);
+    if sink.last_size != Some(size) {
+        sink.last_size = Some(size);
+        sink.sink.on_resize(gl, out.width, out.height);
+    }
+    sink.sink.consume(gl, &out);
+    sink.sink.on_frame_end(gl);
     Ok(out)
 }
 
@@ -1464,8 +3764,26 @@ pub struct FullscreenTriangle {
 
 impl FullscreenTriangle {
     pub unsafe fn new(gl: &glow::Context) -> Result<Self, EngineError> {
+        Self::with_uv_rect(gl, [0.0, 0.0, 1.0, 1.0])
+    }
+
+    /// A triangle whose `a_uv` spans `[x, y, w, h]` across the target instead of `0..1`, so
+    /// passes sampling at `v_uv` see only that region of their inputs (branch crops).
+    pub unsafe fn with_uv_rect(gl: &glow::Context, rect: [f32; 4]) -> Result<Self, EngineError> {
+        let [x, y, w, h] = rect;
         let verts: [f32; 12] = [
-            -1.0, -1.0, 0.0, 0.0, 3.0, -1.0, 2.0, 0.0, -1.0, 3.0, 0.0, 2.0,
+            -1.0,
+            -1.0,
+            x,
+            y,
+            3.0,
+            -1.0,
+            x + 2.0 * w,
+            y,
+            -1.0,
+            3.0,
+            x,
+            y + 2.0 * h,
         ];
 
         let vao = gl
@@ -1529,11 +3847,107 @@ void main(){ o = texture(iChannel0, v_uv); }
 pub fn builtin_mixer_frag(op: MixerOp) -> &'static str {
     match op {
         MixerOp::Crossfade => CROSSFADE_FRAG,
+        MixerOp::Add => ADD_FRAG,
+        MixerOp::Multiply => MULTIPLY_FRAG,
+        MixerOp::KeyMix => KEYMIX_FRAG,
         MixerOp::MatrixMix4 => MATRIXMIX4_FRAG,
-        _ => CROSSFADE_FRAG,
     }
 }
 
+/// Fragment shader for a built-in filter (single input on `iChannel0`).
+pub fn builtin_filter_frag(op: FilterOp) -> &'static str {
+    match op {
+        FilterOp::Pixelate => PIXELATE_FRAG,
+        FilterOp::Posterize => POSTERIZE_FRAG,
+        FilterOp::Dither => DITHER_FRAG,
+    }
+}
+
+/// Upload per-input adjustments to the built-in mixers' `uInputAdjust[]` / `uInputUv[]` arrays.
+///
+/// Always set (identity included): an unset array reads as zero gain and zero UV scale.
+/// Mixer `node`'s input adjustments with the opacity of groups its inputs come from folded into
+/// their gains (see `scheng_runtime::groups`).
+fn group_inputs<const N: usize>(
+    graph: &Graph,
+    params: &ParamRegistry,
+    node: NodeId,
+    mut inputs: [InputAdjust; N],
+) -> [InputAdjust; N] {
+    for edge in graph.incoming(node) {
+        let Some(index) = graph.node(node).and_then(|n| {
+            n.ports.iter().filter(|p| p.dir == PortDir::In).position(|p| p.id == edge.to.port)
+        }) else {
+            continue;
+        };
+        if let Some(adjust) = inputs.get_mut(index) {
+            adjust.gain *= GroupControls::input_opacity(params, graph, node, edge.from.node);
+        }
+    }
+    inputs
+}
+
+unsafe fn set_input_adjust(gl: &glow::Context, prog: glow::NativeProgram, inputs: &[InputAdjust]) {
+    let (adjust, uv): (Vec<[f32; 4]>, Vec<[f32; 4]>) =
+        inputs.iter().map(InputAdjust::to_uniforms).unzip();
+    if let Some(loc) = gl.get_uniform_location(prog, "uInputAdjust[0]") {
+        gl.uniform_4_f32_slice(Some(&loc), adjust.as_flattened());
+    }
+    if let Some(loc) = gl.get_uniform_location(prog, "uInputUv[0]") {
+        gl.uniform_4_f32_slice(Some(&loc), uv.as_flattened());
+    }
+}
+
+pub const PIXELATE_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 FragColor;
+
+uniform sampler2D iChannel0;
+uniform vec2 uResolution;
+uniform float uBlockSize;
+
+void main() {
+    // Sample each block's centre.
+    vec2 block = uBlockSize / max(uResolution, vec2(1.0));
+    FragColor = texture(iChannel0, (floor(v_uv / block) + 0.5) * block);
+}
+"#;
+
+pub const POSTERIZE_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 FragColor;
+
+uniform sampler2D iChannel0;
+uniform float uLevels;
+
+void main() {
+    vec4 c = texture(iChannel0, v_uv);
+    float steps = max(uLevels - 1.0, 1.0);
+    FragColor = vec4(floor(c.rgb * steps + 0.5) / steps, c.a);
+}
+"#;
+
+/// `iChannel1` is the threshold map (`scheng_runtime::DitherMode::threshold_map`), tiled in
+/// output pixels.
+pub const DITHER_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 FragColor;
+
+uniform sampler2D iChannel0;
+uniform sampler2D iChannel1;
+uniform float uLevels;
+uniform float uAmount;
+
+void main() {
+    vec4 c = texture(iChannel0, v_uv);
+    ivec2 size = textureSize(iChannel1, 0);
+    float t = texelFetch(iChannel1, ivec2(gl_FragCoord.xy) % size, 0).r;
+    float steps = max(uLevels - 1.0, 1.0);
+    vec3 q = floor(c.rgb * steps + 0.5 + (t - 0.5) * uAmount) / steps;
+    FragColor = vec4(clamp(q, 0.0, 1.0), c.a);
+}
+"#;
+
 pub const CROSSFADE_FRAG: &str = r#"#version 330 core
 in vec2 v_uv;
 out vec4 FragColor;
@@ -1542,13 +3956,126 @@ uniform sampler2D uInput0;
 uniform sampler2D uInput1;
 uniform float uMix;
 
+uniform vec4 uInputAdjust[2]; // gain, offset, -, -
+uniform vec4 uInputUv[2];     // uv scale.xy, uv offset.zw
+
+// Per-input pre-transform; see scheng_runtime::InputAdjust.
+vec4 sampleInput(sampler2D tex, int i) {
+    vec2 uv = (v_uv - 0.5) * uInputUv[i].xy + 0.5 + uInputUv[i].zw;
+    vec4 c = texture(tex, uv);
+    return vec4(c.rgb * uInputAdjust[i].x + uInputAdjust[i].y, c.a);
+}
+
 void main() {
-    vec4 a = texture(uInput0, v_uv);
-    vec4 b = texture(uInput1, v_uv);
+    vec4 a = sampleInput(uInput0, 0);
+    vec4 b = sampleInput(uInput1, 1);
     FragColor = mix(a, b, uMix);
 }
 "#;
 
+pub const ADD_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 FragColor;
+
+uniform sampler2D uInput0;
+uniform sampler2D uInput1;
+uniform float uGainA;
+uniform float uGainB;
+
+uniform vec4 uInputAdjust[2]; // gain, offset, -, -
+uniform vec4 uInputUv[2];     // uv scale.xy, uv offset.zw
+
+// Per-input pre-transform; see scheng_runtime::InputAdjust.
+vec4 sampleInput(sampler2D tex, int i) {
+    vec2 uv = (v_uv - 0.5) * uInputUv[i].xy + 0.5 + uInputUv[i].zw;
+    vec4 c = texture(tex, uv);
+    return vec4(c.rgb * uInputAdjust[i].x + uInputAdjust[i].y, c.a);
+}
+
+void main() {
+    vec4 a = sampleInput(uInput0, 0);
+    vec4 b = sampleInput(uInput1, 1);
+    FragColor = vec4(a.rgb * uGainA + b.rgb * uGainB, max(a.a, b.a));
+}
+"#;
+
+pub const MULTIPLY_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 FragColor;
+
+uniform sampler2D uInput0;
+uniform sampler2D uInput1;
+uniform float uGainA;
+uniform float uGainB;
+
+uniform vec4 uInputAdjust[2]; // gain, offset, -, -
+uniform vec4 uInputUv[2];     // uv scale.xy, uv offset.zw
+
+// Per-input pre-transform; see scheng_runtime::InputAdjust.
+vec4 sampleInput(sampler2D tex, int i) {
+    vec2 uv = (v_uv - 0.5) * uInputUv[i].xy + 0.5 + uInputUv[i].zw;
+    vec4 c = texture(tex, uv);
+    return vec4(c.rgb * uInputAdjust[i].x + uInputAdjust[i].y, c.a);
+}
+
+void main() {
+    vec4 a = sampleInput(uInput0, 0);
+    vec4 b = sampleInput(uInput1, 1);
+    FragColor = vec4((a.rgb * uGainA) * (b.rgb * uGainB), a.a * b.a);
+}
+"#;
+
+pub const KEYMIX_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 FragColor;
+
+uniform sampler2D uInput0; // background
+uniform sampler2D uInput1; // foreground
+uniform sampler2D uInput2; // garbage matte (optional)
+uniform vec3 uKeyColor;
+uniform float uThreshold;
+uniform float uSoftness;
+uniform float uSpill;
+uniform int uLumaMode;
+uniform int uHasMatte;
+uniform int uInvertMatte;
+
+const vec3 LUMA = vec3(0.2126, 0.7152, 0.0722);
+
+vec2 chroma(vec3 c) {
+    float y = dot(c, LUMA);
+    return vec2((c.b - y) * 0.5389, (c.r - y) * 0.6350);
+}
+
+void main() {
+    vec4 bg = texture(uInput0, v_uv);
+    vec4 fg = texture(uInput1, v_uv);
+
+    float d = uLumaMode != 0 ? dot(fg.rgb, LUMA) : distance(chroma(fg.rgb), chroma(uKeyColor));
+    float alpha = smoothstep(uThreshold, uThreshold + max(uSoftness, 1e-4), d);
+
+    if (uHasMatte != 0) {
+        float m = dot(texture(uInput2, v_uv).rgb, LUMA);
+        alpha *= uInvertMatte != 0 ? 1.0 - m : m;
+    }
+
+    // Spill: limit the key's dominant channel to the larger of the other two.
+    vec3 c = fg.rgb;
+    if (uLumaMode == 0 && uSpill > 0.0) {
+        vec3 k = uKeyColor;
+        if (k.g >= k.r && k.g >= k.b) {
+            c.g = mix(c.g, min(c.g, max(c.r, c.b)), uSpill);
+        } else if (k.b >= k.r) {
+            c.b = mix(c.b, min(c.b, max(c.r, c.g)), uSpill);
+        } else {
+            c.r = mix(c.r, min(c.r, max(c.g, c.b)), uSpill);
+        }
+    }
+
+    FragColor = vec4(mix(bg.rgb, c, alpha), max(bg.a, alpha * fg.a));
+}
+"#;
+
 pub const MATRIXMIX4_FRAG: &str = r#"#version 330 core
 in vec2 v_uv;
 out vec4 FragColor;
@@ -1558,13 +4085,34 @@ uniform sampler2D uInput1;
 uniform sampler2D uInput2;
 uniform sampler2D uInput3;
 uniform vec4 uWeights;
+uniform int uNormalize;
+uniform int uClampOutput;
+uniform int uAllowNegative;
+
+uniform vec4 uInputAdjust[4]; // gain, offset, -, -
+uniform vec4 uInputUv[4];     // uv scale.xy, uv offset.zw
+
+// Per-input pre-transform; see scheng_runtime::InputAdjust.
+vec4 sampleInput(sampler2D tex, int i) {
+    vec2 uv = (v_uv - 0.5) * uInputUv[i].xy + 0.5 + uInputUv[i].zw;
+    vec4 c = texture(tex, uv);
+    return vec4(c.rgb * uInputAdjust[i].x + uInputAdjust[i].y, c.a);
+}
 
 void main() {
-    vec4 a = texture(uInput0, v_uv);
-    vec4 b = texture(uInput1, v_uv);
-    vec4 c = texture(uInput2, v_uv);
-    vec4 d = texture(uInput3, v_uv);
-    FragColor = a * uWeights.x + b * uWeights.y + c * uWeights.z + d * uWeights.w;
+    // Same math as MatrixMixParams::effective_weights.
+    vec4 w = uAllowNegative != 0 ? uWeights : max(uWeights, vec4(0.0));
+    float sum = w.x + w.y + w.z + w.w;
+    if (uNormalize != 0 && abs(sum) > 1e-6) {
+        w /= sum;
+    }
+
+    vec4 a = sampleInput(uInput0, 0);
+    vec4 b = sampleInput(uInput1, 1);
+    vec4 c = sampleInput(uInput2, 2);
+    vec4 d = sampleInput(uInput3, 3);
+    vec4 o = a * w.x + b * w.y + c * w.z + d * w.w;
+    FragColor = uClampOutput != 0 ? clamp(o, 0.0, 1.0) : o;
 }
 "#;
 
@@ -1594,3 +4142,294 @@ pub unsafe fn blit_fullscreen(
     gl.bind_framebuffer(glow::FRAMEBUFFER, None);
     Ok(())
 }
+
+// Patchbay routing runs on a real (headless) context because every sink call takes one, but the
+// sinks here never touch it. Without an EGL device the tests skip unless `SCHENG_REQUIRE_GPU=1`.
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use glutin::api::egl::context::PossiblyCurrentContext;
+    use glutin::api::egl::device::Device;
+    use glutin::api::egl::display::Display;
+    use glutin::config::{ConfigSurfaceTypes, ConfigTemplateBuilder};
+    use glutin::context::{ContextApi, ContextAttributesBuilder, Version};
+    use glutin::prelude::*;
+    use std::cell::RefCell;
+    use std::num::NonZeroU32;
+    use std::rc::Rc;
+
+    struct Gpu {
+        gl: glow::Context,
+        _context: PossiblyCurrentContext,
+        _display: Display,
+    }
+
+    fn gpu() -> Option<Gpu> {
+        let gpu = (|| {
+            let device = Device::query_devices().ok()?.next()?;
+            let display = unsafe { Display::with_device(&device, None) }.ok()?;
+            let template = ConfigTemplateBuilder::new()
+                .with_surface_type(ConfigSurfaceTypes::empty())
+                .build();
+            let config = unsafe { display.find_configs(template) }.ok()?.next()?;
+            let attrs = ContextAttributesBuilder::new()
+                .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+                .build(None);
+            let context = unsafe { display.create_context(&config, &attrs) }
+                .ok()?
+                .make_current_surfaceless()
+                .ok()?;
+            let gl = unsafe {
+                glow::Context::from_loader_function_cstr(|s| display.get_proc_address(s).cast())
+            };
+            Some(Gpu {
+                gl,
+                _context: context,
+                _display: display,
+            })
+        })();
+        if gpu.is_none() {
+            assert!(
+                std::env::var_os("SCHENG_REQUIRE_GPU").is_none(),
+                "SCHENG_REQUIRE_GPU is set but no headless EGL context could be created"
+            );
+            eprintln!("skipping patchbay test: no headless EGL context");
+        }
+        gpu
+    }
+
+    type Log = Rc<RefCell<Vec<String>>>;
+
+    /// Appends `"<tag> <hook>"` to a shared log for every call it gets.
+    struct Journal {
+        tag: &'static str,
+        log: Log,
+    }
+
+    impl Journal {
+        fn new(tag: &'static str, log: &Log) -> Self {
+            Self {
+                tag,
+                log: log.clone(),
+            }
+        }
+
+        fn note(&self, hook: String) {
+            self.log.borrow_mut().push(format!("{} {hook}", self.tag));
+        }
+    }
+
+    impl OutputSink for Journal {
+        fn consume(&mut self, _gl: &glow::Context, _out: &ExecOutput) {
+            self.note("consume".into());
+        }
+
+        fn on_frame_begin(&mut self, _gl: &glow::Context, frame: &FrameCtx) {
+            self.note(format!("begin {}", frame.frame));
+        }
+
+        fn on_frame_end(&mut self, _gl: &glow::Context) {
+            self.note("end".into());
+        }
+
+        fn on_resize(&mut self, _gl: &glow::Context, width: i32, height: i32) {
+            self.note(format!("resize {width}x{height}"));
+        }
+
+        fn destroy(&mut self, _gl: &glow::Context) {
+            self.note("destroy".into());
+        }
+    }
+
+    fn take(log: &Log) -> Vec<String> {
+        std::mem::take(&mut *log.borrow_mut())
+    }
+
+    /// Square outputs with placeholder GL names (the journal never reads them).
+    fn outputs(names: &[(&str, i32)]) -> ExecOutputs {
+        let out = |size| ExecOutput {
+            tex: glow::NativeTexture(NonZeroU32::MIN),
+            fbo: glow::NativeFramebuffer(NonZeroU32::MIN),
+            width: size,
+            height: size,
+        };
+        ExecOutputs {
+            primary: out(4),
+            named: names
+                .iter()
+                .map(|&(name, size)| NamedOutput {
+                    name: name.to_string(),
+                    node: NodeId(0),
+                    declared_width: size,
+                    declared_height: size,
+                    alpha: AlphaPolicy::default(),
+                    out: out(size),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn repatched_routes_stay_addressable_by_name_and_index() {
+        let Some(gpu) = gpu() else { return };
+        let gl = &gpu.gl;
+        let log = Log::default();
+        let outs = outputs(&[("main", 4), ("aux", 4)]);
+        let mut patchbay = PatchbaySink::new();
+        for tag in ["a0", "a1", "a2"] {
+            patchbay.add_route("main", Journal::new(tag, &log));
+        }
+        assert_eq!(patchbay.add_route("aux", Journal::new("b0", &log)), 0);
+
+        // Removing shifts later sinks down: a1 is now #0 and a2 #1.
+        assert!(patchbay.remove_route("main", 0).is_some());
+        assert!(patchbay.remove_route("main", 2).is_none());
+        assert!(patchbay.remove_route("ghost", 0).is_none());
+        assert!(patchbay.set_route_enabled("main", 0, false));
+        assert!(!patchbay.set_route_enabled("main", 2, false));
+        // Replacing keeps the slot's enable flag.
+        assert!(patchbay
+            .replace_route("main", 0, Journal::new("a3", &log))
+            .is_some());
+        assert!(patchbay
+            .replace_route("aux", 1, Journal::new("b1", &log))
+            .is_none());
+        let routes: Vec<_> = patchbay
+            .routes()
+            .map(|r| (r.name, r.index, r.enabled))
+            .collect();
+        assert_eq!(
+            routes,
+            [("main", 0, false), ("main", 1, true), ("aux", 0, true)]
+        );
+
+        patchbay.consume_named(gl, &outs).unwrap();
+        assert_eq!(
+            take(&log),
+            ["a2 resize 4x4", "a2 consume", "b0 resize 4x4", "b0 consume"]
+        );
+        assert!(patchbay.set_route_enabled("main", 0, true));
+        patchbay.consume_named(gl, &outs).unwrap();
+        assert_eq!(take(&log), ["a3 consume", "a2 consume", "b0 consume"]);
+
+        assert_eq!(patchbay.clear_route("main"), 2);
+        assert_eq!(patchbay.clear_route("main"), 0);
+        assert_eq!(patchbay.route_count("main"), 0);
+        // Removing a name's last sink drops the name.
+        assert!(patchbay.remove_route("aux", 0).is_some());
+        assert_eq!(patchbay.routes().count(), 0);
+        patchbay.consume_named(gl, &outs).unwrap();
+        assert!(take(&log).is_empty());
+    }
+
+    #[test]
+    fn divided_names_skip_whole_frames() {
+        let Some(gpu) = gpu() else { return };
+        let gl = &gpu.gl;
+        let log = Log::default();
+        let outs = outputs(&[("main", 4), ("preview", 4)]);
+        let mut patchbay = PatchbaySink::new();
+        patchbay.add_route("main", Journal::new("m", &log));
+        patchbay.add_route("preview", Journal::new("p", &log));
+        patchbay.set_rate_divisor("preview", 3);
+        assert_eq!(patchbay.rate_divisor("preview"), 3);
+        assert_eq!(patchbay.routes().map(|r| r.rate_divisor).max(), Some(3));
+
+        let mut fed = Vec::new();
+        for n in 0..4 {
+            patchbay.begin_frame(gl, &FrameCtx::new(4, 4, 0.0, n));
+            patchbay.consume_named(gl, &outs).unwrap();
+            fed.push(patchbay.fed.clone());
+            patchbay.end_frame(gl);
+        }
+        let preview: Vec<String> = take(&log)
+            .into_iter()
+            .filter(|l| l.starts_with('p'))
+            .collect();
+        // Due on frames 0 and 3 only; frames 1 and 2 get no hooks at all.
+        assert_eq!(
+            preview,
+            [
+                "p begin 0",
+                "p resize 4x4",
+                "p consume",
+                "p end",
+                "p begin 3",
+                "p consume",
+                "p end"
+            ]
+        );
+        assert_eq!(fed[1], ["main"]);
+        assert_eq!(fed[3], ["main", "preview"]);
+
+        // A due name whose output is missing is not fed, so it gets no `on_frame_end`.
+        patchbay.set_rate_divisor("preview", 1);
+        patchbay.set_missing_output_policy(MissingOutputPolicy::Ignore);
+        patchbay.begin_frame(gl, &FrameCtx::new(4, 4, 0.0, 4));
+        patchbay.consume_named(gl, &outputs(&[("main", 4)])).unwrap();
+        patchbay.end_frame(gl);
+        assert_eq!(take(&log), ["m begin 4", "p begin 4", "m consume", "m end"]);
+    }
+
+    #[test]
+    fn out_of_step_calls_follow_the_consume_count() {
+        let Some(gpu) = gpu() else { return };
+        let gl = &gpu.gl;
+        let log = Log::default();
+        let outs = outputs(&[("preview", 4)]);
+        let mut patchbay = PatchbaySink::new();
+        patchbay.add_route("preview", Journal::new("p", &log));
+        patchbay.set_rate_divisor("preview", 2);
+
+        // Without `begin_frame` the sink is still fed, and the count still advances.
+        patchbay.consume_named(gl, &outs).unwrap();
+        patchbay.end_frame(gl);
+        assert_eq!(take(&log), ["p resize 4x4", "p consume", "p end"]);
+        patchbay.consume_named(gl, &outs).unwrap();
+        assert!(take(&log).is_empty());
+
+        // `begin_frame` does not advance the count, so a repeat forwards the begin again.
+        let frame = FrameCtx::new(4, 4, 0.0, 2);
+        patchbay.begin_frame(gl, &frame);
+        patchbay.begin_frame(gl, &frame);
+        patchbay.consume_named(gl, &outs).unwrap();
+        assert_eq!(take(&log), ["p begin 2", "p begin 2", "p consume"]);
+    }
+
+    #[test]
+    fn missing_outputs_follow_the_policy_and_warn_once_per_absence() {
+        let Some(gpu) = gpu() else { return };
+        let gl = &gpu.gl;
+        let log = Log::default();
+        let both = outputs(&[("main", 4), ("preview", 4)]);
+        let main_only = outputs(&[("main", 4)]);
+        let mut patchbay = PatchbaySink::new();
+        patchbay.add_route("main", Journal::new("m", &log));
+        patchbay.add_route("preview", Journal::new("p", &log));
+
+        assert_eq!(patchbay.missing_output_policy(), MissingOutputPolicy::Error);
+        let err = patchbay.consume_named(gl, &main_only).unwrap_err();
+        assert!(err.to_string().contains("'preview'"), "{err}");
+        take(&log);
+
+        patchbay.set_missing_output_policy(MissingOutputPolicy::Warn);
+        for _ in 0..2 {
+            patchbay.consume_named(gl, &main_only).unwrap();
+            assert_eq!(take(&log), ["m consume"]);
+            assert_eq!(patchbay.warned_missing, ["preview"]);
+        }
+        // The output coming back re-arms the warning.
+        patchbay.consume_named(gl, &both).unwrap();
+        assert_eq!(take(&log), ["m consume", "p resize 4x4", "p consume"]);
+        assert!(patchbay.warned_missing.is_empty());
+        patchbay.consume_named(gl, &main_only).unwrap();
+        assert_eq!(patchbay.warned_missing, ["preview"]);
+
+        patchbay.consume_named(gl, &both).unwrap();
+        take(&log);
+        patchbay.set_missing_output_policy(MissingOutputPolicy::Ignore);
+        patchbay.consume_named(gl, &main_only).unwrap();
+        assert_eq!(take(&log), ["m consume"]);
+        assert!(patchbay.warned_missing.is_empty());
+    }
+}
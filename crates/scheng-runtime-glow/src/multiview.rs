@@ -0,0 +1,346 @@
+//! Multiview: several named outputs composited into a labeled grid for a monitoring window.
+//!
+//! `Multiview::render` letterboxes each tile's output into its cell with framebuffer blits and
+//! stamps the tile label (a built-in 5x7 bitmap font, uppercase ASCII) into the bottom-left
+//! corner. The result is an [`ExecOutput`], so any sink can show it; typically a
+//! [`PresentSink`](crate::PresentSink) on a second window. Sources are shown by routing them to
+//! a named `PixelsOut`. Missing outputs leave their cell empty but keep the label.
+
+use std::collections::HashMap;
+
+use glow::HasContext;
+
+use crate::present::{present_rect, ScaleMode};
+use crate::{create_render_target, EngineError, ExecOutput, ExecOutputs, RenderTarget};
+
+/// Grid shape of a [`Multiview`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MultiviewLayout {
+    #[default]
+    Grid2x2,
+    Grid3x3,
+    Grid4x4,
+}
+
+impl MultiviewLayout {
+    /// Cells per row (and per column).
+    pub fn side(self) -> i32 {
+        match self {
+            MultiviewLayout::Grid2x2 => 2,
+            MultiviewLayout::Grid3x3 => 3,
+            MultiviewLayout::Grid4x4 => 4,
+        }
+    }
+
+    pub fn cells(self) -> usize {
+        (self.side() * self.side()) as usize
+    }
+}
+
+/// One cell: the named output it shows and the label drawn over it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiviewTile {
+    pub output: String,
+    pub label: String,
+}
+
+impl MultiviewTile {
+    /// A tile labeled with its output name.
+    pub fn new(output: &str) -> Self {
+        Self {
+            output: output.to_string(),
+            label: output.to_string(),
+        }
+    }
+
+    pub fn with_label(mut self, label: &str) -> Self {
+        self.label = label.to_string();
+        self
+    }
+}
+
+/// Settings for [`Multiview`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultiviewConfig {
+    pub layout: MultiviewLayout,
+    /// Composite size.
+    pub width: i32,
+    pub height: i32,
+    /// Pixels between cells and around the edge.
+    pub gap: i32,
+    pub background: [f32; 4],
+    /// Label glyph scale in pixels per font pixel; 0 hides labels.
+    pub label_scale: i32,
+    /// Tiles in reading order (left to right, top to bottom). Extra tiles are ignored.
+    pub tiles: Vec<MultiviewTile>,
+}
+
+impl Default for MultiviewConfig {
+    fn default() -> Self {
+        Self {
+            layout: MultiviewLayout::default(),
+            width: 1280,
+            height: 720,
+            gap: 4,
+            background: [0.05, 0.05, 0.05, 1.0],
+            label_scale: 2,
+            tiles: Vec::new(),
+        }
+    }
+}
+
+/// Cell `index` of `layout` in a `width x height` composite, as GL `(x, y, w, h)` (origin
+/// bottom-left, so index 0 is the top-left cell).
+pub fn cell_rect(
+    layout: MultiviewLayout,
+    index: usize,
+    width: i32,
+    height: i32,
+    gap: i32,
+) -> (i32, i32, i32, i32) {
+    let side = layout.side();
+    let (col, row) = (index as i32 % side, index as i32 / side);
+    let cell_w = ((width - gap * (side + 1)) / side).max(1);
+    let cell_h = ((height - gap * (side + 1)) / side).max(1);
+    let x = gap + col * (cell_w + gap);
+    let y = height - (gap + cell_h) * (row + 1);
+    (x, y, cell_w, cell_h)
+}
+
+/// Rows of a 5x7 glyph, top first; bit 4 is the leftmost column. Lowercase letters use the
+/// uppercase glyph and unknown characters render as `?`.
+fn glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        'A' => [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x0A, 0x04, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        ' ' => [0x00; 7],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        '(' => [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02],
+        ')' => [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04],
+    }
+}
+
+/// Rasterize `text` as white on black in a box with a one-font-pixel margin, at most
+/// `max_width` pixels wide (characters that do not fit are dropped). Returns tightly packed
+/// RGBA8 rows bottom-up, as GL expects, with the box size.
+pub fn rasterize_label(text: &str, scale: i32, max_width: i32) -> Label {
+    let scale = scale.max(1);
+    let fits = ((max_width / scale - 1) / 6).max(0) as usize;
+    let chars: Vec<char> = text.chars().take(fits).collect();
+    if chars.is_empty() {
+        return (Vec::new(), 0, 0);
+    }
+    // Font units: 6 per character (5 + spacing), +1 margin each side less the last spacing.
+    let (units_w, units_h) = (chars.len() as i32 * 6 + 1, 9);
+    let (w, h) = (units_w * scale, units_h * scale);
+    let mut rgba = vec![0u8; (w * h * 4) as usize];
+    for y in 0..h {
+        let unit_row = units_h - 1 - y / scale;
+        for x in 0..w {
+            let unit_col = x / scale - 1;
+            let lit = (1..=7).contains(&unit_row) && unit_col >= 0 && unit_col % 6 < 5 && {
+                let rows = glyph(chars[(unit_col / 6) as usize]);
+                rows[(unit_row - 1) as usize] & (0x10 >> (unit_col % 6)) != 0
+            };
+            let v = if lit { 255 } else { 0 };
+            let i = ((y * w + x) * 4) as usize;
+            rgba[i..i + 4].copy_from_slice(&[v, v, v, 255]);
+        }
+    }
+    (rgba, w, h)
+}
+
+/// A rasterized label: RGBA8 rows bottom-up, width, height.
+type Label = (Vec<u8>, i32, i32);
+
+/// Grid compositor for monitoring (see the module docs).
+pub struct Multiview {
+    config: MultiviewConfig,
+    target: Option<RenderTarget>,
+    /// Rasterized labels keyed by (text, scale, max width).
+    labels: HashMap<(String, i32, i32), Label>,
+}
+
+impl Multiview {
+    pub fn new(config: MultiviewConfig) -> Self {
+        Self {
+            config,
+            target: None,
+            labels: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &MultiviewConfig {
+        &self.config
+    }
+
+    /// Replace the settings; the composite target is resized on the next `render`.
+    pub fn set_config(&mut self, config: MultiviewConfig) {
+        self.config = config;
+    }
+
+    unsafe fn ensure_target(&mut self, gl: &glow::Context) -> Result<(), EngineError> {
+        let (w, h) = (self.config.width.max(1), self.config.height.max(1));
+        match &mut self.target {
+            Some(rt) if rt.w == w && rt.h == h => {}
+            Some(rt) => rt.resize(gl, w, h),
+            None => self.target = Some(create_render_target(gl, w, h)?),
+        }
+        Ok(())
+    }
+
+    /// Composite this frame's `outs` into the grid.
+    pub unsafe fn render(
+        &mut self,
+        gl: &glow::Context,
+        outs: &ExecOutputs,
+    ) -> Result<ExecOutput, EngineError> {
+        self.ensure_target(gl)?;
+        let rt = self.target.as_ref().expect("target just ensured");
+        let (fbo, tex, w, h) = (rt.fbo, rt.tex, rt.w, rt.h);
+        let cfg = &self.config;
+
+        gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(fbo));
+        gl.viewport(0, 0, w, h);
+        let [r, g, b, a] = cfg.background;
+        gl.clear_color(r, g, b, a);
+        gl.clear(glow::COLOR_BUFFER_BIT);
+
+        let tiles = cfg.tiles.iter().take(cfg.layout.cells());
+        for (i, tile) in tiles.clone().enumerate() {
+            let Some(out) = outs.get(&tile.output) else {
+                continue;
+            };
+            let (cx, cy, cw, ch) = cell_rect(cfg.layout, i, w, h, cfg.gap);
+            let (x, y, fw, fh) = present_rect(out.width, out.height, cw, ch, ScaleMode::Letterbox);
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(out.fbo));
+            gl.blit_framebuffer(
+                0,
+                0,
+                out.width,
+                out.height,
+                cx + x,
+                cy + y,
+                cx + x + fw,
+                cy + y + fh,
+                glow::COLOR_BUFFER_BIT,
+                glow::LINEAR,
+            );
+        }
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
+
+        if cfg.label_scale > 0 {
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+            for (i, tile) in tiles.enumerate() {
+                let (cx, cy, cw, ch) = cell_rect(cfg.layout, i, w, h, cfg.gap);
+                let key = (tile.label.clone(), cfg.label_scale, cw);
+                let (rgba, lw, lh) = self
+                    .labels
+                    .entry(key)
+                    .or_insert_with(|| rasterize_label(&tile.label, cfg.label_scale, cw));
+                if *lw == 0 || *lh > ch {
+                    continue;
+                }
+                gl.tex_sub_image_2d(
+                    glow::TEXTURE_2D,
+                    0,
+                    cx,
+                    cy,
+                    *lw,
+                    *lh,
+                    glow::RGBA,
+                    glow::UNSIGNED_BYTE,
+                    glow::PixelUnpackData::Slice(rgba),
+                );
+            }
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+
+        Ok(ExecOutput {
+            tex,
+            fbo,
+            width: w,
+            height: h,
+        })
+    }
+
+    pub unsafe fn destroy(&mut self, gl: &glow::Context) {
+        if let Some(rt) = self.target.take() {
+            gl.delete_framebuffer(rt.fbo);
+            gl.delete_texture(rt.tex);
+        }
+        self.labels.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_tile_the_frame_top_left_first() {
+        let layout = MultiviewLayout::Grid2x2;
+        assert_eq!(cell_rect(layout, 0, 100, 60, 0), (0, 30, 50, 30));
+        assert_eq!(cell_rect(layout, 1, 100, 60, 0), (50, 30, 50, 30));
+        assert_eq!(cell_rect(layout, 3, 100, 60, 0), (50, 0, 50, 30));
+        assert_eq!(
+            cell_rect(MultiviewLayout::Grid3x3, 4, 94, 94, 4),
+            (34, 34, 26, 26)
+        );
+    }
+
+    #[test]
+    fn labels_rasterize_bottom_up_and_clip_to_width() {
+        let (rgba, w, h) = rasterize_label("I", 1, 100);
+        assert_eq!((w, h), (7, 9));
+        let lit = |x: i32, y: i32| rgba[((y * w + x) * 4) as usize] == 255;
+        // 'I' has a full-width bar at the top (GL row 7) and the stem in column 3.
+        assert!((2..=4).all(|x| lit(x, 7)));
+        assert!(lit(3, 4) && !lit(2, 4));
+        assert!(!lit(3, 0) && !lit(3, 8));
+
+        let (_, w, _) = rasterize_label("CAM 1", 2, 40);
+        assert_eq!(w, (3 * 6 + 1) * 2);
+        assert_eq!(rasterize_label("CAM", 2, 4).1, 0);
+    }
+}
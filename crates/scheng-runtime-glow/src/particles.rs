@@ -0,0 +1,322 @@
+//! Built-in particle system (`NodeKind::Particles`).
+//!
+//! Particle state lives on the GPU in two `RGBA32F` textures (`xy` = position, `zw` =
+//! velocity, one texel per particle) that ping-pong each frame: an update pass integrates
+//! forces and respawns expired particles, then a render pass draws every particle as a point
+//! sprite into the node's target with additive blending. Ages are not stored: each particle has a
+//! fixed phase, so its age is a function of time and spawns stay evenly staggered.
+//!
+//! Parameters come from `NodeProps::particle_params` ([`ParticleParams`]).
+
+use glow::HasContext;
+use scheng_runtime::ParticleParams;
+
+use crate::stats::{RuntimeStats, TextureFormat};
+use crate::{compile_program, EngineError, FullscreenTriangle, FULLSCREEN_VERT};
+
+/// Shared by both passes so they agree on each particle's phase.
+const PARTICLE_COMMON: &str = r#"
+float hash(vec2 p) {
+    p = fract(p * vec2(123.34, 456.21));
+    p += dot(p, p + 45.32);
+    return fract(p.x * p.y);
+}
+float particlePhase(float id) { return hash(vec2(id, 0.37)); }
+"#;
+
+const UPDATE_FRAG: &str = r#"
+uniform sampler2D uState;
+uniform int uSide;
+uniform int uReset;
+uniform float uTime;
+uniform float uDt;
+uniform float uLifetime;
+uniform vec2 uEmitter;
+uniform float uEmitterRadius;
+uniform float uDirection;
+uniform float uSpread;
+uniform float uSpeed;
+uniform vec2 uGravity;
+uniform float uDrag;
+uniform float uTurbulence;
+
+out vec4 o;
+
+void main() {
+    ivec2 p = ivec2(gl_FragCoord.xy);
+    float id = float(p.y * uSide + p.x);
+    float cycle = uTime / uLifetime + particlePhase(id);
+    float prevCycle = cycle - uDt / uLifetime;
+
+    if (uReset != 0 || floor(cycle) != floor(prevCycle)) {
+        float gen = floor(cycle);
+        float r0 = hash(vec2(id, gen + 0.11));
+        float r1 = hash(vec2(gen + 1.7, id));
+        float r2 = hash(vec2(id + 3.1, gen));
+        float a = r2 * 6.2831853;
+        vec2 pos = uEmitter + sqrt(r1) * uEmitterRadius * vec2(cos(a), sin(a));
+        float dir = uDirection + (r0 - 0.5) * uSpread;
+        vec2 vel = vec2(cos(dir), sin(dir)) * uSpeed * (0.5 + 0.5 * r1);
+        // Particles spawned mid-frame (or on reset) start part-way along their path.
+        float age = fract(cycle) * uLifetime;
+        o = vec4(pos + vel * age, vel);
+        return;
+    }
+
+    vec4 s = texelFetch(uState, p, 0);
+    vec2 pos = s.xy;
+    vec2 vel = s.zw;
+    vec2 turb = vec2(sin(pos.y * 13.0 + uTime * 1.3 + id * 0.01),
+                     cos(pos.x * 11.0 - uTime * 1.1 + id * 0.013));
+    vel += (uGravity + turb * uTurbulence) * uDt;
+    vel *= exp(-uDrag * uDt);
+    o = vec4(pos + vel * uDt, vel);
+}
+"#;
+
+const RENDER_VERT: &str = r#"
+uniform sampler2D uState;
+uniform int uSide;
+uniform float uTime;
+uniform float uLifetime;
+uniform float uSize;
+
+out float v_age;
+
+void main() {
+    int id = gl_VertexID;
+    vec4 s = texelFetch(uState, ivec2(id % uSide, id / uSide), 0);
+    v_age = fract(uTime / uLifetime + particlePhase(float(id)));
+    gl_Position = vec4(s.xy * 2.0 - 1.0, 0.0, 1.0);
+    gl_PointSize = uSize;
+}
+"#;
+
+const RENDER_FRAG: &str = r#"#version 330 core
+in float v_age;
+out vec4 o;
+
+uniform vec4 uColor;
+uniform int uFade;
+
+void main() {
+    vec2 d = gl_PointCoord * 2.0 - 1.0;
+    float r = dot(d, d);
+    if (r > 1.0) discard;
+    float a = (1.0 - r) * uColor.a * (uFade != 0 ? 1.0 - v_age : 1.0);
+    o = vec4(uColor.rgb * a, a);
+}
+"#;
+
+/// One state texture + FBO.
+#[derive(Debug)]
+struct StateTarget {
+    fbo: glow::NativeFramebuffer,
+    tex: glow::NativeTexture,
+}
+
+impl StateTarget {
+    unsafe fn new(gl: &glow::Context, side: i32) -> Result<Self, EngineError> {
+        let tex = gl
+            .create_texture()
+            .map_err(|e| EngineError::GlCreate(format!("create_texture failed: {e:?}")))?;
+        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+        for (pname, v) in [
+            (glow::TEXTURE_MIN_FILTER, glow::NEAREST),
+            (glow::TEXTURE_MAG_FILTER, glow::NEAREST),
+            (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+            (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+        ] {
+            gl.tex_parameter_i32(glow::TEXTURE_2D, pname, v as i32);
+        }
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGBA32F as i32,
+            side,
+            side,
+            0,
+            glow::RGBA,
+            glow::FLOAT,
+            None,
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+
+        let fbo = gl
+            .create_framebuffer()
+            .map_err(|e| EngineError::GlCreate(format!("create_framebuffer failed: {e:?}")))?;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+        gl.framebuffer_texture_2d(
+            glow::FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::TEXTURE_2D,
+            Some(tex),
+            0,
+        );
+        let status = gl.check_framebuffer_status(glow::FRAMEBUFFER);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            gl.delete_framebuffer(fbo);
+            gl.delete_texture(tex);
+            return Err(EngineError::GlCreate(format!(
+                "particle state framebuffer incomplete: 0x{status:x}"
+            )));
+        }
+        Ok(Self { fbo, tex })
+    }
+
+    unsafe fn destroy(self, gl: &glow::Context) {
+        gl.delete_framebuffer(self.fbo);
+        gl.delete_texture(self.tex);
+    }
+}
+
+/// GPU state for one `Particles` node.
+#[derive(Debug)]
+pub struct ParticleSystem {
+    side: i32,
+    state: [StateTarget; 2],
+    /// Index into `state` holding the latest positions.
+    cur: usize,
+    update_prog: glow::NativeProgram,
+    render_prog: glow::NativeProgram,
+    /// Core profiles need a VAO bound to draw, even with no attributes.
+    empty_vao: glow::NativeVertexArray,
+    reset: bool,
+}
+
+impl ParticleSystem {
+    pub unsafe fn new(gl: &glow::Context, params: &ParticleParams) -> Result<Self, EngineError> {
+        let side = params.state_side() as i32;
+        let header = "#version 330 core\n";
+        let update_prog = compile_program(
+            gl,
+            FULLSCREEN_VERT,
+            &format!("{header}{PARTICLE_COMMON}{UPDATE_FRAG}"),
+        )?;
+        let render_prog = compile_program(
+            gl,
+            &format!("{header}{PARTICLE_COMMON}{RENDER_VERT}"),
+            RENDER_FRAG,
+        )
+        .inspect_err(|_| gl.delete_program(update_prog))?;
+        let empty_vao = gl
+            .create_vertex_array()
+            .map_err(|e| EngineError::GlCreate(format!("create_vertex_array: {e}")))?;
+        Ok(Self {
+            side,
+            state: [StateTarget::new(gl, side)?, StateTarget::new(gl, side)?],
+            cur: 0,
+            update_prog,
+            render_prog,
+            empty_vao,
+            reset: true,
+        })
+    }
+
+    /// Whether `params` still fit this system's state textures.
+    pub fn matches(&self, params: &ParticleParams) -> bool {
+        params.state_side() as i32 == self.side
+    }
+
+    /// Respawn every particle on the next step.
+    pub fn reset(&mut self) {
+        self.reset = true;
+    }
+
+    /// Advance the simulation by `dt` and draw the particles into `target` (`w` x `h`), which
+    /// is bound again on return.
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe fn step(
+        &mut self,
+        gl: &glow::Context,
+        fs_tri: &FullscreenTriangle,
+        params: &ParticleParams,
+        time: f32,
+        dt: f32,
+        target: glow::NativeFramebuffer,
+        w: i32,
+        h: i32,
+    ) {
+        let lifetime = params.lifetime.max(0.01);
+        let (src, dst) = (self.cur, 1 - self.cur);
+
+        // Update.
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.state[dst].fbo));
+        gl.viewport(0, 0, self.side, self.side);
+        gl.use_program(Some(self.update_prog));
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.state[src].tex));
+        let p = self.update_prog;
+        let loc = |name: &str| gl.get_uniform_location(p, name);
+        gl.uniform_1_i32(loc("uState").as_ref(), 0);
+        gl.uniform_1_i32(loc("uSide").as_ref(), self.side);
+        gl.uniform_1_i32(loc("uReset").as_ref(), self.reset as i32);
+        gl.uniform_1_f32(loc("uTime").as_ref(), time);
+        gl.uniform_1_f32(loc("uDt").as_ref(), dt.clamp(0.0, 0.1));
+        gl.uniform_1_f32(loc("uLifetime").as_ref(), lifetime);
+        gl.uniform_2_f32(
+            loc("uEmitter").as_ref(),
+            params.emitter[0],
+            params.emitter[1],
+        );
+        gl.uniform_1_f32(loc("uEmitterRadius").as_ref(), params.emitter_radius);
+        gl.uniform_1_f32(loc("uDirection").as_ref(), params.direction);
+        gl.uniform_1_f32(loc("uSpread").as_ref(), params.spread);
+        gl.uniform_1_f32(loc("uSpeed").as_ref(), params.speed);
+        gl.uniform_2_f32(
+            loc("uGravity").as_ref(),
+            params.gravity[0],
+            params.gravity[1],
+        );
+        gl.uniform_1_f32(loc("uDrag").as_ref(), params.drag.max(0.0));
+        gl.uniform_1_f32(loc("uTurbulence").as_ref(), params.turbulence);
+        fs_tri.draw(gl);
+        self.cur = dst;
+        self.reset = false;
+
+        // Render.
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(target));
+        gl.viewport(0, 0, w, h);
+        gl.use_program(Some(self.render_prog));
+        gl.bind_texture(glow::TEXTURE_2D, Some(self.state[dst].tex));
+        let p = self.render_prog;
+        let loc = |name: &str| gl.get_uniform_location(p, name);
+        gl.uniform_1_i32(loc("uState").as_ref(), 0);
+        gl.uniform_1_i32(loc("uSide").as_ref(), self.side);
+        gl.uniform_1_f32(loc("uTime").as_ref(), time);
+        gl.uniform_1_f32(loc("uLifetime").as_ref(), lifetime);
+        gl.uniform_1_f32(loc("uSize").as_ref(), params.size.max(1.0));
+        let [r, g, b, a] = params.color;
+        gl.uniform_4_f32(loc("uColor").as_ref(), r, g, b, a);
+        gl.uniform_1_i32(loc("uFade").as_ref(), params.fade as i32);
+
+        gl.enable(glow::PROGRAM_POINT_SIZE);
+        gl.enable(glow::BLEND);
+        gl.blend_func(glow::ONE, glow::ONE);
+        gl.bind_vertex_array(Some(self.empty_vao));
+        let count = params.count.clamp(1, (self.side * self.side) as u32) as i32;
+        gl.draw_arrays(glow::POINTS, 0, count);
+        gl.bind_vertex_array(None);
+        gl.disable(glow::BLEND);
+        gl.disable(glow::PROGRAM_POINT_SIZE);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+    }
+
+    /// Add the programs and state targets to `stats`.
+    pub(crate) fn account(&self, stats: &mut RuntimeStats) {
+        stats.programs += 2;
+        for _ in &self.state {
+            stats.add_target(self.side, self.side, TextureFormat::Rgba32F);
+        }
+    }
+
+    pub unsafe fn destroy(self, gl: &glow::Context) {
+        for s in self.state {
+            s.destroy(gl);
+        }
+        gl.delete_program(self.update_prog);
+        gl.delete_program(self.render_prog);
+        gl.delete_vertex_array(self.empty_vao);
+    }
+}
@@ -0,0 +1,370 @@
+//! Presentation helpers: blit an output to the window with explicit sRGB, scaling and filtering.
+//!
+//! Render targets are plain `RGBA8` holding display-encoded values (shaders write what should
+//! appear on screen). If a platform hands us an sRGB-capable default framebuffer with
+//! `GL_FRAMEBUFFER_SRGB` enabled, the blit would encode a second time and wash the image out;
+//! `SrgbMode::Disable` (the default) prevents that. Use `Enable` only for linear-light content.
+
+use glow::HasContext;
+
+use crate::{
+    compile_program, EngineError, ExecOutput, FullscreenTriangle, OutputSink, FULLSCREEN_VERT,
+};
+
+/// How `GL_FRAMEBUFFER_SRGB` is set while presenting. The previous state is restored afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SrgbMode {
+    /// Content is display-encoded: no conversion on write (correct for scheng's targets).
+    #[default]
+    Disable,
+    /// Content is linear light: let GL encode to sRGB on write.
+    Enable,
+    /// Do not touch the current state.
+    Leave,
+}
+
+/// How the output is fitted into the destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScaleMode {
+    /// Fill the destination, ignoring aspect ratio.
+    Stretch,
+    /// Preserve aspect ratio; bars are filled with `PresentConfig::clear_color`.
+    #[default]
+    Letterbox,
+    /// 1:1 pixels, centred (cropped if larger than the destination).
+    Native,
+}
+
+/// Sampling filter used when the output is scaled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PresentFilter {
+    #[default]
+    Linear,
+    /// Crisp pixels (pixel art, diagnostics).
+    Nearest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PresentConfig {
+    pub srgb: SrgbMode,
+    pub scale: ScaleMode,
+    pub filter: PresentFilter,
+    /// Colour for letterbox / pillarbox bars.
+    pub clear_color: [f32; 4],
+}
+
+impl Default for PresentConfig {
+    fn default() -> Self {
+        Self {
+            srgb: SrgbMode::default(),
+            scale: ScaleMode::default(),
+            filter: PresentFilter::default(),
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+}
+
+/// Destination rectangle `(x, y, w, h)` for a `src_w x src_h` image in a `dst_w x dst_h` target.
+pub fn present_rect(src_w: i32, src_h: i32, dst_w: i32, dst_h: i32, scale: ScaleMode) -> (i32, i32, i32, i32) {
+    if src_w <= 0 || src_h <= 0 || dst_w <= 0 || dst_h <= 0 {
+        return (0, 0, dst_w.max(0), dst_h.max(0));
+    }
+    match scale {
+        ScaleMode::Stretch => (0, 0, dst_w, dst_h),
+        ScaleMode::Native => ((dst_w - src_w) / 2, (dst_h - src_h) / 2, src_w, src_h),
+        ScaleMode::Letterbox => {
+            // Compare aspect ratios without floats: src_w/src_h vs dst_w/dst_h.
+            let (w, h) = if (src_w as i64) * (dst_h as i64) >= (dst_w as i64) * (src_h as i64) {
+                (dst_w, ((dst_w as i64 * src_h as i64) / src_w as i64) as i32)
+            } else {
+                (((dst_h as i64 * src_w as i64) / src_h as i64) as i32, dst_h)
+            };
+            ((dst_w - w) / 2, (dst_h - h) / 2, w, h)
+        }
+    }
+}
+
+/// Blit `out` into the default framebuffer (`dst_w x dst_h`) according to `cfg`.
+pub unsafe fn present(gl: &glow::Context, out: &ExecOutput, dst_w: i32, dst_h: i32, cfg: &PresentConfig) {
+    let (x, y, w, h) = present_rect(out.width, out.height, dst_w, dst_h, cfg.scale);
+
+    let was_srgb = gl.is_enabled(glow::FRAMEBUFFER_SRGB);
+    match cfg.srgb {
+        SrgbMode::Disable => gl.disable(glow::FRAMEBUFFER_SRGB),
+        SrgbMode::Enable => gl.enable(glow::FRAMEBUFFER_SRGB),
+        SrgbMode::Leave => {}
+    }
+
+    gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
+    if (x, y, w, h) != (0, 0, dst_w, dst_h) {
+        let [r, g, b, a] = cfg.clear_color;
+        gl.viewport(0, 0, dst_w, dst_h);
+        gl.clear_color(r, g, b, a);
+        gl.clear(glow::COLOR_BUFFER_BIT);
+    }
+
+    let filter = match cfg.filter {
+        PresentFilter::Linear => glow::LINEAR,
+        PresentFilter::Nearest => glow::NEAREST,
+    };
+    gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(out.fbo));
+    gl.blit_framebuffer(
+        0,
+        0,
+        out.width,
+        out.height,
+        x,
+        y,
+        x + w,
+        y + h,
+        glow::COLOR_BUFFER_BIT,
+        filter,
+    );
+    gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+
+    if cfg.srgb != SrgbMode::Leave {
+        if was_srgb {
+            gl.enable(glow::FRAMEBUFFER_SRGB);
+        } else {
+            gl.disable(glow::FRAMEBUFFER_SRGB);
+        }
+    }
+}
+
+/// Window presentation sink built on [`present`].
+///
+/// The host reports the drawable size with `set_window_size` (e.g. on window resize).
+#[derive(Debug, Clone)]
+pub struct PresentSink {
+    pub config: PresentConfig,
+    window_w: i32,
+    window_h: i32,
+}
+
+impl PresentSink {
+    pub fn new(config: PresentConfig, window_w: i32, window_h: i32) -> Self {
+        Self {
+            config,
+            window_w,
+            window_h,
+        }
+    }
+
+    pub fn set_window_size(&mut self, w: i32, h: i32) {
+        self.window_w = w;
+        self.window_h = h;
+    }
+
+    pub fn window_size(&self) -> (i32, i32) {
+        (self.window_w, self.window_h)
+    }
+}
+
+impl OutputSink for PresentSink {
+    fn consume(&mut self, gl: &glow::Context, out: &ExecOutput) {
+        unsafe { present(gl, out, self.window_w, self.window_h, &self.config) }
+    }
+}
+
+/// Clockwise rotation applied when presenting (camera feeds, portrait displays).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    None,
+    Cw90,
+    Cw180,
+    Cw270,
+}
+
+impl Rotation {
+    /// True when the image's width and height trade places on screen.
+    pub fn swaps_axes(self) -> bool {
+        matches!(self, Rotation::Cw90 | Rotation::Cw270)
+    }
+}
+
+/// Options for [`Presenter`]. Flips apply to the displayed (already rotated) image.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PresenterConfig {
+    pub scale: ScaleMode,
+    pub rotation: Rotation,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// RGB multiplier (alpha untouched).
+    pub gain: f32,
+    /// Colour for letterbox / pillarbox bars.
+    pub clear_color: [f32; 4],
+}
+
+impl Default for PresenterConfig {
+    fn default() -> Self {
+        Self {
+            scale: ScaleMode::default(),
+            rotation: Rotation::default(),
+            flip_x: false,
+            flip_y: false,
+            gain: 1.0,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+        }
+    }
+}
+
+impl PresenterConfig {
+    /// Column-major 2x2 matrix mapping centred screen UVs to centred source UVs.
+    pub fn uv_transform(&self) -> [f32; 4] {
+        let fx = if self.flip_x { -1.0 } else { 1.0 };
+        let fy = if self.flip_y { -1.0 } else { 1.0 };
+        // Inverse rotation (screen -> source), then the flips on the screen axes.
+        match self.rotation {
+            Rotation::None => [fx, 0.0, 0.0, fy],
+            Rotation::Cw90 => [0.0, fx, -fy, 0.0],
+            Rotation::Cw180 => [-fx, 0.0, 0.0, -fy],
+            Rotation::Cw270 => [0.0, -fx, fy, 0.0],
+        }
+    }
+}
+
+const PRESENTER_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 o;
+uniform sampler2D iChannel0;
+uniform mat2 u_uv_xform;
+uniform float u_gain;
+void main() {
+    vec2 uv = u_uv_xform * (v_uv - 0.5) + 0.5;
+    vec4 c = texture(iChannel0, uv);
+    o = vec4(c.rgb * u_gain, c.a);
+}
+"#;
+
+/// Draws a texture to the default framebuffer with fit, rotation, flips and gain.
+///
+/// Unlike [`present`] (a framebuffer blit) this is a shader pass, so it can reorient camera
+/// feeds and works on any texture, not just an `ExecOutput`.
+#[derive(Debug)]
+pub struct Presenter {
+    pub config: PresenterConfig,
+    tri: FullscreenTriangle,
+    program: glow::NativeProgram,
+}
+
+impl Presenter {
+    pub unsafe fn new(gl: &glow::Context, config: PresenterConfig) -> Result<Self, EngineError> {
+        let tri = FullscreenTriangle::new(gl)?;
+        let program = compile_program(gl, FULLSCREEN_VERT, PRESENTER_FRAG)?;
+        Ok(Self { config, tri, program })
+    }
+
+    /// Draw `tex` (`src_w x src_h`) into the default framebuffer (`dst_w x dst_h`).
+    pub unsafe fn present(
+        &self,
+        gl: &glow::Context,
+        tex: glow::NativeTexture,
+        src_w: i32,
+        src_h: i32,
+        dst_w: i32,
+        dst_h: i32,
+    ) {
+        let (fit_w, fit_h) = if self.config.rotation.swaps_axes() {
+            (src_h, src_w)
+        } else {
+            (src_w, src_h)
+        };
+        let (x, y, w, h) = present_rect(fit_w, fit_h, dst_w, dst_h, self.config.scale);
+
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        gl.disable(glow::DEPTH_TEST);
+        gl.disable(glow::BLEND);
+        if (x, y, w, h) != (0, 0, dst_w, dst_h) {
+            let [r, g, b, a] = self.config.clear_color;
+            gl.viewport(0, 0, dst_w, dst_h);
+            gl.clear_color(r, g, b, a);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+        }
+        gl.viewport(x, y, w, h);
+
+        gl.use_program(Some(self.program));
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+        if let Some(loc) = gl.get_uniform_location(self.program, "iChannel0") {
+            gl.uniform_1_i32(Some(&loc), 0);
+        }
+        if let Some(loc) = gl.get_uniform_location(self.program, "u_uv_xform") {
+            gl.uniform_matrix_2_f32_slice(Some(&loc), false, &self.config.uv_transform());
+        }
+        if let Some(loc) = gl.get_uniform_location(self.program, "u_gain") {
+            gl.uniform_1_f32(Some(&loc), self.config.gain);
+        }
+
+        self.tri.draw(gl);
+        gl.viewport(0, 0, dst_w, dst_h);
+    }
+
+    /// [`Presenter::present`] for a plan output.
+    pub unsafe fn present_output(
+        &self,
+        gl: &glow::Context,
+        out: &ExecOutput,
+        dst_w: i32,
+        dst_h: i32,
+    ) {
+        self.present(gl, out.tex, out.width, out.height, dst_w, dst_h);
+    }
+
+    pub unsafe fn destroy(&mut self, gl: &glow::Context) {
+        gl.delete_program(self.program);
+        self.tri.destroy(gl);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn letterbox_preserves_aspect_and_centres() {
+        // 16:9 into 4:3 -> bars top and bottom.
+        assert_eq!(present_rect(1920, 1080, 800, 600, ScaleMode::Letterbox), (0, 75, 800, 450));
+        // 4:3 into 16:9 -> bars left and right.
+        assert_eq!(present_rect(800, 600, 1920, 1080, ScaleMode::Letterbox), (240, 0, 1440, 1080));
+        // Same aspect fills exactly.
+        assert_eq!(present_rect(640, 360, 1280, 720, ScaleMode::Letterbox), (0, 0, 1280, 720));
+    }
+
+    #[test]
+    fn stretch_and_native_rects() {
+        assert_eq!(present_rect(640, 480, 1280, 720, ScaleMode::Stretch), (0, 0, 1280, 720));
+        assert_eq!(present_rect(640, 480, 1280, 720, ScaleMode::Native), (320, 120, 640, 480));
+        assert_eq!(present_rect(0, 480, 1280, 720, ScaleMode::Native), (0, 0, 1280, 720));
+    }
+
+    fn map(cfg: &PresenterConfig, uv: [f32; 2]) -> [f32; 2] {
+        let [a, b, c, d] = cfg.uv_transform();
+        let (x, y) = (uv[0] - 0.5, uv[1] - 0.5);
+        [a * x + c * y + 0.5, b * x + d * y + 0.5]
+    }
+
+    #[test]
+    fn presenter_uv_transform_orientations() {
+        let mut cfg = PresenterConfig::default();
+        assert_eq!(map(&cfg, [0.0, 0.0]), [0.0, 0.0]);
+
+        // 90 CW: the screen's top-left shows the source's bottom-left.
+        cfg.rotation = Rotation::Cw90;
+        assert_eq!(map(&cfg, [0.0, 1.0]), [0.0, 0.0]);
+        assert_eq!(map(&cfg, [1.0, 1.0]), [0.0, 1.0]);
+
+        // 180 is the same as flipping both axes (the old webcam presenter shader).
+        cfg.rotation = Rotation::Cw180;
+        assert_eq!(map(&cfg, [0.25, 0.0]), [0.75, 1.0]);
+        let both = PresenterConfig { flip_x: true, flip_y: true, ..Default::default() };
+        assert_eq!(map(&both, [0.25, 0.0]), [0.75, 1.0]);
+
+        // Flip applies on screen axes after rotation.
+        cfg.rotation = Rotation::Cw270;
+        cfg.flip_x = true;
+        let unflipped = PresenterConfig { rotation: Rotation::Cw270, ..Default::default() };
+        assert_eq!(map(&cfg, [0.0, 0.0]), map(&unflipped, [1.0, 0.0]));
+        assert!(Rotation::Cw270.swaps_axes() && !Rotation::Cw180.swaps_axes());
+    }
+}
@@ -0,0 +1,78 @@
+//! Pixel probes on named outputs (`RuntimeState::probe_pixel`).
+//!
+//! Eyedropper tools and auto-calibration loops (sampling a projected test patch through a
+//! camera) need single values, not frames. The runtime remembers the outputs of the last frame:
+//! `main` after `execute_plan`, and every named output and debug tap after
+//! `execute_plan_outputs`. A probe reads back one texel of one of them as float RGBA, so float
+//! targets report values outside 0..1. Each read stalls until the GPU has finished the frame, so
+//! values are cached until the next frame and probing a pixel twice costs one readback.
+
+use std::collections::HashMap;
+
+use glow::HasContext;
+
+use crate::{ExecOutput, RuntimeState};
+
+/// The last frame's outputs and the values read from them.
+#[derive(Debug, Default)]
+pub(crate) struct OutputProbes {
+    outputs: Vec<(String, ExecOutput)>,
+    cache: HashMap<(String, i32, i32), [f32; 4]>,
+}
+
+impl OutputProbes {
+    /// Replace the probed outputs with this frame's (dropping cached values).
+    pub(crate) fn set_outputs<'a>(
+        &mut self,
+        outputs: impl IntoIterator<Item = (&'a str, ExecOutput)>,
+    ) {
+        self.outputs.clear();
+        self.outputs.extend(
+            outputs
+                .into_iter()
+                .map(|(name, out)| (name.to_string(), out)),
+        );
+        self.cache.clear();
+    }
+
+    /// Forget everything (context lost).
+    pub(crate) fn invalidate(&mut self) {
+        self.outputs.clear();
+        self.cache.clear();
+    }
+}
+
+impl RuntimeState {
+    /// RGBA of pixel `(x, y)` (top-left origin) of output `name` as rendered by the last frame,
+    /// or `None` for an unknown output or a pixel outside it (see [`probe`](crate::probe)).
+    pub unsafe fn probe_pixel(
+        &mut self,
+        gl: &glow::Context,
+        name: &str,
+        x: i32,
+        y: i32,
+    ) -> Option<[f32; 4]> {
+        let probes = &mut self.probes;
+        let &(_, out) = probes.outputs.iter().find(|(n, _)| n == name)?;
+        if !(0..out.width).contains(&x) || !(0..out.height).contains(&y) {
+            return None;
+        }
+        if let Some(&rgba) = probes.cache.get(&(name.to_string(), x, y)) {
+            return Some(rgba);
+        }
+        let mut rgba = [0.0f32; 4];
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(out.fbo));
+        gl.read_pixels(
+            x,
+            out.height - 1 - y,
+            1,
+            1,
+            glow::RGBA,
+            glow::FLOAT,
+            glow::PixelPackData::Slice(bytemuck::cast_slice_mut(&mut rgba)),
+        );
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        probes.cache.insert((name.to_string(), x, y), rgba);
+        Some(rgba)
+    }
+}
@@ -0,0 +1,286 @@
+//! On-disk program binary cache (`glGetProgramBinary` / `glProgramBinary`).
+//!
+//! glow does not expose the program-binary entry points, so the host loads them with the same
+//! loader it used for `glow::Context` ([`ProgramBinaryApi::load`]). Entries are keyed by a stable
+//! hash of the vertex + fragment source and the driver identity (vendor / renderer / version);
+//! a driver update simply misses and recompiles. Any load failure falls back to compiling.
+
+use std::ffi::c_void;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use glow::HasContext;
+
+use crate::{compile_program_with, EngineError};
+
+const MAGIC: &[u8; 4] = b"SCPB";
+const FORMAT_VERSION: u32 = 1;
+/// magic + version + driver hash + vert hash + frag hash + binary format + length.
+const HEADER_LEN: usize = 4 + 4 + 8 + 8 + 8 + 4 + 4;
+
+type GetProgramBinaryFn = unsafe extern "system" fn(u32, i32, *mut i32, *mut u32, *mut c_void);
+type ProgramBinaryFn = unsafe extern "system" fn(u32, u32, *const c_void, i32);
+type GetProgramivFn = unsafe extern "system" fn(u32, u32, *mut i32);
+type ProgramParameteriFn = unsafe extern "system" fn(u32, u32, i32);
+
+/// Program-binary entry points (GL 4.1 / `ARB_get_program_binary`).
+#[derive(Debug, Clone, Copy)]
+pub struct ProgramBinaryApi {
+    get_program_binary: GetProgramBinaryFn,
+    program_binary: ProgramBinaryFn,
+    get_programiv: GetProgramivFn,
+    program_parameteri: Option<ProgramParameteriFn>,
+}
+
+impl ProgramBinaryApi {
+    /// Resolve the entry points through the host's GL loader (e.g. glutin `get_proc_address`).
+    ///
+    /// Returns `None` if the driver does not provide them.
+    pub unsafe fn load<F>(mut loader: F) -> Option<Self>
+    where
+        F: FnMut(&str) -> *const c_void,
+    {
+        let get_program_binary = loader("glGetProgramBinary");
+        let program_binary = loader("glProgramBinary");
+        let get_programiv = loader("glGetProgramiv");
+        let program_parameteri = loader("glProgramParameteri");
+        if get_program_binary.is_null() || program_binary.is_null() || get_programiv.is_null() {
+            return None;
+        }
+        Some(Self {
+            get_program_binary: std::mem::transmute::<*const c_void, GetProgramBinaryFn>(
+                get_program_binary,
+            ),
+            program_binary: std::mem::transmute::<*const c_void, ProgramBinaryFn>(program_binary),
+            get_programiv: std::mem::transmute::<*const c_void, GetProgramivFn>(get_programiv),
+            program_parameteri: (!program_parameteri.is_null()).then(|| {
+                std::mem::transmute::<*const c_void, ProgramParameteriFn>(program_parameteri)
+            }),
+        })
+    }
+}
+
+/// Hit / miss counters for a [`ProgramDiskCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProgramDiskCacheStats {
+    /// Programs restored from disk.
+    pub hits: u64,
+    /// Programs compiled from source (no entry, stale entry or rejected binary).
+    pub misses: u64,
+    /// Binaries written after compiling.
+    pub writes: u64,
+}
+
+/// Directory of cached program binaries.
+#[derive(Debug)]
+pub struct ProgramDiskCache {
+    dir: PathBuf,
+    api: ProgramBinaryApi,
+    /// Hash of vendor/renderer/version, resolved on first use (needs a current context).
+    driver_hash: Option<u64>,
+    stats: ProgramDiskCacheStats,
+}
+
+impl ProgramDiskCache {
+    /// Cache binaries under `dir` (created on first write).
+    pub fn new(dir: impl Into<PathBuf>, api: ProgramBinaryApi) -> Self {
+        Self {
+            dir: dir.into(),
+            api,
+            driver_hash: None,
+            stats: ProgramDiskCacheStats::default(),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn stats(&self) -> ProgramDiskCacheStats {
+        self.stats
+    }
+
+    /// Delete every cached binary in the directory.
+    pub fn clear(&self) -> Result<(), EngineError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(source) => {
+                return Err(EngineError::Io {
+                    path: self.dir.clone(),
+                    source,
+                })
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|e| e == "bin") {
+                fs::remove_file(&path).map_err(|source| EngineError::Io { path, source })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Restore the program from disk, or compile it and store the binary.
+    pub(crate) unsafe fn get_or_compile(
+        &mut self,
+        gl: &glow::Context,
+        vert: &str,
+        frag: &str,
+    ) -> Result<glow::NativeProgram, EngineError> {
+        let driver = *self.driver_hash.get_or_insert_with(|| driver_hash(gl));
+        let (vh, fh) = (fnv1a64(vert.as_bytes()), fnv1a64(frag.as_bytes()));
+        let path = self.dir.join(format!("{driver:016x}-{vh:016x}-{fh:016x}.bin"));
+
+        if let Some(program) = self.try_load(gl, &path, driver, vh, fh) {
+            self.stats.hits += 1;
+            return Ok(program);
+        }
+        self.stats.misses += 1;
+
+        let hint = self.api.program_parameteri;
+        let program = compile_program_with(gl, vert, frag, |p| {
+            if let Some(set) = hint {
+                set(p.0.get(), glow::PROGRAM_BINARY_RETRIEVABLE_HINT, 1);
+            }
+        })?;
+        if self.store(&path, program, driver, vh, fh) {
+            self.stats.writes += 1;
+        }
+        Ok(program)
+    }
+
+    unsafe fn try_load(
+        &self,
+        gl: &glow::Context,
+        path: &Path,
+        driver: u64,
+        vh: u64,
+        fh: u64,
+    ) -> Option<glow::NativeProgram> {
+        let bytes = fs::read(path).ok()?;
+        let header = parse_header(&bytes)?;
+        if header.driver != driver || header.vert != vh || header.frag != fh {
+            return None;
+        }
+        let binary = bytes.get(HEADER_LEN..HEADER_LEN + header.len)?;
+
+        let program = gl.create_program().ok()?;
+        (self.api.program_binary)(
+            program.0.get(),
+            header.format,
+            binary.as_ptr().cast(),
+            binary.len() as i32,
+        );
+        if gl.get_program_link_status(program) {
+            Some(program)
+        } else {
+            // Driver rejected the binary (e.g. changed internals without a version bump).
+            gl.delete_program(program);
+            None
+        }
+    }
+
+    unsafe fn store(&self, path: &Path, program: glow::NativeProgram, driver: u64, vh: u64, fh: u64) -> bool {
+        let id = program.0.get();
+        let mut len = 0i32;
+        (self.api.get_programiv)(id, glow::PROGRAM_BINARY_LENGTH, &mut len);
+        if len <= 0 {
+            return false;
+        }
+        let mut binary = vec![0u8; len as usize];
+        let mut written = 0i32;
+        let mut format = 0u32;
+        (self.api.get_program_binary)(id, len, &mut written, &mut format, binary.as_mut_ptr().cast());
+        if written <= 0 {
+            return false;
+        }
+        binary.truncate(written as usize);
+
+        let mut out = Vec::with_capacity(HEADER_LEN + binary.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        out.extend_from_slice(&driver.to_le_bytes());
+        out.extend_from_slice(&vh.to_le_bytes());
+        out.extend_from_slice(&fh.to_le_bytes());
+        out.extend_from_slice(&format.to_le_bytes());
+        out.extend_from_slice(&(binary.len() as u32).to_le_bytes());
+        out.extend_from_slice(&binary);
+
+        if fs::create_dir_all(&self.dir).is_err() {
+            return false;
+        }
+        // Write-then-rename so a crash never leaves a truncated entry behind.
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, &out).is_ok() && fs::rename(&tmp, path).is_ok()
+    }
+}
+
+struct Header {
+    driver: u64,
+    vert: u64,
+    frag: u64,
+    format: u32,
+    len: usize,
+}
+
+fn parse_header(bytes: &[u8]) -> Option<Header> {
+    if bytes.len() < HEADER_LEN || &bytes[0..4] != MAGIC {
+        return None;
+    }
+    let u32_at = |o: usize| u32::from_le_bytes(bytes[o..o + 4].try_into().unwrap());
+    let u64_at = |o: usize| u64::from_le_bytes(bytes[o..o + 8].try_into().unwrap());
+    if u32_at(4) != FORMAT_VERSION {
+        return None;
+    }
+    Some(Header {
+        driver: u64_at(8),
+        vert: u64_at(16),
+        frag: u64_at(24),
+        format: u32_at(32),
+        len: u32_at(36) as usize,
+    })
+}
+
+unsafe fn driver_hash(gl: &glow::Context) -> u64 {
+    let ident = format!(
+        "{}\n{}\n{}",
+        gl.get_parameter_string(glow::VENDOR),
+        gl.get_parameter_string(glow::RENDERER),
+        gl.get_parameter_string(glow::VERSION)
+    );
+    fnv1a64(ident.as_bytes())
+}
+
+/// FNV-1a: stable across Rust versions (unlike `DefaultHasher`), so keys survive rebuilds.
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in bytes {
+        h ^= *b as u64;
+        h = h.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv_is_stable() {
+        assert_eq!(fnv1a64(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a64(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn header_rejects_bad_magic_and_version() {
+        let mut bytes = vec![0u8; HEADER_LEN];
+        assert!(parse_header(&bytes).is_none());
+        bytes[0..4].copy_from_slice(MAGIC);
+        bytes[4..8].copy_from_slice(&2u32.to_le_bytes());
+        assert!(parse_header(&bytes).is_none());
+        bytes[4..8].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bytes[36..40].copy_from_slice(&7u32.to_le_bytes());
+        assert_eq!(parse_header(&bytes).map(|h| h.len), Some(7));
+    }
+}
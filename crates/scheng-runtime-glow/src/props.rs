@@ -0,0 +1,486 @@
+//! Building [`NodeProps`] and layering one set over another.
+//!
+//! Props are built with the `with_*` methods instead of poking at the maps, and a partial set
+//! (an *overlay*, e.g. a scene's shader swaps and uniforms) is layered over a base set with
+//! [`NodeProps::merge`] or [`NodeProps::merged`]. The base stays untouched by the latter, so
+//! recalling another scene starts again from the base instead of inheriting what the previous
+//! scene changed.
+//!
+//! Merge precedence, overlay over base:
+//! - Per-node entries (shaders and shader banks, parameter blocks, output names and alpha, clear modes, inputs,
+//!   geometry) are replaced whole: the overlay's entry for a node wins, other nodes keep the
+//!   base entry.
+//! - `custom_uniforms` and `global_uniforms` merge by uniform name, so an overlay setting one
+//!   uniform leaves the node's other uniforms alone.
+//! - An overlay input replaces the base input of the same node even across fields: a
+//!   `shared_surfaces` entry drops the base `texture_inputs` entry, and a `video_decode_json` or
+//!   `video_decode_cfg` entry drops the other one.
+//! - Overlay `buffer_sets` come before the base sets (the first set containing a node wins).
+//! - Non-empty overlay `audio_samples` replace the base samples.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use scheng_graph::{Graph, NodeId};
+
+use crate::{
+    AlphaPolicy, BufferSet, ClearMode, Geometry, NodeProps, ShaderBank, ShaderSource,
+    FULLSCREEN_VERT,
+};
+
+/// A per-node parameter block stored in [`NodeProps`] (see [`NodeProps::with_params`]).
+pub trait NodeParams: Sized {
+    /// The `NodeProps` map holding blocks of this type.
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self>;
+}
+
+impl NodeParams for scheng_runtime::MixerParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.mixer_params
+    }
+}
+
+impl NodeParams for scheng_runtime::BlendParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.blend_params
+    }
+}
+
+impl NodeParams for scheng_runtime::ParticleParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.particle_params
+    }
+}
+
+impl NodeParams for scheng_runtime::AudioVisParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.audio_vis_params
+    }
+}
+
+impl NodeParams for scheng_runtime::KeyMixParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.key_params
+    }
+}
+
+impl NodeParams for scheng_runtime::MatrixMixParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.matrix_params
+    }
+}
+
+impl NodeParams for scheng_runtime::MotionParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.motion_params
+    }
+}
+
+impl NodeParams for scheng_runtime::FlowParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.flow_params
+    }
+}
+
+impl NodeParams for scheng_runtime::DisplaceParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.displace_params
+    }
+}
+
+impl NodeParams for scheng_runtime::BloomParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.bloom_params
+    }
+}
+
+impl NodeParams for scheng_runtime::PixelateParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.pixelate_params
+    }
+}
+
+impl NodeParams for scheng_runtime::PosterizeParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.posterize_params
+    }
+}
+
+impl NodeParams for scheng_runtime::DitherParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.dither_params
+    }
+}
+
+impl NodeParams for scheng_runtime::TemporalParams {
+    fn map_mut(props: &mut NodeProps) -> &mut HashMap<NodeId, Self> {
+        &mut props.temporal_params
+    }
+}
+
+impl NodeProps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_shader(mut self, node: NodeId, shader: ShaderSource) -> Self {
+        self.shader_sources.insert(node, shader);
+        self
+    }
+
+    /// A fragment shader drawn with [`FULLSCREEN_VERT`]; `origin` labels it in errors.
+    pub fn with_frag(
+        self,
+        node: NodeId,
+        frag: impl Into<String>,
+        origin: impl Into<String>,
+    ) -> Self {
+        self.with_shader(
+            node,
+            ShaderSource {
+                vert: FULLSCREEN_VERT.to_string(),
+                frag: frag.into(),
+                origin: Some(origin.into()),
+            },
+        )
+    }
+
+    /// Set the parameter block of `node` (mixer, key, bloom, ... picked by the block's type).
+    pub fn with_params<P: NodeParams>(mut self, node: NodeId, params: P) -> Self {
+        P::map_mut(&mut self).insert(node, params);
+        self
+    }
+
+    pub fn with_output_name(mut self, node: NodeId, name: impl Into<String>) -> Self {
+        self.output_names.insert(node, name.into());
+        self
+    }
+
+    /// Shaders `node` switches between live; the slot is a registry parameter (see
+    /// [`banks`](crate::banks)).
+    pub fn with_shader_bank(mut self, node: NodeId, bank: ShaderBank) -> Self {
+        self.shader_banks.insert(node, bank);
+        self
+    }
+
+    /// How `node`'s target starts each frame: a background colour or the previous frame.
+    pub fn with_clear(mut self, node: NodeId, mode: ClearMode) -> Self {
+        self.clear_modes.insert(node, mode);
+        self
+    }
+
+    /// How the `PixelsOut` `node` hands alpha to its sinks.
+    pub fn with_output_alpha(mut self, node: NodeId, policy: AlphaPolicy) -> Self {
+        self.output_alpha.insert(node, policy);
+        self
+    }
+
+    /// Add one of `node`'s custom uniforms.
+    pub fn with_uniform(mut self, node: NodeId, name: impl Into<String>, value: f32) -> Self {
+        self.custom_uniforms
+            .entry(node)
+            .or_default()
+            .insert(name.into(), value);
+        self
+    }
+
+    pub fn with_global_uniform(mut self, name: impl Into<String>, value: f32) -> Self {
+        self.global_uniforms.insert(name.into(), value);
+        self
+    }
+
+    pub fn with_texture_input(mut self, node: NodeId, texture: glow::NativeTexture) -> Self {
+        self.texture_inputs.insert(node, texture);
+        self
+    }
+
+    pub fn with_geometry(mut self, node: NodeId, geometry: Geometry) -> Self {
+        self.geometry.insert(node, geometry);
+        self
+    }
+
+    pub fn with_buffer_set(mut self, set: BufferSet) -> Self {
+        self.buffer_sets.push(set);
+        self
+    }
+
+    #[cfg(feature = "video")]
+    pub fn with_video_config(
+        mut self,
+        node: NodeId,
+        config: scheng_input_video::VideoConfig,
+    ) -> Self {
+        self.video_decode_cfg.insert(node, config);
+        self
+    }
+
+    /// Layer `overlay` over these props (precedence in the [module docs](crate::props)).
+    pub fn merge(&mut self, overlay: &NodeProps) {
+        // Destructured so a new field does not compile until it has a merge rule.
+        let NodeProps {
+            shader_sources,
+            mixer_params,
+            blend_params,
+            particle_params,
+            audio_vis_params,
+            audio_samples,
+            geometry,
+            buffer_sets,
+            key_params,
+            matrix_params,
+            output_names,
+            texture_inputs,
+            shared_surfaces,
+            #[cfg(feature = "video")]
+            video_decode_json,
+            #[cfg(feature = "video")]
+            video_decode_cfg,
+            custom_uniforms,
+            global_uniforms,
+            motion_params,
+            flow_params,
+            displace_params,
+            bloom_params,
+            pixelate_params,
+            posterize_params,
+            dither_params,
+            temporal_params,
+            output_alpha,
+            clear_modes,
+            shader_banks,
+        } = overlay;
+
+        extend_cloned(&mut self.shader_sources, shader_sources);
+        extend_cloned(&mut self.mixer_params, mixer_params);
+        extend_cloned(&mut self.blend_params, blend_params);
+        extend_cloned(&mut self.particle_params, particle_params);
+        extend_cloned(&mut self.audio_vis_params, audio_vis_params);
+        extend_cloned(&mut self.geometry, geometry);
+        extend_cloned(&mut self.key_params, key_params);
+        extend_cloned(&mut self.matrix_params, matrix_params);
+        extend_cloned(&mut self.output_names, output_names);
+        extend_cloned(&mut self.motion_params, motion_params);
+        extend_cloned(&mut self.flow_params, flow_params);
+        extend_cloned(&mut self.displace_params, displace_params);
+        extend_cloned(&mut self.bloom_params, bloom_params);
+        extend_cloned(&mut self.pixelate_params, pixelate_params);
+        extend_cloned(&mut self.posterize_params, posterize_params);
+        extend_cloned(&mut self.dither_params, dither_params);
+        extend_cloned(&mut self.temporal_params, temporal_params);
+        extend_cloned(&mut self.output_alpha, output_alpha);
+        extend_cloned(&mut self.clear_modes, clear_modes);
+        extend_cloned(&mut self.shader_banks, shader_banks);
+
+        for node in shared_surfaces.keys() {
+            self.texture_inputs.remove(node);
+        }
+        extend_cloned(&mut self.texture_inputs, texture_inputs);
+        extend_cloned(&mut self.shared_surfaces, shared_surfaces);
+        #[cfg(feature = "video")]
+        {
+            for node in video_decode_json.keys() {
+                self.video_decode_cfg.remove(node);
+            }
+            for node in video_decode_cfg.keys() {
+                self.video_decode_json.remove(node);
+            }
+            extend_cloned(&mut self.video_decode_json, video_decode_json);
+            extend_cloned(&mut self.video_decode_cfg, video_decode_cfg);
+        }
+
+        for (node, uniforms) in custom_uniforms {
+            extend_cloned(self.custom_uniforms.entry(*node).or_default(), uniforms);
+        }
+        extend_cloned(&mut self.global_uniforms, global_uniforms);
+
+        if !buffer_sets.is_empty() {
+            let base = std::mem::replace(&mut self.buffer_sets, buffer_sets.clone());
+            self.buffer_sets.extend(base);
+        }
+        if !audio_samples.is_empty() {
+            self.audio_samples.clone_from(audio_samples);
+        }
+    }
+
+    /// These props with `overlay` layered on top, leaving both untouched.
+    pub fn merged(&self, overlay: &NodeProps) -> NodeProps {
+        let mut props = self.clone();
+        props.merge(overlay);
+        props
+    }
+
+    /// The overlay a scene applies on top of these props: its shader swaps and uniform values.
+    ///
+    /// Override nodes are looked up in `graph` by id or name and shader paths are resolved
+    /// against `shader_dir` (usually the bank file's directory). Swapped nodes keep the vertex
+    /// shader they have here (or get `FULLSCREEN_VERT`) and recompile on the next frame. A shader
+    /// swap on a node with a (non-empty) shader bank is an error, since the bank would hide it;
+    /// switch the bank's slot parameter instead. Matrix weights are left to the caller, which
+    /// usually crossfades them.
+    pub fn scene_overlay(
+        &self,
+        graph: &Graph,
+        scene: &scheng_runtime::SceneDef,
+        shader_dir: &std::path::Path,
+    ) -> Result<NodeProps, String> {
+        let mut overlay = NodeProps::default();
+        for o in &scene.overrides {
+            let node = o.node_id(graph).ok_or_else(|| {
+                format!("scene '{}': no node {} in the graph", scene.name, o.node)
+            })?;
+            if let Some(rel) = &o.shader {
+                if self
+                    .shader_banks
+                    .get(&node)
+                    .is_some_and(|b| !b.slots.is_empty())
+                {
+                    return Err(format!(
+                        "scene '{}': node {} runs a shader bank, which takes precedence over \
+                         a swapped shader; switch its slot parameter instead",
+                        scene.name, o.node
+                    ));
+                }
+                let path = shader_dir.join(rel);
+                let frag = std::fs::read_to_string(&path)
+                    .map_err(|e| format!("read {}: {e}", path.display()))?;
+                let vert = self
+                    .shader_sources
+                    .get(&node)
+                    .map(|s| s.vert.clone())
+                    .unwrap_or_else(|| FULLSCREEN_VERT.to_string());
+                overlay.shader_sources.insert(
+                    node,
+                    ShaderSource {
+                        vert,
+                        frag,
+                        origin: Some(path.display().to_string()),
+                    },
+                );
+            }
+            if !o.uniforms.is_empty() {
+                overlay
+                    .custom_uniforms
+                    .entry(node)
+                    .or_default()
+                    .extend(o.uniforms.iter().map(|(name, v)| (name.clone(), *v)));
+            }
+        }
+        Ok(overlay)
+    }
+}
+
+/// Insert clones of every `overlay` entry into `base`.
+fn extend_cloned<K: Clone + Eq + Hash, V: Clone>(
+    base: &mut HashMap<K, V>,
+    overlay: &HashMap<K, V>,
+) {
+    base.extend(overlay.iter().map(|(k, v)| (k.clone(), v.clone())));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scheng_graph::NodeKind;
+    use scheng_runtime::{BloomParams, MatrixPreset, MixerParams, SceneDef};
+
+    #[test]
+    fn overlays_replace_entries_and_merge_uniforms() {
+        let (a, b) = (NodeId(1), NodeId(2));
+        let base = NodeProps::new()
+            .with_frag(a, "base a", "a")
+            .with_frag(b, "base b", "b")
+            .with_params(a, MixerParams::new(0.25))
+            .with_uniform(a, "u_gain", 1.0)
+            .with_uniform(a, "u_hue", 0.5)
+            .with_global_uniform("u_master", 1.0)
+            .with_buffer_set(BufferSet::new().with_buffer(0, a));
+        let overlay = NodeProps::new()
+            .with_frag(b, "overlay b", "b2")
+            .with_params(b, BloomParams::default())
+            .with_uniform(a, "u_gain", 2.0)
+            .with_global_uniform("u_master", 0.5)
+            .with_buffer_set(BufferSet::new().with_buffer(0, b));
+
+        let props = base.merged(&overlay);
+        assert_eq!(props.shader_sources[&a].frag, "base a");
+        assert_eq!(props.shader_sources[&b].frag, "overlay b");
+        assert_eq!(props.mixer_params[&a].mix, 0.25);
+        assert!(props.bloom_params.contains_key(&b));
+        let uniforms = &props.custom_uniforms[&a];
+        assert_eq!((uniforms["u_gain"], uniforms["u_hue"]), (2.0, 0.5));
+        assert_eq!(props.global_uniforms["u_master"], 0.5);
+        assert_eq!(
+            props.buffer_sets[0].buffers[0],
+            Some(b),
+            "overlay sets come first"
+        );
+        assert_eq!(props.buffer_sets.len(), 2);
+
+        // The base is untouched, so the next overlay starts from it again.
+        assert_eq!(base.shader_sources[&b].frag, "base b");
+        assert_eq!(base.custom_uniforms[&a]["u_gain"], 1.0);
+    }
+
+    #[test]
+    fn scene_overlays_leave_the_base_alone() {
+        let dir = std::env::temp_dir().join(format!("scheng-props-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("warm.frag"), "warm").unwrap();
+        let mut g = Graph::new();
+        let cam = g.add_node(NodeKind::ShaderPass);
+        g.set_node_name(cam, "cam").unwrap();
+
+        let base = NodeProps::new().with_shader(
+            cam,
+            ShaderSource {
+                vert: "custom vert".into(),
+                frag: "cold".into(),
+                origin: None,
+            },
+        );
+        let warm = SceneDef::new("warm", MatrixPreset::Quad)
+            .with_shader("cam", "warm.frag")
+            .with_uniform("cam", "u_gain", 1.5);
+        let props = base.merged(&base.scene_overlay(&g, &warm, &dir).unwrap());
+        assert_eq!(props.shader_sources[&cam].frag, "warm");
+        assert_eq!(props.shader_sources[&cam].vert, "custom vert");
+        assert_eq!(props.custom_uniforms[&cam]["u_gain"], 1.5);
+
+        let plain = SceneDef::new("plain", MatrixPreset::Quad);
+        let props = base.merged(&base.scene_overlay(&g, &plain, &dir).unwrap());
+        assert_eq!(props.shader_sources[&cam].frag, "cold");
+        assert!(props.custom_uniforms.is_empty());
+
+        let missing = SceneDef::new("missing", MatrixPreset::Quad).with_uniform("nope", "u", 1.0);
+        assert!(base.scene_overlay(&g, &missing, &dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn scene_shader_swaps_on_banked_nodes_are_refused() {
+        let mut g = Graph::new();
+        let cam = g.add_node(NodeKind::ShaderPass);
+        g.set_node_name(cam, "cam").unwrap();
+        let shader = |frag: &str| ShaderSource {
+            vert: FULLSCREEN_VERT.into(),
+            frag: frag.into(),
+            origin: None,
+        };
+        let banked = NodeProps::new()
+            .with_shader(cam, shader("cold"))
+            .with_shader_bank(cam, ShaderBank::new(vec![shader("a"), shader("b")]));
+        let dir = std::path::Path::new("unused");
+
+        // The bank would keep rendering, so the swap is an error rather than a silent no-op.
+        let warm = SceneDef::new("warm", MatrixPreset::Quad).with_shader("cam", "warm.frag");
+        let err = banked.scene_overlay(&g, &warm, dir).unwrap_err();
+        assert!(err.contains("shader bank"), "{err}");
+
+        // Uniforms still apply, and an empty bank is ignored like everywhere else.
+        let gain = SceneDef::new("gain", MatrixPreset::Quad).with_uniform("cam", "u_gain", 2.0);
+        assert!(banked.scene_overlay(&g, &gain, dir).is_ok());
+        let empty = banked.with_shader_bank(cam, ShaderBank::default());
+        let err = empty.scene_overlay(&g, &warm, dir).unwrap_err();
+        assert!(err.contains("warm.frag"), "reads the shader: {err}");
+    }
+}
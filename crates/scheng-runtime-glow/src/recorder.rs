@@ -0,0 +1,473 @@
+//! Recording to disk: program and clean feeds, with takes started and stopped together.
+//!
+//! Convention for shows with overlays (titles, cue markers, test cards, the latency stamp): give
+//! the graph two named outputs. [`OUTPUT_PROGRAM`] is the finished picture that goes to the
+//! projector. [`OUTPUT_CLEAN`] is a second `PixelsOut` fed from the last pass *before* the
+//! overlays. [`name_program_and_clean`] names them and [`route_program_and_clean`] patches them
+//! in a `PatchbaySink`. The clean feed usually goes to a [`RecorderSink`], and the program feed
+//! goes to the presenter (and optionally to a second recorder).
+//!
+//! All recorders built from one [`RecordControl`] start and stop on the same frame.
+//! [`RecordControl::start`] and [`RecordControl::stop`] only place a request. The first recorder
+//! to see a new frame in `on_frame_begin` applies it for every recorder, so each file of a take
+//! covers the same frames. Call start/stop from any thread.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use glow::HasContext;
+use scheng_graph::NodeId;
+
+use crate::{ExecOutput, FrameCtx, NodeProps, OutputName, OutputSink, PatchbaySink};
+
+/// Output name for the projected picture, overlays included.
+pub const OUTPUT_PROGRAM: OutputName = "program";
+
+/// Output name for the picture before overlays (for recording).
+pub const OUTPUT_CLEAN: OutputName = "clean";
+
+/// Name the program and clean `PixelsOut` nodes (see the module docs).
+pub fn name_program_and_clean(props: &mut NodeProps, program: NodeId, clean: NodeId) {
+    props
+        .output_names
+        .insert(program, OUTPUT_PROGRAM.to_string());
+    props.output_names.insert(clean, OUTPUT_CLEAN.to_string());
+}
+
+/// Route [`OUTPUT_PROGRAM`] to `program` and [`OUTPUT_CLEAN`] to `clean`. Returns the route
+/// indices `(program, clean)`.
+pub fn route_program_and_clean<P, C>(
+    patchbay: &mut PatchbaySink,
+    program: P,
+    clean: C,
+) -> (usize, usize)
+where
+    P: OutputSink + 'static,
+    C: OutputSink + 'static,
+{
+    (
+        patchbay.add_route(OUTPUT_PROGRAM, program),
+        patchbay.add_route(OUTPUT_CLEAN, clean),
+    )
+}
+
+#[derive(Debug, Default)]
+struct ControlInner {
+    /// What start/stop last asked for.
+    requested: bool,
+    /// What recorders do on `frame`.
+    recording: bool,
+    take: u64,
+    frame: Option<u64>,
+}
+
+/// Shared start/stop switch for a set of [`RecorderSink`]s. Clones share state.
+#[derive(Debug, Clone, Default)]
+pub struct RecordControl {
+    inner: Arc<Mutex<ControlInner>>,
+}
+
+impl RecordControl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new take on the next frame (no-op while recording).
+    pub fn start(&self) {
+        self.lock().requested = true;
+    }
+
+    /// End the take after the current frame.
+    pub fn stop(&self) {
+        self.lock().requested = false;
+    }
+
+    /// Whether the current frame is being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.lock().recording
+    }
+
+    /// Number of the current (or last) take, starting at 1; 0 before the first take.
+    pub fn take(&self) -> u64 {
+        self.lock().take
+    }
+
+    /// Apply a pending request when `frame` is new. Returns the take recorded on `frame`.
+    fn latch(&self, frame: u64) -> Option<u64> {
+        let mut c = self.lock();
+        if c.frame != Some(frame) {
+            c.frame = Some(frame);
+            if c.requested != c.recording {
+                c.recording = c.requested;
+                c.take += c.recording as u64;
+            }
+        }
+        c.recording.then_some(c.take)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ControlInner> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// How a [`RecorderSink`] writes frames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RecordFormat {
+    /// Pipe raw RGBA into an ffmpeg child process that encodes to `path`.
+    #[default]
+    Ffmpeg,
+    /// Append raw RGBA8 frames (rows top-down) to `path`; no ffmpeg needed.
+    RawRgba,
+}
+
+/// Where and how a [`RecorderSink`] writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecorderConfig {
+    /// Output file. `{take}` is replaced by the zero-padded take number, so recorders that share
+    /// a control can write `clean_{take}.mp4` and `program_{take}.mp4`.
+    pub path: PathBuf,
+    pub format: RecordFormat,
+    /// Frame rate written into the file (ffmpeg only).
+    pub fps: u32,
+    /// Encoder arguments placed before the output path (ffmpeg only).
+    pub encoder_args: Vec<String>,
+    /// ffmpeg binary; defaults to `$scheng_FFMPEG`, then `ffmpeg` on `PATH`.
+    pub ffmpeg_path: Option<PathBuf>,
+}
+
+impl RecorderConfig {
+    /// H.264 at 30 fps through ffmpeg.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            format: RecordFormat::Ffmpeg,
+            fps: 30,
+            encoder_args: [
+                "-c:v", "libx264", "-preset", "veryfast", "-pix_fmt", "yuv420p",
+            ]
+            .map(String::from)
+            .to_vec(),
+            ffmpeg_path: None,
+        }
+    }
+
+    /// Raw RGBA frames instead of ffmpeg.
+    pub fn raw(path: impl Into<PathBuf>) -> Self {
+        Self {
+            format: RecordFormat::RawRgba,
+            ..Self::new(path)
+        }
+    }
+
+    /// ProRes 4444 with alpha through ffmpeg (write a `.mov`), for outputs recorded with a
+    /// straight or premultiplied `AlphaPolicy`. The default H.264 arguments drop alpha.
+    pub fn with_alpha(mut self) -> Self {
+        self.encoder_args = [
+            "-c:v", "prores_ks", "-profile:v", "4444", "-pix_fmt", "yuva444p10le",
+        ]
+        .map(String::from)
+        .to_vec();
+        self
+    }
+
+    /// The file written for `take`.
+    pub fn path_for_take(&self, take: u64) -> PathBuf {
+        let path = self.path.to_string_lossy();
+        if path.contains("{take}") {
+            PathBuf::from(path.replace("{take}", &format!("{take:03}")))
+        } else {
+            self.path.clone()
+        }
+    }
+
+    fn ffmpeg_path(&self) -> PathBuf {
+        self.ffmpeg_path
+            .clone()
+            .or_else(|| std::env::var_os("scheng_FFMPEG").map(PathBuf::from))
+            .unwrap_or_else(|| PathBuf::from("ffmpeg"))
+    }
+}
+
+/// A finished (or running) take of one recorder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TakeSummary {
+    pub take: u64,
+    pub path: PathBuf,
+    pub width: i32,
+    pub height: i32,
+    pub frames: u64,
+}
+
+enum TakeWriter {
+    Ffmpeg {
+        child: Child,
+        stdin: BufWriter<ChildStdin>,
+    },
+    Raw(BufWriter<File>),
+}
+
+impl TakeWriter {
+    fn out(&mut self) -> &mut dyn Write {
+        match self {
+            Self::Ffmpeg { stdin, .. } => stdin,
+            Self::Raw(file) => file,
+        }
+    }
+
+    /// Flush and, for ffmpeg, close the pipe and wait for the encoder to finish the file.
+    fn finish(self) -> Result<(), String> {
+        match self {
+            Self::Ffmpeg { mut child, stdin } => {
+                let flushed = stdin.into_inner().map(drop).map_err(|e| e.to_string());
+                let status = child.wait().map_err(|e| e.to_string())?;
+                flushed?;
+                if status.success() {
+                    Ok(())
+                } else {
+                    Err(format!("ffmpeg exited with {status}"))
+                }
+            }
+            Self::Raw(mut file) => file.flush().map_err(|e| e.to_string()),
+        }
+    }
+}
+
+struct OpenTake {
+    summary: TakeSummary,
+    writer: TakeWriter,
+}
+
+/// Reads back the routed output and writes it to disk while its [`RecordControl`] is recording.
+///
+/// Readback and writing happen on the render thread, so lower the route's rate with
+/// `PatchbaySink::set_rate_divisor`, or write frames from a `TripleBufferSink` consumer instead. A take keeps the size of its first frame;
+/// frames of another size are dropped and reported in [`last_error`](Self::last_error).
+pub struct RecorderSink {
+    config: RecorderConfig,
+    control: RecordControl,
+    /// Take recorded on the current frame, latched in `on_frame_begin`.
+    frame_take: Option<u64>,
+    open: Option<OpenTake>,
+    /// Take that failed to open or write; not retried until the next take.
+    failed_take: Option<u64>,
+    last: Option<TakeSummary>,
+    error: Option<String>,
+    buf: Vec<u8>,
+}
+
+impl RecorderSink {
+    pub fn new(control: RecordControl, config: RecorderConfig) -> Self {
+        Self {
+            config,
+            control,
+            frame_take: None,
+            open: None,
+            failed_take: None,
+            last: None,
+            error: None,
+            buf: Vec::new(),
+        }
+    }
+
+    pub fn config(&self) -> &RecorderConfig {
+        &self.config
+    }
+
+    pub fn control(&self) -> &RecordControl {
+        &self.control
+    }
+
+    /// The take being written, if any.
+    pub fn current_take(&self) -> Option<&TakeSummary> {
+        self.open.as_ref().map(|t| &t.summary)
+    }
+
+    /// The most recently finished take.
+    pub fn last_take(&self) -> Option<&TakeSummary> {
+        self.last.as_ref()
+    }
+
+    /// The latest open, write or encoder error.
+    pub fn last_error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    fn open_take(&self, take: u64, width: i32, height: i32) -> Result<OpenTake, String> {
+        let path = self.config.path_for_take(take);
+        let writer = match self.config.format {
+            RecordFormat::RawRgba => TakeWriter::Raw(BufWriter::new(
+                File::create(&path).map_err(|e| e.to_string())?,
+            )),
+            RecordFormat::Ffmpeg => self.spawn_ffmpeg(&path, width, height)?,
+        };
+        Ok(OpenTake {
+            summary: TakeSummary {
+                take,
+                path,
+                width,
+                height,
+                frames: 0,
+            },
+            writer,
+        })
+    }
+
+    fn spawn_ffmpeg(&self, path: &Path, width: i32, height: i32) -> Result<TakeWriter, String> {
+        let mut child = Command::new(self.config.ffmpeg_path())
+            .args(["-hide_banner", "-loglevel", "error", "-y"])
+            .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+            .args(["-s", &format!("{width}x{height}")])
+            .args(["-r", &self.config.fps.max(1).to_string()])
+            .args(["-i", "-"])
+            .args(&self.config.encoder_args)
+            .arg(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("failed to start ffmpeg: {e}"))?;
+        let stdin = child.stdin.take().ok_or("ffmpeg stdin unavailable")?;
+        Ok(TakeWriter::Ffmpeg {
+            child,
+            stdin: BufWriter::new(stdin),
+        })
+    }
+
+    fn close_take(&mut self) {
+        if let Some(open) = self.open.take() {
+            if let Err(e) = open.writer.finish() {
+                self.error = Some(format!("take {}: {e}", open.summary.take));
+            }
+            self.last = Some(open.summary);
+        }
+    }
+
+    fn write_frame(&mut self, take: u64, width: i32, height: i32) -> Result<(), String> {
+        if self.open.as_ref().is_some_and(|t| t.summary.take != take) {
+            self.close_take();
+        }
+        if self.open.is_none() {
+            self.open = Some(self.open_take(take, width, height)?);
+        }
+        let Some(open) = &mut self.open else {
+            unreachable!()
+        };
+        if (open.summary.width, open.summary.height) != (width, height) {
+            self.error = Some(format!(
+                "take {take}: dropped a {width}x{height} frame (take is {}x{})",
+                open.summary.width, open.summary.height
+            ));
+            return Ok(());
+        }
+        // GL rows are bottom-up; files are top-down.
+        let row = width as usize * 4;
+        let out = open.writer.out();
+        for line in self.buf.chunks_exact(row).rev() {
+            out.write_all(line).map_err(|e| e.to_string())?;
+        }
+        open.summary.frames += 1;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for RecorderSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RecorderSink")
+            .field("config", &self.config)
+            .field("current_take", &self.current_take())
+            .field("last_take", &self.last)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl OutputSink for RecorderSink {
+    fn on_frame_begin(&mut self, _gl: &glow::Context, frame: &FrameCtx) {
+        self.frame_take = self.control.latch(frame.frame);
+        if self.frame_take.is_none() {
+            self.close_take();
+        }
+    }
+
+    fn consume(&mut self, gl: &glow::Context, out: &ExecOutput) {
+        let Some(take) = self.frame_take else { return };
+        if self.failed_take == Some(take) || out.width <= 0 || out.height <= 0 {
+            return;
+        }
+        self.buf.resize((out.width * out.height * 4) as usize, 0);
+        unsafe {
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(out.fbo));
+            gl.read_pixels(
+                0,
+                0,
+                out.width,
+                out.height,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut self.buf),
+            );
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+        }
+        if let Err(e) = self.write_frame(take, out.width, out.height) {
+            eprintln!("[RecorderSink] take {take}: {e}");
+            self.error = Some(format!("take {take}: {e}"));
+            self.failed_take = Some(take);
+            self.close_take();
+        }
+    }
+
+    fn destroy(&mut self, _gl: &glow::Context) {
+        self.close_take();
+    }
+}
+
+impl Drop for RecorderSink {
+    fn drop(&mut self) {
+        // Let ffmpeg write the trailer even if the host never called `destroy`.
+        self.close_take();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_switches_every_recorder_on_the_same_frame() {
+        let control = RecordControl::new();
+        let other = control.clone();
+        assert_eq!(control.latch(0), None);
+
+        // A start between two recorders' frame-begin hooks waits for the next frame.
+        control.start();
+        assert_eq!(other.latch(0), None);
+        assert_eq!(control.latch(1), Some(1));
+        assert_eq!(other.latch(1), Some(1));
+        assert!(control.is_recording());
+
+        control.stop();
+        assert_eq!(other.latch(1), Some(1));
+        assert_eq!(other.latch(2), None);
+        assert_eq!(control.latch(2), None);
+
+        control.start();
+        assert_eq!(control.latch(3), Some(2));
+        assert_eq!(other.take(), 2);
+    }
+
+    #[test]
+    fn take_numbers_fill_the_path_pattern() {
+        let cfg = RecorderConfig::raw("/tmp/show/clean_{take}.rgba");
+        assert_eq!(
+            cfg.path_for_take(7),
+            PathBuf::from("/tmp/show/clean_007.rgba")
+        );
+        assert_eq!(
+            RecorderConfig::new("out.mp4").path_for_take(3),
+            PathBuf::from("out.mp4")
+        );
+    }
+}
@@ -0,0 +1,265 @@
+//! Video scopes: per-channel histogram, luma waveform and vectorscope.
+//!
+//! `ScopeSink` downsamples the routed output on the GPU (framebuffer blit into a small target),
+//! reads back the reduced image and computes the scope data on the CPU. Only the small analysis
+//! image crosses the bus, so scopes are cheap enough to leave running on a monitor output.
+
+use glow::HasContext;
+
+use crate::{create_render_target, EngineError, ExecOutput, OutputSink, RenderTarget};
+
+/// Number of bins per histogram channel / waveform column.
+pub const SCOPE_BINS: usize = 256;
+
+/// Analysis settings for `ScopeSink`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScopeConfig {
+    /// Size of the GPU-downsampled analysis image.
+    pub sample_width: i32,
+    pub sample_height: i32,
+    /// Analyze every N-th frame (1 = every frame).
+    pub stride: u64,
+    /// Horizontal resolution of the waveform (columns across the image).
+    pub waveform_columns: usize,
+    /// Vectorscope grid is `vectorscope_size x vectorscope_size`.
+    pub vectorscope_size: usize,
+}
+
+impl Default for ScopeConfig {
+    fn default() -> Self {
+        Self {
+            sample_width: 256,
+            sample_height: 144,
+            stride: 2,
+            waveform_columns: 256,
+            vectorscope_size: 128,
+        }
+    }
+}
+
+/// Reduced scope data for one analyzed frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScopeData {
+    /// Analysis image size the data was computed from.
+    pub width: i32,
+    pub height: i32,
+    /// Histograms for R, G, B and Rec.709 luma (in that order).
+    pub histogram: [[u32; SCOPE_BINS]; 4],
+    /// Luma waveform, column-major: `waveform[col * SCOPE_BINS + level]`.
+    ///
+    /// Level 0 is black; the host flips vertically for display.
+    pub waveform: Vec<u32>,
+    pub waveform_columns: usize,
+    /// Vectorscope hit counts, row-major `vectorscope[y * size + x]`.
+    ///
+    /// x = Cb, y = Cr, both mapped from `-0.5..0.5` to `0..size`; the centre is neutral grey.
+    pub vectorscope: Vec<u32>,
+    pub vectorscope_size: usize,
+}
+
+impl ScopeData {
+    /// Waveform column `col` (SCOPE_BINS counts, black first).
+    pub fn waveform_column(&self, col: usize) -> &[u32] {
+        &self.waveform[col * SCOPE_BINS..(col + 1) * SCOPE_BINS]
+    }
+}
+
+/// Compute scope data from a tightly packed RGBA8 image (bottom-up rows, as read from GL).
+pub fn analyze_rgba(rgba: &[u8], width: i32, height: i32, cfg: &ScopeConfig) -> ScopeData {
+    let w = width.max(0) as usize;
+    let h = height.max(0) as usize;
+    let cols = cfg.waveform_columns.max(1);
+    let vs = cfg.vectorscope_size.max(1);
+
+    let mut data = ScopeData {
+        width,
+        height,
+        histogram: [[0; SCOPE_BINS]; 4],
+        waveform: vec![0; cols * SCOPE_BINS],
+        waveform_columns: cols,
+        vectorscope: vec![0; vs * vs],
+        vectorscope_size: vs,
+    };
+
+    for y in 0..h {
+        for x in 0..w {
+            let i = (y * w + x) * 4;
+            let Some(px) = rgba.get(i..i + 3) else {
+                return data;
+            };
+            let (r, g, b) = (px[0], px[1], px[2]);
+            data.histogram[0][r as usize] += 1;
+            data.histogram[1][g as usize] += 1;
+            data.histogram[2][b as usize] += 1;
+
+            let (rf, gf, bf) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+            let luma = 0.2126 * rf + 0.7152 * gf + 0.0722 * bf;
+            let level = ((luma * 255.0).round() as usize).min(SCOPE_BINS - 1);
+            data.histogram[3][level] += 1;
+
+            let col = x * cols / w;
+            data.waveform[col * SCOPE_BINS + level] += 1;
+
+            let cb = (bf - luma) / 1.8556;
+            let cr = (rf - luma) / 1.5748;
+            let vx = (((cb + 0.5) * vs as f32) as usize).min(vs - 1);
+            let vy = (((cr + 0.5) * vs as f32) as usize).min(vs - 1);
+            data.vectorscope[vy * vs + vx] += 1;
+        }
+    }
+
+    data
+}
+
+/// A sink that computes histogram / waveform / vectorscope data from its input.
+pub struct ScopeSink {
+    cfg: ScopeConfig,
+    target: Option<RenderTarget>,
+    frame_counter: u64,
+    buf: Vec<u8>,
+    last: Option<ScopeData>,
+}
+
+impl ScopeSink {
+    pub fn new(cfg: ScopeConfig) -> Self {
+        Self {
+            cfg,
+            target: None,
+            frame_counter: 0,
+            buf: Vec::new(),
+            last: None,
+        }
+    }
+
+    pub fn config(&self) -> &ScopeConfig {
+        &self.cfg
+    }
+
+    /// Update settings; the analysis target is reallocated on the next analyzed frame if needed.
+    pub fn set_config(&mut self, cfg: ScopeConfig) {
+        self.cfg = cfg;
+    }
+
+    /// Most recent scope data, if any frame has been analyzed.
+    pub fn last(&self) -> Option<&ScopeData> {
+        self.last.as_ref()
+    }
+
+    unsafe fn ensure_target(&mut self, gl: &glow::Context) -> Result<(), EngineError> {
+        let (w, h) = (self.cfg.sample_width.max(1), self.cfg.sample_height.max(1));
+        match &mut self.target {
+            Some(rt) if rt.w == w && rt.h == h => {}
+            Some(rt) => rt.resize(gl, w, h),
+            None => self.target = Some(create_render_target(gl, w, h)?),
+        }
+        Ok(())
+    }
+}
+
+impl Default for ScopeSink {
+    fn default() -> Self {
+        Self::new(ScopeConfig::default())
+    }
+}
+
+impl OutputSink for ScopeSink {
+    fn consume(&mut self, gl: &glow::Context, out: &ExecOutput) {
+        self.frame_counter += 1;
+        if !self.frame_counter.is_multiple_of(self.cfg.stride.max(1)) {
+            return;
+        }
+
+        unsafe {
+            if let Err(e) = self.ensure_target(gl) {
+                eprintln!("[ScopeSink] target allocation error: {e}");
+                return;
+            }
+            let Some(rt) = self.target.as_ref() else {
+                return;
+            };
+
+            // GPU reduction: linear-filtered blit into the small analysis target.
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(out.fbo));
+            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(rt.fbo));
+            gl.blit_framebuffer(
+                0,
+                0,
+                out.width,
+                out.height,
+                0,
+                0,
+                rt.w,
+                rt.h,
+                glow::COLOR_BUFFER_BIT,
+                glow::LINEAR,
+            );
+            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
+
+            self.buf.resize((rt.w * rt.h * 4) as usize, 0);
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(rt.fbo));
+            gl.read_pixels(
+                0,
+                0,
+                rt.w,
+                rt.h,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                glow::PixelPackData::Slice(&mut self.buf),
+            );
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
+
+            self.last = Some(analyze_rgba(&self.buf, rt.w, rt.h, &self.cfg));
+        }
+    }
+
+    fn destroy(&mut self, gl: &glow::Context) {
+        if let Some(rt) = self.target.take() {
+            unsafe {
+                gl.delete_framebuffer(rt.fbo);
+                gl.delete_texture(rt.tex);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(w: i32, h: i32, rgb: [u8; 3]) -> Vec<u8> {
+        (0..w * h).flat_map(|_| [rgb[0], rgb[1], rgb[2], 255]).collect()
+    }
+
+    #[test]
+    fn grey_frame_lands_on_one_level_and_scope_centre() {
+        let cfg = ScopeConfig {
+            waveform_columns: 4,
+            vectorscope_size: 8,
+            ..ScopeConfig::default()
+        };
+        let data = analyze_rgba(&solid(8, 2, [128, 128, 128]), 8, 2, &cfg);
+
+        for ch in 0..4 {
+            assert_eq!(data.histogram[ch][128], 16);
+        }
+        for col in 0..4 {
+            assert_eq!(data.waveform_column(col)[128], 4);
+        }
+        assert_eq!(data.vectorscope[4 * 8 + 4], 16);
+    }
+
+    #[test]
+    fn red_frame_pushes_vectorscope_towards_cr() {
+        let cfg = ScopeConfig {
+            vectorscope_size: 16,
+            ..ScopeConfig::default()
+        };
+        let data = analyze_rgba(&solid(2, 2, [255, 0, 0]), 2, 2, &cfg);
+        let hit = data.vectorscope.iter().position(|&c| c == 4).unwrap();
+        let (x, y) = (hit % 16, hit / 16);
+        assert!(y > 8, "red should have positive Cr, got row {y}");
+        assert!(x < 8, "red should have negative Cb, got col {x}");
+        assert_eq!(data.histogram[0][255], 4);
+        assert_eq!(data.histogram[1][0], 4);
+    }
+}
@@ -0,0 +1,598 @@
+//! Texture input from platform shared surfaces (feature `shared-surface`).
+//!
+//! Another local app (a video player, a browser, a capture daemon) can hand frames over as an
+//! IOSurface on macOS or a DMA-BUF on Linux instead of CPU pixels. Put a [`SharedSurface`]
+//! describing it in `NodeProps::shared_surfaces` for a `TextureInputPass` node and install a
+//! [`SharedSurfaceImporter`] with `RuntimeState::set_shared_surface_importer`; the node then
+//! outputs the surface at its own size, without the host creating or uploading a texture.
+//!
+//! - **Linux:** the DMA-BUF is imported as an `EGLImage` (`EGL_EXT_image_dma_buf_import`) and
+//!   bound to a `TEXTURE_2D`: zero-copy, the node samples the producer's memory directly. The
+//!   GL context must be an EGL context.
+//! - **macOS:** the IOSurface is bound with `CGLTexImageIOSurface2D`. That only yields a
+//!   rectangle texture, which `sampler2D` cannot read, so each frame it is blitted into a
+//!   `TEXTURE_2D` on the GPU (no CPU copy). The surface must be BGRA8.
+//!
+//! The import is cached per node and redone only when the descriptor changes, so a producer
+//! that reuses its buffers should keep the descriptor stable and one that swaps buffers should
+//! send a new one. Producer-consumer synchronization (fences, double buffering) is the
+//! producer's side of the contract.
+
+use std::collections::HashMap;
+use std::ffi::{c_void, CStr};
+
+use glow::HasContext;
+use scheng_graph::NodeId;
+
+use crate::stats::{RuntimeStats, TextureFormat};
+use crate::EngineError;
+
+/// A shared surface produced outside this process (or outside this GL context).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SharedSurface {
+    /// A global IOSurface (macOS), by `IOSurfaceGetID`.
+    IoSurface { id: u32 },
+    /// A DMA-BUF (Linux).
+    DmaBuf(DmaBuf),
+}
+
+/// A DMA-BUF frame, as exported by the producer (e.g. from `gbm_bo` or a V4L2 / VA-API export).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DmaBuf {
+    pub width: u32,
+    pub height: u32,
+    /// DRM FourCC (e.g. `DRM_FORMAT_ARGB8888`).
+    pub fourcc: u32,
+    /// DRM format modifier, if the producer uses one (`EGL_EXT_image_dma_buf_import_modifiers`).
+    pub modifier: Option<u64>,
+    /// One to four planes. The file descriptors stay owned by the host and must remain open
+    /// while the surface is in use.
+    pub planes: Vec<DmaBufPlane>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DmaBufPlane {
+    pub fd: i32,
+    pub offset: u32,
+    pub stride: u32,
+}
+
+impl DmaBuf {
+    /// `EGL_LINUX_DMA_BUF_EXT` attribute list for `eglCreateImageKHR`, `EGL_NONE`-terminated.
+    #[cfg_attr(
+        not(all(feature = "shared-surface", target_os = "linux")),
+        allow(dead_code)
+    )]
+    fn egl_attribs(&self) -> Result<Vec<i32>, EngineError> {
+        const EGL_NONE: i32 = 0x3038;
+        const EGL_WIDTH: i32 = 0x3057;
+        const EGL_HEIGHT: i32 = 0x3056;
+        const EGL_LINUX_DRM_FOURCC_EXT: i32 = 0x3271;
+        // Per plane: fd, offset, pitch, modifier lo, modifier hi.
+        const PLANE_ATTRIBS: [[i32; 5]; 4] = [
+            [0x3272, 0x3273, 0x3274, 0x3443, 0x3444],
+            [0x3275, 0x3276, 0x3277, 0x3445, 0x3446],
+            [0x3278, 0x3279, 0x327A, 0x3447, 0x3448],
+            [0x3440, 0x3441, 0x3442, 0x3449, 0x344A],
+        ];
+
+        if self.planes.is_empty() || self.planes.len() > PLANE_ATTRIBS.len() {
+            return Err(EngineError::other(format!(
+                "DMA-BUF must have 1 to 4 planes (got {})",
+                self.planes.len()
+            )));
+        }
+        let mut attribs = vec![
+            EGL_WIDTH,
+            self.width as i32,
+            EGL_HEIGHT,
+            self.height as i32,
+            EGL_LINUX_DRM_FOURCC_EXT,
+            self.fourcc as i32,
+        ];
+        for (plane, names) in self.planes.iter().zip(PLANE_ATTRIBS) {
+            attribs.extend([names[0], plane.fd]);
+            attribs.extend([names[1], plane.offset as i32]);
+            attribs.extend([names[2], plane.stride as i32]);
+            if let Some(m) = self.modifier {
+                attribs.extend([names[3], m as u32 as i32, names[4], (m >> 32) as u32 as i32]);
+            }
+        }
+        attribs.push(EGL_NONE);
+        Ok(attribs)
+    }
+}
+
+/// Platform entry points for importing shared surfaces into the current GL context.
+#[derive(Debug)]
+pub struct SharedSurfaceImporter {
+    #[cfg(all(feature = "shared-surface", target_os = "linux"))]
+    egl: linux::Egl,
+}
+
+impl SharedSurfaceImporter {
+    /// Resolve the entry points with the context's `get_proc_address` (the same loader passed
+    /// to `glow::Context::from_loader_function`). macOS links them directly and ignores it.
+    ///
+    /// Fails on other platforms, without the `shared-surface` feature, or when the Linux
+    /// context is not EGL or lacks DMA-BUF import.
+    pub unsafe fn new(loader: impl FnMut(&CStr) -> *const c_void) -> Result<Self, EngineError> {
+        #[cfg(all(feature = "shared-surface", target_os = "linux"))]
+        {
+            Ok(Self {
+                egl: linux::Egl::load(loader)?,
+            })
+        }
+
+        #[cfg(all(feature = "shared-surface", target_os = "macos"))]
+        {
+            let _ = loader;
+            Ok(Self {})
+        }
+
+        #[cfg(not(all(
+            feature = "shared-surface",
+            any(target_os = "linux", target_os = "macos")
+        )))]
+        {
+            let _ = loader;
+            Err(EngineError::other(
+                "shared surfaces need the `shared-surface` feature on macOS or Linux",
+            ))
+        }
+    }
+}
+
+/// A surface bound as a sampleable texture.
+#[derive(Debug)]
+struct ImportedSurface {
+    surface: SharedSurface,
+    tex: glow::NativeTexture,
+    width: i32,
+    height: i32,
+    #[cfg(all(feature = "shared-surface", target_os = "linux"))]
+    image: *mut c_void,
+    #[cfg(all(feature = "shared-surface", target_os = "macos"))]
+    mac: macos::Bound,
+}
+
+/// Per-node imports owned by `RuntimeState`.
+#[derive(Debug, Default)]
+pub(crate) struct SharedInputs {
+    pub(crate) importer: Option<SharedSurfaceImporter>,
+    imported: HashMap<NodeId, ImportedSurface>,
+}
+
+impl SharedInputs {
+    /// `node`'s surface as a `TEXTURE_2D` with its size, importing it on first use or when the
+    /// descriptor changed.
+    pub(crate) unsafe fn texture(
+        &mut self,
+        gl: &glow::Context,
+        node: NodeId,
+        surface: &SharedSurface,
+    ) -> Result<(glow::NativeTexture, i32, i32), EngineError> {
+        if self
+            .imported
+            .get(&node)
+            .is_some_and(|i| &i.surface != surface)
+        {
+            let old = self.imported.remove(&node).expect("checked above");
+            self.release(gl, old);
+        }
+        if !self.imported.contains_key(&node) {
+            let importer = self.importer.as_ref().ok_or_else(|| {
+                EngineError::other(
+                    "NodeProps.shared_surfaces needs RuntimeState::set_shared_surface_importer",
+                )
+            })?;
+            let imported = import(gl, importer, surface)?;
+            self.imported.insert(node, imported);
+        }
+        let imported = &self.imported[&node];
+        #[cfg(all(feature = "shared-surface", target_os = "macos"))]
+        imported.mac.refresh(gl, imported.width, imported.height);
+        Ok((imported.tex, imported.width, imported.height))
+    }
+
+    /// Release imports for nodes no longer given a surface.
+    pub(crate) unsafe fn retain(&mut self, gl: &glow::Context, keep: impl Fn(NodeId) -> bool) {
+        let stale: Vec<NodeId> = self
+            .imported
+            .keys()
+            .copied()
+            .filter(|n| !keep(*n))
+            .collect();
+        for node in stale {
+            let old = self.imported.remove(&node).expect("listed above");
+            self.release(gl, old);
+        }
+    }
+
+    /// Imported textures are counted as RGBA8 at the surface size.
+    pub(crate) fn account(&self, stats: &mut RuntimeStats) {
+        for imported in self.imported.values() {
+            stats.add_texture(imported.width, imported.height, TextureFormat::Rgba8);
+        }
+    }
+
+    /// Forget every import without GL calls (context lost).
+    pub(crate) fn invalidate(&mut self) {
+        self.imported.clear();
+    }
+
+    pub(crate) unsafe fn destroy(&mut self, gl: &glow::Context) {
+        for (_, imported) in std::mem::take(&mut self.imported) {
+            self.release(gl, imported);
+        }
+    }
+
+    unsafe fn release(&self, gl: &glow::Context, imported: ImportedSurface) {
+        gl.delete_texture(imported.tex);
+        #[cfg(all(feature = "shared-surface", target_os = "linux"))]
+        if let Some(importer) = &self.importer {
+            importer.egl.destroy_image(imported.image);
+        }
+        #[cfg(all(feature = "shared-surface", target_os = "macos"))]
+        imported.mac.destroy(gl);
+    }
+}
+
+#[cfg(all(feature = "shared-surface", target_os = "linux"))]
+unsafe fn import(
+    gl: &glow::Context,
+    importer: &SharedSurfaceImporter,
+    surface: &SharedSurface,
+) -> Result<ImportedSurface, EngineError> {
+    let SharedSurface::DmaBuf(buf) = surface else {
+        return Err(EngineError::other(
+            "IOSurface inputs are only supported on macOS",
+        ));
+    };
+    let image = importer.egl.create_image(&buf.egl_attribs()?)?;
+    let tex = gl.create_texture().map_err(EngineError::other)?;
+    gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+    gl.tex_parameter_i32(
+        glow::TEXTURE_2D,
+        glow::TEXTURE_MIN_FILTER,
+        glow::LINEAR as i32,
+    );
+    gl.tex_parameter_i32(
+        glow::TEXTURE_2D,
+        glow::TEXTURE_MAG_FILTER,
+        glow::LINEAR as i32,
+    );
+    gl.tex_parameter_i32(
+        glow::TEXTURE_2D,
+        glow::TEXTURE_WRAP_S,
+        glow::CLAMP_TO_EDGE as i32,
+    );
+    gl.tex_parameter_i32(
+        glow::TEXTURE_2D,
+        glow::TEXTURE_WRAP_T,
+        glow::CLAMP_TO_EDGE as i32,
+    );
+    (importer.egl.image_target_texture_2d)(glow::TEXTURE_2D, image);
+    gl.bind_texture(glow::TEXTURE_2D, None);
+    Ok(ImportedSurface {
+        surface: surface.clone(),
+        tex,
+        width: buf.width as i32,
+        height: buf.height as i32,
+        image,
+    })
+}
+
+#[cfg(all(feature = "shared-surface", target_os = "macos"))]
+unsafe fn import(
+    gl: &glow::Context,
+    _importer: &SharedSurfaceImporter,
+    surface: &SharedSurface,
+) -> Result<ImportedSurface, EngineError> {
+    let SharedSurface::IoSurface { id } = surface else {
+        return Err(EngineError::other(
+            "DMA-BUF inputs are only supported on Linux",
+        ));
+    };
+    let (mac, width, height) = macos::Bound::new(gl, *id)?;
+    let tex = crate::create_host_texture(gl, width, height);
+    mac.attach_target(gl, tex);
+    Ok(ImportedSurface {
+        surface: surface.clone(),
+        tex,
+        width,
+        height,
+        mac,
+    })
+}
+
+#[cfg(not(all(
+    feature = "shared-surface",
+    any(target_os = "linux", target_os = "macos")
+)))]
+unsafe fn import(
+    _gl: &glow::Context,
+    _importer: &SharedSurfaceImporter,
+    _surface: &SharedSurface,
+) -> Result<ImportedSurface, EngineError> {
+    // `SharedSurfaceImporter::new` fails here, so there is never an importer to call this with.
+    unreachable!("shared surface importer without platform support")
+}
+
+#[cfg(all(feature = "shared-surface", target_os = "linux"))]
+mod linux {
+    use std::ffi::{c_void, CStr};
+
+    use crate::EngineError;
+
+    const EGL_LINUX_DMA_BUF_EXT: u32 = 0x3270;
+
+    type GetCurrentDisplay = unsafe extern "C" fn() -> *mut c_void;
+    type CreateImage =
+        unsafe extern "C" fn(*mut c_void, *mut c_void, u32, *mut c_void, *const i32) -> *mut c_void;
+    type DestroyImage = unsafe extern "C" fn(*mut c_void, *mut c_void) -> u32;
+    type ImageTargetTexture2d = unsafe extern "C" fn(u32, *mut c_void);
+
+    #[derive(Debug)]
+    pub(super) struct Egl {
+        display: *mut c_void,
+        create_image: CreateImage,
+        destroy_image: DestroyImage,
+        pub(super) image_target_texture_2d: ImageTargetTexture2d,
+    }
+
+    impl Egl {
+        pub(super) unsafe fn load(
+            mut loader: impl FnMut(&CStr) -> *const c_void,
+        ) -> Result<Self, EngineError> {
+            let mut get = |name: &CStr| {
+                let p = loader(name);
+                if p.is_null() {
+                    Err(EngineError::other(format!(
+                        "shared surfaces: {} unavailable (EGL context with \
+                         EGL_EXT_image_dma_buf_import required)",
+                        name.to_string_lossy()
+                    )))
+                } else {
+                    Ok(p)
+                }
+            };
+            let get_display = std::mem::transmute::<*const c_void, GetCurrentDisplay>(get(
+                c"eglGetCurrentDisplay",
+            )?);
+            let egl = Self {
+                display: get_display(),
+                create_image: std::mem::transmute::<*const c_void, CreateImage>(get(
+                    c"eglCreateImageKHR",
+                )?),
+                destroy_image: std::mem::transmute::<*const c_void, DestroyImage>(get(
+                    c"eglDestroyImageKHR",
+                )?),
+                image_target_texture_2d: std::mem::transmute::<*const c_void, ImageTargetTexture2d>(
+                    get(c"glEGLImageTargetTexture2DOES")?,
+                ),
+            };
+            if egl.display.is_null() {
+                return Err(EngineError::other(
+                    "shared surfaces: no current EGL display",
+                ));
+            }
+            Ok(egl)
+        }
+
+        pub(super) unsafe fn create_image(
+            &self,
+            attribs: &[i32],
+        ) -> Result<*mut c_void, EngineError> {
+            let image = (self.create_image)(
+                self.display,
+                std::ptr::null_mut(),
+                EGL_LINUX_DMA_BUF_EXT,
+                std::ptr::null_mut(),
+                attribs.as_ptr(),
+            );
+            if image.is_null() {
+                return Err(EngineError::other(
+                    "eglCreateImageKHR rejected the DMA-BUF (format, modifier or fd)",
+                ));
+            }
+            Ok(image)
+        }
+
+        pub(super) unsafe fn destroy_image(&self, image: *mut c_void) {
+            (self.destroy_image)(self.display, image);
+        }
+    }
+}
+
+#[cfg(all(feature = "shared-surface", target_os = "macos"))]
+mod macos {
+    use std::ffi::c_void;
+
+    use glow::HasContext;
+
+    use crate::EngineError;
+
+    #[link(name = "OpenGL", kind = "framework")]
+    extern "C" {
+        fn CGLGetCurrentContext() -> *mut c_void;
+        #[allow(clippy::too_many_arguments)]
+        fn CGLTexImageIOSurface2D(
+            ctx: *mut c_void,
+            target: u32,
+            internal_format: u32,
+            width: i32,
+            height: i32,
+            format: u32,
+            ty: u32,
+            surface: *mut c_void,
+            plane: u32,
+        ) -> i32;
+    }
+
+    #[link(name = "IOSurface", kind = "framework")]
+    extern "C" {
+        fn IOSurfaceLookup(id: u32) -> *mut c_void;
+        fn IOSurfaceGetWidth(surface: *mut c_void) -> usize;
+        fn IOSurfaceGetHeight(surface: *mut c_void) -> usize;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    extern "C" {
+        fn CFRelease(cf: *const c_void);
+    }
+
+    /// The surface bound to a rectangle texture, plus the framebuffers that copy it out.
+    #[derive(Debug)]
+    pub(super) struct Bound {
+        surface: *mut c_void,
+        rect: glow::NativeTexture,
+        read_fbo: glow::NativeFramebuffer,
+        draw_fbo: glow::NativeFramebuffer,
+    }
+
+    impl Bound {
+        pub(super) unsafe fn new(
+            gl: &glow::Context,
+            id: u32,
+        ) -> Result<(Self, i32, i32), EngineError> {
+            let surface = IOSurfaceLookup(id);
+            if surface.is_null() {
+                return Err(EngineError::other(format!("no IOSurface with id {id}")));
+            }
+            let width = IOSurfaceGetWidth(surface) as i32;
+            let height = IOSurfaceGetHeight(surface) as i32;
+
+            let rect = gl.create_texture().map_err(EngineError::other)?;
+            gl.bind_texture(glow::TEXTURE_RECTANGLE, Some(rect));
+            let err = CGLTexImageIOSurface2D(
+                CGLGetCurrentContext(),
+                glow::TEXTURE_RECTANGLE,
+                glow::RGBA8,
+                width,
+                height,
+                glow::BGRA,
+                glow::UNSIGNED_INT_8_8_8_8_REV,
+                surface,
+                0,
+            );
+            gl.bind_texture(glow::TEXTURE_RECTANGLE, None);
+            if err != 0 {
+                gl.delete_texture(rect);
+                CFRelease(surface);
+                return Err(EngineError::other(format!(
+                    "CGLTexImageIOSurface2D failed for IOSurface {id} (CGL error {err})"
+                )));
+            }
+
+            let read_fbo = gl.create_framebuffer().map_err(EngineError::other)?;
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(read_fbo));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_RECTANGLE,
+                Some(rect),
+                0,
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            let draw_fbo = gl.create_framebuffer().map_err(EngineError::other)?;
+            Ok((
+                Self {
+                    surface,
+                    rect,
+                    read_fbo,
+                    draw_fbo,
+                },
+                width,
+                height,
+            ))
+        }
+
+        pub(super) unsafe fn attach_target(&self, gl: &glow::Context, tex: glow::NativeTexture) {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.draw_fbo));
+            gl.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(tex),
+                0,
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+
+        /// Copy the surface's current contents into the node texture.
+        pub(super) unsafe fn refresh(&self, gl: &glow::Context, width: i32, height: i32) {
+            gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(self.read_fbo));
+            gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(self.draw_fbo));
+            gl.blit_framebuffer(
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                glow::COLOR_BUFFER_BIT,
+                glow::NEAREST,
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        }
+
+        pub(super) unsafe fn destroy(&self, gl: &glow::Context) {
+            gl.delete_framebuffer(self.read_fbo);
+            gl.delete_framebuffer(self.draw_fbo);
+            gl.delete_texture(self.rect);
+            CFRelease(self.surface);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dma_buf_attribs_list_planes_and_modifier() {
+        let buf = DmaBuf {
+            width: 1920,
+            height: 1080,
+            fourcc: 0x3432_5241, // AR24
+            modifier: Some(0x0100_0000_0000_0002),
+            planes: vec![DmaBufPlane {
+                fd: 7,
+                offset: 0,
+                stride: 7680,
+            }],
+        };
+        let attribs = buf.egl_attribs().unwrap();
+        assert_eq!(
+            attribs,
+            vec![
+                0x3057,
+                1920,
+                0x3056,
+                1080,
+                0x3271,
+                0x3432_5241,
+                0x3272,
+                7,
+                0x3273,
+                0,
+                0x3274,
+                7680,
+                0x3443,
+                2,
+                0x3444,
+                0x0100_0000,
+                0x3038,
+            ]
+        );
+
+        let no_planes = DmaBuf {
+            planes: Vec::new(),
+            ..buf
+        };
+        assert!(no_planes.egl_attribs().is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! Resource accounting (`RuntimeState::stats`).
+//!
+//! A show that runs for weeks should be able to tell a leak from a busy patch. [`RuntimeStats`]
+//! counts the GL objects `RuntimeState` owns and estimates their memory. A texture costs
+//! `width * height * bytes_per_texel`; mipmaps, driver padding and framebuffer objects are not
+//! counted. Sinks and node executors own their resources and are not included, nor are the
+//! pipe buffers of ffmpeg child processes.
+
+/// Storage format of a counted texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TextureFormat {
+    R8,
+    R32F,
+    Rgba8,
+    Rgba16F,
+    Rgba32F,
+}
+
+impl TextureFormat {
+    pub fn bytes_per_texel(self) -> usize {
+        match self {
+            TextureFormat::R8 => 1,
+            TextureFormat::R32F | TextureFormat::Rgba8 => 4,
+            TextureFormat::Rgba16F => 8,
+            TextureFormat::Rgba32F => 16,
+        }
+    }
+
+    /// Estimated bytes of one `width` x `height` texture.
+    pub fn bytes(self, width: i32, height: i32) -> usize {
+        width.max(0) as usize * height.max(0) as usize * self.bytes_per_texel()
+    }
+}
+
+/// Render targets (framebuffer + texture) of one size and format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetGroup {
+    pub width: i32,
+    pub height: i32,
+    pub format: TextureFormat,
+    pub count: usize,
+}
+
+impl TargetGroup {
+    pub fn bytes(&self) -> usize {
+        self.count * self.format.bytes(self.width, self.height)
+    }
+}
+
+/// Snapshot of what a `RuntimeState` holds (see the module docs).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuntimeStats {
+    /// Linked programs: the program cache plus the built-in programs of bloom, particle and
+    /// audio visualizer nodes.
+    pub programs: usize,
+    /// Render targets grouped by size and format, largest total first.
+    pub targets: Vec<TargetGroup>,
+    /// Textures without a framebuffer: video frames, imported shared surfaces, dither maps,
+    /// audio data and 1x1 fills for unconnected inputs.
+    pub host_textures: usize,
+    pub host_texture_bytes: usize,
+    /// Running video decoders and the decoded frames they hold in memory.
+    pub video_decoders: usize,
+    pub video_buffer_bytes: usize,
+}
+
+impl RuntimeStats {
+    pub fn target_count(&self) -> usize {
+        self.targets.iter().map(|g| g.count).sum()
+    }
+
+    pub fn target_bytes(&self) -> usize {
+        self.targets.iter().map(TargetGroup::bytes).sum()
+    }
+
+    /// Estimated GPU memory: render targets plus host textures.
+    pub fn gpu_bytes(&self) -> usize {
+        self.target_bytes() + self.host_texture_bytes
+    }
+
+    pub(crate) fn add_target(&mut self, width: i32, height: i32, format: TextureFormat) {
+        match self
+            .targets
+            .iter_mut()
+            .find(|g| (g.width, g.height, g.format) == (width, height, format))
+        {
+            Some(group) => group.count += 1,
+            None => self.targets.push(TargetGroup {
+                width,
+                height,
+                format,
+                count: 1,
+            }),
+        }
+    }
+
+    pub(crate) fn add_texture(&mut self, width: i32, height: i32, format: TextureFormat) {
+        self.host_textures += 1;
+        self.host_texture_bytes += format.bytes(width, height);
+    }
+
+    /// Sort the groups for display.
+    pub(crate) fn finish(mut self) -> Self {
+        self.targets.sort_by(|a, b| {
+            b.bytes()
+                .cmp(&a.bytes())
+                .then((a.width, a.height, a.format).cmp(&(b.width, b.height, b.format)))
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_targets_and_sums_bytes() {
+        let mut stats = RuntimeStats::default();
+        stats.add_target(4, 4, TextureFormat::Rgba8);
+        stats.add_target(2, 2, TextureFormat::Rgba32F);
+        stats.add_target(4, 4, TextureFormat::Rgba8);
+        stats.add_texture(8, 8, TextureFormat::R8);
+        let stats = stats.finish();
+        assert_eq!(stats.target_count(), 3);
+        assert_eq!(
+            stats.targets[0],
+            TargetGroup {
+                width: 4,
+                height: 4,
+                format: TextureFormat::Rgba8,
+                count: 2
+            }
+        );
+        assert_eq!(stats.target_bytes(), 128 + 64);
+        assert_eq!(stats.gpu_bytes(), 128 + 64 + 64);
+    }
+}
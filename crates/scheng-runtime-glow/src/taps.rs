@@ -0,0 +1,233 @@
+//! Debug taps: any edge as an extra named output, without touching the patch.
+//!
+//! `RuntimeState::set_edge_tap(node, "in", true)` makes `execute_plan_outputs` add the texture
+//! travelling into `node`'s `in` port as an output named `debug:<handle>:in`
+//! ([`tap_output_name`]), which a `PatchbaySink` can route to a preview window or scope like
+//! any other output. Taps are runtime state, so toggling one never recompiles the graph.
+//!
+//! A tap on an edge from a render node aliases the producer's target (no copy); a delayed edge
+//! shows the previous frame, as its consumer sees it. Edges from source nodes
+//! (`TextureInputPass`, `VideoDecodeSource`) are copied into a target owned by the tap, since
+//! sinks expect a framebuffer. A tap on an input that is not connected, or whose name is taken
+//! by an explicit output, produces no output.
+
+use std::collections::HashMap;
+
+use glow::HasContext;
+use scheng_graph::{Endpoint, Graph, NodeId, NodeKind, PortDir};
+
+use crate::stats::{RuntimeStats, TextureFormat};
+use crate::{
+    create_render_target, output_index, AlphaPolicy, EngineError, ExecOutput, FrameCtx, NamedOutput, NodeProps,
+    RenderTarget, RuntimeState,
+};
+
+/// Prefix of every tap output name.
+pub const TAP_PREFIX: &str = "debug:";
+
+/// Output name of the tap on `node`'s input `port`: `debug:<handle>:<port>`.
+pub fn tap_output_name(graph: &Graph, node: NodeId, port: &str) -> String {
+    format!("{TAP_PREFIX}{}:{port}", graph.handle(node))
+}
+
+/// The `(node, port)` a tap output name refers to, if `name` is one and the node exists.
+pub fn parse_tap_name<'a>(graph: &Graph, name: &'a str) -> Option<(NodeId, &'a str)> {
+    let (handle, port) = name.strip_prefix(TAP_PREFIX)?.rsplit_once(':')?;
+    Some((graph.resolve_handle(handle)?, port))
+}
+
+/// Enabled taps and the copies made for edges from source nodes.
+#[derive(Debug, Default)]
+pub(crate) struct EdgeTaps {
+    /// `(node, input port)` in the order they were enabled.
+    pub(crate) enabled: Vec<(NodeId, String)>,
+    copies: HashMap<(NodeId, String), RenderTarget>,
+    read_fbo: Option<glow::NativeFramebuffer>,
+}
+
+impl EdgeTaps {
+    /// Copy `tex` into the tap's own target.
+    unsafe fn copy(
+        &mut self,
+        gl: &glow::Context,
+        tap: &(NodeId, String),
+        (tex, w, h): (glow::NativeTexture, i32, i32),
+    ) -> Result<ExecOutput, EngineError> {
+        let read_fbo = match self.read_fbo {
+            Some(fbo) => fbo,
+            None => {
+                let fbo = gl
+                    .create_framebuffer()
+                    .map_err(|e| EngineError::GlCreate(format!("tap framebuffer: {e:?}")))?;
+                *self.read_fbo.insert(fbo)
+            }
+        };
+        let target = match self.copies.entry(tap.clone()) {
+            std::collections::hash_map::Entry::Occupied(e) => e.into_mut(),
+            std::collections::hash_map::Entry::Vacant(e) => {
+                e.insert(create_render_target(gl, w, h)?)
+            }
+        };
+        if (target.w, target.h) != (w, h) {
+            target.resize(gl, w, h);
+        }
+
+        gl.bind_framebuffer(glow::READ_FRAMEBUFFER, Some(read_fbo));
+        gl.framebuffer_texture_2d(
+            glow::READ_FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::TEXTURE_2D,
+            Some(tex),
+            0,
+        );
+        gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, Some(target.fbo));
+        gl.blit_framebuffer(
+            0,
+            0,
+            w,
+            h,
+            0,
+            0,
+            w,
+            h,
+            glow::COLOR_BUFFER_BIT,
+            glow::NEAREST,
+        );
+        gl.framebuffer_texture_2d(
+            glow::READ_FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::TEXTURE_2D,
+            None,
+            0,
+        );
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+        Ok(ExecOutput {
+            tex: target.tex,
+            fbo: target.fbo,
+            width: w,
+            height: h,
+        })
+    }
+
+    /// Delete copies of taps that were switched off.
+    unsafe fn release_disabled(&mut self, gl: &glow::Context) {
+        let enabled = &self.enabled;
+        self.copies.retain(|tap, t| {
+            let keep = enabled.contains(tap);
+            if !keep {
+                gl.delete_framebuffer(t.fbo);
+                gl.delete_texture(t.tex);
+            }
+            keep
+        });
+    }
+
+    pub(crate) fn account(&self, stats: &mut RuntimeStats) {
+        for t in self.copies.values() {
+            stats.add_target(t.w, t.h, TextureFormat::Rgba8);
+        }
+    }
+
+    /// Forget GL objects without deleting them (context lost).
+    pub(crate) fn invalidate(&mut self) {
+        self.copies.clear();
+        self.read_fbo = None;
+    }
+
+    pub(crate) unsafe fn destroy(&mut self, gl: &glow::Context) {
+        for (_, t) in self.copies.drain() {
+            gl.delete_framebuffer(t.fbo);
+            gl.delete_texture(t.tex);
+        }
+        if let Some(fbo) = self.read_fbo.take() {
+            gl.delete_framebuffer(fbo);
+        }
+    }
+}
+
+/// Outputs for every enabled tap, after `execute_plan` rendered the frame. Names already in
+/// `taken` are skipped.
+pub(crate) unsafe fn tap_outputs(
+    gl: &glow::Context,
+    graph: &Graph,
+    state: &mut RuntimeState,
+    props: &NodeProps,
+    frame: FrameCtx,
+    taken: &[NamedOutput],
+) -> Result<Vec<NamedOutput>, EngineError> {
+    state.taps.release_disabled(gl);
+    let mut outputs = Vec::new();
+    for tap in state.taps.enabled.clone() {
+        let (node, port) = (tap.0, tap.1.as_str());
+        let name = tap_output_name(graph, node, port);
+        if taken.iter().any(|e| e.name == name) {
+            continue;
+        }
+        let Some(edge) = graph.find_port(node, port, PortDir::In).and_then(|id| {
+            let input = Endpoint {
+                node,
+                port: id,
+                dir: PortDir::In,
+            };
+            graph.incoming(node).find(|e| e.to == input)
+        }) else {
+            continue;
+        };
+        let source = edge.from.node;
+        let Some(kind) = graph.node(source).map(|n| &n.kind) else {
+            continue;
+        };
+
+        let out = if kind.renders() {
+            let target = state
+                .targets
+                .get(&source)
+                .and_then(|pp| pp.output(output_index(graph, edge.from), edge.delayed));
+            target.map(|t| ExecOutput {
+                tex: t.tex,
+                fbo: t.fbo,
+                width: t.w,
+                height: t.h,
+            })
+        } else {
+            match source_texture(gl, state, props, frame, source, kind)? {
+                Some(tex) => Some(state.taps.copy(gl, &tap, tex)?),
+                None => None,
+            }
+        };
+        if let Some(out) = out {
+            outputs.push(NamedOutput {
+                name,
+                node: source,
+                declared_width: frame.width,
+                declared_height: frame.height,
+                alpha: AlphaPolicy::Straight,
+                out,
+            });
+        }
+    }
+    Ok(outputs)
+}
+
+/// The texture a source node delivered this frame, as `execute_plan` resolved it.
+unsafe fn source_texture(
+    gl: &glow::Context,
+    state: &mut RuntimeState,
+    props: &NodeProps,
+    frame: FrameCtx,
+    node: NodeId,
+    kind: &NodeKind,
+) -> Result<Option<(glow::NativeTexture, i32, i32)>, EngineError> {
+    match kind {
+        NodeKind::TextureInputPass => match props.texture_inputs.get(&node) {
+            Some(&tex) => Ok(Some((tex, frame.width, frame.height))),
+            None => match props.shared_surfaces.get(&node) {
+                Some(surface) => state.shared.texture(gl, node, surface).map(Some),
+                None => Ok(None),
+            },
+        },
+        #[cfg(feature = "video")]
+        NodeKind::VideoDecodeSource => Ok(state.video_nodes.get(&node).map(|v| v.output())),
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,133 @@
+//! Temporal accumulation on named outputs (`NodeProps::temporal_params`).
+//!
+//! Fast-moving generative content shimmers. For each `PixelsOut` with [`TemporalParams`],
+//! `execute_plan` blends the routed frame with the output's own history after every node has
+//! rendered, in a ping-pong pair owned by the runtime. Two heuristics keep motion sharp: the
+//! history is clamped to the current pixel's 3x3 neighbourhood, and pixels that differ by more
+//! than `reset_threshold` take the current frame. The whole history is dropped when the output
+//! resizes, when frames are not consecutive (seeks, pauses), and on
+//! `RuntimeState::reset_temporal`.
+
+use glow::HasContext;
+use scheng_runtime::TemporalParams;
+
+use crate::stats::RuntimeStats;
+use crate::{EngineError, FullscreenTriangle, PingPong, RenderTarget};
+
+pub const TEMPORAL_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 o;
+uniform sampler2D uCurrent;
+uniform sampler2D uHistory;
+uniform vec2 uTexel;
+uniform float uBlend;
+uniform int uClamp;
+uniform float uResetThreshold;
+void main() {
+    vec4 c = texture(uCurrent, v_uv);
+    vec4 h = texture(uHistory, v_uv);
+    if (uClamp != 0) {
+        vec4 lo = c;
+        vec4 hi = c;
+        for (int y = -1; y <= 1; y++) {
+            for (int x = -1; x <= 1; x++) {
+                vec4 n = texture(uCurrent, v_uv + vec2(x, y) * uTexel);
+                lo = min(lo, n);
+                hi = max(hi, n);
+            }
+        }
+        h = clamp(h, lo, hi);
+    }
+    vec3 d = abs(c.rgb - h.rgb);
+    bool reset = uResetThreshold > 0.0 && max(d.r, max(d.g, d.b)) > uResetThreshold;
+    o = mix(c, h, reset ? 0.0 : uBlend);
+}
+"#;
+
+/// History of one output.
+#[derive(Debug)]
+pub(crate) struct TemporalState {
+    targets: PingPong,
+    /// Frame index of the last accumulated frame; `None` drops the history.
+    last_frame: Option<u64>,
+}
+
+impl TemporalState {
+    pub(crate) unsafe fn new(gl: &glow::Context, w: i32, h: i32) -> Result<Self, EngineError> {
+        Ok(Self {
+            targets: PingPong::new(gl, w, h)?,
+            last_frame: None,
+        })
+    }
+
+    /// Drop the history; the next frame passes through unblended.
+    pub(crate) fn reset(&mut self) {
+        self.last_frame = None;
+    }
+
+    /// The accumulated output of the latest frame.
+    pub(crate) fn output(&self) -> &RenderTarget {
+        &self.targets.curr
+    }
+
+    /// Blend `src` (`w` x `h`) into the history for frame `frame`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) unsafe fn accumulate(
+        &mut self,
+        gl: &glow::Context,
+        fs_tri: &FullscreenTriangle,
+        program: glow::NativeProgram,
+        params: &TemporalParams,
+        src: glow::NativeTexture,
+        frame: u64,
+        w: i32,
+        h: i32,
+    ) {
+        let resized = (self.targets.curr.w, self.targets.curr.h) != (w.max(1), h.max(1));
+        let consecutive = self.last_frame.is_some_and(|last| frame == last + 1);
+        let blend = if resized || !consecutive {
+            0.0
+        } else {
+            params.blend.clamp(0.0, TemporalParams::MAX_BLEND)
+        };
+        self.targets.ensure_size(gl, w.max(1), h.max(1));
+        self.targets.swap();
+        self.last_frame = Some(frame);
+
+        let (tgt, history) = (&self.targets.curr, &self.targets.prev);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(tgt.fbo));
+        gl.viewport(0, 0, tgt.w, tgt.h);
+        gl.disable(glow::BLEND);
+        gl.use_program(Some(program));
+        let loc = |name: &str| gl.get_uniform_location(program, name);
+        gl.uniform_1_i32(loc("uCurrent").as_ref(), 0);
+        gl.uniform_1_i32(loc("uHistory").as_ref(), 1);
+        gl.uniform_2_f32(
+            loc("uTexel").as_ref(),
+            1.0 / tgt.w as f32,
+            1.0 / tgt.h as f32,
+        );
+        gl.uniform_1_f32(loc("uBlend").as_ref(), blend);
+        gl.uniform_1_i32(loc("uClamp").as_ref(), params.clamp_history as i32);
+        gl.uniform_1_f32(
+            loc("uResetThreshold").as_ref(),
+            params.reset_threshold.max(0.0),
+        );
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, Some(src));
+        gl.active_texture(glow::TEXTURE1);
+        gl.bind_texture(glow::TEXTURE_2D, Some(history.tex));
+        fs_tri.draw(gl);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+    }
+
+    pub(crate) fn account(&self, stats: &mut RuntimeStats) {
+        self.targets.account(stats);
+    }
+
+    pub(crate) unsafe fn destroy(self, gl: &glow::Context) {
+        self.targets.destroy(gl);
+    }
+}
@@ -0,0 +1,170 @@
+//! Per-destination colour trim in front of a sink.
+//!
+//! [`TrimSink`] wraps any [`OutputSink`] and, unless its [`ColorTrim`] is the identity, draws the
+//! routed output through a brightness/contrast/gamma pass into its own target before handing it
+//! on. Give each projector its own `TrimSink` (e.g. one per `PatchbaySink` route) and calibrate
+//! them independently. With a registry name set, [`TrimSink::sync`] reads the trim from the
+//! `sink/<name>/trim/...` parameters each frame (see `scheng_runtime::trim`).
+
+use glow::HasContext;
+use scheng_runtime::{ColorTrim, ParamRegistry};
+
+use crate::{
+    compile_program, create_render_target, EngineError, ExecOutput, FrameCtx, FullscreenTriangle,
+    OutputSink, RenderTarget, FULLSCREEN_VERT,
+};
+
+/// Same math as `ColorTrim::apply`.
+const TRIM_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 o;
+uniform sampler2D iChannel0;
+uniform vec3 u_brightness;
+uniform vec3 u_contrast;
+uniform vec3 u_gamma;
+void main() {
+    vec4 c = texture(iChannel0, v_uv);
+    vec3 v = clamp((c.rgb - 0.5) * u_contrast + 0.5 + u_brightness, 0.0, 1.0);
+    o = vec4(pow(v, 1.0 / max(u_gamma, vec3(0.1))), c.a);
+}
+"#;
+
+#[derive(Debug)]
+struct TrimPass {
+    tri: FullscreenTriangle,
+    program: glow::NativeProgram,
+    target: RenderTarget,
+}
+
+/// Applies a [`ColorTrim`] before the wrapped sink consumes the frame.
+#[derive(Debug)]
+pub struct TrimSink<S> {
+    inner: S,
+    pub trim: ColorTrim,
+    name: Option<String>,
+    pass: Option<TrimPass>,
+}
+
+impl<S: OutputSink> TrimSink<S> {
+    pub fn new(inner: S, trim: ColorTrim) -> Self {
+        Self {
+            inner,
+            trim,
+            name: None,
+            pass: None,
+        }
+    }
+
+    /// Read the trim from the registry parameters of sink `name` on [`sync`](Self::sync).
+    pub fn with_registry_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// Pick up the registry trim (no-op without a registry name). Call once per frame, e.g.
+    /// with `RuntimeState::params()`.
+    pub fn sync(&mut self, registry: &ParamRegistry) {
+        if let Some(name) = &self.name {
+            self.trim = ColorTrim::from_registry(registry, name);
+        }
+    }
+
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    unsafe fn draw(
+        &mut self,
+        gl: &glow::Context,
+        out: &ExecOutput,
+    ) -> Result<ExecOutput, EngineError> {
+        let pass = match &mut self.pass {
+            Some(pass) => pass,
+            None => self.pass.insert(TrimPass {
+                tri: FullscreenTriangle::new(gl)?,
+                program: compile_program(gl, FULLSCREEN_VERT, TRIM_FRAG)?,
+                target: create_render_target(gl, out.width, out.height)?,
+            }),
+        };
+        let rt = &mut pass.target;
+        if (rt.w, rt.h) != (out.width.max(1), out.height.max(1)) {
+            rt.resize(gl, out.width, out.height);
+        }
+
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(rt.fbo));
+        gl.viewport(0, 0, rt.w, rt.h);
+        gl.disable(glow::BLEND);
+        gl.use_program(Some(pass.program));
+        gl.active_texture(glow::TEXTURE0);
+        gl.bind_texture(glow::TEXTURE_2D, Some(out.tex));
+        let uniforms = [
+            ("u_brightness", self.trim.brightness),
+            ("u_contrast", self.trim.contrast),
+            ("u_gamma", self.trim.gamma),
+        ];
+        if let Some(loc) = gl.get_uniform_location(pass.program, "iChannel0") {
+            gl.uniform_1_i32(Some(&loc), 0);
+        }
+        for (name, [x, y, z]) in uniforms {
+            if let Some(loc) = gl.get_uniform_location(pass.program, name) {
+                gl.uniform_3_f32(Some(&loc), x, y, z);
+            }
+        }
+        pass.tri.draw(gl);
+        gl.bind_texture(glow::TEXTURE_2D, None);
+        gl.use_program(None);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+
+        Ok(ExecOutput {
+            tex: rt.tex,
+            fbo: rt.fbo,
+            width: rt.w,
+            height: rt.h,
+        })
+    }
+}
+
+impl<S: OutputSink> OutputSink for TrimSink<S> {
+    fn consume(&mut self, gl: &glow::Context, out: &ExecOutput) {
+        if self.trim.is_identity() {
+            self.inner.consume(gl, out);
+            return;
+        }
+        match unsafe { self.draw(gl, out) } {
+            Ok(trimmed) => self.inner.consume(gl, &trimmed),
+            Err(e) => {
+                // Better an untrimmed frame than a dark projector.
+                eprintln!("[TrimSink] trim pass failed: {e}");
+                self.inner.consume(gl, out);
+            }
+        }
+    }
+
+    fn on_frame_begin(&mut self, gl: &glow::Context, frame: &FrameCtx) {
+        self.inner.on_frame_begin(gl, frame);
+    }
+
+    fn on_frame_end(&mut self, gl: &glow::Context) {
+        self.inner.on_frame_end(gl);
+    }
+
+    fn on_resize(&mut self, gl: &glow::Context, width: i32, height: i32) {
+        self.inner.on_resize(gl, width, height);
+    }
+
+    fn destroy(&mut self, gl: &glow::Context) {
+        if let Some(mut pass) = self.pass.take() {
+            unsafe {
+                pass.tri.destroy(gl);
+                gl.delete_program(pass.program);
+                gl.delete_framebuffer(pass.target.fbo);
+                gl.delete_texture(pass.target.tex);
+            }
+        }
+        self.inner.destroy(gl);
+    }
+}
@@ -0,0 +1,237 @@
+//! Standard uniform injection.
+//!
+//! The names, types and meanings live in `scheng_runtime::runtime_contract::STANDARD_UNIFORMS`.
+//! Each program is reflected once (active uniforms, names and types) when it is first used; per
+//! frame only the declared uniforms are set, with no name probing.
+//!
+//! `uNodeSeed` and `uReloadCount` depend on the node, not the frame: they are set by
+//! [`apply_node_uniforms`] and left at 0 by [`apply_standard_uniforms`].
+//!
+//! Shaders that declare the standard block (`runtime_contract::FRAME_BLOCK_GLSL`) read the
+//! per-frame values from one uniform buffer instead. `execute_plan` binds it once per frame and
+//! uploads it only when a node's values differ from the last upload (branch formats change the
+//! resolution), so a large graph of same-sized passes costs one upload per frame.
+
+use glow::HasContext;
+use scheng_runtime::runtime_contract::{
+    node_seed, standard_uniform, StandardUniform, StandardUniformDef, UniformType,
+    FRAME_BLOCK_BINDING, FRAME_BLOCK_NAME, FRAME_BLOCK_SIZE,
+};
+
+use crate::{EngineError, FrameCtx};
+
+/// The standard uniforms one program declares, with their locations.
+#[derive(Debug, Clone, Default)]
+pub struct StandardUniformLocations {
+    locs: Vec<(&'static StandardUniformDef, glow::NativeUniformLocation)>,
+    /// Standard names declared with the wrong GLSL type (skipped rather than set).
+    mismatched: Vec<&'static str>,
+    /// The program declares the standard block (bound to `FRAME_BLOCK_BINDING`).
+    frame_block: bool,
+}
+
+impl StandardUniformLocations {
+    /// Reflect `program`'s active uniforms against the standard set, and bind its standard
+    /// block if it declares one.
+    pub unsafe fn reflect(gl: &glow::Context, program: glow::NativeProgram) -> Self {
+        let mut out = Self::default();
+        if let Some(index) = gl.get_uniform_block_index(program, FRAME_BLOCK_NAME) {
+            gl.uniform_block_binding(program, index, FRAME_BLOCK_BINDING);
+            out.frame_block = true;
+        }
+        for index in 0..gl.get_active_uniforms(program) {
+            let Some(active) = gl.get_active_uniform(program, index) else {
+                continue;
+            };
+            let Some(def) = standard_uniform(&active.name) else {
+                continue;
+            };
+            let expected = match def.ty {
+                UniformType::Float => glow::FLOAT,
+                UniformType::Vec2 => glow::FLOAT_VEC2,
+                UniformType::Vec3 => glow::FLOAT_VEC3,
+            };
+            if active.utype != expected || active.size != 1 {
+                out.mismatched.push(def.name);
+                continue;
+            }
+            if let Some(loc) = gl.get_uniform_location(program, def.name) {
+                out.locs.push((def, loc));
+            }
+        }
+        out
+    }
+
+    /// Standard uniforms the program declares (and will receive).
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.locs.iter().map(|(d, _)| d.name)
+    }
+
+    /// Standard names the program declares with a non-standard type.
+    pub fn mismatched(&self) -> &[&'static str] {
+        &self.mismatched
+    }
+
+    /// Whether the program reads the standard block.
+    pub fn uses_frame_block(&self) -> bool {
+        self.frame_block
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locs.is_empty() && !self.frame_block
+    }
+}
+
+/// The standard block's contents for `frame` (std140, see `runtime_contract::FRAME_BLOCK_GLSL`).
+pub fn frame_block_bytes(frame: &FrameCtx) -> [u8; FRAME_BLOCK_SIZE] {
+    let values = [
+        frame.width as f32,
+        frame.height as f32,
+        frame.time,
+        frame.dt,
+        frame.aspect,
+        frame.seed_f32(),
+        frame.frame as f32,
+        0.0,
+    ];
+    let mut bytes = [0u8; FRAME_BLOCK_SIZE];
+    for (chunk, v) in bytes.chunks_exact_mut(4).zip(values) {
+        chunk.copy_from_slice(&v.to_ne_bytes());
+    }
+    bytes
+}
+
+/// The uniform buffer behind the standard block.
+#[derive(Debug, Default)]
+pub(crate) struct FrameBlock {
+    buffer: Option<glow::NativeBuffer>,
+    /// Contents of the last upload.
+    uploaded: Option<[u8; FRAME_BLOCK_SIZE]>,
+    /// Bound to `FRAME_BLOCK_BINDING` during the current frame.
+    bound: bool,
+}
+
+impl FrameBlock {
+    /// Rebind on the next `update` (the host may have used the binding point in between).
+    pub(crate) fn begin_frame(&mut self) {
+        self.bound = false;
+    }
+
+    /// Make the block hold `frame`'s values and bind it.
+    pub(crate) unsafe fn update(
+        &mut self,
+        gl: &glow::Context,
+        frame: &FrameCtx,
+    ) -> Result<(), EngineError> {
+        let buffer = match self.buffer {
+            Some(buffer) => buffer,
+            None => {
+                let buffer = gl
+                    .create_buffer()
+                    .map_err(|e| EngineError::GlCreate(format!("frame block buffer: {e:?}")))?;
+                gl.bind_buffer(glow::UNIFORM_BUFFER, Some(buffer));
+                gl.buffer_data_size(
+                    glow::UNIFORM_BUFFER,
+                    FRAME_BLOCK_SIZE as i32,
+                    glow::DYNAMIC_DRAW,
+                );
+                gl.bind_buffer(glow::UNIFORM_BUFFER, None);
+                self.uploaded = None;
+                *self.buffer.insert(buffer)
+            }
+        };
+        let bytes = frame_block_bytes(frame);
+        if self.uploaded != Some(bytes) {
+            gl.bind_buffer(glow::UNIFORM_BUFFER, Some(buffer));
+            gl.buffer_sub_data_u8_slice(glow::UNIFORM_BUFFER, 0, &bytes);
+            gl.bind_buffer(glow::UNIFORM_BUFFER, None);
+            self.uploaded = Some(bytes);
+        }
+        if !self.bound {
+            gl.bind_buffer_base(glow::UNIFORM_BUFFER, FRAME_BLOCK_BINDING, Some(buffer));
+            self.bound = true;
+        }
+        Ok(())
+    }
+
+    /// Forget the buffer without deleting it (context lost).
+    pub(crate) fn invalidate(&mut self) {
+        *self = Self::default();
+    }
+
+    pub(crate) unsafe fn destroy(&mut self, gl: &glow::Context) {
+        if let Some(buffer) = self.buffer.take() {
+            gl.delete_buffer(buffer);
+        }
+        *self = Self::default();
+    }
+}
+
+/// Set every standard uniform in `locs` from `frame`. The program must be in use.
+pub unsafe fn apply_standard_uniforms(
+    gl: &glow::Context,
+    locs: &StandardUniformLocations,
+    frame: &FrameCtx,
+) {
+    for (def, loc) in &locs.locs {
+        let loc = Some(loc);
+        match (def.value, def.ty) {
+            (StandardUniform::Resolution, UniformType::Vec3) => {
+                gl.uniform_3_f32(loc, frame.width as f32, frame.height as f32, 1.0)
+            }
+            (StandardUniform::Resolution, _) => {
+                gl.uniform_2_f32(loc, frame.width as f32, frame.height as f32)
+            }
+            (StandardUniform::Time, _) => gl.uniform_1_f32(loc, frame.time),
+            (StandardUniform::DeltaTime, _) => gl.uniform_1_f32(loc, frame.dt),
+            (StandardUniform::Aspect, _) => gl.uniform_1_f32(loc, frame.aspect),
+            (StandardUniform::Seed, _) => gl.uniform_1_f32(loc, frame.seed_f32()),
+            (StandardUniform::NodeSeed | StandardUniform::ReloadCount, _) => {}
+        }
+    }
+}
+
+/// Values of the node-scoped standard uniforms.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct NodeUniformValues {
+    /// `uNodeSeed`, see [`node_seed_f32`].
+    pub seed: f32,
+    /// `uReloadCount` (`RuntimeState::reload_count`).
+    pub reload_count: u32,
+}
+
+/// `uNodeSeed` of the node with `handle` in a run seeded with `seed`, in `[0, 1)`.
+pub fn node_seed_f32(handle: &str, seed: u32) -> f32 {
+    (node_seed(handle, seed) as f64 / 4_294_967_296.0) as f32
+}
+
+/// Set the node-scoped standard uniforms in `locs`. The program must be in use.
+pub unsafe fn apply_node_uniforms(
+    gl: &glow::Context,
+    locs: &StandardUniformLocations,
+    values: &NodeUniformValues,
+) {
+    for (def, loc) in &locs.locs {
+        match def.value {
+            StandardUniform::NodeSeed => gl.uniform_1_f32(Some(loc), values.seed),
+            StandardUniform::ReloadCount => gl.uniform_1_f32(Some(loc), values.reload_count as f32),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_block_follows_the_std140_offsets() {
+        let frame = FrameCtx::new(640, 360, 2.5, 42).with_dt(0.5);
+        let bytes = frame_block_bytes(&frame);
+        let at = |offset: usize| f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        assert_eq!((at(0), at(4)), (640.0, 360.0));
+        assert_eq!((at(8), at(12)), (2.5, 0.5));
+        assert!((at(16) - 640.0 / 360.0).abs() < 1e-6);
+        assert_eq!((at(20), at(24)), (0.0, 42.0));
+    }
+}
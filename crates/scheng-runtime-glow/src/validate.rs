@@ -0,0 +1,285 @@
+//! Up-front checks of [`NodeProps`] against the graph they will run with.
+//!
+//! `execute_plan` finds most prop mistakes only when it reaches the node, mid-frame. Call
+//! [`validate_props`] before the render loop starts to list them all at once. With
+//! [`ExecOptions::strict_props`](crate::ExecOptions::strict_props) set, `execute_plan` runs the
+//! same check every frame and refuses to render while anything is reported.
+//!
+//! Per-frame inputs (`texture_inputs`, `shared_surfaces`) may legitimately arrive after
+//! validation, so a `TextureInputPass` without one is not reported; an entry keyed to the wrong
+//! node is.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use scheng_graph::{Graph, NodeId, NodeKind};
+use scheng_runtime::ParamRegistry;
+
+use crate::{resolve_pass_shader, NodeProps};
+
+/// What is wrong with a prop entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DiagnosticKind {
+    /// An entry is keyed by a node the graph does not contain.
+    UnknownNode,
+    /// An entry is keyed by a node of a kind that never reads it.
+    WrongNodeKind,
+    /// A render node has no shader: no `shader_sources` entry, no built-in and no upstream
+    /// `ShaderSource`.
+    MissingShader,
+    /// A `VideoDecodeSource` has neither `video_decode_json` nor `video_decode_cfg`, or the crate
+    /// was built without its `video` feature.
+    MissingVideoConfig,
+    /// Two `PixelsOut` nodes share an output name.
+    DuplicateOutputName,
+}
+
+/// One problem found by [`validate_props`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub node: NodeId,
+    pub kind: DiagnosticKind,
+    /// The `NodeProps` field involved (e.g. `"output_names"`).
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "node #{} ({}): {}",
+            self.node.0, self.field, self.message
+        )
+    }
+}
+
+/// Check `props` against `graph`. Results are sorted by node, then field.
+pub fn validate_props(graph: &Graph, props: &NodeProps) -> Vec<Diagnostic> {
+    use NodeKind::*;
+    let mut out = Vec::new();
+
+    let mut keyed = |field: &'static str, ids: Vec<NodeId>, accepts: &dyn Fn(&NodeKind) -> bool| {
+        for id in ids {
+            match graph.node(id) {
+                None => out.push(Diagnostic {
+                    node: id,
+                    kind: DiagnosticKind::UnknownNode,
+                    field,
+                    message: "no such node in the graph".to_string(),
+                }),
+                Some(node) if !accepts(&node.kind) => out.push(Diagnostic {
+                    node: id,
+                    kind: DiagnosticKind::WrongNodeKind,
+                    field,
+                    message: format!("ignored by {:?} nodes", node.kind),
+                }),
+                Some(_) => {}
+            }
+        }
+    };
+    fn ids<V>(map: &HashMap<NodeId, V>) -> Vec<NodeId> {
+        map.keys().copied().collect()
+    }
+
+    keyed("shader_sources", ids(&props.shader_sources), &|k| {
+        *k == ShaderSource || k.renders()
+    });
+    keyed("mixer_params", ids(&props.mixer_params), &|k| {
+        matches!(k, Crossfade | Add | Multiply)
+    });
+    keyed("blend_params", ids(&props.blend_params), &|k| {
+        matches!(k, Add | Multiply)
+    });
+    keyed("particle_params", ids(&props.particle_params), &|k| {
+        *k == Particles
+    });
+    keyed("audio_vis_params", ids(&props.audio_vis_params), &|k| {
+        matches!(k, AudioWaveform | AudioSpectrum)
+    });
+    keyed("geometry", ids(&props.geometry), &|k| *k == GeometryPass);
+    keyed("key_params", ids(&props.key_params), &|k| *k == KeyMix);
+    keyed("matrix_params", ids(&props.matrix_params), &|k| {
+        *k == MatrixMix4
+    });
+    keyed("output_names", ids(&props.output_names), &|k| {
+        *k == PixelsOut
+    });
+    keyed("texture_inputs", ids(&props.texture_inputs), &|k| {
+        *k == TextureInputPass
+    });
+    keyed("shared_surfaces", ids(&props.shared_surfaces), &|k| {
+        *k == TextureInputPass
+    });
+    #[cfg(feature = "video")]
+    keyed("video_decode_json", ids(&props.video_decode_json), &|k| {
+        *k == VideoDecodeSource
+    });
+    #[cfg(feature = "video")]
+    keyed("video_decode_cfg", ids(&props.video_decode_cfg), &|k| {
+        *k == VideoDecodeSource
+    });
+    keyed("custom_uniforms", ids(&props.custom_uniforms), &|k| {
+        k.renders()
+    });
+    keyed("motion_params", ids(&props.motion_params), &|k| {
+        *k == MotionDetect
+    });
+    keyed("flow_params", ids(&props.flow_params), &|k| {
+        *k == OpticalFlow
+    });
+    keyed("displace_params", ids(&props.displace_params), &|k| {
+        *k == FlowDisplace
+    });
+    keyed("bloom_params", ids(&props.bloom_params), &|k| *k == Bloom);
+    keyed("pixelate_params", ids(&props.pixelate_params), &|k| {
+        *k == Pixelate
+    });
+    keyed("posterize_params", ids(&props.posterize_params), &|k| {
+        *k == Posterize
+    });
+    keyed("dither_params", ids(&props.dither_params), &|k| *k == Dither);
+    keyed("temporal_params", ids(&props.temporal_params), &|k| {
+        *k == PixelsOut
+    });
+    keyed("output_alpha", ids(&props.output_alpha), &|k| {
+        *k == PixelsOut
+    });
+    keyed("clear_modes", ids(&props.clear_modes), &|k| {
+        k.renders()
+    });
+    keyed("shader_banks", ids(&props.shader_banks), &|k| {
+        k.renders()
+    });
+    for set in &props.buffer_sets {
+        let members = set
+            .buffers
+            .iter()
+            .flatten()
+            .chain(&set.readers)
+            .copied()
+            .collect();
+        keyed("buffer_sets", members, &|k| k.renders());
+    }
+
+    for node in graph.nodes() {
+        if node.kind.renders() && !matches!(
+            node.kind,
+            Particles | AudioWaveform | AudioSpectrum | Bloom
+        ) {
+            if let Err(e) = resolve_pass_shader(graph, props, &ParamRegistry::new(), node.id) {
+                out.push(Diagnostic {
+                    node: node.id,
+                    kind: DiagnosticKind::MissingShader,
+                    field: "shader_sources",
+                    message: e.to_string(),
+                });
+            }
+        }
+        #[cfg(not(feature = "video"))]
+        if node.kind == VideoDecodeSource {
+            out.push(Diagnostic {
+                node: node.id,
+                kind: DiagnosticKind::MissingVideoConfig,
+                field: "video_decode_cfg",
+                message: "VideoDecodeSource needs scheng-runtime-glow's `video` feature".to_string(),
+            });
+        }
+        #[cfg(feature = "video")]
+        if node.kind == VideoDecodeSource
+            && !props.video_decode_json.contains_key(&node.id)
+            && !props.video_decode_cfg.contains_key(&node.id)
+        {
+            out.push(Diagnostic {
+                node: node.id,
+                kind: DiagnosticKind::MissingVideoConfig,
+                field: "video_decode_cfg",
+                message: "VideoDecodeSource needs a video_decode_json or video_decode_cfg entry"
+                    .to_string(),
+            });
+        }
+    }
+
+    let mut names: Vec<(&String, NodeId)> = props
+        .output_names
+        .iter()
+        .filter(|(id, _)| graph.node(**id).is_some_and(|n| n.kind == PixelsOut))
+        .map(|(id, name)| (name, *id))
+        .collect();
+    names.sort_by_key(|&(name, id)| (name, id.0));
+    for pair in names.windows(2) {
+        if pair[0].0 == pair[1].0 {
+            out.push(Diagnostic {
+                node: pair[1].1,
+                kind: DiagnosticKind::DuplicateOutputName,
+                field: "output_names",
+                message: format!(
+                    "output name {:?} is also used by node #{}",
+                    pair[0].0, pair[0].1 .0
+                ),
+            });
+        }
+    }
+
+    out.sort_by(|a, b| (a.node.0, a.field).cmp(&(b.node.0, b.field)));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ShaderSource, FULLSCREEN_VERT};
+
+    fn shader() -> ShaderSource {
+        ShaderSource {
+            vert: FULLSCREEN_VERT.to_string(),
+            frag: String::new(),
+            origin: None,
+        }
+    }
+
+    #[test]
+    fn reports_mismatched_and_missing_props() {
+        let mut g = Graph::new();
+        let video = g.add_node(NodeKind::VideoDecodeSource);
+        let pass = g.add_node(NodeKind::ShaderPass);
+        let bare = g.add_node(NodeKind::ShaderPass);
+        let out = g.add_node(NodeKind::PixelsOut);
+        let out2 = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(video, "out", pass, "in").unwrap();
+        g.connect_named(pass, "out", out, "in").unwrap();
+        g.connect_named(bare, "out", out2, "in").unwrap();
+
+        let mut props = NodeProps::default();
+        props.shader_sources.insert(pass, shader());
+        props.shader_sources.insert(NodeId(99), shader());
+        props.output_names.insert(pass, "main".into());
+        props.output_names.insert(out, "program".into());
+        props.output_names.insert(out2, "program".into());
+
+        let found: Vec<(NodeId, DiagnosticKind)> = validate_props(&g, &props)
+            .iter()
+            .map(|d| (d.node, d.kind))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (video, DiagnosticKind::MissingVideoConfig),
+                (pass, DiagnosticKind::WrongNodeKind),
+                (bare, DiagnosticKind::MissingShader),
+                (out2, DiagnosticKind::DuplicateOutputName),
+                (NodeId(99), DiagnosticKind::UnknownNode),
+            ]
+        );
+
+        props.shader_sources.remove(&NodeId(99));
+        props.shader_sources.insert(bare, shader());
+        props.output_names.remove(&pass);
+        props.output_names.insert(out2, "preview".into());
+        #[cfg(feature = "video")]
+        {
+            props.video_decode_json.insert(video, "clip.json".into());
+            assert!(validate_props(&g, &props).is_empty());
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Frame blending for `VideoDecodeSource` nodes (`FrameInterpolation::Blend`).
+//!
+//! Played slower than its fps, a clip holds each decoded frame for several output frames and
+//! slow motion steps. With blending on, the node keeps the previous decoded frame next to the
+//! current one and crossfades them by the timeline's fractional position between the two, so
+//! slow playback and scrubbing ramp smoothly. The crossfade runs into the latest frame, which
+//! costs one source frame of latency.
+
+use glow::HasContext;
+
+use crate::stats::{RuntimeStats, TextureFormat};
+use crate::{
+    create_host_texture, create_render_target, EngineError, FullscreenTriangle, RenderTarget,
+};
+
+pub(crate) const VIDEO_BLEND_FRAG: &str = r#"#version 330 core
+in vec2 v_uv;
+out vec4 o;
+uniform sampler2D uPrev;
+uniform sampler2D uCurr;
+uniform float uMix;
+void main() { o = mix(texture(uPrev, v_uv), texture(uCurr, v_uv), uMix); }
+"#;
+
+/// Weight of the current frame at timeline `time`, for a clip at `fps` whose current frame
+/// was uploaded at timeline frame index `shown` (`< 0`: nothing uploaded yet).
+pub(crate) fn blend_weight(time: f32, fps: f32, shown: i64) -> f32 {
+    if shown < 0 || fps <= 0.0 {
+        return 1.0;
+    }
+    (time.max(0.0) * fps - shown as f32).clamp(0.0, 1.0)
+}
+
+/// The previous decoded frame and the target the blend renders into.
+#[derive(Debug)]
+pub(crate) struct FrameBlend {
+    /// Swapped with the node's texture before each upload, so it holds the frame before.
+    pub(crate) prev: glow::NativeTexture,
+    target: RenderTarget,
+}
+
+impl FrameBlend {
+    pub(crate) unsafe fn new(gl: &glow::Context, w: i32, h: i32) -> Result<Self, EngineError> {
+        Ok(Self {
+            prev: create_host_texture(gl, w, h),
+            target: create_render_target(gl, w, h)?,
+        })
+    }
+
+    /// Reallocate for a new clip size; the previous frame is lost.
+    pub(crate) unsafe fn resize(&mut self, gl: &glow::Context, w: i32, h: i32) {
+        gl.delete_texture(self.prev);
+        self.prev = create_host_texture(gl, w, h);
+        self.target.resize(gl, w, h);
+    }
+
+    /// The blended frame.
+    pub(crate) fn output(&self) -> (glow::NativeTexture, i32, i32) {
+        (self.target.tex, self.target.w, self.target.h)
+    }
+
+    /// Render `mix(prev, curr, weight)` into the target (`program` is [`VIDEO_BLEND_FRAG`]).
+    pub(crate) unsafe fn render(
+        &self,
+        gl: &glow::Context,
+        fs_tri: &FullscreenTriangle,
+        program: glow::NativeProgram,
+        curr: glow::NativeTexture,
+        weight: f32,
+    ) {
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(self.target.fbo));
+        gl.viewport(0, 0, self.target.w, self.target.h);
+        gl.disable(glow::BLEND);
+        gl.use_program(Some(program));
+        for (unit, (name, tex)) in [("uPrev", self.prev), ("uCurr", curr)]
+            .into_iter()
+            .enumerate()
+        {
+            gl.active_texture(glow::TEXTURE0 + unit as u32);
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+            if let Some(loc) = gl.get_uniform_location(program, name) {
+                gl.uniform_1_i32(Some(&loc), unit as i32);
+            }
+        }
+        if let Some(loc) = gl.get_uniform_location(program, "uMix") {
+            gl.uniform_1_f32(Some(&loc), weight);
+        }
+        fs_tri.draw(gl);
+        for unit in [1, 0] {
+            gl.active_texture(glow::TEXTURE0 + unit);
+            gl.bind_texture(glow::TEXTURE_2D, None);
+        }
+        gl.use_program(None);
+        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+    }
+
+    pub(crate) fn account(&self, stats: &mut RuntimeStats) {
+        stats.add_texture(self.target.w, self.target.h, TextureFormat::Rgba8);
+        stats.add_target(self.target.w, self.target.h, TextureFormat::Rgba8);
+    }
+
+    pub(crate) unsafe fn destroy(self, gl: &glow::Context) {
+        gl.delete_texture(self.prev);
+        gl.delete_framebuffer(self.target.fbo);
+        gl.delete_texture(self.target.tex);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weight_ramps_between_uploads() {
+        // 30 fps clip at quarter speed: frame 3 was uploaded when the timeline reached 0.1 s.
+        assert_eq!(blend_weight(0.1, 30.0, 3), 0.0);
+        assert!((blend_weight(0.1125, 30.0, 3) - 0.375).abs() < 1e-4);
+        assert_eq!(
+            blend_weight(0.2, 30.0, 3),
+            1.0,
+            "a late decoder holds the frame"
+        );
+        assert_eq!(
+            blend_weight(0.05, 30.0, 3),
+            0.0,
+            "seeking back shows the old frame"
+        );
+        assert_eq!(blend_weight(0.5, 30.0, -1), 1.0);
+    }
+}
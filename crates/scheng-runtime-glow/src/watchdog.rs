@@ -0,0 +1,246 @@
+//! Frame-time watchdog with automatic quality fallback.
+//!
+//! The host feeds frame times (or brackets frames with `begin_frame` / `end_frame`). When the
+//! time stays above `threshold` for `consecutive_frames`, the watchdog raises its fallback level
+//! and runs the policy; after `recover_frames` good frames it steps back down. Each change is
+//! returned as an `EngineEvent` for logging / monitoring.
+//!
+//! The default policy ([`degrade_policy`]) first bypasses the configured flagged nodes, then
+//! lowers `render_scale` in `scale_step` increments down to `min_render_scale`.
+
+use std::time::{Duration, Instant};
+
+use scheng_core::events::EngineEvent;
+use scheng_graph::NodeId;
+
+use crate::RuntimeState;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchdogConfig {
+    /// Frames slower than this count as overloaded.
+    pub threshold: Duration,
+    /// Consecutive slow frames before stepping the fallback level up.
+    pub consecutive_frames: u32,
+    /// Consecutive good frames before stepping back down (0 = never recover automatically).
+    pub recover_frames: u32,
+    /// Render-scale decrement per level.
+    pub scale_step: f32,
+    pub min_render_scale: f32,
+    /// Expensive nodes the default policy bypasses first.
+    pub flagged_nodes: Vec<NodeId>,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            threshold: Duration::from_micros(33_333),
+            consecutive_frames: 30,
+            recover_frames: 600,
+            scale_step: 0.25,
+            min_render_scale: 0.5,
+            flagged_nodes: Vec::new(),
+        }
+    }
+}
+
+impl WatchdogConfig {
+    /// Highest level the default policy can use.
+    pub fn max_level(&self) -> u32 {
+        let node_level = u32::from(!self.flagged_nodes.is_empty());
+        let step = self.scale_step.max(0.01);
+        let scale_levels = ((1.0 - self.min_render_scale.clamp(0.1, 1.0)) / step).ceil() as u32;
+        node_level + scale_levels
+    }
+}
+
+/// Which way the level moved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchdogTrigger {
+    Overload,
+    Recovered,
+}
+
+/// Passed to the policy on every level change.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WatchdogReport {
+    pub trigger: WatchdogTrigger,
+    /// Frame time that completed the streak.
+    pub frame_time: Duration,
+    /// New fallback level (0 = full quality).
+    pub level: u32,
+}
+
+pub type WatchdogPolicy = Box<dyn FnMut(&WatchdogReport, &WatchdogConfig, &mut RuntimeState)>;
+
+/// Default policy: level 1 bypasses flagged nodes (if any), further levels reduce render scale.
+pub fn degrade_policy(report: &WatchdogReport, cfg: &WatchdogConfig, state: &mut RuntimeState) {
+    let mut level = report.level;
+    let bypass = !cfg.flagged_nodes.is_empty() && level >= 1;
+    for node in &cfg.flagged_nodes {
+        state.set_node_bypassed(*node, bypass);
+    }
+    if !cfg.flagged_nodes.is_empty() {
+        level = level.saturating_sub(1);
+    }
+    let scale = (1.0 - cfg.scale_step * level as f32).max(cfg.min_render_scale);
+    state.set_render_scale(scale);
+}
+
+/// Streak counting, separated from the policy so it can be reasoned about on its own.
+#[derive(Debug, Clone, Default)]
+struct Streaks {
+    over: u32,
+    under: u32,
+    level: u32,
+}
+
+impl Streaks {
+    fn observe(&mut self, frame_time: Duration, cfg: &WatchdogConfig) -> Option<WatchdogTrigger> {
+        if frame_time > cfg.threshold {
+            self.over += 1;
+            self.under = 0;
+            if self.over >= cfg.consecutive_frames.max(1) && self.level < cfg.max_level() {
+                self.over = 0;
+                self.level += 1;
+                return Some(WatchdogTrigger::Overload);
+            }
+        } else {
+            self.under += 1;
+            self.over = 0;
+            if cfg.recover_frames > 0 && self.under >= cfg.recover_frames && self.level > 0 {
+                self.under = 0;
+                self.level -= 1;
+                return Some(WatchdogTrigger::Recovered);
+            }
+        }
+        None
+    }
+}
+
+pub struct Watchdog {
+    cfg: WatchdogConfig,
+    streaks: Streaks,
+    policy: WatchdogPolicy,
+    frame_start: Option<Instant>,
+}
+
+impl std::fmt::Debug for Watchdog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Watchdog")
+            .field("cfg", &self.cfg)
+            .field("level", &self.streaks.level)
+            .finish()
+    }
+}
+
+impl Watchdog {
+    /// Watchdog using [`degrade_policy`].
+    pub fn new(cfg: WatchdogConfig) -> Self {
+        Self {
+            cfg,
+            streaks: Streaks::default(),
+            policy: Box::new(degrade_policy),
+            frame_start: None,
+        }
+    }
+
+    /// Replace the fallback policy.
+    pub fn with_policy(
+        mut self,
+        policy: impl FnMut(&WatchdogReport, &WatchdogConfig, &mut RuntimeState) + 'static,
+    ) -> Self {
+        self.policy = Box::new(policy);
+        self
+    }
+
+    pub fn config(&self) -> &WatchdogConfig {
+        &self.cfg
+    }
+
+    /// Current fallback level (0 = full quality).
+    pub fn level(&self) -> u32 {
+        self.streaks.level
+    }
+
+    pub fn begin_frame(&mut self) {
+        self.frame_start = Some(Instant::now());
+    }
+
+    /// Measure since `begin_frame` and `observe` the result.
+    pub fn end_frame(&mut self, state: &mut RuntimeState) -> Option<EngineEvent> {
+        let start = self.frame_start.take()?;
+        self.observe(start.elapsed(), state)
+    }
+
+    /// Feed one frame time. Returns an event when the fallback level changes.
+    pub fn observe(&mut self, frame_time: Duration, state: &mut RuntimeState) -> Option<EngineEvent> {
+        let trigger = self.streaks.observe(frame_time, &self.cfg)?;
+        let report = WatchdogReport {
+            trigger,
+            frame_time,
+            level: self.streaks.level,
+        };
+        (self.policy)(&report, &self.cfg, state);
+
+        Some(match trigger {
+            WatchdogTrigger::Overload => EngineEvent::WatchdogTriggered {
+                frame_ms: frame_time.as_secs_f32() * 1000.0,
+                threshold_ms: self.cfg.threshold.as_secs_f32() * 1000.0,
+                level: report.level,
+            },
+            WatchdogTrigger::Recovered => EngineEvent::WatchdogRecovered {
+                level: report.level,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg() -> WatchdogConfig {
+        WatchdogConfig {
+            threshold: Duration::from_millis(20),
+            consecutive_frames: 3,
+            recover_frames: 2,
+            scale_step: 0.25,
+            min_render_scale: 0.5,
+            flagged_nodes: vec![NodeId(4)],
+        }
+    }
+
+    #[test]
+    fn streak_must_be_consecutive_and_level_is_capped() {
+        let cfg = cfg();
+        assert_eq!(cfg.max_level(), 3);
+        let mut s = Streaks::default();
+        let slow = Duration::from_millis(30);
+        let fast = Duration::from_millis(5);
+
+        assert_eq!(s.observe(slow, &cfg), None);
+        assert_eq!(s.observe(slow, &cfg), None);
+        assert_eq!(s.observe(fast, &cfg), None); // streak broken
+        for _ in 0..2 {
+            assert_eq!(s.observe(slow, &cfg), None);
+        }
+        assert_eq!(s.observe(slow, &cfg), Some(WatchdogTrigger::Overload));
+        for _ in 0..20 {
+            s.observe(slow, &cfg);
+        }
+        assert_eq!(s.level, 3);
+    }
+
+    #[test]
+    fn recovers_one_level_per_good_streak() {
+        let cfg = cfg();
+        let mut s = Streaks {
+            level: 2,
+            ..Streaks::default()
+        };
+        let fast = Duration::from_millis(5);
+        assert_eq!(s.observe(fast, &cfg), None);
+        assert_eq!(s.observe(fast, &cfg), Some(WatchdogTrigger::Recovered));
+        assert_eq!(s.level, 1);
+    }
+}
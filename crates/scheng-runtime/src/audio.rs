@@ -0,0 +1,197 @@
+//! Waveform and spectrum data for the audio visualizer nodes (`NodeKind::AudioWaveform`,
+//! `NodeKind::AudioSpectrum`).
+//!
+//! The host hands the runtime its latest block of mono samples each frame; these helpers
+//! reduce that block to one value in `0..1` per column so every backend draws the same
+//! picture. Waveform columns keep the peak of their slice of the block (0.5 is silence).
+//! Spectrum columns are log-spaced bands of a Hann-windowed FFT, in decibels mapped from
+//! [`AudioVisParams::min_db`]..0 dBFS to `0..1`.
+
+/// How a visualizer draws its columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AudioVisStyle {
+    /// Vertical bars (from the bottom for spectra, from the centre line for waveforms).
+    #[default]
+    Bars,
+    /// A continuous line through the column values.
+    Line,
+    /// Bars wrapped around a ring, growing outwards from `inner_radius`.
+    Radial,
+}
+
+/// Parameters for the audio visualizer nodes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AudioVisParams {
+    pub style: AudioVisStyle,
+    /// Foreground colour (straight alpha) over opaque black.
+    pub color: [f32; 4],
+    /// Input gain applied before reduction.
+    pub gain: f32,
+    /// Columns (bars, ring segments or line points), clamped to `2..=AudioVisParams::MAX_BINS`.
+    pub bins: u32,
+    /// Line thickness in pixels (`Line` style).
+    pub line_width: f32,
+    /// Empty fraction of each bar's column (`Bars` and `Radial`), `0..1`.
+    pub bar_gap: f32,
+    /// Spectrum only: weight of the previous frame's bands (`0..1`, 0 = no smoothing).
+    pub smoothing: f32,
+    /// Spectrum only: level drawn as an empty band, in dBFS.
+    pub min_db: f32,
+    /// `Radial` only: radius of the ring the bars start from, as a fraction of the shorter
+    /// output side.
+    pub inner_radius: f32,
+}
+
+impl Default for AudioVisParams {
+    fn default() -> Self {
+        Self {
+            style: AudioVisStyle::Bars,
+            color: [0.3, 0.9, 1.0, 1.0],
+            gain: 1.0,
+            bins: 64,
+            line_width: 2.0,
+            bar_gap: 0.2,
+            smoothing: 0.6,
+            min_db: -60.0,
+            inner_radius: 0.2,
+        }
+    }
+}
+
+impl AudioVisParams {
+    pub const MAX_BINS: u32 = 1024;
+    /// Largest FFT the spectrum uses; longer blocks are cut to their most recent samples.
+    pub const MAX_FFT: usize = 4096;
+
+    /// `bins`, clamped.
+    pub fn bin_count(&self) -> usize {
+        self.bins.clamp(2, Self::MAX_BINS) as usize
+    }
+}
+
+/// Reduce `samples` (-1..1) to `columns` waveform values in `0..1` (0.5 = silence). Each column
+/// keeps the sample of largest magnitude in its slice, so short transients stay visible.
+pub fn waveform_columns(samples: &[f32], columns: usize, gain: f32) -> Vec<f32> {
+    (0..columns)
+        .map(|c| {
+            if samples.is_empty() {
+                return 0.5;
+            }
+            let lo = c * samples.len() / columns;
+            let hi = ((c + 1) * samples.len() / columns).max(lo + 1).min(samples.len());
+            let peak = samples[lo.min(samples.len() - 1)..hi]
+                .iter()
+                .copied()
+                .fold(0.0f32, |p, s| if s.abs() > p.abs() { s } else { p });
+            0.5 + 0.5 * (peak * gain).clamp(-1.0, 1.0)
+        })
+        .collect()
+}
+
+/// Reduce the most recent samples of `samples` to `bands` log-spaced spectrum values in `0..1`.
+///
+/// The FFT size is the largest power of two that fits the block (up to
+/// [`AudioVisParams::MAX_FFT`]); a full-scale sine reads 0 dBFS. Bands span bin 1 to Nyquist
+/// and each takes the loudest FFT bin it covers. Blocks shorter than 16 samples are silent.
+pub fn spectrum_bands(samples: &[f32], bands: usize, gain: f32, min_db: f32) -> Vec<f32> {
+    let n = samples.len().min(AudioVisParams::MAX_FFT);
+    if n < 16 || bands == 0 {
+        return vec![0.0; bands];
+    }
+    let n = 1 << (usize::BITS - 1 - n.leading_zeros());
+    let block = &samples[samples.len() - n..];
+
+    // Hann window, normalised so a full-scale sine has amplitude 1.
+    let window: Vec<f32> = (0..n)
+        .map(|i| 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / n as f32).cos())
+        .collect();
+    let norm = 2.0 / window.iter().sum::<f32>();
+    let mut re: Vec<f32> = block.iter().zip(&window).map(|(s, w)| s * w * gain).collect();
+    let mut im = vec![0.0; n];
+    fft(&mut re, &mut im);
+    let mag: Vec<f32> = (0..=n / 2)
+        .map(|k| (re[k] * re[k] + im[k] * im[k]).sqrt() * norm)
+        .collect();
+
+    let nyquist = (n / 2) as f32;
+    let floor = min_db.min(-1.0);
+    (0..bands)
+        .map(|b| {
+            let lo = nyquist.powf(b as f32 / bands as f32) as usize;
+            let hi = (nyquist.powf((b + 1) as f32 / bands as f32) as usize).max(lo + 1);
+            let amp = mag[lo.max(1)..hi.min(n / 2 + 1)]
+                .iter()
+                .copied()
+                .fold(0.0f32, f32::max);
+            let db = 20.0 * amp.max(1e-9).log10();
+            ((db - floor) / -floor).clamp(0.0, 1.0)
+        })
+        .collect()
+}
+
+/// In-place iterative radix-2 FFT; `re.len()` must be a power of two.
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let step = -std::f32::consts::TAU / len as f32;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (s, c) = (step * k as f32).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let tr = re[b] * c - im[b] * s;
+                let ti = re[b] * s + im[b] * c;
+                re[b] = re[a] - tr;
+                im[b] = im[a] - ti;
+                re[a] += tr;
+                im[a] += ti;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waveform_keeps_peaks_and_centres_silence() {
+        let mut samples = vec![0.0; 64];
+        samples[5] = -0.8;
+        samples[40] = 0.5;
+        let cols = waveform_columns(&samples, 4, 1.0);
+        assert_eq!(cols, [0.5 - 0.4, 0.5, 0.75, 0.5]);
+        assert_eq!(waveform_columns(&[], 3, 1.0), [0.5; 3]);
+        assert_eq!(waveform_columns(&[1.0], 2, 4.0), [1.0, 1.0]);
+    }
+
+    #[test]
+    fn spectrum_places_a_sine_in_its_band() {
+        let n = 1024;
+        // Bin 64 of a 1024-point FFT: band index ~ bands * log(64) / log(512).
+        let sine: Vec<f32> = (0..n)
+            .map(|i| (std::f32::consts::TAU * 64.0 * i as f32 / n as f32).sin())
+            .collect();
+        let bands = spectrum_bands(&sine, 9, 1.0, -60.0);
+        let loudest = (0..9).max_by(|&a, &b| bands[a].total_cmp(&bands[b])).unwrap();
+        assert_eq!(loudest, 6, "{bands:?}");
+        assert!(bands[6] > 0.95, "full-scale sine reads ~0 dBFS: {bands:?}");
+        assert!(bands[0] < 0.2, "{bands:?}");
+
+        assert_eq!(spectrum_bands(&vec![0.0; n], 4, 1.0, -60.0), [0.0; 4]);
+        assert_eq!(spectrum_bands(&[0.5; 8], 4, 1.0, -60.0), [0.0; 4]);
+    }
+}
@@ -0,0 +1,361 @@
+//! Automation lanes: live parameter moves recorded against the timeline and played back.
+//!
+//! An improvised performance moves parameters from OSC, MIDI or a UI. [`Automation`] keeps one
+//! [`AutomationLane`] per [`ParamRegistry`] key and, once per frame, either plays the lane back
+//! into the registry or records the registry value into the lane, depending on the lane's
+//! [`AutomationMode`]. Live moves are detected by comparing the registry with the value
+//! automation wrote last frame, so control surfaces need no extra wiring.
+//!
+//! Like [`switching`](crate::switching), everything is driven by an explicit timeline time in
+//! seconds. Call [`Automation::apply`] only while the transport runs. Time going backwards (a
+//! loop or a seek) starts a new pass; call [`Automation::end_pass`] when the transport stops.
+
+use std::collections::BTreeMap;
+
+use crate::ParamRegistry;
+
+/// What a lane does with live moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AutomationMode {
+    /// Play back only; live moves are overridden on the next frame.
+    #[default]
+    Read,
+    /// Record the parameter for the whole pass, replacing what was there.
+    Overwrite,
+    /// Play back, but record while the parameter is being moved. Playback resumes on
+    /// [`Automation::release`] or once the value stops changing for the touch timeout.
+    Touch,
+    /// Like `Touch`, but after the first move keep recording the last value until the pass ends.
+    Latch,
+}
+
+impl AutomationMode {
+    fn records(self) -> bool {
+        self != AutomationMode::Read
+    }
+}
+
+/// How playback fills the time between two points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AutomationInterpolation {
+    /// Hold each point's value until the next one.
+    Step,
+    #[default]
+    Linear,
+    /// Smoothstep: eases out of one point and into the next.
+    Smooth,
+}
+
+/// One recorded value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AutomationPoint {
+    /// Timeline time in seconds.
+    pub time: f64,
+    pub value: f32,
+}
+
+/// Recording state of a lane within the current pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Pass {
+    /// The registry value after automation's last write.
+    written: Option<f32>,
+    /// Time of the last live move while `Touch` is held.
+    touched: Option<f64>,
+    /// A `Touch` held by the host (see [`Automation::touch`]) until `release`.
+    held: bool,
+    latched: bool,
+    /// Time of the last recorded point in this pass.
+    recorded: Option<f64>,
+}
+
+/// Points for one parameter, sorted by time.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AutomationLane {
+    points: Vec<AutomationPoint>,
+    pub mode: AutomationMode,
+    pub interpolation: AutomationInterpolation,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pass: Pass,
+}
+
+impl AutomationLane {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_mode(mut self, mode: AutomationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_interpolation(mut self, interpolation: AutomationInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    pub fn points(&self) -> &[AutomationPoint] {
+        &self.points
+    }
+
+    /// Add a point, replacing one at the same time.
+    pub fn insert(&mut self, time: f64, value: f32) {
+        let i = self.points.partition_point(|p| p.time < time);
+        match self.points.get_mut(i) {
+            Some(p) if p.time == time => p.value = value,
+            _ => self.points.insert(i, AutomationPoint { time, value }),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.points.clear();
+    }
+
+    /// The lane's value at `time`: the first or last point's value outside the recorded range,
+    /// `None` for an empty lane.
+    pub fn value_at(&self, time: f64) -> Option<f32> {
+        let i = self.points.partition_point(|p| p.time <= time);
+        let Some(next) = self.points.get(i) else {
+            return self.points.last().map(|p| p.value);
+        };
+        let Some(prev) = i.checked_sub(1).map(|i| self.points[i]) else {
+            return Some(next.value);
+        };
+        let t = ((time - prev.time) / (next.time - prev.time)) as f32;
+        let t = match self.interpolation {
+            AutomationInterpolation::Step => 0.0,
+            AutomationInterpolation::Linear => t,
+            AutomationInterpolation::Smooth => t * t * (3.0 - 2.0 * t),
+        };
+        Some(prev.value + (next.value - prev.value) * t)
+    }
+
+    /// Write `value` at `time`, replacing the points recorded over since the last write.
+    fn record(&mut self, time: f64, value: f32) {
+        let from = self.pass.recorded.unwrap_or(time);
+        self.points.retain(|p| p.time <= from || p.time > time);
+        self.insert(time, value);
+        self.pass.recorded = Some(time);
+    }
+
+    /// Whether the lane records this frame, given whether the parameter moved since the last.
+    fn recording(&mut self, time: f64, moved: bool, touch_release: f64) -> bool {
+        match self.mode {
+            AutomationMode::Read => false,
+            AutomationMode::Overwrite => true,
+            AutomationMode::Touch => {
+                if moved {
+                    self.pass.touched = Some(time);
+                }
+                let active = self.pass.touched.is_some_and(|t| time - t <= touch_release);
+                if !active {
+                    self.pass.touched = None;
+                }
+                active || self.pass.held
+            }
+            AutomationMode::Latch => {
+                self.pass.latched |= moved || self.pass.held;
+                self.pass.latched
+            }
+        }
+    }
+}
+
+/// Automation lanes keyed by registry parameter (see the module docs).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Automation {
+    lanes: BTreeMap<String, AutomationLane>,
+    /// Seconds without a move after which a `Touch` lane returns to playback.
+    pub touch_release: f64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_time: Option<f64>,
+}
+
+impl Default for Automation {
+    fn default() -> Self {
+        Self {
+            lanes: BTreeMap::new(),
+            touch_release: 0.5,
+            last_time: None,
+        }
+    }
+}
+
+impl Automation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_touch_release(mut self, secs: f64) -> Self {
+        self.touch_release = secs.max(0.0);
+        self
+    }
+
+    /// The lane for `param`, created empty (in `Read` mode) if missing.
+    pub fn lane_mut(&mut self, param: &str) -> &mut AutomationLane {
+        self.lanes.entry(param.to_string()).or_default()
+    }
+
+    pub fn lane(&self, param: &str) -> Option<&AutomationLane> {
+        self.lanes.get(param)
+    }
+
+    pub fn remove_lane(&mut self, param: &str) -> Option<AutomationLane> {
+        self.lanes.remove(param)
+    }
+
+    /// `(param, lane)` pairs, sorted by parameter.
+    pub fn lanes(&self) -> impl Iterator<Item = (&str, &AutomationLane)> {
+        self.lanes.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Set `param`'s mode, creating the lane. Recording state starts afresh.
+    pub fn set_mode(&mut self, param: &str, mode: AutomationMode) {
+        let lane = self.lane_mut(param);
+        lane.mode = mode;
+        lane.pass = Pass {
+            written: lane.pass.written,
+            ..Pass::default()
+        };
+    }
+
+    /// Hold `param`'s `Touch`/`Latch` lane in recording, e.g. while a UI fader is grabbed,
+    /// until [`release`](Self::release).
+    pub fn touch(&mut self, param: &str) {
+        if let Some(lane) = self.lanes.get_mut(param) {
+            lane.pass.held = true;
+        }
+    }
+
+    /// Return a `Touch` lane to playback (a `Latch` lane keeps recording until the pass ends).
+    pub fn release(&mut self, param: &str) {
+        if let Some(lane) = self.lanes.get_mut(param) {
+            lane.pass.held = false;
+            lane.pass.touched = None;
+        }
+    }
+
+    /// Stop recording on every lane (transport stopped). The next `apply` starts a new pass.
+    pub fn end_pass(&mut self) {
+        self.last_time = None;
+        for lane in self.lanes.values_mut() {
+            lane.pass = Pass {
+                written: lane.pass.written,
+                ..Pass::default()
+            };
+        }
+    }
+
+    /// Play back or record every lane at timeline `time` (see the module docs). Returns how
+    /// many lanes recorded this frame.
+    pub fn apply(&mut self, time: f64, params: &mut ParamRegistry) -> usize {
+        if self.last_time.is_some_and(|t| time < t) {
+            self.end_pass();
+        }
+        self.last_time = Some(time);
+        let mut recorded = 0;
+        for (name, lane) in &mut self.lanes {
+            let current = params.get(name);
+            let moved = matches!((current, lane.pass.written), (Some(c), Some(w)) if c != w);
+            let value = match current {
+                Some(v)
+                    if lane.mode.records() && lane.recording(time, moved, self.touch_release) =>
+                {
+                    lane.record(time, v);
+                    recorded += 1;
+                    Some(v)
+                }
+                _ => {
+                    lane.pass.recorded = None;
+                    match lane.value_at(time) {
+                        Some(v) => Some(params.set(name, v)),
+                        None => current,
+                    }
+                }
+            };
+            lane.pass.written = value;
+        }
+        recorded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAIN: &str = "node/fx/gain";
+
+    #[test]
+    fn playback_interpolates_between_points() {
+        let mut lane = AutomationLane::new();
+        lane.insert(2.0, 1.0);
+        lane.insert(0.0, 0.0);
+        assert_eq!(lane.value_at(-1.0), Some(0.0));
+        assert_eq!(lane.value_at(0.5), Some(0.25));
+        assert_eq!(lane.value_at(9.0), Some(1.0));
+        let step = lane
+            .clone()
+            .with_interpolation(AutomationInterpolation::Step);
+        assert_eq!(step.value_at(1.9), Some(0.0));
+        let smooth = lane.with_interpolation(AutomationInterpolation::Smooth);
+        assert_eq!(smooth.value_at(0.5), Some(0.15625));
+        assert_eq!(AutomationLane::new().value_at(1.0), None);
+    }
+
+    #[test]
+    fn overwrite_replaces_the_pass_and_read_plays_it_back() {
+        let mut params = ParamRegistry::new();
+        let mut auto = Automation::new();
+        auto.lane_mut(GAIN).insert(0.5, 9.0);
+        auto.set_mode(GAIN, AutomationMode::Overwrite);
+        for (t, v) in [(0.0, 0.2), (1.0, 0.4)] {
+            params.set(GAIN, v);
+            assert_eq!(auto.apply(t, &mut params), 1);
+        }
+        let points = auto.lane(GAIN).unwrap().points();
+        assert_eq!(points.len(), 2, "the old point was recorded over");
+
+        auto.set_mode(GAIN, AutomationMode::Read);
+        auto.apply(0.5, &mut params);
+        assert!((params.get(GAIN).unwrap() - 0.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn touch_records_only_while_moving_and_latch_holds() {
+        let mut params = ParamRegistry::new();
+        let mut auto = Automation::new().with_touch_release(0.25);
+        let lane = auto.lane_mut(GAIN);
+        lane.insert(0.0, 0.0);
+        lane.insert(4.0, 0.0);
+        auto.set_mode(GAIN, AutomationMode::Touch);
+
+        assert_eq!(auto.apply(0.0, &mut params), 0);
+        params.set(GAIN, 0.7);
+        assert_eq!(auto.apply(1.0, &mut params), 1, "a live move records");
+        assert_eq!(auto.apply(1.1, &mut params), 1, "still touched");
+        assert_eq!(
+            auto.apply(2.0, &mut params),
+            0,
+            "released after the timeout"
+        );
+        // Playback resumes, ramping from the recorded 0.7 back to the old point at 4 s.
+        let expected = 0.7 * (4.0 - 2.0) / (4.0 - 1.1);
+        assert!((params.get(GAIN).unwrap() - expected).abs() < 1e-6);
+
+        auto.set_mode(GAIN, AutomationMode::Latch);
+        params.set(GAIN, 0.5);
+        assert_eq!(auto.apply(3.0, &mut params), 1);
+        assert_eq!(auto.apply(3.5, &mut params), 1, "latched without moving");
+        auto.end_pass();
+        assert_eq!(auto.apply(3.9, &mut params), 0);
+        assert_eq!(auto.lane(GAIN).unwrap().value_at(3.5), Some(0.5));
+    }
+}
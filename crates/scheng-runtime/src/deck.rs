@@ -0,0 +1,432 @@
+//! A/B decks with a crossfader: the usual live-mixing topology, patched in one call.
+//!
+//! Each deck is a [`LayerStack`]: its layers are composited bottom-up by `Crossfade` nodes
+//! whose mix is the layer's opacity. The two stack heads feed one more `Crossfade`, the
+//! crossfader, which is the program bus. [`DeckMixer`] adds the nodes to an existing graph
+//! (the layers are whatever render nodes the host already has) and keeps every control in the
+//! [`ParamRegistry`], so OSC/MIDI mappings drive it like a DJ mixer:
+//!
+//! | Key (under `deck/<name>/`) | Range | Meaning |
+//! |---|---|---|
+//! | `crossfader` | 0..1 | Fader position, 0 = deck A, 1 = deck B |
+//! | `curve` | 0..2 | [`FadeCurve`] index |
+//! | `fade_time` | 0..30 | Seconds for a full auto-fade |
+//! | `cut`, `auto` | 0..1 | Momentary: set to 1 to cut / auto-fade to the other deck |
+//! | `a/layer/<i>/opacity`, `b/...` | 0..1 | Opacity of layer `i >= 1` (layer 0 is opaque) |
+//!
+//! Call [`DeckMixer::tick`] once per frame with the timeline time, then copy
+//! [`DeckMixer::mixer_params`] into the backend's mixer props. Moving the crossfader by hand
+//! during an auto-fade cancels it.
+
+use scheng_core::EngineError;
+use scheng_graph::{Graph, NodeId, NodeKind};
+
+use crate::{MixerParams, ParamMeta, ParamRegistry};
+
+/// One side of a [`DeckMixer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Deck {
+    A,
+    B,
+}
+
+impl Deck {
+    /// Crossfader position that shows only this deck.
+    pub fn position(self) -> f32 {
+        match self {
+            Deck::A => 0.0,
+            Deck::B => 1.0,
+        }
+    }
+
+    pub fn other(self) -> Deck {
+        match self {
+            Deck::A => Deck::B,
+            Deck::B => Deck::A,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Deck::A => "a",
+            Deck::B => "b",
+        }
+    }
+}
+
+/// Maps the crossfader position to the program mix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FadeCurve {
+    /// Mix follows the fader.
+    #[default]
+    Linear,
+    /// Smoothstep: eases in and out of each deck.
+    Smooth,
+    /// Holds each deck over the outer 40% of travel and crosses in the middle fifth.
+    Sharp,
+}
+
+impl FadeCurve {
+    pub const ALL: [FadeCurve; 3] = [FadeCurve::Linear, FadeCurve::Smooth, FadeCurve::Sharp];
+
+    /// Program mix (0 = A, 1 = B) at fader position `x`.
+    pub fn apply(self, x: f32) -> f32 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            FadeCurve::Linear => x,
+            FadeCurve::Smooth => x * x * (3.0 - 2.0 * x),
+            FadeCurve::Sharp => ((x - 0.5) * 5.0 + 0.5).clamp(0.0, 1.0),
+        }
+    }
+
+    /// The curve for a registry value (rounded, clamped to the known curves).
+    pub fn from_index(v: f32) -> FadeCurve {
+        Self::ALL[(v.round().max(0.0) as usize).min(Self::ALL.len() - 1)]
+    }
+}
+
+/// Layers composited bottom-up: layer 0 is the opaque base, each further layer is crossfaded
+/// over the stack below it by its opacity.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerStack {
+    layers: Vec<NodeId>,
+    /// `blends[i]` composites `layers[i + 1]` over the stack below.
+    blends: Vec<NodeId>,
+}
+
+impl LayerStack {
+    /// Add the blend nodes for `layers` (bottom first) to `graph`.
+    pub fn build(graph: &mut Graph, layers: &[NodeId]) -> Result<Self, EngineError> {
+        let (&base, rest) = layers
+            .split_first()
+            .ok_or_else(|| EngineError::other("layer stack: needs at least one layer"))?;
+        let mut head = base;
+        let mut blends = Vec::with_capacity(rest.len());
+        for &layer in rest {
+            let blend = graph.add_node(NodeKind::Crossfade);
+            graph.connect_named(head, "out", blend, "a")?;
+            graph.connect_named(layer, "out", blend, "b")?;
+            blends.push(blend);
+            head = blend;
+        }
+        Ok(Self {
+            layers: layers.to_vec(),
+            blends,
+        })
+    }
+
+    /// Layer nodes, bottom first.
+    pub fn layers(&self) -> &[NodeId] {
+        &self.layers
+    }
+
+    /// The node carrying the composited stack (the base layer when there is only one).
+    pub fn head(&self) -> NodeId {
+        self.blends.last().copied().unwrap_or(self.layers[0])
+    }
+
+    /// The `Crossfade` node that blends layer `index` (`>= 1`) over the layers below it.
+    pub fn blend_node(&self, index: usize) -> Option<NodeId> {
+        self.blends.get(index.checked_sub(1)?).copied()
+    }
+}
+
+/// An auto-fade in progress.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AutoFade {
+    from: f32,
+    to: f32,
+    start: f64,
+    duration: f64,
+}
+
+/// Two [`LayerStack`] decks and a crossfader, with controls in the registry (see the module
+/// docs).
+#[derive(Debug, Clone)]
+pub struct DeckMixer {
+    name: String,
+    a: LayerStack,
+    b: LayerStack,
+    crossfader: NodeId,
+    fade: Option<AutoFade>,
+    /// Fader position this mixer last wrote; anything else means a hand on the fader.
+    written: Option<f32>,
+}
+
+impl DeckMixer {
+    /// Build both decks and the crossfader into `graph`. Connect [`program`](Self::program) to
+    /// an output; the deck heads can feed preview outputs.
+    pub fn build(
+        graph: &mut Graph,
+        name: &str,
+        a_layers: &[NodeId],
+        b_layers: &[NodeId],
+    ) -> Result<Self, EngineError> {
+        let a = LayerStack::build(graph, a_layers)?;
+        let b = LayerStack::build(graph, b_layers)?;
+        let crossfader = graph.add_node(NodeKind::Crossfade);
+        graph.connect_named(a.head(), "out", crossfader, "a")?;
+        graph.connect_named(b.head(), "out", crossfader, "b")?;
+        Ok(Self {
+            name: name.to_string(),
+            a,
+            b,
+            crossfader,
+            fade: None,
+            written: None,
+        })
+    }
+
+    /// The crossfader node (the program bus).
+    pub fn program(&self) -> NodeId {
+        self.crossfader
+    }
+
+    pub fn deck(&self, deck: Deck) -> &LayerStack {
+        match deck {
+            Deck::A => &self.a,
+            Deck::B => &self.b,
+        }
+    }
+
+    /// Registry key of control `param` (e.g. `"crossfader"`).
+    pub fn key(&self, param: &str) -> String {
+        format!("deck/{}/{param}", self.name)
+    }
+
+    /// Registry key of the opacity of `deck`'s layer `index`.
+    pub fn opacity_key(&self, deck: Deck, index: usize) -> String {
+        self.key(&format!("{}/layer/{index}/opacity", deck.key()))
+    }
+
+    /// Declare every control, starting on deck A with all layers opaque.
+    pub fn declare(&self, registry: &mut ParamRegistry) {
+        let max_curve = (FadeCurve::ALL.len() - 1) as f32;
+        let controls = [
+            (
+                "crossfader",
+                ParamMeta::new(0.0, 1.0, 0.0),
+                "0 = deck A, 1 = deck B",
+            ),
+            (
+                "curve",
+                ParamMeta::new(0.0, max_curve, 0.0),
+                "0 linear, 1 smooth, 2 sharp",
+            ),
+            (
+                "fade_time",
+                ParamMeta::new(0.0, 30.0, 2.0),
+                "seconds for a full auto-fade",
+            ),
+            (
+                "cut",
+                ParamMeta::new(0.0, 1.0, 0.0),
+                "set to 1 to cut to the other deck",
+            ),
+            (
+                "auto",
+                ParamMeta::new(0.0, 1.0, 0.0),
+                "set to 1 to fade to the other deck",
+            ),
+        ];
+        for (param, meta, description) in controls {
+            registry.declare(self.key(param), meta.with_description(description));
+        }
+        for deck in [Deck::A, Deck::B] {
+            for index in 1..self.deck(deck).layers.len() {
+                registry.declare(
+                    self.opacity_key(deck, index),
+                    ParamMeta::new(0.0, 1.0, 1.0)
+                        .with_description(format!("deck {deck:?} layer {index} opacity")),
+                );
+            }
+        }
+    }
+
+    /// The deck the fader is closer to.
+    pub fn on_air(&self, registry: &ParamRegistry) -> Deck {
+        if registry.get_or(&self.key("crossfader"), 0.0) < 0.5 {
+            Deck::A
+        } else {
+            Deck::B
+        }
+    }
+
+    pub fn is_fading(&self) -> bool {
+        self.fade.is_some()
+    }
+
+    /// Jump the fader to `deck`, cancelling any auto-fade.
+    pub fn cut(&mut self, registry: &mut ParamRegistry, deck: Deck) {
+        self.fade = None;
+        self.write(registry, deck.position());
+    }
+
+    /// Start fading to `deck` at timeline time `now`. A partial fade takes the matching share
+    /// of `fade_time`; a zero `fade_time` cuts.
+    pub fn auto_fade(&mut self, registry: &mut ParamRegistry, deck: Deck, now: f64) {
+        let from = registry.get_or(&self.key("crossfader"), 0.0);
+        let to = deck.position();
+        let duration =
+            registry.get_or(&self.key("fade_time"), 2.0) as f64 * (to - from).abs() as f64;
+        if duration <= 0.0 {
+            self.cut(registry, deck);
+            return;
+        }
+        self.fade = Some(AutoFade {
+            from,
+            to,
+            start: now,
+            duration,
+        });
+        self.written = Some(from);
+    }
+
+    /// Handle the `cut` / `auto` triggers and advance an auto-fade. Call once per frame.
+    pub fn tick(&mut self, registry: &mut ParamRegistry, now: f64) {
+        let fader = registry.get_or(&self.key("crossfader"), 0.0);
+        if self.written.is_some_and(|w| w != fader) {
+            // Someone moved the fader.
+            self.fade = None;
+            self.written = None;
+        }
+        for (trigger, fade) in [("cut", false), ("auto", true)] {
+            let key = self.key(trigger);
+            if registry.get_or(&key, 0.0) >= 0.5 {
+                registry.set(&key, 0.0);
+                let target = self.target(registry).other();
+                if fade {
+                    self.auto_fade(registry, target, now);
+                } else {
+                    self.cut(registry, target);
+                }
+            }
+        }
+        if let Some(f) = self.fade {
+            let t = ((now - f.start) / f.duration).clamp(0.0, 1.0) as f32;
+            self.write(registry, f.from + (f.to - f.from) * t);
+            if t >= 1.0 {
+                self.fade = None;
+            }
+        }
+    }
+
+    /// `Crossfade` parameters for the crossfader and every layer blend, from the registry.
+    pub fn mixer_params(&self, registry: &ParamRegistry) -> Vec<(NodeId, MixerParams)> {
+        let curve = FadeCurve::from_index(registry.get_or(&self.key("curve"), 0.0));
+        let fader = registry.get_or(&self.key("crossfader"), 0.0);
+        let mut params = vec![(self.crossfader, MixerParams::new(curve.apply(fader)))];
+        for deck in [Deck::A, Deck::B] {
+            for (i, &blend) in self.deck(deck).blends.iter().enumerate() {
+                let opacity = registry.get_or(&self.opacity_key(deck, i + 1), 1.0);
+                params.push((blend, MixerParams::new(opacity.clamp(0.0, 1.0))));
+            }
+        }
+        params
+    }
+
+    /// The deck being faded to, else the one on air.
+    fn target(&self, registry: &ParamRegistry) -> Deck {
+        match self.fade {
+            Some(f) if f.to > f.from => Deck::B,
+            Some(_) => Deck::A,
+            None => self.on_air(registry),
+        }
+    }
+
+    fn write(&mut self, registry: &mut ParamRegistry, position: f32) {
+        let stored = registry.set(&self.key("crossfader"), position);
+        self.written = Some(stored);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mixer() -> (Graph, DeckMixer, ParamRegistry) {
+        let mut g = Graph::new();
+        let [a0, a1, b0] = [(); 3].map(|_| g.add_node(NodeKind::ShaderSource));
+        let deck = DeckMixer::build(&mut g, "main", &[a0, a1], &[b0]).unwrap();
+        let out = g.add_node(NodeKind::PixelsOut);
+        g.connect_named(deck.program(), "out", out, "in").unwrap();
+        let mut registry = ParamRegistry::new();
+        deck.declare(&mut registry);
+        (g, deck, registry)
+    }
+
+    #[test]
+    fn builds_stacks_into_the_crossfader() {
+        let (g, deck, registry) = mixer();
+        g.compile().expect("deck graph compiles");
+        let a = deck.deck(Deck::A);
+        assert_eq!(a.layers().len(), 2);
+        assert_eq!(a.head(), a.blend_node(1).unwrap());
+        assert_eq!(a.blend_node(0), None);
+        assert_eq!(deck.deck(Deck::B).head(), deck.deck(Deck::B).layers()[0]);
+        assert!(LayerStack::build(&mut Graph::new(), &[]).is_err());
+
+        assert_eq!(registry.get("deck/main/a/layer/1/opacity"), Some(1.0));
+        assert_eq!(registry.get("deck/main/b/layer/1/opacity"), None);
+        assert_eq!(
+            deck.mixer_params(&registry),
+            [
+                (deck.program(), MixerParams::new(0.0)),
+                (a.head(), MixerParams::new(1.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn cut_auto_fade_and_manual_takeover() {
+        let (_, mut deck, mut reg) = mixer();
+        let fader = |reg: &ParamRegistry| reg.get("deck/main/crossfader").unwrap();
+
+        reg.set("deck/main/auto", 1.0);
+        deck.tick(&mut reg, 10.0);
+        assert_eq!(
+            reg.get("deck/main/auto"),
+            Some(0.0),
+            "triggers are momentary"
+        );
+        assert!(deck.is_fading());
+        deck.tick(&mut reg, 11.0);
+        assert_eq!(fader(&reg), 0.5);
+        // A second `auto` mid-fade reverses towards A, from where the fader is.
+        reg.set("deck/main/auto", 1.0);
+        deck.tick(&mut reg, 11.0);
+        deck.tick(&mut reg, 11.5);
+        assert_eq!(fader(&reg), 0.25);
+        deck.tick(&mut reg, 20.0);
+        assert_eq!((fader(&reg), deck.is_fading()), (0.0, false));
+
+        deck.auto_fade(&mut reg, Deck::B, 30.0);
+        deck.tick(&mut reg, 30.5);
+        reg.set("deck/main/crossfader", 0.9);
+        deck.tick(&mut reg, 31.0);
+        assert_eq!(
+            (fader(&reg), deck.is_fading()),
+            (0.9, false),
+            "hand on the fader"
+        );
+
+        reg.set("deck/main/cut", 1.0);
+        deck.tick(&mut reg, 32.0);
+        assert_eq!((fader(&reg), deck.on_air(&reg)), (0.0, Deck::A));
+    }
+
+    #[test]
+    fn curves_shape_the_program_mix() {
+        let (_, deck, mut reg) = mixer();
+        reg.set("deck/main/crossfader", 0.25);
+        let mix = |reg: &ParamRegistry| deck.mixer_params(reg)[0].1.mix;
+        assert_eq!(mix(&reg), 0.25);
+        reg.set("deck/main/curve", 1.0);
+        assert_eq!(mix(&reg), 0.15625);
+        reg.set("deck/main/curve", 2.0);
+        assert_eq!(mix(&reg), 0.0);
+        reg.set("deck/main/crossfader", 0.55);
+        assert!((mix(&reg) - 0.75).abs() < 1e-6);
+        assert_eq!(FadeCurve::from_index(7.0), FadeCurve::Sharp);
+    }
+}
@@ -0,0 +1,199 @@
+//! Dither threshold maps for `NodeKind::Dither`.
+//!
+//! Backends tile a square map of thresholds in `0..1` over the output and quantize each pixel
+//! to [`DitherParams::levels`] after offsetting it by `(threshold - 0.5) * amount`. The maps are
+//! generated here so every backend dithers identically: an 8x8 Bayer matrix for
+//! [`DitherMode::Ordered`] and a 32x32 void-and-cluster blue-noise map for
+//! [`DitherMode::BlueNoise`].
+
+/// Which threshold map [`DitherParams`] uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DitherMode {
+    /// 8x8 Bayer matrix: the classic cross-hatch look.
+    #[default]
+    Ordered,
+    /// Blue noise: no visible pattern, grain only.
+    BlueNoise,
+}
+
+impl DitherMode {
+    /// Side of the square threshold map.
+    pub fn map_size(self) -> usize {
+        match self {
+            DitherMode::Ordered => 8,
+            DitherMode::BlueNoise => 32,
+        }
+    }
+
+    /// Row-major thresholds in `0..1`, `map_size()` squared.
+    pub fn threshold_map(self) -> Vec<f32> {
+        let n = self.map_size();
+        let ranks = match self {
+            DitherMode::Ordered => bayer_ranks(n),
+            DitherMode::BlueNoise => blue_noise_ranks(n),
+        };
+        let total = (n * n) as f32;
+        ranks.into_iter().map(|r| (r as f32 + 0.5) / total).collect()
+    }
+}
+
+/// Parameters for `NodeKind::Dither`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DitherParams {
+    pub mode: DitherMode,
+    /// Output levels per channel (at least 2).
+    pub levels: u32,
+    /// Threshold offset scale in quantization steps (0 = plain posterize, 1 = full dither).
+    pub amount: f32,
+}
+
+impl Default for DitherParams {
+    fn default() -> Self {
+        Self {
+            mode: DitherMode::Ordered,
+            levels: 4,
+            amount: 1.0,
+        }
+    }
+}
+
+/// Bayer matrix ranks for a power-of-two `n`, from interleaving the bits of `x ^ y` and `y`.
+fn bayer_ranks(n: usize) -> Vec<usize> {
+    let bits = n.trailing_zeros();
+    let mut ranks = Vec::with_capacity(n * n);
+    for y in 0..n {
+        for x in 0..n {
+            let xy = x ^ y;
+            let mut rank = 0;
+            for bit in 0..bits {
+                rank = (rank << 2) | (((xy >> bit) & 1) << 1) | ((y >> bit) & 1);
+            }
+            ranks.push(rank);
+        }
+    }
+    ranks
+}
+
+/// Void-and-cluster (Ulichney) ranks on an `n` x `n` torus: points are added where they are
+/// furthest from all others, so every prefix of the ranking is evenly spread.
+fn blue_noise_ranks(n: usize) -> Vec<usize> {
+    let total = n * n;
+    // Gaussian energy kernel on the torus, indexed by wrapped (dx, dy).
+    let sigma2 = 2.0 * 1.5f32 * 1.5;
+    let wrap = |d: usize| d.min(n - d) as f32;
+    let kernel: Vec<f32> = (0..total)
+        .map(|i| {
+            let (dx, dy) = (wrap(i % n), wrap(i / n));
+            (-(dx * dx + dy * dy) / sigma2).exp()
+        })
+        .collect();
+    let at = |a: usize, b: usize| {
+        let dx = (a % n + n - b % n) % n;
+        let dy = (a / n + n - b / n) % n;
+        kernel[dy * n + dx]
+    };
+    let update = |energy: &mut [f32], p: usize, sign: f32| {
+        for (i, e) in energy.iter_mut().enumerate() {
+            *e += sign * at(i, p);
+        }
+    };
+    // Tightest cluster among set points, largest void among empty ones (first index on ties,
+    // so the result is deterministic).
+    let pick = |energy: &[f32], set: &[bool], want: bool, max: bool| {
+        (0..total)
+            .filter(|&i| set[i] == want)
+            .fold(None, |best: Option<usize>, i| match best {
+                Some(b) if (max && energy[i] <= energy[b]) || (!max && energy[i] >= energy[b]) => {
+                    Some(b)
+                }
+                _ => Some(i),
+            })
+            .expect("non-empty candidate set")
+    };
+
+    // Initial pattern: a tenth of the points from a fixed LCG, then relaxed until stable.
+    let mut set = vec![false; total];
+    let mut energy = vec![0.0; total];
+    let mut state: u32 = 0x1234_5678;
+    let initial = (total / 10).max(1);
+    let mut placed = 0;
+    while placed < initial {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        let p = (state >> 8) as usize % total;
+        if !set[p] {
+            set[p] = true;
+            update(&mut energy, p, 1.0);
+            placed += 1;
+        }
+    }
+    loop {
+        let cluster = pick(&energy, &set, true, true);
+        set[cluster] = false;
+        update(&mut energy, cluster, -1.0);
+        let void = pick(&energy, &set, false, false);
+        set[void] = true;
+        update(&mut energy, void, 1.0);
+        if void == cluster {
+            break;
+        }
+    }
+
+    let mut ranks = vec![0; total];
+    // Rank the initial points by removing tightest clusters first.
+    let (mut s, mut e) = (set.clone(), energy.clone());
+    for rank in (0..initial).rev() {
+        let cluster = pick(&e, &s, true, true);
+        s[cluster] = false;
+        update(&mut e, cluster, -1.0);
+        ranks[cluster] = rank;
+    }
+    // Then fill the remaining voids in order.
+    for rank in initial..total {
+        let void = pick(&energy, &set, false, false);
+        set[void] = true;
+        update(&mut energy, void, 1.0);
+        ranks[void] = rank;
+    }
+    ranks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_are_permutations_of_evenly_spaced_thresholds() {
+        for mode in [DitherMode::Ordered, DitherMode::BlueNoise] {
+            let n = mode.map_size();
+            let mut map = mode.threshold_map();
+            assert_eq!(map.len(), n * n);
+            assert_eq!(map, mode.threshold_map(), "{mode:?} is deterministic");
+            map.sort_by(f32::total_cmp);
+            for (i, t) in map.iter().enumerate() {
+                assert_eq!(*t, (i as f32 + 0.5) / (n * n) as f32, "{mode:?}");
+            }
+        }
+        let bayer = DitherMode::Ordered.threshold_map();
+        let rank = |i: usize| (bayer[i] * 64.0) as usize;
+        assert_eq!((0..4).map(rank).collect::<Vec<_>>(), [0, 32, 8, 40]);
+    }
+
+    #[test]
+    fn blue_noise_spreads_every_threshold_band() {
+        let map = DitherMode::BlueNoise.threshold_map();
+        // Every 4x4 tile of a blue-noise map averages close to 0.5, unlike white noise.
+        for ty in 0..8 {
+            for tx in 0..8 {
+                let sum: f32 = (0..16)
+                    .map(|i| map[(ty * 4 + i / 4) * 32 + tx * 4 + i % 4])
+                    .sum();
+                let mean = sum / 16.0;
+                assert!((mean - 0.5).abs() < 0.2, "tile ({tx}, {ty}) mean {mean}");
+            }
+        }
+    }
+}
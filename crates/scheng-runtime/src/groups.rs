@@ -0,0 +1,124 @@
+//! Collective controls for node groups (`Graph::add_to_group`).
+//!
+//! A performer kills or fades a whole section of the patch with one control. Each group has
+//! two parameters in the [`ParamRegistry`]:
+//!
+//! - `group/<name>/bypass` (0 or 1): members are bypassed like `set_node_bypassed` nodes.
+//! - `group/<name>/opacity` (0..=1): scales what members contribute to mixers outside the group.
+//!
+//! Backends resolve both per frame. Bypass applies to every member. Opacity applies where an
+//! edge leaves the group: when a mixer samples an input whose source is in groups the mixer is
+//! not in, that input's gain is multiplied by those groups' opacities. Edges into anything else
+//! are not scaled, so a fade needs a mixer downstream of the group.
+
+use scheng_graph::{Graph, NodeId};
+
+use crate::{ParamMeta, ParamRegistry};
+
+/// One group's controls.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroupControls {
+    pub bypass: bool,
+    pub opacity: f32,
+}
+
+impl Default for GroupControls {
+    fn default() -> Self {
+        Self {
+            bypass: false,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl GroupControls {
+    /// Declare `group`'s parameters (values default to not bypassed, fully opaque).
+    pub fn declare(registry: &mut ParamRegistry, group: &str) {
+        registry.declare(
+            group_param_key(group, "bypass"),
+            ParamMeta::new(0.0, 1.0, 0.0).with_description(format!("bypass group '{group}'")),
+        );
+        registry.declare(
+            group_param_key(group, "opacity"),
+            ParamMeta::new(0.0, 1.0, 1.0)
+                .with_description(format!("opacity of group '{group}' at mixers")),
+        );
+    }
+
+    /// Read `group`'s controls; unset parameters keep their defaults. Bypass is on above 0.5.
+    pub fn from_registry(registry: &ParamRegistry, group: &str) -> Self {
+        Self {
+            bypass: registry.get_or(&group_param_key(group, "bypass"), 0.0) > 0.5,
+            opacity: registry
+                .get_or(&group_param_key(group, "opacity"), 1.0)
+                .clamp(0.0, 1.0),
+        }
+    }
+
+    /// Whether any of `node`'s groups is bypassed.
+    pub fn node_bypassed(registry: &ParamRegistry, graph: &Graph, node: NodeId) -> bool {
+        graph
+            .node_groups(node)
+            .any(|g| Self::from_registry(registry, g).bypass)
+    }
+
+    /// Factor for the input of mixer `consumer` fed by `source`: the product of the opacities
+    /// of `source`'s groups that `consumer` is not in.
+    pub fn input_opacity(
+        registry: &ParamRegistry,
+        graph: &Graph,
+        consumer: NodeId,
+        source: NodeId,
+    ) -> f32 {
+        graph
+            .node_groups(source)
+            .filter(|g| !graph.group_members(g).contains(&consumer))
+            .map(|g| Self::from_registry(registry, g).opacity)
+            .product()
+    }
+}
+
+/// Declare the controls of every group in `graph`.
+pub fn declare_group_params(registry: &mut ParamRegistry, graph: &Graph) {
+    for group in graph.groups() {
+        GroupControls::declare(registry, group);
+    }
+}
+
+/// Registry key for a group control: `group/<name>/<param>`.
+pub fn group_param_key(group: &str, param: &str) -> String {
+    format!("group/{group}/{param}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scheng_graph::NodeKind;
+
+    #[test]
+    fn opacity_applies_where_edges_leave_the_group() {
+        let mut g = Graph::new();
+        let src = g.add_node(NodeKind::ShaderSource);
+        let fx = g.add_node(NodeKind::ShaderPass);
+        let mix = g.add_node(NodeKind::Crossfade);
+        g.add_to_group(src, "drums").unwrap();
+        g.add_to_group(fx, "drums").unwrap();
+        g.add_to_group(fx, "fx").unwrap();
+
+        let mut reg = ParamRegistry::new();
+        declare_group_params(&mut reg, &g);
+        assert_eq!(reg.iter_prefix("group/").count(), 4);
+        assert_eq!(GroupControls::input_opacity(&reg, &g, mix, fx), 1.0);
+
+        reg.set(&group_param_key("drums", "opacity"), 0.5);
+        reg.set(&group_param_key("fx", "opacity"), 0.5);
+        assert_eq!(GroupControls::input_opacity(&reg, &g, mix, fx), 0.25);
+        // Inside the group the edge is not scaled.
+        assert_eq!(GroupControls::input_opacity(&reg, &g, fx, src), 1.0);
+
+        assert!(!GroupControls::node_bypassed(&reg, &g, src));
+        reg.set(&group_param_key("fx", "bypass"), 1.0);
+        assert!(GroupControls::node_bypassed(&reg, &g, fx));
+        assert!(!GroupControls::node_bypassed(&reg, &g, src));
+    }
+}
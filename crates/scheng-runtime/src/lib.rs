@@ -7,8 +7,37 @@
 #![deny(rustdoc::broken_intra_doc_links)]
 #![deny(missing_debug_implementations)]
 
-use scheng_graph::NodeKind;
+use std::collections::BTreeMap;
+
+use scheng_graph::{NodeId, NodeKind};
+pub mod audio;
+pub mod automation;
+pub mod deck;
+pub mod dither;
+pub mod groups;
+pub mod params;
 pub mod runtime_contract;
+pub mod session;
+pub mod shader_params;
+pub mod switching;
+pub mod telemetry;
+pub mod trim;
+
+pub use audio::{spectrum_bands, waveform_columns, AudioVisParams, AudioVisStyle};
+pub use automation::{
+    Automation, AutomationInterpolation, AutomationLane, AutomationMode, AutomationPoint,
+};
+pub use deck::{Deck, DeckMixer, FadeCurve, LayerStack};
+pub use dither::{DitherMode, DitherParams};
+pub use groups::{declare_group_params, group_param_key, GroupControls};
+pub use params::{node_param_key, node_param_key_in, ParamMeta, ParamRegistry};
+#[cfg(feature = "serde")]
+pub use session::Autosave;
+pub use session::{Session, SessionScene, SessionTransport, SESSION_SCHEMA};
+pub use shader_params::{declare_shader_params, scan_shader_params, ShaderParam, ShaderParamScan};
+pub use switching::{Quantizer, Quantum, SceneStep, SceneSwitcher};
+pub use telemetry::Telemetry;
+pub use trim::{trim_param_key, ColorTrim};
 // -------------------------------------------------------------------------------------------------
 // Standard ops
 // -------------------------------------------------------------------------------------------------
@@ -22,6 +51,8 @@ pub enum MixerOp {
     Add,
     /// 2-input multiplicative blend.
     Multiply,
+    /// Chroma/luma key of `b` over `a`, with an optional garbage matte (see [`KeyMixParams`]).
+    KeyMix,
     /// Weighted sum of up to 4 inputs (iChannel0..3).
     ///
     /// This is the minimal "matrix mixer" primitive: higher-level matrix/routing tools can be
@@ -29,45 +60,571 @@ pub enum MixerOp {
     MatrixMix4,
 }
 
+/// Single-input stock looks, so basic treatments don't need a custom shader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FilterOp {
+    /// Mosaic of square blocks (see [`PixelateParams`]).
+    Pixelate,
+    /// Quantize each channel to a few levels (see [`PosterizeParams`]).
+    Posterize,
+    /// Quantize with an ordered or blue-noise threshold map (see [`DitherParams`]).
+    Dither,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum StandardOp {
     Mixer(MixerOp),
+    Filter(FilterOp),
 }
 
 // -------------------------------------------------------------------------------------------------
 // Parameter blocks
 // -------------------------------------------------------------------------------------------------
 
-/// Parameters for 2-input mixers (e.g., Crossfade).
+/// Per-input processing a mixer applies before blending, so simple balancing does not need an
+/// extra ShaderPass per input.
+///
+/// The input is sampled at `(uv - 0.5) * uv_scale + 0.5 + uv_offset`, then
+/// `rgb = rgb * gain + offset` (alpha untouched).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct InputAdjust {
+    pub gain: f32,
+    pub offset: f32,
+    /// UV zoom around the centre (`[2, 2]` shows the middle quarter).
+    pub uv_scale: [f32; 2],
+    /// UV translation, applied after scaling.
+    pub uv_offset: [f32; 2],
+}
+
+impl Default for InputAdjust {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            offset: 0.0,
+            uv_scale: [1.0, 1.0],
+            uv_offset: [0.0, 0.0],
+        }
+    }
+}
+
+impl InputAdjust {
+    /// Gain only.
+    pub fn gain(gain: f32) -> Self {
+        Self {
+            gain,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn with_uv(mut self, scale: [f32; 2], offset: [f32; 2]) -> Self {
+        self.uv_scale = scale;
+        self.uv_offset = offset;
+        self
+    }
+
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// `[gain, offset, 0, 0]` and `[scale.x, scale.y, offset.x, offset.y]`, the layout of the
+    /// built-in mixers' `uInputAdjust[]` / `uInputUv[]` uniforms.
+    pub fn to_uniforms(&self) -> ([f32; 4], [f32; 4]) {
+        (
+            [self.gain, self.offset, 0.0, 0.0],
+            [self.uv_scale[0], self.uv_scale[1], self.uv_offset[0], self.uv_offset[1]],
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+fn all_identity(inputs: &[InputAdjust]) -> bool {
+    inputs.iter().all(InputAdjust::is_identity)
+}
+
+/// Parameters for 2-input mixers (Crossfade, Add, Multiply).
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct MixerParams {
     /// Crossfade amount: 0.0 = A, 1.0 = B.
     pub mix: f32,
+    /// Adjustments for inputs `a` and `b`.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "all_identity"))]
+    pub inputs: [InputAdjust; 2],
 }
 
 impl Default for MixerParams {
     fn default() -> Self {
-        Self { mix: 0.5 }
+        Self::new(0.5)
+    }
+}
+
+impl MixerParams {
+    pub fn new(mix: f32) -> Self {
+        Self {
+            mix,
+            inputs: [InputAdjust::default(); 2],
+        }
+    }
+
+    /// Set the adjustment for input `index` (0 = a, 1 = b); out-of-range indices are ignored.
+    pub fn with_input(mut self, index: usize, adjust: InputAdjust) -> Self {
+        if let Some(slot) = self.inputs.get_mut(index) {
+            *slot = adjust;
+        }
+        self
+    }
+}
+
+/// Parameters for the 2-input Add and Multiply mixers.
+///
+/// Add: `a.rgb * gain_a + b.rgb * gain_b`. Multiply: `(a.rgb * gain_a) * (b.rgb * gain_b)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BlendParams {
+    /// Gain applied to input `a` before blending.
+    pub gain_a: f32,
+    /// Gain applied to input `b` before blending.
+    pub gain_b: f32,
+}
+
+impl Default for BlendParams {
+    fn default() -> Self {
+        Self {
+            gain_a: 1.0,
+            gain_b: 1.0,
+        }
+    }
+}
+
+/// What [`KeyMixParams`] keys on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum KeyMode {
+    /// Distance from `key_color` in chroma (CbCr), so lighting changes on the screen matter less.
+    #[default]
+    Chroma,
+    /// Foreground luma below `threshold` is keyed out.
+    Luma,
+}
+
+/// Parameters for the KeyMix mixer.
+///
+/// Ports: `a` = background, `b` = foreground to key, `matte` = optional garbage matte (its luma;
+/// black removes the foreground regardless of the key). The foreground alpha is
+/// `smoothstep(threshold, threshold + softness, distance) * matte`, then
+/// `out = mix(a, despilled(b), alpha)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct KeyMixParams {
+    pub mode: KeyMode,
+    /// Screen colour for [`KeyMode::Chroma`] (linear RGB, 0..1).
+    pub key_color: [f32; 3],
+    /// Distance (chroma) or luma level below which the foreground is fully transparent.
+    pub threshold: f32,
+    /// Width of the transparent-to-opaque ramp above `threshold`; 0 is a hard edge.
+    pub softness: f32,
+    /// Spill suppression, 0..1: how far the key colour cast is pulled out of the foreground.
+    pub spill: f32,
+    /// Use the garbage matte inverted (white removes).
+    pub invert_matte: bool,
+}
+
+impl Default for KeyMixParams {
+    fn default() -> Self {
+        Self {
+            mode: KeyMode::Chroma,
+            key_color: [0.0, 1.0, 0.0],
+            threshold: 0.15,
+            softness: 0.1,
+            spill: 0.5,
+            invert_matte: false,
+        }
+    }
+}
+
+impl KeyMixParams {
+    /// Chroma key on `key_color` with the default threshold, softness and spill.
+    pub fn chroma(key_color: [f32; 3]) -> Self {
+        Self {
+            key_color,
+            ..Self::default()
+        }
+    }
+
+    /// Luma key: foreground darker than `threshold` is removed.
+    pub fn luma(threshold: f32) -> Self {
+        Self {
+            mode: KeyMode::Luma,
+            threshold,
+            spill: 0.0,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_softness(mut self, softness: f32) -> Self {
+        self.softness = softness;
+        self
+    }
+
+    pub fn with_spill(mut self, spill: f32) -> Self {
+        self.spill = spill;
+        self
+    }
+
+    pub fn with_invert_matte(mut self, invert: bool) -> Self {
+        self.invert_matte = invert;
+        self
     }
 }
 
 /// Parameters for MatrixMix4.
 ///
-/// Output = Σ texture(iChannelN) * weights[N]
+/// Output = Σ texture(iChannelN) * w[N], where `w` is [`MatrixMixParams::effective_weights`].
+/// The defaults are a plain additive mixer: weights used as given, summing above 1 brightens.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct MatrixMixParams {
     pub weights: [f32; 4],
+    /// Divide the weights by their sum (a true weighted average). No-op when the sum is ~0.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_false"))]
+    pub normalize: bool,
+    /// Clamp the mixed colour to `0..1` (matters for float targets and negative weights).
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_false"))]
+    pub clamp_output: bool,
+    /// Keep negative weights (subtractive mixing); when false they are treated as 0.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_true"))]
+    pub allow_negative: bool,
+    /// Adjustments for inputs 0..3, applied before weighting.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "all_identity"))]
+    pub inputs: [InputAdjust; 4],
+}
+
+// Options at their defaults are left out of JSON so existing documents keep their shape.
+#[cfg(feature = "serde")]
+fn is_false(b: &bool) -> bool {
+    !*b
+}
+
+#[cfg(feature = "serde")]
+fn is_true(b: &bool) -> bool {
+    *b
 }
 
 impl Default for MatrixMixParams {
+    fn default() -> Self {
+        // default passthrough channel 0
+        Self::new([1.0, 0.0, 0.0, 0.0])
+    }
+}
+
+impl MatrixMixParams {
+    /// Additive mixing with `weights` as given.
+    pub fn new(weights: [f32; 4]) -> Self {
+        Self {
+            weights,
+            normalize: false,
+            clamp_output: false,
+            allow_negative: true,
+            inputs: [InputAdjust::default(); 4],
+        }
+    }
+
+    /// Set the adjustment for input `index` (0..3); out-of-range indices are ignored.
+    pub fn with_input(mut self, index: usize, adjust: InputAdjust) -> Self {
+        if let Some(slot) = self.inputs.get_mut(index) {
+            *slot = adjust;
+        }
+        self
+    }
+
+    pub fn with_normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    pub fn with_clamp_output(mut self, clamp: bool) -> Self {
+        self.clamp_output = clamp;
+        self
+    }
+
+    pub fn with_allow_negative(mut self, allow: bool) -> Self {
+        self.allow_negative = allow;
+        self
+    }
+
+    /// The weights the built-in shader applies (it does the same math on the GPU).
+    pub fn effective_weights(&self) -> [f32; 4] {
+        let mut w = self.weights;
+        if !self.allow_negative {
+            w = w.map(|x| x.max(0.0));
+        }
+        if self.normalize {
+            let sum: f32 = w.iter().sum();
+            if sum.abs() > 1e-6 {
+                w = w.map(|x| x / sum);
+            }
+        }
+        w
+    }
+}
+
+/// Parameters for `NodeKind::MotionDetect` (frame-difference analysis).
+///
+/// Results are published into the runtime's [`ParamRegistry`] under the node's key prefix:
+/// `node/<id>/energy` (whole frame) and `node/<id>/cell/<i>` (row-major grid cells).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MotionParams {
+    /// Grid resolution for per-cell energy (1x1 = whole frame only).
+    pub grid_cols: u32,
+    pub grid_rows: u32,
+    /// Per-pixel luma differences below this are treated as noise (0..1).
+    pub threshold: f32,
+    /// Multiplier applied to the difference before thresholding.
+    pub gain: f32,
+}
+
+impl Default for MotionParams {
     fn default() -> Self {
         Self {
-            // default passthrough channel 0
-            weights: [1.0, 0.0, 0.0, 0.0],
+            grid_cols: 1,
+            grid_rows: 1,
+            threshold: 0.05,
+            gain: 1.0,
         }
     }
 }
 
+/// Parameters for `NodeKind::Particles`.
+///
+/// Positions and velocities are in UV space (0..1, y up), per second. Every particle lives for
+/// `lifetime` seconds and then respawns at the emitter, with spawns staggered so
+/// `count / lifetime` particles are emitted per second.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ParticleParams {
+    /// Number of particles (rounded up to a square state texture; clamped to 1..=1_048_576).
+    pub count: u32,
+    pub lifetime: f32,
+    /// Emitter centre (UV).
+    pub emitter: [f32; 2],
+    /// Particles spawn uniformly within this radius of `emitter` (UV).
+    pub emitter_radius: f32,
+    /// Launch direction in radians (0 = +x, PI/2 = up).
+    pub direction: f32,
+    /// Full angle of the launch cone in radians (TAU = all directions).
+    pub spread: f32,
+    /// Launch speed (UV per second); each particle gets 50–100% of it.
+    pub speed: f32,
+    /// Constant acceleration (UV per second squared).
+    pub gravity: [f32; 2],
+    /// Velocity damping per second (0 = none).
+    pub drag: f32,
+    /// Strength of a time-varying curl-like force field.
+    pub turbulence: f32,
+    /// Point size in pixels.
+    pub size: f32,
+    /// Particle colour (straight alpha); particles are blended additively.
+    pub color: [f32; 4],
+    /// Fade each particle out over its lifetime.
+    pub fade: bool,
+}
+
+impl Default for ParticleParams {
+    fn default() -> Self {
+        Self {
+            count: 16_384,
+            lifetime: 3.0,
+            emitter: [0.5, 0.2],
+            emitter_radius: 0.02,
+            direction: std::f32::consts::FRAC_PI_2,
+            spread: 0.6,
+            speed: 0.5,
+            gravity: [0.0, -0.2],
+            drag: 0.2,
+            turbulence: 0.1,
+            size: 2.0,
+            color: [1.0, 0.8, 0.5, 0.6],
+            fade: true,
+        }
+    }
+}
+
+impl ParticleParams {
+    /// Side of the square state texture holding `count` particles.
+    pub fn state_side(&self) -> u32 {
+        let n = self.count.clamp(1, 1 << 20);
+        let mut side = (n as f64).sqrt() as u32;
+        while side * side < n {
+            side += 1;
+        }
+        side
+    }
+}
+
+/// Flow textures store `0.5 + flow_uv * FLOW_ENCODING_SCALE` in R/G, so the representable range
+/// is ±`0.5 / FLOW_ENCODING_SCALE` of the frame per axis. Producers and consumers must agree.
+pub const FLOW_ENCODING_SCALE: f32 = 4.0;
+
+/// Parameters for `NodeKind::OpticalFlow` (two-frame gradient flow estimate).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FlowParams {
+    /// Half-size of the integration window in pixels (window is `2r+1` square). Clamped to 1..=4.
+    pub window_radius: u32,
+    /// Regularisation added to the structure tensor; higher = smoother, smaller vectors.
+    pub lambda: f32,
+    /// Multiplier applied to the estimated flow before encoding.
+    pub gain: f32,
+}
+
+impl Default for FlowParams {
+    fn default() -> Self {
+        Self {
+            window_radius: 2,
+            lambda: 0.001,
+            gain: 1.0,
+        }
+    }
+}
+
+/// Parameters for `NodeKind::FlowDisplace` (offset input `a` by the flow texture on `b`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DisplaceParams {
+    /// Displacement multiplier (1.0 = move pixels by the encoded flow; negative reverses).
+    pub amount: f32,
+}
+
+impl Default for DisplaceParams {
+    fn default() -> Self {
+        Self { amount: 1.0 }
+    }
+}
+
+/// Parameters for `NodeKind::Pixelate`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PixelateParams {
+    /// Block side in output pixels (at least 1; 1 passes the input through).
+    pub block_size: f32,
+}
+
+impl Default for PixelateParams {
+    fn default() -> Self {
+        Self { block_size: 8.0 }
+    }
+}
+
+/// Parameters for `NodeKind::Posterize`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PosterizeParams {
+    /// Output levels per channel (at least 2): values snap to `k / (levels - 1)`.
+    pub levels: u32,
+}
+
+impl Default for PosterizeParams {
+    fn default() -> Self {
+        Self { levels: 4 }
+    }
+}
+
+/// Parameters for `NodeKind::Bloom` (bright-pass threshold, mip-chain blur, additive composite).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BloomParams {
+    /// Brightness (largest RGB channel) above which pixels glow; 0 blooms the whole image.
+    pub threshold: f32,
+    /// Width of the soft ramp below `threshold` (0 = hard cut).
+    pub knee: f32,
+    /// Strength of the glow added back over the input.
+    pub intensity: f32,
+    /// Upsample filter spread in texels of each level; larger is softer.
+    pub radius: f32,
+    /// Mip levels in the chain, each half the size of the last (clamped to
+    /// `1..=BloomParams::MAX_LEVELS`). More levels reach further.
+    pub levels: u32,
+}
+
+impl Default for BloomParams {
+    fn default() -> Self {
+        Self {
+            threshold: 0.8,
+            knee: 0.1,
+            intensity: 0.8,
+            radius: 1.0,
+            levels: 6,
+        }
+    }
+}
+
+impl BloomParams {
+    pub const MAX_LEVELS: u32 = 8;
+
+    /// Levels actually used for a `width` x `height` input: `levels`, clamped, and stopping
+    /// before a level would shrink below one pixel.
+    pub fn levels_for(&self, width: u32, height: u32) -> u32 {
+        let fit = 32 - width.min(height).max(1).leading_zeros() - 1;
+        self.levels.clamp(1, Self::MAX_LEVELS).min(fit.max(1))
+    }
+}
+
+/// Temporal accumulation on a named output (`NodeProps::temporal_params`, keyed by the
+/// `PixelsOut` node). Each frame the output becomes `mix(current, history, blend)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TemporalParams {
+    /// Weight of the accumulated history (`0..=0.98`; 0 = off, higher = smoother and slower).
+    pub blend: f32,
+    /// Clamp the history to the current pixel's 3x3 neighbourhood, so moving edges do not
+    /// leave trails.
+    pub clamp_history: bool,
+    /// Per-channel difference between current and (clamped) history above which a pixel drops
+    /// its history for the frame (0 disables).
+    pub reset_threshold: f32,
+}
+
+impl Default for TemporalParams {
+    fn default() -> Self {
+        Self {
+            blend: 0.8,
+            clamp_history: true,
+            reset_threshold: 0.5,
+        }
+    }
+}
+
+impl TemporalParams {
+    /// Highest usable `blend`; above it 8-bit history stops converging.
+    pub const MAX_BLEND: f32 = 0.98;
+}
+
 // -------------------------------------------------------------------------------------------------
 // Presets (C4d)
 // -------------------------------------------------------------------------------------------------
@@ -113,29 +670,31 @@ impl MatrixPreset {
         }
     }
 
+    /// The preset whose weights are closest (Euclidean) to `weights`; ties go to the earlier
+    /// entry of [`ALL`](Self::ALL).
+    pub fn nearest(weights: [f32; 4]) -> MatrixPreset {
+        let dist = |p: MatrixPreset| -> f32 {
+            let w = p.params().weights;
+            (0..4).map(|i| (w[i] - weights[i]).powi(2)).sum()
+        };
+        let mut best = MatrixPreset::ALL[0];
+        for p in MatrixPreset::ALL {
+            if dist(p) < dist(best) {
+                best = p;
+            }
+        }
+        best
+    }
+
     pub fn params(self) -> MatrixMixParams {
         match self {
-            MatrixPreset::Solo0 => MatrixMixParams {
-                weights: [1.0, 0.0, 0.0, 0.0],
-            },
-            MatrixPreset::Solo1 => MatrixMixParams {
-                weights: [0.0, 1.0, 0.0, 0.0],
-            },
-            MatrixPreset::Solo2 => MatrixMixParams {
-                weights: [0.0, 0.0, 1.0, 0.0],
-            },
-            MatrixPreset::Solo3 => MatrixMixParams {
-                weights: [0.0, 0.0, 0.0, 1.0],
-            },
-            MatrixPreset::Quad => MatrixMixParams {
-                weights: [0.25, 0.25, 0.25, 0.25],
-            },
-            MatrixPreset::Sum01 => MatrixMixParams {
-                weights: [0.5, 0.5, 0.0, 0.0],
-            },
-            MatrixPreset::Sum23 => MatrixMixParams {
-                weights: [0.0, 0.0, 0.5, 0.5],
-            },
+            MatrixPreset::Solo0 => MatrixMixParams::new([1.0, 0.0, 0.0, 0.0]),
+            MatrixPreset::Solo1 => MatrixMixParams::new([0.0, 1.0, 0.0, 0.0]),
+            MatrixPreset::Solo2 => MatrixMixParams::new([0.0, 0.0, 1.0, 0.0]),
+            MatrixPreset::Solo3 => MatrixMixParams::new([0.0, 0.0, 0.0, 1.0]),
+            MatrixPreset::Quad => MatrixMixParams::new([0.25, 0.25, 0.25, 0.25]),
+            MatrixPreset::Sum01 => MatrixMixParams::new([0.5, 0.5, 0.0, 0.0]),
+            MatrixPreset::Sum23 => MatrixMixParams::new([0.0, 0.0, 0.5, 0.5]),
         }
     }
 }
@@ -154,7 +713,11 @@ pub fn standard_op_for(kind: NodeKind) -> Option<StandardOp> {
         Crossfade => Some(StandardOp::Mixer(MixerOp::Crossfade)),
         Add => Some(StandardOp::Mixer(MixerOp::Add)),
         Multiply => Some(StandardOp::Mixer(MixerOp::Multiply)),
+        KeyMix => Some(StandardOp::Mixer(MixerOp::KeyMix)),
         MatrixMix4 => Some(StandardOp::Mixer(MixerOp::MatrixMix4)),
+        Pixelate => Some(StandardOp::Filter(FilterOp::Pixelate)),
+        Posterize => Some(StandardOp::Filter(FilterOp::Posterize)),
+        Dither => Some(StandardOp::Filter(FilterOp::Dither)),
         _ => None,
     }
 }
@@ -163,22 +726,195 @@ pub fn standard_op_for(kind: NodeKind) -> Option<StandardOp> {
 // Bank/scene helpers (portable performance data)
 // -------------------------------------------------------------------------------------------------
 
-/// A named scene that selects a preset (can be extended later with keyer params, etc.).
+/// How performance data names a graph node: a raw id, or a stable name set with
+/// `Graph::add_node_named`. Serialized as a JSON number or string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum NodeRef {
+    /// Raw graph node id (`NodeId.0`); changes when the patch is rebuilt.
+    Id(u32),
+    Name(String),
+}
+
+impl NodeRef {
+    /// The node this refers to in `graph`, if it exists.
+    pub fn resolve(&self, graph: &scheng_graph::Graph) -> Option<NodeId> {
+        match self {
+            NodeRef::Id(id) => graph.node(NodeId(*id)).map(|n| n.id),
+            NodeRef::Name(name) => graph.node_by_name(name),
+        }
+    }
+}
+
+impl Default for NodeRef {
+    fn default() -> Self {
+        NodeRef::Id(0)
+    }
+}
+
+impl From<NodeId> for NodeRef {
+    fn from(id: NodeId) -> Self {
+        NodeRef::Id(id.0)
+    }
+}
+
+impl From<&str> for NodeRef {
+    fn from(name: &str) -> Self {
+        NodeRef::Name(name.to_string())
+    }
+}
+
+impl std::fmt::Display for NodeRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeRef::Id(id) => write!(f, "{id}"),
+            NodeRef::Name(name) => write!(f, "'{name}'"),
+        }
+    }
+}
+
+/// Per-node changes a scene applies on top of its matrix routing.
+///
+/// Backends apply all overrides of a scene in one step (see `NodeProps::scene_overlay`
+/// in `scheng-runtime-glow`), so a look never renders half-switched.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeOverride {
+    /// The node, by id or (preferably, so the scene survives a rebuild) by name.
+    pub node: NodeRef,
+    /// Fragment shader file to swap in, relative to the bank file's directory.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub shader: Option<String>,
+    /// Uniform values merged into the node's custom uniforms.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "BTreeMap::is_empty")
+    )]
+    pub uniforms: BTreeMap<String, f32>,
+}
+
+impl NodeOverride {
+    /// The node in `graph` (see [`NodeRef::resolve`]).
+    pub fn node_id(&self, graph: &scheng_graph::Graph) -> Option<NodeId> {
+        self.node.resolve(graph)
+    }
+}
+
+/// A named scene: a matrix routing plus optional per-node shader swaps and uniform values.
+///
+/// `weights`, when set, are the scene's actual matrix weights; `preset` then only labels the
+/// scene (bank JSON that gives weights alone gets the [nearest](MatrixPreset::nearest) preset).
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SceneDef {
     pub name: String,
     pub preset: MatrixPreset,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub weights: Option<[f32; 4]>,
+    /// At most one entry per node.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub overrides: Vec<NodeOverride>,
+}
+
+impl SceneDef {
+    pub fn new(name: impl Into<String>, preset: MatrixPreset) -> Self {
+        Self {
+            name: name.into(),
+            preset,
+            weights: None,
+            overrides: Vec::new(),
+        }
+    }
+
+    /// A user-authored mix, labelled with the nearest built-in preset.
+    pub fn custom(name: impl Into<String>, weights: [f32; 4]) -> Self {
+        Self {
+            name: name.into(),
+            preset: MatrixPreset::nearest(weights),
+            weights: Some(weights),
+            overrides: Vec::new(),
+        }
+    }
+
+    pub fn with_weights(mut self, weights: [f32; 4]) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Swap `node`'s fragment shader for the file at `path` when the scene is applied. `node`
+    /// is a `NodeId` or a node name.
+    pub fn with_shader(mut self, node: impl Into<NodeRef>, path: impl Into<String>) -> Self {
+        self.override_mut(node.into()).shader = Some(path.into());
+        self
+    }
+
+    /// Set a uniform on `node` (a `NodeId` or a node name) when the scene is applied.
+    pub fn with_uniform(
+        mut self,
+        node: impl Into<NodeRef>,
+        name: impl Into<String>,
+        value: f32,
+    ) -> Self {
+        self.override_mut(node.into())
+            .uniforms
+            .insert(name.into(), value);
+        self
+    }
+
+    /// The override written for `node` exactly as given (an id does not match a name).
+    pub fn override_for(&self, node: impl Into<NodeRef>) -> Option<&NodeOverride> {
+        let node = node.into();
+        self.overrides.iter().find(|o| o.node == node)
+    }
+
+    fn override_mut(&mut self, node: NodeRef) -> &mut NodeOverride {
+        let i = match self.overrides.iter().position(|o| o.node == node) {
+            Some(i) => i,
+            None => {
+                self.overrides.push(NodeOverride {
+                    node,
+                    ..NodeOverride::default()
+                });
+                self.overrides.len() - 1
+            }
+        };
+        &mut self.overrides[i]
+    }
+
+    /// The weights this scene applies: explicit `weights`, else the preset's.
+    pub fn params(&self) -> MatrixMixParams {
+        match self.weights {
+            Some(weights) => MatrixMixParams::new(weights),
+            None => self.preset.params(),
+        }
+    }
 }
 
 /// A named bank (collection of scenes).
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BankDef {
     pub name: String,
     pub scenes: Vec<SceneDef>,
 }
 
+/// Schema of bank files ([`BankSet::from_json_path`] / [`BankSet::to_json`]).
+pub const BANKS_SCHEMA: scheng_core::JsonSchema =
+    scheng_core::JsonSchema::new("banks", 1, &[scheng_core::Migration::unchanged(0)]);
+
 /// A collection of banks.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BankSet {
     pub banks: Vec<BankDef>,
 }
@@ -192,30 +928,44 @@ impl BankSet {
                 SceneDef {
                     name: "solo_0".to_string(),
                     preset: MatrixPreset::Solo0,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "solo_1".to_string(),
                     preset: MatrixPreset::Solo1,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "solo_2".to_string(),
                     preset: MatrixPreset::Solo2,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "solo_3".to_string(),
                     preset: MatrixPreset::Solo3,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "quad".to_string(),
                     preset: MatrixPreset::Quad,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "sum01".to_string(),
                     preset: MatrixPreset::Sum01,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "sum23".to_string(),
                     preset: MatrixPreset::Sum23,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
             ],
         };
@@ -226,30 +976,44 @@ impl BankSet {
                 SceneDef {
                     name: "A".to_string(),
                     preset: MatrixPreset::Solo0,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "B".to_string(),
                     preset: MatrixPreset::Solo1,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "C".to_string(),
                     preset: MatrixPreset::Solo2,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "D".to_string(),
                     preset: MatrixPreset::Solo3,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "AB".to_string(),
                     preset: MatrixPreset::Sum01,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "CD".to_string(),
                     preset: MatrixPreset::Sum23,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
                 SceneDef {
                     name: "ALL".to_string(),
                     preset: MatrixPreset::Quad,
+                    weights: None,
+                    overrides: Vec::new(),
                 },
             ],
         };
@@ -266,7 +1030,12 @@ impl BankSet {
         #[derive(serde::Deserialize)]
         struct JsonScene {
             name: String,
-            preset: String,
+            #[serde(default)]
+            preset: Option<String>,
+            #[serde(default)]
+            weights: Option<[f32; 4]>,
+            #[serde(default)]
+            overrides: Vec<NodeOverride>,
         }
         #[derive(serde::Deserialize)]
         struct JsonBank {
@@ -279,9 +1048,8 @@ impl BankSet {
         }
 
         let path = path.as_ref();
-        let bytes = fs::read(path).map_err(|e| format!("read {}: {e}", path.display()))?;
-        let root: JsonRoot =
-            serde_json::from_slice(&bytes).map_err(|e| format!("parse json: {e}"))?;
+        let text = fs::read_to_string(path).map_err(|e| format!("read {}: {e}", path.display()))?;
+        let root: JsonRoot = BANKS_SCHEMA.from_str(&text)?;
 
         if root.banks.is_empty() {
             return Err("json has no banks".to_string());
@@ -294,15 +1062,48 @@ impl BankSet {
             }
             let mut scenes = Vec::new();
             for s in b.scenes {
-                let Some(p) = preset_from_str(&s.preset) else {
-                    return Err(format!(
-                        "unknown preset '{}' in scene '{}'",
-                        s.preset, s.name
-                    ));
+                // `preset` may name a preset or spell out weights ("0.5, 0.5, 0, 0"); an
+                // explicit `weights` array wins over both.
+                let (named, preset_weights) = match s.preset.as_deref() {
+                    Some(p) => match (preset_from_str(p), weights_from_str(p)) {
+                        (None, None) => {
+                            return Err(format!("unknown preset '{}' in scene '{}'", p, s.name))
+                        }
+                        found => found,
+                    },
+                    None => (None, None),
                 };
+                let weights = s.weights.or(preset_weights);
+                if weights.is_some_and(|w| w.iter().any(|x| !x.is_finite())) {
+                    return Err(format!("non-finite weights in scene '{}'", s.name));
+                }
+                let preset = match (named, weights) {
+                    (Some(p), _) => p,
+                    (None, Some(w)) => MatrixPreset::nearest(w),
+                    (None, None) => {
+                        return Err(format!("scene '{}' needs a preset or weights", s.name))
+                    }
+                };
+                let mut seen = std::collections::HashSet::new();
+                for o in &s.overrides {
+                    if !seen.insert(&o.node) {
+                        return Err(format!(
+                            "duplicate override for node {} in scene '{}'",
+                            o.node, s.name
+                        ));
+                    }
+                    if let Some((k, _)) = o.uniforms.iter().find(|(_, v)| !v.is_finite()) {
+                        return Err(format!(
+                            "non-finite uniform '{}' on node {} in scene '{}'",
+                            k, o.node, s.name
+                        ));
+                    }
+                }
                 scenes.push(SceneDef {
                     name: s.name,
-                    preset: p,
+                    preset,
+                    weights,
+                    overrides: s.overrides,
                 });
             }
             banks.push(BankDef {
@@ -317,6 +1118,165 @@ impl BankSet {
 
         Ok(BankSet { banks })
     }
+
+    /// Pretty JSON in the format [`from_json_path`](Self::from_json_path) reads, stamped with
+    /// the current [`BANKS_SCHEMA`] version.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        BANKS_SCHEMA
+            .to_string_pretty(self)
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+
+    /// Save to `path`. Writes a sibling temp file first and renames it over the target, so a
+    /// crash mid-save never leaves a truncated bank file.
+    #[cfg(feature = "serde")]
+    pub fn to_json_path(&self, path: impl AsRef<std::path::Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let tmp = std::path::PathBuf::from(tmp);
+        std::fs::write(&tmp, self.to_json())
+            .map_err(|e| format!("write {}: {e}", tmp.display()))?;
+        std::fs::rename(&tmp, path).map_err(|e| format!("rename to {}: {e}", path.display()))
+    }
+
+    // ---- In-place editing ----
+    //
+    // Indices are 0-based. Banks left without scenes are kept in memory but skipped by
+    // `from_json_path` on the next load.
+
+    pub fn bank_index(&self, name: &str) -> Option<usize> {
+        self.banks.iter().position(|b| b.name == name)
+    }
+
+    /// Append an empty bank and return its index.
+    pub fn add_bank(&mut self, name: impl Into<String>) -> usize {
+        self.banks.push(BankDef {
+            name: name.into(),
+            scenes: Vec::new(),
+        });
+        self.banks.len() - 1
+    }
+
+    pub fn rename_bank(&mut self, bank: usize, name: impl Into<String>) -> Result<(), String> {
+        self.bank_mut(bank)?.name = name.into();
+        Ok(())
+    }
+
+    /// Remove a bank. The last bank cannot be removed (a `BankSet` is never empty).
+    pub fn remove_bank(&mut self, bank: usize) -> Result<BankDef, String> {
+        self.bank_mut(bank)?;
+        if self.banks.len() == 1 {
+            return Err("cannot remove the last bank".to_string());
+        }
+        Ok(self.banks.remove(bank))
+    }
+
+    /// Move a bank so it ends up at index `to`.
+    pub fn move_bank(&mut self, from: usize, to: usize) -> Result<(), String> {
+        move_item(&mut self.banks, from, to).map_err(|i| format!("bank index {i} out of range"))
+    }
+
+    /// Append a scene and return its index within the bank.
+    pub fn add_scene(&mut self, bank: usize, scene: SceneDef) -> Result<usize, String> {
+        let b = self.bank_mut(bank)?;
+        b.scenes.push(scene);
+        Ok(b.scenes.len() - 1)
+    }
+
+    pub fn rename_scene(
+        &mut self,
+        bank: usize,
+        scene: usize,
+        name: impl Into<String>,
+    ) -> Result<(), String> {
+        self.scene_mut(bank, scene)?.name = name.into();
+        Ok(())
+    }
+
+    pub fn set_scene_preset(
+        &mut self,
+        bank: usize,
+        scene: usize,
+        preset: MatrixPreset,
+    ) -> Result<(), String> {
+        self.scene_mut(bank, scene)?.preset = preset;
+        Ok(())
+    }
+
+    pub fn remove_scene(&mut self, bank: usize, scene: usize) -> Result<SceneDef, String> {
+        self.scene_mut(bank, scene)?;
+        Ok(self.banks[bank].scenes.remove(scene))
+    }
+
+    /// Move a scene within its bank so it ends up at index `to`.
+    pub fn move_scene(&mut self, bank: usize, from: usize, to: usize) -> Result<(), String> {
+        let b = self.bank_mut(bank)?;
+        move_item(&mut b.scenes, from, to).map_err(|i| format!("scene index {i} out of range"))
+    }
+
+    fn bank_mut(&mut self, bank: usize) -> Result<&mut BankDef, String> {
+        self.banks
+            .get_mut(bank)
+            .ok_or_else(|| format!("bank index {bank} out of range"))
+    }
+
+    fn scene_mut(&mut self, bank: usize, scene: usize) -> Result<&mut SceneDef, String> {
+        self.bank_mut(bank)?
+            .scenes
+            .get_mut(scene)
+            .ok_or_else(|| format!("scene index {scene} out of range in bank {bank}"))
+    }
+}
+
+/// Remove at `from` and reinsert at `to`. Returns the offending index on error.
+fn move_item<T>(items: &mut Vec<T>, from: usize, to: usize) -> Result<(), usize> {
+    for i in [from, to] {
+        if i >= items.len() {
+            return Err(i);
+        }
+    }
+    let item = items.remove(from);
+    items.insert(to, item);
+    Ok(())
+}
+
+/// Presets serialize as their [`name`](MatrixPreset::name) and accept the same aliases as
+/// [`preset_from_str`], so scene JSON written by hand and by `serde_json` look the same.
+#[cfg(feature = "serde")]
+impl serde::Serialize for MatrixPreset {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_str(self.name())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MatrixPreset {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(d)?;
+        preset_from_str(&s)
+            .ok_or_else(|| serde::de::Error::custom(format_args!("unknown preset '{s}'")))
+    }
+}
+
+/// Parse four weights written as `"0.5, 0.5, 0, 0"` (commas and/or spaces, optional `[...]`).
+///
+/// Bank JSON falls back to this when a scene's `preset` string is not a preset name.
+pub fn weights_from_str(s: &str) -> Option<[f32; 4]> {
+    let s = s.trim();
+    let s = s
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .unwrap_or(s);
+    let mut out = [0.0; 4];
+    let mut parts = s
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|p| !p.is_empty());
+    for w in &mut out {
+        *w = parts.next()?.parse::<f32>().ok().filter(|v| v.is_finite())?;
+    }
+    parts.next().is_none().then_some(out)
 }
 
 /// Convert user-facing strings to a known preset name.
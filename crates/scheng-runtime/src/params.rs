@@ -0,0 +1,155 @@
+//! Parameter registry: a flat, backend-agnostic store of named scalar parameters.
+//!
+//! The registry is the meeting point between control surfaces (OSC/MIDI/UI), analysis nodes that
+//! publish measurements, and whatever the host routes into uniforms. Names are plain strings with
+//! a `/`-separated convention (see [`node_param_key`]); iteration order is sorted by name so
+//! snapshots and generated layouts are deterministic.
+
+use std::collections::BTreeMap;
+
+use scheng_graph::NodeId;
+
+/// Optional metadata describing a parameter's range and purpose.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParamMeta {
+    pub min: f32,
+    pub max: f32,
+    pub default: f32,
+    pub description: String,
+}
+
+impl ParamMeta {
+    pub fn new(min: f32, max: f32, default: f32) -> Self {
+        Self {
+            min,
+            max,
+            default,
+            description: String::new(),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Clamp `v` into `[min, max]` (tolerates reversed bounds).
+    pub fn clamp(&self, v: f32) -> f32 {
+        let (lo, hi) = if self.min <= self.max {
+            (self.min, self.max)
+        } else {
+            (self.max, self.min)
+        };
+        v.clamp(lo, hi)
+    }
+}
+
+/// Named scalar parameters with optional metadata.
+#[derive(Debug, Clone, Default)]
+pub struct ParamRegistry {
+    values: BTreeMap<String, f32>,
+    meta: BTreeMap<String, ParamMeta>,
+    revision: u64,
+}
+
+impl ParamRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a parameter with metadata. The value is initialized to `meta.default` if unset.
+    pub fn declare(&mut self, name: impl Into<String>, meta: ParamMeta) {
+        let name = name.into();
+        if !self.values.contains_key(&name) {
+            self.values.insert(name.clone(), meta.default);
+            self.revision += 1;
+        }
+        self.meta.insert(name, meta);
+    }
+
+    /// Set a parameter value. Declared parameters are clamped to their range.
+    ///
+    /// Returns the value actually stored.
+    pub fn set(&mut self, name: &str, value: f32) -> f32 {
+        let v = match self.meta.get(name) {
+            Some(m) => m.clamp(value),
+            None => value,
+        };
+        match self.values.get_mut(name) {
+            Some(slot) => {
+                if *slot != v {
+                    *slot = v;
+                    self.revision += 1;
+                }
+            }
+            None => {
+                self.values.insert(name.to_string(), v);
+                self.revision += 1;
+            }
+        }
+        v
+    }
+
+    pub fn get(&self, name: &str) -> Option<f32> {
+        self.values.get(name).copied()
+    }
+
+    /// Value, or `fallback` if the parameter is unset.
+    pub fn get_or(&self, name: &str, fallback: f32) -> f32 {
+        self.get(name).unwrap_or(fallback)
+    }
+
+    pub fn meta(&self, name: &str) -> Option<&ParamMeta> {
+        self.meta.get(name)
+    }
+
+    /// Remove a parameter (value and metadata). Returns the last value.
+    pub fn remove(&mut self, name: &str) -> Option<f32> {
+        self.meta.remove(name);
+        let v = self.values.remove(name);
+        if v.is_some() {
+            self.revision += 1;
+        }
+        v
+    }
+
+    /// All `(name, value)` pairs, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f32)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), *v))
+    }
+
+    /// Parameters whose name starts with `prefix`, sorted by name.
+    pub fn iter_prefix<'a>(&'a self, prefix: &'a str) -> impl Iterator<Item = (&'a str, f32)> + 'a {
+        self.values
+            .range(prefix.to_string()..)
+            .take_while(move |(k, _)| k.starts_with(prefix))
+            .map(|(k, v)| (k.as_str(), *v))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Monotonic counter bumped whenever a value is added, changed or removed.
+    ///
+    /// Cheap change detection for monitors and autosave.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+}
+
+/// Canonical registry key for a per-node parameter: `node/<id>/<param>`.
+pub fn node_param_key(node: NodeId, param: &str) -> String {
+    format!("node/{}/{}", node.0, param)
+}
+
+/// Registry key for a per-node parameter addressed by the node's handle:
+/// `node/<name>/<param>` for named nodes (see `Graph::add_node_named`), so OSC mappings survive a
+/// rebuild, else the same key as [`node_param_key`].
+pub fn node_param_key_in(graph: &scheng_graph::Graph, node: NodeId, param: &str) -> String {
+    format!("node/{}/{}", graph.handle(node), param)
+}
@@ -29,16 +29,125 @@ pub fn input_channel_for(kind: NodeKind, port_name: &str) -> Option<u32> {
     }
 }
 
-/// Maps a uniform name to a stable location index.
+/// GLSL type of a standard uniform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UniformType {
+    Float,
+    Vec2,
+    Vec3,
+}
+
+/// The per-frame value a standard uniform carries. Several names can share one value
+/// (`uTime`, `u_time` and `iTime` are all [`StandardUniform::Time`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StandardUniform {
+    /// Seconds on the host timeline (`FrameCtx::time`).
+    Time,
+    /// Output size in pixels; the `vec3` form has `z = 1.0` (Shadertoy pixel aspect).
+    Resolution,
+    /// Seconds since the previous frame (`FrameCtx::dt`).
+    DeltaTime,
+    /// Host output width / height (`FrameCtx::aspect`).
+    Aspect,
+    /// Per-run seed mapped to `[0, 1)` (`FrameCtx::seed`).
+    Seed,
+    /// Per-node seed in `[0, 1)`: [`node_seed`] of the node's handle and the run seed. It does
+    /// not change when the node's shader is reloaded.
+    NodeSeed,
+    /// How often the node's shader source has changed since the runtime first built it.
+    ReloadCount,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StandardUniformDef {
+    pub name: &'static str,
+    pub value: StandardUniform,
+    pub ty: UniformType,
+}
+
+const fn def(name: &'static str, value: StandardUniform, ty: UniformType) -> StandardUniformDef {
+    StandardUniformDef { name, value, ty }
+}
+
+/// Uniforms every backend sets on every shader pass that declares them.
+///
+/// Contract:
+/// - a shader opts in by declaring the uniform with exactly this name and type
+/// - adding entries is additive; renaming, retyping or removing one is breaking
+pub const STANDARD_UNIFORMS: &[StandardUniformDef] = &[
+    def("uTime", StandardUniform::Time, UniformType::Float),
+    def("u_time", StandardUniform::Time, UniformType::Float),
+    def("iTime", StandardUniform::Time, UniformType::Float),
+    def("uResolution", StandardUniform::Resolution, UniformType::Vec2),
+    def("u_resolution", StandardUniform::Resolution, UniformType::Vec2),
+    def("iResolution", StandardUniform::Resolution, UniformType::Vec3),
+    def("uDeltaTime", StandardUniform::DeltaTime, UniformType::Float),
+    def("iTimeDelta", StandardUniform::DeltaTime, UniformType::Float),
+    def("uAspect", StandardUniform::Aspect, UniformType::Float),
+    def("uSeed", StandardUniform::Seed, UniformType::Float),
+    def("uNodeSeed", StandardUniform::NodeSeed, UniformType::Float),
+    def("uReloadCount", StandardUniform::ReloadCount, UniformType::Float),
+];
+
+/// The standard uniform called `name`, if any.
+pub fn standard_uniform(name: &str) -> Option<&'static StandardUniformDef> {
+    STANDARD_UNIFORMS.iter().find(|d| d.name == name)
+}
+
+/// Name of the standard uniform block. A shader that declares [`FRAME_BLOCK_GLSL`] reads the
+/// per-frame values from one buffer the backend uploads once per frame (per distinct size), instead
+/// of the backend setting each standard uniform on each program.
+pub const FRAME_BLOCK_NAME: &str = "SchengFrame";
+
+/// Uniform buffer binding point the backend binds the frame block to.
+pub const FRAME_BLOCK_BINDING: u32 = 0;
+
+/// Size in bytes of the frame block (std140, [`FRAME_BLOCK_GLSL`]).
+pub const FRAME_BLOCK_SIZE: usize = 32;
+
+/// The standard block declaration, to paste into a fragment shader (GLSL 3.30 / ES 3.00). Values
+/// match the standard uniforms of the same meaning; `frame` is `FrameCtx::frame` as a float.
+///
+/// Contract:
+/// - the layout is std140 and its offsets are fixed: `resolution` 0, `time` 8, `delta_time` 12,
+///   `aspect` 16, `seed` 20, `frame` 24
+/// - members are only ever added at the end
+pub const FRAME_BLOCK_GLSL: &str = "layout(std140) uniform SchengFrame {
+    vec2 resolution;
+    float time;
+    float delta_time;
+    float aspect;
+    float seed;
+    float frame;
+} scheng;
+";
+
+/// Seed of the node with `handle` (`Graph::handle`) in a run seeded with `seed`.
+///
+/// Contract:
+/// - depends only on the handle and the run seed, so it is stable across shader reloads and
+///   graph rebuilds that keep the node's name
+/// - the hash is fixed; changing it reseeds every saved patch
+pub fn node_seed(handle: &str, seed: u32) -> u32 {
+    // FNV-1a over the handle, then a splitmix64 finalizer with the run seed.
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for b in handle.bytes() {
+        h = (h ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    let mut x = h ^ ((seed as u64) << 32 | seed as u64);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (x ^ (x >> 31)) as u32
+}
+
+/// True for names with a reserved meaning: the standard uniforms plus the names reserved for
+/// host input (`uMouse`, `uParam0..3`).
 ///
 /// Contract:
-/// - backends should bind these uniforms consistently (by name or by location if supported)
 /// - adding new uniforms is additive; changing/removing is breaking
 pub fn uniform_name_is_known(name: &str) -> bool {
-    matches!(
-        name,
-        "uTime" | "uResolution" | "uMouse" | "uSeed" | "uParam0" | "uParam1" | "uParam2" | "uParam3"
-    )
+    standard_uniform(name).is_some()
+        || matches!(name, "uMouse" | "uParam0" | "uParam1" | "uParam2" | "uParam3")
 }
 
 use std::collections::{HashMap, HashSet};
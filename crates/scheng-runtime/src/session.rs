@@ -0,0 +1,308 @@
+//! Session state that survives a restart: parameters, the active scene and the transport.
+//!
+//! A show tweaks parameters live; without a session file those tweaks vanish on exit. The host
+//! builds a [`Session`] from its live state and hands it to an [`Autosave`], which writes it
+//! every `interval` (skipping unchanged state) and once more on exit via
+//! [`Autosave::save_now`]. Writes go to a sibling temp file that is synced and renamed over the
+//! session, so a crash mid-save leaves the previous session intact. Restoring is opt-in
+//! ([`Autosave::with_restore`]): [`Autosave::restore`] returns nothing unless enabled.
+
+use std::collections::BTreeMap;
+#[cfg(feature = "serde")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "serde")]
+use std::time::{Duration, Instant};
+
+use crate::ParamRegistry;
+
+/// Schema of session files ([`Session::to_json`] / [`Session::load`]).
+pub const SESSION_SCHEMA: scheng_core::JsonSchema =
+    scheng_core::JsonSchema::new("session", 1, &[scheng_core::Migration::unchanged(0)]);
+
+/// Bank and scene indices of the last recalled scene.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionScene {
+    pub bank: usize,
+    pub scene: usize,
+}
+
+/// Transport state at save time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SessionTransport {
+    pub playing: bool,
+    pub speed: f32,
+    /// Normalized position 0..1.
+    pub position: f32,
+}
+
+impl Default for SessionTransport {
+    fn default() -> Self {
+        Self {
+            playing: true,
+            speed: 1.0,
+            position: 0.0,
+        }
+    }
+}
+
+/// The live state worth keeping across restarts.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Session {
+    /// Parameter values by name (see [`ParamRegistry`]).
+    pub params: BTreeMap<String, f32>,
+    pub active_scene: Option<SessionScene>,
+    pub transport: SessionTransport,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy every parameter value from a registry.
+    pub fn with_params(mut self, params: &ParamRegistry) -> Self {
+        self.params = params
+            .iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        self
+    }
+
+    pub fn with_active_scene(mut self, bank: usize, scene: usize) -> Self {
+        self.active_scene = Some(SessionScene { bank, scene });
+        self
+    }
+
+    pub fn with_transport(mut self, transport: SessionTransport) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Set every saved parameter on `params` (declared ones are clamped to their current
+    /// range). Non-finite values are skipped. Returns how many were applied.
+    pub fn apply_params(&self, params: &mut ParamRegistry) -> usize {
+        self.params
+            .iter()
+            .filter(|(_, v)| v.is_finite())
+            .map(|(name, v)| params.set(name, *v))
+            .count()
+    }
+
+    /// Pretty JSON stamped with the current [`SESSION_SCHEMA`] version.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        SESSION_SCHEMA
+            .to_string_pretty(self)
+            .unwrap_or_else(|_| "{}".to_string())
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        SESSION_SCHEMA.from_str(text)
+    }
+
+    /// Read a session file; `Ok(None)` when there is none yet.
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>, String> {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_json(&text)
+                .map(Some)
+                .map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("read {}: {e}", path.display())),
+        }
+    }
+
+    /// Save atomically to `path` (see the module docs).
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        write_atomic(path.as_ref(), &self.to_json())
+    }
+}
+
+/// Write `text` to a synced sibling temp file, then rename it over `path`.
+#[cfg(feature = "serde")]
+fn write_atomic(path: &Path, text: &str) -> Result<(), String> {
+    use std::io::Write;
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let write = || -> std::io::Result<()> {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(text.as_bytes())?;
+        file.sync_all()
+    };
+    write().map_err(|e| format!("write {}: {e}", tmp.display()))?;
+    std::fs::rename(&tmp, path).map_err(|e| format!("rename to {}: {e}", path.display()))
+}
+
+/// Periodic autosave of a [`Session`] to one file.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone)]
+pub struct Autosave {
+    path: PathBuf,
+    interval: Duration,
+    restore: bool,
+    last_save: Option<Instant>,
+    /// JSON of the last successful write; identical state is not rewritten.
+    last_written: Option<String>,
+}
+
+#[cfg(feature = "serde")]
+impl Autosave {
+    /// Autosave to `path` every 10 seconds, without restore-on-start.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            interval: Duration::from_secs(10),
+            restore: false,
+            last_save: None,
+            last_written: None,
+        }
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Opt in to [`restore`](Self::restore) returning the saved session.
+    pub fn with_restore(mut self, restore: bool) -> Self {
+        self.restore = restore;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// The session to restore on start: `Ok(None)` when restore is off or nothing was saved.
+    pub fn restore(&mut self) -> Result<Option<Session>, String> {
+        if !self.restore {
+            return Ok(None);
+        }
+        let session = Session::load(&self.path)?;
+        // The file already holds this state; don't rewrite it on the first tick.
+        self.last_written = session.as_ref().map(Session::to_json);
+        Ok(session)
+    }
+
+    /// Call once per frame. When `interval` has passed since the last save, builds the session
+    /// with `session` and writes it if it changed. Returns whether the file was written.
+    pub fn tick(
+        &mut self,
+        now: Instant,
+        session: impl FnOnce() -> Session,
+    ) -> Result<bool, String> {
+        match self.last_save {
+            Some(last) if now.saturating_duration_since(last) < self.interval => Ok(false),
+            Some(_) => {
+                self.last_save = Some(now);
+                self.write(&session())
+            }
+            // The first tick starts the clock; the state at startup is not worth a write.
+            None => {
+                self.last_save = Some(now);
+                Ok(false)
+            }
+        }
+    }
+
+    /// Save now if the state changed (e.g. on exit). Returns whether the file was written.
+    pub fn save_now(&mut self, session: &Session) -> Result<bool, String> {
+        self.last_save = Some(Instant::now());
+        self.write(session)
+    }
+
+    fn write(&mut self, session: &Session) -> Result<bool, String> {
+        let json = session.to_json();
+        if self.last_written.as_ref() == Some(&json) {
+            return Ok(false);
+        }
+        write_atomic(&self.path, &json)?;
+        self.last_written = Some(json);
+        Ok(true)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use crate::ParamMeta;
+
+    #[test]
+    fn autosave_writes_changes_atomically_and_restores_on_opt_in() {
+        let dir = std::env::temp_dir().join(format!("scheng-session-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("session.json");
+        let _ = std::fs::remove_file(&path);
+
+        let mut params = ParamRegistry::new();
+        params.declare("mix", ParamMeta::new(0.0, 1.0, 0.5));
+        params.set("mix", 0.75);
+        let live = |params: &ParamRegistry| {
+            Session::new()
+                .with_params(params)
+                .with_active_scene(1, 2)
+                .with_transport(SessionTransport {
+                    playing: false,
+                    speed: 2.0,
+                    position: 0.25,
+                })
+        };
+
+        let t0 = Instant::now();
+        let mut autosave = Autosave::new(&path).with_interval(Duration::from_secs(5));
+        assert!(
+            !autosave.tick(t0, || live(&params)).unwrap(),
+            "first tick only starts the clock"
+        );
+        assert!(!autosave
+            .tick(t0 + Duration::from_secs(1), || unreachable!())
+            .unwrap());
+        assert!(autosave
+            .tick(t0 + Duration::from_secs(5), || live(&params))
+            .unwrap());
+        assert!(
+            !autosave
+                .tick(t0 + Duration::from_secs(10), || live(&params))
+                .unwrap(),
+            "unchanged state is not rewritten"
+        );
+        params.set("mix", 0.1);
+        assert!(autosave.save_now(&live(&params)).unwrap());
+        let mut tmp = path.clone().into_os_string();
+        tmp.push(".tmp");
+        assert!(!Path::new(&tmp).exists());
+
+        // Restore is opt-in.
+        assert_eq!(Autosave::new(&path).restore().unwrap(), None);
+        let mut next = Autosave::new(&path).with_restore(true);
+        let session = next.restore().unwrap().expect("saved session");
+        assert_eq!(session, live(&params));
+        assert!(
+            !next.save_now(&session).unwrap(),
+            "restored state is already on disk"
+        );
+
+        let mut fresh = ParamRegistry::new();
+        fresh.declare("mix", ParamMeta::new(0.0, 0.05, 0.0));
+        assert_eq!(session.apply_params(&mut fresh), 1);
+        assert_eq!(fresh.get("mix"), Some(0.05), "clamped to the current range");
+
+        std::fs::write(&path, "{\"schema_version\": 9}").unwrap();
+        assert!(next.restore().unwrap_err().contains("newer"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,191 @@
+//! Parameters declared in shader source.
+//!
+//! A shader author annotates a uniform in a comment or pragma:
+//!
+//! ```glsl
+//! // @param u_decay range(0.9, 1.0) default(0.995) "Trail decay per frame"
+//! #pragma param u_gain range(0, 4) default(1)
+//! uniform float u_decay;
+//! uniform float u_gain;
+//! ```
+//!
+//! [`scan_shader_params`] extracts these as [`ParamMeta`]; a backend declares them in its
+//! [`ParamRegistry`] under `node/<handle>/<uniform>` ([`declare_shader_params`]) whenever a
+//! node's source changes, so OSC/MIDI mappings and generated UIs see new controls on hot reload.
+//! `range` defaults to `0..1` and `default` to the bottom of the range; the quoted description
+//! is optional. Malformed annotations are reported and skipped, never fatal.
+
+use scheng_graph::{Graph, NodeId};
+
+use crate::{node_param_key_in, ParamMeta, ParamRegistry};
+
+/// One `@param` annotation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderParam {
+    /// The uniform the value is bound to.
+    pub uniform: String,
+    pub meta: ParamMeta,
+    /// 1-based source line of the annotation.
+    pub line: usize,
+}
+
+/// Result of [`scan_shader_params`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShaderParamScan {
+    /// Annotations in source order (the first one wins for a repeated uniform).
+    pub params: Vec<ShaderParam>,
+    /// `line N: ...` messages for annotations that were skipped.
+    pub errors: Vec<String>,
+}
+
+/// Find every `// @param` and `#pragma param` annotation in `source`.
+pub fn scan_shader_params(source: &str) -> ShaderParamScan {
+    let mut scan = ShaderParamScan::default();
+    for (i, line) in source.lines().enumerate() {
+        let Some(rest) = annotation(line) else {
+            continue;
+        };
+        let line = i + 1;
+        match parse_param(rest, line) {
+            Ok(p) if scan.params.iter().any(|q| q.uniform == p.uniform) => scan
+                .errors
+                .push(format!("line {line}: {} is already declared", p.uniform)),
+            Ok(p) => scan.params.push(p),
+            Err(e) => scan.errors.push(format!("line {line}: {e}")),
+        }
+    }
+    scan
+}
+
+/// Declare `params` for `node` in `registry` (keys from [`node_param_key_in`]) and return
+/// `(uniform, registry key)` pairs. A value that already exists is kept, clamped to the new range.
+pub fn declare_shader_params(
+    registry: &mut ParamRegistry,
+    graph: &Graph,
+    node: NodeId,
+    params: &[ShaderParam],
+) -> Vec<(String, String)> {
+    params
+        .iter()
+        .map(|p| {
+            let key = node_param_key_in(graph, node, &p.uniform);
+            let current = registry.get(&key);
+            registry.declare(key.clone(), p.meta.clone());
+            if let Some(v) = current {
+                registry.set(&key, v);
+            }
+            (p.uniform.clone(), key)
+        })
+        .collect()
+}
+
+/// The text after `@param` / `#pragma param`, if `line` is an annotation.
+fn annotation(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if let Some(pragma) = line.strip_prefix("#pragma") {
+        return pragma.trim_start().strip_prefix("param ");
+    }
+    let comment = &line[line.find("//")? + 2..];
+    comment.trim_start().strip_prefix("@param ")
+}
+
+fn parse_param(text: &str, line: usize) -> Result<ShaderParam, String> {
+    let text = text.trim();
+    let end = text
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(text.len());
+    let (uniform, mut rest) = text.split_at(end);
+    if uniform.is_empty() || uniform.starts_with(|c: char| c.is_ascii_digit()) {
+        return Err(format!("expected a uniform name, found `{text}`"));
+    }
+
+    let (mut range, mut default, mut description) = ((0.0, 1.0), None, String::new());
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let close = quoted.find('"').ok_or("unterminated description")?;
+            description = quoted[..close].to_string();
+            rest = &quoted[close + 1..];
+            continue;
+        }
+        let (open, close) = match (rest.find('('), rest.find(')')) {
+            (Some(open), Some(close)) if open < close => (open, close),
+            _ => return Err(format!("expected `name(...)`, found `{rest}`")),
+        };
+        let (clause, args) = (rest[..open].trim(), &rest[open + 1..close]);
+        let values: Option<Vec<f32>> = args
+            .split(',')
+            .map(|a| a.trim().parse::<f32>().ok().filter(|v| v.is_finite()))
+            .collect();
+        match (clause, values.as_deref()) {
+            ("range", Some(&[lo, hi])) => range = (lo, hi),
+            ("default", Some(&[v])) => default = Some(v),
+            ("range" | "default", _) => return Err(format!("bad arguments in `{clause}({args})`")),
+            _ => return Err(format!("unknown clause `{clause}`")),
+        }
+        rest = &rest[close + 1..];
+    }
+
+    let meta = ParamMeta::new(range.0, range.1, range.0);
+    let meta = ParamMeta {
+        default: meta.clamp(default.unwrap_or(range.0)),
+        ..meta
+    }
+    .with_description(description);
+    Ok(ShaderParam {
+        uniform: uniform.to_string(),
+        meta,
+        line,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scans_comment_and_pragma_annotations() {
+        let src = "#version 330 core\n\
+            // @param u_decay range(0.9, 1.0) default(0.995) \"Trail decay\"\n\
+            #pragma param u_gain range(0,4)\n\
+            uniform float u_decay; // not an annotation\n\
+            // @param u_mix\n\
+            // @param u_bad range(1)\n\
+            // @param u_gain default(2)\n\
+            // @param u_odd speed(3)\n";
+        let scan = scan_shader_params(src);
+        let names: Vec<_> = scan.params.iter().map(|p| p.uniform.as_str()).collect();
+        assert_eq!(names, ["u_decay", "u_gain", "u_mix"]);
+        let decay = &scan.params[0].meta;
+        assert_eq!((decay.min, decay.max, decay.default), (0.9, 1.0, 0.995));
+        assert_eq!(decay.description, "Trail decay");
+        assert_eq!(scan.params[1].meta, ParamMeta::new(0.0, 4.0, 0.0));
+        assert_eq!(scan.params[2].meta, ParamMeta::new(0.0, 1.0, 0.0));
+        assert_eq!(scan.params[2].line, 5);
+        assert_eq!(scan.errors.len(), 3, "{:?}", scan.errors);
+        assert!(scan.errors[0].starts_with("line 6: bad arguments"));
+        assert!(scan.errors[1].contains("already declared"));
+        assert!(scan.errors[2].contains("unknown clause `speed`"));
+    }
+
+    #[test]
+    fn redeclaring_keeps_values_within_the_new_range() {
+        let mut g = Graph::new();
+        let node = g.add_node(scheng_graph::NodeKind::ShaderPass);
+        let mut registry = ParamRegistry::new();
+        let first = scan_shader_params("// @param u_gain range(0, 4) default(1)");
+        let keys = declare_shader_params(&mut registry, &g, node, &first.params);
+        let key = node_param_key_in(&g, node, "u_gain");
+        assert_eq!(keys, [("u_gain".to_string(), key.clone())]);
+        assert_eq!(registry.get(&key), Some(1.0));
+
+        registry.set(&key, 3.0);
+        let reloaded = scan_shader_params("// @param u_gain range(0, 2) default(1)");
+        declare_shader_params(&mut registry, &g, node, &reloaded.params);
+        assert_eq!(registry.get(&key), Some(2.0));
+        assert_eq!(registry.meta(&key).unwrap().max, 2.0);
+    }
+}
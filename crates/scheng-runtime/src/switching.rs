@@ -0,0 +1,255 @@
+//! Quantized scene switching: a time- or beat-based quantizer, a scene queue, and the armed/apply
+//! state machine that pops the queue on boundaries.
+//!
+//! Everything is driven by an explicit timeline time in seconds (the host's `FrameCtx::time`,
+//! a sync client, or a beat clock), so the same inputs always switch on the same frame.
+
+use std::collections::VecDeque;
+
+use crate::{BankSet, SceneDef};
+
+/// The length of one switching slot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Quantum {
+    /// A fixed period in seconds.
+    Seconds(f64),
+    /// Every `beats` beats at `bpm` (e.g. `beats: 4.0` is one bar of 4/4).
+    Beats { bpm: f64, beats: f64 },
+}
+
+impl Quantum {
+    /// Slot length in seconds (never below 1 ms).
+    pub fn period_secs(&self) -> f64 {
+        let p = match *self {
+            Quantum::Seconds(s) => s,
+            Quantum::Beats { bpm, beats } => 60.0 / bpm * beats,
+        };
+        if p.is_finite() {
+            p.max(0.001)
+        } else {
+            0.001
+        }
+    }
+}
+
+/// Splits the timeline into slots and reports slot boundaries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantizer {
+    quantum: Quantum,
+    origin: f64,
+    last_slot: Option<u64>,
+}
+
+impl Quantizer {
+    pub fn new(quantum: Quantum) -> Self {
+        Self {
+            quantum,
+            origin: 0.0,
+            last_slot: None,
+        }
+    }
+
+    /// Timeline time of slot 0 (e.g. the downbeat of the first bar).
+    pub fn with_origin(mut self, origin: f64) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn quantum(&self) -> Quantum {
+        self.quantum
+    }
+
+    /// Change the quantum, re-anchoring slots at `time` so the next boundary is one new period
+    /// away (a tempo change never fires a spurious boundary).
+    pub fn set_quantum(&mut self, quantum: Quantum, time: f64) {
+        self.quantum = quantum;
+        self.origin = time;
+        self.last_slot = Some(0);
+    }
+
+    /// Slot index containing `time` (0 before the origin).
+    pub fn slot(&self, time: f64) -> u64 {
+        let t = (time - self.origin) / self.quantum.period_secs();
+        if t > 0.0 {
+            t.floor() as u64
+        } else {
+            0
+        }
+    }
+
+    /// Timeline time of the first boundary after `time`.
+    pub fn next_boundary(&self, time: f64) -> f64 {
+        let period = self.quantum.period_secs();
+        if time < self.origin {
+            return self.origin;
+        }
+        self.origin + (self.slot(time) + 1) as f64 * period
+    }
+
+    /// True when `time` is in a later slot than the previous call. The first call only primes
+    /// the quantizer and returns false.
+    pub fn poll(&mut self, time: f64) -> bool {
+        let slot = self.slot(time);
+        let crossed = self.last_slot.is_some_and(|last| slot > last);
+        // Seeking backwards re-primes instead of firing.
+        self.last_slot = Some(slot);
+        crossed
+    }
+}
+
+/// A queued reference to `banks.banks[bank_idx].scenes[scene_idx]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SceneStep {
+    pub bank_idx: usize,
+    pub scene_idx: usize,
+}
+
+impl SceneStep {
+    pub fn new(bank_idx: usize, scene_idx: usize) -> Self {
+        Self {
+            bank_idx,
+            scene_idx,
+        }
+    }
+
+    /// The scene this step points at, if the indices are still valid.
+    pub fn resolve<'a>(&self, banks: &'a BankSet) -> Option<&'a SceneDef> {
+        banks.banks.get(self.bank_idx)?.scenes.get(self.scene_idx)
+    }
+}
+
+/// Scene queue plus the armed/apply state machine.
+///
+/// Steps are enqueued at any time; while armed, each quantizer boundary pops the next step,
+/// provided the host reports it is idle (not still transitioning to the previous scene). Boundaries
+/// are consumed whether or not anything is popped, so a step never fires mid-slot.
+#[derive(Debug, Clone)]
+pub struct SceneSwitcher<T = SceneStep> {
+    quantizer: Quantizer,
+    queue: VecDeque<T>,
+    armed: bool,
+}
+
+impl<T> SceneSwitcher<T> {
+    pub fn new(quantizer: Quantizer) -> Self {
+        Self {
+            quantizer,
+            queue: VecDeque::new(),
+            armed: false,
+        }
+    }
+
+    pub fn quantizer(&self) -> &Quantizer {
+        &self.quantizer
+    }
+
+    pub fn quantizer_mut(&mut self) -> &mut Quantizer {
+        &mut self.quantizer
+    }
+
+    pub fn is_armed(&self) -> bool {
+        self.armed
+    }
+
+    pub fn set_armed(&mut self, armed: bool) {
+        self.armed = armed;
+    }
+
+    pub fn enqueue(&mut self, step: T) {
+        self.queue.push_back(step);
+    }
+
+    pub fn clear(&mut self) {
+        self.queue.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Queued steps, next first.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.queue.iter()
+    }
+
+    /// Call once per frame. Returns the step to apply now, if any.
+    pub fn poll(&mut self, time: f64, idle: bool) -> Option<T> {
+        if self.quantizer.poll(time) && self.armed && idle {
+            self.queue.pop_front()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beats_quantum_and_boundaries() {
+        let q = Quantum::Beats {
+            bpm: 120.0,
+            beats: 4.0,
+        };
+        assert_eq!(q.period_secs(), 2.0);
+        assert_eq!(Quantum::Seconds(0.0).period_secs(), 0.001);
+
+        let mut quant = Quantizer::new(q).with_origin(1.0);
+        assert_eq!(quant.slot(0.5), 0);
+        assert_eq!(quant.slot(3.5), 1);
+        assert_eq!(quant.next_boundary(0.0), 1.0);
+        assert_eq!(quant.next_boundary(3.5), 5.0);
+
+        assert!(!quant.poll(0.0), "first poll primes");
+        assert!(!quant.poll(2.9));
+        assert!(quant.poll(3.0));
+        assert!(!quant.poll(4.0));
+        // Backwards seek does not fire; moving on from there does.
+        assert!(!quant.poll(1.5));
+        assert!(quant.poll(3.1));
+    }
+
+    #[test]
+    fn set_quantum_reanchors() {
+        let mut quant = Quantizer::new(Quantum::Seconds(1.0));
+        quant.poll(0.0);
+        quant.poll(2.5);
+        quant.set_quantum(Quantum::Seconds(0.5), 2.7);
+        assert!(!quant.poll(3.0));
+        assert!(quant.poll(3.2));
+    }
+
+    #[test]
+    fn switcher_pops_on_armed_idle_boundaries_only() {
+        let mut sw = SceneSwitcher::new(Quantizer::new(Quantum::Seconds(0.5)));
+        sw.enqueue(SceneStep::new(0, 1));
+        sw.enqueue(SceneStep::new(0, 2));
+        sw.poll(0.0, true);
+
+        // Not armed: the boundary passes.
+        assert_eq!(sw.poll(0.5, true), None);
+        sw.set_armed(true);
+        assert_eq!(sw.poll(0.7, true), None, "mid-slot");
+        // Busy host: boundary consumed, nothing popped, and it does not fire late.
+        assert_eq!(sw.poll(1.0, false), None);
+        assert_eq!(sw.poll(1.2, true), None);
+        assert_eq!(sw.poll(1.5, true), Some(SceneStep::new(0, 1)));
+        assert_eq!(sw.poll(2.0, true), Some(SceneStep::new(0, 2)));
+        assert!(sw.is_empty());
+        assert_eq!(sw.poll(2.5, true), None);
+    }
+
+    #[test]
+    fn steps_resolve_against_banks() {
+        let banks = BankSet::builtin_matrix_banks();
+        let first = &banks.banks[0].scenes[0];
+        assert_eq!(SceneStep::new(0, 0).resolve(&banks), Some(first));
+        assert_eq!(SceneStep::new(0, 99).resolve(&banks), None);
+        assert_eq!(SceneStep::new(99, 0).resolve(&banks), None);
+    }
+}
@@ -0,0 +1,313 @@
+//! Engine telemetry: counters, gauges and frame-time percentiles for unattended installations.
+//!
+//! The host owns one [`Telemetry`], calls [`record_frame`](Telemetry::record_frame) once per
+//! rendered frame and bumps counters for the events it sees (decoder restarts, OSC messages,
+//! ...). Everything can be read back directly (the pull API) or rendered in the Prometheus text
+//! format with [`to_prometheus`](Telemetry::to_prometheus), which `scheng-control-http` serves
+//! at `/metrics`.
+//!
+//! Time is the sum of recorded frame times, not the wall clock, so the same frames always give
+//! the same numbers. Every counter also gets a per-second rate, refreshed about once a second
+//! and exported as a gauge named after the counter with `_total` replaced by `_per_second`.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Frames recorded (`record_frame` calls).
+pub const FRAMES: &str = "scheng_frames_total";
+/// Frames that took longer than 1.5x the target interval, counted in missed intervals.
+pub const DROPPED_FRAMES: &str = "scheng_dropped_frames_total";
+/// Video / capture decoders the host had to restart.
+pub const DECODER_RESTARTS: &str = "scheng_decoder_restarts_total";
+/// OSC messages received.
+pub const OSC_MESSAGES: &str = "scheng_osc_messages_total";
+/// Frames per second over the frame-time window.
+pub const FPS: &str = "scheng_fps";
+/// Frame time summary (quantiles over the window, plus lifetime sum and count).
+pub const FRAME_TIME: &str = "scheng_frame_time_seconds";
+
+/// Quantiles exported for [`FRAME_TIME`].
+const QUANTILES: [f64; 4] = [0.5, 0.9, 0.99, 1.0];
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Rate {
+    /// Counter value and clock at the start of the current interval.
+    since_value: u64,
+    since: f64,
+    per_second: f64,
+}
+
+/// Counters, gauges and a frame-time window (see the module docs).
+#[derive(Debug, Clone)]
+pub struct Telemetry {
+    counters: BTreeMap<String, u64>,
+    gauges: BTreeMap<String, f64>,
+    help: BTreeMap<String, String>,
+    rates: BTreeMap<String, Rate>,
+    frame_times: VecDeque<f64>,
+    window: usize,
+    frame_time_sum: f64,
+    target_frame_time: Option<f64>,
+    /// Seconds of recorded frame time.
+    clock: f64,
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Telemetry {
+    /// Frame-time percentiles over the last 240 frames.
+    pub fn new() -> Self {
+        let mut t = Self {
+            counters: BTreeMap::new(),
+            gauges: BTreeMap::new(),
+            help: BTreeMap::new(),
+            rates: BTreeMap::new(),
+            frame_times: VecDeque::new(),
+            window: 240,
+            frame_time_sum: 0.0,
+            target_frame_time: None,
+            clock: 0.0,
+        };
+        for (name, help) in [
+            (FRAMES, "Frames rendered."),
+            (
+                DROPPED_FRAMES,
+                "Frame intervals missed against the target rate.",
+            ),
+            (DECODER_RESTARTS, "Video or capture decoders restarted."),
+            (OSC_MESSAGES, "OSC messages received."),
+        ] {
+            t.describe(name, help);
+            t.counters.insert(name.to_string(), 0);
+        }
+        t.describe(FPS, "Frames per second over the frame-time window.");
+        t.describe(FRAME_TIME, "Frame time in seconds.");
+        t
+    }
+
+    /// Keep the last `frames` frame times for percentiles and fps (at least one).
+    pub fn with_window(mut self, frames: usize) -> Self {
+        self.window = frames.max(1);
+        while self.frame_times.len() > self.window {
+            self.frame_times.pop_front();
+        }
+        self
+    }
+
+    /// Count [`DROPPED_FRAMES`] against this rate (`None` disables drop detection).
+    pub fn set_target_fps(&mut self, fps: Option<f32>) {
+        self.target_frame_time = fps.filter(|f| *f > 0.0).map(|f| 1.0 / f as f64);
+    }
+
+    /// `# HELP` text for a metric.
+    pub fn describe(&mut self, name: impl Into<String>, help: impl Into<String>) {
+        self.help.insert(name.into(), help.into());
+    }
+
+    /// Record one frame that took `frame_time`.
+    pub fn record_frame(&mut self, frame_time: Duration) {
+        let dt = frame_time.as_secs_f64();
+        self.clock += dt;
+        self.frame_time_sum += dt;
+        self.frame_times.push_back(dt);
+        if self.frame_times.len() > self.window {
+            self.frame_times.pop_front();
+        }
+        self.inc(FRAMES);
+
+        if let Some(target) = self.target_frame_time {
+            if dt > target * 1.5 {
+                let missed = ((dt / target).round() as u64).saturating_sub(1).max(1);
+                self.add(DROPPED_FRAMES, missed);
+            }
+        }
+
+        let window_secs: f64 = self.frame_times.iter().sum();
+        let fps = if window_secs > 0.0 {
+            self.frame_times.len() as f64 / window_secs
+        } else {
+            0.0
+        };
+        self.gauges.insert(FPS.to_string(), fps);
+        self.update_rates();
+    }
+
+    pub fn inc(&mut self, name: &str) {
+        self.add(name, 1);
+    }
+
+    /// Add `n` to a counter, creating it at zero first.
+    pub fn add(&mut self, name: &str, n: u64) {
+        match self.counters.get_mut(name) {
+            Some(v) => *v += n,
+            None => {
+                self.counters.insert(name.to_string(), n);
+            }
+        }
+    }
+
+    pub fn counter(&self, name: &str) -> u64 {
+        self.counters.get(name).copied().unwrap_or(0)
+    }
+
+    pub fn set_gauge(&mut self, name: &str, value: f64) {
+        match self.gauges.get_mut(name) {
+            Some(v) => *v = value,
+            None => {
+                self.gauges.insert(name.to_string(), value);
+            }
+        }
+    }
+
+    pub fn gauge(&self, name: &str) -> Option<f64> {
+        self.gauges.get(name).copied()
+    }
+
+    /// Frames per second over the window (0 before the first frame).
+    pub fn fps(&self) -> f64 {
+        self.gauge(FPS).unwrap_or(0.0)
+    }
+
+    /// Per-second rate of a counter over the last completed interval of about one second.
+    pub fn rate(&self, counter: &str) -> f64 {
+        self.rates.get(counter).map_or(0.0, |r| r.per_second)
+    }
+
+    /// Frame time in seconds at quantile `q` (`0.5` = median, `1.0` = max) over the window.
+    pub fn frame_time_percentile(&self, q: f64) -> Option<f64> {
+        let mut sorted: Vec<f64> = self.frame_times.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        quantile(&sorted, q)
+    }
+
+    /// All metrics in the Prometheus text exposition format (version 0.0.4).
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        for (name, value) in &self.counters {
+            self.header(&mut out, name, "counter");
+            let _ = writeln!(out, "{name} {value}");
+        }
+        for (name, rate) in &self.rates {
+            let rate_name = rate_name(name);
+            let _ = writeln!(out, "# TYPE {rate_name} gauge");
+            let _ = writeln!(out, "{rate_name} {}", fmt_value(rate.per_second));
+        }
+        for (name, value) in &self.gauges {
+            self.header(&mut out, name, "gauge");
+            let _ = writeln!(out, "{name} {}", fmt_value(*value));
+        }
+
+        self.header(&mut out, FRAME_TIME, "summary");
+        let mut sorted: Vec<f64> = self.frame_times.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        for q in QUANTILES {
+            if let Some(v) = quantile(&sorted, q) {
+                let _ = writeln!(out, "{FRAME_TIME}{{quantile=\"{q}\"}} {}", fmt_value(v));
+            }
+        }
+        let _ = writeln!(out, "{FRAME_TIME}_sum {}", fmt_value(self.frame_time_sum));
+        let _ = writeln!(out, "{FRAME_TIME}_count {}", self.counter(FRAMES));
+        out
+    }
+
+    fn header(&self, out: &mut String, name: &str, kind: &str) {
+        if let Some(help) = self.help.get(name) {
+            let _ = writeln!(out, "# HELP {name} {}", help.replace('\n', " "));
+        }
+        let _ = writeln!(out, "# TYPE {name} {kind}");
+    }
+
+    fn update_rates(&mut self) {
+        for (name, value) in &self.counters {
+            let rate = self.rates.entry(name.clone()).or_insert(Rate {
+                since_value: *value,
+                since: self.clock,
+                per_second: 0.0,
+            });
+            let elapsed = self.clock - rate.since;
+            if elapsed >= 1.0 {
+                rate.per_second = value.saturating_sub(rate.since_value) as f64 / elapsed;
+                rate.since_value = *value;
+                rate.since = self.clock;
+            }
+        }
+    }
+}
+
+fn rate_name(counter: &str) -> String {
+    format!(
+        "{}_per_second",
+        counter.strip_suffix("_total").unwrap_or(counter)
+    )
+}
+
+/// Nearest-rank quantile of an ascending slice.
+fn quantile(sorted: &[f64], q: f64) -> Option<f64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (q.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+fn fmt_value(v: f64) -> String {
+    if v.is_nan() {
+        "NaN".into()
+    } else if v.is_infinite() {
+        if v > 0.0 { "+Inf" } else { "-Inf" }.into()
+    } else {
+        v.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_drops_and_percentiles() {
+        let mut t = Telemetry::new().with_window(4);
+        t.set_target_fps(Some(50.0));
+        for ms in [20, 20, 20, 60] {
+            t.record_frame(Duration::from_millis(ms));
+        }
+        assert_eq!(t.counter(FRAMES), 4);
+        // 60 ms at a 20 ms target misses two intervals.
+        assert_eq!(t.counter(DROPPED_FRAMES), 2);
+        assert_eq!(t.frame_time_percentile(0.5), Some(0.02));
+        assert_eq!(t.frame_time_percentile(1.0), Some(0.06));
+        assert!((t.fps() - 4.0 / 0.12).abs() < 1e-9);
+    }
+
+    #[test]
+    fn counter_rates_follow_recorded_time() {
+        let mut t = Telemetry::new();
+        for _ in 0..20 {
+            t.add(OSC_MESSAGES, 5);
+            t.record_frame(Duration::from_millis(100));
+        }
+        // 5 messages per 100 ms frame.
+        assert!((t.rate(OSC_MESSAGES) - 50.0).abs() < 1e-6);
+        assert!((t.rate(FRAMES) - 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn prometheus_text_format() {
+        let mut t = Telemetry::new();
+        t.inc(DECODER_RESTARTS);
+        t.set_gauge("scheng_temperature_celsius", f64::INFINITY);
+        t.record_frame(Duration::from_millis(16));
+        let text = t.to_prometheus();
+        assert!(text.contains("# TYPE scheng_decoder_restarts_total counter\n"));
+        assert!(text.contains("scheng_decoder_restarts_total 1\n"));
+        assert!(text.contains("# TYPE scheng_osc_messages_per_second gauge\n"));
+        assert!(text.contains("scheng_temperature_celsius +Inf\n"));
+        assert!(text.contains("scheng_frame_time_seconds{quantile=\"0.99\"} 0.016\n"));
+        assert!(text.contains("scheng_frame_time_seconds_count 1\n"));
+    }
+}
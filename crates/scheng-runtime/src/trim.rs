@@ -0,0 +1,136 @@
+//! Per-destination colour trim: brightness, contrast and gamma per channel.
+//!
+//! Each projector or display gets its own [`ColorTrim`], applied by the backend in a final pass
+//! before the sink sees the frame (`scheng_runtime_glow::TrimSink`). Trims live in the
+//! [`ParamRegistry`] under `sink/<name>/trim/<param>_<r|g|b>` so control surfaces can calibrate
+//! outputs live.
+
+use crate::{ParamMeta, ParamRegistry};
+
+/// Channel suffixes used in registry keys.
+const CHANNELS: [&str; 3] = ["r", "g", "b"];
+
+/// Per-channel output trim. Applied as
+/// `pow(clamp((c - 0.5) * contrast + 0.5 + brightness, 0, 1), 1 / gamma)`; alpha is untouched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorTrim {
+    /// Offset added after contrast (`-1..=1`, default 0).
+    pub brightness: [f32; 3],
+    /// Scale around mid-grey (`0..=4`, default 1).
+    pub contrast: [f32; 3],
+    /// Output gamma (`0.1..=4`, default 1; above 1 brightens midtones).
+    pub gamma: [f32; 3],
+}
+
+impl Default for ColorTrim {
+    fn default() -> Self {
+        Self {
+            brightness: [0.0; 3],
+            contrast: [1.0; 3],
+            gamma: [1.0; 3],
+        }
+    }
+}
+
+impl ColorTrim {
+    /// Registry parameters: `(name, min, max, default)`, in field order.
+    const PARAMS: [(&'static str, f32, f32, f32); 3] = [
+        ("brightness", -1.0, 1.0, 0.0),
+        ("contrast", 0.0, 4.0, 1.0),
+        ("gamma", 0.1, 4.0, 1.0),
+    ];
+
+    /// True when the trim leaves every value unchanged (backends skip the pass).
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Trim one RGB value in 0..=1 code values (the reference for backend shaders).
+    pub fn apply(&self, rgb: [f32; 3]) -> [f32; 3] {
+        std::array::from_fn(|i| {
+            let c = (rgb[i] - 0.5) * self.contrast[i] + 0.5 + self.brightness[i];
+            c.clamp(0.0, 1.0).powf(1.0 / self.gamma[i].max(0.1))
+        })
+    }
+
+    fn fields_mut(&mut self) -> [&mut [f32; 3]; 3] {
+        [&mut self.brightness, &mut self.contrast, &mut self.gamma]
+    }
+
+    /// Declare the trim parameters for sink `name` (values default to identity).
+    pub fn declare(registry: &mut ParamRegistry, name: &str) {
+        for (param, min, max, default) in Self::PARAMS {
+            for ch in CHANNELS {
+                registry.declare(
+                    trim_param_key(name, param, ch),
+                    ParamMeta::new(min, max, default)
+                        .with_description(format!("{param} trim, {ch} channel, sink '{name}'")),
+                );
+            }
+        }
+    }
+
+    /// Read sink `name`'s trim; unset parameters keep their identity value.
+    pub fn from_registry(registry: &ParamRegistry, name: &str) -> Self {
+        let mut trim = Self::default();
+        for ((param, ..), field) in Self::PARAMS.into_iter().zip(trim.fields_mut()) {
+            for (i, ch) in CHANNELS.into_iter().enumerate() {
+                field[i] = registry.get_or(&trim_param_key(name, param, ch), field[i]);
+            }
+        }
+        trim
+    }
+
+    /// Write this trim into the registry for sink `name`.
+    pub fn store(&self, registry: &mut ParamRegistry, name: &str) {
+        let fields = [&self.brightness, &self.contrast, &self.gamma];
+        for ((param, ..), field) in Self::PARAMS.into_iter().zip(fields) {
+            for (i, ch) in CHANNELS.into_iter().enumerate() {
+                registry.set(&trim_param_key(name, param, ch), field[i]);
+            }
+        }
+    }
+}
+
+/// Registry key for one trim component: `sink/<name>/trim/<param>_<channel>`.
+pub fn trim_param_key(sink: &str, param: &str, channel: &str) -> String {
+    format!("sink/{sink}/trim/{param}_{channel}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_passes_values_through() {
+        let trim = ColorTrim::default();
+        assert!(trim.is_identity());
+        assert_eq!(trim.apply([0.0, 0.25, 1.0]), [0.0, 0.25, 1.0]);
+
+        let trim = ColorTrim {
+            brightness: [0.1, 0.0, 0.0],
+            contrast: [1.0, 2.0, 1.0],
+            gamma: [1.0, 1.0, 2.0],
+        };
+        let [r, g, b] = trim.apply([0.5, 0.75, 0.25]);
+        assert!((r - 0.6).abs() < 1e-6 && (g - 1.0).abs() < 1e-6 && (b - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn registry_roundtrip_clamps_to_declared_ranges() {
+        let mut reg = ParamRegistry::new();
+        ColorTrim::declare(&mut reg, "projector_left");
+        assert_eq!(reg.iter_prefix("sink/projector_left/trim/").count(), 9);
+        assert!(ColorTrim::from_registry(&reg, "projector_left").is_identity());
+
+        let trim = ColorTrim {
+            gamma: [1.2, 1.0, 9.0],
+            ..ColorTrim::default()
+        };
+        trim.store(&mut reg, "projector_left");
+        assert_eq!(reg.get("sink/projector_left/trim/gamma_r"), Some(1.2));
+        let back = ColorTrim::from_registry(&reg, "projector_left");
+        assert_eq!(back.gamma, [1.2, 1.0, 4.0]);
+    }
+}
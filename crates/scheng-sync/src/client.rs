@@ -0,0 +1,319 @@
+//! Client side: follows a master timeline by slewing a local clock.
+
+use std::collections::VecDeque;
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use scheng_runtime_glow::FrameCtx;
+
+use crate::packet::{SyncPacket, PACKET_LEN};
+
+/// Tuning for [`SyncClient`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncConfig {
+    /// Added to the master time to compensate one-way network delay (usually well under 1 ms on
+    /// a LAN, so 0 is fine).
+    pub latency: Duration,
+    /// Errors beyond this jump instead of slewing (seeks, first lock).
+    pub snap_threshold_secs: f64,
+    /// How fast the error is closed, per second.
+    pub gain: f64,
+    /// Largest correction in seconds per second (0.1 = play up to 10% fast/slow).
+    pub max_slew: f64,
+    /// Without packets for this long the client free-runs and reports unlocked.
+    pub timeout: Duration,
+    /// Packets kept for the jitter filter.
+    pub window: usize,
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            latency: Duration::ZERO,
+            snap_threshold_secs: 0.25,
+            gain: 4.0,
+            max_slew: 0.1,
+            timeout: Duration::from_secs(1),
+            window: 16,
+        }
+    }
+}
+
+/// Non-blocking sync receiver; drive it once per frame with [`poll`](Self::poll) and
+/// [`tick`](Self::tick) (or [`apply`](Self::apply)).
+#[derive(Debug)]
+pub struct SyncClient {
+    sock: UdpSocket,
+    cfg: SyncConfig,
+    epoch: Instant,
+    master: Option<u32>,
+    last: Option<SyncPacket>,
+    last_rx: Option<Instant>,
+    /// `master time - local time` at receipt, newest last. Delayed packets only ever make this
+    /// smaller, so the window maximum is the least-delayed estimate.
+    offsets: VecDeque<f64>,
+    fps: f64,
+    time: f64,
+    last_tick: Option<Instant>,
+}
+
+impl SyncClient {
+    /// Listen on an address like "0.0.0.0:7410".
+    pub fn bind(addr: &str) -> io::Result<Self> {
+        let sock = UdpSocket::bind(addr)?;
+        sock.set_nonblocking(true)?;
+        Ok(Self {
+            sock,
+            cfg: SyncConfig::default(),
+            epoch: Instant::now(),
+            master: None,
+            last: None,
+            last_rx: None,
+            offsets: VecDeque::new(),
+            fps: 60.0,
+            time: 0.0,
+            last_tick: None,
+        })
+    }
+
+    pub fn with_config(mut self, cfg: SyncConfig) -> Self {
+        self.cfg = cfg;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.sock.local_addr()
+    }
+
+    /// Id of the master being followed.
+    pub fn master_id(&self) -> Option<u32> {
+        self.master
+    }
+
+    /// True while packets from the master keep arriving.
+    pub fn is_locked(&self, now: Instant) -> bool {
+        self.last_rx
+            .is_some_and(|t| now.saturating_duration_since(t) <= self.cfg.timeout)
+    }
+
+    /// Whether the master is playing (false before the first packet).
+    pub fn is_playing(&self) -> bool {
+        self.last.is_some_and(|p| p.playing)
+    }
+
+    /// Drain the socket. Returns the number of packets accepted.
+    pub fn poll(&mut self) -> usize {
+        let mut buf = [0u8; 64];
+        let mut n = 0;
+        loop {
+            match self.sock.recv_from(&mut buf) {
+                Ok((len, _from)) if len >= PACKET_LEN => {
+                    if let Some(p) = SyncPacket::decode(&buf[..len]) {
+                        if self.on_packet(p, Instant::now()) {
+                            n += 1;
+                        }
+                    }
+                }
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(_e) => break,
+            }
+        }
+        n
+    }
+
+    /// Feed one packet received at `rx`. Packets from a second master are ignored until the
+    /// current one times out.
+    pub fn on_packet(&mut self, p: SyncPacket, rx: Instant) -> bool {
+        if self.master.is_some_and(|m| m != p.master_id) && self.is_locked(rx) {
+            return false;
+        }
+        if self.master != Some(p.master_id) {
+            self.master = Some(p.master_id);
+            self.last = None;
+            self.offsets.clear();
+        }
+        if let Some(prev) = self.last {
+            // Reordered datagrams would only add jitter.
+            if p.sequence.wrapping_sub(prev.sequence) as i32 <= 0 {
+                return false;
+            }
+            let dt = p.time - prev.time;
+            if dt > 0.0 && p.frame > prev.frame {
+                let fps = (p.frame - prev.frame) as f64 / dt;
+                self.fps = self.fps * 0.9 + fps * 0.1;
+            }
+        }
+
+        let offset = p.time - self.local_secs(rx);
+        // A seek or pause invalidates the window.
+        let stale = self
+            .offsets
+            .iter()
+            .copied()
+            .reduce(f64::max)
+            .is_some_and(|max| (offset - max).abs() > self.cfg.snap_threshold_secs);
+        if stale || !p.playing {
+            self.offsets.clear();
+        }
+        self.offsets.push_back(offset);
+        while self.offsets.len() > self.cfg.window.max(1) {
+            self.offsets.pop_front();
+        }
+        self.last = Some(p);
+        self.last_rx = Some(rx);
+        true
+    }
+
+    fn local_secs(&self, at: Instant) -> f64 {
+        at.saturating_duration_since(self.epoch).as_secs_f64()
+    }
+
+    /// Where the master timeline is at `now`, if locked.
+    pub fn master_time(&self, now: Instant) -> Option<f64> {
+        if !self.is_locked(now) {
+            return None;
+        }
+        let p = self.last?;
+        if !p.playing {
+            return Some(p.time);
+        }
+        let offset = self.offsets.iter().copied().reduce(f64::max)?;
+        Some(self.local_secs(now) + offset + self.cfg.latency.as_secs_f64())
+    }
+
+    /// Advance the local clock to `now`, slewing towards the master. Returns the timeline time.
+    pub fn tick(&mut self, now: Instant) -> f64 {
+        let dt = self
+            .last_tick
+            .map(|t| now.saturating_duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+        self.last_tick = Some(now);
+
+        match self.master_time(now) {
+            // Unlocked: keep running on the local clock so a dropped network does not freeze.
+            None => self.time += dt,
+            Some(target) if !self.is_playing() => self.time = target,
+            Some(target) => {
+                self.time += dt;
+                let err = target - self.time;
+                if err.abs() > self.cfg.snap_threshold_secs {
+                    self.time = target;
+                } else {
+                    let limit = self.cfg.max_slew * dt;
+                    self.time += (err * self.cfg.gain * dt).clamp(-limit, limit);
+                }
+            }
+        }
+        self.time
+    }
+
+    /// Current (slewed) timeline time.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Frame index matching [`time`](Self::time), extrapolated from the last packet at the
+    /// master's measured frame rate.
+    pub fn frame(&self) -> u64 {
+        match self.last {
+            Some(p) => {
+                let frames = ((self.time - p.time) * self.fps).round();
+                (p.frame as f64 + frames).max(0.0) as u64
+            }
+            None => (self.time * self.fps) as u64,
+        }
+    }
+
+    /// Poll, tick and write `time`/`frame` into the frame context.
+    pub fn apply(&mut self, ctx: &mut FrameCtx) {
+        self.poll();
+        self.tick(Instant::now());
+        ctx.time = self.time as f32;
+        ctx.frame = self.frame();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SyncMaster;
+
+    fn packet(seq: u32, time: f64) -> SyncPacket {
+        SyncPacket {
+            master_id: 1,
+            sequence: seq,
+            frame: (time * 60.0).round() as u64,
+            time,
+            playing: true,
+        }
+    }
+
+    #[test]
+    fn jitter_is_filtered_and_drift_slews() {
+        let mut c = SyncClient::bind("127.0.0.1:0").unwrap();
+        let t0 = c.epoch;
+        let at = |s: f64| t0 + Duration::from_secs_f64(s);
+
+        // Master timeline = local + 100 s; the second packet arrives 30 ms late.
+        c.on_packet(packet(1, 100.0), at(0.0));
+        c.on_packet(packet(2, 100.1), at(0.13));
+        assert!((c.master_time(at(0.2)).unwrap() - 100.2).abs() < 1e-9);
+
+        // First tick snaps.
+        assert!((c.tick(at(0.2)) - 100.2).abs() < 1e-9);
+        assert_eq!(c.frame(), 6012);
+
+        // Local clock falls 50 ms behind (below the snap threshold) and is slewed, not jumped.
+        c.time -= 0.05;
+        let t = c.tick(at(0.3));
+        assert!(t > 100.25 && t < 100.3, "{t}");
+        let mut now = 0.3;
+        while now < 2.0 {
+            now += 1.0 / 60.0;
+            c.on_packet(packet(c.last.unwrap().sequence + 1, 100.0 + now), at(now));
+            c.tick(at(now));
+        }
+        assert!((c.time() - (100.0 + now)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn seek_snaps_and_second_master_is_ignored() {
+        let mut c = SyncClient::bind("127.0.0.1:0").unwrap();
+        let t0 = c.epoch;
+        let at = |s: f64| t0 + Duration::from_secs_f64(s);
+        c.on_packet(packet(1, 10.0), at(0.0));
+        c.tick(at(0.0));
+
+        c.on_packet(packet(2, 50.0), at(0.1));
+        assert!((c.tick(at(0.1)) - 50.0).abs() < 1e-9);
+
+        let other = SyncPacket {
+            master_id: 2,
+            ..packet(1, 0.0)
+        };
+        assert!(!c.on_packet(other, at(0.2)));
+        // Once the first master times out the other one is adopted.
+        assert!(c.on_packet(other, at(5.0)));
+        assert_eq!(c.master_id(), Some(2));
+    }
+
+    #[test]
+    fn loopback_master_to_client() {
+        let mut client = SyncClient::bind("127.0.0.1:0").unwrap();
+        let addr = client.local_addr().unwrap();
+        let mut master = SyncMaster::new(addr).unwrap().with_master_id(9);
+        assert!(master.publish(120, 2.0, true).unwrap());
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while client.poll() == 0 && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(client.master_id(), Some(9));
+        let mut ctx = FrameCtx::new(1, 1, 0.0, 0);
+        client.apply(&mut ctx);
+        assert!(ctx.time >= 2.0 && ctx.time < 2.5);
+        assert!(ctx.frame >= 120);
+    }
+}
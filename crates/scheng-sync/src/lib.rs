@@ -0,0 +1,33 @@
+//! scheng-sync
+//!
+//! Network genlock for multi-machine installations. One instance runs a [`SyncMaster`] that
+//! broadcasts its frame index and timeline time over UDP; every other instance runs a
+//! [`SyncClient`] that slews its own `FrameCtx::time` towards the master, so separate machines
+//! render the same moment of the timeline within a frame of each other.
+//!
+//! The client never stalls the render loop: packets are drained non-blocking, small errors are
+//! corrected by running slightly fast or slow (at most `max_slew`), seeks snap, and if the master
+//! disappears the client free-runs on its local clock.
+//!
+//! ```no_run
+//! use scheng_sync::{SyncClient, SyncMaster, SYNC_PORT};
+//!
+//! // Master machine, once per frame:
+//! let mut master = SyncMaster::new(("255.255.255.255", SYNC_PORT))?;
+//! # let (frame, time) = (0u64, 0.0f64);
+//! master.publish(frame, time, true)?;
+//!
+//! // Client machines, once per frame before executing the plan:
+//! let mut client = SyncClient::bind("0.0.0.0:7410")?;
+//! # let mut ctx = scheng_runtime_glow::FrameCtx::new(1920, 1080, 0.0, 0);
+//! client.apply(&mut ctx);
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+pub mod client;
+pub mod master;
+pub mod packet;
+
+pub use client::{SyncClient, SyncConfig};
+pub use master::SyncMaster;
+pub use packet::{SyncPacket, SYNC_PORT};
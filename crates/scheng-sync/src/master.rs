@@ -0,0 +1,89 @@
+//! Master side: broadcasts the local timeline.
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::packet::SyncPacket;
+
+/// Broadcasts frame index and timeline time to clients.
+#[derive(Debug)]
+pub struct SyncMaster {
+    sock: UdpSocket,
+    target: SocketAddr,
+    master_id: u32,
+    sequence: u32,
+    interval: Duration,
+    last_sent: Option<Instant>,
+}
+
+impl SyncMaster {
+    /// Send to a broadcast, multicast or unicast address, e.g. "255.255.255.255:7410".
+    pub fn new(target: impl ToSocketAddrs) -> io::Result<Self> {
+        let target = target
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no address"))?;
+        let sock = UdpSocket::bind("0.0.0.0:0")?;
+        sock.set_broadcast(true)?;
+        sock.set_nonblocking(true)?;
+        Ok(Self {
+            sock,
+            target,
+            master_id: default_master_id(),
+            sequence: 0,
+            interval: Duration::from_millis(20),
+            last_sent: None,
+        })
+    }
+
+    /// Minimum time between packets (default 20 ms). Clients extrapolate in between.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_master_id(mut self, id: u32) -> Self {
+        self.master_id = id;
+        self
+    }
+
+    pub fn master_id(&self) -> u32 {
+        self.master_id
+    }
+
+    /// Call once per rendered frame with the master's `FrameCtx` values. Rate-limited by the
+    /// interval; returns whether a packet was sent.
+    pub fn publish(&mut self, frame: u64, time: f64, playing: bool) -> io::Result<bool> {
+        let now = Instant::now();
+        if self
+            .last_sent
+            .is_some_and(|t| now.duration_since(t) < self.interval)
+        {
+            return Ok(false);
+        }
+        self.last_sent = Some(now);
+        self.sequence = self.sequence.wrapping_add(1);
+        let packet = SyncPacket {
+            master_id: self.master_id,
+            sequence: self.sequence,
+            frame,
+            time,
+            playing,
+        };
+        match self.sock.send_to(&packet.encode(), self.target) {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Distinct enough per process start to tell two masters apart.
+fn default_master_id() -> u32 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    nanos ^ std::process::id().rotate_left(16)
+}
@@ -0,0 +1,80 @@
+//! Wire format: one fixed-size UDP datagram per clock tick.
+//!
+//! ```text
+//! 0..4   magic "SCSY"
+//! 4      version (1)
+//! 5      flags (bit 0 = playing)
+//! 6..8   reserved
+//! 8..12  master id (u32, BE) — lets clients ignore a second master on the same network
+//! 12..16 sequence (u32, BE)
+//! 16..24 frame index (u64, BE)
+//! 24..32 timeline time in seconds (f64, BE)
+//! ```
+
+pub const SYNC_PORT: u16 = 7410;
+
+const MAGIC: &[u8; 4] = b"SCSY";
+const VERSION: u8 = 1;
+pub const PACKET_LEN: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyncPacket {
+    pub master_id: u32,
+    pub sequence: u32,
+    pub frame: u64,
+    pub time: f64,
+    pub playing: bool,
+}
+
+impl SyncPacket {
+    pub fn encode(&self) -> [u8; PACKET_LEN] {
+        let mut p = [0u8; PACKET_LEN];
+        p[0..4].copy_from_slice(MAGIC);
+        p[4] = VERSION;
+        p[5] = self.playing as u8;
+        p[8..12].copy_from_slice(&self.master_id.to_be_bytes());
+        p[12..16].copy_from_slice(&self.sequence.to_be_bytes());
+        p[16..24].copy_from_slice(&self.frame.to_be_bytes());
+        p[24..32].copy_from_slice(&self.time.to_be_bytes());
+        p
+    }
+
+    pub fn decode(buf: &[u8]) -> Option<Self> {
+        if buf.len() < PACKET_LEN || &buf[0..4] != MAGIC || buf[4] != VERSION {
+            return None;
+        }
+        let time = f64::from_be_bytes(buf[24..32].try_into().ok()?);
+        if !time.is_finite() {
+            return None;
+        }
+        Some(Self {
+            master_id: u32::from_be_bytes(buf[8..12].try_into().ok()?),
+            sequence: u32::from_be_bytes(buf[12..16].try_into().ok()?),
+            frame: u64::from_be_bytes(buf[16..24].try_into().ok()?),
+            time,
+            playing: buf[5] & 1 != 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip_and_rejects_foreign_datagrams() {
+        let p = SyncPacket {
+            master_id: 7,
+            sequence: 42,
+            frame: 1_000_000,
+            time: 16_666.5,
+            playing: true,
+        };
+        let bytes = p.encode();
+        assert_eq!(SyncPacket::decode(&bytes), Some(p));
+        assert!(SyncPacket::decode(&bytes[..31]).is_none());
+        let mut bad = bytes;
+        bad[4] = 2;
+        assert!(SyncPacket::decode(&bad).is_none());
+    }
+}
@@ -1,8 +1,15 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+#[cfg(feature = "osc")]
 use rosc::{OscMessage, OscType};
 
+pub mod timecode;
+
+pub use timecode::{
+    ChaseConfig, ChaseState, FrameRate, LtcDecoder, MtcDecoder, Timecode, TimecodeChase,
+};
+
 /// Transport state that the video decoder will read each frame.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct TransportState {
@@ -54,6 +61,7 @@ pub struct ControlLayer {
     pub transport: TransportState,
     pub color: ColorState,
     keymap: Keymap,
+    #[cfg(feature = "osc")]
     oscmap: Oscmap,
 }
 
@@ -161,6 +169,7 @@ fn clamp_f32(x: f32, min: f32, max: f32) -> f32 {
 }
 
 /// Helper to parse an OSC argument into f32, if possible.
+#[cfg(feature = "osc")]
 fn parse_osc_f32(arg: &OscType) -> Option<f32> {
     match arg {
         OscType::Float(v) => Some(*v),
@@ -218,11 +227,13 @@ impl Keymap {
 }
 
 /// OSC map: maps an OSC address to an OSC action kind.
+#[cfg(feature = "osc")]
 #[derive(Debug, Default)]
 pub struct Oscmap {
     bindings: HashMap<String, OscActionKind>,
 }
 
+#[cfg(feature = "osc")]
 impl Oscmap {
     pub fn from_config(cfgs: &[OscBindingConfig]) -> Self {
         let mut bindings = HashMap::new();
@@ -236,7 +247,7 @@ impl Oscmap {
         let kind = self.bindings.get(&msg.addr)?;
         // Most OSC actions take a single float argument.
         let to_f32_arg =
-            |msg: &OscMessage| msg.args.first().and_then(|arg| parse_osc_f32(arg));
+            |msg: &OscMessage| msg.args.first().and_then(parse_osc_f32);
 
         let kind = match kind {
             OscActionKind::TogglePause => ConcreteActionKind::TogglePause,
@@ -356,6 +367,7 @@ impl ControlLayer {
             transport: TransportState::default(),
             color: ColorState::default(),
             keymap: Keymap::from_config(&cfg.keys),
+            #[cfg(feature = "osc")]
             oscmap: Oscmap::from_config(&cfg.osc),
         }
     }
@@ -368,6 +380,7 @@ impl ControlLayer {
     }
 
     /// Call this from your OSC handler.
+    #[cfg(feature = "osc")]
     pub fn on_osc(&mut self, msg: OscMessage) {
         if let Some(act) = self.oscmap.lookup(&msg) {
             act.apply(&mut self.transport, &mut self.color);
@@ -378,6 +391,7 @@ impl ControlLayer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(feature = "osc")]
     use rosc::{OscMessage, OscType};
 
     fn layer_with_basic_keymap() -> ControlLayer {
@@ -416,16 +430,16 @@ mod tests {
         let mut layer = layer_with_basic_keymap();
 
         // initial state
-        assert_eq!(layer.transport.paused, false);
+        assert!(!layer.transport.paused);
         assert!((layer.transport.speed - 1.0).abs() < 1e-6);
 
         // press space → toggle pause
         layer.on_key(' ');
-        assert_eq!(layer.transport.paused, true);
+        assert!(layer.transport.paused);
 
         // press space again → back to play
         layer.on_key(' ');
-        assert_eq!(layer.transport.paused, false);
+        assert!(!layer.transport.paused);
 
         // press 'f' → set speed 0.5
         layer.on_key('f');
@@ -445,6 +459,7 @@ mod tests {
         assert!(layer.color.brightness <= 2.0 + 1e-6);
     }
 
+    #[cfg(feature = "osc")]
     #[test]
     fn osc_set_speed_and_brightness() {
         let mut layer = layer_with_basic_keymap();
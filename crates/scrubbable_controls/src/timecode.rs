@@ -0,0 +1,586 @@
+//! Timecode input and chase mode.
+//!
+//! - [`MtcDecoder`] assembles MIDI Timecode from raw MIDI messages (quarter frames and
+//!   full-frame SysEx), so it can sit directly in a `midir` input callback.
+//! - [`LtcDecoder`] decodes SMPTE LTC from mono audio samples.
+//! - [`TimecodeChase`] turns the decoded stream into a smoothed playhead and drives a
+//!   [`TransportState`](crate::TransportState): small drift is absorbed by nudging `speed`, large
+//!   jumps relocate, and a lost signal freewheels briefly before pausing.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::TransportState;
+
+/// SMPTE frame rates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrameRate {
+    Fps24,
+    #[default]
+    Fps25,
+    /// 29.97 drop-frame.
+    Fps2997Df,
+    Fps30,
+}
+
+impl FrameRate {
+    /// Frames per second (actual rate, 29.97 for drop-frame).
+    pub fn fps(self) -> f64 {
+        match self {
+            FrameRate::Fps24 => 24.0,
+            FrameRate::Fps25 => 25.0,
+            FrameRate::Fps2997Df => 30000.0 / 1001.0,
+            FrameRate::Fps30 => 30.0,
+        }
+    }
+
+    /// Frame labels per second (30 for drop-frame).
+    pub fn nominal(self) -> u32 {
+        match self {
+            FrameRate::Fps24 => 24,
+            FrameRate::Fps25 => 25,
+            FrameRate::Fps2997Df | FrameRate::Fps30 => 30,
+        }
+    }
+
+    /// MTC rate code (bits 5-6 of the hours byte).
+    fn from_mtc_code(code: u8) -> Self {
+        match code & 0x03 {
+            0 => FrameRate::Fps24,
+            1 => FrameRate::Fps25,
+            2 => FrameRate::Fps2997Df,
+            _ => FrameRate::Fps30,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Timecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub rate: FrameRate,
+}
+
+impl Timecode {
+    pub fn new(hours: u8, minutes: u8, seconds: u8, frames: u8, rate: FrameRate) -> Self {
+        Self {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            rate,
+        }
+    }
+
+    /// Frames since 00:00:00:00, accounting for drop-frame numbering.
+    pub fn frame_number(&self) -> u64 {
+        let nominal = self.rate.nominal() as u64;
+        let total_minutes = self.hours as u64 * 60 + self.minutes as u64;
+        let labels = (total_minutes * 60 + self.seconds as u64) * nominal + self.frames as u64;
+        match self.rate {
+            // Frames 0 and 1 are skipped every minute except every tenth.
+            FrameRate::Fps2997Df => labels - 2 * (total_minutes - total_minutes / 10),
+            _ => labels,
+        }
+    }
+
+    /// Inverse of [`frame_number`](Self::frame_number); wraps at 24 hours.
+    pub fn from_frame_number(frame: u64, rate: FrameRate) -> Self {
+        let mut labels = frame;
+        if rate == FrameRate::Fps2997Df {
+            // 17982 frames per 10 minutes, 1798 per dropped minute.
+            let tens = frame / 17982;
+            let rem = frame % 17982;
+            labels += 18 * tens;
+            if rem > 1 {
+                labels += 2 * ((rem - 2) / 1798);
+            }
+        }
+        let nominal = rate.nominal() as u64;
+        Self {
+            hours: ((labels / (nominal * 3600)) % 24) as u8,
+            minutes: ((labels / (nominal * 60)) % 60) as u8,
+            seconds: ((labels / nominal) % 60) as u8,
+            frames: (labels % nominal) as u8,
+            rate,
+        }
+    }
+
+    pub fn to_seconds(&self) -> f64 {
+        self.frame_number() as f64 / self.rate.fps()
+    }
+
+    pub fn add_frames(&self, n: u64) -> Self {
+        Self::from_frame_number(self.frame_number() + n, self.rate)
+    }
+}
+
+impl fmt::Display for Timecode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sep = if self.rate == FrameRate::Fps2997Df {
+            ';'
+        } else {
+            ':'
+        };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{sep}{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
+/// MIDI Timecode decoder.
+///
+/// Quarter frames spread one timecode over two frames, so a value is only emitted after all
+/// eight pieces arrived in order; it is advanced by the two frames that took.
+#[derive(Debug, Default)]
+pub struct MtcDecoder {
+    pieces: [u8; 8],
+    /// Bit n set = piece n received since the last piece 0.
+    seen: u8,
+}
+
+impl MtcDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed one MIDI message. Returns a timecode after a full quarter-frame cycle or a
+    /// full-frame SysEx (`F0 7F <dev> 01 01 hh mm ss ff F7`, sent on locate).
+    pub fn feed(&mut self, msg: &[u8]) -> Option<Timecode> {
+        match msg {
+            [0xF1, data, ..] => self.quarter_frame(*data),
+            [0xF0, 0x7F, _dev, 0x01, 0x01, hr, mn, sc, fr, ..] => {
+                self.seen = 0;
+                Some(Timecode::new(
+                    hr & 0x1F,
+                    *mn,
+                    *sc,
+                    *fr,
+                    FrameRate::from_mtc_code(hr >> 5),
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    fn quarter_frame(&mut self, data: u8) -> Option<Timecode> {
+        let piece = ((data >> 4) & 0x07) as usize;
+        if piece == 0 {
+            self.seen = 0;
+        }
+        self.pieces[piece] = data & 0x0F;
+        self.seen |= 1 << piece;
+        if piece != 7 || self.seen != 0xFF {
+            return None;
+        }
+        let p = &self.pieces;
+        let tc = Timecode::new(
+            p[6] | ((p[7] & 0x01) << 4),
+            p[4] | (p[5] << 4),
+            p[2] | (p[3] << 4),
+            p[0] | (p[1] << 4),
+            FrameRate::from_mtc_code(p[7] >> 1),
+        );
+        Some(tc.add_frames(2))
+    }
+}
+
+/// SMPTE LTC decoder for mono audio.
+///
+/// LTC is biphase-mark coded: every bit cell starts with a transition and a `1` has a second
+/// one mid-cell. The bit period is tracked so varispeed and wow are tolerated. LTC does not
+/// carry the frame rate (apart from the drop-frame flag), so it is supplied up front.
+#[derive(Debug)]
+pub struct LtcDecoder {
+    rate: FrameRate,
+    /// Samples per bit cell, adapted as bits arrive.
+    bit_period: f32,
+    since_edge: f32,
+    level: bool,
+    half_pending: bool,
+    bits: u128,
+}
+
+/// Sync word (bits 64..80, in transmission order packed LSB first).
+const LTC_SYNC: u128 = 0xBFFC;
+
+impl LtcDecoder {
+    pub fn new(sample_rate: u32, rate: FrameRate) -> Self {
+        Self {
+            rate,
+            bit_period: sample_rate as f32 / (rate.fps() as f32 * 80.0),
+            since_edge: 0.0,
+            level: false,
+            half_pending: false,
+            bits: 0,
+        }
+    }
+
+    /// Feed samples; returns the last complete timecode in the block, if any.
+    pub fn feed(&mut self, samples: &[f32]) -> Option<Timecode> {
+        let mut out = None;
+        for &s in samples {
+            self.since_edge += 1.0;
+            // Hysteresis keeps noise around zero from producing edges.
+            let level = if s > 0.05 {
+                true
+            } else if s < -0.05 {
+                false
+            } else {
+                self.level
+            };
+            if level == self.level {
+                continue;
+            }
+            self.level = level;
+            let d = std::mem::take(&mut self.since_edge);
+            if d > self.bit_period * 0.75 {
+                self.half_pending = false;
+                self.bit_period = self.bit_period * 0.9 + d * 0.1;
+                if let Some(tc) = self.push_bit(false) {
+                    out = Some(tc);
+                }
+            } else if self.half_pending {
+                self.half_pending = false;
+                self.bit_period = self.bit_period * 0.9 + d * 2.0 * 0.1;
+                if let Some(tc) = self.push_bit(true) {
+                    out = Some(tc);
+                }
+            } else {
+                self.half_pending = true;
+            }
+        }
+        out
+    }
+
+    fn push_bit(&mut self, bit: bool) -> Option<Timecode> {
+        self.bits = (self.bits >> 1) | ((bit as u128) << 79);
+        if (self.bits >> 64) & 0xFFFF != LTC_SYNC {
+            return None;
+        }
+        let b = self.bits;
+        let field = |at: u32, len: u32| ((b >> at) & ((1 << len) - 1)) as u8;
+        let rate = if field(10, 1) == 1 {
+            FrameRate::Fps2997Df
+        } else {
+            self.rate
+        };
+        let tc = Timecode::new(
+            field(48, 4) + 10 * field(56, 2),
+            field(32, 4) + 10 * field(40, 3),
+            field(16, 4) + 10 * field(24, 3),
+            field(0, 4) + 10 * field(8, 2),
+            rate,
+        );
+        // The word has just ended, so the frame it labels is over.
+        Some(tc.add_frames(1))
+    }
+}
+
+/// Tuning for [`TimecodeChase`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChaseConfig {
+    /// Timecode (seconds) that maps to clip position 0.
+    pub offset_secs: f64,
+    /// Errors beyond this relocate instead of being smoothed.
+    pub jump_threshold_secs: f64,
+    /// Speed correction per second of error.
+    pub gain: f64,
+    /// Largest speed correction (0.05 = ±5%).
+    pub max_correction: f64,
+    /// Keep running this long after timecode stops before pausing.
+    pub freewheel: Duration,
+}
+
+impl Default for ChaseConfig {
+    fn default() -> Self {
+        Self {
+            offset_secs: 0.0,
+            jump_threshold_secs: 0.5,
+            gain: 2.0,
+            max_correction: 0.05,
+            freewheel: Duration::from_millis(500),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChaseState {
+    /// No timecode yet, or stopped after freewheeling.
+    Idle,
+    /// Timecode is advancing and the playhead follows it.
+    Locked,
+    /// Timecode was received but is not advancing (source parked).
+    Parked,
+    /// Timecode stopped arriving; extrapolating until `freewheel` runs out.
+    Freewheel,
+}
+
+/// Follows an external timecode with a smoothed playhead.
+#[derive(Debug)]
+pub struct TimecodeChase {
+    cfg: ChaseConfig,
+    /// Last received timecode (seconds) and when it arrived.
+    last: Option<(f64, Instant)>,
+    running: bool,
+    playhead: Option<f64>,
+    speed: f64,
+    last_tick: Option<Instant>,
+    state: ChaseState,
+}
+
+impl TimecodeChase {
+    pub fn new(cfg: ChaseConfig) -> Self {
+        Self {
+            cfg,
+            last: None,
+            running: false,
+            playhead: None,
+            speed: 0.0,
+            last_tick: None,
+            state: ChaseState::Idle,
+        }
+    }
+
+    pub fn config(&self) -> &ChaseConfig {
+        &self.cfg
+    }
+
+    pub fn state(&self) -> ChaseState {
+        self.state
+    }
+
+    /// Smoothed clip position in seconds (timecode minus `offset_secs`).
+    pub fn playhead(&self) -> Option<f64> {
+        self.playhead
+    }
+
+    /// Current playback speed including drift correction.
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Record a decoded timecode.
+    pub fn on_timecode(&mut self, tc: &Timecode, now: Instant) {
+        let secs = tc.to_seconds();
+        self.running = match self.last {
+            // Less than a frame of movement (in either direction) means the source is parked.
+            Some((prev, _)) => (secs - prev).abs() > 0.5 / tc.rate.fps(),
+            None => false,
+        };
+        self.last = Some((secs, now));
+    }
+
+    /// Where the source is now, extrapolated from the last timecode while it is running.
+    fn target(&self, now: Instant) -> Option<f64> {
+        let (secs, at) = self.last?;
+        let ahead = if self.running {
+            now.saturating_duration_since(at).as_secs_f64()
+        } else {
+            0.0
+        };
+        Some(secs + ahead - self.cfg.offset_secs)
+    }
+
+    /// Advance the playhead to `now`. Call once per frame.
+    pub fn tick(&mut self, now: Instant) -> Option<f64> {
+        let dt = self
+            .last_tick
+            .map(|t| now.saturating_duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+        self.last_tick = Some(now);
+
+        let (_, at) = self.last?;
+        let silent = now.saturating_duration_since(at);
+        if silent > self.cfg.freewheel {
+            self.running = false;
+            self.speed = 0.0;
+            self.state = ChaseState::Idle;
+            return self.playhead;
+        }
+        let target = self.target(now)?;
+
+        if !self.running {
+            self.playhead = Some(target);
+            self.speed = 0.0;
+            self.state = ChaseState::Parked;
+            return self.playhead;
+        }
+
+        self.state = if silent.as_secs_f64() > 0.1 {
+            ChaseState::Freewheel
+        } else {
+            ChaseState::Locked
+        };
+        // Free-run at the previous speed, then steer towards the source.
+        let predicted = self.playhead.map(|p| p + dt * self.speed);
+        match predicted {
+            Some(p) if (target - p).abs() <= self.cfg.jump_threshold_secs => {
+                let correction = ((target - p) * self.cfg.gain)
+                    .clamp(-self.cfg.max_correction, self.cfg.max_correction);
+                self.speed = 1.0 + correction;
+                self.playhead = Some(p);
+            }
+            _ => {
+                self.speed = 1.0;
+                self.playhead = Some(target);
+            }
+        }
+        self.playhead
+    }
+
+    /// Tick and write the result into `tr` for a clip of `duration_secs`.
+    pub fn apply(&mut self, now: Instant, tr: &mut TransportState, duration_secs: f64) {
+        let Some(pos) = self.tick(now) else {
+            return;
+        };
+        tr.paused = self.state == ChaseState::Idle || self.state == ChaseState::Parked;
+        tr.speed = self.speed as f32;
+        tr.scrub_delta = 0.0;
+        if duration_secs > 0.0 {
+            tr.norm_pos = (pos / duration_secs).clamp(0.0, 1.0) as f32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_frame_numbering_roundtrips() {
+        let tc = Timecode::new(0, 1, 0, 2, FrameRate::Fps2997Df);
+        assert_eq!(tc.frame_number(), 1800);
+        assert_eq!(Timecode::from_frame_number(1800, FrameRate::Fps2997Df), tc);
+        let ten = Timecode::new(0, 10, 0, 0, FrameRate::Fps2997Df);
+        assert_eq!(ten.frame_number(), 17982);
+        assert_eq!(ten.to_string(), "00:10:00;00");
+        for n in [0, 1799, 1800, 17981, 17982, 123_456] {
+            let tc = Timecode::from_frame_number(n, FrameRate::Fps2997Df);
+            assert_eq!(tc.frame_number(), n, "{tc}");
+        }
+    }
+
+    #[test]
+    fn mtc_quarter_frames_and_full_frame() {
+        let mut dec = MtcDecoder::new();
+        // 01:02:03:04 @ 25 fps.
+        let nibbles = [4, 0, 3, 0, 2, 0, 1, 1 << 1];
+        let mut got = None;
+        for (i, n) in nibbles.iter().enumerate() {
+            got = dec.feed(&[0xF1, ((i as u8) << 4) | n]);
+            if i < 7 {
+                assert!(got.is_none());
+            }
+        }
+        assert_eq!(got, Some(Timecode::new(1, 2, 3, 6, FrameRate::Fps25)));
+
+        // Starting mid-cycle must not emit a half-assembled value.
+        let mut dec = MtcDecoder::new();
+        assert!(dec.feed(&[0xF1, 0x70 | (1 << 1)]).is_none());
+
+        let full = [
+            0xF0,
+            0x7F,
+            0x7F,
+            0x01,
+            0x01,
+            (3 << 5) | 10,
+            20,
+            30,
+            15,
+            0xF7,
+        ];
+        assert_eq!(
+            dec.feed(&full),
+            Some(Timecode::new(10, 20, 30, 15, FrameRate::Fps30))
+        );
+    }
+
+    /// Biphase-mark encode LTC frames starting at `start`.
+    fn ltc_audio(start: Timecode, frames: u64, samples_per_bit: usize) -> Vec<f32> {
+        let mut out = Vec::new();
+        let mut level = 1.0f32;
+        for i in 0..frames {
+            let tc = start.add_frames(i);
+            let mut word: u128 = 0;
+            let mut put = |at: u32, v: u8| word |= (v as u128) << at;
+            put(0, tc.frames % 10);
+            put(8, tc.frames / 10);
+            put(16, tc.seconds % 10);
+            put(24, tc.seconds / 10);
+            put(32, tc.minutes % 10);
+            put(40, tc.minutes / 10);
+            put(48, tc.hours % 10);
+            put(56, tc.hours / 10);
+            word |= LTC_SYNC << 64;
+            for bit in 0..80 {
+                let one = (word >> bit) & 1 == 1;
+                level = -level;
+                for s in 0..samples_per_bit {
+                    if one && s == samples_per_bit / 2 {
+                        level = -level;
+                    }
+                    out.push(level * 0.5);
+                }
+            }
+        }
+        // Closing edge: a bit is only known once its cell ends.
+        out.extend(std::iter::repeat_n(-level * 0.5, samples_per_bit));
+        out
+    }
+
+    #[test]
+    fn ltc_decodes_biphase_audio() {
+        let start = Timecode::new(1, 0, 59, 20, FrameRate::Fps25);
+        // 48 kHz / (25 fps * 80 bits) = 24 samples per bit.
+        let audio = ltc_audio(start, 10, 24);
+        let mut dec = LtcDecoder::new(48_000, FrameRate::Fps25);
+        let last = dec.feed(&audio);
+        assert_eq!(last, Some(Timecode::new(1, 1, 0, 5, FrameRate::Fps25)));
+    }
+
+    #[test]
+    fn chase_smooths_drift_and_relocates_on_jumps() {
+        let t0 = Instant::now();
+        let ms = |n: u64| t0 + Duration::from_millis(n);
+        let tc = |secs: u64| Timecode::from_frame_number(secs * 25, FrameRate::Fps25);
+        let mut chase = TimecodeChase::new(ChaseConfig {
+            offset_secs: 10.0,
+            ..ChaseConfig::default()
+        });
+
+        assert_eq!(chase.tick(t0), None);
+        chase.on_timecode(&tc(20), t0);
+        assert_eq!(chase.tick(t0), Some(10.0));
+        assert_eq!(chase.state(), ChaseState::Parked);
+
+        // Source starts rolling: first running tick locates exactly.
+        chase.on_timecode(&tc(21), ms(1000));
+        assert_eq!(chase.tick(ms(1000)), Some(11.0));
+        assert_eq!(chase.state(), ChaseState::Locked);
+
+        // Timecode arrives 100 ms "late": playhead is ahead, so speed dips below 1.
+        chase.on_timecode(&tc(22), ms(2100));
+        chase.tick(ms(2100));
+        assert!(chase.speed() < 1.0 && chase.speed() >= 0.95);
+
+        // A big jump relocates.
+        chase.on_timecode(&tc(60), ms(2140));
+        assert_eq!(chase.tick(ms(2140)), Some(50.0));
+
+        let mut tr = TransportState::default();
+        chase.apply(ms(2300), &mut tr, 100.0);
+        assert_eq!(chase.state(), ChaseState::Freewheel);
+        assert!(!tr.paused && (tr.norm_pos - 0.5016).abs() < 1e-3);
+
+        // Freewheel runs out: pause, holding position.
+        chase.apply(ms(3000), &mut tr, 100.0);
+        assert_eq!(chase.state(), ChaseState::Idle);
+        assert!(tr.paused);
+    }
+}
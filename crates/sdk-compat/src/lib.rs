@@ -2,6 +2,9 @@
 //!
 //! This crate exists to ensure the public SDK surface remains usable by third-party
 //! consumers. It is not shipped or run; it must only build.
+//!
+//! It depends only on `scheng-core`, `scheng-graph` and `scheng-runtime`, so building it also
+//! proves the data model still builds without GL.
 
 use scheng_graph::{Graph, NodeKind};
 use scheng_runtime::{standard_op_for, BankDef, BankSet, MatrixPreset, SceneDef};
@@ -29,6 +32,8 @@ pub fn _compile_witness() {
     let _scene = SceneDef {
         name: "solo_0".to_string(),
         preset: MatrixPreset::Solo0,
+        weights: None,
+        overrides: Vec::new(),
     };
     let _bank = BankDef {
         name: "Basic".to_string(),
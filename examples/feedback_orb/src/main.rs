@@ -1,20 +1,15 @@
 use glow::HasContext;
 use scheng_control_osc::OscParamReceiver;
+use scheng_host_winit::WindowGl;
 use scheng_passes::PingPongTarget;
 use scheng_runtime_glow::{
     compile_program, create_render_target, EngineError, FullscreenTriangle, FULLSCREEN_VERT,
 };
 
-use std::num::NonZeroU32;
 use std::time::Instant;
 
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::WindowBuilder;
-
-use glutin::display::GetGlDisplay;
-use glutin::prelude::*;
-use raw_window_handle::HasRawWindowHandle;
 
 fn main() {
     if let Err(e) = run() {
@@ -26,57 +21,13 @@ fn main() {
 fn run() -> Result<(), EngineError> {
     let event_loop = EventLoop::new();
 
-    let window_builder = WindowBuilder::new()
-        .with_title("scheng-sdk: orb source + dual-input feedback")
-        .with_inner_size(winit::dpi::LogicalSize::new(960.0, 540.0));
-
-    let template = glutin::config::ConfigTemplateBuilder::new().with_alpha_size(8);
-
-    let display_builder =
-        glutin_winit::DisplayBuilder::new().with_window_builder(Some(window_builder));
-
-    let (window, gl_config) = display_builder
-        .build(&event_loop, template, |mut configs| configs.next().unwrap())
-        .map_err(|e| EngineError::GlCreate(format!("DisplayBuilder.build: {e}")))?;
-
-    let window = window
-        .ok_or_else(|| EngineError::GlCreate("DisplayBuilder did not create a window".into()))?;
-    let gl_display = gl_config.display();
-    let raw_window_handle = window.raw_window_handle();
-
-    let context_attributes = glutin::context::ContextAttributesBuilder::new()
-        .with_profile(glutin::context::GlProfile::Core)
-        .build(Some(raw_window_handle));
-
-    let not_current_gl_context = unsafe {
-        gl_display
-            .create_context(&gl_config, &context_attributes)
-            .map_err(|e| EngineError::GlCreate(format!("create_context: {e}")))?
-    };
-
-    let size = window.inner_size();
-    let attrs = glutin::surface::SurfaceAttributesBuilder::<glutin::surface::WindowSurface>::new()
-        .build(
-            raw_window_handle,
-            NonZeroU32::new(size.width.max(1)).unwrap(),
-            NonZeroU32::new(size.height.max(1)).unwrap(),
-        );
-
-    let gl_surface = unsafe {
-        gl_display
-            .create_window_surface(&gl_config, &attrs)
-            .map_err(|e| EngineError::GlCreate(format!("create_window_surface: {e}")))?
-    };
-
-    let gl_context = not_current_gl_context
-        .make_current(&gl_surface)
-        .map_err(|e| EngineError::GlCreate(format!("make_current: {e}")))?;
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            gl_display.get_proc_address(std::ffi::CString::new(s).unwrap().as_c_str()) as *const _
-        })
-    };
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng-sdk: orb source + dual-input feedback",
+        winit::dpi::LogicalSize::new(960.0, 540.0),
+        None,
+    )?;
+    let size = win.window.inner_size();
 
     let fs_tri = unsafe { FullscreenTriangle::new(&gl)? };
 
@@ -221,12 +172,7 @@ void main() {
                 WindowEvent::Resized(physical_size) => {
                     let w = physical_size.width.max(1);
                     let h = physical_size.height.max(1);
-
-                    gl_surface.resize(
-                        &gl_context,
-                        NonZeroU32::new(w).unwrap(),
-                        NonZeroU32::new(h).unwrap(),
-                    );
+                    win.resize(physical_size);
 
                     unsafe {
                         ping.resize(&gl, w as i32, h as i32).unwrap();
@@ -235,16 +181,16 @@ void main() {
                         orb_rt = create_render_target(&gl, w as i32, h as i32).unwrap();
                     }
 
-                    window.request_redraw();
+                    win.request_redraw();
                 }
 
                 _ => {}
             },
 
-            Event::MainEventsCleared => window.request_redraw(),
+            Event::MainEventsCleared => win.request_redraw(),
 
             Event::RedrawRequested(_) => {
-                let s = window.inner_size();
+                let s = win.window.inner_size();
                 let w = s.width.max(1) as i32;
                 let h = s.height.max(1) as i32;
                 let t = start.elapsed().as_secs_f32();
@@ -362,7 +308,7 @@ void main() {
                     gl.bind_framebuffer(glow::DRAW_FRAMEBUFFER, None);
                 }
 
-                gl_surface.swap_buffers(&gl_context).unwrap();
+                win.swap_buffers().unwrap();
                 ping.swap();
             }
 
@@ -0,0 +1,10 @@
+#version 330 core
+uniform vec2 uResolution;
+uniform float uTime;
+uniform float u_freq;
+out vec4 FragColor;
+void main(){
+    vec2 uv = gl_FragCoord.xy / uResolution.xy;
+    float v = step(0.5, fract(uv.y * u_freq + uTime * 0.5));
+    FragColor = vec4(v, 0.2 * v, 0.6, 1.0);
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use scheng_runtime::BankSet;
 
@@ -6,7 +6,16 @@ pub fn print_bank(banks: &BankSet, bank_idx: usize) {
     let bank = &banks.banks[bank_idx];
     eprintln!("[bank] {} ({} scenes)", bank.name, bank.scenes.len());
     for (i, s) in bank.scenes.iter().enumerate() {
-        eprintln!("  {}: {}  -> {}", i + 1, s.name, s.preset.name());
+        match s.weights {
+            Some(w) => eprintln!("  {}: {}  -> {:?} (~{})", i + 1, s.name, w, s.preset.name()),
+            None => eprintln!("  {}: {}  -> {}", i + 1, s.name, s.preset.name()),
+        }
+        for o in &s.overrides {
+            eprintln!(
+                "       node {}: shader={:?} uniforms={:?}",
+                o.node, o.shader, o.uniforms
+            );
+        }
     }
 }
 
@@ -21,3 +30,31 @@ pub fn parse_args_banks_path() -> Option<PathBuf> {
     }
     None
 }
+
+/// Where edits are saved: the `--banks` file, or `banks.json` when running on the built-ins.
+pub fn save_path(loaded_from: Option<&Path>) -> PathBuf {
+    loaded_from
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("banks.json"))
+}
+
+/// Directory scene shader paths resolve against: the `--banks` file's, or the working directory.
+pub fn shader_dir(loaded_from: Option<&Path>) -> PathBuf {
+    loaded_from
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub fn save(banks: &BankSet, path: &Path) -> bool {
+    match banks.to_json_path(path) {
+        Ok(()) => {
+            eprintln!("[banks] saved to {}", path.display());
+            true
+        }
+        Err(e) => {
+            eprintln!("[banks] save failed: {e}");
+            false
+        }
+    }
+}
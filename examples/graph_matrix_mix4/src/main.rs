@@ -1,28 +1,16 @@
-use std::collections::VecDeque;
-use std::num::NonZeroU32;
 use std::time::{Duration, Instant};
 
 use glow::HasContext;
 
-use glutin::config::{ConfigTemplateBuilder, GlConfig};
-use glutin::context::{
-    ContextApi, ContextAttributesBuilder, NotCurrentContext, NotCurrentGlContextSurfaceAccessor,
-    Version,
-};
-use glutin::display::{GetGlDisplay, GlDisplay};
-use glutin::prelude::GlSurface;
-use glutin::surface::{SurfaceAttributesBuilder, SwapInterval, WindowSurface};
-
-use glutin_winit::DisplayBuilder;
-use raw_window_handle::HasRawWindowHandle;
-
 use winit::dpi::PhysicalSize;
 use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::WindowBuilder;
 
 use scheng_graph as graph;
-use scheng_runtime::{BankSet, MatrixMixParams, MatrixPreset};
+use scheng_host_winit::WindowGl;
+use scheng_runtime::{
+    BankSet, MatrixMixParams, MatrixPreset, Quantizer, Quantum, SceneStep, SceneSwitcher,
+};
 use scheng_runtime_glow as rt;
 
 struct PresentBlitSink {
@@ -53,15 +41,9 @@ impl rt::OutputSink for PresentBlitSink {
     }
 }
 
-
-
 mod banks;
-mod quant;
-mod queue;
 mod transition;
 
-use quant::Quantizer;
-use queue::QueuedStep;
 use transition::PresetTransition;
 
 // ---------------- shaders ----------------
@@ -116,63 +98,14 @@ fn main() {
     // ---------------- Window + GL ----------------
     let event_loop = EventLoop::new();
 
-    let window_builder = WindowBuilder::new()
-        .with_title("scheng matrix mix4 (C4g: queued + quantized scenes)")
-        .with_inner_size(PhysicalSize::new(960, 540));
-
-    let template = ConfigTemplateBuilder::new();
-
-    let display_builder = DisplayBuilder::new().with_window_builder(Some(window_builder));
-    let (window, gl_config) = display_builder
-        .build(&event_loop, template, |configs| {
-            configs
-                .reduce(|a, b| {
-                    if b.num_samples() > a.num_samples() {
-                        b
-                    } else {
-                        a
-                    }
-                })
-                .unwrap()
-        })
-        .unwrap();
-
-    let window = window.expect("window");
-    let gl_display = gl_config.display();
-
-    let context_attrs = ContextAttributesBuilder::new()
-        .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
-        .build(Some(window.raw_window_handle()));
-
-    let not_current: NotCurrentContext = unsafe {
-        gl_display
-            .create_context(&gl_config, &context_attrs)
-            .unwrap()
-    };
-
-    let size = window.inner_size();
-    let surface_attrs = SurfaceAttributesBuilder::<WindowSurface>::new().build(
-        window.raw_window_handle(),
-        NonZeroU32::new(size.width.max(1)).unwrap(),
-        NonZeroU32::new(size.height.max(1)).unwrap(),
-    );
-
-    let gl_surface = unsafe {
-        gl_display
-            .create_window_surface(&gl_config, &surface_attrs)
-            .unwrap()
-    };
-
-    let gl_context = not_current.make_current(&gl_surface).unwrap();
-    gl_surface
-        .set_swap_interval(&gl_context, SwapInterval::Wait(NonZeroU32::new(1).unwrap()))
-        .ok();
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            gl_display.get_proc_address(std::ffi::CString::new(s).unwrap().as_c_str()) as *const _
-        })
-    };
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng matrix mix4 (C4g: queued + quantized scenes)",
+        PhysicalSize::new(960, 540),
+        Some((3, 3)),
+    )
+    .expect("create window + GL context");
+    win.set_vsync(true);
 
     // ---------------- Graph ----------------
     let mut g = graph::Graph::new();
@@ -195,40 +128,13 @@ fn main() {
     let plan = g.compile().unwrap();
 
     // ---------------- Runtime ----------------
-    let mut props = rt::NodeProps::default();
-
-    props.shader_sources.insert(
-        p0,
-        rt::ShaderSource {
-            vert: rt::FULLSCREEN_VERT.to_string(),
-            frag: FRAG_R.to_string(),
-            origin: Some("graph_matrix_mix4:p0".to_string()),
-        },
-    );
-    props.shader_sources.insert(
-        p1,
-        rt::ShaderSource {
-            vert: rt::FULLSCREEN_VERT.to_string(),
-            frag: FRAG_G.to_string(),
-            origin: Some("graph_matrix_mix4:p1".to_string()),
-        },
-    );
-    props.shader_sources.insert(
-        p2,
-        rt::ShaderSource {
-            vert: rt::FULLSCREEN_VERT.to_string(),
-            frag: FRAG_B.to_string(),
-            origin: Some("graph_matrix_mix4:p2".to_string()),
-        },
-    );
-    props.shader_sources.insert(
-        p3,
-        rt::ShaderSource {
-            vert: rt::FULLSCREEN_VERT.to_string(),
-            frag: FRAG_W.to_string(),
-            origin: Some("graph_matrix_mix4:p3".to_string()),
-        },
-    );
+    // Scenes render as overlays on these base props; the base itself never changes.
+    let base_props = rt::NodeProps::new()
+        .with_frag(p0, FRAG_R, "graph_matrix_mix4:p0")
+        .with_frag(p1, FRAG_G, "graph_matrix_mix4:p1")
+        .with_frag(p2, FRAG_B, "graph_matrix_mix4:p2")
+        .with_frag(p3, FRAG_W, "graph_matrix_mix4:p3");
+    let mut props = base_props.clone();
 
     let mut state = unsafe { rt::RuntimeState::new(&gl) }.unwrap();
     let start = Instant::now();
@@ -236,26 +142,26 @@ fn main() {
     // C4e: smooth transitions (still used)
     let transition_duration = Duration::from_secs(1);
     let mut current_preset = MatrixPreset::Quad;
+    // Differs from the preset's weights when a scene spells out its own mix.
+    let mut current_weights = current_preset.params().weights;
+    let mut pending_preset = current_preset;
     let mut transition: Option<PresetTransition> = None;
 
     // Animation mode (improv). When true, we ignore banks/queue.
     let mut animate = true;
 
     // C4g: quantized queued switching (default 500ms quantum)
-    let quantum = Duration::from_millis(500);
-    let quant = Quantizer::new(quantum, start);
-    let mut last_slot: u64 = quant.slot();
-
     // "Armed" means: at each quant boundary, pop next queued scene and transition to it.
-    let mut armed = false;
-    let mut queue: VecDeque<QueuedStep> = VecDeque::new();
+    let quantum = Quantum::Seconds(0.5);
+    let mut switcher: SceneSwitcher = SceneSwitcher::new(Quantizer::new(quantum));
 
     // Banks: either JSON or built-in (JSON is loaded via scheng-runtime standard helpers)
     let mut banks: BankSet = BankSet::builtin_matrix_banks();
     let mut bank_idx: usize = 0;
 
-    if let Some(path) = banks::parse_args_banks_path() {
-        match BankSet::from_json_path(&path) {
+    let banks_arg = banks::parse_args_banks_path();
+    if let Some(path) = &banks_arg {
+        match BankSet::from_json_path(path) {
             Ok(b) => {
                 eprintln!("[banks] loaded from {}", path.display());
                 banks = b;
@@ -269,7 +175,13 @@ fn main() {
         eprintln!("[banks] built-in (use --banks <file.json> to load)");
     }
 
-    // Print initial bank
+    // Live bank editing: the selected scene is the last one enqueued in the current bank.
+    let save_path = banks::save_path(banks_arg.as_deref());
+    // Scene shader paths are relative to the bank file.
+    let shader_dir = banks::shader_dir(banks_arg.as_deref());
+    let mut selected_scene: usize = 0;
+    let mut dirty = false;
+
     // Print initial bank
     banks::print_bank(&banks, bank_idx);
 
@@ -280,18 +192,18 @@ fn main() {
         match event {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => {
+                    if dirty {
+                        eprintln!(
+                            "[banks] exiting with unsaved edits (S saves to {})",
+                            save_path.display()
+                        );
+                    }
                     unsafe { state.destroy(&gl) };
                     *control_flow = ControlFlow::Exit;
                 }
 
                 WindowEvent::Resized(new_size) => {
-                    let w = new_size.width.max(1);
-                    let h = new_size.height.max(1);
-                    gl_surface.resize(
-                        &gl_context,
-                        NonZeroU32::new(w).unwrap(),
-                        NonZeroU32::new(h).unwrap(),
-                    );
+                    win.resize(new_size);
                 }
 
                 WindowEvent::KeyboardInput {
@@ -313,33 +225,98 @@ fn main() {
                                 bank_idx - 1
                             };
 
+                            selected_scene = 0;
                             banks::print_bank(&banks, bank_idx);
                             return;
                         }
                         VirtualKeyCode::RBracket => {
                             let bank_count = banks.banks.len();
                             bank_idx = (bank_idx + 1) % bank_count;
+                            selected_scene = 0;
 
                             banks::print_bank(&banks, bank_idx);
                             return;
                         }
                         VirtualKeyCode::Return => {
                             // In C4g: Enter is the "arm" toggle, plus print status.
-                            armed = !armed;
+                            switcher.set_armed(!switcher.is_armed());
                             eprintln!(
-                                "[queue] armed={armed} quantum={}ms len={}",
-                                quantum.as_millis(),
-                                queue.len()
+                                "[queue] armed={} quantum={}ms len={}",
+                                switcher.is_armed(),
+                                (quantum.period_secs() * 1000.0).round(),
+                                switcher.len()
                             );
 
                             banks::print_bank(&banks, bank_idx);
+                            if dirty {
+                                eprintln!(
+                                    "[banks] unsaved edits (S saves to {})",
+                                    save_path.display()
+                                );
+                            }
                             return;
                         }
                         VirtualKeyCode::Back => {
-                            queue.clear();
+                            switcher.clear();
                             eprintln!("[queue] cleared");
                             return;
                         }
+
+                        // Bank editing. Structural edits clear the queue (it holds indices).
+                        VirtualKeyCode::N => {
+                            let name = format!("scene_{}", banks.banks[bank_idx].scenes.len() + 1);
+                            let mut scene = scheng_runtime::SceneDef::new(name, current_preset);
+                            if current_weights != current_preset.params().weights {
+                                scene = scene.with_weights(current_weights);
+                            }
+                            if let Ok(i) = banks.add_scene(bank_idx, scene) {
+                                selected_scene = i;
+                                dirty = true;
+                                eprintln!(
+                                    "[edit] added scene {} ({})",
+                                    i + 1,
+                                    current_preset.name()
+                                );
+                                banks::print_bank(&banks, bank_idx);
+                            }
+                            return;
+                        }
+                        VirtualKeyCode::Delete => {
+                            if banks.banks[bank_idx].scenes.len() <= 1 {
+                                eprintln!("[edit] keeping the last scene of the bank");
+                                return;
+                            }
+                            if let Ok(s) = banks.remove_scene(bank_idx, selected_scene) {
+                                switcher.clear();
+                                selected_scene = selected_scene.saturating_sub(1);
+                                dirty = true;
+                                eprintln!("[edit] removed scene '{}' (queue cleared)", s.name);
+                                banks::print_bank(&banks, bank_idx);
+                            }
+                            return;
+                        }
+                        VirtualKeyCode::Comma | VirtualKeyCode::Period => {
+                            let to = if key == VirtualKeyCode::Comma {
+                                selected_scene.checked_sub(1)
+                            } else {
+                                Some(selected_scene + 1)
+                            };
+                            if let Some(to) = to {
+                                if banks.move_scene(bank_idx, selected_scene, to).is_ok() {
+                                    switcher.clear();
+                                    selected_scene = to;
+                                    dirty = true;
+                                    banks::print_bank(&banks, bank_idx);
+                                }
+                            }
+                            return;
+                        }
+                        VirtualKeyCode::S => {
+                            if banks::save(&banks, &save_path) {
+                                dirty = false;
+                            }
+                            return;
+                        }
                         _ => {}
                     }
 
@@ -347,8 +324,8 @@ fn main() {
                     if key == VirtualKeyCode::Space {
                         animate = !animate;
                         if animate {
-                            armed = false;
-                            queue.clear();
+                            switcher.set_armed(false);
+                            switcher.clear();
                             transition = None;
                             eprintln!("[animate] true (queue/banks disabled; queue cleared)");
                         } else {
@@ -374,6 +351,7 @@ fn main() {
                     if let Some(i0) = scene_index_0 {
                         // Enqueue only when not animating
                         animate = false;
+                        selected_scene = i0;
 
                         // Resolve preset from current bank
                         {
@@ -383,18 +361,14 @@ fn main() {
                                 return;
                             }
                             let s = &bank.scenes[i0];
-                            queue.push_back(QueuedStep {
-                                bank_idx,
-                                scene_idx: i0,
-                                preset: s.preset,
-                            });
+                            switcher.enqueue(SceneStep::new(bank_idx, i0));
                             eprintln!(
                                 "[queue] + {} | {} ({}): {}  (len={})",
                                 bank.name,
                                 i0 + 1,
                                 s.name,
                                 s.preset.name(),
-                                queue.len()
+                                switcher.len()
                             );
                         }
                     }
@@ -403,63 +377,53 @@ fn main() {
                 _ => {}
             },
 
-            Event::MainEventsCleared => window.request_redraw(),
+            Event::MainEventsCleared => win.request_redraw(),
 
             Event::RedrawRequested(_) => {
-                let size = window.inner_size();
+                let size = win.window.inner_size();
                 let w = size.width.max(1);
                 let h = size.height.max(1);
 
-                let frame = rt::FrameCtx {
-                    time: start.elapsed().as_secs_f32(),
-                    width: w as i32,
-                    height: h as i32,
-                    frame: 0,
-                };
+                let frame = rt::FrameCtx::new(w as i32, h as i32, start.elapsed().as_secs_f32(), 0);
 
                 let t = frame.time;
 
                 // C4g: On quant boundary, if armed and idle (not transitioning), pop next queued step.
                 // This ensures determinism and avoids changing targets mid-transition.
-                    if !animate
-                        && quant.is_boundary(&mut last_slot)
-                        && armed
-                        && transition.is_none()
-                    {
-                            if let Some(step) = queue.pop_front() {
-                                let to = step.preset;
-                                if to != current_preset {
-                                    transition = Some(PresetTransition::new(
-                                        current_preset,
-                                        to,
-                                        transition_duration,
-                                    ));
-
-                                    // Print scene name if we can
-                                    {
-                                        let bank = &banks.banks[step.bank_idx];
-                                        let scene = &bank.scenes[step.scene_idx];
-                                        eprintln!(
-                                            "[apply] {} | {} ({}): {} -> {} ({}ms)  (queue len={})",
-                                            bank.name,
-                                            step.scene_idx + 1,
-                                            scene.name,
-                                            current_preset.name(),
-                                            to.name(),
-                                            transition_duration.as_millis(),
-                                            queue.len()
-                                        );
-                                    }
-                                } else {
-                                    eprintln!(
-                                        "[apply] skipped (already {})",
-                                        current_preset.name()
-                                    );
-                                }
-                            }
-                        }
-                    
-                
+                let step = if animate {
+                    None
+                } else {
+                    switcher.poll(start.elapsed().as_secs_f64(), transition.is_none())
+                };
+                if let Some((step, scene)) = step.and_then(|st| Some((st, st.resolve(&banks)?))) {
+                    // Shader swaps and uniforms switch at once; weights crossfade.
+                    match base_props.scene_overlay(&g, scene, &shader_dir) {
+                        Ok(overlay) => props = base_props.merged(&overlay),
+                        Err(e) => eprintln!("[apply] {}: overrides not applied: {e}", scene.name),
+                    }
+                    let to = scene.preset;
+                    let to_weights = scene.params().weights;
+                    if to_weights != current_weights {
+                        transition = Some(PresetTransition::new(
+                            current_weights,
+                            to_weights,
+                            transition_duration,
+                        ));
+                        pending_preset = to;
+                        eprintln!(
+                            "[apply] {} | {} ({}): {} -> {} ({}ms)  (queue len={})",
+                            banks.banks[step.bank_idx].name,
+                            step.scene_idx + 1,
+                            scene.name,
+                            current_preset.name(),
+                            to.name(),
+                            transition_duration.as_millis(),
+                            switcher.len()
+                        );
+                    } else {
+                        eprintln!("[apply] skipped (already {})", current_preset.name());
+                    }
+                }
 
                 // Determine weights (animation OR transition OR static preset)
                 let (weights, done_transition) = if animate {
@@ -474,21 +438,25 @@ fn main() {
                     let (wts, done) = tr.weights();
                     (wts, done)
                 } else {
-                    (current_preset.params().weights, false)
+                    (current_weights, false)
                 };
 
                 // Finalize transition if needed
                 if !animate && done_transition {
                     if let Some(tr) = transition {
-                        current_preset = tr.to;
+                        current_weights = tr.to;
+                        current_preset = pending_preset;
                         transition = None;
                         eprintln!("[preset] now {}", current_preset.name());
                     }
                 }
 
-                props.matrix_params.insert(mix, MatrixMixParams { weights });
+                props.matrix_params.insert(mix, MatrixMixParams::new(weights));
 
-                let mut sink = PresentBlitSink { w: size.width as i32, h: size.height as i32 };
+                let mut sink = rt::TrackedSink::new(PresentBlitSink {
+                    w: size.width as i32,
+                    h: size.height as i32,
+                });
 
                 let exec = match unsafe {
                     rt::execute_plan_to_sink(&gl, &g, &plan, &mut state, &props, frame, &mut sink)
@@ -518,7 +486,7 @@ fn main() {
                     gl.bind_framebuffer(glow::READ_FRAMEBUFFER, None);
                 }
 
-                gl_surface.swap_buffers(&gl_context).unwrap();
+                win.swap_buffers().unwrap();
             }
 
             _ => {}
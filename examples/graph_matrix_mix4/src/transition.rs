@@ -1,7 +1,5 @@
 use std::time::{Duration, Instant};
 
-use scheng_runtime::MatrixPreset;
-
 // fn saturate(x: f32) -> f32 {
 //     if x < 0.0 {
 //         0.0
@@ -25,17 +23,17 @@ fn lerp(a: f32, b: f32, t: f32) -> f32 {
     a + (b - a) * t
 }
 
-/// Smooth crossfade between two matrix presets.
+/// Smooth crossfade between two sets of matrix weights.
 #[derive(Debug, Clone, Copy)]
 pub struct PresetTransition {
-    pub from: MatrixPreset,
-    pub to: MatrixPreset,
+    pub from: [f32; 4],
+    pub to: [f32; 4],
     started_at: Instant,
     duration: Duration,
 }
 
 impl PresetTransition {
-    pub fn new(from: MatrixPreset, to: MatrixPreset, duration: Duration) -> Self {
+    pub fn new(from: [f32; 4], to: [f32; 4], duration: Duration) -> Self {
         Self {
             from,
             to,
@@ -50,8 +48,7 @@ impl PresetTransition {
         let dur = self.duration.as_secs_f32().max(0.0001);
         let t = smoothstep01(elapsed / dur);
 
-        let a = self.from.params().weights;
-        let b = self.to.params().weights;
+        let (a, b) = (self.from, self.to);
 
         let w = [
             lerp(a[0], b[0], t),
@@ -1,20 +1,12 @@
-use std::num::NonZeroU32;
 use std::time::Instant;
 
 use glow::HasContext;
-use glutin::config::ConfigTemplateBuilder;
-use glutin::context::{
-    ContextAttributesBuilder, NotCurrentGlContextSurfaceAccessor, PossiblyCurrentContext,
-};
-use glutin::display::GetGlDisplay;
-use glutin::prelude::{GlConfig, GlDisplay, GlSurface};
-use glutin_winit::DisplayBuilder;
-use raw_window_handle::HasRawWindowHandle;
 use winit::dpi::PhysicalSize;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::ControlFlow;
 
 use scheng_graph as graph;
+use scheng_host_winit::WindowGl;
 use scheng_runtime::MatrixMixParams;
 use scheng_runtime_glow as rt;
 
@@ -58,96 +50,6 @@ fn parse_args() -> AppConfig {
     }
 }
 
-struct Presenter {
-    tri: rt::FullscreenTriangle,
-    program: glow::NativeProgram,
-}
-
-impl Presenter {
-    unsafe fn new(gl: &glow::Context) -> Result<Self, rt::EngineError> {
-        // Fragment shader in external file.
-        let frag_src = include_str!("../shader_present.glsl");
-        let tri = rt::FullscreenTriangle::new(gl)?;
-        let program = rt::compile_program(gl, rt::FULLSCREEN_VERT, frag_src)?;
-        Ok(Self { tri, program })
-    }
-
-    unsafe fn present(&self, gl: &glow::Context, tex: glow::NativeTexture, w: i32, h: i32) {
-        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
-        gl.viewport(0, 0, w, h);
-        gl.disable(glow::DEPTH_TEST);
-        gl.disable(glow::BLEND);
-
-        gl.use_program(Some(self.program));
-
-        gl.active_texture(glow::TEXTURE0);
-        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
-        if let Some(loc) = gl.get_uniform_location(self.program, "iChannel0") {
-            gl.uniform_1_i32(Some(&loc), 0);
-        }
-
-        self.tri.draw(gl);
-    }
-}
-
-// make_gl copied from webcam_source_minimal, with only title changed.
-fn make_gl(
-    event_loop: &winit::event_loop::EventLoop<()>,
-) -> (
-    winit::window::Window,
-    glutin::surface::Surface<glutin::surface::WindowSurface>,
-    PossiblyCurrentContext,
-    glow::Context,
-) {
-    let template =
-        ConfigTemplateBuilder::new().with_alpha_size(8).with_depth_size(0).with_stencil_size(0);
-
-    let display_builder = DisplayBuilder::new().with_window_builder(Some(
-        winit::window::WindowBuilder::new()
-            .with_title("scheng: graph_matrix_mix4_webcam")
-            .with_inner_size(PhysicalSize::new(WIN_W, WIN_H)),
-    ));
-
-    let (window, gl_config) = display_builder
-        .build(event_loop, template, |configs| {
-            configs
-                .reduce(|a, b| if a.num_samples() > b.num_samples() { a } else { b })
-                .unwrap()
-        })
-        .unwrap();
-
-    let window = window.unwrap();
-    let raw_window_handle = window.raw_window_handle();
-
-    let gl_display = gl_config.display();
-
-    let context_attributes = ContextAttributesBuilder::new().build(Some(raw_window_handle));
-    let not_current_gl_context =
-        unsafe { gl_display.create_context(&gl_config, &context_attributes).unwrap() };
-
-    let size = window.inner_size();
-    let attrs = glutin::surface::SurfaceAttributesBuilder::<glutin::surface::WindowSurface>::new()
-        .build(
-            raw_window_handle,
-            NonZeroU32::new(size.width.max(1)).unwrap(),
-            NonZeroU32::new(size.height.max(1)).unwrap(),
-        );
-
-    let gl_surface = unsafe { gl_display.create_window_surface(&gl_config, &attrs).unwrap() };
-
-    let gl_context = not_current_gl_context.make_current(&gl_surface).unwrap();
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            gl_display.get_proc_address(std::ffi::CStr::from_bytes_with_nul_unchecked(
-                format!("{s}\0").as_bytes(),
-            )) as *const _
-        })
-    };
-
-    (window, gl_surface, gl_context, gl)
-}
-
 unsafe fn make_host_texture(gl: &glow::Context, w: i32, h: i32) -> glow::NativeTexture {
     let tex = gl.create_texture().unwrap();
     gl.bind_texture(glow::TEXTURE_2D, Some(tex));
@@ -209,7 +111,6 @@ fn open_any_webcam() -> Option<Webcam> {
     None
 }
 
-
 fn main() {
     let cfg = parse_args();
 
@@ -225,7 +126,13 @@ fn main() {
     println!("Send /param/w0..w3 to control mix weights (0.0–1.0).");
 
     let event_loop = winit::event_loop::EventLoop::new();
-    let (window, gl_surface, gl_context, gl) = make_gl(&event_loop);
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng: graph_matrix_mix4_webcam",
+        PhysicalSize::new(WIN_W, WIN_H),
+        None,
+    )
+    .expect("create window + GL context");
 
     // --- Graph ---
     let mut g = graph::Graph::new();
@@ -290,15 +197,17 @@ fn main() {
     );
 
     // Initial 4-way equal mix; will be overridden by OSC each frame.
-    props.matrix_params.insert(
-        mix,
-        MatrixMixParams {
-            weights: [0.25, 0.25, 0.25, 0.25],
-        },
-    );
+    props
+        .matrix_params
+        .insert(mix, MatrixMixParams::new([0.25, 0.25, 0.25, 0.25]));
 
     let mut state = unsafe { rt::RuntimeState::new(&gl).expect("rt state") };
-    let presenter = unsafe { Presenter::new(&gl).expect("presenter") };
+    // Camera frames arrive upside down; the presenter rotates them for display.
+    let presenter_cfg = rt::PresenterConfig {
+        rotation: rt::Rotation::Cw180,
+        ..Default::default()
+    };
+    let presenter = unsafe { rt::Presenter::new(&gl, presenter_cfg).expect("presenter") };
 
     // --- Webcam (optional, auto-detect index 0..3) ---
     let mut cam_opt: Option<Webcam> = open_any_webcam();
@@ -323,15 +232,11 @@ fn main() {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                 WindowEvent::Resized(size) => {
-                    gl_surface.resize(
-                        &gl_context,
-                        NonZeroU32::new(size.width.max(1)).unwrap(),
-                        NonZeroU32::new(size.height.max(1)).unwrap(),
-                    );
+                    win.resize(size);
                 }
                 _ => {}
             },
-            Event::MainEventsCleared => window.request_redraw(),
+            Event::MainEventsCleared => win.request_redraw(),
             Event::RedrawRequested(_) => {
                 // Drain OSC updates once per frame; library strips /param/ prefix.
                 for (name, val) in osc.poll() {
@@ -373,16 +278,11 @@ fn main() {
                 // Bind the host texture (webcam or empty) into the TextureInputPass node.
                 props.texture_inputs.insert(tex_in, host_tex);
 
-                let size = window.inner_size();
+                let size = win.window.inner_size();
                 let w = size.width as i32;
                 let h = size.height as i32;
 
-                let frame = rt::FrameCtx {
-                    width: w,
-                    height: h,
-                    time: elapsed,
-                    frame: frame_index,
-                };
+                let frame = rt::FrameCtx::new(w, h, elapsed, frame_index);
                 frame_index = frame_index.wrapping_add(1);
 
                 unsafe {
@@ -391,8 +291,8 @@ fn main() {
                             .expect("execute");
                     let primary = outs.primary;
 
-                    presenter.present(&gl, primary.tex, w, h);
-                    gl_surface.swap_buffers(&gl_context).unwrap();
+                    presenter.present_output(&gl, &primary, w, h);
+                    win.swap_buffers().unwrap();
                 }
             }
             _ => {}
@@ -1,8 +1,9 @@
 use glow::HasContext;
 use scheng_core::EngineError;
 use scheng_graph::{Graph, NodeKind, PortDir};
+use scheng_host_winit::WindowGl;
 use scheng_runtime_glow::{
-    execute_plan_to_sink, FrameCtx, NodeProps, OutputSink, RuntimeState, ShaderSource,
+    execute_plan_to_sink, FrameCtx, NodeProps, OutputSink, RuntimeState, ShaderSource, TrackedSink,
 };
 
 struct PresentBlitSink {
@@ -34,21 +35,9 @@ impl OutputSink for PresentBlitSink {
     }
 }
 
-use raw_window_handle::HasRawWindowHandle;
 use std::time::Instant;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::WindowBuilder;
-
-use glutin::config::ConfigTemplateBuilder;
-use glutin::config::GlConfig;
-use glutin::context::{
-    ContextApi, ContextAttributesBuilder, NotCurrentGlContextSurfaceAccessor, Version,
-};
-use glutin::display::GetGlDisplay;
-use glutin::display::GlDisplay;
-use glutin::prelude::GlSurface;
-use glutin::surface::{SurfaceAttributesBuilder, SwapInterval, WindowSurface};
 
 const FULLSCREEN_VERT: &str = r#"#version 330 core
 layout(location=0) in vec2 a_pos;
@@ -245,72 +234,13 @@ fn main() -> Result<(), EngineError> {
 
     // --- Window / GL context ---
     let event_loop = EventLoop::new();
-    let window_builder = WindowBuilder::new()
-        .with_title("scheng graph_mixer2 (C3d)")
-        .with_inner_size(winit::dpi::LogicalSize::new(960.0, 540.0));
-
-    let template = ConfigTemplateBuilder::new()
-        .with_alpha_size(8)
-        .with_depth_size(0)
-        .with_stencil_size(0)
-        .with_transparency(false);
-
-    let display_builder =
-        glutin_winit::DisplayBuilder::new().with_window_builder(Some(window_builder));
-
-    let (window, gl_config) = display_builder
-        .build(&event_loop, template, |configs| {
-            configs
-                .reduce(|accum, config| {
-                    if config.num_samples() > accum.num_samples() {
-                        config
-                    } else {
-                        accum
-                    }
-                })
-                .unwrap()
-        })
-        .map_err(|e| EngineError::GlCreate(format!("DisplayBuilder.build: {e}")))?;
-
-    let window = window.unwrap();
-
-    let raw_window_handle = window.raw_window_handle();
-
-    let gl_display = gl_config.display();
-    let context_attributes = ContextAttributesBuilder::new()
-        .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
-        .build(Some(raw_window_handle));
-
-    let not_current = unsafe { gl_display.create_context(&gl_config, &context_attributes) }
-        .map_err(|e| EngineError::GlCreate(format!("create_context: {e}")))?;
-
-    let (width, height): (u32, u32) = window.inner_size().into();
-
-    let attrs = SurfaceAttributesBuilder::<WindowSurface>::new().build(
-        raw_window_handle,
-        std::num::NonZeroU32::new(width.max(1)).unwrap(),
-        std::num::NonZeroU32::new(height.max(1)).unwrap(),
-    );
-
-    let gl_surface = unsafe { gl_display.create_window_surface(&gl_config, &attrs) }
-        .map_err(|e| EngineError::GlCreate(format!("create_window_surface: {e}")))?;
-
-    let gl_context = not_current
-        .make_current(&gl_surface)
-        .map_err(|e| EngineError::GlCreate(format!("make_current: {e}")))?;
-
-    gl_surface
-        .set_swap_interval(
-            &gl_context,
-            SwapInterval::Wait(std::num::NonZeroU32::new(1).unwrap()),
-        )
-        .ok();
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            gl_display.get_proc_address(std::ffi::CString::new(s).unwrap().as_c_str()) as *const _
-        })
-    };
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng graph_mixer2 (C3d)",
+        winit::dpi::LogicalSize::new(960.0, 540.0),
+        Some((3, 3)),
+    )?;
+    win.set_vsync(true);
 
     let start = Instant::now();
     let mut state = unsafe { RuntimeState::new(&gl)? };
@@ -321,31 +251,23 @@ fn main() -> Result<(), EngineError> {
         match event {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-                WindowEvent::Resized(size) => {
-                    let w = size.width.max(1);
-                    let h = size.height.max(1);
-                    gl_surface.resize(
-                        &gl_context,
-                        std::num::NonZeroU32::new(w).unwrap(),
-                        std::num::NonZeroU32::new(h).unwrap(),
-                    );
-                }
+                WindowEvent::Resized(size) => win.resize(size),
                 _ => {}
             },
 
             Event::RedrawRequested(_) => unsafe {
-                let size = window.inner_size();
-                let frame = FrameCtx {
-                    time: start.elapsed().as_secs_f32(),
-                    width: size.width as i32,
-                    height: size.height as i32,
-                    frame: 0,
-                };
-
-                let mut sink = PresentBlitSink {
+                let size = win.window.inner_size();
+                let frame = FrameCtx::new(
+                    size.width as i32,
+                    size.height as i32,
+                    start.elapsed().as_secs_f32(),
+                    0,
+                );
+
+                let mut sink = TrackedSink::new(PresentBlitSink {
                     w: size.width as i32,
                     h: size.height as i32,
-                };
+                });
                 match execute_plan_to_sink(&gl, &graph, &plan, &mut state, &props, frame, &mut sink)
                 {
                     Ok(out_tex) => {
@@ -370,11 +292,11 @@ fn main() -> Result<(), EngineError> {
                     Err(e) => eprintln!("execute_plan error: {e}"),
                 }
 
-                gl_surface.swap_buffers(&gl_context).unwrap();
+                win.swap_buffers().unwrap();
             },
 
             Event::MainEventsCleared => {
-                window.request_redraw();
+                win.request_redraw();
             }
 
             _ => {}
@@ -1,26 +1,13 @@
-use std::num::NonZeroU32;
 use std::time::Instant;
 
 use glow::HasContext;
 
-use glutin::config::ConfigTemplateBuilder;
-
-use glutin::context::{
-    ContextApi, ContextAttributesBuilder, NotCurrentContext, NotCurrentGlContextSurfaceAccessor,
-    Version,
-};
-use glutin::display::{GetGlDisplay, GlDisplay};
-use glutin::prelude::GlSurface;
-use glutin::surface::{SurfaceAttributesBuilder, SwapInterval, WindowSurface};
-
-use glutin_winit::DisplayBuilder;
-use raw_window_handle::HasRawWindowHandle;
 use winit::dpi::PhysicalSize;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::WindowBuilder;
 
 use scheng_graph as graph;
+use scheng_host_winit::WindowGl;
 
 // IMPORTANT: OutputSink + ExecOutputs live in runtime-glow.
 use scheng_runtime_glow as rt;
@@ -177,56 +164,15 @@ void main() {
 fn main() {
     let event_loop = EventLoop::new();
 
-    let window_builder = WindowBuilder::new()
-        .with_title("scheng graph mixer builtin")
-        .with_inner_size(PhysicalSize::new(960, 540));
-
-    // IMPORTANT: glutin-winit expects ConfigTemplateBuilder, NOT a built ConfigTemplate.
-    let template = ConfigTemplateBuilder::new()
-        .with_alpha_size(8)
-        .with_depth_size(0)
-        .with_stencil_size(0);
-
-    let display_builder = DisplayBuilder::new().with_window_builder(Some(window_builder));
-
-    let (window, gl_config) = display_builder
-        .build(&event_loop, template, |mut configs| configs.next().unwrap())
-        .unwrap();
-
-    let window = window.unwrap();
-    let gl_display = gl_config.display();
-
-    let context_attributes = ContextAttributesBuilder::new()
-        .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
-        .build(Some(window.raw_window_handle()));
-
-    let not_current: NotCurrentContext =
-        unsafe { gl_display.create_context(&gl_config, &context_attributes).unwrap() };
-
-    let size = window.inner_size();
-    let surface_attrs = SurfaceAttributesBuilder::<WindowSurface>::new().build(
-        window.raw_window_handle(),
-        NonZeroU32::new(size.width.max(1)).unwrap(),
-        NonZeroU32::new(size.height.max(1)).unwrap(),
-    );
-
-    let gl_surface = unsafe {
-        gl_display
-            .create_window_surface(&gl_config, &surface_attrs)
-            .unwrap()
-    };
-
-    let gl_context = not_current.make_current(&gl_surface).unwrap();
-
-    gl_surface
-        .set_swap_interval(&gl_context, SwapInterval::Wait(NonZeroU32::new(1).unwrap()))
-        .ok();
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            gl_display.get_proc_address(std::ffi::CString::new(s).unwrap().as_c_str()) as *const _
-        })
-    };
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng graph mixer builtin",
+        PhysicalSize::new(960, 540),
+        Some((3, 3)),
+    )
+    .expect("create window + GL context");
+    win.set_vsync(true);
+    let (w, h) = win.size();
 
     // -------------------------
     // Graph
@@ -284,16 +230,22 @@ fn main() {
 
     props
         .mixer_params
-        .insert(mix, scheng_runtime::MixerParams { mix: 0.35 });
+        .insert(mix, scheng_runtime::MixerParams::new(0.35));
 
     let mut state = unsafe { rt::RuntimeState::new(&gl) }.unwrap();
 
+    // Compile every program before the first frame (no first-use hitch).
+    let report = unsafe { state.precompile(&gl, &g, &props) };
+    for (node, err) in &report.failures {
+        eprintln!("[precompile] node {node:?}: {err}");
+    }
+
     // -------------------------
     // Step 6 routing
     // -------------------------
 
     // Preview is local (so we can resize cleanly).
-    let mut preview_sink = PreviewRouteSink::new(size.width as i32, size.height as i32);
+    let mut preview_sink = PreviewRouteSink::new(w as i32, h as i32);
 
     // Program routes via Patchbay (composable sinks).
     let program_sink = ProgramRouteSink::new().unwrap();
@@ -313,23 +265,16 @@ fn main() {
                 WindowEvent::Resized(new_size) => {
                     preview_sink.resize(new_size.width as i32, new_size.height as i32);
 
-                    gl_surface.resize(
-                        &gl_context,
-                        NonZeroU32::new(new_size.width.max(1)).unwrap(),
-                        NonZeroU32::new(new_size.height.max(1)).unwrap(),
-                    );
+                    win.resize(new_size);
                 }
                 _ => {}
             },
             Event::RedrawRequested(_) => {
-                let frame = rt::FrameCtx {
-                    time: start.elapsed().as_secs_f32(),
-                    width: RENDER_W,
-                    height: RENDER_H,
-                    frame: frame_idx,
-                };
+                let frame =
+                    rt::FrameCtx::new(RENDER_W, RENDER_H, start.elapsed().as_secs_f32(), frame_idx);
                 frame_idx += 1;
 
+                patchbay.begin_frame(&gl, &frame);
                 let outs =
                     unsafe { rt::execute_plan_outputs(&gl, &g, &plan, &mut state, &props, frame) }
                         .unwrap();
@@ -345,10 +290,11 @@ fn main() {
 
                 // Program: Patchbay routes program output into sinks (Syphon, readback, history, etc).
                 patchbay.consume_named(&gl, &outs).unwrap();
+                patchbay.end_frame(&gl);
 
-                gl_surface.swap_buffers(&gl_context).unwrap();
+                win.swap_buffers().unwrap();
             }
-            Event::MainEventsCleared => window.request_redraw(),
+            Event::MainEventsCleared => win.request_redraw(),
             _ => {}
         }
     });
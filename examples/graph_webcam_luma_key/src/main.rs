@@ -1,20 +1,12 @@
-use std::num::NonZeroU32;
 use std::time::Instant;
 
 use glow::HasContext;
-use glutin::config::ConfigTemplateBuilder;
-use glutin::context::{
-    ContextAttributesBuilder, NotCurrentGlContextSurfaceAccessor, PossiblyCurrentContext,
-};
-use glutin::display::GetGlDisplay;
-use glutin::prelude::{GlConfig, GlDisplay, GlSurface};
-use glutin_winit::DisplayBuilder;
-use raw_window_handle::HasRawWindowHandle;
 use winit::dpi::PhysicalSize;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::ControlFlow;
 
 use scheng_graph as graph;
+use scheng_host_winit::WindowGl;
 use scheng_runtime::MatrixMixParams;
 use scheng_runtime_glow as rt;
 
@@ -59,106 +51,8 @@ fn parse_args() -> AppConfig {
     }
 }
 
-struct Presenter {
-    tri: rt::FullscreenTriangle,
-    program: glow::NativeProgram,
-}
-
-// Presenter shader: 180° rotate / flip for display.
-const PRESENT_FRAG: &str = r#"#version 330 core
-in vec2 v_uv;
-out vec4 o;
-uniform sampler2D iChannel0;
-void main() {
-    vec2 uv = vec2(1.0 - v_uv.x, 1.0 - v_uv.y);
-    o = texture(iChannel0, uv);
-}
-"#;
-
-impl Presenter {
-    unsafe fn new(gl: &glow::Context) -> Result<Self, rt::EngineError> {
-        let tri = rt::FullscreenTriangle::new(gl)?;
-        let program = rt::compile_program(gl, rt::FULLSCREEN_VERT, PRESENT_FRAG)?;
-        Ok(Self { tri, program })
-    }
-
-    unsafe fn present(&self, gl: &glow::Context, tex: glow::NativeTexture, w: i32, h: i32) {
-        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
-        gl.viewport(0, 0, w, h);
-        gl.disable(glow::DEPTH_TEST);
-        gl.disable(glow::BLEND);
-
-        gl.use_program(Some(self.program));
-
-        gl.active_texture(glow::TEXTURE0);
-        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
-        if let Some(loc) = gl.get_uniform_location(self.program, "iChannel0") {
-            gl.uniform_1_i32(Some(&loc), 0);
-        }
-
-        self.tri.draw(gl);
-    }
-}
-
 // --- GL / window bootstrap (based on webcam_source_minimal) ---
 
-fn make_gl(
-    event_loop: &winit::event_loop::EventLoop<()>,
-) -> (
-    winit::window::Window,
-    glutin::surface::Surface<glutin::surface::WindowSurface>,
-    PossiblyCurrentContext,
-    glow::Context,
-) {
-    let template =
-        ConfigTemplateBuilder::new().with_alpha_size(8).with_depth_size(0).with_stencil_size(0);
-
-    let display_builder = DisplayBuilder::new().with_window_builder(Some(
-        winit::window::WindowBuilder::new()
-            .with_title("scheng: webcam luma key (matrix mix4 + OSC)")
-            .with_inner_size(PhysicalSize::new(WIN_W, WIN_H)),
-    ));
-
-    let (window, gl_config) = display_builder
-        .build(event_loop, template, |configs| {
-            configs
-                .reduce(|a, b| if a.num_samples() > b.num_samples() { a } else { b })
-                .unwrap()
-        })
-        .unwrap();
-
-    let window = window.unwrap();
-    let raw_window_handle = window.raw_window_handle();
-
-    let gl_display = gl_config.display();
-
-    let context_attributes = ContextAttributesBuilder::new().build(Some(raw_window_handle));
-    let not_current_gl_context =
-        unsafe { gl_display.create_context(&gl_config, &context_attributes).unwrap() };
-
-    let size = window.inner_size();
-    let attrs = glutin::surface::SurfaceAttributesBuilder::<glutin::surface::WindowSurface>::new()
-        .build(
-            raw_window_handle,
-            NonZeroU32::new(size.width.max(1)).unwrap(),
-            NonZeroU32::new(size.height.max(1)).unwrap(),
-        );
-
-    let gl_surface = unsafe { gl_display.create_window_surface(&gl_config, &attrs).unwrap() };
-
-    let gl_context = not_current_gl_context.make_current(&gl_surface).unwrap();
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            gl_display.get_proc_address(std::ffi::CStr::from_bytes_with_nul_unchecked(
-                format!("{s}\0").as_bytes(),
-            )) as *const _
-        })
-    };
-
-    (window, gl_surface, gl_context, gl)
-}
-
 unsafe fn make_host_texture(gl: &glow::Context, w: i32, h: i32) -> glow::NativeTexture {
     let tex = gl.create_texture().unwrap();
     gl.bind_texture(glow::TEXTURE_2D, Some(tex));
@@ -237,7 +131,13 @@ fn main() {
     println!("  /param/w3        or /param/bg_gain");
 
     let event_loop = winit::event_loop::EventLoop::new();
-    let (window, gl_surface, gl_context, gl) = make_gl(&event_loop);
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng: webcam luma key (matrix mix4 + OSC)",
+        PhysicalSize::new(WIN_W, WIN_H),
+        None,
+    )
+    .expect("create window + GL context");
 
     // --- Graph: webcam -> pass -> mix4(key) + 3 GLSL layers -> PixelsOut ---
 
@@ -329,15 +229,17 @@ fn main() {
     let mut w2: f32 = 1.0;
     let mut w3: f32 = 1.0;
 
-    props.matrix_params.insert(
-        mix,
-        MatrixMixParams {
-            weights: [w0, w1, w2, w3],
-        },
-    );
+    props
+        .matrix_params
+        .insert(mix, MatrixMixParams::new([w0, w1, w2, w3]));
 
     let mut state = unsafe { rt::RuntimeState::new(&gl).expect("rt state") };
-    let presenter = unsafe { Presenter::new(&gl).expect("presenter") };
+    // Camera frames arrive upside down; the presenter rotates them for display.
+    let presenter_cfg = rt::PresenterConfig {
+        rotation: rt::Rotation::Cw180,
+        ..Default::default()
+    };
+    let presenter = unsafe { rt::Presenter::new(&gl, presenter_cfg).expect("presenter") };
 
     // --- Webcam host texture (optional) ---
 
@@ -357,15 +259,11 @@ fn main() {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                 WindowEvent::Resized(size) => {
-                    gl_surface.resize(
-                        &gl_context,
-                        NonZeroU32::new(size.width.max(1)).unwrap(),
-                        NonZeroU32::new(size.height.max(1)).unwrap(),
-                    );
+                    win.resize(size);
                 }
                 _ => {}
             },
-            Event::MainEventsCleared => window.request_redraw(),
+            Event::MainEventsCleared => win.request_redraw(),
             Event::RedrawRequested(_) => {
                 // ---- OSC: update weights, log everything we see ----
                 let mut any_osc = false;
@@ -428,16 +326,11 @@ fn main() {
                 // Provide webcam (or empty) texture to TextureInputPass node.
                 props.texture_inputs.insert(tex_in, host_tex);
 
-                let size = window.inner_size();
+                let size = win.window.inner_size();
                 let w = size.width as i32;
                 let h = size.height as i32;
 
-                let frame = rt::FrameCtx {
-                    width: w,
-                    height: h,
-                    time: elapsed,
-                    frame: frame_index,
-                };
+                let frame = rt::FrameCtx::new(w, h, elapsed, frame_index);
                 frame_index = frame_index.wrapping_add(1);
 
                 unsafe {
@@ -446,8 +339,8 @@ fn main() {
                             .expect("execute");
                     let main_out = outs.primary;
 
-                    presenter.present(&gl, main_out.tex, w, h);
-                    gl_surface.swap_buffers(&gl_context).unwrap();
+                    presenter.present_output(&gl, &main_out, w, h);
+                    win.swap_buffers().unwrap();
                 }
             }
             _ => {}
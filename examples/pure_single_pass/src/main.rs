@@ -1,13 +1,9 @@
 use glow::HasContext;
+use scheng_host_winit::WindowGl;
 use scheng_runtime_glow::{compile_program, EngineError, FullscreenTriangle, FULLSCREEN_VERT};
 use std::time::Instant;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::WindowBuilder;
-
-use glutin::display::GetGlDisplay;
-use glutin::prelude::*;
-use raw_window_handle::HasRawWindowHandle;
 
 fn main() {
     if let Err(e) = run() {
@@ -19,58 +15,12 @@ fn main() {
 fn run() -> Result<(), EngineError> {
     let event_loop = EventLoop::new();
 
-    let window_builder = WindowBuilder::new()
-        .with_title("scheng-sdk: pure single pass")
-        .with_inner_size(winit::dpi::LogicalSize::new(960.0, 540.0));
-
-    let template = glutin::config::ConfigTemplateBuilder::new().with_alpha_size(8);
-
-    let display_builder =
-        glutin_winit::DisplayBuilder::new().with_window_builder(Some(window_builder));
-
-    let (window, gl_config) = display_builder
-        .build(&event_loop, template, |mut configs| configs.next().unwrap())
-        .map_err(|e| EngineError::GlCreate(format!("DisplayBuilder.build: {e}")))?;
-
-    let window = window
-        .ok_or_else(|| EngineError::GlCreate("DisplayBuilder did not create a window".into()))?;
-    let gl_display = gl_config.display();
-
-    let raw_window_handle = window.raw_window_handle();
-
-    let context_attributes = glutin::context::ContextAttributesBuilder::new()
-        .with_profile(glutin::context::GlProfile::Core)
-        .build(Some(raw_window_handle));
-
-    let not_current_gl_context = unsafe {
-        gl_display
-            .create_context(&gl_config, &context_attributes)
-            .map_err(|e| EngineError::GlCreate(format!("create_context: {e}")))?
-    };
-
-    let size = window.inner_size();
-    let attrs = glutin::surface::SurfaceAttributesBuilder::<glutin::surface::WindowSurface>::new()
-        .build(
-            raw_window_handle,
-            size.width.try_into().unwrap(),
-            size.height.try_into().unwrap(),
-        );
-
-    let gl_surface = unsafe {
-        gl_display
-            .create_window_surface(&gl_config, &attrs)
-            .map_err(|e| EngineError::GlCreate(format!("create_window_surface: {e}")))?
-    };
-
-    let gl_context = not_current_gl_context
-        .make_current(&gl_surface)
-        .map_err(|e| EngineError::GlCreate(format!("make_current: {e}")))?;
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            gl_display.get_proc_address(std::ffi::CString::new(s).unwrap().as_c_str()) as *const _
-        })
-    };
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng-sdk: pure single pass",
+        winit::dpi::LogicalSize::new(960.0, 540.0),
+        None,
+    )?;
 
     let fs_tri = unsafe { FullscreenTriangle::new(&gl)? };
 
@@ -97,11 +47,11 @@ void main() {
                 ..
             } => *control_flow = ControlFlow::Exit,
 
-            Event::MainEventsCleared => window.request_redraw(),
+            Event::MainEventsCleared => win.request_redraw(),
             Event::RedrawRequested(_) => unsafe {
                 let t = start.elapsed().as_secs_f32();
                 gl.bind_framebuffer(glow::FRAMEBUFFER, None);
-                let s = window.inner_size();
+                let s = win.window.inner_size();
                 gl.viewport(0, 0, s.width as i32, s.height as i32);
 
                 gl.use_program(Some(program));
@@ -111,7 +61,7 @@ void main() {
                 fs_tri.draw(&gl);
                 gl.use_program(None);
 
-                gl_surface.swap_buffers(&gl_context).unwrap();
+                win.swap_buffers().unwrap();
             },
             _ => {}
         }
@@ -1,23 +1,13 @@
-use std::num::NonZeroU32;
 use std::time::Instant;
 
 use glow::HasContext;
 
-use glutin::config::ConfigTemplateBuilder;
-use glutin::context::{ContextApi, ContextAttributesBuilder, NotCurrentContext, Version};
-use glutin::context::NotCurrentGlContextSurfaceAccessor;
-use glutin::display::{GetGlDisplay, GlDisplay};
-use glutin::prelude::GlSurface;
-use glutin::surface::{SurfaceAttributesBuilder, SwapInterval, WindowSurface};
-
-use glutin_winit::DisplayBuilder;
-use raw_window_handle::HasRawWindowHandle;
 use winit::dpi::PhysicalSize;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
-use winit::window::WindowBuilder;
 
 use scheng_graph as graph;
+use scheng_host_winit::WindowGl;
 use scheng_runtime_glow as rt;
 
 // IMPORTANT: bring trait into scope for `.consume()`
@@ -79,60 +69,14 @@ fn build_graph(props: &mut rt::NodeProps) -> graph::Graph {
 fn main() {
     let event_loop = EventLoop::new();
 
-    let template = ConfigTemplateBuilder::new().with_alpha_size(8);
-    let display_builder = DisplayBuilder::new().with_window_builder(Some(
-        WindowBuilder::new()
-            .with_title("scheng readback minimal")
-            .with_inner_size(PhysicalSize::new(RENDER_W as u32, RENDER_H as u32)),
-    ));
-
-    let (window, gl_config) = display_builder
-        .build(&event_loop, template, |mut configs| configs.next().unwrap())
-        .unwrap();
-
-    let window = window.unwrap();
-
-    let raw_window_handle = window.raw_window_handle();
-    let gl_display = gl_config.display();
-
-    let context_attributes = ContextAttributesBuilder::new()
-        .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
-        .build(Some(raw_window_handle));
-
-    let fallback_context_attributes = ContextAttributesBuilder::new()
-        .with_context_api(ContextApi::Gles(Some(Version::new(3, 0))))
-        .build(Some(raw_window_handle));
-
-    let not_current_gl_context: NotCurrentContext = unsafe {
-        gl_display
-            .create_context(&gl_config, &context_attributes)
-            .unwrap_or_else(|_| {
-                gl_display
-                    .create_context(&gl_config, &fallback_context_attributes)
-                    .expect("failed to create gl context")
-            })
-    };
-
-    let attrs = SurfaceAttributesBuilder::<WindowSurface>::new().build(
-        raw_window_handle,
-        NonZeroU32::new(RENDER_W as u32).unwrap(),
-        NonZeroU32::new(RENDER_H as u32).unwrap(),
-    );
-
-    let gl_surface = unsafe { gl_display.create_window_surface(&gl_config, &attrs).unwrap() };
-
-    let gl_context = not_current_gl_context.make_current(&gl_surface).unwrap();
-
-    gl_surface
-        .set_swap_interval(&gl_context, SwapInterval::Wait(NonZeroU32::new(1).unwrap()))
-        .ok();
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            let cstr = std::ffi::CString::new(s).unwrap();
-            gl_display.get_proc_address(&cstr).cast()
-        })
-    };
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng readback minimal",
+        PhysicalSize::new(RENDER_W as u32, RENDER_H as u32),
+        Some((3, 3)),
+    )
+    .expect("create window + GL context");
+    win.set_vsync(true);
 
     let mut props = rt::NodeProps::default();
     let g = build_graph(&mut props);
@@ -161,23 +105,14 @@ fn main() {
                 WindowEvent::Resized(size) => {
                     present.w = size.width as i32;
                     present.h = size.height as i32;
-                    gl_surface.resize(
-                        &gl_context,
-                        NonZeroU32::new(size.width.max(1)).unwrap(),
-                        NonZeroU32::new(size.height.max(1)).unwrap(),
-                    );
+                    win.resize(size);
                 }
                 _ => {}
             },
             Event::MainEventsCleared => {
                 let time = t0.elapsed().as_secs_f32();
 
-                let frame = rt::FrameCtx {
-                    width: RENDER_W,
-                    height: RENDER_H,
-                    time,
-                    frame: 0,
-                };
+                let frame = rt::FrameCtx::new(RENDER_W, RENDER_H, time, 0);
 
                 let outs = unsafe {
                     rt::execute_plan_outputs(&gl, &g, &plan, &mut state, &props, frame)
@@ -192,7 +127,7 @@ fn main() {
                 // 2) show on-screen so it's easy to debug
                 present.consume(&gl, main_out);
 
-                gl_surface.swap_buffers(&gl_context).unwrap();
+                win.swap_buffers().unwrap();
             }
             _ => {}
         }
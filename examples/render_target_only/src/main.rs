@@ -1,13 +1,9 @@
 use glow::HasContext;
+use scheng_host_winit::WindowGl;
 use scheng_runtime_glow::{
     compile_program, create_render_target, EngineError, FullscreenTriangle, FULLSCREEN_VERT,
 };
 use winit::event_loop::EventLoop;
-use winit::window::WindowBuilder;
-
-use glutin::display::GetGlDisplay;
-use glutin::prelude::*;
-use raw_window_handle::HasRawWindowHandle;
 
 fn main() {
     if let Err(e) = run() {
@@ -19,58 +15,12 @@ fn main() {
 fn run() -> Result<(), EngineError> {
     let event_loop = EventLoop::new();
 
-    let window_builder = WindowBuilder::new()
-        .with_title("scheng-sdk: render_target_only (prints checksum then exits)")
-        .with_inner_size(winit::dpi::LogicalSize::new(256.0, 256.0));
-
-    let template = glutin::config::ConfigTemplateBuilder::new().with_alpha_size(8);
-
-    let display_builder =
-        glutin_winit::DisplayBuilder::new().with_window_builder(Some(window_builder));
-
-    let (window, gl_config) = display_builder
-        .build(&event_loop, template, |mut configs| configs.next().unwrap())
-        .map_err(|e| EngineError::GlCreate(format!("DisplayBuilder.build: {e}")))?;
-
-    let window = window
-        .ok_or_else(|| EngineError::GlCreate("DisplayBuilder did not create a window".into()))?;
-    let gl_display = gl_config.display();
-
-    let raw_window_handle = window.raw_window_handle();
-
-    let context_attributes = glutin::context::ContextAttributesBuilder::new()
-        .with_profile(glutin::context::GlProfile::Core)
-        .build(Some(raw_window_handle));
-
-    let not_current_gl_context = unsafe {
-        gl_display
-            .create_context(&gl_config, &context_attributes)
-            .map_err(|e| EngineError::GlCreate(format!("create_context: {e}")))?
-    };
-
-    let size = window.inner_size();
-    let attrs = glutin::surface::SurfaceAttributesBuilder::<glutin::surface::WindowSurface>::new()
-        .build(
-            raw_window_handle,
-            size.width.try_into().unwrap(),
-            size.height.try_into().unwrap(),
-        );
-
-    let gl_surface = unsafe {
-        gl_display
-            .create_window_surface(&gl_config, &attrs)
-            .map_err(|e| EngineError::GlCreate(format!("create_window_surface: {e}")))?
-    };
-
-    let gl_context = not_current_gl_context
-        .make_current(&gl_surface)
-        .map_err(|e| EngineError::GlCreate(format!("make_current: {e}")))?;
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            gl_display.get_proc_address(std::ffi::CString::new(s).unwrap().as_c_str()) as *const _
-        })
-    };
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng-sdk: render_target_only (prints checksum then exits)",
+        winit::dpi::LogicalSize::new(256.0, 256.0),
+        None,
+    )?;
 
     // Build runtime objects
     let fs_tri = unsafe { FullscreenTriangle::new(&gl)? };
@@ -121,7 +71,6 @@ void main() {
     }
 
     // Exit immediately (no loop); window may flash briefly depending on platform.
-    drop(gl_surface);
-    drop(gl_context);
+    drop(win);
     Ok(())
 }
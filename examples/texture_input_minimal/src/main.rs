@@ -6,21 +6,15 @@
 // feeds it into `NodeKind::TextureInputPass`, then routes to a `PixelsOut`
 // and presents to the window.
 
-use std::num::NonZeroU32;
 use std::time::Instant;
 
 use glow::HasContext;
-use glutin::config::ConfigTemplateBuilder;
-use glutin::context::{ContextAttributesBuilder, NotCurrentGlContextSurfaceAccessor, PossiblyCurrentContext};
-use glutin::display::GetGlDisplay;
-use glutin::prelude::{GlConfig, GlDisplay, GlSurface};
-use glutin_winit::DisplayBuilder;
-use raw_window_handle::{HasRawWindowHandle, HasRawDisplayHandle};
 use winit::dpi::PhysicalSize;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::ControlFlow;
 
 use scheng_graph as graph;
+use scheng_host_winit::WindowGl;
 use scheng_runtime_glow as rt;
 
 const WIN_W: u32 = 960;
@@ -34,77 +28,6 @@ uniform sampler2D iChannel0;
 void main() { o = texture(iChannel0, v_uv); }
 "#;
 
-struct Presenter {
-    tri: rt::FullscreenTriangle,
-    program: glow::NativeProgram,
-}
-
-impl Presenter {
-    unsafe fn new(gl: &glow::Context) -> Result<Self, rt::EngineError> {
-        let tri = rt::FullscreenTriangle::new(gl)?;
-        let program = rt::compile_program(gl, rt::FULLSCREEN_VERT, PRESENT_FRAG)?;
-        Ok(Self { tri, program })
-    }
-
-    unsafe fn present(&self, gl: &glow::Context, tex: glow::NativeTexture, w: i32, h: i32) {
-        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
-        gl.viewport(0, 0, w, h);
-        gl.disable(glow::DEPTH_TEST);
-        gl.disable(glow::BLEND);
-
-        gl.use_program(Some(self.program));
-
-        gl.active_texture(glow::TEXTURE0);
-        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
-        if let Some(loc) = gl.get_uniform_location(self.program, "iChannel0") {
-            gl.uniform_1_i32(Some(&loc), 0);
-        }
-
-        self.tri.draw(gl);
-    }
-}
-
-fn make_gl(
-    event_loop: &winit::event_loop::EventLoop<()>,
-) -> (winit::window::Window, glutin::surface::Surface<glutin::surface::WindowSurface>, PossiblyCurrentContext, glow::Context) {
-    let template = ConfigTemplateBuilder::new().with_alpha_size(8).with_depth_size(0).with_stencil_size(0);
-
-    let display_builder = DisplayBuilder::new().with_window_builder(Some(
-        winit::window::WindowBuilder::new()
-            .with_title("scheng: texture_input_minimal")
-            .with_inner_size(PhysicalSize::new(WIN_W, WIN_H)),
-    ));
-
-    let (window, gl_config) = display_builder
-        .build(event_loop, template, |configs| configs.reduce(|a, b| if a.num_samples() > b.num_samples() { a } else { b }).unwrap())
-        .unwrap();
-
-    let window = window.unwrap();
-    let raw_window_handle = window.raw_window_handle();
-    let _raw_display_handle = window.raw_display_handle();
-
-    let gl_display = gl_config.display();
-
-    let context_attributes = ContextAttributesBuilder::new().build(Some(raw_window_handle));
-    let not_current_gl_context = unsafe { gl_display.create_context(&gl_config, &context_attributes).unwrap() };
-
-    let size = window.inner_size();
-    let attrs = glutin::surface::SurfaceAttributesBuilder::<glutin::surface::WindowSurface>::new()
-        .build(raw_window_handle, NonZeroU32::new(size.width.max(1)).unwrap(), NonZeroU32::new(size.height.max(1)).unwrap());
-
-    let gl_surface = unsafe { gl_display.create_window_surface(&gl_config, &attrs).unwrap() };
-
-    let gl_context = not_current_gl_context.make_current(&gl_surface).unwrap();
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            gl_display.get_proc_address(std::ffi::CStr::from_bytes_with_nul_unchecked(format!("{s}\0").as_bytes())) as *const _
-        })
-    };
-
-    (window, gl_surface, gl_context, gl)
-}
-
 unsafe fn make_host_texture(gl: &glow::Context, w: i32, h: i32) -> glow::NativeTexture {
     let tex = gl.create_texture().unwrap();
     gl.bind_texture(glow::TEXTURE_2D, Some(tex));
@@ -160,7 +83,13 @@ unsafe fn update_host_texture(gl: &glow::Context, tex: glow::NativeTexture, w: i
 
 fn main() {
     let event_loop = winit::event_loop::EventLoop::new();
-    let (window, gl_surface, gl_context, gl) = make_gl(&event_loop);
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng: texture_input_minimal",
+        PhysicalSize::new(WIN_W, WIN_H),
+        None,
+    )
+    .expect("create window + GL context");
 
     // Graph (v1 invariant): TextureInputPass (Source) -> ShaderPass (render) -> PixelsOut (Output)
     let mut g = graph::Graph::new();
@@ -188,7 +117,7 @@ fn main() {
     );
 
     let mut state = unsafe { rt::RuntimeState::new(&gl).expect("rt state") };
-    let presenter = unsafe { Presenter::new(&gl).expect("presenter") };
+    let presenter = unsafe { rt::Presenter::new(&gl, rt::PresenterConfig::default()).expect("presenter") };
 
     let tex_w = 512i32;
     let tex_h = 512i32;
@@ -203,16 +132,12 @@ fn main() {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                 WindowEvent::Resized(size) => {
-                    gl_surface.resize(
-                        &gl_context,
-                        NonZeroU32::new(size.width.max(1)).unwrap(),
-                        NonZeroU32::new(size.height.max(1)).unwrap(),
-                    );
+                    win.resize(size);
                 }
                 _ => {}
             },
             Event::MainEventsCleared => {
-                window.request_redraw();
+                win.request_redraw();
             }
             Event::RedrawRequested(_) => {
                 let elapsed = t0.elapsed().as_secs_f32();
@@ -220,17 +145,17 @@ fn main() {
 
                 props.texture_inputs.insert(tex_in, host_tex);
 
-                let size = window.inner_size();
+                let size = win.window.inner_size();
                 let w = size.width as i32;
                 let h = size.height as i32;
-                let frame = rt::FrameCtx { width: w, height: h, time: elapsed, frame: 0 };
+                let frame = rt::FrameCtx::new(w, h, elapsed, 0);
 
                 unsafe {
                     let outs = rt::execute_plan_outputs(&gl, &g, &plan, &mut state, &props, frame)
                         .expect("execute");
                     let main_out = outs.primary;
-                    presenter.present(&gl, main_out.tex, w, h);
-                    gl_surface.swap_buffers(&gl_context).unwrap();
+                    presenter.present_output(&gl, &main_out, w, h);
+                    win.swap_buffers().unwrap();
                 }
             }
             _ => {}
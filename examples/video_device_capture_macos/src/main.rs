@@ -1,25 +1,15 @@
-use std::io::Read;
-use std::num::NonZeroU32;
-use std::process::{Command, Stdio};
 use std::time::Instant;
 
 use glow::HasContext;
-use glutin::config::ConfigTemplateBuilder;
-use glutin::context::{
-    ContextAttributesBuilder, NotCurrentGlContextSurfaceAccessor, PossiblyCurrentContext,
-};
-use glutin::display::GetGlDisplay;
-use glutin::prelude::{GlConfig, GlDisplay, GlSurface};
-use glutin_winit::DisplayBuilder;
-use raw_window_handle::HasRawWindowHandle;
 use winit::dpi::PhysicalSize;
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::ControlFlow;
 
 use scheng_graph as graph;
+use scheng_host_winit::WindowGl;
 use scheng_runtime_glow as rt;
 
-use scheng_input_webcam::Webcam;
+use scheng_input_webcam::{list_ffmpeg_devices, FfmpegCapture, Webcam};
 
 const WIN_W: u32 = 960;
 const WIN_H: u32 = 540;
@@ -33,117 +23,10 @@ uniform sampler2D iChannel0;
 void main() { o = texture(iChannel0, v_uv); }
 "#;
 
-// Presenter shader for nokhwa backend: 180° rotation (flip X + Y).
-const PRESENT_FRAG_NOKHWA: &str = r#"#version 330 core
-in vec2 v_uv;
-out vec4 o;
-uniform sampler2D iChannel0;
-void main() {
-    vec2 uv = vec2(1.0 - v_uv.x, 1.0 - v_uv.y);
-    o = texture(iChannel0, uv);
-}
-"#;
-
-// Presenter shader for ffmpeg backend: flip Y only (no horizontal mirror).
-const PRESENT_FRAG_FFMPEG: &str = r#"#version 330 core
-in vec2 v_uv;
-out vec4 o;
-uniform sampler2D iChannel0;
-void main() {
-    vec2 uv = vec2(v_uv.x, 1.0 - v_uv.y);
-    o = texture(iChannel0, uv);
-}
-"#;
-
-// Simple ffmpeg/avfoundation capture backend just for this example.
-struct FfmpegDevice {
-    width: u32,
-    height: u32,
-    frame_len: usize,
-    child: std::process::Child,
-    stdout: std::process::ChildStdout,
-    buffer: Vec<u8>,
-    eof_logged: bool,
-}
-
-impl Drop for FfmpegDevice {
-    fn drop(&mut self) {
-        // Ensure the ffmpeg process is terminated so the capture device is released.
-        // This prevents the "works every second run" device-lock behavior.
-        let _ = self.child.kill();
-        let _ = self.child.wait();
-    }
-}
-
-impl FfmpegDevice {
-    fn new(device_index: u32, width: u32, height: u32) -> Result<Self, String> {
-        // AVFoundation input spec supports "VIDEO_INDEX:AUDIO_INDEX".
-        // We explicitly request video only to avoid audio-device binding issues and
-        // reduce nondeterministic fallback behavior.
-        let input_spec = format!("{device_index}:none");
-
-        let mut cmd = Command::new("ffmpeg");
-        cmd.arg("-hide_banner")
-            .arg("-loglevel")
-            .arg("error")
-            .arg("-f")
-            .arg("avfoundation")
-            // avfoundation is real-time; no -re needed
-            .arg("-framerate")
-            .arg("30")
-            .arg("-video_size")
-            .arg(format!("{}x{}", width, height))
-            .arg("-i")
-            .arg(input_spec)
-            .arg("-pix_fmt")
-            .arg("rgba")
-            .arg("-f")
-            .arg("rawvideo")
-            .arg("pipe:1")
-            .stdout(Stdio::piped());
-
-        let mut child = cmd.spawn().map_err(|e| format!("spawn ffmpeg: {e}"))?;
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| "ffmpeg stdout not piped".to_string())?;
-
-        let frame_len = (width as usize) * (height as usize) * 4;
-        let buffer = vec![0u8; frame_len];
-
-        Ok(Self {
-            width,
-            height,
-            frame_len,
-            child,
-            stdout,
-            buffer,
-            eof_logged: false,
-        })
-    }
-
-    fn poll_rgba(&mut self) -> Option<(u32, u32, Vec<u8>)> {
-        if self.frame_len == 0 {
-            return None;
-        }
-
-        match self.stdout.read_exact(&mut self.buffer) {
-            Ok(()) => Some((self.width, self.height, self.buffer.clone())),
-            Err(e) => {
-                if !self.eof_logged {
-                    eprintln!("ffmpeg device capture ended or failed: {e}");
-                    self.eof_logged = true;
-                }
-                None
-            }
-        }
-    }
-}
-
 // Unified frame source: either WebCam (nokhwa) or ffmpeg device.
 enum FrameSource {
     Webcam(Webcam),
-    Ffmpeg(FfmpegDevice),
+    Ffmpeg(FfmpegCapture),
 }
 
 impl FrameSource {
@@ -153,99 +36,14 @@ impl FrameSource {
                 .poll_rgba()
                 .ok()
                 .map(|f| (f.width, f.height, f.bytes)),
-            FrameSource::Ffmpeg(dev) => dev.poll_rgba(),
-        }
-    }
-}
-
-struct Presenter {
-    tri: rt::FullscreenTriangle,
-    program: glow::NativeProgram,
-}
-
-impl Presenter {
-    // Minimal change: allow choosing the presenter frag shader.
-    unsafe fn new(gl: &glow::Context, frag_src: &str) -> Result<Self, rt::EngineError> {
-        let tri = rt::FullscreenTriangle::new(gl)?;
-        let program = rt::compile_program(gl, rt::FULLSCREEN_VERT, frag_src)?;
-        Ok(Self { tri, program })
-    }
-
-    unsafe fn present(&self, gl: &glow::Context, tex: glow::NativeTexture, w: i32, h: i32) {
-        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
-        gl.viewport(0, 0, w, h);
-        gl.disable(glow::DEPTH_TEST);
-        gl.disable(glow::BLEND);
-
-        gl.use_program(Some(self.program));
-
-        gl.active_texture(glow::TEXTURE0);
-        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
-        if let Some(loc) = gl.get_uniform_location(self.program, "iChannel0") {
-            gl.uniform_1_i32(Some(&loc), 0);
+            FrameSource::Ffmpeg(dev) => dev
+                .poll_rgba()
+                .ok()
+                .map(|f| (f.width, f.height, f.bytes)),
         }
-
-        self.tri.draw(gl);
     }
 }
 
-fn make_gl(
-    event_loop: &winit::event_loop::EventLoop<()>,
-) -> (
-    winit::window::Window,
-    glutin::surface::Surface<glutin::surface::WindowSurface>,
-    PossiblyCurrentContext,
-    glow::Context,
-) {
-    let template =
-        ConfigTemplateBuilder::new().with_alpha_size(8).with_depth_size(0).with_stencil_size(0);
-
-    let display_builder = DisplayBuilder::new().with_window_builder(Some(
-        winit::window::WindowBuilder::new()
-            .with_title("scheng: video_device_capture_macos (Step 13.0)")
-            .with_inner_size(PhysicalSize::new(WIN_W, WIN_H)),
-    ));
-
-    let (window, gl_config) = display_builder
-        .build(event_loop, template, |configs| {
-            configs
-                .reduce(|a, b| if a.num_samples() > b.num_samples() { a } else { b })
-                .unwrap()
-        })
-        .unwrap();
-
-    let window = window.unwrap();
-    let raw_window_handle = window.raw_window_handle();
-
-    let gl_display = gl_config.display();
-
-    let context_attributes = ContextAttributesBuilder::new().build(Some(raw_window_handle));
-    let not_current_gl_context =
-        unsafe { gl_display.create_context(&gl_config, &context_attributes).unwrap() };
-
-    let size = window.inner_size();
-    let attrs = glutin::surface::SurfaceAttributesBuilder::<glutin::surface::WindowSurface>::new()
-        .build(
-            raw_window_handle,
-            NonZeroU32::new(size.width.max(1)).unwrap(),
-            NonZeroU32::new(size.height.max(1)).unwrap(),
-        );
-
-    let gl_surface = unsafe { gl_display.create_window_surface(&gl_config, &attrs).unwrap() };
-
-    let gl_context = not_current_gl_context.make_current(&gl_surface).unwrap();
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            gl_display.get_proc_address(std::ffi::CStr::from_bytes_with_nul_unchecked(
-                format!("{s}\0").as_bytes(),
-            )) as *const _
-        })
-    };
-
-    (window, gl_surface, gl_context, gl)
-}
-
 unsafe fn make_host_texture(gl: &glow::Context, w: i32, h: i32) -> glow::NativeTexture {
     let tex = gl.create_texture().unwrap();
     gl.bind_texture(glow::TEXTURE_2D, Some(tex));
@@ -388,9 +186,14 @@ fn list_devices_and_exit() -> ! {
         }
 
         println!("\n--- ffmpeg avfoundation devices ---");
-        let _ = Command::new("ffmpeg")
-            .args(["-f", "avfoundation", "-list_devices", "true", "-i", ""])
-            .status();
+        match list_ffmpeg_devices() {
+            Ok(devices) => {
+                for d in devices {
+                    println!("[{}] {}", d.id, d.name);
+                }
+            }
+            Err(e) => eprintln!("Failed to list devices via ffmpeg: {e}"),
+        }
 
         std::process::exit(0);
     }
@@ -404,7 +207,13 @@ fn main() {
     }
 
     let event_loop = winit::event_loop::EventLoop::new();
-    let (window, gl_surface, gl_context, gl) = make_gl(&event_loop);
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng: video_device_capture_macos (Step 13.0)",
+        PhysicalSize::new(WIN_W, WIN_H),
+        None,
+    )
+    .expect("create window + GL context");
 
     // --- Graph ---
     let mut g = graph::Graph::new();
@@ -432,12 +241,19 @@ fn main() {
 
     let mut state = unsafe { rt::RuntimeState::new(&gl).expect("rt state") };
 
-    // Choose presenter orientation based on backend.
-    let presenter_frag = match cfg.backend.as_str() {
-        "ffmpeg" => PRESENT_FRAG_FFMPEG,
-        _ => PRESENT_FRAG_NOKHWA,
+    // Choose presenter orientation based on backend: nokhwa frames need a 180° rotation,
+    // ffmpeg frames only a vertical flip (no horizontal mirror).
+    let presenter_cfg = match cfg.backend.as_str() {
+        "ffmpeg" => rt::PresenterConfig {
+            flip_y: true,
+            ..Default::default()
+        },
+        _ => rt::PresenterConfig {
+            rotation: rt::Rotation::Cw180,
+            ..Default::default()
+        },
     };
-    let presenter = unsafe { Presenter::new(&gl, presenter_frag).expect("presenter") };
+    let presenter = unsafe { rt::Presenter::new(&gl, presenter_cfg).expect("presenter") };
 
     // --- Video device (webcam / capture) ---
     let mut source = match cfg.backend.as_str() {
@@ -464,7 +280,7 @@ fn main() {
                 "Using backend=ffmpeg (avfoundation), device index {}, {}x{}",
                 cfg.device_index, cfg.width, cfg.height
             );
-            let dev = match FfmpegDevice::new(cfg.device_index, cfg.width, cfg.height) {
+            let dev = match FfmpegCapture::new(cfg.device_index, cfg.width, cfg.height) {
                 Ok(dev) => dev,
                 Err(e) => {
                     eprintln!(
@@ -497,15 +313,11 @@ fn main() {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                 WindowEvent::Resized(size) => {
-                    gl_surface.resize(
-                        &gl_context,
-                        NonZeroU32::new(size.width.max(1)).unwrap(),
-                        NonZeroU32::new(size.height.max(1)).unwrap(),
-                    );
+                    win.resize(size);
                 }
                 _ => {}
             },
-            Event::MainEventsCleared => window.request_redraw(),
+            Event::MainEventsCleared => win.request_redraw(),
             Event::RedrawRequested(_) => {
                 let elapsed = t0.elapsed().as_secs_f32();
 
@@ -527,16 +339,11 @@ fn main() {
 
                 props.texture_inputs.insert(tex_in, host_tex);
 
-                let size = window.inner_size();
+                let size = win.window.inner_size();
                 let w = size.width as i32;
                 let h = size.height as i32;
 
-                let frame_ctx = rt::FrameCtx {
-                    width: w,
-                    height: h,
-                    time: elapsed,
-                    frame: frame_index,
-                };
+                let frame_ctx = rt::FrameCtx::new(w, h, elapsed, frame_index);
                 frame_index = frame_index.wrapping_add(1);
 
                 unsafe {
@@ -545,8 +352,8 @@ fn main() {
                             .expect("execute");
                     let main_out = outs.primary;
 
-                    presenter.present(&gl, main_out.tex, w, h);
-                    gl_surface.swap_buffers(&gl_context).unwrap();
+                    presenter.present_output(&gl, &main_out, w, h);
+                    win.swap_buffers().unwrap();
                 }
             }
             _ => {}
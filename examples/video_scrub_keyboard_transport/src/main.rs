@@ -12,25 +12,15 @@
 //   - Feeds decoded video frames into the graph and presents them.
 //   - Step 12.1: adds keyboard transport (play/pause, speed, scrub) driving FrameCtx::time.
 
-use std::num::NonZeroU32;
 use std::time::Instant;
 
 use glow::HasContext;
-use glutin::config::ConfigTemplateBuilder;
-use glutin::context::{
-    ContextAttributesBuilder,
-    NotCurrentGlContextSurfaceAccessor,
-    PossiblyCurrentContext,
-};
-use glutin::display::GetGlDisplay;
-use glutin::prelude::{GlConfig, GlDisplay, GlSurface};
-use glutin_winit::DisplayBuilder;
-use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
 use winit::dpi::PhysicalSize;
 use winit::event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent};
 use winit::event_loop::ControlFlow;
 
 use scheng_graph as graph;
+use scheng_host_winit::WindowGl;
 use scheng_runtime_glow as rt;
 
 const WIN_W: u32 = 960;
@@ -133,103 +123,15 @@ impl Transport {
     }
 }
 
-struct Presenter {
-    tri: rt::FullscreenTriangle,
-    program: glow::NativeProgram,
-}
-
-impl Presenter {
-    unsafe fn new(gl: &glow::Context) -> Result<Self, rt::EngineError> {
-        let tri = rt::FullscreenTriangle::new(gl)?;
-        let program = rt::compile_program(gl, rt::FULLSCREEN_VERT, PRESENT_FRAG)?;
-        Ok(Self { tri, program })
-    }
-
-    unsafe fn present(&self, gl: &glow::Context, tex: glow::NativeTexture, w: i32, h: i32) {
-        gl.bind_framebuffer(glow::FRAMEBUFFER, None);
-        gl.viewport(0, 0, w, h);
-        gl.disable(glow::DEPTH_TEST);
-        gl.disable(glow::BLEND);
-
-        gl.use_program(Some(self.program));
-
-        gl.active_texture(glow::TEXTURE0);
-        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
-        if let Some(loc) = gl.get_uniform_location(self.program, "iChannel0") {
-            gl.uniform_1_i32(Some(&loc), 0);
-        }
-
-        self.tri.draw(gl);
-    }
-}
-
-fn make_gl(
-    event_loop: &winit::event_loop::EventLoop<()>,
-) -> (
-    winit::window::Window,
-    glutin::surface::Surface<glutin::surface::WindowSurface>,
-    PossiblyCurrentContext,
-    glow::Context,
-) {
-    let template = ConfigTemplateBuilder::new()
-        .with_alpha_size(8)
-        .with_depth_size(0)
-        .with_stencil_size(0);
-
-    let display_builder = DisplayBuilder::new().with_window_builder(Some(
-        winit::window::WindowBuilder::new()
-            .with_title("scheng: video_scrub_keyboard_transport")
-            .with_inner_size(PhysicalSize::new(WIN_W, WIN_H)),
-    ));
-
-    let (window, gl_config) = display_builder
-        .build(event_loop, template, |configs| {
-            configs
-                .reduce(|a, b| if a.num_samples() > b.num_samples() { a } else { b })
-                .unwrap()
-        })
-        .unwrap();
-
-    let window = window.unwrap();
-    let raw_window_handle = window.raw_window_handle();
-    let _raw_display_handle = window.raw_display_handle();
-
-    let gl_display = gl_config.display();
-
-    let context_attributes = ContextAttributesBuilder::new().build(Some(raw_window_handle));
-    let not_current_gl_context =
-        unsafe { gl_display.create_context(&gl_config, &context_attributes).unwrap() };
-
-    let size = window.inner_size();
-    let attrs =
-        glutin::surface::SurfaceAttributesBuilder::<glutin::surface::WindowSurface>::new().build(
-            raw_window_handle,
-            NonZeroU32::new(size.width.max(1)).unwrap(),
-            NonZeroU32::new(size.height.max(1)).unwrap(),
-        );
-
-    let gl_surface = unsafe { gl_display.create_window_surface(&gl_config, &attrs).unwrap() };
-
-    let gl_context = not_current_gl_context
-        .make_current(&gl_surface)
-        .unwrap();
-
-    let gl = unsafe {
-        glow::Context::from_loader_function(|s| {
-            gl_display.get_proc_address(
-                std::ffi::CStr::from_bytes_with_nul_unchecked(
-                    format!("{s}\0").as_bytes(),
-                ),
-            ) as *const _
-        })
-    };
-
-    (window, gl_surface, gl_context, gl)
-}
-
 fn main() {
     let event_loop = winit::event_loop::EventLoop::new();
-    let (window, gl_surface, gl_context, gl) = make_gl(&event_loop);
+    let (win, gl) = WindowGl::new(
+        &event_loop,
+        "scheng: video_scrub_keyboard_transport",
+        PhysicalSize::new(WIN_W, WIN_H),
+        None,
+    )
+    .expect("create window + GL context");
 
     // Graph: VideoDecodeSource -> ShaderPass -> PixelsOut
     let mut g = graph::Graph::new();
@@ -301,7 +203,7 @@ fn main() {
         .insert(video_src, std::path::PathBuf::from(cfg_path));
 
     let mut state = unsafe { rt::RuntimeState::new(&gl).expect("rt state") };
-    let presenter = unsafe { Presenter::new(&gl).expect("presenter") };
+    let presenter = unsafe { rt::Presenter::new(&gl, rt::PresenterConfig::default()).expect("presenter") };
 
     // Legacy time origin placeholder (kept to avoid ripping out code; we now
     // drive FrameCtx::time from `transport` instead).
@@ -326,11 +228,7 @@ fn main() {
             Event::WindowEvent { event, .. } => match event {
                 WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
                 WindowEvent::Resized(size) => {
-                    gl_surface.resize(
-                        &gl_context,
-                        NonZeroU32::new(size.width.max(1)).unwrap(),
-                        NonZeroU32::new(size.height.max(1)).unwrap(),
-                    );
+                    win.resize(size);
                 }
                 WindowEvent::KeyboardInput { input, .. } => {
                     // Feed keyboard into our transport.
@@ -339,23 +237,18 @@ fn main() {
                 _ => {}
             },
             Event::MainEventsCleared => {
-                window.request_redraw();
+                win.request_redraw();
             }
             Event::RedrawRequested(_) => {
                 // Advance transport and use its playhead as FrameCtx::time.
                 transport.update();
                 let playhead = transport.playhead;
 
-                let size = window.inner_size();
+                let size = win.window.inner_size();
                 let w = size.width as i32;
                 let h = size.height as i32;
 
-                let frame = rt::FrameCtx {
-                    width: w,
-                    height: h,
-                    time: playhead,
-                    frame: 0,
-                };
+                let frame = rt::FrameCtx::new(w, h, playhead, 0);
 
                 unsafe {
                     gl.clear_color(0.02, 0.02, 0.02, 1.0);
@@ -376,8 +269,8 @@ fn main() {
                     // If this ever logs a weird size, we know something
                     // upstream is off — but 960x540 here is sane.
                     println!("[debug] main_out {}x{}", main_out.width, main_out.height);
-                    presenter.present(&gl, main_out.tex, w, h);
-                    gl_surface.swap_buffers(&gl_context).unwrap();
+                    presenter.present_output(&gl, &main_out, w, h);
+                    win.swap_buffers().unwrap();
                 }
             }
             _ => {}